@@ -0,0 +1,11 @@
+ALTER TABLE attendance
+  ADD COLUMN IF NOT EXISTS period_id TEXT CHECK (period_id IS NULL OR char_length(period_id) = 24);
+
+-- A class roll can now be taken once per day (period_id IS NULL) and once per
+-- timetable period, so the per-day uniqueness is widened to include the period.
+ALTER TABLE attendance DROP CONSTRAINT IF EXISTS attendance_school_id_student_id_date_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS attendance_student_date_period_unique
+  ON attendance (school_id, student_id, date, (COALESCE(period_id, '')));
+
+CREATE INDEX IF NOT EXISTS attendance_class_date_idx ON attendance (class_id, date DESC);
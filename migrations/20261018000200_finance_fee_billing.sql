@@ -0,0 +1,61 @@
+CREATE TABLE IF NOT EXISTS fee_structures (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  education_year_id TEXT REFERENCES education_years(id) ON DELETE SET NULL,
+  term_id TEXT,
+  name TEXT NOT NULL,
+  description TEXT,
+  currency TEXT NOT NULL DEFAULT 'RWF',
+  due_at TIMESTAMPTZ,
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  deleted_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS fee_structures_school_term_idx
+  ON fee_structures (school_id, education_year_id, term_id);
+CREATE INDEX IF NOT EXISTS fee_structures_class_idx ON fee_structures (class_id);
+CREATE TRIGGER fee_structures_set_updated_at BEFORE UPDATE ON fee_structures
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS fee_structure_items (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  fee_structure_id TEXT NOT NULL REFERENCES fee_structures(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  amount NUMERIC NOT NULL CHECK (amount >= 0),
+  is_optional BOOLEAN NOT NULL DEFAULT false,
+  position INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS fee_structure_items_structure_idx
+  ON fee_structure_items (fee_structure_id, position);
+
+-- finance_records becomes the per-student ledger. Invoices, balances brought
+-- forward and positive adjustments are debits; payments are credits; refunds
+-- reverse a payment. Legacy rows keep their record_type ('fee', ...).
+ALTER TABLE finance_records
+  ADD COLUMN IF NOT EXISTS invoice_id TEXT REFERENCES finance_records(id) ON DELETE CASCADE,
+  ADD COLUMN IF NOT EXISTS related_record_id TEXT REFERENCES finance_records(id) ON DELETE SET NULL,
+  ADD COLUMN IF NOT EXISTS fee_structure_id TEXT REFERENCES fee_structures(id) ON DELETE SET NULL,
+  ADD COLUMN IF NOT EXISTS class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  ADD COLUMN IF NOT EXISTS education_year_id TEXT REFERENCES education_years(id) ON DELETE SET NULL,
+  ADD COLUMN IF NOT EXISTS term_id TEXT,
+  ADD COLUMN IF NOT EXISTS reference_number TEXT,
+  ADD COLUMN IF NOT EXISTS payment_method TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS finance_records_school_reference_unique
+  ON finance_records (school_id, reference_number)
+  WHERE reference_number IS NOT NULL;
+
+-- One invoice per student per fee structure
+CREATE UNIQUE INDEX IF NOT EXISTS finance_records_invoice_structure_unique
+  ON finance_records (student_id, fee_structure_id)
+  WHERE record_type = 'invoice' AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS finance_records_invoice_idx ON finance_records (invoice_id);
+CREATE INDEX IF NOT EXISTS finance_records_school_type_idx
+  ON finance_records (school_id, record_type, created_at DESC);
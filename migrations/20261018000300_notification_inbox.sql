@@ -0,0 +1,41 @@
+-- Persistent in-app notifications. A row targets one user (user_id set) or
+-- every active member of a school (user_id NULL, school_id set). Read state
+-- lives in notification_reads so school-wide rows are stored once.
+CREATE TABLE IF NOT EXISTS notifications (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT REFERENCES users(id) ON DELETE CASCADE,
+  school_id TEXT REFERENCES schools(id) ON DELETE CASCADE,
+  category TEXT NOT NULL,
+  event_type TEXT NOT NULL,
+  entity_id TEXT,
+  title TEXT NOT NULL,
+  data JSONB NOT NULL DEFAULT '{}'::jsonb,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_created_idx
+  ON notifications (user_id, created_at DESC) WHERE user_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS notifications_school_created_idx
+  ON notifications (school_id, created_at DESC) WHERE user_id IS NULL;
+CREATE INDEX IF NOT EXISTS notifications_category_idx ON notifications (category);
+
+CREATE TABLE IF NOT EXISTS notification_reads (
+  notification_id TEXT NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  read_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (notification_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS notification_reads_user_idx ON notification_reads (user_id);
+
+CREATE TABLE IF NOT EXISTS notification_preferences (
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  category TEXT NOT NULL,
+  muted BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  PRIMARY KEY (user_id, category)
+);
+
+CREATE TRIGGER notification_preferences_set_updated_at BEFORE UPDATE ON notification_preferences
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,3 @@
+-- School backups are native PostgreSQL snapshots (gzip JSON lines with a
+-- manifest). The archive checksum is verified before a restore.
+ALTER TABLE school_backups ADD COLUMN IF NOT EXISTS checksum TEXT;
@@ -0,0 +1,20 @@
+-- Per-school automated backup schedule and retention. `schedule` is a
+-- five-field cron expression evaluated in UTC; `last_run_at` is the claimed
+-- schedule slot so several API instances never run the same slot twice.
+CREATE TABLE IF NOT EXISTS school_backup_policies (
+  school_id TEXT PRIMARY KEY REFERENCES schools(id) ON DELETE CASCADE,
+  enabled BOOLEAN NOT NULL DEFAULT true,
+  schedule TEXT NOT NULL DEFAULT '0 2 * * *',
+  keep_daily INTEGER NOT NULL DEFAULT 7 CHECK (keep_daily >= 0),
+  keep_weekly INTEGER NOT NULL DEFAULT 4 CHECK (keep_weekly >= 0),
+  keep_monthly INTEGER NOT NULL DEFAULT 12 CHECK (keep_monthly >= 0),
+  last_run_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER school_backup_policies_set_updated_at BEFORE UPDATE ON school_backup_policies
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE INDEX IF NOT EXISTS school_backups_retention_idx
+  ON school_backups (school_id, backup_type, status, created_at DESC);
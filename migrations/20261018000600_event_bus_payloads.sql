@@ -0,0 +1,9 @@
+-- Events too large for a NOTIFY payload (8000 bytes) are parked here and
+-- referenced by id in the notification. Rows are short-lived.
+CREATE TABLE IF NOT EXISTS event_bus_payloads (
+  id TEXT PRIMARY KEY,
+  event JSONB NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS event_bus_payloads_created_idx ON event_bus_payloads (created_at);
@@ -0,0 +1,2 @@
+-- Cluster-wide SSE event ids; clients resume from them with Last-Event-ID.
+CREATE SEQUENCE IF NOT EXISTS event_bus_event_id_seq;
@@ -0,0 +1,70 @@
+CREATE TABLE IF NOT EXISTS promotion_rules (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  education_year_id TEXT REFERENCES education_years(id) ON DELETE SET NULL,
+  name TEXT NOT NULL,
+  min_gpa_threshold DOUBLE PRECISION NOT NULL DEFAULT 0,
+  min_attendance_percentage DOUBLE PRECISION,
+  required_subjects_passed TEXT[] NOT NULL DEFAULT '{}',
+  custom_rules JSONB,
+  created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  deleted_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS promotion_rules_school_year_idx
+  ON promotion_rules (school_id, education_year_id)
+  WHERE deleted_at IS NULL;
+CREATE TRIGGER promotion_rules_set_updated_at BEFORE UPDATE ON promotion_rules
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- A promotion proposal for one education year. It is reviewed while
+-- 'Proposed' and moves students when 'Approved'.
+CREATE TABLE IF NOT EXISTS promotion_batches (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  rule_id TEXT REFERENCES promotion_rules(id) ON DELETE SET NULL,
+  education_year_id TEXT NOT NULL REFERENCES education_years(id) ON DELETE CASCADE,
+  to_education_year_id TEXT REFERENCES education_years(id) ON DELETE SET NULL,
+  from_class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  to_class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  status TEXT NOT NULL DEFAULT 'Proposed',
+  created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  executed_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  executed_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS promotion_batches_school_year_idx
+  ON promotion_batches (school_id, education_year_id, created_at DESC);
+-- Only one open proposal per year and class scope
+CREATE UNIQUE INDEX IF NOT EXISTS promotion_batches_open_unique
+  ON promotion_batches (school_id, education_year_id, (COALESCE(from_class_id, '')))
+  WHERE status = 'Proposed';
+CREATE TRIGGER promotion_batches_set_updated_at BEFORE UPDATE ON promotion_batches
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS promotion_batch_results (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  batch_id TEXT NOT NULL REFERENCES promotion_batches(id) ON DELETE CASCADE,
+  enrollment_id TEXT NOT NULL REFERENCES student_school_enrollments(id) ON DELETE CASCADE,
+  student_name TEXT NOT NULL,
+  from_class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  current_gpa DOUBLE PRECISION NOT NULL DEFAULT 0,
+  attendance_percentage DOUBLE PRECISION,
+  failed_subjects TEXT[] NOT NULL DEFAULT '{}',
+  promotion_status TEXT NOT NULL,
+  reason TEXT NOT NULL,
+  promoted_to_class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  is_overridden BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (batch_id, enrollment_id)
+);
+
+CREATE INDEX IF NOT EXISTS promotion_batch_results_batch_idx
+  ON promotion_batch_results (batch_id, promotion_status);
+CREATE TRIGGER promotion_batch_results_set_updated_at BEFORE UPDATE ON promotion_batch_results
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,69 @@
+-- Relative weight of each term in the annual result. Terms are keyed by the
+-- same term_id the exams and student_term_results carry.
+CREATE TABLE IF NOT EXISTS education_year_term_weights (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  education_year_id TEXT NOT NULL REFERENCES education_years(id) ON DELETE CASCADE,
+  term_id TEXT NOT NULL,
+  weight DOUBLE PRECISION NOT NULL CHECK (weight >= 0),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (education_year_id, term_id)
+);
+
+CREATE TRIGGER education_year_term_weights_set_updated_at BEFORE UPDATE ON education_year_term_weights
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS student_annual_results (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  student_id TEXT NOT NULL REFERENCES student_profiles(id) ON DELETE CASCADE,
+  class_id TEXT REFERENCES classes(id) ON DELETE SET NULL,
+  education_year_id TEXT NOT NULL REFERENCES education_years(id) ON DELETE CASCADE,
+  average_percentage DOUBLE PRECISION NOT NULL DEFAULT 0,
+  gpa DOUBLE PRECISION NOT NULL DEFAULT 0,
+  cumulative_gpa DOUBLE PRECISION,
+  total_credits INTEGER,
+  grade TEXT NOT NULL DEFAULT '',
+  rank_in_class INTEGER,
+  total_students INTEGER,
+  calculated_at TIMESTAMPTZ,
+  is_finalized BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (student_id, education_year_id)
+);
+
+CREATE INDEX IF NOT EXISTS student_annual_results_class_year_gpa_idx
+  ON student_annual_results (class_id, education_year_id, gpa DESC, average_percentage DESC);
+CREATE TRIGGER student_annual_results_set_updated_at BEFORE UPDATE ON student_annual_results
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS student_annual_term_results (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  result_id TEXT NOT NULL REFERENCES student_annual_results(id) ON DELETE CASCADE,
+  term_id TEXT NOT NULL,
+  weight DOUBLE PRECISION NOT NULL DEFAULT 0,
+  average_percentage DOUBLE PRECISION NOT NULL DEFAULT 0,
+  gpa DOUBLE PRECISION NOT NULL DEFAULT 0,
+  results_counted INTEGER NOT NULL DEFAULT 0,
+  position INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS student_annual_subject_results (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  result_id TEXT NOT NULL REFERENCES student_annual_results(id) ON DELETE CASCADE,
+  class_subject_id TEXT REFERENCES class_subjects(id) ON DELETE SET NULL,
+  subject_name TEXT NOT NULL,
+  percentage DOUBLE PRECISION NOT NULL DEFAULT 0,
+  grade TEXT NOT NULL DEFAULT '',
+  credits INTEGER,
+  position INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS student_annual_term_results_result_idx
+  ON student_annual_term_results (result_id, position);
+CREATE INDEX IF NOT EXISTS student_annual_subject_results_result_idx
+  ON student_annual_subject_results (result_id, position);
@@ -0,0 +1,54 @@
+-- Weekly availability of a user, one row per time range. Teachers without
+-- rows are treated as always available by the timetable solver.
+CREATE TABLE IF NOT EXISTS user_availability (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  day TEXT NOT NULL,
+  starts_at TIME NOT NULL,
+  ends_at TIME NOT NULL,
+  position INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS user_availability_user_idx ON user_availability (user_id, position);
+
+CREATE TABLE IF NOT EXISTS school_rooms (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  room_type TEXT NOT NULL DEFAULT 'Classroom',
+  capacity INTEGER CHECK (capacity IS NULL OR capacity > 0),
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  deleted_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS school_rooms_school_name_unique
+  ON school_rooms (school_id, lower(name))
+  WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS school_rooms_school_type_idx
+  ON school_rooms (school_id, lower(room_type))
+  WHERE deleted_at IS NULL;
+CREATE TRIGGER school_rooms_set_updated_at BEFORE UPDATE ON school_rooms
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Per-subject inputs for the timetable solver
+CREATE TABLE IF NOT EXISTS class_subject_scheduling_rules (
+  class_subject_id TEXT PRIMARY KEY REFERENCES class_subjects(id) ON DELETE CASCADE,
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  required_room_type TEXT,
+  is_heavy BOOLEAN,
+  max_consecutive_periods INTEGER CHECK (max_consecutive_periods IS NULL OR max_consecutive_periods > 0),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER class_subject_scheduling_rules_set_updated_at BEFORE UPDATE ON class_subject_scheduling_rules
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- A class keeps one timetable per term
+DROP INDEX IF EXISTS class_timetables_class_year_unique;
+CREATE UNIQUE INDEX IF NOT EXISTS class_timetables_class_year_term_unique
+  ON class_timetables (class_id, education_year_id, term_order);
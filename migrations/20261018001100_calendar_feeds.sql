@@ -0,0 +1,15 @@
+-- Revocable subscription tokens for per-user iCalendar feeds. Only the
+-- SHA-256 of the token is stored; the token itself is shown once.
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  token_hash TEXT NOT NULL UNIQUE,
+  label TEXT,
+  last_used_at TIMESTAMPTZ,
+  revoked_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS calendar_feed_tokens_user_idx
+  ON calendar_feed_tokens (user_id, created_at DESC);
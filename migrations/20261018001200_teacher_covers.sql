@@ -0,0 +1,54 @@
+CREATE TABLE IF NOT EXISTS teacher_absences (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  teacher_id TEXT NOT NULL REFERENCES teachers(id) ON DELETE CASCADE,
+  starts_on DATE NOT NULL,
+  ends_on DATE NOT NULL,
+  reason TEXT,
+  status TEXT NOT NULL DEFAULT 'Active' CHECK (status IN ('Active', 'Cancelled')),
+  created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CHECK (ends_on >= starts_on)
+);
+
+CREATE INDEX IF NOT EXISTS teacher_absences_school_dates_idx
+  ON teacher_absences (school_id, starts_on, ends_on)
+  WHERE status = 'Active';
+CREATE INDEX IF NOT EXISTS teacher_absences_teacher_idx ON teacher_absences (teacher_id, starts_on);
+CREATE TRIGGER teacher_absences_set_updated_at BEFORE UPDATE ON teacher_absences
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- One covering teacher for one period on one date
+CREATE TABLE IF NOT EXISTS teacher_covers (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  absence_id TEXT NOT NULL REFERENCES teacher_absences(id) ON DELETE CASCADE,
+  class_timetable_id TEXT NOT NULL REFERENCES class_timetables(id) ON DELETE CASCADE,
+  class_id TEXT NOT NULL REFERENCES classes(id) ON DELETE CASCADE,
+  period_id TEXT NOT NULL CHECK (char_length(period_id) = 24),
+  class_subject_id TEXT REFERENCES class_subjects(id) ON DELETE SET NULL,
+  cover_date DATE NOT NULL,
+  starts_at TIME NOT NULL,
+  ends_at TIME NOT NULL,
+  absent_teacher_id TEXT NOT NULL REFERENCES teachers(id) ON DELETE CASCADE,
+  cover_teacher_id TEXT NOT NULL REFERENCES teachers(id) ON DELETE CASCADE,
+  status TEXT NOT NULL DEFAULT 'Confirmed' CHECK (status IN ('Confirmed', 'Cancelled')),
+  note TEXT,
+  confirmed_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CHECK (cover_teacher_id <> absent_teacher_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS teacher_covers_period_date_unique
+  ON teacher_covers (period_id, cover_date)
+  WHERE status = 'Confirmed';
+CREATE INDEX IF NOT EXISTS teacher_covers_cover_teacher_idx
+  ON teacher_covers (cover_teacher_id, cover_date)
+  WHERE status = 'Confirmed';
+CREATE INDEX IF NOT EXISTS teacher_covers_class_idx
+  ON teacher_covers (class_id, cover_date)
+  WHERE status = 'Confirmed';
+CREATE TRIGGER teacher_covers_set_updated_at BEFORE UPDATE ON teacher_covers
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
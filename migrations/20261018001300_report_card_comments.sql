@@ -0,0 +1,20 @@
+-- Teacher remarks printed on report cards. A row without a class subject
+-- is the class teacher's general remark for the student and exam.
+CREATE TABLE IF NOT EXISTS report_card_comments (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  student_id TEXT NOT NULL REFERENCES student_profiles(id) ON DELETE CASCADE,
+  exam_id TEXT NOT NULL REFERENCES exams(id) ON DELETE CASCADE,
+  class_subject_id TEXT REFERENCES class_subjects(id) ON DELETE CASCADE,
+  body TEXT NOT NULL,
+  author_user_id TEXT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS report_card_comments_target_unique
+  ON report_card_comments (student_id, exam_id, coalesce(class_subject_id, ''));
+CREATE INDEX IF NOT EXISTS report_card_comments_exam_idx
+  ON report_card_comments (exam_id, student_id);
+CREATE TRIGGER report_card_comments_set_updated_at BEFORE UPDATE ON report_card_comments
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,32 @@
+-- Committed bulk imports of students or teachers and the per-row outcome,
+-- kept so the import report can be fetched again later.
+CREATE TABLE IF NOT EXISTS bulk_imports (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL CHECK (kind IN ('Student', 'Teacher')),
+  file_name TEXT,
+  total_rows INTEGER NOT NULL DEFAULT 0,
+  created_count INTEGER NOT NULL DEFAULT 0,
+  duplicate_count INTEGER NOT NULL DEFAULT 0,
+  error_count INTEGER NOT NULL DEFAULT 0,
+  created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS bulk_imports_school_idx ON bulk_imports (school_id, created_at DESC);
+CREATE TRIGGER bulk_imports_set_updated_at BEFORE UPDATE ON bulk_imports
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS bulk_import_rows (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  import_id TEXT NOT NULL REFERENCES bulk_imports(id) ON DELETE CASCADE,
+  row_number INTEGER NOT NULL,
+  status TEXT NOT NULL CHECK (status IN ('Created', 'Duplicate', 'Invalid')),
+  name TEXT,
+  email TEXT,
+  entity_id TEXT CHECK (entity_id IS NULL OR char_length(entity_id) = 24),
+  parent_id TEXT CHECK (parent_id IS NULL OR char_length(parent_id) = 24),
+  errors TEXT[] NOT NULL DEFAULT '{}',
+  UNIQUE (import_id, row_number)
+);
@@ -0,0 +1,46 @@
+-- Marks moderation: the teacher submits a subject's marks for an exam, the
+-- head of department reviews them and an admin approves, which locks them.
+-- A missing row means the marks are still a draft.
+CREATE TABLE IF NOT EXISTS score_moderations (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  school_id TEXT NOT NULL REFERENCES schools(id) ON DELETE CASCADE,
+  exam_id TEXT NOT NULL REFERENCES exams(id) ON DELETE CASCADE,
+  class_subject_id TEXT NOT NULL REFERENCES class_subjects(id) ON DELETE CASCADE,
+  status TEXT NOT NULL DEFAULT 'Draft'
+    CHECK (status IN ('Draft', 'Submitted', 'Reviewed', 'Approved', 'Reopened')),
+  submitted_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  submitted_at TIMESTAMPTZ,
+  reviewed_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  reviewed_at TIMESTAMPTZ,
+  approved_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+  approved_at TIMESTAMPTZ,
+  note TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (exam_id, class_subject_id)
+);
+
+CREATE INDEX IF NOT EXISTS score_moderations_school_idx
+  ON score_moderations (school_id, status);
+CREATE TRIGGER score_moderations_set_updated_at BEFORE UPDATE ON score_moderations
+FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Every transition, reopens included, with who made it and why
+CREATE TABLE IF NOT EXISTS score_moderation_events (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  moderation_id TEXT NOT NULL REFERENCES score_moderations(id) ON DELETE CASCADE,
+  action TEXT NOT NULL CHECK (action IN ('Submit', 'Review', 'Return', 'Approve', 'Reopen')),
+  from_status TEXT NOT NULL,
+  to_status TEXT NOT NULL,
+  actor_user_id TEXT REFERENCES users(id) ON DELETE SET NULL,
+  note TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS score_moderation_events_moderation_idx
+  ON score_moderation_events (moderation_id, created_at);
+
+-- Set once every subject is approved; parents and students see the exam's
+-- results only after this.
+ALTER TABLE exams ADD COLUMN IF NOT EXISTS results_published_at TIMESTAMPTZ;
+ALTER TABLE exams ADD COLUMN IF NOT EXISTS results_published_by TEXT REFERENCES users(id) ON DELETE SET NULL;
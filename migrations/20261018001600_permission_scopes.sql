@@ -0,0 +1,16 @@
+-- How far a granted permission reaches inside its school: the holder's own
+-- records, the classes they teach, or the whole school.
+ALTER TABLE permissions
+  ADD COLUMN IF NOT EXISTS scope TEXT NOT NULL DEFAULT 'School'
+    CHECK (scope IN ('Own', 'Class', 'School'));
+
+UPDATE permissions SET scope = 'Own'
+WHERE code LIKE '%.own' OR code LIKE 'parent.read.child.%';
+
+UPDATE permissions SET scope = 'Class'
+WHERE code LIKE '%.class' OR code = 'submission.grade';
+
+-- Tenant guards look memberships up by user first
+CREATE INDEX IF NOT EXISTS school_memberships_user_school_idx
+  ON school_memberships (user_id, school_id)
+  WHERE status = 'active' AND ended_at IS NULL;
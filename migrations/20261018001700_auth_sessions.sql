@@ -0,0 +1,30 @@
+-- Signed-in devices. Each session is one refresh-token family: every refresh
+-- rotates the token, and presenting an already used token revokes the
+-- whole session.
+CREATE TABLE IF NOT EXISTS auth_sessions (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  user_agent TEXT,
+  ip_address TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_used_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  revoked_at TIMESTAMPTZ,
+  revoked_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS auth_sessions_user_idx
+  ON auth_sessions (user_id, last_used_at DESC);
+
+-- Only hashes are stored; the token itself is shown to the client once
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  session_id TEXT NOT NULL REFERENCES auth_sessions(id) ON DELETE CASCADE,
+  token_hash TEXT NOT NULL UNIQUE,
+  parent_id TEXT REFERENCES refresh_tokens(id) ON DELETE SET NULL,
+  issued_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_session_idx ON refresh_tokens (session_id);
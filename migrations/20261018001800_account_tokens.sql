@@ -0,0 +1,20 @@
+-- When the address on the account was last proven to belong to the user
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+
+-- Single-use links mailed to a user. Only hashes are stored; issuing a new
+-- token of a purpose retires the older ones.
+CREATE TABLE IF NOT EXISTS account_tokens (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  purpose TEXT NOT NULL CHECK (purpose IN ('password_reset', 'email_verification')),
+  token_hash TEXT NOT NULL UNIQUE,
+  -- Address the token was sent to; verification only counts for it
+  email TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS account_tokens_user_purpose_idx
+  ON account_tokens (user_id, purpose)
+  WHERE used_at IS NULL;
@@ -0,0 +1,38 @@
+-- TOTP second factor. The secret is pending until the first code confirms
+-- it; last_used_step stops the same code being accepted twice.
+CREATE TABLE IF NOT EXISTS user_two_factor (
+  user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  secret TEXT NOT NULL,
+  enabled_at TIMESTAMPTZ,
+  last_used_step BIGINT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- One-time recovery codes, stored hashed
+CREATE TABLE IF NOT EXISTS user_recovery_codes (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  code_hash TEXT NOT NULL,
+  used_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS user_recovery_codes_user_idx
+  ON user_recovery_codes (user_id)
+  WHERE used_at IS NULL;
+
+-- A password that checked out, waiting for its second factor
+CREATE TABLE IF NOT EXISTS two_factor_challenges (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  token_hash TEXT NOT NULL UNIQUE,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  expires_at TIMESTAMPTZ NOT NULL,
+  used_at TIMESTAMPTZ
+);
+
+-- Schools can require every admin and staff member to use a second factor
+ALTER TABLE schools
+  ADD COLUMN IF NOT EXISTS require_two_factor BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,9 @@
+-- Rows whose email already belongs to an account outside the school are
+-- reported as conflicts instead of being attached to that account.
+ALTER TABLE bulk_imports
+  ADD COLUMN IF NOT EXISTS conflict_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE bulk_import_rows DROP CONSTRAINT IF EXISTS bulk_import_rows_status_check;
+ALTER TABLE bulk_import_rows
+  ADD CONSTRAINT bulk_import_rows_status_check
+  CHECK (status IN ('Created', 'Duplicate', 'Conflict', 'Invalid'));
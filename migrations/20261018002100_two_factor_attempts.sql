@@ -0,0 +1,5 @@
+-- Wrong codes given to disable two-factor or regenerate recovery codes,
+-- counted per window so a stolen session cannot guess its way through
+ALTER TABLE user_two_factor
+  ADD COLUMN IF NOT EXISTS failed_attempts INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS attempts_reset_at TIMESTAMPTZ NOT NULL DEFAULT now();
@@ -0,0 +1,15 @@
+-- Every password reset asked for, by address and client, so the forgot
+-- password endpoint can be throttled without revealing which addresses exist
+CREATE TABLE IF NOT EXISTS password_reset_requests (
+  id TEXT PRIMARY KEY CHECK (char_length(id) = 24),
+  email TEXT NOT NULL,
+  ip_address TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS password_reset_requests_email_idx
+  ON password_reset_requests (email, created_at);
+
+CREATE INDEX IF NOT EXISTS password_reset_requests_ip_idx
+  ON password_reset_requests (ip_address, created_at)
+  WHERE ip_address IS NOT NULL;
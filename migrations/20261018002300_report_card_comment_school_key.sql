@@ -0,0 +1,5 @@
+-- One comment per school, student, exam and subject, so a comment can never
+-- be upserted over another school's row
+DROP INDEX IF EXISTS report_card_comments_target_unique;
+CREATE UNIQUE INDEX IF NOT EXISTS report_card_comments_target_unique
+  ON report_card_comments (school_id, student_id, exam_id, coalesce(class_subject_id, ''));
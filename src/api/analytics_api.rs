@@ -3,13 +3,14 @@ use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
 use crate::{
     config::state::AppState,
     domain::{
-        analytics::{AttendanceRateQuery, EnrollmentTrendsQuery},
+        analytics::{AttendanceRateQuery, EnrollmentTrendsQuery, ExamStatisticsQuery},
         auth_user::AuthUserDto,
+        score_moderation::REVIEW_PERMISSION,
     },
-    guards::role_guard::check_permission,
+    guards::role_guard::{check_permission, require_permission},
     helpers::event_helpers::get_school_id_from_request,
     models::id_model::IdType,
-    services::analytics_service::AnalyticsService,
+    services::{analytics_service::AnalyticsService, permission_service::PermissionService},
     utils::request_context::postgres_pool,
 };
 
@@ -22,7 +23,14 @@ async fn get_enrollment_trends(
     state: web::Data<AppState>,
 ) -> impl Responder {
     // Check permission: analytics.read.school
-    if let Err(err) = check_permission(&user, "analytics.read.school") {
+    if let Err(err) = check_permission(
+        &user,
+        get_school_id_from_request(&req).as_deref(),
+        "analytics.read.school",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -41,7 +49,7 @@ async fn get_enrollment_trends(
 
     match service.get_enrollment_trends(&school_id, query.year).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -54,7 +62,14 @@ async fn get_attendance_rate(
     state: web::Data<AppState>,
 ) -> impl Responder {
     // Check permission: analytics.read.school
-    if let Err(err) = check_permission(&user, "analytics.read.school") {
+    if let Err(err) = check_permission(
+        &user,
+        get_school_id_from_request(&req).as_deref(),
+        "analytics.read.school",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -76,7 +91,7 @@ async fn get_attendance_rate(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -88,7 +103,14 @@ async fn get_pass_fail_distribution(
     state: web::Data<AppState>,
 ) -> impl Responder {
     // Check permission: analytics.read.school
-    if let Err(err) = check_permission(&user, "analytics.read.school") {
+    if let Err(err) = check_permission(
+        &user,
+        get_school_id_from_request(&req).as_deref(),
+        "analytics.read.school",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -107,7 +129,7 @@ async fn get_pass_fail_distribution(
 
     match service.get_pass_fail_distribution(&school_id, None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -119,7 +141,14 @@ async fn get_fee_summary(
     state: web::Data<AppState>,
 ) -> impl Responder {
     // Check permission: analytics.read.school
-    if let Err(err) = check_permission(&user, "analytics.read.school") {
+    if let Err(err) = check_permission(
+        &user,
+        get_school_id_from_request(&req).as_deref(),
+        "analytics.read.school",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -138,7 +167,7 @@ async fn get_fee_summary(
 
     match service.get_fee_summary(&school_id).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -150,7 +179,14 @@ async fn get_teacher_workload(
     state: web::Data<AppState>,
 ) -> impl Responder {
     // Check permission: analytics.read.school
-    if let Err(err) = check_permission(&user, "analytics.read.school") {
+    if let Err(err) = check_permission(
+        &user,
+        get_school_id_from_request(&req).as_deref(),
+        "analytics.read.school",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -169,7 +205,55 @@ async fn get_teacher_workload(
 
     match service.get_teacher_workload(&school_id).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
+    }
+}
+
+// ========== EXAM STATISTICS ==========
+#[get("/exams/{exam_id}/statistics")]
+async fn get_exam_statistics(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<ExamStatisticsQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match get_school_id_from_request(&req) {
+        Some(id) => id,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "message": "School ID is required"
+            }))
+        }
+    };
+
+    // School analytics, or heads of department who review marks
+    let permissions = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) =
+        require_permission(&user, &school_id, "analytics.read.school", &permissions).await
+    {
+        if require_permission(&user, &school_id, REVIEW_PERMISSION, &permissions)
+            .await
+            .is_err()
+        {
+            return HttpResponse::Forbidden().json(serde_json::json!({
+                "message": err
+            }));
+        }
+    }
+
+    let service = AnalyticsService::new(postgres_pool(&state));
+
+    match service
+        .get_exam_statistics(
+            &IdType::from_string(school_id),
+            &IdType::from_string(path.into_inner()),
+            &query,
+        )
+        .await
+    {
+        Ok(data) => HttpResponse::Ok().json(data),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -181,7 +265,8 @@ fn blueprint(cfg: &mut web::ServiceConfig) {
             .service(get_attendance_rate)
             .service(get_pass_fail_distribution)
             .service(get_fee_summary)
-            .service(get_teacher_workload),
+            .service(get_teacher_workload)
+            .service(get_exam_statistics),
     );
 }
 
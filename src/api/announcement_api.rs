@@ -6,14 +6,29 @@ use crate::{
         announcement::{Announcement, AnnouncementPartial},
         auth_user::AuthUserDto,
     },
-    guards::role_guard::check_admin_or_staff,
+    guards::role_guard::require_context_permission,
     handler::delete_target_handler::delete_target_handler,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
-    services::{announcement_service::AnnouncementService, event_service::EventService},
+    services::{
+        announcement_service::AnnouncementService, event_service::EventService,
+        permission_service::PermissionService,
+    },
     utils::request_context::{postgres_pool, request_context},
 };
 
+/// The request's school, once the caller may manage announcements there
+async fn manage_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<Option<String>, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_context_permission(req, user, "announcement.manage", &permission_service)
+        .await
+        .map_err(|err| err.to_response())
+}
+
 fn scoped_school_id(req: &HttpRequest, query: &RequestQuery) -> Option<String> {
     query
         .school_id
@@ -41,7 +56,7 @@ async fn get_all_announcements(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -65,7 +80,7 @@ async fn get_all_announcements_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -80,7 +95,7 @@ async fn get_announcement_by_id_with_relations(
 
     match service.find_one_with_relations(Some(&id), None, None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -95,7 +110,7 @@ async fn get_announcement_by_id(
 
     match service.find_one(Some(&id), None, None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -113,7 +128,7 @@ async fn get_announcement_by_match(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -131,7 +146,7 @@ async fn get_announcement_by_other_match(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -142,14 +157,11 @@ async fn create_announcement(
     data: web::Json<Announcement>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": e
-        }));
-    }
-
+    let school_id = match manage_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
     let service = AnnouncementService::new(postgres_pool(&state));
-    let school_id = request_context(&req).school_id;
 
     match service.create(data.into_inner(), school_id).await {
         Ok(item) => {
@@ -171,7 +183,7 @@ async fn create_announcement(
 
             HttpResponse::Created().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -183,10 +195,8 @@ async fn update_announcement(
     data: web::Json<AnnouncementPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": e
-        }));
+    if let Err(response) = manage_school_id(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -212,7 +222,7 @@ async fn update_announcement(
 
             HttpResponse::Ok().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -223,7 +233,10 @@ async fn delete_announcement(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let _logged_user = user.into_inner();
+    if let Err(response) = manage_school_id(&req, &user, &state).await {
+        return response;
+    }
+
     let id = IdType::from_string(path.into_inner());
     let service = AnnouncementService::new(postgres_pool(&state));
 
@@ -232,7 +245,7 @@ async fn delete_announcement(
             if let Some(target_id) = announcement.id {
                 match delete_target_handler(postgres_pool(&state), &target_id).await {
                     Ok(_) => {}
-                    Err(err) => return HttpResponse::BadRequest().json(err),
+                    Err(err) => return err.to_response(),
                 }
             }
 
@@ -254,7 +267,7 @@ async fn delete_announcement(
 
             HttpResponse::Ok().json(announcement)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -272,7 +285,7 @@ async fn count_announcements(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!({ "count": count })),
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
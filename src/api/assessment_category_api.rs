@@ -28,7 +28,7 @@ async fn get_all_categories(
     let context = request_context(&req);
     let category_query = match AssessmentCategoryQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -41,7 +41,7 @@ async fn get_all_categories(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -65,7 +65,7 @@ async fn get_category_by_id(
         .await
     {
         Ok(category) => HttpResponse::Ok().json(category),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -78,13 +78,13 @@ async fn validate_weight(
 ) -> impl Responder {
     let class_subject_id = match parse_object_id_value(&path.into_inner()) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let education_year_id = match query.education_year_id.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
         None => {
             return HttpResponse::BadRequest().json(serde_json::json!({
@@ -103,7 +103,7 @@ async fn validate_weight(
             "total_weight": total,
             "remaining": 100.0 - total
         })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -121,7 +121,7 @@ async fn create_category(
     if category.created_by.is_none() {
         let user_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         category.created_by = Some(user_id);
     }
@@ -129,7 +129,7 @@ async fn create_category(
         if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
             category.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -153,7 +153,7 @@ async fn create_category(
 
             HttpResponse::Created().json(category)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -187,7 +187,7 @@ async fn update_category(
 
             HttpResponse::Ok().json(category)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -220,7 +220,7 @@ async fn delete_category(
 
             HttpResponse::Ok().json(category)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
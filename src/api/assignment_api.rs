@@ -6,15 +6,21 @@ use crate::{
         assignment::{Assignment, AssignmentPartial, Submission, SubmissionPartial},
         auth_user::AuthUserDto,
         common_details::UserRole,
+        role::PermissionTarget,
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    guards::role_guard::{
+        require_parent_child_access, require_permission, require_scoped_permission,
+        require_tenant_access,
     },
-    guards::role_guard::{require_parent_child_access, require_permission},
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         assignment_service::{AssignmentQuery, AssignmentService},
         event_service::EventService,
-        parent_service::ParentService,
-        role_service::RoleService,
+        permission_service::PermissionService,
+        tenant_service::TenantService,
     },
     utils::{
         object_id::parse_object_id_value,
@@ -32,7 +38,7 @@ async fn get_all_assignments(
     let context = request_context(&req);
     let assignment_query = match AssignmentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -45,7 +51,7 @@ async fn get_all_assignments(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -67,7 +73,7 @@ async fn get_assignment_by_id(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -102,12 +108,11 @@ async fn create_assignment(
                 "message": "Feature assignments.enabled is not enabled"
             }));
         }
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     }
 
-    let role_service = RoleService::new(&state.pg.pool);
-    if let Err(e) = require_permission(&user, &school_id, "assignment.create", &role_service).await
-    {
+    let permissions = PermissionService::for_request(&req, &state.pg.pool);
+    if let Err(e) = require_permission(&user, &school_id, "assignment.create", &permissions).await {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": e
         }));
@@ -117,7 +122,7 @@ async fn create_assignment(
     if assignment.school_id.is_none() {
         assignment.school_id = match parse_object_id_value(&school_id) {
             Ok(id) => Some(id),
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
     }
 
@@ -132,7 +137,7 @@ async fn create_assignment(
                     "message": "Teacher record not found for this user"
                 }));
             }
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         }
     }
 
@@ -155,7 +160,7 @@ async fn create_assignment(
 
             HttpResponse::Created().json(assignment)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -179,9 +184,8 @@ async fn update_assignment(
             }
         };
 
-    let role_service = RoleService::new(&state.pg.pool);
-    if let Err(e) = require_permission(&user, &school_id, "assignment.update", &role_service).await
-    {
+    let permissions = PermissionService::for_request(&req, &state.pg.pool);
+    if let Err(e) = require_permission(&user, &school_id, "assignment.update", &permissions).await {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": e
         }));
@@ -201,7 +205,7 @@ async fn update_assignment(
                     }
                 }
             }
-            Err(err) => return HttpResponse::NotFound().json(err),
+            Err(err) => return err.to_response(),
         }
     }
 
@@ -224,7 +228,7 @@ async fn update_assignment(
 
             HttpResponse::Ok().json(assignment)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -247,9 +251,8 @@ async fn delete_assignment(
             }
         };
 
-    let role_service = RoleService::new(&state.pg.pool);
-    if let Err(e) = require_permission(&user, &school_id, "assignment.delete", &role_service).await
-    {
+    let permissions = PermissionService::for_request(&req, &state.pg.pool);
+    if let Err(e) = require_permission(&user, &school_id, "assignment.delete", &permissions).await {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": e
         }));
@@ -269,7 +272,7 @@ async fn delete_assignment(
                     }
                 }
             }
-            Err(err) => return HttpResponse::NotFound().json(err),
+            Err(err) => return err.to_response(),
         }
     }
 
@@ -292,7 +295,7 @@ async fn delete_assignment(
 
             HttpResponse::Ok().json(assignment)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -306,7 +309,7 @@ async fn count_assignments(
     let context = request_context(&req);
     let assignment_query = match AssignmentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -314,7 +317,7 @@ async fn count_assignments(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -356,7 +359,7 @@ async fn submit_assignment(
                 "message": "Student record not found for this user"
             }));
         }
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
     submission.student_id = Some(student.student_id);
 
@@ -394,7 +397,7 @@ async fn submit_assignment(
 
             HttpResponse::Created().json(submission)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -424,10 +427,56 @@ async fn get_assignment_submissions(
         }
     };
     let service = AssignmentService::new(postgres_pool(&state));
+    let assignment = match service
+        .find_one_assignment(Some(&IdType::from_string(assignment_id.clone())), None)
+        .await
+    {
+        Ok(assignment) => assignment,
+        Err(err) => return err.to_response(),
+    };
+    let Some(school_id) = assignment.school_id.map(|id| id.to_hex()) else {
+        return AppError::not_found("Assignment has no school").to_response();
+    };
+
+    let tenant_service = TenantService::new(postgres_pool(&state));
+    if let Err(err) =
+        require_tenant_access(&user, TenantEntity::School, &school_id, &tenant_service).await
+    {
+        return AppError::forbidden(err).to_response();
+    }
+
+    // Teachers see submissions of their own assignments, or of classes a
+    // role lets them read
+    if user.role == Some(UserRole::TEACHER) {
+        let owns = match assignment.teacher_id {
+            Some(teacher_id) => service
+                .teacher_belongs_to_user(&teacher_id, &user.id)
+                .await
+                .unwrap_or(false),
+            None => false,
+        };
+        if !owns {
+            let mut target = PermissionTarget::school(&school_id);
+            if let Some(class_id) = assignment.class_id {
+                target = target.class(class_id.to_hex());
+            }
+            if let Err(err) = require_scoped_permission(
+                &user,
+                "submission.read.class",
+                &target,
+                &PermissionService::for_request(&req, postgres_pool(&state)),
+            )
+            .await
+            {
+                return AppError::forbidden(err).to_response();
+            }
+        }
+    }
+
     let context = request_context(&req);
     let mut submission_query = match AssignmentQuery::from_request(&query, context.school_id) {
         Ok(query) => query,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
     submission_query.assignment_id = Some(assignment_id);
 
@@ -441,7 +490,7 @@ async fn get_assignment_submissions(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -472,7 +521,7 @@ async fn grade_submission(
         Ok(None) if matches!(user.role, Some(UserRole::ADMIN)) => {
             match parse_object_id_value(&user.id) {
                 Ok(id) => id,
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             }
         }
         Ok(None) => {
@@ -480,7 +529,7 @@ async fn grade_submission(
                 "message": "Teacher record not found"
             }));
         }
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     if !matches!(user.role, Some(UserRole::ADMIN)) {
@@ -500,7 +549,7 @@ async fn grade_submission(
                     }
                 }
             }
-            Err(err) => return HttpResponse::NotFound().json(err),
+            Err(err) => return err.to_response(),
         }
     }
 
@@ -526,13 +575,13 @@ async fn grade_submission(
         .await
     {
         Ok(submission) => HttpResponse::Ok().json(submission),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/submissions/{id}")]
 async fn get_submission_by_id(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
@@ -541,7 +590,7 @@ async fn get_submission_by_id(
     let service = AssignmentService::new(postgres_pool(&state));
     let submission = match service.find_one_submission(Some(&id), None).await {
         Ok(sub) => sub,
-        Err(err) => return HttpResponse::NotFound().json(err),
+        Err(err) => return err.to_response(),
     };
 
     if matches!(user.role, Some(UserRole::STUDENT)) {
@@ -559,13 +608,15 @@ async fn get_submission_by_id(
 
     if matches!(user.role, Some(UserRole::PARENT)) {
         if let Some(student_id) = submission.student_id {
-            let parent_service = ParentService::new(&state.pg.pool);
-            if let Err(e) =
-                require_parent_child_access(&user, &student_id.to_hex(), &parent_service).await
+            if let Err(err) = require_parent_child_access(
+                &user,
+                &student_id.to_hex(),
+                "submission.read.class",
+                &PermissionService::for_request(&req, &state.pg.pool),
+            )
+            .await
             {
-                return HttpResponse::Forbidden().json(serde_json::json!({
-                    "message": e
-                }));
+                return err.to_response();
             }
         } else {
             return HttpResponse::Forbidden().json(serde_json::json!({
@@ -600,7 +651,7 @@ async fn get_submission_by_id(
                 }))
             }
         }
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -638,13 +689,13 @@ async fn update_submission(
                     }
                 }
             }
-            Err(err) => return HttpResponse::NotFound().json(err),
+            Err(err) => return err.to_response(),
         }
     }
 
     match service.update_submission(&id, &data.into_inner()).await {
         Ok(submission) => HttpResponse::Ok().json(submission),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -0,0 +1,346 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        attendance::{
+            AttendancePartial, AttendanceQuery, AttendanceRangeQuery, BulkAttendanceRequest,
+            RegisterQuery,
+        },
+        auth_user::AuthUserDto,
+        role::PermissionTarget,
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    guards::role_guard::{
+        check_admin_or_class_teacher, check_student_access, require_request_permission,
+        require_request_school, require_scoped_permission,
+    },
+    models::id_model::IdType,
+    repositories::attendance_repo::AttendanceRepo,
+    services::{
+        attendance_service::AttendanceService, event_service::EventService,
+        permission_service::PermissionService, tenant_service::TenantService,
+    },
+    utils::request_context::{postgres_pool, request_context},
+};
+
+/// Admins and teachers of the class, or members whose school role grants
+/// `permission` for it, at whatever scope the permission is granted
+async fn check_class_attendance(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    class_id: &str,
+    permission: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let tenant_service = TenantService::new(postgres_pool(state));
+    let Err(message) = check_admin_or_class_teacher(user, class_id, &tenant_service).await else {
+        return Ok(());
+    };
+    let Ok(school_id) = tenant_service
+        .school_of(TenantEntity::Class, class_id)
+        .await
+    else {
+        return Err(AppError::forbidden(message).to_response());
+    };
+
+    let target = PermissionTarget::school(school_id).class(class_id);
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_scoped_permission(user, permission, &target, &permission_service)
+        .await
+        .map_err(|_| AppError::forbidden(message).to_response())
+}
+
+#[get("")]
+async fn get_all_attendance(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<AttendanceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match query.class_id.as_deref() {
+        Some(class_id) => {
+            if let Err(response) =
+                check_class_attendance(&req, &user, class_id, "attendance.read.class", &state).await
+            {
+                return response;
+            }
+            let tenant_service = TenantService::new(postgres_pool(&state));
+            require_request_school(&req, &user, &tenant_service).await
+        }
+        None => {
+            let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+            require_request_permission(&req, &user, "attendance.read.school", &permission_service)
+                .await
+        }
+    };
+    let school_id = match school_id {
+        Ok(school_id) => school_id,
+        Err(err) => return err.to_response(),
+    };
+
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+
+    match service.get_all(&school_id, &query).await {
+        Ok(data) => HttpResponse::Ok().json(data),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/class/{class_id}/register")]
+async fn get_class_register(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<RegisterQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let class_id = path.into_inner();
+    if let Err(response) =
+        check_class_attendance(&req, &user, &class_id, "attendance.read.class", &state).await
+    {
+        return response;
+    }
+
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+    let context = request_context(&req);
+
+    if let Err(err) = service
+        .resolve_class_school(&class_id, context.school_id.as_deref())
+        .await
+    {
+        return err.to_response();
+    }
+
+    let date = query.date.unwrap_or_else(|| Utc::now().date_naive());
+    match service
+        .get_register(&class_id, date, query.period_id.as_deref())
+        .await
+    {
+        Ok(register) => HttpResponse::Ok().json(register),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/class/{class_id}/register")]
+async fn submit_class_register(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<BulkAttendanceRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let class_id = path.into_inner();
+    if let Err(response) =
+        check_class_attendance(&req, &user, &class_id, "attendance.record.class", &state).await
+    {
+        return response;
+    }
+
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+    let context = request_context(&req);
+
+    let school_id = match service
+        .resolve_class_school(&class_id, context.school_id.as_deref())
+        .await
+    {
+        Ok(school_id) => school_id,
+        Err(err) => return err.to_response(),
+    };
+
+    match service
+        .submit_register(&school_id, &class_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(register) => {
+            let register_clone = register.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                EventService::broadcast_updated(
+                    &state_clone,
+                    "attendance_register",
+                    &register_clone.class_id.to_hex(),
+                    Some(school_id),
+                    &register_clone,
+                )
+                .await;
+            });
+
+            HttpResponse::Ok().json(register)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/student/{student_id}/summary")]
+async fn get_student_summary(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<AttendanceRangeQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let student_id = path.into_inner();
+    let tenant_service = TenantService::new(postgres_pool(&state));
+    if let Err(err) = check_student_access(&user, &student_id, &tenant_service).await {
+        return AppError::forbidden(err).to_response();
+    }
+
+    let school_id = match require_request_school(&req, &user, &tenant_service).await {
+        Ok(school_id) => school_id,
+        Err(err) => return err.to_response(),
+    };
+
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+
+    match service
+        .student_summary(&school_id, &student_id, query.from, query.to)
+        .await
+    {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/{id}")]
+async fn get_attendance_by_id(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let id = IdType::from_string(path.into_inner());
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+
+    let record = match service.find_one(&id).await {
+        Ok(record) => record,
+        Err(err) => return err.to_response(),
+    };
+
+    let class_id = record.class_id.map(|id| id.to_hex()).unwrap_or_default();
+    if let Err(response) =
+        check_class_attendance(&req, &user, &class_id, "attendance.read.class", &state).await
+    {
+        return response;
+    }
+
+    HttpResponse::Ok().json(record)
+}
+
+#[put("/{id}")]
+async fn update_attendance(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<AttendancePartial>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let id = IdType::from_string(path.into_inner());
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+
+    let existing = match service.find_one(&id).await {
+        Ok(record) => record,
+        Err(err) => return err.to_response(),
+    };
+
+    let class_id = existing.class_id.map(|id| id.to_hex()).unwrap_or_default();
+    if let Err(response) =
+        check_class_attendance(&req, &user, &class_id, "attendance.record.class", &state).await
+    {
+        return response;
+    }
+
+    match service.update(&id, data.into_inner(), Some(&user.id)).await {
+        Ok(record) => {
+            let record_clone = record.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = record_clone.id {
+                    EventService::broadcast_updated(
+                        &state_clone,
+                        "attendance",
+                        &id.to_hex(),
+                        record_clone.school_id.map(|id| id.to_hex()),
+                        &record_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Ok().json(record)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/{id}")]
+async fn delete_attendance(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let id = IdType::from_string(path.into_inner());
+    let repo = AttendanceRepo::new(postgres_pool(&state));
+    let service = AttendanceService::new(&repo);
+
+    let existing = match service.find_one(&id).await {
+        Ok(record) => record,
+        Err(err) => return err.to_response(),
+    };
+
+    let class_id = existing.class_id.map(|id| id.to_hex()).unwrap_or_default();
+    if let Err(response) =
+        check_class_attendance(&req, &user, &class_id, "attendance.record.class", &state).await
+    {
+        return response;
+    }
+
+    match service.delete(&id).await {
+        Ok(record) => {
+            let record_clone = record.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = record_clone.id {
+                    EventService::broadcast_deleted(
+                        &state_clone,
+                        "attendance",
+                        &id.to_hex(),
+                        record_clone.school_id.map(|id| id.to_hex()),
+                        &record_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Ok().json(record)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_all_attendance)
+            .service(get_class_register)
+            .service(submit_class_register)
+            .service(get_student_summary)
+            .service(get_attendance_by_id)
+            .service(update_attendance)
+            .service(delete_attendance),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "attendance", blueprint);
+}
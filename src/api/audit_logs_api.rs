@@ -5,7 +5,7 @@ use crate::{
     domain::auth_user::AuthUserDto,
     guards::role_guard::check_permission,
     models::{api_request_model::RequestQuery, id_model::IdType},
-    services::audit_log_service::AuditLogService,
+    services::{audit_log_service::AuditLogService, permission_service::PermissionService},
     utils::request_context::{postgres_pool, request_context},
 };
 
@@ -23,14 +23,21 @@ async fn get_all_audit_logs(
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = scoped_school_id(&req, &query);
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
     }
 
     let service = AuditLogService::new(postgres_pool(&state));
-    let school_id = scoped_school_id(&req, &query);
 
     match service
         .get_all(
@@ -43,7 +50,7 @@ async fn get_all_audit_logs(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -54,14 +61,21 @@ async fn get_all_audit_logs_with_relations(
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = scoped_school_id(&req, &query);
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
     }
 
     let service = AuditLogService::new(postgres_pool(&state));
-    let school_id = scoped_school_id(&req, &query);
 
     match service
         .get_all_with_relations(
@@ -74,18 +88,26 @@ async fn get_all_audit_logs_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/{id}")]
 async fn get_audit_log_by_id(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = request_context(&req).school_id;
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -94,20 +116,31 @@ async fn get_audit_log_by_id(
     let id = IdType::from_string(path.into_inner());
     let service = AuditLogService::new(postgres_pool(&state));
 
-    match service.find_one(Some(&id), None, None).await {
+    match service
+        .find_one(Some(&id), None, school_id.as_deref())
+        .await
+    {
         Ok(audit_log) => HttpResponse::Ok().json(audit_log),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/{id}/others")]
 async fn get_audit_log_by_id_with_relations(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = request_context(&req).school_id;
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
@@ -116,9 +149,12 @@ async fn get_audit_log_by_id_with_relations(
     let id = IdType::from_string(path.into_inner());
     let service = AuditLogService::new(postgres_pool(&state));
 
-    match service.find_one_with_relations(Some(&id), None, None).await {
+    match service
+        .find_one_with_relations(Some(&id), None, school_id.as_deref())
+        .await
+    {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -129,21 +165,28 @@ async fn get_audit_log_by_match(
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = scoped_school_id(&req, &query);
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
     }
 
     let service = AuditLogService::new(postgres_pool(&state));
-    let school_id = scoped_school_id(&req, &query);
 
     match service
         .find_one(None, Some(&query), school_id.as_deref())
         .await
     {
         Ok(audit_log) => HttpResponse::Ok().json(audit_log),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -154,21 +197,28 @@ async fn get_audit_log_by_other_match(
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = scoped_school_id(&req, &query);
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
     }
 
     let service = AuditLogService::new(postgres_pool(&state));
-    let school_id = scoped_school_id(&req, &query);
 
     match service
         .find_one_with_relations(None, Some(&query), school_id.as_deref())
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -179,21 +229,28 @@ async fn count_audit_logs(
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_permission(&user, "audit.view") {
+    let school_id = scoped_school_id(&req, &query);
+    if let Err(err) = check_permission(
+        &user,
+        school_id.as_deref(),
+        "audit.view",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
         return HttpResponse::Forbidden().json(serde_json::json!({
             "message": err
         }));
     }
 
     let service = AuditLogService::new(postgres_pool(&state));
-    let school_id = scoped_school_id(&req, &query);
 
     match service
         .count_audit_logs(query.filter.clone(), Some(&query), school_id.as_deref())
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!({ "count": count })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -1,50 +1,95 @@
-use actix_web::{get, patch, post, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, patch, post, web, HttpMessage, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
     domain::{
+        account_token::{ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest},
         auth::{LoginUser, RegisterUser},
+        auth_session::{RefreshTokenRequest, SessionMetadata},
         auth_user::AuthUserDto,
+        two_factor::{
+            RecoveryCodes, TwoFactorChallengeRequest, TwoFactorCodeRequest, TwoFactorVerifyRequest,
+        },
         user::UpdateUserDto,
     },
+    errors::AppError,
     middleware::jwt_middleware::JwtMiddleware,
-    models::request_error_model::ReqErrModel,
     repositories::user_repo::UserRepo,
-    services::{auth_service::AuthService, user_service::UserService},
+    services::{
+        account_token_service::AccountTokenService, auth_service::AuthService,
+        auth_session_service::AuthSessionService, two_factor_service::TwoFactorService,
+        user_service::UserService,
+    },
 };
 
+/// Device and address a sign-in or refresh came from
+fn session_metadata(req: &HttpRequest) -> SessionMetadata {
+    SessionMetadata {
+        user_agent: req
+            .headers()
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned),
+        ip_address: req
+            .connection_info()
+            .realip_remote_addr()
+            .map(str::to_owned),
+    }
+}
+
 #[post("/register")]
 async fn register_user(
+    req: HttpRequest,
     data: web::Json<RegisterUser>,
     state: web::Data<AppState>,
 ) -> impl Responder {
     let repo = UserRepo::new(&state.pg.pool);
     let service = AuthService::new(&repo);
     let user_service = UserService::new(&repo);
-    match service.register(&user_service, data.into_inner()).await {
-        Ok((token, user)) => HttpResponse::Created().json(serde_json::json!({
-          "id": user.id.map(|i| i.to_string()),
-            "email": user.email,
-            "name": user.name,
-            "access_token": token,
-            "image": user.image,
-            "role": user.role,
-            "username": user.username,
-            "bio": user.bio,
-            "school_access_token": ""
-        })),
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+    let meta = session_metadata(&req);
+    match service
+        .register(&user_service, data.into_inner(), &meta)
+        .await
+    {
+        Ok((token, refresh, user)) => {
+            if let Some(user_id) = user.id.map(|id| id.to_hex()) {
+                let tokens = AccountTokenService::new(&state.pg.pool, state.mailer.clone());
+                tokio::spawn(async move {
+                    if let Err(err) = tokens.send_verification(&user_id).await {
+                        log::error!("Failed to send verification mail: {}", err.message);
+                    }
+                });
+            }
+            HttpResponse::Created().json(serde_json::json!({
+            "id": user.id.map(|i| i.to_string()),
+              "email": user.email,
+              "name": user.name,
+              "access_token": token,
+              "refresh_token": refresh,
+              "image": user.image,
+              "role": user.role,
+              "username": user.username,
+              "bio": user.bio,
+              "school_access_token": ""
+              }))
+        }
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
 #[post("/login")]
-async fn login_user(data: web::Json<LoginUser>, state: web::Data<AppState>) -> impl Responder {
+async fn login_user(
+    req: HttpRequest,
+    data: web::Json<LoginUser>,
+    state: web::Data<AppState>,
+) -> impl Responder {
     let user_repo = UserRepo::new(&state.pg.pool);
     let auth_service = AuthService::new(&user_repo);
+    let meta = session_metadata(&req);
 
-    match auth_service.login(data.into_inner(), &state).await {
+    match auth_service.login(data.into_inner(), &state, &meta).await {
         Ok(response) => HttpResponse::Ok().json(response),
-        Err(message) => HttpResponse::Unauthorized().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -63,14 +108,14 @@ async fn get_me(req: HttpRequest, state: web::Data<AppState>) -> impl Responder
                     s.to_string()
                 }
             }
-            Err(_) => return HttpResponse::Unauthorized().body("Invalid authorization header"),
+            Err(_) => return AppError::unauthorized("Invalid authorization header").to_response(),
         },
-        None => return HttpResponse::Unauthorized().body("Missing authorization header"),
+        None => return AppError::unauthorized("Missing authorization header").to_response(),
     };
 
     match service.get_user_from_token(&token).await {
         Ok(user) => HttpResponse::Ok().json(user),
-        Err(message) => HttpResponse::Unauthorized().json(ReqErrModel { message }),
+        Err(message) => AppError::unauthorized(message).to_response(),
     }
 }
 
@@ -82,18 +127,12 @@ async fn onboarding_user(
 ) -> impl Responder {
     let logged_user = match req.extensions().get::<AuthUserDto>() {
         Some(u) => u.clone(),
-        None => {
-            return HttpResponse::Unauthorized().json(ReqErrModel {
-                message: "Unauthorized".to_string(),
-            })
-        }
+        None => return AppError::unauthorized("Unauthorized").to_response(),
     };
 
     if let Err(err) = crate::guards::role_guard::check_owner_or_admin(&logged_user, &logged_user.id)
     {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+        return AppError::forbidden(err.to_string()).to_response();
     }
 
     let repo = UserRepo::new(&state.pg.pool);
@@ -101,7 +140,12 @@ async fn onboarding_user(
     let auth_service = AuthService::new(&repo);
 
     match auth_service
-        .onboard_user(&logged_user.id, data.into_inner(), &user_service)
+        .onboard_user(
+            &logged_user.id,
+            logged_user.session_id.clone(),
+            data.into_inner(),
+            &user_service,
+        )
         .await
     {
         Ok((new_token, user)) => HttpResponse::Ok().json(serde_json::json!({
@@ -115,43 +159,250 @@ async fn onboarding_user(
             "bio": user.bio,
             "school_access_token": ""
         })),
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
-#[post("/refresh")]
-async fn refresh_token(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+/// Rotate a refresh token. Needs no access token, since it is used once
+/// the access token has expired.
+#[post("/auth/refresh")]
+async fn refresh_token(
+    req: HttpRequest,
+    data: web::Json<RefreshTokenRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
     let repo = UserRepo::new(&state.pg.pool);
     let service = AuthService::new(&repo);
+    let meta = session_metadata(&req);
 
-    // Extract token from Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(hv) => match hv.to_str() {
-            Ok(s) => {
-                if let Some(stripped) = s.strip_prefix("Bearer ") {
-                    stripped.to_string()
-                } else {
-                    s.to_string()
-                }
-            }
-            Err(_) => {
-                return HttpResponse::Unauthorized().json(ReqErrModel {
-                    message: "Invalid authorization header".to_string(),
-                })
-            }
-        },
-        None => {
-            return HttpResponse::Unauthorized().json(ReqErrModel {
-                message: "Missing authorization header".to_string(),
-            })
-        }
-    };
+    match service
+        .refresh_token(&data.refresh_token, &state, &meta)
+        .await
+    {
+        Ok(tokens) => HttpResponse::Ok().json(tokens),
+        Err(err) => err.to_response(),
+    }
+}
 
-    match service.refresh_token(&token, &state).await {
-        Ok(new_token) => HttpResponse::Ok().json(serde_json::json!({
-            "accessToken": new_token
+/// Mail a password reset link. Answers the same whether or not the
+/// address has an account.
+#[post("/auth/password/forgot")]
+async fn forgot_password(
+    req: HttpRequest,
+    data: web::Json<ForgotPasswordRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = AccountTokenService::new(&state.pg.pool, state.mailer.clone());
+    let meta = session_metadata(&req);
+    match service
+        .request_password_reset(&data.email, meta.ip_address.as_deref())
+        .await
+    {
+        Ok(()) => HttpResponse::Accepted().json(serde_json::json!({
+            "message": "If the address belongs to an account, a reset link is on its way"
         })),
-        Err(message) => HttpResponse::Unauthorized().json(message),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/auth/password/reset")]
+async fn reset_password(
+    data: web::Json<ResetPasswordRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = AccountTokenService::new(&state.pg.pool, state.mailer.clone());
+    match service.reset_password(&data.token, &data.password).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Password updated, please sign in again"
+        })),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/auth/email/verify")]
+async fn verify_email(
+    data: web::Json<VerifyEmailRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = AccountTokenService::new(&state.pg.pool, state.mailer.clone());
+    match service.verify_email(&data.token).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "message": "Email verified" })),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Send the signed-in user a new verification link
+#[post("/email/verification")]
+async fn resend_verification(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = AccountTokenService::new(&state.pg.pool, state.mailer.clone());
+    match service.send_verification(&user.id).await {
+        Ok(()) => HttpResponse::Accepted().json(serde_json::json!({
+            "message": "Verification email sent"
+        })),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Answer a login challenge with an authenticator or recovery code
+#[post("/auth/2fa/verify")]
+async fn verify_two_factor(
+    req: HttpRequest,
+    data: web::Json<TwoFactorVerifyRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let repo = UserRepo::new(&state.pg.pool);
+    let service = AuthService::new(&repo);
+    let meta = session_metadata(&req);
+
+    match service
+        .complete_two_factor_login(&data.challenge_token, &data.code, &state, &meta)
+        .await
+    {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Enrol from a login challenge that has `setup_required`
+#[post("/auth/2fa/challenge/setup")]
+async fn challenge_two_factor_setup(
+    data: web::Json<TwoFactorChallengeRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service.challenge_setup(&data.challenge_token).await {
+        Ok(setup) => HttpResponse::Ok().json(setup),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/2fa")]
+async fn two_factor_status(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service.status(&user.id, user.role.as_ref()).await {
+        Ok(status) => HttpResponse::Ok().json(status),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Start enrolment: returns the secret and its QR provisioning URI
+#[post("/2fa/setup")]
+async fn setup_two_factor(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service.begin_setup(&user.id, &user.email).await {
+        Ok(setup) => HttpResponse::Ok().json(setup),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Confirm enrolment with a first code; recovery codes are shown once
+#[post("/2fa/confirm")]
+async fn confirm_two_factor(
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<TwoFactorCodeRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service.confirm_setup(&user.id, &data.code).await {
+        Ok(recovery_codes) => HttpResponse::Ok().json(RecoveryCodes { recovery_codes }),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/2fa/disable")]
+async fn disable_two_factor(
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<TwoFactorCodeRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service
+        .disable(&user.id, user.role.as_ref(), &data.code)
+        .await
+    {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/2fa/recovery-codes")]
+async fn regenerate_recovery_codes(
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<TwoFactorCodeRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service
+        .regenerate_recovery_codes(&user.id, &data.code)
+        .await
+    {
+        Ok(recovery_codes) => HttpResponse::Ok().json(RecoveryCodes { recovery_codes }),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Sessions the signed-in user is logged in with
+#[get("/sessions")]
+async fn list_sessions(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = AuthSessionService::new(&state.pg.pool);
+    match service.list(&user.id, user.session_id.as_deref()).await {
+        Ok(sessions) => HttpResponse::Ok().json(sessions),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/sessions/{id}")]
+async fn revoke_session(
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let service = AuthSessionService::new(&state.pg.pool);
+    match service
+        .revoke(&user.id, &path.into_inner(), user.session_id.as_deref())
+        .await
+    {
+        Ok(session) => HttpResponse::Ok().json(session),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Log out of the session the access token belongs to
+#[post("/logout")]
+async fn logout(user: web::ReqData<AuthUserDto>, state: web::Data<AppState>) -> impl Responder {
+    let Some(session_id) = user.session_id.clone() else {
+        return AppError::bad_request("Token is not bound to a session").to_response();
+    };
+
+    let service = AuthSessionService::new(&state.pg.pool);
+    match service
+        .revoke(&user.id, &session_id, Some(&session_id))
+        .await
+    {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Log out everywhere by revoking every session of the user
+#[post("/logout-all")]
+async fn logout_all(user: web::ReqData<AuthUserDto>, state: web::Data<AppState>) -> impl Responder {
+    let service = AuthSessionService::new(&state.pg.pool);
+    match service.revoke_all(&user.id, "logout_all").await {
+        Ok(revoked) => HttpResponse::Ok().json(serde_json::json!({ "revoked": revoked })),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -159,11 +410,28 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(register_user);
     cfg.service(login_user);
     cfg.service(get_me);
+    // Registered ahead of the `/auth` scope so they stay reachable without
+    // an access token
+    cfg.service(refresh_token);
+    cfg.service(forgot_password);
+    cfg.service(reset_password);
+    cfg.service(verify_email);
+    cfg.service(verify_two_factor);
+    cfg.service(challenge_two_factor_setup);
 
     cfg.service(
         web::scope("/auth")
             .wrap(JwtMiddleware)
             .service(onboarding_user)
-            .service(refresh_token),
+            .service(resend_verification)
+            .service(two_factor_status)
+            .service(setup_two_factor)
+            .service(confirm_two_factor)
+            .service(disable_two_factor)
+            .service(regenerate_recovery_codes)
+            .service(list_sessions)
+            .service(revoke_session)
+            .service(logout)
+            .service(logout_all),
     );
 }
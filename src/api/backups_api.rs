@@ -1,172 +1,259 @@
-use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
-    domain::auth_user::AuthUserDto,
-    guards::role_guard::check_admin,
+    domain::{auth_user::AuthUserDto, backup::UpdateBackupPolicy},
+    errors::AppError,
+    guards::role_guard::require_request_permission,
     models::{api_request_model::RequestQuery, id_model::IdType},
-    services::backup_service::BackupService,
+    services::{backup_service::BackupService, permission_service::PermissionService},
     utils::{object_id::parse_object_id_value, request_context::postgres_pool},
 };
 
-fn school_id_from_state(_state: &web::Data<AppState>, req: &HttpRequest) -> Option<String> {
-    use actix_web::HttpMessage;
-    use crate::models::school_token_model::SchoolToken;
-    req.extensions().get::<SchoolToken>().map(|t| t.id.clone())
+/// The request's school, once the caller holds `permission` for its backups
+async fn backup_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    permission: &str,
+    state: &web::Data<AppState>,
+) -> Result<crate::utils::object_id::ObjectId, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    let school_id = require_request_permission(req, user, permission, &permission_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    parse_object_id_value(&school_id).map_err(|err| err.to_response())
 }
 
 #[get("")]
 async fn get_all_backups(
     req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.read", &state).await {
+        Ok(oid) => oid.to_hex(),
+        Err(response) => return response,
+    };
+
     let service = BackupService::new(postgres_pool(&state));
-    let school_id = school_id_from_state(&state, &req);
-    match service.get_all(query.filter.clone(), query.limit, query.skip, school_id.as_deref()).await {
+    match service.get_all(query.filter.clone(), query.limit, query.skip, Some(&school_id)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/others")]
 async fn get_all_backups_with_relations(
     req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.read", &state).await {
+        Ok(oid) => oid.to_hex(),
+        Err(response) => return response,
+    };
+
     let service = BackupService::new(postgres_pool(&state));
-    let school_id = school_id_from_state(&state, &req);
-    match service.get_all_with_relations(query.filter.clone(), query.limit, query.skip, school_id.as_deref()).await {
+    match service.get_all_with_relations(query.filter.clone(), query.limit, query.skip, Some(&school_id)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/{id}")]
 async fn get_backup_by_id(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.read", &state).await {
+        Ok(oid) => oid.to_hex(),
+        Err(response) => return response,
+    };
+
     let id = IdType::from_string(path.into_inner());
     let service = BackupService::new(postgres_pool(&state));
-    match service.find_one(Some(&id), None).await {
+    match service.find_one(Some(&id), Some(&school_id)).await {
         Ok(backup) => HttpResponse::Ok().json(backup),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/{id}/others")]
 async fn get_backup_by_id_with_relations(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.read", &state).await {
+        Ok(oid) => oid.to_hex(),
+        Err(response) => return response,
+    };
+
     let id = IdType::from_string(path.into_inner());
     let service = BackupService::new(postgres_pool(&state));
-    match service.find_one_with_relations(Some(&id), None).await {
+    match service.find_one_with_relations(Some(&id), Some(&school_id)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("/manual")]
 async fn create_manual_backup(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err_msg) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err_msg.to_string() }));
-    }
-
-    let school_id = match user.current_school_id.as_ref() {
-        Some(id) => match parse_object_id_value(id) {
-            Ok(oid) => oid,
-            Err(err) => return HttpResponse::BadRequest().json(err),
-        },
-        None => return HttpResponse::BadRequest().json(serde_json::json!({ "message": "School ID not found in user context" })),
+    let school_id = match backup_school_id(&req, &user, "backup.manage", &state).await {
+        Ok(oid) => oid,
+        Err(response) => return response,
     };
 
     let service = BackupService::new(postgres_pool(&state));
     match service.create_manual_backup(school_id, &user, &state).await {
         Ok(backup) => HttpResponse::Created().json(backup),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("/{id}/restore")]
 async fn restore_backup(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err_msg) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err_msg.to_string() }));
-    }
+    let school_id = match backup_school_id(&req, &user, "backup.manage", &state).await {
+        Ok(oid) => oid,
+        Err(response) => return response,
+    };
 
     let id = IdType::from_string(path.into_inner());
     let service = BackupService::new(postgres_pool(&state));
 
     match service.find_one(Some(&id), None).await {
         Ok(backup) => {
-            let backup_school_id = backup.school_id.map(|oid| oid.to_hex());
-            if backup_school_id != user.current_school_id {
-                return HttpResponse::Forbidden().json(serde_json::json!({ "message": "Cannot restore backup from another school" }));
+            if backup.school_id != Some(school_id) {
+                return AppError::forbidden("Cannot restore backup from another school").to_response();
             }
         }
-        Err(err) => return HttpResponse::NotFound().json(err),
+        Err(err) => return err.to_response(),
     }
 
     match service.restore_backup(&id, &user, &state).await {
         Ok(restore_record) => HttpResponse::Ok().json(restore_record),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[delete("/{id}")]
 async fn delete_backup(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
-    }
+    let school_id = match backup_school_id(&req, &user, "backup.manage", &state).await {
+        Ok(oid) => oid,
+        Err(response) => return response,
+    };
 
     let id = IdType::from_string(path.into_inner());
     let service = BackupService::new(postgres_pool(&state));
+    match service.find_one(Some(&id), None).await {
+        Ok(backup) if backup.school_id != Some(school_id) => {
+            return AppError::forbidden("Cannot delete backup from another school").to_response();
+        }
+        Ok(_) => {}
+        Err(err) => return err.to_response(),
+    }
     match service.delete(&id).await {
         Ok(backup) => HttpResponse::Ok().json(backup),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/count")]
 async fn count_backups(
     req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.read", &state).await {
+        Ok(oid) => oid.to_hex(),
+        Err(response) => return response,
+    };
+
     let service = BackupService::new(postgres_pool(&state));
-    let school_id = school_id_from_state(&state, &req);
-    match service.count_backups(query.filter.clone(), school_id.as_deref()).await {
+    match service.count_backups(query.filter.clone(), Some(&school_id)).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("")]
+async fn get_backup_policy(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.manage", &state).await {
+        Ok(oid) => oid,
+        Err(response) => return response,
+    };
+
+    let service = BackupService::new(postgres_pool(&state));
+    match service.get_policy(&school_id).await {
+        Ok(policy) => HttpResponse::Ok().json(policy),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("")]
+async fn update_backup_policy(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<UpdateBackupPolicy>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match backup_school_id(&req, &user, "backup.manage", &state).await {
+        Ok(oid) => oid,
+        Err(response) => return response,
+    };
+
+    let service = BackupService::new(postgres_pool(&state));
+    match service.update_policy(&school_id, data.into_inner(), &user).await {
+        Ok(policy) => HttpResponse::Ok().json(policy),
+        Err(err) => err.to_response(),
     }
 }
 
 fn blueprint(cfg: &mut web::ServiceConfig) {
-    cfg.service(get_all_backups)
-        .service(get_all_backups_with_relations)
-        .service(get_backup_by_id)
-        .service(get_backup_by_id_with_relations)
-        .service(count_backups)
-        .service(
-            web::scope("")
-                .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
-                .service(create_manual_backup)
-                .service(restore_backup)
-                .service(delete_backup),
-        );
+    // Registered before `/{id}` so "policy" and "count" are not taken for a
+    // backup id
+    cfg.service(
+        web::scope("/policy")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_backup_policy)
+            .service(update_backup_policy),
+    )
+    .service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(count_backups)
+            .service(get_all_backups)
+            .service(get_all_backups_with_relations)
+            .service(get_backup_by_id)
+            .service(get_backup_by_id_with_relations)
+            .service(create_manual_backup)
+            .service(restore_backup)
+            .service(delete_backup),
+    );
 }
 
 pub fn init(cfg: &mut web::ServiceConfig) {
@@ -0,0 +1,193 @@
+use actix_multipart::Multipart;
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use futures::StreamExt;
+
+use crate::{
+    config::state::AppState,
+    domain::{auth_user::AuthUserDto, bulk_import::ImportOptions},
+    guards::role_guard::require_request_permission,
+    services::{
+        audit_log_service::AuditLogService, bulk_import_service::BulkImportService,
+        permission_service::PermissionService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
+};
+
+/// Uploads larger than this are refused before parsing
+const MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+async fn school_id_for(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<ObjectId, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    let school_id = require_request_permission(req, user, "import.manage", &permission_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    ObjectId::parse_str(&school_id).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid school id" }))
+    })
+}
+
+/// Multipart upload with a `file` part (CSV or XLSX) and a `data` part
+/// holding the JSON import options
+#[post("")]
+async fn run_import(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    mut payload: Multipart,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let mut options: Option<ImportOptions> = None;
+    let mut file_bytes: Option<Vec<u8>> = None;
+    let mut file_name: Option<String> = None;
+
+    while let Some(item) = payload.next().await {
+        let mut field = match item {
+            Ok(f) => f,
+            Err(e) => {
+                return HttpResponse::BadRequest()
+                    .json(serde_json::json!({"message": format!("Multipart error: {}", e)}))
+            }
+        };
+
+        let field_name = field.name().map(str::to_string);
+        if field_name.as_deref() == Some("file") {
+            file_name = field
+                .content_disposition()
+                .and_then(|cd| cd.get_filename())
+                .map(|s| s.to_string());
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let data = match chunk {
+                Ok(d) => d,
+                Err(e) => {
+                    return HttpResponse::BadRequest()
+                        .json(serde_json::json!({"message": format!("Read error: {}", e)}))
+                }
+            };
+            if bytes.len() + data.len() > MAX_UPLOAD_BYTES {
+                return HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                    "message": "Import files are limited to 10 MB"
+                }));
+            }
+            bytes.extend_from_slice(&data);
+        }
+
+        match field_name.as_deref() {
+            Some("data") => {
+                options = match serde_json::from_slice(&bytes) {
+                    Ok(o) => Some(o),
+                    Err(e) => {
+                        return HttpResponse::BadRequest()
+                            .json(serde_json::json!({"message": format!("JSON error: {}", e)}))
+                    }
+                }
+            }
+            Some("file") => file_bytes = Some(bytes),
+            _ => {}
+        }
+    }
+
+    let (Some(options), Some(file_bytes)) = (options, file_bytes) else {
+        return HttpResponse::BadRequest()
+            .json(serde_json::json!({"message": "Both the data and file parts are required"}));
+    };
+
+    let service = BulkImportService::new(postgres_pool(&state));
+    let dry_run = options.dry_run;
+    match service
+        .run(&school_id, &user, options, file_name, &file_bytes)
+        .await
+    {
+        Ok(report) if report.committed => {
+            if let Some(id) = report.id {
+                AuditLogService::new(postgres_pool(&state))
+                    .log_event(
+                        school_id,
+                        &user,
+                        "bulk_import.commit",
+                        "bulk_import",
+                        id,
+                        None,
+                        None,
+                        None,
+                    )
+                    .await
+                    .ok();
+            }
+            HttpResponse::Created().json(report)
+        }
+        // Nothing was written: either a dry run or invalid rows blocked it
+        Ok(report) if dry_run => HttpResponse::Ok().json(report),
+        Ok(report) => HttpResponse::UnprocessableEntity().json(report),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("")]
+async fn list_imports(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    match BulkImportService::new(postgres_pool(&state))
+        .list(&school_id)
+        .await
+    {
+        Ok(imports) => HttpResponse::Ok().json(imports),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/{id}")]
+async fn get_import(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let Ok(id) = ObjectId::parse_str(path.into_inner()) else {
+        return HttpResponse::BadRequest()
+            .json(serde_json::json!({ "message": "Invalid import id" }));
+    };
+
+    match BulkImportService::new(postgres_pool(&state))
+        .find(&school_id, &id)
+        .await
+    {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(run_import)
+            .service(list_imports)
+            .service(get_import),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "imports", blueprint);
+}
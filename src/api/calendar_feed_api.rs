@@ -0,0 +1,167 @@
+use std::env;
+
+use actix_web::{
+    delete, get, http::header, post, web, HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        auth_user::AuthUserDto,
+        calendar_feed::{CalendarFeedCreated, CreateCalendarFeed},
+    },
+    services::calendar_feed_service::CalendarFeedService,
+    utils::{
+        object_id::ObjectId,
+        request_context::{postgres_pool, request_context},
+    },
+};
+
+fn server_url(req: &HttpRequest) -> String {
+    if let Ok(public_api_url) = env::var("PUBLIC_API_URL") {
+        let public_api_url = public_api_url.trim().trim_end_matches('/');
+        if !public_api_url.is_empty() {
+            return public_api_url.to_string();
+        }
+    }
+
+    let connection_info = req.connection_info();
+    format!("{}://{}", connection_info.scheme(), connection_info.host())
+}
+
+fn user_id_for(user: &AuthUserDto) -> Result<ObjectId, HttpResponse> {
+    ObjectId::parse_str(&user.id).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid user id" }))
+    })
+}
+
+/// The school a new feed covers: the request's school context or the
+/// user's current school, as long as the user belongs to it.
+fn school_id_for(req: &HttpRequest, user: &AuthUserDto) -> Result<ObjectId, HttpResponse> {
+    let Some(school_id) = request_context(req)
+        .school_id
+        .or_else(|| user.current_school_id.clone())
+    else {
+        return Err(HttpResponse::BadRequest()
+            .json(serde_json::json!({ "message": "School ID is required" })));
+    };
+
+    let member = user.current_school_id.as_deref() == Some(school_id.as_str())
+        || user
+            .schools
+            .as_ref()
+            .is_some_and(|schools| schools.contains(&school_id))
+        || req
+            .extensions()
+            .get::<crate::models::school_token_model::SchoolToken>()
+            .is_some();
+    if !member {
+        return Err(HttpResponse::Forbidden()
+            .json(serde_json::json!({ "message": "You are not a member of this school" })));
+    }
+
+    ObjectId::parse_str(&school_id).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid school id" }))
+    })
+}
+
+#[get("")]
+async fn get_feeds(user: web::ReqData<AuthUserDto>, state: web::Data<AppState>) -> impl Responder {
+    let user_id = match user_id_for(&user) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = CalendarFeedService::new(postgres_pool(&state));
+    match service.list(&user_id).await {
+        Ok(feeds) => HttpResponse::Ok().json(feeds),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("")]
+async fn create_feed(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+    body: Option<web::Json<CreateCalendarFeed>>,
+) -> impl Responder {
+    let user_id = match user_id_for(&user) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let school_id = match school_id_for(&req, &user) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = CalendarFeedService::new(postgres_pool(&state));
+    let dto = body.map(|body| body.into_inner()).unwrap_or_default();
+    match service.create(&user_id, &school_id, dto).await {
+        Ok((feed, token)) => {
+            let url = format!("{}/calendar-feeds/{}/calendar.ics", server_url(&req), token);
+            HttpResponse::Created().json(CalendarFeedCreated { feed, token, url })
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/{id}")]
+async fn revoke_feed(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let user_id = match user_id_for(&user) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let id = match ObjectId::parse_str(path.into_inner()) {
+        Ok(id) => id,
+        Err(_) => {
+            return HttpResponse::BadRequest()
+                .json(serde_json::json!({ "message": "Invalid feed id" }))
+        }
+    };
+
+    let service = CalendarFeedService::new(postgres_pool(&state));
+    match service.revoke(&user_id, &id).await {
+        Ok(feed) => HttpResponse::Ok().json(feed),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Subscription endpoint for calendar apps. The token in the path is the
+/// only credential, so it is not wrapped in the JWT middleware.
+#[get("/calendar-feeds/{token}/calendar.ics")]
+async fn feed_calendar(state: web::Data<AppState>, path: web::Path<String>) -> impl Responder {
+    let service = CalendarFeedService::new(postgres_pool(&state));
+    let feed = match service.resolve(&path.into_inner()).await {
+        Ok(feed) => feed,
+        Err(err) => return err.to_response(),
+    };
+
+    match service.render(&feed).await {
+        Ok(calendar) => HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "text/calendar; charset=utf-8"))
+            .insert_header((header::CACHE_CONTROL, "private, max-age=300"))
+            .body(calendar),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_feeds)
+            .service(create_feed)
+            .service(revoke_feed),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    // Ahead of the JWT scope, which would otherwise claim the path
+    cfg.service(feed_calendar);
+    crate::utils::route_utils::mount_dual_routes(cfg, "calendar-feeds", blueprint);
+}
@@ -5,14 +5,18 @@ use crate::{
     domain::{
         auth_user::AuthUserDto,
         class::{Class, UpdateClass},
+        tenant::TenantEntity,
     },
     errors::AppError,
-    guards::role_guard::check_admin_or_staff,
+    guards::role_guard::{
+        require_entity_permission, require_permission, require_request_permission,
+    },
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         class_service::{ClassQuery, ClassService},
         event_service::EventService,
+        permission_service::PermissionService,
     },
     utils::request_context::{postgres_pool, request_context},
 };
@@ -27,7 +31,7 @@ async fn get_all_classes(
     let context = request_context(&req);
     let class_query = match ClassQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -35,7 +39,7 @@ async fn get_all_classes(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -49,7 +53,7 @@ async fn get_all_classes_with_relations(
     let context = request_context(&req);
     let class_query = match ClassQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -57,7 +61,7 @@ async fn get_all_classes_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -79,7 +83,7 @@ async fn get_class_by_id_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -101,7 +105,7 @@ async fn get_class_by_id(
         .await
     {
         Ok(class) => HttpResponse::Ok().json(class),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -115,12 +119,12 @@ async fn get_class_by_match(
     let context = request_context(&req);
     let class_query = match ClassQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, class_query).await {
         Ok(class) => HttpResponse::Ok().json(class),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -134,12 +138,12 @@ async fn get_class_by_other_match(
     let context = request_context(&req);
     let class_query = match ClassQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one_with_relations(None, class_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -150,29 +154,34 @@ async fn create_class(
     data: web::Json<Class>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
-    }
-
     let service = ClassService::new(postgres_pool(&state));
-    let context = request_context(&req);
     let mut class = data.clone();
 
     if class.creator_id.is_none() {
         let user_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         class.creator_id = Some(user_id);
     }
-    if class.school_id.is_none() {
-        if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
-            class.school_id = match IdType::from_string(school_id).to_object_id() {
-                Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
-            };
-        }
-    }
+
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    let school_id = match class.school_id {
+        Some(school_id) => require_permission(
+            &user,
+            &school_id.to_hex(),
+            "class.manage",
+            &permission_service,
+        )
+        .await
+        .map(|_| school_id.to_hex())
+        .map_err(AppError::forbidden),
+        None => require_request_permission(&req, &user, "class.manage", &permission_service).await,
+    };
+    class.school_id = match school_id.and_then(|id| IdType::from_string(id).to_object_id()) {
+        Ok(id) => Some(id),
+        Err(err) => return err.to_response(),
+    };
 
     match service.create(class).await {
         Ok(class) => {
@@ -193,7 +202,7 @@ async fn create_class(
 
             HttpResponse::Created().json(class)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -205,11 +214,21 @@ async fn update_class(
     data: web::Json<UpdateClass>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
+    let class_id = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) = require_entity_permission(
+        &user,
+        TenantEntity::Class,
+        &class_id,
+        "class.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(class_id);
     let service = ClassService::new(postgres_pool(&state));
 
     match service.update(&id, &data.into_inner()).await {
@@ -232,7 +251,7 @@ async fn update_class(
 
             HttpResponse::Ok().json(class)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -243,11 +262,21 @@ async fn delete_class(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
+    let class_id = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) = require_entity_permission(
+        &user,
+        TenantEntity::Class,
+        &class_id,
+        "class.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(class_id);
     let service = ClassService::new(postgres_pool(&state));
 
     match service.delete(&id).await {
@@ -269,7 +298,7 @@ async fn delete_class(
 
             HttpResponse::Ok().json(class)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -283,7 +312,7 @@ async fn count_classes(
     let context = request_context(&req);
     let class_query = match ClassQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -291,7 +320,7 @@ async fn count_classes(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -302,21 +331,31 @@ async fn create_many_subclasses_by_class_id(
     path: web::Path<(String, String)>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
-    }
-
     let service = ClassService::new(postgres_pool(&state));
     let logged_user = user.into_inner();
     let (main_class_id_str, count) = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) = require_entity_permission(
+        &logged_user,
+        TenantEntity::Class,
+        &main_class_id_str,
+        "class.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
+    }
     let main_class_id = IdType::String(main_class_id_str);
 
     let user_id = match IdType::from_string(&logged_user.id).to_object_id() {
         Ok(i) => i,
         Err(e) => {
-            return HttpResponse::BadRequest().json(AppError {
-                message: format!("field to change user id into object id: {}", e.message),
-            })
+            return AppError::bad_request(format!(
+                "field to change user id into object id: {}",
+                e.message
+            ))
+            .to_response()
         }
     };
 
@@ -353,7 +392,7 @@ async fn create_many_subclasses_by_class_id(
 
             HttpResponse::Created().json(subclasses)
         }
-        Err(error) => HttpResponse::BadRequest().json(error),
+        Err(error) => error.to_response(),
     }
 }
 
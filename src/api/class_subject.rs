@@ -22,7 +22,7 @@ async fn get_all_class_subjects(
     let context = request_context(&req);
     let subject_query = match ClassSubjectQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -30,7 +30,7 @@ async fn get_all_class_subjects(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -44,7 +44,7 @@ async fn get_all_class_subjects_with_others(
     let context = request_context(&req);
     let subject_query = match ClassSubjectQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -52,7 +52,7 @@ async fn get_all_class_subjects_with_others(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -74,7 +74,7 @@ async fn get_class_subject_by_id(
         .await
     {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -88,12 +88,12 @@ async fn get_class_subject_by_match(
     let context = request_context(&req);
     let subject_query = match ClassSubjectQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, subject_query).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -115,7 +115,7 @@ async fn get_class_subject_by_id_others(
         .await
     {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -129,12 +129,12 @@ async fn get_class_subject_by_match_others(
     let context = request_context(&req);
     let subject_query = match ClassSubjectQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one_with_relations(None, subject_query).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -148,7 +148,7 @@ async fn count_subjects(
     let context = request_context(&req);
     let subject_query = match ClassSubjectQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -156,7 +156,7 @@ async fn count_subjects(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -173,7 +173,7 @@ async fn create_class_subject(
         if let Some(school_id) = context.school_id {
             subject.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -198,7 +198,7 @@ async fn create_class_subject(
 
             HttpResponse::Created().json(subject)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -231,7 +231,7 @@ async fn update_class_subject(
 
             HttpResponse::Ok().json(subject)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -263,7 +263,7 @@ async fn delete_class_subject(
 
             HttpResponse::Ok().json(subject)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -1,16 +1,41 @@
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
     domain::{
         auth_user::AuthUserDto,
         class_timetable::{ClassTimetable, ClassTimetablePartial},
+        tenant::TenantEntity,
     },
+    guards::role_guard::require_entity_permission,
     models::{api_request_model::RequestQuery, id_model::IdType},
-    services::{class_timetable_service::ClassTimetableService, event_service::EventService},
-    utils::request_context::postgres_pool,
+    services::{
+        class_timetable_service::ClassTimetableService, event_service::EventService,
+        permission_service::PermissionService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
 };
 
+/// Require the caller may manage timetables of the class
+async fn check_timetable_access(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    class_id: &ObjectId,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_entity_permission(
+        user,
+        TenantEntity::Class,
+        &class_id.to_hex(),
+        "timetable.manage",
+        &permission_service,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| err.to_response())
+}
+
 #[get("")]
 async fn get_all_timetables(
     query: web::Query<RequestQuery>,
@@ -19,7 +44,7 @@ async fn get_all_timetables(
     let service = ClassTimetableService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -32,20 +57,19 @@ async fn get_timetable_by_id(
     let service = ClassTimetableService::new(postgres_pool(&state));
     match service.find_one_by_id(&id).await {
         Ok(timetable) => HttpResponse::Ok().json(timetable),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("")]
 async fn create_timetable(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<ClassTimetable>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
+    if let Err(response) = check_timetable_access(&req, &user, &data.class_id, &state).await {
+        return response;
     }
 
     let service = ClassTimetableService::new(postgres_pool(&state));
@@ -60,25 +84,27 @@ async fn create_timetable(
             });
             HttpResponse::Created().json(timetable)
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
 #[put("/{id}")]
 async fn update_timetable(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     data: web::Json<ClassTimetablePartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
-    }
-
     let id = IdType::from_string(path.into_inner());
     let service = ClassTimetableService::new(postgres_pool(&state));
+    let before = match service.find_one_by_id(&id).await {
+        Ok(timetable) => timetable,
+        Err(err) => return err.to_response(),
+    };
+    if let Err(response) = check_timetable_access(&req, &user, &before.class_id, &state).await {
+        return response;
+    }
     match service.update_timetable(&id, &data.into_inner()).await {
         Ok(timetable) => {
             let timetable_clone = timetable.clone();
@@ -90,40 +116,39 @@ async fn update_timetable(
             });
             HttpResponse::Ok().json(timetable)
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
 #[delete("/{id}")]
 async fn delete_timetable(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
-    }
-
     let id = IdType::from_string(path.into_inner());
     let service = ClassTimetableService::new(postgres_pool(&state));
-    let before_delete = service.find_one_by_id(&id).await.ok();
+    let before = match service.find_one_by_id(&id).await {
+        Ok(timetable) => timetable,
+        Err(err) => return err.to_response(),
+    };
+    if let Err(response) = check_timetable_access(&req, &user, &before.class_id, &state).await {
+        return response;
+    }
 
     match service.delete_timetable(&id).await {
         Ok(_) => {
-            if let Some(timetable) = before_delete {
-                let timetable_clone = timetable.clone();
-                let state_clone = state.clone();
-                actix_rt::spawn(async move {
-                    if let Some(id) = timetable_clone.id {
-                        EventService::broadcast_deleted(&state_clone, "class_timetable", &id.to_hex(), None, &timetable_clone).await;
-                    }
-                });
-            }
+            let timetable_clone = before.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = timetable_clone.id {
+                    EventService::broadcast_deleted(&state_clone, "class_timetable", &id.to_hex(), None, &timetable_clone).await;
+                }
+            });
             HttpResponse::Ok().json(serde_json::json!({ "message": "Class timetable deleted successfully" }))
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -39,7 +39,7 @@ async fn get_all_comments(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -63,7 +63,7 @@ async fn get_all_comments_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -78,7 +78,7 @@ async fn get_comment_by_id(
 
     match service.find_one(Some(&id), None, None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -112,7 +112,7 @@ async fn create_comment(
 
             HttpResponse::Created().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -148,7 +148,7 @@ async fn update_comment(
 
             HttpResponse::Ok().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -183,7 +183,7 @@ async fn delete_comment(
 
             HttpResponse::Ok().json(comment)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -201,7 +201,7 @@ async fn count_comments(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!({ "count": count })),
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
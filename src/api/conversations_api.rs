@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use actix_web::{get, post, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, web, HttpMessage, HttpRequest, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -10,11 +10,14 @@ use crate::{
         common_details::UserRole,
         conversation::{Conversation, ConversationKey},
     },
-    errors::AppError,
+    errors::{AppError, ErrorCode},
     middleware::school_token_middleware::OptionalSchoolTokenMiddleware,
     models::{id_model::IdType, school_token_model::SchoolToken},
     schema::common_schema::ActorRef,
-    services::conversation_service::ConversationService,
+    services::{
+        conversation_hub::WsMessage, conversation_service::ConversationService,
+        event_service::EventService,
+    },
     utils::{object_id::ObjectId, request_context::postgres_pool},
 };
 
@@ -68,11 +71,7 @@ async fn create_conversation(
 
     let auth_user_object_id = match ObjectId::from_str(auth_user_id) {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Invalid user ID format".to_string(),
-            })
-        }
+        Err(_) => return AppError::bad_request("Invalid user ID format").to_response(),
     };
 
     let auth_user_role = auth_user.role.clone().unwrap_or(UserRole::STUDENT);
@@ -96,38 +95,31 @@ async fn create_conversation(
 
     // Validate minimum participants (after potentially adding auth user)
     if participants.len() < 2 {
-        return HttpResponse::BadRequest().json(AppError {
-            message: "Minimum 2 participants required".to_string(),
-        });
+        return AppError::bad_request("Minimum 2 participants required").to_response();
     }
 
     // Validate maximum participants for group conversations
     if participants.len() > 50 {
-        return HttpResponse::BadRequest().json(AppError {
-            message: "Maximum 50 participants allowed for group conversations".to_string(),
-        });
+        return AppError::bad_request("Maximum 50 participants allowed for group conversations")
+            .to_response();
     }
 
     // Validate group conversation name
     if body.is_group {
         if body.name.is_none() || body.name.as_ref().unwrap().trim().is_empty() {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Group name is required for group conversations".to_string(),
-            });
+            return AppError::bad_request("Group name is required for group conversations")
+                .to_response();
         }
         if let Some(ref name) = body.name {
             if name.len() > 100 {
-                return HttpResponse::BadRequest().json(AppError {
-                    message: "Group name must be 1-100 characters".to_string(),
-                });
+                return AppError::bad_request("Group name must be 1-100 characters").to_response();
             }
         }
     } else {
         // For non-group conversations, name must be null
         if body.name.is_some() {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Name must be null for direct conversations".to_string(),
-            });
+            return AppError::bad_request("Name must be null for direct conversations")
+                .to_response();
         }
     }
 
@@ -136,21 +128,18 @@ async fn create_conversation(
     for participant in &participants {
         let key = format!("{}:{:?}", participant.id.to_hex(), participant.role);
         if !unique_participants.insert(key) {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Duplicate participants are not allowed".to_string(),
-            });
+            return AppError::conflict("Duplicate participants are not allowed").to_response();
         }
     }
 
     // Validate encrypted keys match participants
     if encrypted_keys.len() != participants.len() {
-        return HttpResponse::BadRequest().json(AppError {
-            message: format!(
-                "Must provide exactly one encrypted key per participant. Expected {}, got {}",
-                participants.len(),
-                encrypted_keys.len()
-            ),
-        });
+        return AppError::bad_request(format!(
+            "Must provide exactly one encrypted key per participant. Expected {}, got {}",
+            participants.len(),
+            encrypted_keys.len()
+        ))
+        .to_response();
     }
 
     // Validate each encrypted key matches a participant
@@ -160,12 +149,11 @@ async fn create_conversation(
             .find(|p| p.id.to_hex() == key_data.user_id && p.role == key_data.user_role);
 
         if matching_participant.is_none() {
-            return HttpResponse::BadRequest().json(AppError {
-                message: format!(
-                    "Encrypted key for user {} with role {:?} does not match any participant",
-                    key_data.user_id, key_data.user_role
-                ),
-            });
+            return AppError::bad_request(format!(
+                "Encrypted key for user {} with role {:?} does not match any participant",
+                key_data.user_id, key_data.user_role
+            ))
+            .to_response();
         }
 
         // Validate base64 format
@@ -175,9 +163,8 @@ async fn create_conversation(
         )
         .is_err()
         {
-            return HttpResponse::UnprocessableEntity().json(AppError {
-                message: "Invalid encrypted key format. Must be valid base64.".to_string(),
-            });
+            return AppError::bad_request("Invalid encrypted key format. Must be valid base64.")
+                .to_response();
         }
     }
 
@@ -209,7 +196,7 @@ async fn create_conversation(
 
     let created = match service.create(conversation).await {
         Ok(conv) => conv,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let conversation_id = created.id.unwrap();
@@ -219,9 +206,7 @@ async fn create_conversation(
         let user_id = match ObjectId::parse_str(&key_data.user_id) {
             Ok(id) => id,
             Err(_) => {
-                return HttpResponse::BadRequest().json(AppError {
-                    message: "Invalid user ID in encrypted_keys".to_string(),
-                })
+                return AppError::bad_request("Invalid user ID in encrypted_keys").to_response()
             }
         };
 
@@ -235,15 +220,126 @@ async fn create_conversation(
         };
 
         if let Err(err) = service.store_conversation_key(key).await {
-            return HttpResponse::BadRequest().json(err);
+            return err.to_response();
         }
     }
 
+    // Participants learn about the conversation on their event stream, since
+    // they have no socket open for it yet
+    let state_clone = state.clone();
+    let participant_ids: Vec<String> = participants.iter().map(|p| p.id.to_hex()).collect();
+    actix_rt::spawn(async move {
+        let conversation_id = conversation_id.to_hex();
+        let frame = WsMessage::ConversationCreated {
+            conversation_id: conversation_id.clone(),
+        };
+        for user_id in participant_ids {
+            EventService::broadcast_to_user(
+                &state_clone,
+                "conversation_created",
+                "conversation",
+                &conversation_id,
+                &user_id,
+                &frame,
+            )
+            .await;
+        }
+    });
+
     HttpResponse::Created().json(serde_json::json!({
         "conversation": created
     }))
 }
 
+#[derive(Debug, Deserialize)]
+struct AddParticipantRequest {
+    participant: ActorRef,
+    encrypted_key: String,
+}
+
+#[post("/{id}/participants")]
+async fn add_participant(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<AddParticipantRequest>,
+) -> impl Responder {
+    let auth_user = user.into_inner();
+
+    let conversation_id = match ObjectId::parse_str(path.into_inner()) {
+        Ok(id) => id,
+        Err(_) => return AppError::bad_request("Invalid conversation ID").to_response(),
+    };
+
+    let auth_user_id = match ObjectId::parse_str(&auth_user.id) {
+        Ok(id) => id,
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
+    };
+
+    if base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD,
+        &body.encrypted_key,
+    )
+    .is_err()
+    {
+        return AppError::bad_request("Invalid encrypted key format. Must be valid base64.")
+            .to_response();
+    }
+
+    let service = ConversationService::new(postgres_pool(&state));
+
+    match service.is_participant(conversation_id, auth_user_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return AppError::forbidden("You are not a participant in this conversation")
+                .to_response()
+        }
+        Err(err) => return err.to_response(),
+    }
+
+    let added = match service
+        .add_participant(conversation_id, &body.participant, &body.encrypted_key)
+        .await
+    {
+        Ok(added) => added,
+        Err(err) => return err.to_response(),
+    };
+
+    if !added {
+        return AppError::conflict("User is already a participant").to_response();
+    }
+
+    let conversation_id = conversation_id.to_hex();
+    let user_id = body.participant.id.to_hex();
+    let frame = WsMessage::ParticipantAdded {
+        conversation_id: conversation_id.clone(),
+        user_id: user_id.clone(),
+    };
+    state
+        .conversation_hub
+        .publish(&conversation_id, &frame, None)
+        .await;
+
+    let state_clone = state.clone();
+    let event_conversation_id = conversation_id.clone();
+    actix_rt::spawn(async move {
+        EventService::broadcast_to_user(
+            &state_clone,
+            "participant_added",
+            "conversation",
+            &event_conversation_id,
+            &user_id,
+            &frame,
+        )
+        .await;
+    });
+
+    HttpResponse::Created().json(serde_json::json!({
+        "conversation_id": conversation_id,
+        "participant": body.participant
+    }))
+}
+
 #[get("")]
 async fn get_conversations(
     req: HttpRequest,
@@ -262,11 +358,7 @@ async fn get_conversations(
 
     let auth_user_id = match ObjectId::parse_str(&auth_user.id) {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Invalid user ID".to_string(),
-            })
-        }
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
     };
 
     let page = query.page.unwrap_or(1);
@@ -280,7 +372,7 @@ async fn get_conversations(
         .await
     {
         Ok(data) => data,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     HttpResponse::Ok().json(result)
@@ -300,11 +392,7 @@ async fn get_conversation(
 
     let auth_user_id = match ObjectId::parse_str(&auth_user.id) {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Invalid user ID".to_string(),
-            })
-        }
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
     };
 
     let conversation = match service
@@ -313,12 +401,11 @@ async fn get_conversation(
     {
         Ok(conv) => conv,
         Err(err) => {
-            if err.message.contains("not found") {
-                return HttpResponse::Forbidden().json(AppError {
-                    message: "You are not a participant in this conversation".to_string(),
-                });
+            if err.code == ErrorCode::NotFound {
+                return AppError::forbidden("You are not a participant in this conversation")
+                    .to_response();
             }
-            return HttpResponse::NotFound().json(err);
+            return err.to_response();
         }
     };
 
@@ -336,20 +423,12 @@ async fn get_conversation_key(
 
     let conversation_id = match ObjectId::parse_str(&path.into_inner()) {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Invalid conversation ID".to_string(),
-            })
-        }
+        Err(_) => return AppError::bad_request("Invalid conversation ID").to_response(),
     };
 
     let auth_user_id = match ObjectId::parse_str(&auth_user.id) {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(AppError {
-                message: "Invalid user ID".to_string(),
-            })
-        }
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
     };
 
     let service = ConversationService::new(postgres_pool(&state));
@@ -361,18 +440,93 @@ async fn get_conversation_key(
     {
         Ok(k) => k,
         Err(err) => {
-            if err.message.contains("not found") {
-                return HttpResponse::Forbidden().json(AppError {
-                    message: "You are not a participant in this conversation".to_string(),
-                });
+            if err.code == ErrorCode::NotFound {
+                return AppError::forbidden("You are not a participant in this conversation")
+                    .to_response();
             }
-            return HttpResponse::NotFound().json(err);
+            return err.to_response();
         }
     };
 
     HttpResponse::Ok().json(key)
 }
 
+/// Take a participant out of a group conversation, or leave it. Their open
+/// sockets are closed on every node.
+#[delete("/{id}/participants/{participant_id}")]
+async fn remove_participant(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let auth_user = user.into_inner();
+    let (conversation_id, participant_id) = path.into_inner();
+
+    let conversation_id = match ObjectId::parse_str(conversation_id) {
+        Ok(id) => id,
+        Err(_) => return AppError::bad_request("Invalid conversation ID").to_response(),
+    };
+    let participant_id = match ObjectId::parse_str(participant_id) {
+        Ok(id) => id,
+        Err(_) => return AppError::bad_request("Invalid participant ID").to_response(),
+    };
+    let auth_user_id = match ObjectId::parse_str(&auth_user.id) {
+        Ok(id) => id,
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
+    };
+
+    let service = ConversationService::new(postgres_pool(&state));
+
+    match service.is_participant(conversation_id, auth_user_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return AppError::forbidden("You are not a participant in this conversation")
+                .to_response()
+        }
+        Err(err) => return err.to_response(),
+    }
+
+    match service
+        .remove_participant(conversation_id, participant_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return AppError::not_found("Participant not found").to_response(),
+        Err(err) => return err.to_response(),
+    }
+
+    let conversation_id = conversation_id.to_hex();
+    let user_id = participant_id.to_hex();
+    state
+        .conversation_hub
+        .unsubscribe(&conversation_id, &user_id)
+        .await;
+    let frame = WsMessage::ParticipantRemoved {
+        conversation_id: conversation_id.clone(),
+        user_id: user_id.clone(),
+    };
+    state
+        .conversation_hub
+        .publish(&conversation_id, &frame, None)
+        .await;
+
+    let state_clone = state.clone();
+    let event_conversation_id = conversation_id.clone();
+    actix_rt::spawn(async move {
+        EventService::broadcast_to_user(
+            &state_clone,
+            "participant_removed",
+            "conversation",
+            &event_conversation_id,
+            &user_id,
+            &frame,
+        )
+        .await;
+    });
+
+    HttpResponse::NoContent().finish()
+}
+
 fn blueprint(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("")
@@ -381,7 +535,9 @@ fn blueprint(cfg: &mut web::ServiceConfig) {
             .service(create_conversation)
             .service(get_conversations)
             .service(get_conversation)
-            .service(get_conversation_key),
+            .service(get_conversation_key)
+            .service(add_participant)
+            .service(remove_participant),
     );
 }
 
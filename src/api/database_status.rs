@@ -1,7 +1,7 @@
 use actix_web::{get, web, HttpResponse, Responder};
 
 use crate::{
-    config::state::AppState, models::request_error_model::ReqErrModel,
+    config::state::AppState, errors::AppError,
     services::database_status_service::get_postgres_stats, utils::request_context::postgres_pool,
 };
 
@@ -11,7 +11,7 @@ pub async fn db_status(state: web::Data<AppState>) -> impl Responder {
 
     match service {
         Ok(status) => HttpResponse::Ok().json(status),
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -24,7 +24,7 @@ pub async fn school_db_status(
 
     match get_postgres_stats(postgres_pool(&state), Some(&school_id)).await {
         Ok(status) => HttpResponse::Ok().json(status),
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -6,16 +6,29 @@ use crate::{
         auth_user::AuthUserDto,
         education_year::{EducationYear, EducationYearPartial},
     },
-    guards::role_guard::check_admin,
+    guards::role_guard::require_context_permission,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         education_year_service::{EducationYearQuery, EducationYearService},
         event_service::EventService,
+        permission_service::PermissionService,
     },
     utils::request_context::{postgres_pool, request_context},
 };
 
+/// The request's school, once the caller may manage education years there
+async fn manage_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<Option<String>, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_context_permission(req, user, "education_year.manage", &permission_service)
+        .await
+        .map_err(|err| err.to_response())
+}
+
 #[get("")]
 async fn get_all_education_years(
     req: HttpRequest,
@@ -26,7 +39,7 @@ async fn get_all_education_years(
     let context = request_context(&req);
     let year_query = match EducationYearQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -34,7 +47,7 @@ async fn get_all_education_years(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -54,7 +67,7 @@ async fn get_current_education_years(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -68,7 +81,7 @@ async fn get_all_education_years_with_relations(
     let context = request_context(&req);
     let year_query = match EducationYearQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -76,7 +89,7 @@ async fn get_all_education_years_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -98,7 +111,7 @@ async fn get_education_year_by_id(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -120,7 +133,7 @@ async fn get_education_year_by_id_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -134,12 +147,12 @@ async fn get_education_year_by_match(
     let context = request_context(&req);
     let year_query = match EducationYearQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, year_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -153,12 +166,12 @@ async fn get_education_year_by_other_match(
     let context = request_context(&req);
     let year_query = match EducationYearQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one_with_relations(None, year_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -172,7 +185,7 @@ async fn count_education_years(
     let context = request_context(&req);
     let year_query = match EducationYearQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -180,7 +193,7 @@ async fn count_education_years(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -191,28 +204,26 @@ async fn create_education_year(
     data: web::Json<EducationYear>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
-    }
+    let school_id = match manage_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
 
     let service = EducationYearService::new(postgres_pool(&state));
-    let context = request_context(&req);
     let mut education_year = data.into_inner();
 
     if education_year.created_by.is_none() {
         let user_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         education_year.created_by = Some(user_id);
     }
     if education_year.school_id.is_none() {
-        if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
+        if let Some(school_id) = school_id {
             education_year.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -237,7 +248,7 @@ async fn create_education_year(
 
             HttpResponse::Created().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -249,10 +260,8 @@ async fn update_education_year(
     data: web::Json<EducationYearPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+    if let Err(response) = manage_school_id(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -278,7 +287,7 @@ async fn update_education_year(
 
             HttpResponse::Ok().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -289,17 +298,15 @@ async fn delete_education_year(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+    if let Err(response) = manage_school_id(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
     let service = EducationYearService::new(postgres_pool(&state));
     let user_id = match IdType::from_string(&user.id).to_object_id() {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.delete(&id, user_id).await {
@@ -322,21 +329,19 @@ async fn delete_education_year(
 
             HttpResponse::Ok().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("/{id}/restore")]
 async fn restore_education_year(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+    if let Err(response) = manage_school_id(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -344,7 +349,7 @@ async fn restore_education_year(
 
     match service.restore(&id).await {
         Ok(item) => HttpResponse::Ok().json(item),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
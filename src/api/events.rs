@@ -1,11 +1,183 @@
 use actix_web::{get, web, HttpMessage, HttpRequest, HttpResponse, Responder};
 use bytes::Bytes;
-use futures::StreamExt;
+use futures::channel::mpsc;
+use futures::future::{self, Either};
+use futures::{Stream, StreamExt};
+use std::time::Duration;
+use uuid::Uuid;
 
 use crate::config::state::AppState;
 use crate::domain::auth_user::AuthUserDto;
 use crate::models::school_token_model::SchoolToken;
-use crate::services::event_bus::{Event, EVENT_CONNECTED};
+use crate::repositories::notification_repo::NotificationRepo;
+use crate::services::event_bus::{Event, EVENT_CONNECTED, EVENT_RESYNC};
+use crate::services::notification_service::NotificationService;
+use crate::utils::request_context::postgres_pool;
+
+/// Reconnect delay suggested to EventSource clients.
+const SSE_RETRY_MS: u64 = 3000;
+
+/// SSE comment sent after this long without events so proxies keep the
+/// connection open. Overridable with `SSE_HEARTBEAT_SECS`.
+const DEFAULT_HEARTBEAT_SECS: u64 = 15;
+
+const HEARTBEAT_MESSAGE: &str = ": heartbeat\n\n";
+
+fn heartbeat_interval() -> Duration {
+    let secs = std::env::var("SSE_HEARTBEAT_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_HEARTBEAT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// `Last-Event-ID` header sent by EventSource on reconnect, or the
+/// `last_event_id` query parameter for clients that cannot set headers.
+fn last_event_id(req: &HttpRequest) -> Option<u64> {
+    req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            web::Query::<std::collections::HashMap<String, String>>::from_query(req.query_string())
+                .ok()
+                .and_then(|query| query.get("last_event_id").cloned())
+        })
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Unread inbox items, oldest first, formatted as SSE messages so a client
+/// that reconnects catches up on what it missed.
+async fn unread_replay(
+    state: &web::Data<AppState>,
+    user_id: Option<&str>,
+    school_id: Option<&str>,
+) -> Vec<String> {
+    let Some(user_id) = user_id else {
+        return vec![];
+    };
+
+    let repo = NotificationRepo::new(postgres_pool(state));
+    match NotificationService::new(&repo)
+        .replay(user_id, school_id)
+        .await
+    {
+        Ok(notifications) => notifications
+            .into_iter()
+            .map(|notification| {
+                let mut event = Event::new(
+                    &notification.event_type,
+                    &notification.category,
+                    notification.data,
+                )
+                .for_school(notification.school_id.map(|id| id.to_hex()))
+                .with_notification_id(notification.id.map(|id| id.to_hex()));
+                if let Some(entity_id) = &notification.entity_id {
+                    event = event.with_entity_id(entity_id);
+                }
+                if let Some(user_id) = notification.user_id {
+                    event = event.for_user(&user_id.to_hex());
+                }
+                if let Some(created_at) = notification.created_at {
+                    event.timestamp = created_at;
+                }
+                event.to_sse_format()
+            })
+            .collect(),
+        Err(err) => {
+            log::warn!("Failed to load unread notifications: {}", err.message);
+            vec![]
+        }
+    }
+}
+
+/// Messages to send right after connecting, and the last event id among them.
+/// A fresh connection gets the unread inbox. A resuming client gets the
+/// buffered events after its `Last-Event-ID`, preceded by a `resync` notice and
+/// the unread inbox when some of them are no longer buffered.
+async fn catch_up(
+    state: &web::Data<AppState>,
+    session_user_id: &str,
+    account_id: Option<&str>,
+    school_id: Option<&str>,
+    last_event_id: Option<u64>,
+) -> (Vec<String>, Option<u64>) {
+    let Some(last_event_id) = last_event_id else {
+        return (unread_replay(state, account_id, school_id).await, None);
+    };
+
+    let replay = state
+        .event_bus
+        .replay_since(session_user_id, school_id, last_event_id)
+        .await;
+    let mut messages = Vec::new();
+    if !replay.complete {
+        let resync = Event::new(
+            EVENT_RESYNC,
+            "system",
+            serde_json::json!({
+                "message": "Some events are no longer available; refetch current data",
+                "last_event_id": last_event_id
+            }),
+        )
+        .for_school(school_id.map(str::to_string));
+        messages.push(resync.to_sse_format());
+        messages.extend(unread_replay(state, account_id, school_id).await);
+    }
+    messages.extend(replay.events.iter().map(Event::to_sse_format));
+    let replayed_through = replay.events.iter().filter_map(|event| event.id).max();
+    (messages, replayed_through)
+}
+
+fn message_event_id(message: &str) -> Option<u64> {
+    message
+        .strip_prefix("id: ")
+        .and_then(|rest| rest.split('\n').next())
+        .and_then(|id| id.parse().ok())
+}
+
+/// The SSE body: the catch-up messages, then live events with a heartbeat
+/// comment whenever the stream has been idle for a while. Live events already
+/// sent as part of the catch-up (`replayed_through`) are skipped.
+fn event_stream(
+    state: web::Data<AppState>,
+    client_id: Uuid,
+    mut rx: mpsc::UnboundedReceiver<String>,
+    initial_messages: Vec<String>,
+    replayed_through: Option<u64>,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let heartbeat = heartbeat_interval();
+
+    async_stream::stream! {
+        for message in initial_messages {
+            yield Ok::<Bytes, actix_web::Error>(Bytes::from(message));
+        }
+
+        loop {
+            let idle = Box::pin(actix_rt::time::sleep(heartbeat));
+            match future::select(rx.next(), idle).await {
+                Either::Left((Some(message), _)) => {
+                    if let (Some(id), Some(through)) = (message_event_id(&message), replayed_through) {
+                        if id <= through {
+                            continue;
+                        }
+                    }
+                    yield Ok::<Bytes, actix_web::Error>(Bytes::from(message));
+                }
+                Either::Left((None, _)) => break,
+                Either::Right(_) => {
+                    yield Ok::<Bytes, actix_web::Error>(Bytes::from_static(HEARTBEAT_MESSAGE.as_bytes()));
+                }
+            }
+        }
+
+        let event_bus = state.event_bus.clone();
+        actix_web::rt::spawn(async move {
+            event_bus.remove_client(&client_id).await;
+        });
+    }
+}
 
 /// SSE endpoint FOR SCHOOL CONTEXT: /school/events/stream
 #[get("/stream")]
@@ -24,13 +196,15 @@ pub async fn school_events_stream(req: HttpRequest, state: web::Data<AppState>)
     };
 
     let school_id = Some(school_token.id.clone());
+    let school_token_id = school_id.clone();
+    let account_id = school_token.member.as_ref().and_then(|m| m.get_user_id());
     let user_id = school_token
         .member
         .as_ref()
         .and_then(|m| m.get_id())
         .unwrap_or_else(|| "unknown".to_string());
 
-    let (client_id, mut rx) = state
+    let (client_id, rx) = state
         .event_bus
         .register_client(user_id.clone(), school_id.clone())
         .await;
@@ -47,20 +221,27 @@ pub async fn school_events_stream(req: HttpRequest, state: web::Data<AppState>)
     )
     .for_school(school_id);
 
-    let initial_message = connected_event.to_sse_format();
-
-    let stream = async_stream::stream! {
-        yield Ok::<Bytes, actix_web::Error>(Bytes::from(initial_message));
-
-        while let Some(message) = rx.next().await {
-            yield Ok::<Bytes, actix_web::Error>(Bytes::from(message));
-        }
-
-        let event_bus = state.event_bus.clone();
-        actix_web::rt::spawn(async move {
-            event_bus.remove_client(&client_id).await;
-        });
-    };
+    let mut initial_messages = vec![format!(
+        "retry: {}\n{}",
+        SSE_RETRY_MS,
+        connected_event.to_sse_format()
+    )];
+    let (catch_up_messages, replayed_through) = catch_up(
+        &state,
+        &user_id,
+        account_id.as_deref(),
+        school_token_id.as_deref(),
+        last_event_id(&req),
+    )
+    .await;
+    initial_messages.extend(catch_up_messages);
+    let stream = event_stream(
+        state.clone(),
+        client_id,
+        rx,
+        initial_messages,
+        replayed_through,
+    );
 
     HttpResponse::Ok()
         .insert_header(("Content-Type", "text/event-stream"))
@@ -72,7 +253,7 @@ pub async fn school_events_stream(req: HttpRequest, state: web::Data<AppState>)
         .insert_header(("Access-Control-Allow-Credentials", "true"))
         .insert_header((
             "Access-Control-Allow-Headers",
-            "Content-Type, Authorization",
+            "Content-Type, Authorization, Last-Event-ID",
         ))
         .streaming(stream)
 }
@@ -80,14 +261,14 @@ pub async fn school_events_stream(req: HttpRequest, state: web::Data<AppState>)
 /// SSE endpoint FOR NON-SCHOOL CONTEXT: /events/stream
 #[get("/stream")]
 pub async fn global_events_stream(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     state: web::Data<AppState>,
 ) -> impl Responder {
     let logged_user = user.into_inner();
     let user_id = logged_user.id.clone();
 
-    let (client_id, mut rx) = state.event_bus.register_client(user_id.clone(), None).await;
+    let (client_id, rx) = state.event_bus.register_client(user_id.clone(), None).await;
 
     let connected_event = Event::new(
         EVENT_CONNECTED,
@@ -100,20 +281,21 @@ pub async fn global_events_stream(
     )
     .for_school(None);
 
-    let initial_message = connected_event.to_sse_format();
-
-    let stream = async_stream::stream! {
-        yield Ok::<Bytes, actix_web::Error>(Bytes::from(initial_message));
-
-        while let Some(message) = rx.next().await {
-            yield Ok::<Bytes, actix_web::Error>(Bytes::from(message));
-        }
-
-        let event_bus = state.event_bus.clone();
-        actix_web::rt::spawn(async move {
-            event_bus.remove_client(&client_id).await;
-        });
-    };
+    let mut initial_messages = vec![format!(
+        "retry: {}\n{}",
+        SSE_RETRY_MS,
+        connected_event.to_sse_format()
+    )];
+    let (catch_up_messages, replayed_through) =
+        catch_up(&state, &user_id, Some(&user_id), None, last_event_id(&req)).await;
+    initial_messages.extend(catch_up_messages);
+    let stream = event_stream(
+        state.clone(),
+        client_id,
+        rx,
+        initial_messages,
+        replayed_through,
+    );
 
     HttpResponse::Ok()
         .insert_header(("Content-Type", "text/event-stream"))
@@ -124,7 +306,7 @@ pub async fn global_events_stream(
         .insert_header(("Access-Control-Allow-Credentials", "true"))
         .insert_header((
             "Access-Control-Allow-Headers",
-            "Content-Type, Authorization",
+            "Content-Type, Authorization, Last-Event-ID",
         ))
         .streaming(stream)
 }
@@ -5,13 +5,18 @@ use crate::{
     domain::{
         auth_user::AuthUserDto,
         exam::{Exam, ExamPartial},
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    guards::role_guard::{
+        require_entity_permission, require_permission, require_request_permission,
     },
-    guards::role_guard::check_admin_staff_or_teacher,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         event_service::EventService,
         exam_service::{ExamQuery, ExamService},
+        permission_service::PermissionService,
     },
     utils::request_context::{postgres_pool, request_context},
 };
@@ -26,7 +31,7 @@ async fn get_all_exams(
     let context = request_context(&req);
     let exam_query = match ExamQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -34,7 +39,7 @@ async fn get_all_exams(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -53,7 +58,7 @@ async fn get_exam_by_id(
         .await
     {
         Ok(exam) => HttpResponse::Ok().json(exam),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -64,29 +69,34 @@ async fn create_exam(
     data: web::Json<Exam>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
-    }
-
     let service = ExamService::new(postgres_pool(&state));
-    let context = request_context(&req);
     let mut exam = data.clone();
 
     if exam.created_by.is_none() {
         let user_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         exam.created_by = Some(user_id);
     }
-    if exam.school_id.is_none() {
-        if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
-            exam.school_id = match IdType::from_string(school_id).to_object_id() {
-                Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
-            };
-        }
-    }
+
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    let school_id = match exam.school_id {
+        Some(school_id) => require_permission(
+            &user,
+            &school_id.to_hex(),
+            "exam.manage",
+            &permission_service,
+        )
+        .await
+        .map(|_| school_id.to_hex())
+        .map_err(AppError::forbidden),
+        None => require_request_permission(&req, &user, "exam.manage", &permission_service).await,
+    };
+    exam.school_id = match school_id.and_then(|id| IdType::from_string(id).to_object_id()) {
+        Ok(id) => Some(id),
+        Err(err) => return err.to_response(),
+    };
 
     match service.create(exam).await {
         Ok(exam) => {
@@ -107,7 +117,7 @@ async fn create_exam(
 
             HttpResponse::Created().json(exam)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -119,11 +129,21 @@ async fn update_exam(
     data: web::Json<ExamPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
+    let exam_id = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) = require_entity_permission(
+        &user,
+        TenantEntity::Exam,
+        &exam_id,
+        "exam.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(exam_id);
     let service = ExamService::new(postgres_pool(&state));
 
     match service.update(&id, &data.into_inner()).await {
@@ -145,7 +165,7 @@ async fn update_exam(
 
             HttpResponse::Ok().json(exam)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -156,11 +176,21 @@ async fn delete_exam(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err }));
+    let exam_id = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) = require_entity_permission(
+        &user,
+        TenantEntity::Exam,
+        &exam_id,
+        "exam.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(exam_id);
     let service = ExamService::new(postgres_pool(&state));
 
     match service.delete(&id).await {
@@ -182,18 +212,32 @@ async fn delete_exam(
 
             HttpResponse::Ok().json(exam)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("/{id}/publish")]
 async fn publish_exam(
     req: HttpRequest,
-    _user: web::ReqData<AuthUserDto>,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let id = IdType::from_string(path.into_inner());
+    let exam_id = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) = require_entity_permission(
+        &user,
+        TenantEntity::Exam,
+        &exam_id,
+        "exam.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
+    }
+
+    let id = IdType::from_string(exam_id);
     let service = ExamService::new(postgres_pool(&state));
 
     match service.publish(&id).await {
@@ -215,7 +259,7 @@ async fn publish_exam(
 
             HttpResponse::Ok().json(exam)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -229,12 +273,12 @@ async fn count_exams(
     let context = request_context(&req);
     let exam_query = match ExamQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.count_exams(query.filter.clone(), exam_query).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
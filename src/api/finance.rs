@@ -0,0 +1,545 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        auth_user::AuthUserDto,
+        finance::{
+            AdjustmentRequest, FeeStructure, FeeStructurePartial, FinanceQuery,
+            GenerateInvoicesRequest, RecordPaymentRequest, RefundRequest,
+        },
+    },
+    errors::AppError,
+    guards::role_guard::{
+        check_student_access, require_permission, require_request_permission,
+        require_request_school,
+    },
+    models::id_model::IdType,
+    repositories::finance_repo::FinanceRepo,
+    services::{
+        event_service::EventService, finance_service::FinanceService,
+        permission_service::PermissionService, tenant_service::TenantService,
+    },
+    utils::request_context::postgres_pool,
+};
+
+async fn school_id_for(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<String, HttpResponse> {
+    let tenant_service = TenantService::new(postgres_pool(state));
+    require_request_school(req, user, &tenant_service)
+        .await
+        .map_err(|err| err.to_response())
+}
+
+/// The request's school, once the caller holds the permission there
+async fn permitted_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    permission: &str,
+    state: &web::Data<AppState>,
+) -> Result<String, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_request_permission(req, user, permission, &permission_service)
+        .await
+        .map_err(|err| err.to_response())
+}
+
+// ========== FEE STRUCTURES ==========
+
+#[get("/fee-structures")]
+async fn get_fee_structures(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<FinanceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.read", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service.get_structures(&school_id, &query).await {
+        Ok(structures) => HttpResponse::Ok().json(structures),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/fee-structures")]
+async fn create_fee_structure(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<FeeStructure>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service
+        .create_structure(&school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(structure) => {
+            let structure_clone = structure.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = structure_clone.id {
+                    EventService::broadcast_created(
+                        &state_clone,
+                        "fee_structure",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &structure_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Created().json(structure)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/fee-structures/{id}")]
+async fn get_fee_structure(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.read", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service.find_structure(&id, &school_id).await {
+        Ok(structure) => HttpResponse::Ok().json(structure),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/fee-structures/{id}")]
+async fn update_fee_structure(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<FeeStructurePartial>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service
+        .update_structure(&id, &school_id, data.into_inner())
+        .await
+    {
+        Ok(structure) => {
+            let structure_clone = structure.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = structure_clone.id {
+                    EventService::broadcast_updated(
+                        &state_clone,
+                        "fee_structure",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &structure_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Ok().json(structure)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/fee-structures/{id}")]
+async fn delete_fee_structure(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service.delete_structure(&id, &school_id).await {
+        Ok(structure) => {
+            let structure_clone = structure.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = structure_clone.id {
+                    EventService::broadcast_deleted(
+                        &state_clone,
+                        "fee_structure",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &structure_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Ok().json(structure)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/fee-structures/{id}/invoices")]
+async fn generate_invoices(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<GenerateInvoicesRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service
+        .generate_invoices(&id, &school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(result) => {
+            let invoices = result.invoices.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                for invoice in invoices {
+                    if let Some(id) = invoice.id {
+                        EventService::broadcast_created(
+                            &state_clone,
+                            "invoice",
+                            &id.to_hex(),
+                            Some(school_id.clone()),
+                            &invoice,
+                        )
+                        .await;
+                    }
+                }
+            });
+
+            HttpResponse::Created().json(result)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+// ========== LEDGER ==========
+
+#[get("/records")]
+async fn get_finance_records(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<FinanceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.read", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service.get_records(&school_id, &query).await {
+        Ok(records) => HttpResponse::Ok().json(records),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/invoices/{id}")]
+async fn get_invoice(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    let invoice = match service.get_invoice(&id, &school_id).await {
+        Ok(invoice) => invoice,
+        Err(err) => return err.to_response(),
+    };
+
+    let student_id = invoice
+        .invoice
+        .student_id
+        .map(|id| id.to_hex())
+        .unwrap_or_default();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if require_permission(&user, &school_id, "finance.read", &permission_service)
+        .await
+        .is_err()
+    {
+        let tenant_service = TenantService::new(postgres_pool(&state));
+        if let Err(err) = check_student_access(&user, &student_id, &tenant_service).await {
+            return AppError::forbidden(err).to_response();
+        }
+    }
+
+    HttpResponse::Ok().json(invoice)
+}
+
+#[post("/payments")]
+async fn record_payment(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<RecordPaymentRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service
+        .record_payment(&school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(receipt) => {
+            let payment = receipt.payment.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = payment.id {
+                    EventService::broadcast_created(
+                        &state_clone,
+                        "payment",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &payment,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Created().json(receipt)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/payments/{id}/receipt")]
+async fn get_payment_receipt(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    let receipt = match service.get_receipt(&id, &school_id).await {
+        Ok(receipt) => receipt,
+        Err(err) => return err.to_response(),
+    };
+
+    let student_id = receipt
+        .payment
+        .student_id
+        .map(|id| id.to_hex())
+        .unwrap_or_default();
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if require_permission(&user, &school_id, "finance.read", &permission_service)
+        .await
+        .is_err()
+    {
+        let tenant_service = TenantService::new(postgres_pool(&state));
+        if let Err(err) = check_student_access(&user, &student_id, &tenant_service).await {
+            return AppError::forbidden(err).to_response();
+        }
+    }
+
+    HttpResponse::Ok().json(receipt)
+}
+
+#[post("/payments/{id}/refunds")]
+async fn refund_payment(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<RefundRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service
+        .refund_payment(&id, &school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(refund) => {
+            let refund_clone = refund.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = refund_clone.id {
+                    EventService::broadcast_created(
+                        &state_clone,
+                        "refund",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &refund_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Created().json(refund)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/adjustments")]
+async fn adjust_invoice(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<AdjustmentRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match permitted_school_id(&req, &user, "finance.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service
+        .adjust_invoice(&school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(invoice) => {
+            let invoice_clone = invoice.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = invoice_clone.invoice.id {
+                    EventService::broadcast_updated(
+                        &state_clone,
+                        "invoice",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &invoice_clone,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Ok().json(invoice)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/students/{student_id}/statement")]
+async fn get_student_statement(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let student_id = path.into_inner();
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if require_permission(&user, &school_id, "finance.read", &permission_service)
+        .await
+        .is_err()
+    {
+        let tenant_service = TenantService::new(postgres_pool(&state));
+        if let Err(err) = check_student_access(&user, &student_id, &tenant_service).await {
+            return AppError::forbidden(err).to_response();
+        }
+    }
+
+    let repo = FinanceRepo::new(postgres_pool(&state));
+    let service = FinanceService::new(&repo);
+
+    match service.student_statement(&school_id, &student_id).await {
+        Ok(statement) => HttpResponse::Ok().json(statement),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_fee_structures)
+            .service(create_fee_structure)
+            .service(generate_invoices)
+            .service(get_fee_structure)
+            .service(update_fee_structure)
+            .service(delete_fee_structure)
+            .service(get_finance_records)
+            .service(get_invoice)
+            .service(record_payment)
+            .service(get_payment_receipt)
+            .service(refund_payment)
+            .service(adjust_invoice)
+            .service(get_student_statement),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "finance", blueprint);
+}
@@ -28,7 +28,7 @@ async fn get_all_grading_scales(
     let context = request_context(&req);
     let scale_query = match GradingScaleQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -36,7 +36,7 @@ async fn get_all_grading_scales(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -58,7 +58,7 @@ async fn get_grading_scale_by_id(
         .await
     {
         Ok(scale) => HttpResponse::Ok().json(scale),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -77,7 +77,7 @@ async fn create_grading_scale(
     if scale.created_by.is_none() {
         let user_id = match parse_object_id_value(&user.id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         scale.created_by = Some(user_id);
     }
@@ -85,7 +85,7 @@ async fn create_grading_scale(
         if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
             scale.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -109,7 +109,7 @@ async fn create_grading_scale(
 
             HttpResponse::Created().json(scale)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -143,7 +143,7 @@ async fn update_grading_scale(
 
             HttpResponse::Ok().json(scale)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -176,7 +176,7 @@ async fn activate_grading_scale(
 
             HttpResponse::Ok().json(scale)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
@@ -8,10 +8,12 @@ use crate::{
         auth_user::AuthUserDto,
         join_school_request::{CreateJoinSchoolRequest, JoinSchoolByCode},
     },
+    errors::AppError,
     guards::role_guard,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         event_service::EventService, join_school_request_service::JoinSchoolRequestService,
+        permission_service::PermissionService,
     },
     utils::{object_id::ObjectId, request_context::postgres_pool},
 };
@@ -24,7 +26,7 @@ async fn get_all_join_requests(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip, query.school_id.as_deref()).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -37,7 +39,7 @@ async fn get_join_request_by_id(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.find_one(Some(&id), None).await {
         Ok(req) => HttpResponse::Ok().json(req),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -49,7 +51,7 @@ async fn get_join_requests_with_relations(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.get_all_with_relations(query.filter.clone(), query.limit, query.skip, query.school_id.as_deref()).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -71,7 +73,7 @@ async fn create_join_school(
             });
             HttpResponse::Ok().json(join_school)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -91,7 +93,7 @@ async fn join_school_by_code(
             });
             HttpResponse::Ok().json(token)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -106,13 +108,13 @@ async fn accept_join_request(
 
     let invited_user_id = match ObjectId::from_str(&logged_user.id) {
         Ok(oid) => oid,
-        Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid user ID" })),
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
     };
 
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.accept_request(&id, invited_user_id, Some(invited_user_id), state.clone(), &logged_user).await {
         Ok(token) => HttpResponse::Ok().json(token),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -128,7 +130,7 @@ async fn reject_join_request(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.reject_request(&id, responded_by).await {
         Ok(req) => HttpResponse::Ok().json(req),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -144,7 +146,7 @@ async fn cancel_join_request(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.cancel_request(&id, responded_by).await {
         Ok(req) => HttpResponse::Ok().json(req),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -156,7 +158,7 @@ async fn count_join_requests(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.count(query.filter.clone(), query.school_id.as_deref()).await {
         Ok(count) => HttpResponse::Ok().json(count),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -168,7 +170,7 @@ async fn get_join_request_by_match(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.find_one(None, query.school_id.as_deref()).await {
         Ok(school) => HttpResponse::Ok().json(school),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -181,7 +183,7 @@ async fn get_join_request_by_id_with_relations(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.find_one_with_relations(Some(&id), None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -193,24 +195,28 @@ async fn get_join_request_by_other_match(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.find_one_with_relations(None, query.school_id.as_deref()).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[delete("/admin/cleanup-expired/{older_than_days}")]
 async fn cleanup_expired_join_requests(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<i64>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-    if let Err(err) = role_guard::check_admin_staff_or_teacher(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err) =
+        role_guard::check_permission(&user, None, "join_request.cleanup", &permission_service)
+            .await
+    {
+        return AppError::forbidden(err).to_response();
     }
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.cleanup_expired_requests(path.into_inner()).await {
         Ok(response) => HttpResponse::Ok().json(response),
-        Err(error) => HttpResponse::BadRequest().json(error),
+        Err(error) => error.to_response(),
     }
 }
 
@@ -223,7 +229,7 @@ async fn get_my_pending_join_requests(
     let service = JoinSchoolRequestService::new(postgres_pool(&state));
     match service.get_my_pending_request(&logged_user.email).await {
         Ok(requests) => HttpResponse::Ok().json(requests),
-        Err(e) => HttpResponse::BadRequest().json(e),
+        Err(e) => e.to_response(),
     }
 }
 
@@ -9,10 +9,14 @@ use crate::{
         common_details::UserRole,
         learning_material::{LearningMaterial, LearningMaterialPartial},
     },
-    guards::role_guard::check_admin_staff_or_teacher,
+    errors::AppError,
+    guards::role_guard::require_context_permission,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
-    services::{event_service::EventService, learning_material_service::LearningMaterialService},
+    services::{
+        event_service::EventService, learning_material_service::LearningMaterialService,
+        permission_service::PermissionService,
+    },
     utils::{
         object_id::parse_object_id_value,
         request_context::{postgres_pool, request_context},
@@ -26,6 +30,19 @@ fn scoped_school_id(req: &HttpRequest, query: &RequestQuery) -> Option<String> {
         .or_else(|| request_context(req).school_id)
 }
 
+/// Require the caller may manage learning materials in the request's school
+async fn check_manage_access(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_context_permission(req, user, "material.manage", &permission_service)
+        .await
+        .map(|_| ())
+        .map_err(|err| err.to_response())
+}
+
 fn only_published_for(user: &AuthUserDto) -> bool {
     matches!(user.role, Some(UserRole::STUDENT) | Some(UserRole::PARENT))
 }
@@ -52,7 +69,7 @@ async fn get_all_materials(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -78,7 +95,7 @@ async fn get_all_materials_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -95,12 +112,11 @@ async fn get_material_by_id(
     match service.find_one(Some(&id), None, None, false).await {
         Ok(material) => {
             if !material.is_published && only_published_for(&user) {
-                return HttpResponse::Forbidden()
-                    .json(serde_json::json!({"message": "Access denied"}));
+                return AppError::forbidden("Access denied").to_response();
             }
             HttpResponse::Ok().json(material)
         }
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -120,12 +136,11 @@ async fn get_material_by_id_with_relations(
     {
         Ok(data) => {
             if !data.learning_material.is_published && only_published_for(&user) {
-                return HttpResponse::Forbidden()
-                    .json(serde_json::json!({"message": "Access denied"}));
+                return AppError::forbidden("Access denied").to_response();
             }
             HttpResponse::Ok().json(data)
         }
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -136,8 +151,8 @@ async fn create_material(
     mut payload: Multipart,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({"message": err}));
+    if let Err(response) = check_manage_access(&req, &user, &state).await {
+        return response;
     }
 
     let service = LearningMaterialService::new(postgres_pool(&state));
@@ -213,7 +228,7 @@ async fn create_material(
 
     let user_id = match parse_object_id_value(&user.id) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
     material.uploaded_by = Some(user_id);
 
@@ -238,7 +253,7 @@ async fn create_material(
             });
             HttpResponse::Created().json(created)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -250,8 +265,8 @@ async fn update_material(
     mut payload: Multipart,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({"message": err}));
+    if let Err(response) = check_manage_access(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -347,7 +362,7 @@ async fn update_material(
             });
             HttpResponse::Ok().json(updated)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -358,8 +373,8 @@ async fn delete_material(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({"message": err}));
+    if let Err(response) = check_manage_access(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -383,7 +398,7 @@ async fn delete_material(
             });
             HttpResponse::Ok().json(material)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -407,7 +422,7 @@ async fn count_materials(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!({ "count": count })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
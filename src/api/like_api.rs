@@ -39,7 +39,7 @@ async fn get_all_likes(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -57,7 +57,7 @@ async fn get_all_likes_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -72,7 +72,7 @@ async fn get_like_by_id(
 
     match service.find_one(Some(&id), None, None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -90,7 +90,7 @@ async fn get_like_by_match(
         .await
     {
         Ok(like) => HttpResponse::Ok().json(like),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -108,7 +108,7 @@ async fn get_like_by_other_match(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -123,7 +123,7 @@ async fn get_like_by_id_with_relations(
 
     match service.find_one_with_relations(Some(&id), None, None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -158,7 +158,7 @@ async fn create_like(
 
             HttpResponse::Created().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -194,7 +194,7 @@ async fn update_like(
 
             HttpResponse::Ok().json(item)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -229,7 +229,7 @@ async fn delete_like(
 
             HttpResponse::Ok().json(like)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -247,7 +247,7 @@ async fn count_likes(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!({ "count": count })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -15,7 +15,7 @@ async fn get_provinces(
     let service = LocationService::new(postgres_pool(&state));
     match service.provinces(query.country.as_deref()).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -30,7 +30,7 @@ async fn get_districts(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -42,7 +42,7 @@ async fn get_sectors(query: web::Query<SectorQuery>, state: web::Data<AppState>)
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -59,7 +59,7 @@ async fn get_cells(query: web::Query<CellQuery>, state: web::Data<AppState>) ->
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -80,7 +80,7 @@ async fn get_villages(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -19,7 +19,7 @@ async fn get_all_main_classes(
     let service = MainClassService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip, Some(&query)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -34,7 +34,7 @@ async fn get_all_main_classes_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -46,7 +46,7 @@ async fn get_all_main_classes_by_other_match(
     let service = MainClassService::new(postgres_pool(&state));
     match service.find_one_with_relations(None, Some(&query)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -59,7 +59,7 @@ async fn get_main_class_by_id(
     let service = MainClassService::new(postgres_pool(&state));
     match service.find_one(Some(&id), None).await {
         Ok(main_class) => HttpResponse::Ok().json(main_class),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -72,7 +72,7 @@ async fn get_main_class_by_id_with_relations(
     let service = MainClassService::new(postgres_pool(&state));
     match service.find_one_with_relations(Some(&id), None).await {
         Ok(main_class) => HttpResponse::Ok().json(main_class),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -84,7 +84,7 @@ async fn get_main_class_by_match(
     let service = MainClassService::new(postgres_pool(&state));
     match service.find_one(None, Some(&query)).await {
         Ok(main_class) => HttpResponse::Ok().json(main_class),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -108,7 +108,7 @@ async fn create_main_class(
             });
             HttpResponse::Created().json(main_class)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -134,7 +134,7 @@ async fn update_main_class(
             });
             HttpResponse::Ok().json(main_class)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -159,7 +159,7 @@ async fn delete_main_class(
             });
             HttpResponse::Ok().json(main_class)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -171,7 +171,7 @@ async fn count_main_classes(
     let service = MainClassService::new(postgres_pool(&state));
     match service.count(query.filter.clone(), Some(&query)).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
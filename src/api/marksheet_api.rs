@@ -0,0 +1,207 @@
+use actix_multipart::Multipart;
+use actix_web::{get, http::header, post, web, HttpRequest, HttpResponse, Responder};
+use futures::StreamExt;
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        auth_user::AuthUserDto,
+        marksheet::{MarksheetTemplateQuery, MarksheetUpload},
+    },
+    guards::role_guard::require_request_school,
+    services::{
+        audit_log_service::AuditLogService, marksheet_service::MarksheetService,
+        tenant_service::TenantService,
+    },
+    utils::{
+        object_id::ObjectId,
+        request_context::postgres_pool,
+        spreadsheet::{write_csv, write_xlsx},
+    },
+};
+
+/// Uploads larger than this are refused before parsing
+const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+async fn school_id_for(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<ObjectId, HttpResponse> {
+    let tenant_service = TenantService::new(postgres_pool(state));
+    let school_id = require_request_school(req, user, &tenant_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    ObjectId::parse_str(&school_id).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid school id" }))
+    })
+}
+
+/// File-name safe form of the sheet label
+fn file_stem(label: &str) -> String {
+    let stem: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let stem = stem
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if stem.is_empty() {
+        "marksheet".to_string()
+    } else {
+        format!("marksheet-{}", stem.to_lowercase())
+    }
+}
+
+/// The class roster with existing marks, to fill in offline
+#[get("/template")]
+async fn download_template(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<MarksheetTemplateQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let query = query.into_inner();
+    let format = query.format.as_deref().unwrap_or("xlsx").to_lowercase();
+    if format != "csv" && format != "xlsx" {
+        return HttpResponse::BadRequest()
+            .json(serde_json::json!({ "message": "Format must be csv or xlsx" }));
+    }
+
+    let service = MarksheetService::new(postgres_pool(&state));
+    let (label, table) = match service
+        .template(&school_id, &user, &query.target(), query.max_score)
+        .await
+    {
+        Ok(template) => template,
+        Err(err) => return err.to_response(),
+    };
+
+    let stem = file_stem(&label);
+    let (content_type, body) = if format == "csv" {
+        ("text/csv; charset=utf-8", write_csv(&table).into_bytes())
+    } else {
+        // Score and max_score cells stay numeric
+        (XLSX_CONTENT_TYPE, write_xlsx(&label, &table, &[3, 4]))
+    };
+    HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, content_type))
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", stem, format),
+        ))
+        .body(body)
+}
+
+/// Multipart upload with a `file` part (the filled marksheet) and a `data`
+/// part holding the JSON upload options. Dry runs return the preview only.
+#[post("")]
+async fn upload_marksheet(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    mut payload: Multipart,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let mut options: Option<MarksheetUpload> = None;
+    let mut file_bytes: Option<Vec<u8>> = None;
+
+    while let Some(item) = payload.next().await {
+        let mut field = match item {
+            Ok(f) => f,
+            Err(e) => {
+                return HttpResponse::BadRequest()
+                    .json(serde_json::json!({"message": format!("Multipart error: {}", e)}))
+            }
+        };
+
+        let field_name = field.name().map(str::to_string);
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let data = match chunk {
+                Ok(d) => d,
+                Err(e) => {
+                    return HttpResponse::BadRequest()
+                        .json(serde_json::json!({"message": format!("Read error: {}", e)}))
+                }
+            };
+            if bytes.len() + data.len() > MAX_UPLOAD_BYTES {
+                return HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                    "message": "Marksheets are limited to 5 MB"
+                }));
+            }
+            bytes.extend_from_slice(&data);
+        }
+
+        match field_name.as_deref() {
+            Some("data") => {
+                options = match serde_json::from_slice(&bytes) {
+                    Ok(o) => Some(o),
+                    Err(e) => {
+                        return HttpResponse::BadRequest()
+                            .json(serde_json::json!({"message": format!("JSON error: {}", e)}))
+                    }
+                }
+            }
+            Some("file") => file_bytes = Some(bytes),
+            _ => {}
+        }
+    }
+
+    let (Some(options), Some(file_bytes)) = (options, file_bytes) else {
+        return HttpResponse::BadRequest()
+            .json(serde_json::json!({"message": "Both the data and file parts are required"}));
+    };
+
+    let service = MarksheetService::new(postgres_pool(&state));
+    let dry_run = options.dry_run;
+    match service
+        .upload(&school_id, &user, options, &file_bytes)
+        .await
+    {
+        Ok(preview) if preview.committed => {
+            AuditLogService::new(postgres_pool(&state))
+                .log_event(
+                    school_id,
+                    &user,
+                    "marksheet.upload",
+                    "class_subject",
+                    preview.class_subject_id,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .ok();
+            HttpResponse::Ok().json(preview)
+        }
+        // Nothing was written: either a dry run or invalid rows blocked it
+        Ok(preview) if dry_run => HttpResponse::Ok().json(preview),
+        Ok(preview) => HttpResponse::UnprocessableEntity().json(preview),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(download_template)
+            .service(upload_marksheet),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "marksheets", blueprint);
+}
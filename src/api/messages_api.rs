@@ -11,7 +11,10 @@ use crate::{
     middleware::school_token_middleware::OptionalSchoolTokenMiddleware,
     models::id_model::IdType,
     schema::common_schema::ActorRef,
-    services::{conversation_service::ConversationService, message_service::MessageService},
+    services::{
+        conversation_hub::WsMessage, conversation_service::ConversationService,
+        message_service::MessageService,
+    },
     utils::{object_id::ObjectId, request_context::postgres_pool},
 };
 
@@ -43,20 +46,16 @@ async fn create_message(
         .extensions()
         .get::<crate::domain::auth_user::AuthUserDto>()
         .cloned()
-        .ok_or_else(|| AppError {
-            message: "User not authenticated".to_string(),
-        })?;
+        .ok_or_else(|| AppError::unauthorized("User not authenticated"))?;
 
-    let conversation_id = ObjectId::parse_str(&path.into_inner()).map_err(|_| AppError {
-        message: "Invalid conversation ID".to_string(),
-    })?;
+    let conversation_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid conversation ID"))?;
 
     let conv_service = ConversationService::new(postgres_pool(&state));
     let msg_service = MessageService::new(postgres_pool(&state));
 
-    let auth_user_id = ObjectId::parse_str(&auth_user.id).map_err(|_| AppError {
-        message: "Invalid user ID".to_string(),
-    })?;
+    let auth_user_id =
+        ObjectId::parse_str(&auth_user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
 
     let auth_user_role = auth_user
         .role
@@ -67,9 +66,7 @@ async fn create_message(
     let conversation = conv_service
         .find_one(Some(&IdType::ObjectId(conversation_id)), Some(auth_user_id))
         .await
-        .map_err(|_| AppError {
-            message: "You are not a participant in this conversation".to_string(),
-        })?;
+        .map_err(|_| AppError::forbidden("You are not a participant in this conversation"))?;
 
     // Ensure message school_id matches conversation school_id
     let message_school_id = conversation.school_id;
@@ -96,6 +93,22 @@ async fn create_message(
 
     let created = msg_service.create(message).await?;
 
+    if let Some(message_id) = created.id {
+        let conversation_id = conversation_id.to_hex();
+        state
+            .conversation_hub
+            .publish(
+                &conversation_id,
+                &WsMessage::MessageCreated {
+                    conversation_id: conversation_id.clone(),
+                    message_id: message_id.to_hex(),
+                    data: Box::new(created.clone()),
+                },
+                None,
+            )
+            .await;
+    }
+
     Ok(HttpResponse::Created().json(created))
 }
 
@@ -110,13 +123,10 @@ async fn get_messages(
         .extensions()
         .get::<crate::domain::auth_user::AuthUserDto>()
         .cloned()
-        .ok_or_else(|| AppError {
-            message: "User not authenticated".to_string(),
-        })?;
+        .ok_or_else(|| AppError::unauthorized("User not authenticated"))?;
 
-    let conversation_id = ObjectId::parse_str(&path.into_inner()).map_err(|_| AppError {
-        message: "Invalid conversation ID".to_string(),
-    })?;
+    let conversation_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid conversation ID"))?;
 
     let page = query.page.unwrap_or(1);
     let limit = query.limit.unwrap_or(50).min(100);
@@ -124,17 +134,14 @@ async fn get_messages(
     let conv_service = ConversationService::new(postgres_pool(&state));
     let msg_service = MessageService::new(postgres_pool(&state));
 
-    let auth_user_id = ObjectId::parse_str(&auth_user.id).map_err(|_| AppError {
-        message: "Invalid user ID".to_string(),
-    })?;
+    let auth_user_id =
+        ObjectId::parse_str(&auth_user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
 
     // Verify user is participant (single query)
     conv_service
         .find_one(Some(&IdType::ObjectId(conversation_id)), Some(auth_user_id))
         .await
-        .map_err(|_| AppError {
-            message: "You are not a participant in this conversation".to_string(),
-        })?;
+        .map_err(|_| AppError::forbidden("You are not a participant in this conversation"))?;
 
     let (messages, total) = msg_service
         .get_conversation_messages_with_relations(conversation_id, page, limit)
@@ -163,13 +170,10 @@ async fn get_files(
         .extensions()
         .get::<crate::domain::auth_user::AuthUserDto>()
         .cloned()
-        .ok_or_else(|| AppError {
-            message: "User not authenticated".to_string(),
-        })?;
+        .ok_or_else(|| AppError::unauthorized("User not authenticated"))?;
 
-    let conversation_id = ObjectId::parse_str(&path.into_inner()).map_err(|_| AppError {
-        message: "Invalid conversation ID".to_string(),
-    })?;
+    let conversation_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid conversation ID"))?;
 
     let page = query.page.unwrap_or(1);
     let limit = query.limit.unwrap_or(20);
@@ -177,17 +181,14 @@ async fn get_files(
     let conv_service = ConversationService::new(postgres_pool(&state));
     let msg_service = MessageService::new(postgres_pool(&state));
 
-    let auth_user_id = ObjectId::parse_str(&auth_user.id).map_err(|_| AppError {
-        message: "Invalid user ID".to_string(),
-    })?;
+    let auth_user_id =
+        ObjectId::parse_str(&auth_user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
 
     // Verify user is participant (single query)
     conv_service
         .find_one(Some(&IdType::ObjectId(conversation_id)), Some(auth_user_id))
         .await
-        .map_err(|_| AppError {
-            message: "You are not a participant in this conversation".to_string(),
-        })?;
+        .map_err(|_| AppError::forbidden("You are not a participant in this conversation"))?;
 
     let (messages, total) = msg_service
         .get_conversation_files(conversation_id, page, limit)
@@ -215,47 +216,130 @@ async fn delete_message(
         .extensions()
         .get::<crate::domain::auth_user::AuthUserDto>()
         .cloned()
-        .ok_or_else(|| AppError {
-            message: "User not authenticated".to_string(),
-        })?;
+        .ok_or_else(|| AppError::unauthorized("User not authenticated"))?;
 
     let (conversation_id_str, message_id_str) = path.into_inner();
-    let conversation_id = ObjectId::parse_str(&conversation_id_str).map_err(|_| AppError {
-        message: "Invalid conversation ID".to_string(),
-    })?;
+    let conversation_id = ObjectId::parse_str(&conversation_id_str)
+        .map_err(|_| AppError::bad_request("Invalid conversation ID"))?;
 
     let conv_service = ConversationService::new(postgres_pool(&state));
     let msg_service = MessageService::new(postgres_pool(&state));
 
-    let auth_user_id = ObjectId::parse_str(&auth_user.id).map_err(|_| AppError {
-        message: "Invalid user ID".to_string(),
-    })?;
+    let auth_user_id =
+        ObjectId::parse_str(&auth_user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
 
     // Verify user is participant (single query)
     conv_service
         .find_one(Some(&IdType::ObjectId(conversation_id)), Some(auth_user_id))
         .await
-        .map_err(|_| AppError {
-            message: "You are not a participant in this conversation".to_string(),
-        })?;
+        .map_err(|_| AppError::forbidden("You are not a participant in this conversation"))?;
 
     let message = msg_service
         .find_one(&IdType::String(message_id_str.clone()))
         .await?;
 
     if message.sender.id != auth_user_id {
-        return Err(AppError {
-            message: "You can only delete your own messages".to_string(),
-        });
+        return Err(AppError::bad_request(
+            "You can only delete your own messages",
+        ));
+    }
+
+    if message.conversation_id != conversation_id {
+        return Err(AppError::not_found("Message not found"));
     }
 
     let deleted = msg_service
-        .soft_delete(&IdType::String(message_id_str))
+        .soft_delete(&IdType::String(message_id_str.clone()))
         .await?;
 
+    state
+        .conversation_hub
+        .publish(
+            &conversation_id.to_hex(),
+            &WsMessage::MessageDeleted {
+                message_id: message_id_str,
+            },
+            None,
+        )
+        .await;
+
     Ok(HttpResponse::Ok().json(deleted))
 }
 
+/// Record a read receipt for `reader` and tell the conversation. Shared with
+/// the socket's `read_ack` frames.
+pub(crate) async fn mark_message_read(
+    state: &web::Data<AppState>,
+    conversation_id: ObjectId,
+    message_id: &str,
+    reader: &ActorRef,
+) -> Result<WsMessage, AppError> {
+    let msg_service = MessageService::new(postgres_pool(state));
+    let message = msg_service
+        .find_one(&IdType::String(message_id.to_string()))
+        .await?;
+
+    if message.conversation_id != conversation_id || message.deleted_at.is_some() {
+        return Err(AppError::not_found("Message not found"));
+    }
+
+    let read_at = msg_service
+        .mark_read(&IdType::String(message_id.to_string()), reader)
+        .await?;
+
+    let frame = WsMessage::MessageRead {
+        message_id: message_id.to_string(),
+        user_id: reader.id.to_hex(),
+        read_at,
+    };
+    state
+        .conversation_hub
+        .publish(&conversation_id.to_hex(), &frame, None)
+        .await;
+    Ok(frame)
+}
+
+#[post("/{conversation_id}/messages/{message_id}/read")]
+async fn mark_read(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let auth_user = req
+        .extensions()
+        .get::<crate::domain::auth_user::AuthUserDto>()
+        .cloned()
+        .ok_or_else(|| AppError::unauthorized("User not authenticated"))?;
+
+    let (conversation_id_str, message_id_str) = path.into_inner();
+    let conversation_id = ObjectId::parse_str(&conversation_id_str)
+        .map_err(|_| AppError::bad_request("Invalid conversation ID"))?;
+
+    let auth_user_id =
+        ObjectId::parse_str(&auth_user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
+
+    let conv_service = ConversationService::new(postgres_pool(&state));
+    if !conv_service
+        .is_participant(conversation_id, auth_user_id)
+        .await?
+    {
+        return Err(AppError::forbidden(
+            "You are not a participant in this conversation",
+        ));
+    }
+
+    let reader = ActorRef {
+        id: auth_user_id,
+        role: auth_user
+            .role
+            .clone()
+            .unwrap_or(crate::domain::common_details::UserRole::STUDENT),
+    };
+    let receipt = mark_message_read(&state, conversation_id, &message_id_str, &reader).await?;
+
+    Ok(HttpResponse::Ok().json(receipt))
+}
+
 fn blueprint(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("")
@@ -264,7 +348,8 @@ fn blueprint(cfg: &mut web::ServiceConfig) {
             .service(create_message)
             .service(get_messages)
             .service(get_files)
-            .service(delete_message),
+            .service(delete_message)
+            .service(mark_read),
     );
 }
 
@@ -1,65 +1,50 @@
-use actix_web::{get, web, Error, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::{get, web, Error, HttpRequest, HttpResponse};
 use actix_ws::Message;
 use futures_util::StreamExt;
-use serde::{Deserialize, Serialize};
-
-use crate::{config::state::AppState, utils::object_id::ObjectId};
-
-// WebSocket message types
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(tag = "type", rename_all = "snake_case")]
-enum WsMessage {
-    MessageCreated {
-        conversation_id: String,
-        message_id: String,
-    },
-    MessageRead {
-        message_id: String,
-        user_id: String,
-    },
-    MessageDeleted {
-        message_id: String,
-    },
-    ConversationCreated {
-        conversation_id: String,
-    },
-    ParticipantAdded {
-        conversation_id: String,
-        user_id: String,
-    },
-    Error {
-        message: String,
-    },
-    Ping,
-    Pong,
+use serde::Deserialize;
+
+use crate::{
+    api::messages_api::mark_message_read,
+    config::state::AppState,
+    domain::{auth_user::AuthUserDto, common_details::UserRole},
+    schema::common_schema::ActorRef,
+    services::conversation_hub::WsMessage,
+    utils::{jwt::verify_jwt, object_id::ObjectId, request_context::postgres_pool},
+};
+
+#[derive(Debug, Deserialize)]
+struct SocketQuery {
+    token: Option<String>,
+}
+
+/// Browsers cannot set headers on a WebSocket handshake, so the token may
+/// also come as `?token=`.
+fn authenticate(req: &HttpRequest, query: &SocketQuery) -> Option<AuthUserDto> {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or_else(|| query.token.clone())?;
+    verify_jwt(&token).map(|claims| claims.user)
 }
 
 #[get("/m/ws/{conversation_id}")]
 async fn websocket_handler(
     req: HttpRequest,
     path: web::Path<String>,
+    query: web::Query<SocketQuery>,
     stream: web::Payload,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, Error> {
-    // Extract authentication from request
-    let school_id = req
-        .extensions()
-        .get::<ObjectId>()
-        .copied()
-        .ok_or_else(|| actix_web::error::ErrorUnauthorized("School ID not found"))?;
-
-    let auth_user = req
-        .extensions()
-        .get::<crate::domain::auth_user::AuthUserDto>()
-        .cloned()
+    let auth_user = authenticate(&req, &query)
         .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not authenticated"))?;
 
     let conversation_id = path.into_inner();
 
-    let _school_id = school_id;
-
     let conv_service =
-        crate::services::conversation_service::ConversationService::new(&state.pg.pool);
+        crate::services::conversation_service::ConversationService::new(postgres_pool(&state));
     let conversation_oid = ObjectId::parse_str(&conversation_id)
         .map_err(|_| actix_web::error::ErrorBadRequest("Invalid conversation ID"))?;
 
@@ -77,6 +62,25 @@ async fn websocket_handler(
 
     let (response, mut session, mut stream) = actix_ws::handle(&req, stream)?;
 
+    let hub = state.conversation_hub.clone();
+    let (session_id, mut outgoing) = hub.join(&conversation_id, &auth_user.id).await;
+    let reader = ActorRef {
+        id: auth_user_id,
+        role: auth_user.role.clone().unwrap_or(UserRole::STUDENT),
+    };
+
+    // Forward hub frames to this client. The stream ends when the hub drops
+    // the session, e.g. once the user is removed from the conversation.
+    let mut forward_session = session.clone();
+    let forwarder = actix_web::rt::spawn(async move {
+        while let Some(text) = outgoing.next().await {
+            if forward_session.text(text).await.is_err() {
+                return;
+            }
+        }
+        let _ = forward_session.close(None).await;
+    });
+
     // Spawn task to handle WebSocket messages
     actix_web::rt::spawn(async move {
         log::info!(
@@ -87,32 +91,56 @@ async fn websocket_handler(
         while let Some(msg) = stream.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
-                    log::debug!("Received text message: {}", text);
-
-                    // Parse incoming message
-                    if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                        match ws_msg {
-                            WsMessage::Ping => {
-                                let pong = serde_json::to_string(&WsMessage::Pong).unwrap();
-                                let _ = session.text(pong).await;
-                            }
-                            _ => {
-                                log::warn!("Unexpected message type from client");
+                    let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) else {
+                        let error = WsMessage::Error {
+                            message: "Invalid message".to_string(),
+                        };
+                        let _ = session.text(error.to_text()).await;
+                        continue;
+                    };
+
+                    match ws_msg {
+                        WsMessage::Ping => {
+                            let _ = session.text(WsMessage::Pong.to_text()).await;
+                        }
+                        WsMessage::Typing { is_typing, .. } => {
+                            let typing = WsMessage::Typing {
+                                user_id: Some(reader.id.to_hex()),
+                                is_typing,
+                            };
+                            hub.publish(&conversation_id, &typing, Some(&session_id))
+                                .await;
+                        }
+                        WsMessage::ReadAck { message_id } => {
+                            if let Err(err) =
+                                mark_message_read(&state, conversation_oid, &message_id, &reader)
+                                    .await
+                            {
+                                let error = WsMessage::Error {
+                                    message: err.message,
+                                };
+                                let _ = session.text(error.to_text()).await;
                             }
                         }
+                        _ => {
+                            log::warn!("Unexpected message type from client");
+                        }
                     }
                 }
                 Ok(Message::Ping(bytes)) => {
                     let _ = session.pong(&bytes).await;
                 }
                 Ok(Message::Close(reason)) => {
-                    let _ = session.close(reason).await;
+                    let _ = session.clone().close(reason).await;
                     break;
                 }
-                _ => break,
+                Ok(_) => {}
+                Err(_) => break,
             }
         }
 
+        hub.leave(&conversation_id, &session_id).await;
+        forwarder.abort();
         log::info!(
             "WebSocket connection closed for conversation: {}",
             conversation_id
@@ -38,9 +38,7 @@ async fn upload_public_key(
 
     let user_id = match ObjectId::parse_str(&auth_user.id) {
         Ok(id) => id,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(AppError { message: "Invalid user ID".to_string() })
-        }
+        Err(_) => return AppError::bad_request("Invalid user ID").to_response(),
     };
 
     let service = UserPublicKeyService::new(postgres_pool(&state));
@@ -50,7 +48,7 @@ async fn upload_public_key(
             message: "Public key uploaded successfully".to_string(),
             user_id: auth_user.id,
         }),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -63,9 +61,7 @@ async fn get_public_keys(
 ) -> impl Responder {
     let user_ids_str = match query.get("user_ids") {
         Some(ids) => ids,
-        None => {
-            return HttpResponse::BadRequest().json(AppError { message: "user_ids parameter is required".to_string() })
-        }
+        None => return AppError::bad_request("user_ids parameter is required").to_response(),
     };
 
     let user_ids: Vec<ObjectId> = user_ids_str
@@ -74,18 +70,18 @@ async fn get_public_keys(
         .collect();
 
     if user_ids.is_empty() {
-        return HttpResponse::BadRequest().json(AppError { message: "At least one valid user ID is required".to_string() });
+        return AppError::bad_request("At least one valid user ID is required").to_response();
     }
 
     if user_ids.len() > 50 {
-        return HttpResponse::BadRequest().json(AppError { message: "Maximum 50 user IDs allowed per request".to_string() });
+        return AppError::bad_request("Maximum 50 user IDs allowed per request").to_response();
     }
 
     let service = UserPublicKeyService::new(postgres_pool(&state));
 
     match service.get_or_create_public_keys(user_ids).await {
         Ok(public_keys) => HttpResponse::Ok().json(GetPublicKeysResponse { public_keys }),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -4,9 +4,12 @@ mod analytics_api;
 mod announcement_api;
 mod assessment_category_api;
 mod assignment_api;
+mod attendance;
 mod audit_logs_api;
 mod auth_api;
 mod backups_api;
+mod bulk_import_api;
+mod calendar_feed_api;
 mod class_api;
 mod class_subject;
 mod class_timetable;
@@ -16,27 +19,34 @@ mod database_status;
 mod education_year_api;
 mod events;
 mod exam_api;
+mod finance;
 mod grading_scale_api;
 mod join_school_request_api;
 mod learning_materials_api;
 mod like_api;
 mod location_api;
 mod main_class_api;
+mod marksheet_api;
 mod messages_api;
 mod messaging_socket;
 mod messaging_users_api;
+mod notifications;
 mod parent_api;
+mod promotion_api;
 mod ranking_api;
 mod recycle_bin_api;
+mod report_card_api;
 mod results_api;
 mod roles_api;
 mod school_api;
 mod school_collections;
 mod school_staff_api;
 mod score_api;
+mod score_moderation_api;
 mod sector_api;
 mod students_api;
 mod swagger_docs;
+mod teacher_cover_api;
 mod teachers_api;
 mod template_subject_api;
 mod trade_api;
@@ -62,6 +72,7 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
     parent_api::init(cfg);
     join_school_request_api::init(cfg);
     school_collections::school_class_timetable::init(cfg);
+    school_collections::school_room::init(cfg);
     school_collections::school_timetable::init(cfg);
     template_subject_api::init(cfg);
     class_subject::init(cfg);
@@ -75,8 +86,15 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
     score_api::init(cfg);
     grading_scale_api::init(cfg);
     results_api::init(cfg);
+    report_card_api::init(cfg);
+    bulk_import_api::init(cfg);
+    marksheet_api::init(cfg);
+    score_moderation_api::init(cfg);
+    promotion_api::init(cfg);
     ranking_api::init(cfg);
     assignment_api::init(cfg);
+    attendance::init(cfg);
+    finance::init(cfg);
     roles_api::init(cfg);
     audit_logs_api::init(cfg);
     backups_api::init(cfg);
@@ -84,12 +102,15 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
     learning_materials_api::init(cfg);
     analytics_api::init(cfg);
     location_api::init(cfg);
+    notifications::init(cfg);
+    calendar_feed_api::init(cfg);
+    teacher_cover_api::init(cfg);
+
+    // WebSocket route, ahead of the /m scope which would otherwise claim it
+    messaging_socket::init(cfg);
 
     // Messaging routes with /m prefix
     messaging_users_api::init(cfg);
     conversations_api::init(cfg);
     messages_api::init(cfg);
-
-    // WebSocket route
-    messaging_socket::init(cfg);
 }
@@ -0,0 +1,157 @@
+use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder};
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        auth_user::AuthUserDto,
+        notification::{NotificationQuery, UpdateNotificationPreference},
+    },
+    models::id_model::IdType,
+    repositories::notification_repo::NotificationRepo,
+    services::notification_service::NotificationService,
+    utils::request_context::{postgres_pool, request_context},
+};
+
+#[derive(Debug, serde::Deserialize)]
+struct MarkAllReadQuery {
+    category: Option<String>,
+}
+
+#[get("")]
+async fn get_notifications(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<NotificationQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = request_context(&req).school_id;
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service
+        .get_all(&user.id, school_id.as_deref(), &query)
+        .await
+    {
+        Ok(notifications) => HttpResponse::Ok().json(notifications),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/unread-count")]
+async fn get_unread_count(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = request_context(&req).school_id;
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service.unread_count(&user.id, school_id.as_deref()).await {
+        Ok(count) => HttpResponse::Ok().json(count),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/read-all")]
+async fn mark_all_notifications_read(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<MarkAllReadQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = request_context(&req).school_id;
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service
+        .mark_all_read(&user.id, school_id.as_deref(), query.category.as_deref())
+        .await
+    {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/preferences")]
+async fn get_notification_preferences(
+    user: web::ReqData<AuthUserDto>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service.preferences(&user.id).await {
+        Ok(preferences) => HttpResponse::Ok().json(preferences),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/preferences/{category}")]
+async fn update_notification_preference(
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<UpdateNotificationPreference>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service
+        .set_preference(&user.id, &path.into_inner(), data.muted)
+        .await
+    {
+        Ok(preference) => HttpResponse::Ok().json(preference),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/{id}/read")]
+async fn mark_notification_read(
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let id = IdType::from_string(path.into_inner());
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service.mark_read(&id, &user.id).await {
+        Ok(notification) => HttpResponse::Ok().json(notification),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/{id}/unread")]
+async fn mark_notification_unread(
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let id = IdType::from_string(path.into_inner());
+    let repo = NotificationRepo::new(postgres_pool(&state));
+    let service = NotificationService::new(&repo);
+
+    match service.mark_unread(&id, &user.id).await {
+        Ok(notification) => HttpResponse::Ok().json(notification),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_notifications)
+            .service(get_unread_count)
+            .service(mark_all_notifications_read)
+            .service(get_notification_preferences)
+            .service(update_notification_preference)
+            .service(mark_notification_read)
+            .service(mark_notification_unread),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "notifications", blueprint);
+}
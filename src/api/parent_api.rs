@@ -7,16 +7,31 @@ use crate::{
         common_details::UserRole,
         parent::{Parent, ParentPartial},
     },
-    guards::role_guard::{check_admin_or_staff, require_parent_child_access},
+    errors::AppError,
+    guards::role_guard::{require_context_permission, require_parent_child_access},
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         event_service::EventService,
         parent_service::{ParentQuery, ParentService},
+        permission_service::PermissionService,
     },
     utils::request_context::{postgres_pool, request_context},
 };
 
+/// The request's school, once the caller holds `permission` there
+async fn permitted_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    permission: &str,
+    state: &web::Data<AppState>,
+) -> Result<Option<String>, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_context_permission(req, user, permission, &permission_service)
+        .await
+        .map_err(|err| err.to_response())
+}
+
 // =========================
 // ADMIN/STAFF ENDPOINTS
 // =========================
@@ -28,15 +43,15 @@ async fn get_all_parents(
     state: web::Data<AppState>,
     user: web::ReqData<AuthUserDto>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.read", &state).await {
+        return response;
     }
 
     let service = ParentService::new(postgres_pool(&state));
     let context = request_context(&req);
     let parent_query = match ParentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -44,7 +59,7 @@ async fn get_all_parents(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -55,15 +70,15 @@ async fn get_all_parents_with_relations(
     state: web::Data<AppState>,
     user: web::ReqData<AuthUserDto>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.read", &state).await {
+        return response;
     }
 
     let service = ParentService::new(postgres_pool(&state));
     let context = request_context(&req);
     let parent_query = match ParentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -71,7 +86,7 @@ async fn get_all_parents_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -82,8 +97,8 @@ async fn get_parent_by_id(
     state: web::Data<AppState>,
     user: web::ReqData<AuthUserDto>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.read", &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -93,7 +108,7 @@ async fn get_parent_by_id(
 
     match service.find_one(Some(&id), parent_query).await {
         Ok(parent) => HttpResponse::Ok().json(parent),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -104,8 +119,8 @@ async fn get_parent_by_id_with_relations(
     state: web::Data<AppState>,
     user: web::ReqData<AuthUserDto>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.read", &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -118,7 +133,7 @@ async fn get_parent_by_id_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -129,19 +144,19 @@ async fn create_parent(
     data: web::Json<Parent>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
-    }
+    let school_id = match permitted_school_id(&req, &user, "parent.manage", &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
 
     let service = ParentService::new(postgres_pool(&state));
-    let context = request_context(&req);
 
     let mut parent = data.into_inner();
     if parent.school_id.is_none() {
-        if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
+        if let Some(school_id) = school_id {
             parent.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -165,7 +180,7 @@ async fn create_parent(
 
             HttpResponse::Created().json(parent)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -177,8 +192,8 @@ async fn update_parent(
     data: web::Json<ParentPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.manage", &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -203,7 +218,7 @@ async fn update_parent(
 
             HttpResponse::Ok().json(parent)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -214,8 +229,8 @@ async fn delete_parent(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.manage", &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -240,7 +255,7 @@ async fn delete_parent(
 
             HttpResponse::Ok().json(parent)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -251,15 +266,15 @@ async fn count_parents(
     state: web::Data<AppState>,
     user: web::ReqData<AuthUserDto>,
 ) -> impl Responder {
-    if let Err(e) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    if let Err(response) = permitted_school_id(&req, &user, "parent.read", &state).await {
+        return response;
     }
 
     let service = ParentService::new(postgres_pool(&state));
     let context = request_context(&req);
     let parent_query = match ParentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -267,7 +282,7 @@ async fn count_parents(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -283,36 +298,29 @@ async fn get_parent_dashboard(
 ) -> impl Responder {
     // Check if user is a parent
     if user.role != Some(UserRole::PARENT) {
-        return HttpResponse::Forbidden()
-            .json(serde_json::json!({ "error": "Only parents can access this endpoint" }));
+        return AppError::forbidden("Only parents can access this endpoint").to_response();
     }
 
     let school_id = match get_school_id_from_request(&req) {
         Some(id) => id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(serde_json::json!({ "error": "School ID required" }))
-        }
+        None => return AppError::bad_request("School ID required").to_response(),
     };
 
     let service = ParentService::new(postgres_pool(&state));
 
     let parent = match service.find_by_user_id(&user.id, Some(&school_id)).await {
         Ok(p) => p,
-        Err(err) => return HttpResponse::NotFound().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let parent_id = match parent.id {
         Some(id) => IdType::ObjectId(id),
-        None => {
-            return HttpResponse::InternalServerError()
-                .json(serde_json::json!({ "error": "Parent ID not found" }))
-        }
+        None => return AppError::internal("Parent ID not found").to_response(),
     };
 
     match service.get_dashboard(&parent_id, &school_id, &state).await {
         Ok(dashboard) => HttpResponse::Ok().json(dashboard),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -327,17 +335,21 @@ async fn get_student_attendance(
 
     let school_id = match get_school_id_from_request(&req) {
         Some(id) => id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(serde_json::json!({ "error": "School ID required" }))
-        }
+        None => return AppError::bad_request("School ID required").to_response(),
     };
 
     let service = ParentService::new(postgres_pool(&state));
 
-    // Validate parent-child access using new guard
-    if let Err(e) = require_parent_child_access(&user, &student_id, &service).await {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    // Parents reach their own children, staff the students they may read
+    if let Err(err) = require_parent_child_access(
+        &user,
+        &student_id,
+        "attendance.read.class",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
+        return err.to_response();
     }
 
     match service
@@ -345,7 +357,7 @@ async fn get_student_attendance(
         .await
     {
         Ok(summary) => HttpResponse::Ok().json(summary),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -361,17 +373,21 @@ async fn get_student_results(
 
     let school_id = match get_school_id_from_request(&req) {
         Some(id) => id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(serde_json::json!({ "error": "School ID required" }))
-        }
+        None => return AppError::bad_request("School ID required").to_response(),
     };
 
     let service = ParentService::new(postgres_pool(&state));
 
-    // Validate parent-child access using new guard
-    if let Err(e) = require_parent_child_access(&user, &student_id, &service).await {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    // Parents reach their own children, staff the students they may read
+    if let Err(err) = require_parent_child_access(
+        &user,
+        &student_id,
+        "result.read",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
+        return err.to_response();
     }
 
     let education_year_id = query.education_year_id.as_deref();
@@ -382,7 +398,7 @@ async fn get_student_results(
         .await
     {
         Ok(results) => HttpResponse::Ok().json(results),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -397,17 +413,21 @@ async fn get_student_finance(
 
     let school_id = match get_school_id_from_request(&req) {
         Some(id) => id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(serde_json::json!({ "error": "School ID required" }))
-        }
+        None => return AppError::bad_request("School ID required").to_response(),
     };
 
     let service = ParentService::new(postgres_pool(&state));
 
-    // Validate parent-child access using new guard
-    if let Err(e) = require_parent_child_access(&user, &student_id, &service).await {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "error": e }));
+    // Parents reach their own children, staff the students they may read
+    if let Err(err) = require_parent_child_access(
+        &user,
+        &student_id,
+        "finance.read",
+        &PermissionService::for_request(&req, postgres_pool(&state)),
+    )
+    .await
+    {
+        return err.to_response();
     }
 
     match service
@@ -415,7 +435,7 @@ async fn get_student_finance(
         .await
     {
         Ok(summary) => HttpResponse::Ok().json(summary),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -428,31 +448,24 @@ async fn get_parent_announcements(
 ) -> impl Responder {
     // Check if user is a parent
     if user.role != Some(UserRole::PARENT) {
-        return HttpResponse::Forbidden()
-            .json(serde_json::json!({ "error": "Only parents can access this endpoint" }));
+        return AppError::forbidden("Only parents can access this endpoint").to_response();
     }
 
     let school_id = match get_school_id_from_request(&req) {
         Some(id) => id,
-        None => {
-            return HttpResponse::BadRequest()
-                .json(serde_json::json!({ "error": "School ID required" }))
-        }
+        None => return AppError::bad_request("School ID required").to_response(),
     };
 
     let service = ParentService::new(postgres_pool(&state));
 
     let parent = match service.find_by_user_id(&user.id, Some(&school_id)).await {
         Ok(p) => p,
-        Err(err) => return HttpResponse::NotFound().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let parent_id = match parent.id {
         Some(id) => IdType::ObjectId(id),
-        None => {
-            return HttpResponse::InternalServerError()
-                .json(serde_json::json!({ "error": "Parent ID not found" }))
-        }
+        None => return AppError::internal("Parent ID not found").to_response(),
     };
 
     match service
@@ -460,7 +473,7 @@ async fn get_parent_announcements(
         .await
     {
         Ok(announcements) => HttpResponse::Ok().json(announcements),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
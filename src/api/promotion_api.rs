@@ -0,0 +1,385 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        auth_user::AuthUserDto,
+        promotion::{
+            CreatePromotionProposal, PromotionBatchQuery, PromotionBatchWithSummary,
+            PromotionDecision, PromotionRule, PromotionRulePartial,
+        },
+    },
+    guards::role_guard::require_request_permission,
+    models::id_model::IdType,
+    repositories::promotion_repo::PromotionRepo,
+    services::{
+        audit_log_service::AuditLogService, event_service::EventService,
+        permission_service::PermissionService, promotion_service::PromotionService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
+};
+
+#[derive(Debug, Deserialize)]
+struct RuleQuery {
+    education_year_id: Option<String>,
+}
+
+/// The request's school, once the caller may manage promotions there
+async fn admin_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<String, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_request_permission(req, user, "promotion.manage", &permission_service)
+        .await
+        .map_err(|err| err.to_response())
+}
+
+// ========== RULES ==========
+
+#[get("/rules")]
+async fn get_rules(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<RuleQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service
+        .get_rules(&school_id, query.education_year_id.as_deref())
+        .await
+    {
+        Ok(rules) => HttpResponse::Ok().json(rules),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/rules")]
+async fn create_rule(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<PromotionRule>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service
+        .create_rule(&school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(rule) => HttpResponse::Created().json(rule),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/rules/{id}")]
+async fn get_rule(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service.find_rule(&id, &school_id).await {
+        Ok(rule) => HttpResponse::Ok().json(rule),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/rules/{id}")]
+async fn update_rule(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<PromotionRulePartial>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service
+        .update_rule(&id, &school_id, data.into_inner())
+        .await
+    {
+        Ok(rule) => HttpResponse::Ok().json(rule),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/rules/{id}")]
+async fn delete_rule(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service.delete_rule(&id, &school_id).await {
+        Ok(rule) => HttpResponse::Ok().json(rule),
+        Err(err) => err.to_response(),
+    }
+}
+
+// ========== PROPOSALS ==========
+
+#[get("/proposals")]
+async fn get_proposals(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<PromotionBatchQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service.get_batches(&school_id, &query).await {
+        Ok(batches) => HttpResponse::Ok().json(
+            batches
+                .into_iter()
+                .map(PromotionBatchWithSummary::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Evaluate students against the promotion rule and store a proposal to review
+#[post("/proposals")]
+async fn create_proposal(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<CreatePromotionProposal>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service
+        .propose(&school_id, data.into_inner(), Some(&user.id))
+        .await
+    {
+        Ok(batch) => {
+            let batch = PromotionBatchWithSummary::from(batch);
+            let batch_clone = batch.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = batch_clone.batch.id {
+                    EventService::broadcast_created(
+                        &state_clone,
+                        "promotion_batch",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &batch_clone.summary,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Created().json(batch)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/proposals/{id}")]
+async fn get_proposal(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service.find_batch(&id, &school_id).await {
+        Ok(batch) => HttpResponse::Ok().json(PromotionBatchWithSummary::from(batch)),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Override the proposed outcome for one student
+#[put("/proposals/{id}/students/{student_id}")]
+async fn decide_student(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<(String, String)>,
+    data: web::Json<PromotionDecision>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let (id, student_id) = path.into_inner();
+    let id = IdType::from_string(id);
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service
+        .decide(&id, &school_id, &student_id, data.into_inner())
+        .await
+    {
+        Ok(batch) => HttpResponse::Ok().json(PromotionBatchWithSummary::from(batch)),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Approve the proposal and move students into their new classes
+#[post("/proposals/{id}/approve")]
+async fn approve_proposal(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service.approve(&id, &school_id, Some(&user.id)).await {
+        Ok((batch, moved)) => {
+            let batch = PromotionBatchWithSummary::from(batch);
+            if let (Some(batch_id), Ok(school_oid)) =
+                (batch.batch.id, ObjectId::parse_str(&school_id))
+            {
+                AuditLogService::new(postgres_pool(&state))
+                    .log_event(
+                        school_oid,
+                        &user,
+                        "promotion.approve",
+                        "promotion_batch",
+                        batch_id,
+                        None,
+                        None,
+                        None,
+                    )
+                    .await
+                    .ok();
+            }
+
+            let batch_clone = batch.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = batch_clone.batch.id {
+                    EventService::broadcast_updated(
+                        &state_clone,
+                        "promotion_batch",
+                        &id.to_hex(),
+                        Some(school_id),
+                        &batch_clone.summary,
+                    )
+                    .await;
+                }
+            });
+
+            HttpResponse::Ok().json(serde_json::json!({
+                "proposal": batch,
+                "students_moved": moved
+            }))
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/proposals/{id}/cancel")]
+async fn cancel_proposal(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let id = IdType::from_string(path.into_inner());
+    let repo = PromotionRepo::new(postgres_pool(&state));
+    let service = PromotionService::new(&repo);
+
+    match service.cancel(&id, &school_id).await {
+        Ok(batch) => HttpResponse::Ok().json(PromotionBatchWithSummary::from(batch)),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_rules)
+            .service(create_rule)
+            .service(get_rule)
+            .service(update_rule)
+            .service(delete_rule)
+            .service(get_proposals)
+            .service(create_proposal)
+            .service(get_proposal)
+            .service(decide_student)
+            .service(approve_proposal)
+            .service(cancel_proposal),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "promotions", blueprint);
+}
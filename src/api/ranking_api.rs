@@ -18,13 +18,13 @@ async fn calculate_rankings(
 ) -> impl Responder {
     let exam_id = match parse_object_id_value(&path.into_inner()) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let class_id = match query.class_id.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
         None => {
             return HttpResponse::BadRequest().json(serde_json::json!({
@@ -41,7 +41,7 @@ async fn calculate_rankings(
             "count": rankings.len(),
             "rankings": rankings
         })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -55,19 +55,19 @@ async fn get_class_rankings(
 
     let class_id = match parse_object_id_value(&class_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let exam_id = match parse_object_id_value(&exam_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let service = RankingService::new(postgres_pool(&state));
 
     match service.get_class_rankings(&class_id, &exam_id).await {
         Ok(rankings) => HttpResponse::Ok().json(rankings),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -82,12 +82,12 @@ async fn get_top_students(
 
     let class_id = match parse_object_id_value(&class_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let exam_id = match parse_object_id_value(&exam_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let limit = query.limit.unwrap_or(10);
@@ -96,7 +96,7 @@ async fn get_top_students(
 
     match service.get_top_students(&class_id, &exam_id, limit).await {
         Ok(students) => HttpResponse::Ok().json(students),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -111,12 +111,12 @@ async fn get_at_risk_students(
 
     let class_id = match parse_object_id_value(&class_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let exam_id = match parse_object_id_value(&exam_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     // Default threshold is 2.0 GPA
@@ -129,7 +129,47 @@ async fn get_at_risk_students(
         .await
     {
         Ok(students) => HttpResponse::Ok().json(students),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Students whose average dropped sharply since their previous exam
+#[get("/class/{class_id}/exam/{exam_id}/declining")]
+async fn get_declining_students(
+    _req: HttpRequest,
+    path: web::Path<(String, String)>,
+    query: web::Query<RequestQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let (class_id_str, exam_id_str) = path.into_inner();
+
+    let class_id = match parse_object_id_value(&class_id_str) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let exam_id = match parse_object_id_value(&exam_id_str) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let compare_exam_id = match query.compare_exam_id.as_deref().map(parse_object_id_value) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(err)) => return err.to_response(),
+        None => None,
+    };
+
+    // Default threshold is a 10 point drop
+    let threshold = query.drop_threshold.unwrap_or(10.0);
+
+    let service = RankingService::new(postgres_pool(&state));
+
+    match service
+        .get_declining_students(&class_id, &exam_id, compare_exam_id.as_ref(), threshold)
+        .await
+    {
+        Ok(students) => HttpResponse::Ok().json(students),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -137,6 +177,7 @@ fn blueprint(cfg: &mut web::ServiceConfig) {
     cfg.service(get_class_rankings)
         .service(get_top_students)
         .service(get_at_risk_students)
+        .service(get_declining_students)
         .service(
             web::scope("")
                 .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
@@ -5,8 +5,9 @@ use serde::Deserialize;
 use crate::{
     config::state::AppState,
     domain::auth_user::AuthUserDto,
-    guards::role_guard::check_admin,
-    services::recycle_bin_service::RecycleBinService,
+    errors::AppError,
+    guards::role_guard::{check_permission, require_request_permission},
+    services::{permission_service::PermissionService, recycle_bin_service::RecycleBinService},
     utils::{object_id::parse_object_id_value, request_context::postgres_pool},
 };
 
@@ -21,21 +22,18 @@ struct RecycleBinQuery {
 
 #[get("")]
 async fn get_recycle_bin(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     query: web::Query<RecycleBinQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err_msg) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err_msg.to_string() }));
-    }
-
-    let school_id = match user.current_school_id.as_ref() {
-        Some(id) => match parse_object_id_value(id) {
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    let school_id = match require_request_permission(&req, &user, "recycle_bin.manage", &permission_service).await {
+        Ok(id) => match parse_object_id_value(&id) {
             Ok(oid) => oid,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => return HttpResponse::BadRequest().json(serde_json::json!({ "message": "School ID not found in user context" })),
+        Err(err) => return err.to_response(),
     };
 
     let service = RecycleBinService::new(postgres_pool(&state));
@@ -49,7 +47,7 @@ async fn get_recycle_bin(
 
     match service.get_deleted_entities(query.entity_type.clone(), start_date, end_date, school_id, query.limit, query.skip).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -61,37 +59,41 @@ struct RestoreRequest {
 
 #[post("/restore")]
 async fn restore_entity(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<RestoreRequest>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err_msg) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err_msg.to_string() }));
+    // Entities are looked up by id alone, so this stays a platform action
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err_msg) = check_permission(&user, None, "recycle_bin.manage", &permission_service).await {
+        return AppError::forbidden(err_msg).to_response();
     }
 
     let service = RecycleBinService::new(postgres_pool(&state));
     match service.restore_entity(&data.entity_type, &data.entity_id, &user, &state).await {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "message": "Entity restored successfully" })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[delete("/permanent")]
 async fn permanently_delete_entity(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<RestoreRequest>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    if let Err(err_msg) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err_msg.to_string() }));
+    // Entities are looked up by id alone, so this stays a platform action
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(err_msg) = check_permission(&user, None, "recycle_bin.manage", &permission_service).await {
+        return AppError::forbidden(err_msg).to_response();
     }
 
     let service = RecycleBinService::new(postgres_pool(&state));
     match service.permanently_delete(&data.entity_type, &data.entity_id, &user, &state).await {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({ "message": "Entity permanently deleted" })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
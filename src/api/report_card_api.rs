@@ -0,0 +1,224 @@
+use actix_web::{get, http::header, put, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+
+use crate::{
+    config::state::AppState,
+    domain::{
+        auth_user::AuthUserDto,
+        report_card::{ReportCard, UpsertReportCardComment},
+        role::PermissionTarget,
+    },
+    errors::AppError,
+    guards::role_guard::{require_request_school, require_scoped_permission},
+    handler::report_card_handler::render_report_cards,
+    services::{
+        permission_service::PermissionService, report_card_service::ReportCardService,
+        score_moderation_service::ScoreModerationService, tenant_service::TenantService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
+};
+
+async fn school_id_for(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<ObjectId, HttpResponse> {
+    let tenant_service = TenantService::new(postgres_pool(state));
+    let school_id = require_request_school(req, user, &tenant_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    ObjectId::parse_str(&school_id)
+        .map_err(|_| AppError::bad_request("Invalid school id").to_response())
+}
+
+fn path_ids(
+    raw: (String, String),
+    labels: (&str, &str),
+) -> Result<(ObjectId, ObjectId), HttpResponse> {
+    let first = ObjectId::parse_str(&raw.0)
+        .map_err(|_| AppError::bad_request(format!("Invalid {} id", labels.0)).to_response())?;
+    let second = ObjectId::parse_str(&raw.1)
+        .map_err(|_| AppError::bad_request(format!("Invalid {} id", labels.1)).to_response())?;
+    Ok((first, second))
+}
+
+async fn pdf_response(
+    service: &ReportCardService,
+    school_id: &ObjectId,
+    cards: &[ReportCard],
+    filename: &str,
+) -> HttpResponse {
+    let logo = match service.school_logo(school_id).await {
+        Some(url) => ReportCardService::fetch_logo(&url).await,
+        None => None,
+    };
+    let pdf = render_report_cards(filename, cards, logo.as_deref(), Utc::now());
+    HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "application/pdf"))
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}.pdf\"", filename),
+        ))
+        .body(pdf)
+}
+
+/// Staff holding `report_card.read` for the student see the card at any
+/// time. The student and linked parents wait for the results to be published.
+async fn ensure_can_read(
+    service: &ReportCardService,
+    user: &AuthUserDto,
+    student_id: &ObjectId,
+    exam_id: &ObjectId,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    if service
+        .has_student_permission(user, student_id, "report_card.read")
+        .await?
+    {
+        return Ok(());
+    }
+    if !service.is_self_or_parent(user, student_id).await? {
+        return Err(AppError::forbidden(
+            "You cannot view this student's report card",
+        ));
+    }
+    if !ScoreModerationService::new(postgres_pool(state))
+        .results_published(exam_id)
+        .await?
+    {
+        return Err(AppError::forbidden(
+            "Results for this exam are not published yet",
+        ));
+    }
+    Ok(())
+}
+
+#[get("/student/{student_id}/exam/{exam_id}/pdf")]
+async fn student_report_card(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let (student_id, exam_id) = match path_ids(path.into_inner(), ("student", "exam")) {
+        Ok(ids) => ids,
+        Err(response) => return response,
+    };
+
+    let service = ReportCardService::new(postgres_pool(&state));
+    if let Err(err) = ensure_can_read(&service, &user, &student_id, &exam_id, &state).await {
+        return err.to_response();
+    }
+
+    match service
+        .student_card(&school_id, &student_id, &exam_id)
+        .await
+    {
+        Ok(card) => {
+            let filename = format!("report-card-{}", student_id.to_hex());
+            pdf_response(&service, &school_id, &[card], &filename).await
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Every card of the class for the exam as one PDF, a page break between
+/// students, ready to print in one go
+#[get("/class/{class_id}/exam/{exam_id}/pdf")]
+async fn class_report_cards(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let (class_id, exam_id) = match path_ids(path.into_inner(), ("class", "exam")) {
+        Ok(ids) => ids,
+        Err(response) => return response,
+    };
+    let target = PermissionTarget::school(school_id.to_hex()).class(class_id.to_hex());
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(message) =
+        require_scoped_permission(&user, "report_card.read", &target, &permission_service).await
+    {
+        return AppError::forbidden(message).to_response();
+    }
+
+    let service = ReportCardService::new(postgres_pool(&state));
+    match service.class_cards(&school_id, &class_id, &exam_id).await {
+        Ok(cards) if cards.is_empty() => {
+            AppError::not_found("No results calculated for this class and exam").to_response()
+        }
+        Ok(cards) => {
+            let filename = format!("report-cards-{}-{}", class_id.to_hex(), exam_id.to_hex());
+            pdf_response(&service, &school_id, &cards, &filename).await
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/student/{student_id}/exam/{exam_id}/comments")]
+async fn get_comments(
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let (student_id, exam_id) = match path_ids(path.into_inner(), ("student", "exam")) {
+        Ok(ids) => ids,
+        Err(response) => return response,
+    };
+
+    let service = ReportCardService::new(postgres_pool(&state));
+    if let Err(err) = ensure_can_read(&service, &user, &student_id, &exam_id, &state).await {
+        return err.to_response();
+    }
+
+    match service.comments(&student_id, &exam_id).await {
+        Ok(comments) => HttpResponse::Ok().json(comments),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/comments")]
+async fn upsert_comment(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    body: web::Json<UpsertReportCardComment>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = ReportCardService::new(postgres_pool(&state));
+    match service
+        .upsert_comment(&school_id, &user, body.into_inner())
+        .await
+    {
+        Ok(comment) => HttpResponse::Ok().json(comment),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(student_report_card)
+            .service(class_report_cards)
+            .service(get_comments)
+            .service(upsert_comment),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "report-cards", blueprint);
+}
@@ -1,16 +1,31 @@
-use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
-    domain::auth_user::AuthUserDto,
+    domain::{auth_user::AuthUserDto, role::PermissionTarget, student_annual_result::TermWeight},
+    errors::AppError,
+    guards::role_guard::{require_permission, require_scoped_permission},
     models::api_request_model::RequestQuery,
-    services::gpa_calculation_service::GpaCalculationService,
+    services::{
+        annual_result_service::AnnualResultService, gpa_calculation_service::GpaCalculationService,
+        permission_service::PermissionService, report_card_service::ReportCardService,
+        score_moderation_service::ScoreModerationService,
+    },
     utils::{
-        object_id::parse_object_id_value,
+        object_id::{parse_object_id_value, ObjectId},
         request_context::{postgres_pool, request_context},
     },
 };
 
+fn school_id_for(req: &HttpRequest, query: &RequestQuery) -> Result<ObjectId, HttpResponse> {
+    let school_id = query
+        .school_id
+        .clone()
+        .or(request_context(req).school_id)
+        .ok_or_else(|| AppError::bad_request("school_id is required").to_response())?;
+    parse_object_id_value(&school_id).map_err(|err| err.to_response())
+}
+
 #[post("/calculate/{exam_id}")]
 async fn calculate_exam_results(
     req: HttpRequest,
@@ -21,31 +36,23 @@ async fn calculate_exam_results(
 ) -> impl Responder {
     let exam_id = match parse_object_id_value(&path.into_inner()) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let class_id = match query.class_id.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "message": "class_id is required"
-            }))
-        }
+        None => return AppError::bad_request("class_id is required").to_response(),
     };
 
     let education_year_id = match query.education_year_id.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "message": "education_year_id is required"
-            }))
-        }
+        None => return AppError::bad_request("education_year_id is required").to_response(),
     };
 
     let context = request_context(&req);
@@ -53,15 +60,25 @@ async fn calculate_exam_results(
     let school_id = match school_id_raw.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "message": "school_id is required"
-            }))
-        }
+        None => return AppError::bad_request("school_id is required").to_response(),
     };
 
+    match ScoreModerationService::new(postgres_pool(&state))
+        .results_published(&exam_id)
+        .await
+    {
+        Ok(false) => {}
+        Ok(true) => {
+            return AppError::conflict(
+                "Results for this exam are published; reopen a subject to recalculate",
+            )
+            .to_response()
+        }
+        Err(err) => return err.to_response(),
+    }
+
     let service = GpaCalculationService::new(postgres_pool(&state));
 
     match service
@@ -79,13 +96,16 @@ async fn calculate_exam_results(
             "count": results.len(),
             "results": results
         })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
+/// Staff and teachers see results as calculated; the student and their
+/// parents only once the exam's results are published
 #[get("/student/{student_id}/term/{term_id}")]
 async fn get_student_term_results(
     _req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<(String, String)>,
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
@@ -94,35 +114,59 @@ async fn get_student_term_results(
 
     let student_id = match parse_object_id_value(&student_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let exam_id = match query.exam_id.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "message": "exam_id is required"
-            }))
-        }
+        None => return AppError::bad_request("exam_id is required").to_response(),
     };
 
+    let report_cards = ReportCardService::new(postgres_pool(&state));
+    let is_staff = match report_cards
+        .has_student_permission(&user, &student_id, "result.read")
+        .await
+    {
+        Ok(is_staff) => is_staff,
+        Err(err) => return err.to_response(),
+    };
+    if !is_staff {
+        match report_cards.is_self_or_parent(&user, &student_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return AppError::forbidden("You cannot view this student's results").to_response()
+            }
+            Err(err) => return err.to_response(),
+        }
+        match ScoreModerationService::new(postgres_pool(&state))
+            .results_published(&exam_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                return AppError::forbidden("Results for this exam are not published yet")
+                    .to_response()
+            }
+            Err(err) => return err.to_response(),
+        }
+    }
+
     let service = GpaCalculationService::new(postgres_pool(&state));
 
     match service.get_student_result(&student_id, &exam_id).await {
         Ok(Some(result)) => HttpResponse::Ok().json(result),
-        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
-            "message": "Result not found"
-        })),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Ok(None) => AppError::not_found("Result not found").to_response(),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/class/{class_id}/exam/{exam_id}")]
 async fn get_class_exam_results(
     req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<(String, String)>,
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
@@ -131,24 +175,20 @@ async fn get_class_exam_results(
 
     let class_id = match parse_object_id_value(&class_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let exam_id = match parse_object_id_value(&exam_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let education_year_id = match query.education_year_id.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "message": "education_year_id is required"
-            }))
-        }
+        None => return AppError::bad_request("education_year_id is required").to_response(),
     };
 
     let context = request_context(&req);
@@ -156,14 +196,17 @@ async fn get_class_exam_results(
     let school_id = match school_id_raw.as_ref() {
         Some(id) => match parse_object_id_value(id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         },
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "message": "school_id is required"
-            }))
-        }
+        None => return AppError::bad_request("school_id is required").to_response(),
     };
+    let target = PermissionTarget::school(school_id.to_hex()).class(class_id.to_hex());
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(message) =
+        require_scoped_permission(&user, "result.read", &target, &permission_service).await
+    {
+        return AppError::forbidden(message).to_response();
+    }
 
     let service = GpaCalculationService::new(postgres_pool(&state));
 
@@ -178,18 +221,195 @@ async fn get_class_exam_results(
         .await
     {
         Ok(results) => HttpResponse::Ok().json(results),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
+    }
+}
+
+// ========== ANNUAL RESULTS ==========
+
+/// Combine every term of the year into annual results for a class and rank them
+#[post("/calculate/year/{education_year_id}")]
+async fn calculate_annual_results(
+    req: HttpRequest,
+    _user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<RequestQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let education_year_id = match parse_object_id_value(&path.into_inner()) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let class_id = match query.class_id.as_ref() {
+        Some(id) => match parse_object_id_value(id) {
+            Ok(id) => id,
+            Err(err) => return err.to_response(),
+        },
+        None => return AppError::bad_request("class_id is required").to_response(),
+    };
+
+    let school_id = match school_id_for(&req, &query) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = AnnualResultService::new(postgres_pool(&state));
+
+    match service
+        .calculate_class_annual_results(&class_id, &education_year_id, &school_id)
+        .await
+    {
+        Ok(results) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Annual results calculated successfully",
+            "count": results.len(),
+            "results": results
+        })),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/student/{student_id}/year/{education_year_id}")]
+async fn get_student_annual_result(
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let (student_id, education_year_id) = path.into_inner();
+    let student_id = match parse_object_id_value(&student_id) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+    let education_year_id = match parse_object_id_value(&education_year_id) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let service = AnnualResultService::new(postgres_pool(&state));
+
+    match service
+        .get_student_annual_result(&student_id, &education_year_id)
+        .await
+    {
+        Ok(Some(result)) => HttpResponse::Ok().json(result),
+        Ok(None) => AppError::not_found("Annual result not found").to_response(),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/student/{student_id}/cumulative")]
+async fn get_student_cumulative_gpa(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let student_id = match parse_object_id_value(&path.into_inner()) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let service = AnnualResultService::new(postgres_pool(&state));
+
+    match service.get_cumulative_gpa(&student_id).await {
+        Ok(cumulative) => HttpResponse::Ok().json(cumulative),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/class/{class_id}/year/{education_year_id}")]
+async fn get_class_annual_results(
+    path: web::Path<(String, String)>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let (class_id, education_year_id) = path.into_inner();
+    let class_id = match parse_object_id_value(&class_id) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+    let education_year_id = match parse_object_id_value(&education_year_id) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let service = AnnualResultService::new(postgres_pool(&state));
+
+    match service
+        .get_class_annual_results(&class_id, &education_year_id)
+        .await
+    {
+        Ok(results) => HttpResponse::Ok().json(results),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/year/{education_year_id}/term-weights")]
+async fn get_term_weights(path: web::Path<String>, state: web::Data<AppState>) -> impl Responder {
+    let education_year_id = match parse_object_id_value(&path.into_inner()) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let service = AnnualResultService::new(postgres_pool(&state));
+
+    match service.get_term_weights(&education_year_id).await {
+        Ok(weights) => HttpResponse::Ok().json(weights),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/year/{education_year_id}/term-weights")]
+async fn set_term_weights(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<RequestQuery>,
+    data: web::Json<Vec<TermWeight>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let education_year_id = match parse_object_id_value(&path.into_inner()) {
+        Ok(id) => id,
+        Err(err) => return err.to_response(),
+    };
+
+    let school_id = match school_id_for(&req, &query) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    if let Err(message) = require_permission(
+        &user,
+        &school_id.to_hex(),
+        "result.configure",
+        &permission_service,
+    )
+    .await
+    {
+        return AppError::forbidden(message).to_response();
+    }
+
+    let service = AnnualResultService::new(postgres_pool(&state));
+
+    match service
+        .set_term_weights(&school_id, &education_year_id, data.into_inner())
+        .await
+    {
+        Ok(weights) => HttpResponse::Ok().json(weights),
+        Err(err) => err.to_response(),
     }
 }
 
 fn blueprint(cfg: &mut web::ServiceConfig) {
-    cfg.service(get_student_term_results)
-        .service(get_class_exam_results)
-        .service(
-            web::scope("")
-                .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
-                .service(calculate_exam_results),
-        );
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_student_term_results)
+            .service(get_class_exam_results)
+            .service(calculate_exam_results)
+            .service(calculate_annual_results)
+            .service(get_student_annual_result)
+            .service(get_student_cumulative_gpa)
+            .service(get_class_annual_results)
+            .service(get_term_weights)
+            .service(set_term_weights),
+    );
 }
 
 pub fn init(cfg: &mut web::ServiceConfig) {
@@ -4,18 +4,51 @@ use crate::{
     config::state::AppState,
     domain::{
         auth_user::AuthUserDto,
-        role::{Role, RolePartial},
+        role::{PermissionExplainQuery, Role, RolePartial},
     },
-    guards::role_guard::check_admin,
+    errors::AppError,
+    guards::role_guard::check_permission,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         event_service::EventService,
+        permission_service::PermissionService,
         role_service::{RoleQuery, RoleService},
     },
     utils::request_context::{postgres_pool, request_context},
 };
 
+/// Admins manage every role; anyone else needs the permission in the
+/// school the role belongs to
+async fn check_role_permission(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    school_id: Option<String>,
+    permission: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    check_permission(
+        user,
+        school_id.as_deref(),
+        permission,
+        &PermissionService::for_request(req, postgres_pool(state)),
+    )
+    .await
+    .map_err(|err| AppError::forbidden(err).to_response())
+}
+
+/// School of an existing role, `None` for platform-wide roles
+async fn role_school_id(
+    service: &RoleService,
+    id: &IdType,
+) -> Result<Option<String>, HttpResponse> {
+    service
+        .find_one(Some(id), None)
+        .await
+        .map(|role| role.school_id.map(|id| id.to_hex()))
+        .map_err(|err| err.to_response())
+}
+
 #[get("")]
 async fn get_all_roles(
     req: HttpRequest,
@@ -26,7 +59,7 @@ async fn get_all_roles(
     let context = request_context(&req);
     let role_query = match RoleQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -34,7 +67,7 @@ async fn get_all_roles(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -48,7 +81,7 @@ async fn get_all_roles_with_relations(
     let context = request_context(&req);
     let role_query = match RoleQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -56,7 +89,7 @@ async fn get_all_roles_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -73,7 +106,7 @@ async fn get_role_by_id_with_relations(
 
     match service.find_one_with_relations(Some(&id), role_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -90,7 +123,7 @@ async fn get_role_by_id(
 
     match service.find_one(Some(&id), role_query).await {
         Ok(role) => HttpResponse::Ok().json(role),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -104,12 +137,12 @@ async fn get_role_by_match(
     let context = request_context(&req);
     let role_query = match RoleQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, role_query).await {
         Ok(role) => HttpResponse::Ok().json(role),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -123,12 +156,12 @@ async fn get_role_by_other_match(
     let context = request_context(&req);
     let role_query = match RoleQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one_with_relations(None, role_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -139,13 +172,6 @@ async fn create_role(
     data: web::Json<Role>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Only Admin can create roles
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
-    }
-
     let service = RoleService::new(postgres_pool(&state));
     let context = request_context(&req);
     let mut role = data.into_inner();
@@ -153,11 +179,18 @@ async fn create_role(
         if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
             role.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
 
+    let school_id = role.school_id.map(|id| id.to_hex());
+    if let Err(response) =
+        check_role_permission(&req, &user, school_id, "role.create", &state).await
+    {
+        return response;
+    }
+
     match service.create_role(role).await {
         Ok(role) => {
             let role_clone = role.clone();
@@ -177,7 +210,7 @@ async fn create_role(
 
             HttpResponse::Created().json(role)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -189,15 +222,17 @@ async fn update_role(
     data: web::Json<RolePartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Only Admin can update roles
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
-    }
-
     let id = IdType::from_string(path.into_inner());
     let service = RoleService::new(postgres_pool(&state));
+    let school_id = match role_school_id(&service, &id).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    if let Err(response) =
+        check_role_permission(&req, &user, school_id, "role.update", &state).await
+    {
+        return response;
+    }
 
     match service.update_role(&id, &data.into_inner()).await {
         Ok(role) => {
@@ -218,7 +253,7 @@ async fn update_role(
 
             HttpResponse::Ok().json(role)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -229,15 +264,17 @@ async fn delete_role(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Only Admin can delete roles
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
-    }
-
     let id = IdType::from_string(path.into_inner());
     let service = RoleService::new(postgres_pool(&state));
+    let school_id = match role_school_id(&service, &id).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    if let Err(response) =
+        check_role_permission(&req, &user, school_id, "role.delete", &state).await
+    {
+        return response;
+    }
 
     match service.delete_role(&id).await {
         Ok(role) => {
@@ -258,7 +295,7 @@ async fn delete_role(
 
             HttpResponse::Ok().json(role)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -272,12 +309,12 @@ async fn count_roles(
     let context = request_context(&req);
     let role_query = match RoleQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.count_roles(query.filter.clone(), role_query).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -296,16 +333,21 @@ struct AssignRoleRequest {
 
 #[post("/assign")]
 async fn assign_role(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<AssignRoleRequest>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Only Admin can assign roles
-    if let Err(err) = check_admin(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+    if let Err(response) = check_role_permission(
+        &req,
+        &user,
+        Some(data.school_id.clone()),
+        "role.assign",
+        &state,
+    )
+    .await
+    {
+        return response;
     }
 
     let service = RoleService::new(postgres_pool(&state));
@@ -319,7 +361,31 @@ async fn assign_role(
         .await
     {
         Ok(assignment) => HttpResponse::Created().json(assignment),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Why a user would or would not hold a permission, for whoever may
+/// assign roles in the school asked about
+#[get("/permissions/explain")]
+async fn explain_permission(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<PermissionExplainQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    if let Err(response) =
+        check_role_permission(&req, &user, query.school_id.clone(), "role.assign", &state).await
+    {
+        return response;
+    }
+
+    match PermissionService::new(postgres_pool(&state))
+        .explain(&query)
+        .await
+    {
+        Ok(decision) => HttpResponse::Ok().json(decision),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -338,7 +404,8 @@ fn blueprint(cfg: &mut web::ServiceConfig) {
                 .service(create_role)
                 .service(update_role)
                 .service(delete_role)
-                .service(assign_role),
+                .service(assign_role)
+                .service(explain_permission),
         );
 }
 
@@ -3,15 +3,25 @@ use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responde
 use crate::{
     config::state::AppState,
     domain::{
+        audit_log::AuditSeverity,
         auth_user::AuthUserDto,
         school::{School, SchoolAcademicRequest, SchoolPartial},
+        tenant::TenantEntity,
+        two_factor::SchoolTwoFactorPolicy,
     },
     errors::AppError,
+    guards::role_guard::{
+        check_permission, check_school_access, require_entity_permission, require_tenant_access,
+    },
     models::{api_request_model::RequestQuery, id_model::IdType},
     repositories::user_repo::UserRepo,
     services::{
-        event_service::EventService, school_service::SchoolService, user_service::UserService,
+        audit_log_service::AuditLogService, event_service::EventService,
+        permission_service::PermissionService, school_service::SchoolService,
+        tenant_service::TenantService, two_factor_service::TwoFactorService,
+        user_service::UserService,
     },
+    utils::object_id::ObjectId,
 };
 
 #[get("")]
@@ -23,7 +33,7 @@ async fn get_all_schools(
 
     match service.get_all_for_query(&query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -34,7 +44,7 @@ async fn get_school_by_id(path: web::Path<String>, state: web::Data<AppState>) -
 
     match service.find_one(Some(&id), None).await {
         Ok(school) => HttpResponse::Ok().json(school),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -47,22 +57,24 @@ async fn get_school_by_match(
 
     match service.find_by_request_query(&query).await {
         Ok(school) => HttpResponse::Ok().json(school),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("")]
 async fn create_school(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<School>,
     state: web::Data<AppState>,
 ) -> impl Responder {
     let logged_user = user.into_inner();
 
-    if let Err(err) = crate::guards::role_guard::check_admin_or_staff(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+    let permission_service = PermissionService::for_request(&req, &state.pg.pool);
+    if let Err(err) =
+        check_permission(&logged_user, None, "school.create", &permission_service).await
+    {
+        return AppError::forbidden(err).to_response();
     }
 
     let service = SchoolService::new(&state.pg.pool);
@@ -87,11 +99,7 @@ async fn create_school(
 
             let school_id = match school.id {
                 Some(id) => IdType::ObjectId(id),
-                None => {
-                    return HttpResponse::BadRequest().json(AppError {
-                        message: "Failed to get school id".into(),
-                    })
-                }
+                None => return AppError::internal("Failed to get school id").to_response(),
             };
 
             let user_repo = UserRepo::new(&state.pg.pool);
@@ -102,7 +110,7 @@ async fn create_school(
             match user_service.add_school_to_user(&user_id, &school_id).await {
                 Ok(_) => (),
                 Err(err) => {
-                    return HttpResponse::BadRequest().json(err);
+                    return err.to_response();
                 }
             }
 
@@ -112,7 +120,7 @@ async fn create_school(
             {
                 Ok(token) => token,
                 Err(err) => {
-                    return HttpResponse::BadRequest().json(err);
+                    return err.to_response();
                 }
             };
 
@@ -124,18 +132,24 @@ async fn create_school(
 
             HttpResponse::Created().json(school_json)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[put("/{id}")]
 async fn update_school(
-    _user: web::ReqData<AuthUserDto>,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     data: web::Json<SchoolPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let id = IdType::from_string(path.into_inner());
+    let school_id = path.into_inner();
+    let tenant_service = TenantService::new(&state.pg.pool);
+    if let Err(err) = check_school_access(&user, &school_id, &tenant_service).await {
+        return AppError::forbidden(err).to_response();
+    }
+
+    let id = IdType::from_string(school_id);
     let service = SchoolService::new(&state.pg.pool);
 
     match service.update(&id, &data.into_inner()).await {
@@ -158,17 +172,23 @@ async fn update_school(
 
             HttpResponse::Ok().json(school)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[delete("/{id}")]
 async fn delete_school(
-    _user: web::ReqData<AuthUserDto>,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let id = IdType::from_string(path.into_inner());
+    let school_id = path.into_inner();
+    let tenant_service = TenantService::new(&state.pg.pool);
+    if let Err(err) = check_school_access(&user, &school_id, &tenant_service).await {
+        return AppError::forbidden(err).to_response();
+    }
+
+    let id = IdType::from_string(school_id);
     let service = SchoolService::new(&state.pg.pool);
 
     match service.delete(&id).await {
@@ -191,7 +211,7 @@ async fn delete_school(
 
             HttpResponse::Ok().json(school)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -204,18 +224,24 @@ async fn count_schools(
 
     match service.count_for_query(&query).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[get("/{id}/search/members")]
 async fn search_school_members(
-    _req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     query: web::Query<RequestQuery>,
     state: web::Data<AppState>,
 ) -> impl Responder {
     let school_id = path.into_inner();
+    let tenant_service = TenantService::new(&state.pg.pool);
+    if let Err(err) =
+        require_tenant_access(&user, TenantEntity::School, &school_id, &tenant_service).await
+    {
+        return AppError::forbidden(err).to_response();
+    }
 
     // Get school to verify it exists and get database name
     let school_service = SchoolService::new(&state.pg.pool);
@@ -224,7 +250,7 @@ async fn search_school_members(
         .await
     {
         Ok(school) => school,
-        Err(err) => return HttpResponse::NotFound().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match school_service
@@ -232,7 +258,7 @@ async fn search_school_members(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -245,23 +271,17 @@ async fn refresh_school_token(req: HttpRequest, state: web::Data<AppState>) -> i
         Some(hv) => match hv.to_str() {
             Ok(s) => s.trim().to_string(),
             Err(_) => {
-                return HttpResponse::Unauthorized().json(AppError {
-                    message: "Invalid School-Token header format".to_string(),
-                })
+                return AppError::unauthorized("Invalid School-Token header format").to_response()
             }
         },
-        None => {
-            return HttpResponse::Unauthorized().json(AppError {
-                message: "Missing School-Token header".to_string(),
-            })
-        }
+        None => return AppError::unauthorized("Missing School-Token header").to_response(),
     };
 
     match school_service.refresh_school_token(&token).await {
         Ok(new_token) => HttpResponse::Ok().json(serde_json::json!({
             "schoolAccessToken": new_token
         })),
-        Err(error) => HttpResponse::Unauthorized().json(error),
+        Err(error) => error.to_response(),
     }
 }
 
@@ -276,12 +296,11 @@ async fn setup_school_academics(
 
     let target_school_id_str = path.into_inner();
 
+    let tenant_service = TenantService::new(&state.pg.pool);
     if let Err(err) =
-        crate::guards::role_guard::check_school_access(&logged_user, &target_school_id_str)
+        check_school_access(&logged_user, &target_school_id_str, &tenant_service).await
     {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+        return AppError::forbidden(err.to_string()).to_response();
     }
 
     let service = SchoolService::new(&state.pg.pool);
@@ -322,7 +341,67 @@ async fn setup_school_academics(
 
             HttpResponse::Ok().json(response)
         }
-        Err(e) => HttpResponse::BadRequest().json(e),
+        Err(e) => e.to_response(),
+    }
+}
+
+/// Require every admin and staff member of the school to sign in with a
+/// second factor. Only admins, or a role granted `school.security.manage`,
+/// may change it, and every change is audited.
+#[put("/{id}/two-factor")]
+async fn set_school_two_factor_policy(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    data: web::Json<SchoolTwoFactorPolicy>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = path.into_inner();
+    let permission_service = PermissionService::for_request(&req, &state.pg.pool);
+    if let Err(err) = require_entity_permission(
+        &user,
+        TenantEntity::School,
+        &school_id,
+        "school.security.manage",
+        &permission_service,
+    )
+    .await
+    {
+        return err.to_response();
+    }
+    let school_oid = match ObjectId::parse_str(&school_id) {
+        Ok(id) => id,
+        Err(_) => return AppError::bad_request("Invalid school id").to_response(),
+    };
+
+    let policy = data.into_inner();
+    let service = TwoFactorService::new(&state.pg.pool);
+    match service
+        .set_school_policy(&school_id, policy.require_two_factor)
+        .await
+    {
+        Ok(()) => {
+            let action = if policy.require_two_factor {
+                "school.two_factor.require"
+            } else {
+                "school.two_factor.relax"
+            };
+            AuditLogService::new(&state.pg.pool)
+                .log_event(
+                    school_oid,
+                    &user,
+                    action,
+                    "school",
+                    school_oid,
+                    None,
+                    None,
+                    Some(AuditSeverity::WARNING),
+                )
+                .await
+                .ok();
+            HttpResponse::Ok().json(policy)
+        }
+        Err(err) => err.to_response(),
     }
 }
 
@@ -339,6 +418,7 @@ pub fn init(cfg: &mut web::ServiceConfig) {
             .service(create_school)
             .service(update_school)
             .service(delete_school)
-            .service(setup_school_academics),
+            .service(setup_school_academics)
+            .service(set_school_two_factor_policy),
     );
 }
@@ -1,2 +1,3 @@
 pub mod school_class_timetable;
+pub mod school_room;
 pub mod school_timetable;
@@ -1,12 +1,29 @@
-use actix_web::{post, web, HttpMessage, HttpResponse, Responder};
+use actix_web::{get, post, put, web, HttpMessage, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
+    domain::{
+        timetable_solver::{SubjectSchedulingRule, TimetableSolveRequest},
+        timetable_validation::TimetableValidationQuery,
+    },
     models::{id_model::IdType, school_token_model::SchoolToken},
-    services::{class_timetable_service::ClassTimetableService, event_service::EventService},
-    utils::request_context::postgres_pool,
+    services::{
+        class_timetable_service::ClassTimetableService, event_service::EventService,
+        timetable_validation_service::TimetableValidationService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
 };
 
+fn school_id_for(req: &HttpRequest) -> Result<ObjectId, HttpResponse> {
+    let Some(claims) = req.extensions().get::<SchoolToken>().cloned() else {
+        return Err(HttpResponse::Unauthorized()
+            .json(serde_json::json!({ "message": "School token required" })));
+    };
+    ObjectId::parse_str(&claims.id).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid school id" }))
+    })
+}
+
 #[post("/generate/{class_id}")]
 async fn generate_timetable(
     req: actix_web::HttpRequest,
@@ -16,25 +33,138 @@ async fn generate_timetable(
     let school_claims = match req.extensions().get::<SchoolToken>() {
         Some(claims) => claims.clone(),
         None => {
-            return HttpResponse::Unauthorized().json(serde_json::json!({ "message": "School token required" }))
+            return HttpResponse::Unauthorized()
+                .json(serde_json::json!({ "message": "School token required" }))
         }
     };
 
     let service = ClassTimetableService::new(postgres_pool(&state));
     let class_id = IdType::from_string(path.into_inner());
 
-    match service.generate_timetable(&class_id, &state, &Some(school_claims)).await {
+    match service
+        .generate_timetable(&class_id, &Some(school_claims))
+        .await
+    {
         Ok(timetable) => {
             let timetable_clone = timetable.clone();
             let state_clone = state.clone();
             actix_rt::spawn(async move {
                 if let Some(id) = timetable_clone.id {
-                    EventService::broadcast_created(&state_clone, "class_timetable", &id.to_hex(), None, &timetable_clone).await;
+                    EventService::broadcast_created(
+                        &state_clone,
+                        "class_timetable",
+                        &id.to_hex(),
+                        None,
+                        &timetable_clone,
+                    )
+                    .await;
                 }
             });
             HttpResponse::Created().json(timetable)
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Solve the timetables of many classes at once. Lessons that cannot be
+/// placed are returned in `unplaced` with the constraint that blocked them.
+#[post("/solve")]
+async fn solve_timetables(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<TimetableSolveRequest>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = ClassTimetableService::new(postgres_pool(&state));
+    match service.solve_term(&school_id, body.into_inner()).await {
+        Ok(result) => {
+            if result.saved {
+                let timetables = result.timetables.clone();
+                let state_clone = state.clone();
+                actix_rt::spawn(async move {
+                    for timetable in timetables {
+                        if let Some(id) = timetable.id {
+                            EventService::broadcast_updated(
+                                &state_clone,
+                                "class_timetable",
+                                &id.to_hex(),
+                                Some(school_id.to_hex()),
+                                &timetable,
+                            )
+                            .await;
+                        }
+                    }
+                });
+            }
+            HttpResponse::Ok().json(result)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Clash report for the school's class timetables of one term
+#[get("/validation")]
+async fn validate_timetables(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<TimetableValidationQuery>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = TimetableValidationService::new(postgres_pool(&state));
+    match service.validate_term(&school_id, query.into_inner()).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/subject-rules")]
+async fn get_subject_rules(req: HttpRequest, state: web::Data<AppState>) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = ClassTimetableService::new(postgres_pool(&state));
+    match service.get_subject_rules(&school_id).await {
+        Ok(rules) => HttpResponse::Ok().json(rules),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/subject-rules/{class_subject_id}")]
+async fn upsert_subject_rule(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<SubjectSchedulingRule>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let class_subject_id = match ObjectId::parse_str(path.into_inner()) {
+        Ok(id) => id,
+        Err(_) => {
+            return HttpResponse::BadRequest()
+                .json(serde_json::json!({ "message": "Invalid class_subject_id" }))
+        }
+    };
+
+    let service = ClassTimetableService::new(postgres_pool(&state));
+    match service
+        .upsert_subject_rule(&school_id, &class_subject_id, body.into_inner())
+        .await
+    {
+        Ok(rule) => HttpResponse::Ok().json(rule),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -42,6 +172,10 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/school/class-timetables")
             .wrap(crate::middleware::school_token_middleware::SchoolTokenMiddleware)
-            .service(generate_timetable),
+            .service(generate_timetable)
+            .service(solve_timetables)
+            .service(validate_timetables)
+            .service(get_subject_rules)
+            .service(upsert_subject_rule),
     );
 }
@@ -0,0 +1,193 @@
+use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::{
+    config::state::AppState,
+    domain::school_room::{SchoolRoom, SchoolRoomPartial},
+    models::school_token_model::SchoolToken,
+    services::{event_service::EventService, school_room_service::SchoolRoomService},
+    utils::{object_id::ObjectId, request_context::postgres_pool},
+};
+
+#[derive(Debug, Deserialize)]
+struct RoomQuery {
+    room_type: Option<String>,
+}
+
+fn school_id_for(req: &HttpRequest) -> Result<ObjectId, HttpResponse> {
+    let Some(claims) = req.extensions().get::<SchoolToken>().cloned() else {
+        return Err(HttpResponse::Unauthorized()
+            .json(serde_json::json!({ "message": "School token required" })));
+    };
+    ObjectId::parse_str(&claims.id).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid school id" }))
+    })
+}
+
+fn room_id_for(raw: String) -> Result<ObjectId, HttpResponse> {
+    ObjectId::parse_str(raw).map_err(|_| {
+        HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid room id" }))
+    })
+}
+
+#[get("")]
+async fn get_rooms(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<RoomQuery>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = SchoolRoomService::new(postgres_pool(&state));
+    match service
+        .get_all(&school_id, query.into_inner().room_type)
+        .await
+    {
+        Ok(rooms) => HttpResponse::Ok().json(rooms),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/{id}")]
+async fn get_room(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let id = match room_id_for(path.into_inner()) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = SchoolRoomService::new(postgres_pool(&state));
+    match service.find_one(&school_id, &id).await {
+        Ok(room) => HttpResponse::Ok().json(room),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("")]
+async fn create_room(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<SchoolRoom>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = SchoolRoomService::new(postgres_pool(&state));
+    match service.create(&school_id, body.into_inner()).await {
+        Ok(room) => {
+            let room_clone = room.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                if let Some(id) = room_clone.id {
+                    EventService::broadcast_created(
+                        &state_clone,
+                        "school_room",
+                        &id.to_hex(),
+                        Some(school_id.to_hex()),
+                        &room_clone,
+                    )
+                    .await;
+                }
+            });
+            HttpResponse::Created().json(room)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[put("/{id}")]
+async fn update_room(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<SchoolRoomPartial>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let id = match room_id_for(path.into_inner()) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = SchoolRoomService::new(postgres_pool(&state));
+    match service.update(&school_id, &id, body.into_inner()).await {
+        Ok(room) => {
+            let room_clone = room.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                EventService::broadcast_updated(
+                    &state_clone,
+                    "school_room",
+                    &id.to_hex(),
+                    Some(school_id.to_hex()),
+                    &room_clone,
+                )
+                .await;
+            });
+            HttpResponse::Ok().json(room)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/{id}")]
+async fn delete_room(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let id = match room_id_for(path.into_inner()) {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = SchoolRoomService::new(postgres_pool(&state));
+    match service.delete(&school_id, &id).await {
+        Ok(room) => {
+            let room_clone = room.clone();
+            let state_clone = state.clone();
+            actix_rt::spawn(async move {
+                EventService::broadcast_deleted(
+                    &state_clone,
+                    "school_room",
+                    &id.to_hex(),
+                    Some(school_id.to_hex()),
+                    &room_clone,
+                )
+                .await;
+            });
+            HttpResponse::Ok().json(room)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/school/rooms")
+            .wrap(crate::middleware::school_token_middleware::SchoolTokenMiddleware)
+            .service(get_rooms)
+            .service(get_room)
+            .service(create_room)
+            .service(update_room)
+            .service(delete_room),
+    );
+}
@@ -24,7 +24,7 @@ async fn get_all_timetables(
     let service = SchoolTimetableService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -38,7 +38,7 @@ async fn get_timetable_by_id(
     let service = SchoolTimetableService::new(postgres_pool(&state));
     match service.find_one(Some(&id), None).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -54,7 +54,7 @@ async fn get_timetable_by_flied(
     let sid = query.school_id.as_deref().or(school_id.as_deref());
     match service.find_one(None, sid).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -72,7 +72,7 @@ async fn get_by_school_and_academic_year(
     if let (Ok(sid), Ok(ayid)) = (school_id, academic_year_id) {
         match service.find_by_school_and_academic_year(&sid, &Some(ayid)).await {
             Ok(data) => HttpResponse::Ok().json(data),
-            Err(err) => HttpResponse::NotFound().json(err),
+            Err(err) => err.to_response(),
         }
     } else {
         HttpResponse::BadRequest().json(serde_json::json!({ "message": "Invalid IDs provided" }))
@@ -96,7 +96,7 @@ async fn get_current_timetable(state: web::Data<AppState>, req: HttpRequest) ->
         .await
     {
         Ok(v) => v,
-        Err(e) => return HttpResponse::BadRequest().json(e),
+        Err(e) => return e.to_response(),
     };
 
     let school_id = match ObjectId::from_str(&school_claims.id) {
@@ -106,7 +106,7 @@ async fn get_current_timetable(state: web::Data<AppState>, req: HttpRequest) ->
 
     match service.find_by_school_and_academic_year(&school_id, &education_year.id).await {
         Ok(timetable) => HttpResponse::Ok().json(timetable),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -129,7 +129,7 @@ async fn create_timetable(
             });
             HttpResponse::Created().json(tt)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -153,7 +153,7 @@ async fn generate_timetable(
         .await
     {
         Ok(v) => v,
-        Err(e) => return Ok(HttpResponse::BadRequest().json(e)),
+        Err(e) => return Ok(e.to_response()),
     };
 
     let education_year_id = IdType::from_object_id(education_year.id.unwrap());
@@ -171,7 +171,7 @@ async fn generate_timetable(
             });
             Ok(HttpResponse::Created().json(timetable))
         }
-        Err(message) => Ok(HttpResponse::BadRequest().json(message)),
+        Err(err) => Ok(err.to_response()),
     }
 }
 
@@ -196,7 +196,7 @@ async fn update_timetable(
             });
             HttpResponse::Ok().json(tt)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -224,7 +224,7 @@ async fn delete_timetable(
             }
             HttpResponse::Ok().json(res)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -25,7 +25,7 @@ async fn get_all_school_staff(
     let context = request_context(&req);
     let staff_query = match SchoolStaffQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -33,7 +33,7 @@ async fn get_all_school_staff(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -50,7 +50,7 @@ async fn get_school_staff_by_id(
 
     match service.find_one(Some(&id), staff_query).await {
         Ok(staff) => HttpResponse::Ok().json(staff),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -64,12 +64,12 @@ async fn get_school_staff_by_match(
     let context = request_context(&req);
     let staff_query = match SchoolStaffQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, staff_query).await {
         Ok(staff) => HttpResponse::Ok().json(staff),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -88,7 +88,7 @@ async fn create_school_staff(
     if staff.creator_id.is_none() {
         let user_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         staff.creator_id = Some(user_id);
     }
@@ -96,7 +96,7 @@ async fn create_school_staff(
         if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
             staff.school_id = match IdType::from_string(school_id).to_object_id() {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -121,7 +121,7 @@ async fn create_school_staff(
 
             HttpResponse::Created().json(staff)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -156,7 +156,7 @@ async fn update_school_staff(
 
             HttpResponse::Ok().json(staff)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -190,7 +190,7 @@ async fn delete_school_staff(
 
             HttpResponse::Ok().json(staff)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -204,12 +204,12 @@ async fn count_school_staff(
     let context = request_context(&req);
     let staff_query = match SchoolStaffQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.count_staff(query.filter.clone(), staff_query).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
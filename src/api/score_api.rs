@@ -28,7 +28,7 @@ async fn get_all_scores(
     let context = request_context(&req);
     let score_query = match ScoreQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -36,7 +36,7 @@ async fn get_all_scores(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -51,7 +51,7 @@ async fn get_score_by_id(
 
     match service.find_one(&id).await {
         Ok(score) => HttpResponse::Ok().json(score),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -65,19 +65,19 @@ async fn get_student_exam_scores(
 
     let student_id = match parse_object_id_value(&student_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let exam_id = match parse_object_id_value(&exam_id_str) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let service = ScoreService::new(postgres_pool(&state));
 
     match service.get_student_exam_scores(&student_id, &exam_id).await {
         Ok(scores) => HttpResponse::Ok().json(scores),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -89,14 +89,14 @@ async fn get_score_audit_logs(
 ) -> impl Responder {
     let score_id = match parse_object_id_value(&path.into_inner()) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let service = ScoreService::new(postgres_pool(&state));
 
     match service.get_audit_logs(&score_id).await {
         Ok(logs) => HttpResponse::Ok().json(logs),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -115,7 +115,7 @@ async fn create_score(
     if score.entered_by.is_none() {
         let user_id = match parse_object_id_value(&user.id) {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         score.entered_by = Some(user_id);
     }
@@ -123,7 +123,7 @@ async fn create_score(
         if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
             score.school_id = match parse_object_id_value(&school_id) {
                 Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
+                Err(err) => return err.to_response(),
             };
         }
     }
@@ -147,7 +147,7 @@ async fn create_score(
 
             HttpResponse::Created().json(score)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -163,7 +163,7 @@ async fn create_bulk_scores(
 
     let user_id = match parse_object_id_value(&user.id) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let mut scores = data.into_inner();
@@ -179,7 +179,7 @@ async fn create_bulk_scores(
             {
                 score.school_id = match parse_object_id_value(&school_id) {
                     Ok(id) => Some(id),
-                    Err(err) => return HttpResponse::BadRequest().json(err),
+                    Err(err) => return err.to_response(),
                 };
             }
         }
@@ -187,7 +187,7 @@ async fn create_bulk_scores(
 
     match service.create_many(scores).await {
         Ok(created_scores) => HttpResponse::Created().json(created_scores),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -204,7 +204,7 @@ async fn update_score(
 
     let user_id = match parse_object_id_value(&user.id) {
         Ok(id) => id,
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     let update_data = data.into_inner();
@@ -232,7 +232,7 @@ async fn update_score(
 
             HttpResponse::Ok().json(score)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -265,7 +265,7 @@ async fn delete_score(
 
             HttpResponse::Ok().json(score)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
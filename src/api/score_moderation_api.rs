@@ -0,0 +1,169 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+
+use crate::{
+    config::state::AppState,
+    domain::{auth_user::AuthUserDto, score_moderation::ModerationRequest},
+    errors::AppError,
+    guards::role_guard::{require_request_permission, require_request_school},
+    services::{
+        audit_log_service::AuditLogService, event_service::EventService,
+        permission_service::PermissionService, score_moderation_service::ScoreModerationService,
+        tenant_service::TenantService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
+};
+
+async fn school_id_for(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<ObjectId, HttpResponse> {
+    let tenant_service = TenantService::new(postgres_pool(state));
+    let school_id = require_request_school(req, user, &tenant_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    ObjectId::parse_str(&school_id)
+        .map_err(|_| AppError::bad_request("Invalid school id").to_response())
+}
+
+/// Moderation status of every subject of the exam, with its history
+#[get("/exam/{exam_id}")]
+async fn get_exam_moderation(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    let school_id =
+        match require_request_permission(&req, &user, "moderation.read", &permission_service).await
+        {
+            Ok(id) => id,
+            Err(err) => return err.to_response(),
+        };
+    let Ok(school_id) = ObjectId::parse_str(&school_id) else {
+        return AppError::bad_request("Invalid school id").to_response();
+    };
+    let Ok(exam_id) = ObjectId::parse_str(path.into_inner()) else {
+        return AppError::bad_request("Invalid exam id").to_response();
+    };
+
+    match ScoreModerationService::new(postgres_pool(&state))
+        .exam(&school_id, &exam_id)
+        .await
+    {
+        Ok(moderation) => HttpResponse::Ok().json(moderation),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Submit, review, return, approve or reopen one subject's marks
+#[post("")]
+async fn moderate_scores(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    data: web::Json<ModerationRequest>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let request = data.into_inner();
+    let action = request.action;
+
+    match ScoreModerationService::new(postgres_pool(&state))
+        .act(&school_id, &user, request)
+        .await
+    {
+        Ok(moderation) => {
+            if let Some(id) = moderation.id {
+                AuditLogService::new(postgres_pool(&state))
+                    .log_event(
+                        school_id,
+                        &user,
+                        &format!("score_moderation.{}", action.as_str().to_lowercase()),
+                        "score_moderation",
+                        id,
+                        None,
+                        None,
+                        None,
+                    )
+                    .await
+                    .ok();
+                EventService::broadcast_updated(
+                    &state,
+                    "score_moderation",
+                    &id.to_hex(),
+                    Some(school_id.to_hex()),
+                    &moderation,
+                )
+                .await;
+            }
+            HttpResponse::Ok().json(moderation)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Finalize the exam's results and release them to students and parents
+#[post("/exam/{exam_id}/publish")]
+async fn publish_results(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let Ok(exam_id) = ObjectId::parse_str(path.into_inner()) else {
+        return AppError::bad_request("Invalid exam id").to_response();
+    };
+
+    match ScoreModerationService::new(postgres_pool(&state))
+        .publish_results(&school_id, &user, &exam_id)
+        .await
+    {
+        Ok(moderation) => {
+            AuditLogService::new(postgres_pool(&state))
+                .log_event(
+                    school_id,
+                    &user,
+                    "exam.results_publish",
+                    "exam",
+                    exam_id,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .ok();
+            EventService::broadcast_updated(
+                &state,
+                "exam",
+                &exam_id.to_hex(),
+                Some(school_id.to_hex()),
+                &moderation,
+            )
+            .await;
+            HttpResponse::Ok().json(moderation)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_exam_moderation)
+            .service(moderate_scores)
+            .service(publish_results),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "score-moderation", blueprint);
+}
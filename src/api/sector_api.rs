@@ -22,7 +22,7 @@ async fn get_all_sectors(
     let service = SectorService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip, Some(&query)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -32,7 +32,7 @@ async fn get_sector_by_id(path: web::Path<String>, state: web::Data<AppState>) -
     let service = SectorService::new(postgres_pool(&state));
     match service.find_one(Some(&id), None).await {
         Ok(sector) => HttpResponse::Ok().json(sector),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -44,7 +44,7 @@ async fn get_sector_by_match(
     let service = SectorService::new(postgres_pool(&state));
     match service.find_one(None, Some(&query)).await {
         Ok(sector) => HttpResponse::Ok().json(sector),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -68,7 +68,7 @@ async fn create_sector(
             });
             HttpResponse::Created().json(sector)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -94,7 +94,7 @@ async fn update_sector(
             });
             HttpResponse::Ok().json(sector)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -119,7 +119,7 @@ async fn delete_sector(
             });
             HttpResponse::Ok().json(sector)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -131,7 +131,7 @@ async fn count_sectors(
     let service = SectorService::new(postgres_pool(&state));
     match service.count(query.filter.clone(), Some(&query)).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -144,7 +144,7 @@ async fn get_sectors_by_ids(
     let ids: Vec<IdType> = body.ids.iter().map(|id| IdType::from_string(id.clone())).collect();
     match service.find_by_ids(ids).await {
         Ok(sectors) => HttpResponse::Ok().json(sectors),
-        Err(error) => HttpResponse::NotFound().json(error),
+        Err(error) => error.to_response(),
     }
 }
 
@@ -5,17 +5,42 @@ use crate::{
     domain::{
         auth_user::AuthUserDto,
         student::{Student, StudentPartial},
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    guards::role_guard::{
+        require_entity_permission, require_permission, require_request_permission,
     },
-    guards::role_guard::check_admin_staff_or_teacher,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         event_service::EventService,
+        permission_service::PermissionService,
         student_service::{StudentQuery, StudentService},
     },
     utils::request_context::{postgres_pool, request_context},
 };
 
+/// Require the caller may manage the student in the school they belong to
+async fn check_student_manage(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    student_id: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_entity_permission(
+        user,
+        TenantEntity::Student,
+        student_id,
+        "student.manage",
+        &permission_service,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| err.to_response())
+}
+
 #[get("")]
 async fn get_all_students(
     req: HttpRequest,
@@ -26,7 +51,7 @@ async fn get_all_students(
     let context = request_context(&req);
     let student_query = match StudentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -34,7 +59,7 @@ async fn get_all_students(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -48,7 +73,7 @@ async fn get_all_students_with_relations(
     let context = request_context(&req);
     let student_query = match StudentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -56,7 +81,7 @@ async fn get_all_students_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -76,7 +101,7 @@ async fn get_student_by_id_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -93,7 +118,7 @@ async fn get_student_by_id(
 
     match service.find_one(Some(&id), student_query).await {
         Ok(student) => HttpResponse::Ok().json(student),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -107,12 +132,12 @@ async fn get_student_by_match(
     let context = request_context(&req);
     let student_query = match StudentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, student_query).await {
         Ok(student) => HttpResponse::Ok().json(student),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -126,12 +151,12 @@ async fn get_student_by_other_match(
     let context = request_context(&req);
     let student_query = match StudentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one_with_relations(None, student_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -142,34 +167,36 @@ async fn create_student(
     data: web::Json<Student>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin, Staff, or Teacher can create students
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
-    }
-
     let service = StudentService::new(postgres_pool(&state));
-    let context = request_context(&req);
 
     let mut student = data.clone();
 
     if data.creator_id.is_none() {
         student.creator_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => Some(id),
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
     }
 
-    if student.school_id.is_none() {
-        let school_id = context.school_id.or_else(|| user.current_school_id.clone());
-        if let Some(school_id) = school_id {
-            student.school_id = match IdType::from_string(school_id).to_object_id() {
-                Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
-            };
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    let school_id = match student.school_id {
+        Some(school_id) => require_permission(
+            &user,
+            &school_id.to_hex(),
+            "student.manage",
+            &permission_service,
+        )
+        .await
+        .map(|_| school_id.to_hex())
+        .map_err(AppError::forbidden),
+        None => {
+            require_request_permission(&req, &user, "student.manage", &permission_service).await
         }
-    }
+    };
+    student.school_id = match school_id.and_then(|id| IdType::from_string(id).to_object_id()) {
+        Ok(id) => Some(id),
+        Err(err) => return err.to_response(),
+    };
 
     match service.create(student, Some(&state)).await {
         Ok(student) => {
@@ -190,7 +217,7 @@ async fn create_student(
 
             HttpResponse::Created().json(student)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -202,14 +229,12 @@ async fn update_student(
     data: web::Json<StudentPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin, Staff, or Teacher can update students
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
+    let student_id = path.into_inner();
+    if let Err(response) = check_student_manage(&req, &user, &student_id, &state).await {
+        return response;
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(student_id);
     let service = StudentService::new(postgres_pool(&state));
 
     match service.update(&id, &data.into_inner()).await {
@@ -231,7 +256,7 @@ async fn update_student(
 
             HttpResponse::Ok().json(student)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -242,14 +267,12 @@ async fn delete_student(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin, Staff, or Teacher can delete students
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
+    let student_id = path.into_inner();
+    if let Err(response) = check_student_manage(&req, &user, &student_id, &state).await {
+        return response;
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(student_id);
     let service = StudentService::new(postgres_pool(&state));
     let user_id = IdType::from_string(&user.id);
 
@@ -272,30 +295,28 @@ async fn delete_student(
 
             HttpResponse::Ok().json(student)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("/{id}/restore")]
 async fn restore_student(
-    _req: HttpRequest,
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin or Staff can restore students
-    if let Err(err) = check_admin_staff_or_teacher(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
+    let student_id = path.into_inner();
+    if let Err(response) = check_student_manage(&req, &user, &student_id, &state).await {
+        return response;
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(student_id);
     let service = StudentService::new(postgres_pool(&state));
 
     match service.restore(&id).await {
         Ok(student) => HttpResponse::Ok().json(student),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -309,7 +330,7 @@ async fn count_students(
     let context = request_context(&req);
     let student_query = match StudentQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -317,7 +338,7 @@ async fn count_students(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -0,0 +1,408 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+
+use crate::{
+    config::state::AppState,
+    domain::role::PermissionTarget,
+    domain::{
+        auth_user::AuthUserDto,
+        teacher_cover::{ConfirmCover, CreateTeacherAbsence, DateRangeQuery},
+    },
+    errors::AppError,
+    guards::role_guard::{
+        require_request_permission, require_request_school, require_scoped_permission,
+    },
+    services::{
+        audit_log_service::AuditLogService, event_service::EventService,
+        permission_service::PermissionService, teacher_cover_service::TeacherCoverService,
+        tenant_service::TenantService,
+    },
+    utils::{object_id::ObjectId, request_context::postgres_pool},
+};
+
+async fn school_id_for(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<ObjectId, HttpResponse> {
+    let tenant_service = TenantService::new(postgres_pool(state));
+    let school_id = require_request_school(req, user, &tenant_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    ObjectId::parse_str(&school_id)
+        .map_err(|_| AppError::bad_request("Invalid school id").to_response())
+}
+
+/// The request's school, once the caller may manage covers there
+async fn admin_school_id(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<ObjectId, HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    let school_id = require_request_permission(req, user, "cover.manage", &permission_service)
+        .await
+        .map_err(|err| err.to_response())?;
+    ObjectId::parse_str(&school_id)
+        .map_err(|_| AppError::bad_request("Invalid school id").to_response())
+}
+
+/// Let the caller read schedules in the school: school-wide, or for a class
+/// they teach when `class_id` is given
+async fn check_schedule_access(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    school_id: &ObjectId,
+    class_id: Option<&ObjectId>,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    let mut target = PermissionTarget::school(school_id.to_hex());
+    if let Some(class_id) = class_id {
+        target = target.class(class_id.to_hex());
+    }
+    require_scoped_permission(user, "schedule.read", &target, &permission_service)
+        .await
+        .map_err(|message| AppError::forbidden(message).to_response())
+}
+
+fn path_id(raw: String, label: &str) -> Result<ObjectId, HttpResponse> {
+    ObjectId::parse_str(raw)
+        .map_err(|_| AppError::bad_request(format!("Invalid {} id", label)).to_response())
+}
+
+// ========== ABSENCES ==========
+
+#[get("/absences")]
+async fn get_absences(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    query: web::Query<DateRangeQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    match service.list_absences(&school_id, &query).await {
+        Ok(absences) => HttpResponse::Ok().json(absences),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[post("/absences")]
+async fn create_absence(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    body: web::Json<CreateTeacherAbsence>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    let created_by = ObjectId::parse_str(&user.id).ok();
+    match service
+        .create_absence(&school_id, body.into_inner(), created_by.as_ref())
+        .await
+    {
+        Ok(absence) => {
+            let Some(id) = absence.id else {
+                return HttpResponse::Created().json(absence);
+            };
+            AuditLogService::new(postgres_pool(&state))
+                .log_event(
+                    school_id,
+                    &user,
+                    "teacher_absence.create",
+                    "teacher_absence",
+                    id,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .ok();
+
+            // The plan carries the suggestions the admin picks covers from
+            match service.cover_plan(&school_id, &id).await {
+                Ok(plan) => HttpResponse::Created().json(plan),
+                Err(_) => HttpResponse::Created().json(absence),
+            }
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/absences/{id}")]
+async fn get_cover_plan(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let id = match path_id(path.into_inner(), "absence") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    match service.cover_plan(&school_id, &id).await {
+        Ok(plan) => HttpResponse::Ok().json(plan),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/absences/{id}")]
+async fn cancel_absence(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let id = match path_id(path.into_inner(), "absence") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    match service.cancel_absence(&school_id, &id).await {
+        Ok(absence) => {
+            AuditLogService::new(postgres_pool(&state))
+                .log_event(
+                    school_id,
+                    &user,
+                    "teacher_absence.cancel",
+                    "teacher_absence",
+                    id,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .ok();
+            HttpResponse::Ok().json(absence)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+// ========== COVERS ==========
+
+#[post("/absences/{id}/covers")]
+async fn confirm_cover(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    body: web::Json<ConfirmCover>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let absence_id = match path_id(path.into_inner(), "absence") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    let confirmed_by = ObjectId::parse_str(&user.id).ok();
+    match service
+        .confirm_cover(
+            &school_id,
+            &absence_id,
+            body.into_inner(),
+            confirmed_by.as_ref(),
+        )
+        .await
+    {
+        Ok((cover, cover_user_id)) => {
+            let Some(cover_id) = cover.id else {
+                return HttpResponse::Created().json(cover);
+            };
+            AuditLogService::new(postgres_pool(&state))
+                .log_event(
+                    school_id,
+                    &user,
+                    "teacher_cover.confirm",
+                    "teacher_cover",
+                    cover_id,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .ok();
+
+            if let Some(cover_user_id) = cover_user_id {
+                let cover_clone = cover.clone();
+                let state_clone = state.clone();
+                actix_rt::spawn(async move {
+                    EventService::broadcast_to_user(
+                        &state_clone,
+                        "cover_assigned",
+                        "teacher_cover",
+                        &cover_id.to_hex(),
+                        &cover_user_id,
+                        &cover_clone,
+                    )
+                    .await;
+                });
+            }
+
+            HttpResponse::Created().json(cover)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+#[delete("/covers/{id}")]
+async fn cancel_cover(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match admin_school_id(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let id = match path_id(path.into_inner(), "cover") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    match service.cancel_cover(&school_id, &id).await {
+        Ok(cover) => {
+            AuditLogService::new(postgres_pool(&state))
+                .log_event(
+                    school_id,
+                    &user,
+                    "teacher_cover.cancel",
+                    "teacher_cover",
+                    id,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .ok();
+            HttpResponse::Ok().json(cover)
+        }
+        Err(err) => err.to_response(),
+    }
+}
+
+// ========== DATED SCHEDULES ==========
+
+#[get("/teacher/{teacher_id}/schedule")]
+async fn teacher_schedule(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<DateRangeQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let teacher_id = match path_id(path.into_inner(), "teacher") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    // Teachers always see their own timetable
+    let is_own = match service.teacher_user_id(&school_id, &teacher_id).await {
+        Ok(user_id) => user_id.as_deref() == Some(user.id.as_str()),
+        Err(err) => return err.to_response(),
+    };
+    if !is_own {
+        if let Err(response) = check_schedule_access(&req, &user, &school_id, None, &state).await {
+            return response;
+        }
+    }
+
+    let (from, to) = match TeacherCoverService::resolve_range(&query, Utc::now().date_naive()) {
+        Ok(range) => range,
+        Err(err) => return err.to_response(),
+    };
+
+    match service
+        .teacher_schedule(&school_id, &teacher_id, from, to)
+        .await
+    {
+        Ok(periods) => HttpResponse::Ok().json(periods),
+        Err(err) => err.to_response(),
+    }
+}
+
+#[get("/class/{class_id}/schedule")]
+async fn class_schedule(
+    req: HttpRequest,
+    user: web::ReqData<AuthUserDto>,
+    path: web::Path<String>,
+    query: web::Query<DateRangeQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let school_id = match school_id_for(&req, &user, &state).await {
+        Ok(school_id) => school_id,
+        Err(response) => return response,
+    };
+    let class_id = match path_id(path.into_inner(), "class") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    if let Err(response) =
+        check_schedule_access(&req, &user, &school_id, Some(&class_id), &state).await
+    {
+        return response;
+    }
+    let (from, to) = match TeacherCoverService::resolve_range(&query, Utc::now().date_naive()) {
+        Ok(range) => range,
+        Err(err) => return err.to_response(),
+    };
+
+    let service = TeacherCoverService::new(postgres_pool(&state));
+    match service
+        .class_schedule(&school_id, &class_id, from, to)
+        .await
+    {
+        Ok(periods) => HttpResponse::Ok().json(periods),
+        Err(err) => err.to_response(),
+    }
+}
+
+fn blueprint(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(crate::middleware::jwt_middleware::JwtMiddleware)
+            .service(get_absences)
+            .service(create_absence)
+            .service(get_cover_plan)
+            .service(cancel_absence)
+            .service(confirm_cover)
+            .service(cancel_cover)
+            .service(teacher_schedule)
+            .service(class_schedule),
+    );
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    crate::utils::route_utils::mount_dual_routes(cfg, "teacher-covers", blueprint);
+}
@@ -5,17 +5,42 @@ use crate::{
     domain::{
         auth_user::AuthUserDto,
         teacher::{Teacher, UpdateTeacher},
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    guards::role_guard::{
+        require_entity_permission, require_permission, require_request_permission,
     },
-    guards::role_guard::check_admin_or_staff,
     helpers::event_helpers::get_school_id_from_request,
     models::{api_request_model::RequestQuery, id_model::IdType},
     services::{
         event_service::EventService,
+        permission_service::PermissionService,
         teacher_service::{TeacherQuery, TeacherService},
     },
     utils::request_context::{postgres_pool, request_context},
 };
 
+/// Require the caller may manage teachers in the teacher's school
+async fn check_teacher_manage(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    teacher_id: &str,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    require_entity_permission(
+        user,
+        TenantEntity::Teacher,
+        teacher_id,
+        "teacher.manage",
+        &permission_service,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| err.to_response())
+}
+
 #[get("")]
 async fn get_all_teachers(
     req: HttpRequest,
@@ -26,7 +51,7 @@ async fn get_all_teachers(
     let context = request_context(&req);
     let teacher_query = match TeacherQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -34,7 +59,7 @@ async fn get_all_teachers(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -48,7 +73,7 @@ async fn get_all_teachers_with_relations(
     let context = request_context(&req);
     let teacher_query = match TeacherQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -56,7 +81,7 @@ async fn get_all_teachers_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -76,7 +101,7 @@ async fn get_teacher_by_id_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -93,7 +118,7 @@ async fn get_teacher_by_id(
 
     match service.find_one(Some(&id), teacher_query).await {
         Ok(teacher) => HttpResponse::Ok().json(teacher),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -107,12 +132,12 @@ async fn get_teacher_by_match(
     let context = request_context(&req);
     let teacher_query = match TeacherQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one(None, teacher_query).await {
         Ok(teacher) => HttpResponse::Ok().json(teacher),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -126,12 +151,12 @@ async fn get_teacher_by_other_match(
     let context = request_context(&req);
     let teacher_query = match TeacherQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service.find_one_with_relations(None, teacher_query).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -142,33 +167,37 @@ async fn create_teacher(
     data: web::Json<Teacher>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin or Staff can create teachers
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
-    }
-
     let service = TeacherService::new(postgres_pool(&state));
-    let context = request_context(&req);
 
     let mut teacher = data.clone();
 
     if teacher.creator_id.is_none() {
         let user_id = match IdType::from_string(&user.id).to_object_id() {
             Ok(id) => id,
-            Err(err) => return HttpResponse::BadRequest().json(err),
+            Err(err) => return err.to_response(),
         };
         teacher.creator_id = Some(user_id);
     }
-    if teacher.school_id.is_none() {
-        if let Some(school_id) = context.school_id.or_else(|| user.current_school_id.clone()) {
-            teacher.school_id = match IdType::from_string(school_id).to_object_id() {
-                Ok(id) => Some(id),
-                Err(err) => return HttpResponse::BadRequest().json(err),
-            };
+
+    let permission_service = PermissionService::for_request(&req, postgres_pool(&state));
+    let school_id = match teacher.school_id {
+        Some(school_id) => require_permission(
+            &user,
+            &school_id.to_hex(),
+            "teacher.manage",
+            &permission_service,
+        )
+        .await
+        .map(|_| school_id.to_hex())
+        .map_err(AppError::forbidden),
+        None => {
+            require_request_permission(&req, &user, "teacher.manage", &permission_service).await
         }
-    }
+    };
+    teacher.school_id = match school_id.and_then(|id| IdType::from_string(id).to_object_id()) {
+        Ok(id) => Some(id),
+        Err(err) => return err.to_response(),
+    };
 
     match service.create(teacher, Some(&state)).await {
         Ok(teacher) => {
@@ -190,7 +219,7 @@ async fn create_teacher(
 
             HttpResponse::Created().json(teacher)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -202,14 +231,12 @@ async fn update_teacher(
     data: web::Json<UpdateTeacher>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin or Staff can update teachers
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
+    let teacher_id = path.into_inner();
+    if let Err(response) = check_teacher_manage(&req, &user, &teacher_id, &state).await {
+        return response;
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(teacher_id);
     let service = TeacherService::new(postgres_pool(&state));
 
     match service.update(&id, &data.into_inner()).await {
@@ -232,7 +259,7 @@ async fn update_teacher(
 
             HttpResponse::Ok().json(teacher)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -243,14 +270,12 @@ async fn delete_teacher(
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    // Check permission: Admin or Staff can delete teachers
-    if let Err(err) = check_admin_or_staff(&user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err
-        }));
+    let teacher_id = path.into_inner();
+    if let Err(response) = check_teacher_manage(&req, &user, &teacher_id, &state).await {
+        return response;
     }
 
-    let id = IdType::from_string(path.into_inner());
+    let id = IdType::from_string(teacher_id);
     let service = TeacherService::new(postgres_pool(&state));
 
     match service.delete(&id).await {
@@ -273,7 +298,7 @@ async fn delete_teacher(
 
             HttpResponse::Ok().json(teacher)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -287,7 +312,7 @@ async fn count_teachers(
     let context = request_context(&req);
     let teacher_query = match TeacherQuery::from_request(&query, context.school_id) {
         Ok(query) => Some(query),
-        Err(err) => return HttpResponse::BadRequest().json(err),
+        Err(err) => return err.to_response(),
     };
 
     match service
@@ -295,7 +320,7 @@ async fn count_teachers(
         .await
     {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -1,4 +1,4 @@
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
@@ -6,11 +6,29 @@ use crate::{
         auth_user::AuthUserDto,
         template_subject::{TemplateSubject, TemplateSubjectPartial},
     },
+    errors::AppError,
+    guards::role_guard::check_permission,
     models::{api_request_model::RequestQuery, id_model::IdType},
-    services::{event_service::EventService, template_subject_service::TemplateSubjectService},
+    services::{
+        event_service::EventService, permission_service::PermissionService,
+        template_subject_service::TemplateSubjectService,
+    },
     utils::request_context::postgres_pool,
 };
 
+/// Template subjects are shared by every school, so managing them is a
+/// platform action
+async fn check_manage_access(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    state: &web::Data<AppState>,
+) -> Result<(), HttpResponse> {
+    let permission_service = PermissionService::for_request(req, postgres_pool(state));
+    check_permission(user, None, "template_subject.manage", &permission_service)
+        .await
+        .map_err(|err| AppError::forbidden(err).to_response())
+}
+
 #[get("")]
 async fn get_all_template_subjects(
     query: web::Query<RequestQuery>,
@@ -19,7 +37,7 @@ async fn get_all_template_subjects(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip, Some(&query)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -31,7 +49,7 @@ async fn get_all_template_subjects_with_others(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.get_all_with_other(query.filter.clone(), query.limit, query.skip).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -44,7 +62,7 @@ async fn get_template_subject_by_id(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.find_one_by_id(&id).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -57,7 +75,7 @@ async fn get_template_subject_by_id_others(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.find_one_with_relations(&id).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -70,7 +88,7 @@ async fn get_template_subject_by_code(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.find_one_by_code(&code).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -83,7 +101,7 @@ async fn get_template_subject_by_code_others(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.find_one_with_relations_by_code(&code).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -96,7 +114,7 @@ async fn find_many_by_prerequisite_with_relations(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.find_many_by_prerequisite_with_relations(&id).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -109,20 +127,19 @@ async fn find_many_by_prerequisite(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.find_many_by_prerequisite(&id).await {
         Ok(subject) => HttpResponse::Ok().json(subject),
-        Err(message) => HttpResponse::NotFound().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
 #[post("")]
 async fn create_template_subject(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<TemplateSubject>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
+    if let Err(response) = check_manage_access(&req, &user, &state).await {
+        return response;
     }
 
     let service = TemplateSubjectService::new(postgres_pool(&state));
@@ -137,21 +154,20 @@ async fn create_template_subject(
             });
             HttpResponse::Created().json(subject)
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
 #[put("/{id}")]
 async fn update_template_subject(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     data: web::Json<TemplateSubjectPartial>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
+    if let Err(response) = check_manage_access(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -167,20 +183,19 @@ async fn update_template_subject(
             });
             HttpResponse::Ok().json(subject)
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
 #[delete("/{id}")]
 async fn delete_template_subject(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     path: web::Path<String>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({ "message": err.to_string() }));
+    if let Err(response) = check_manage_access(&req, &user, &state).await {
+        return response;
     }
 
     let id = IdType::from_string(path.into_inner());
@@ -196,7 +211,7 @@ async fn delete_template_subject(
             });
             HttpResponse::Ok().json(serde_json::json!(subject))
         }
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -208,7 +223,7 @@ async fn count_template_subjects(
     let service = TemplateSubjectService::new(postgres_pool(&state));
     match service.count_template_subjects(query.filter.clone(), Some(&query)).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(message) => HttpResponse::BadRequest().json(message),
+        Err(err) => err.to_response(),
     }
 }
 
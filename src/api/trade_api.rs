@@ -22,7 +22,7 @@ async fn get_all_trades(
     let service = TradeService::new(postgres_pool(&state));
     match service.get_all(query.filter.clone(), query.limit, query.skip, Some(&query)).await {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -37,7 +37,7 @@ async fn get_all_trades_with_relations(
         .await
     {
         Ok(data) => HttpResponse::Ok().json(data),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -47,7 +47,7 @@ async fn get_trade_by_id(path: web::Path<String>, state: web::Data<AppState>) ->
     let service = TradeService::new(postgres_pool(&state));
     match service.find_one(Some(&id), None).await {
         Ok(trade) => HttpResponse::Ok().json(trade),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -60,7 +60,7 @@ async fn get_trade_by_id_with_relations(
     let service = TradeService::new(postgres_pool(&state));
     match service.find_one_with_relations(Some(&id), None).await {
         Ok(trade) => HttpResponse::Ok().json(trade),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -72,7 +72,7 @@ async fn get_trade_by_others_relations(
     let service = TradeService::new(postgres_pool(&state));
     match service.find_one_with_relations(None, Some(&query)).await {
         Ok(trade) => HttpResponse::Ok().json(trade),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -84,7 +84,7 @@ async fn get_trade_by_match(
     let service = TradeService::new(postgres_pool(&state));
     match service.find_one(None, Some(&query)).await {
         Ok(trade) => HttpResponse::Ok().json(trade),
-        Err(err) => HttpResponse::NotFound().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -94,7 +94,7 @@ async fn get_by_ids(body: web::Json<GetByIdsBody>, state: web::Data<AppState>) -
     let ids: Vec<IdType> = body.ids.iter().map(|id| IdType::from_string(id.clone())).collect();
     match service.find_by_ids(ids).await {
         Ok(trades) => HttpResponse::Ok().json(trades),
-        Err(error) => HttpResponse::NotFound().json(error),
+        Err(error) => error.to_response(),
     }
 }
 
@@ -118,7 +118,7 @@ async fn create_trade(
             });
             HttpResponse::Created().json(trade)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -144,7 +144,7 @@ async fn update_trade(
             });
             HttpResponse::Ok().json(trade)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -169,7 +169,7 @@ async fn delete_trade(
             });
             HttpResponse::Ok().json(trade)
         }
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -181,7 +181,7 @@ async fn count_trades(
     let service = TradeService::new(postgres_pool(&state));
     match service.count(query.filter.clone(), Some(&query)).await {
         Ok(count) => HttpResponse::Ok().json(serde_json::json!(count)),
-        Err(err) => HttpResponse::BadRequest().json(err),
+        Err(err) => err.to_response(),
     }
 }
 
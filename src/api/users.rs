@@ -1,4 +1,4 @@
-use actix_web::{delete, get, post, put, web, HttpMessage, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse, Responder};
 
 use crate::{
     config::state::AppState,
@@ -6,9 +6,14 @@ use crate::{
         auth_user::AuthUserDto,
         user::{UpdateUserDto, User},
     },
-    models::{api_request_model::RequestQuery, id_model::IdType, request_error_model::ReqErrModel},
+    errors::AppError,
+    guards::role_guard::check_permission,
+    models::{api_request_model::RequestQuery, id_model::IdType},
     repositories::user_repo::UserRepo,
-    services::{event_service::EventService, user_service::UserService},
+    services::{
+        event_service::EventService, permission_service::PermissionService,
+        user_service::UserService,
+    },
 };
 
 #[get("")]
@@ -24,7 +29,7 @@ async fn get_all_users(
         .await
     {
         Ok(users) => HttpResponse::Ok().json(users),
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -35,7 +40,7 @@ async fn get_user_stats(state: web::Data<AppState>) -> impl Responder {
 
     match service.get_user_stats().await {
         Ok(stats) => HttpResponse::Ok().json(stats),
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -48,7 +53,7 @@ async fn get_user_by_id(path: web::Path<String>, state: web::Data<AppState>) ->
 
     match service.get_user_by_id(&user_id).await {
         Ok(user) => HttpResponse::Ok().json(user),
-        Err(message) => HttpResponse::NotFound().json(ReqErrModel { message }),
+        Err(message) => AppError::not_found(message).to_response(),
     }
 }
 
@@ -64,22 +69,20 @@ async fn get_user_by_username(
 
     match service.get_user_by_username(&username).await {
         Ok(user) => HttpResponse::Ok().json(user),
-        Err(message) => HttpResponse::NotFound().json(ReqErrModel { message }),
+        Err(message) => AppError::not_found(message).to_response(),
     }
 }
 
 #[post("")]
 async fn create_user(
+    req: HttpRequest,
     user: web::ReqData<AuthUserDto>,
     data: web::Json<User>,
     state: web::Data<AppState>,
 ) -> impl Responder {
-    let logged_user = user.into_inner();
-
-    if let Err(err) = crate::guards::role_guard::check_admin(&logged_user) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+    let permission_service = PermissionService::for_request(&req, &state.pg.pool);
+    if let Err(err) = check_permission(&user, None, "user.create", &permission_service).await {
+        return AppError::forbidden(err).to_response();
     }
 
     let repo = UserRepo::new(&state.pg.pool);
@@ -105,7 +108,7 @@ async fn create_user(
 
             HttpResponse::Created().json(user)
         }
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -118,11 +121,7 @@ async fn update_user(
 ) -> impl Responder {
     let logged_user = match req.extensions().get::<AuthUserDto>() {
         Some(u) => u.clone(),
-        None => {
-            return HttpResponse::Unauthorized().json(serde_json::json!({
-                "message": "Unauthorized"
-            }))
-        }
+        None => return AppError::unauthorized("Unauthorized").to_response(),
     };
 
     let target_user_id_str = path.into_inner();
@@ -130,9 +129,7 @@ async fn update_user(
     if let Err(err) =
         crate::guards::role_guard::check_owner_or_admin(&logged_user, &target_user_id_str)
     {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+        return AppError::forbidden(err.to_string()).to_response();
     }
 
     let target_user_id = IdType::from_string(target_user_id_str);
@@ -162,7 +159,7 @@ async fn update_user(
 
             HttpResponse::Ok().json(user)
         }
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -178,9 +175,7 @@ async fn delete_user(
     if let Err(err) =
         crate::guards::role_guard::check_owner_or_admin(&logged_user, &target_user_id_str)
     {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+        return AppError::forbidden(err.to_string()).to_response();
     }
 
     let target_user_id = IdType::from_string(target_user_id_str);
@@ -213,7 +208,7 @@ async fn delete_user(
                 "message": "User deleted successfully"
             }))
         }
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(message) => AppError::bad_request(message).to_response(),
     }
 }
 
@@ -231,9 +226,7 @@ async fn add_school_to_user(
     let school_id = IdType::from_string(&school_id_str);
 
     if let Err(err) = crate::guards::role_guard::check_owner_or_admin(&logged_user, &user_id_str) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+        return AppError::forbidden(err.to_string()).to_response();
     }
 
     let repo = UserRepo::new(&state.pg.pool);
@@ -259,7 +252,7 @@ async fn add_school_to_user(
 
             HttpResponse::Ok().json(updated_user)
         }
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(err) => err.to_response(),
     }
 }
 
@@ -277,9 +270,7 @@ async fn remove_school_from_user(
     let school_id = IdType::from_string(&school_id_str);
 
     if let Err(err) = crate::guards::role_guard::check_owner_or_admin(&logged_user, &user_id_str) {
-        return HttpResponse::Forbidden().json(serde_json::json!({
-            "message": err.to_string()
-        }));
+        return AppError::forbidden(err.to_string()).to_response();
     }
 
     let repo = UserRepo::new(&state.pg.pool);
@@ -305,7 +296,7 @@ async fn remove_school_from_user(
 
             HttpResponse::Ok().json(updated_user)
         }
-        Err(message) => HttpResponse::BadRequest().json(ReqErrModel { message }),
+        Err(err) => err.to_response(),
     }
 }
 
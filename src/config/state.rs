@@ -1,18 +1,29 @@
 use crate::config::postgres_manager::PgManager;
+use crate::services::conversation_hub::ConversationHub;
 use crate::services::event_bus::EventBus;
+use crate::services::mail_transport::{self, MailTransport};
+use crate::services::pg_event_transport::transport_from_env;
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppState {
     pub pg: PgManager,
     pub event_bus: Arc<EventBus>,
+    pub conversation_hub: Arc<ConversationHub>,
+    pub mailer: Arc<dyn MailTransport>,
 }
 
 impl AppState {
     pub fn new(pg: PgManager) -> Self {
+        let transport = transport_from_env(&pg.pool);
+        let conversation_hub = Arc::new(ConversationHub::with_transport(transport.clone()));
         Self {
             pg,
-            event_bus: Arc::new(EventBus::new()),
+            event_bus: Arc::new(
+                EventBus::with_transport(transport).with_conversation_hub(conversation_hub.clone()),
+            ),
+            conversation_hub,
+            mailer: mail_transport::transport_from_env(),
         }
     }
 }
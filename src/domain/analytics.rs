@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{domain::student_term_result::StudentResultDelta, utils::statistics::Distribution};
+
 // ========== ENROLLMENT TRENDS ==========
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EnrollmentTrend {
@@ -55,3 +57,61 @@ pub struct AttendanceRateQuery {
     pub from: Option<DateTime<Utc>>,
     pub to: Option<DateTime<Utc>>,
 }
+
+// ========== EXAM STATISTICS ==========
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExamStatisticsQuery {
+    /// Limit to a class and its subclasses
+    pub class_id: Option<String>,
+    /// Histogram buckets, 10 by default
+    pub bins: Option<usize>,
+    /// Exam to compare against; each student's previous exam by default
+    pub compare_exam_id: Option<String>,
+    /// Drop in percentage points that flags a student, 10 by default
+    pub drop_threshold: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubjectStatistics {
+    pub class_subject_id: String,
+    pub subject_name: String,
+    pub class_id: Option<String>,
+    pub class_name: Option<String>,
+    pub teacher_name: Option<String>,
+    pub distribution: Distribution,
+}
+
+/// One subject's results in one of the parallel subclasses
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParallelSubject {
+    pub class_subject_id: String,
+    pub class_name: Option<String>,
+    pub teacher_name: Option<String>,
+    pub count: i64,
+    pub mean: f64,
+    pub median: f64,
+    /// Mean minus the mean of the whole group
+    pub delta_from_group: f64,
+}
+
+/// The same subject taught in sibling subclasses of one class
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TeacherComparison {
+    pub parent_class_id: String,
+    pub parent_class_name: Option<String>,
+    pub subject_name: String,
+    pub group_mean: f64,
+    pub subjects: Vec<ParallelSubject>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExamStatistics {
+    pub exam_id: String,
+    /// Every student's percentage in every subject
+    pub overall: Distribution,
+    pub subjects: Vec<SubjectStatistics>,
+    pub teacher_comparisons: Vec<TeacherComparison>,
+    pub drop_threshold: f64,
+    pub student_deltas: Vec<StudentResultDelta>,
+    pub flagged_count: i64,
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, make_partial, utils::object_id::ObjectId};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttendanceStatus {
+    Present,
+    Absent,
+    Late,
+    Excused,
+}
+
+make_partial! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct Attendance {
+        #[serde(
+            rename = "_id",
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub id: Option<ObjectId>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub school_id: Option<ObjectId>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub class_id: Option<ObjectId>,
+
+        // Enrollment id, the same `_id` the students API returns
+        #[serde(
+            serialize_with = "object_id_helpers::serialize_oid",
+            deserialize_with = "object_id_helpers::deserialize_oid"
+        )]
+        pub student_id: ObjectId,
+
+        pub date: NaiveDate,
+
+        // Timetable period; None when the roll is taken for the whole day
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub period_id: Option<ObjectId>,
+
+        pub status: AttendanceStatus,
+        pub note: Option<String>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub recorded_by: Option<ObjectId>,
+
+        #[serde(default)]
+        pub created_at: Option<DateTime<Utc>>,
+
+        #[serde(default)]
+        pub updated_at: Option<DateTime<Utc>>,
+    } => AttendancePartial
+}
+
+/// One student's mark inside a bulk class roll submission.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttendanceEntry {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+    pub status: AttendanceStatus,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BulkAttendanceRequest {
+    pub date: NaiveDate,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub period_id: Option<ObjectId>,
+
+    /// Status applied to enrolled students missing from `records`
+    pub default_status: Option<AttendanceStatus>,
+
+    #[serde(default)]
+    pub records: Vec<AttendanceEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegisterStudent {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+    pub name: String,
+    pub registration_number: Option<String>,
+    pub image: Option<String>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub attendance_id: Option<ObjectId>,
+    pub status: Option<AttendanceStatus>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AttendanceCounts {
+    pub present: i64,
+    pub absent: i64,
+    pub late: i64,
+    pub excused: i64,
+    pub unmarked: i64,
+}
+
+impl AttendanceCounts {
+    pub fn total_marked(&self) -> i64 {
+        self.present + self.absent + self.late + self.excused
+    }
+
+    /// Share of marked days the student was in school. A late arrival still
+    /// counts as attended; absences and excused days do not.
+    pub fn attendance_percentage(&self) -> f64 {
+        let total = self.total_marked();
+        if total > 0 {
+            ((self.present + self.late) as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The class roll for one day (and optionally one period).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClassAttendanceRegister {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_id: ObjectId,
+    pub date: NaiveDate,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub period_id: Option<ObjectId>,
+
+    pub students: Vec<RegisterStudent>,
+    pub counts: AttendanceCounts,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StudentAttendanceSummary {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+    pub counts: AttendanceCounts,
+    pub total_records: i64,
+    pub attendance_percentage: f64,
+}
+
+// ========== QUERY PARAMETERS ==========
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AttendanceQuery {
+    pub class_id: Option<String>,
+    pub student_id: Option<String>,
+    pub period_id: Option<String>,
+    pub status: Option<AttendanceStatus>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+    pub skip: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RegisterQuery {
+    pub date: Option<NaiveDate>,
+    pub period_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AttendanceRangeQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
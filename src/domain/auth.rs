@@ -21,6 +21,8 @@ pub struct LoginResponse {
     pub email: String,
     pub name: String,
     pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
     pub image: Option<String>,
     pub role: Option<UserRole>,
     pub username: Option<String>,
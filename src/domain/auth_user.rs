@@ -17,6 +17,9 @@ pub struct AuthUserDto {
     pub current_school_user_id: Option<String>,
     pub schools: Option<Vec<String>>,
     pub accessible_classes: Option<Vec<String>>,
+    /// Sign-in session the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
     pub iat: Option<i64>, // issued at
     pub exp: Option<i64>, // expiration
 }
@@ -70,9 +70,79 @@ make_partial! {
         pub completed_at: Option<DateTime<Utc>>,
 
         pub error_message: Option<String>,
+
+        /// SHA-256 of the archive file, checked before a restore
+        #[serde(default)]
+        pub checksum: Option<String>,
     } => SchoolBackupPartial
 }
 
+pub const BACKUP_FORMAT: &str = "space-together.school-snapshot";
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// First line of a snapshot archive. The rest of the (gzip) file is one JSON
+/// object per line: `{"table": "...", "row": {...}}`, grouped by table in
+/// `tables` order.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub school_id: String,
+    pub created_at: DateTime<Utc>,
+    pub tables: Vec<BackupTableManifest>,
+    pub total_rows: u64,
+    /// SHA-256 of every row line after the manifest
+    pub payload_sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupTableManifest {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub row_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupArchiveLine {
+    pub table: String,
+    pub row: serde_json::Value,
+}
+
+pub const DEFAULT_BACKUP_SCHEDULE: &str = "0 2 * * *";
+
+/// Automated backup schedule and retention of one school. Completed automated
+/// backups are kept if they are the newest of one of the last `keep_daily`
+/// days, `keep_weekly` ISO weeks or `keep_monthly` months; manual backups are
+/// never pruned.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupPolicy {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub school_id: Option<ObjectId>,
+    pub enabled: bool,
+    /// Five-field cron expression, evaluated in UTC
+    pub schedule: String,
+    pub keep_daily: i32,
+    pub keep_weekly: i32,
+    pub keep_monthly: i32,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UpdateBackupPolicy {
+    pub enabled: Option<bool>,
+    pub schedule: Option<String>,
+    pub keep_daily: Option<i32>,
+    pub keep_weekly: Option<i32>,
+    pub keep_monthly: Option<i32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SchoolBackupWithRelations {
     #[serde(flatten)]
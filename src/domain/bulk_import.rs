@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// Rows accepted from one upload
+pub const MAX_IMPORT_ROWS: usize = 5_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ImportKind {
+    Student,
+    Teacher,
+}
+
+impl ImportKind {
+    /// Fields a column can be mapped to
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            ImportKind::Student => &[
+                "name",
+                "email",
+                "phone",
+                "gender",
+                "date_of_birth",
+                "registration_number",
+                "admission_year",
+                "class",
+                "parent_name",
+                "parent_email",
+                "parent_phone",
+                "parent_relationship",
+            ],
+            ImportKind::Teacher => &["name", "email", "phone", "gender", "teacher_type", "class"],
+        }
+    }
+
+    pub fn required_fields(&self) -> &'static [&'static str] {
+        &["name", "email"]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportKind::Student => "Student",
+            ImportKind::Teacher => "Teacher",
+        }
+    }
+}
+
+/// Other header spellings that map to a field without an explicit mapping
+pub const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("full_name", "name"),
+    ("names", "name"),
+    ("email_address", "email"),
+    ("phone_number", "phone"),
+    ("telephone", "phone"),
+    ("sex", "gender"),
+    ("dob", "date_of_birth"),
+    ("birth_date", "date_of_birth"),
+    ("reg_no", "registration_number"),
+    ("registration_no", "registration_number"),
+    ("class_name", "class"),
+    ("classroom", "class"),
+    ("guardian_name", "parent_name"),
+    ("guardian_email", "parent_email"),
+    ("guardian_phone", "parent_phone"),
+    ("relationship", "parent_relationship"),
+    ("type", "teacher_type"),
+];
+
+/// The `data` part of an import upload
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImportOptions {
+    pub kind: ImportKind,
+    /// Field name to column header. Unmapped fields fall back to a header
+    /// with the same name or a known alias.
+    #[serde(default)]
+    pub mapping: BTreeMap<String, String>,
+    /// Validate and report only; nothing is written
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    /// Class used for rows without a class column value
+    pub class_id: Option<String>,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+impl ImportOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = self.kind.fields();
+        if let Some(field) = self
+            .mapping
+            .keys()
+            .find(|field| !fields.contains(&field.as_str()))
+        {
+            return Err(format!(
+                "Unknown {} import field: {}",
+                self.kind.as_str().to_lowercase(),
+                field
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ImportRowStatus {
+    /// Valid and would be created; only seen on dry runs
+    Ready,
+    Created,
+    /// Already exists in the school, left untouched
+    Duplicate,
+    /// The email belongs to an account outside the school, left untouched
+    /// rather than attached to someone else's account
+    Conflict,
+    Invalid,
+}
+
+impl ImportRowStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportRowStatus::Ready => "Ready",
+            ImportRowStatus::Created => "Created",
+            ImportRowStatus::Duplicate => "Duplicate",
+            ImportRowStatus::Conflict => "Conflict",
+            ImportRowStatus::Invalid => "Invalid",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportRowResult {
+    /// Spreadsheet row number, the header being row 1
+    pub row_number: u32,
+    pub status: ImportRowStatus,
+    pub name: Option<String>,
+    pub email: Option<String>,
+
+    /// The created record, or the existing one a duplicate matched
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub entity_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub parent_id: Option<ObjectId>,
+
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportReport {
+    /// Set once the import is committed
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub school_id: ObjectId,
+
+    pub kind: ImportKind,
+    pub file_name: Option<String>,
+    pub dry_run: bool,
+    pub committed: bool,
+    /// Field name to the column header it was read from
+    #[serde(default)]
+    pub mapping: BTreeMap<String, String>,
+    #[serde(default)]
+    pub unmapped_columns: Vec<String>,
+
+    pub total_rows: u32,
+    pub ready_count: u32,
+    pub created_count: u32,
+    pub duplicate_count: u32,
+    #[serde(default)]
+    pub conflict_count: u32,
+    pub error_count: u32,
+    pub rows: Vec<ImportRowResult>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub created_by: Option<ObjectId>,
+    pub created_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// A subscription URL a user handed to a calendar app
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalendarFeed {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub user_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub school_id: ObjectId,
+
+    pub label: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CreateCalendarFeed {
+    /// e.g. "Phone", "Work laptop"
+    pub label: Option<String>,
+}
+
+/// Returned once on creation; the token cannot be read back later
+#[derive(Debug, Serialize, Clone)]
+pub struct CalendarFeedCreated {
+    pub feed: CalendarFeed,
+    pub token: String,
+    pub url: String,
+}
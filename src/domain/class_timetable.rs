@@ -2,7 +2,7 @@ use crate::{helpers::object_id_helpers, make_partial, utils::{object_id::ObjectI
 use chrono::{DateTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum PeriodType {
     Subject,
     Break,
@@ -33,6 +33,22 @@ pub struct Period {
     )]
     pub subject_id: Option<ObjectId>,
 
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub teacher_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub room_id: Option<ObjectId>,
+
     pub start_offset: i32,                // minutes from day start
     pub duration_minutes: i32,            // positive
 
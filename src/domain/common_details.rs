@@ -49,6 +49,17 @@ impl RelatedUser {
             RelatedUser::PARENT(p) => p.id.as_ref().map(|id| id.to_hex()),
         }
     }
+
+    /// Account (`users`) id behind the school member, when it has one.
+    pub fn get_user_id(&self) -> Option<String> {
+        match self {
+            RelatedUser::STUDENT(s) => s.user_id.as_ref().map(|id| id.to_hex()),
+            RelatedUser::TEACHER(t) => t.user_id.as_ref().map(|id| id.to_hex()),
+            RelatedUser::SCHOOLSTAFF(ss) => ss.user_id.as_ref().map(|id| id.to_hex()),
+            RelatedUser::USER(u) => u.id.as_ref().map(|id| id.to_hex()),
+            RelatedUser::PARENT(p) => p.user_id.as_ref().map(|id| id.to_hex()),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
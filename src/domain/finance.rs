@@ -0,0 +1,383 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, make_partial, utils::object_id::ObjectId};
+
+pub const DEFAULT_CURRENCY: &str = "RWF";
+
+fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FinanceRecordType {
+    Invoice,
+    Payment,
+    Refund,
+    Adjustment,
+    BalanceForward,
+    /// Rows written before the ledger existed
+    Fee,
+}
+
+impl FinanceRecordType {
+    /// Sign of the record in a student's balance: debits raise what is owed,
+    /// credits lower it. Adjustments and carried balances hold their own sign
+    /// in `amount`; a refund gives money back, so the balance goes up again.
+    pub fn balance_sign(&self) -> f64 {
+        match self {
+            FinanceRecordType::Payment => -1.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FinanceStatus {
+    Pending,
+    Partial,
+    Paid,
+    CarriedForward,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Cash,
+    BankTransfer,
+    MobileMoney,
+    Card,
+    Cheque,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeeItem {
+    pub name: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub is_optional: bool,
+}
+
+make_partial! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct FeeStructure {
+        #[serde(
+            rename = "_id",
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub id: Option<ObjectId>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub school_id: Option<ObjectId>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub class_id: Option<ObjectId>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub education_year_id: Option<ObjectId>,
+
+        pub term_id: Option<String>, // Reference to Term in EducationYear
+
+        pub name: String,
+        pub description: Option<String>,
+
+        pub items: Vec<FeeItem>,
+
+        #[serde(default)]
+        pub total_amount: f64,
+
+        #[serde(default = "default_currency")]
+        pub currency: String,
+
+        pub due_date: Option<DateTime<Utc>>,
+
+        #[serde(default)]
+        pub is_active: Option<bool>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub created_by: Option<ObjectId>,
+
+        #[serde(default)]
+        pub created_at: Option<DateTime<Utc>>,
+
+        #[serde(default)]
+        pub updated_at: Option<DateTime<Utc>>,
+    } => FeeStructurePartial
+}
+
+impl FeeStructure {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".into());
+        }
+        if self.items.is_empty() {
+            return Err("at least one fee item is required".into());
+        }
+        for item in &self.items {
+            if item.name.trim().is_empty() {
+                return Err("fee item name is required".into());
+            }
+            if !item.amount.is_finite() || item.amount < 0.0 {
+                return Err(format!(
+                    "fee item '{}' must have a non-negative amount",
+                    item.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Amount billed to a student: mandatory items only.
+    pub fn mandatory_total(&self) -> f64 {
+        self.items
+            .iter()
+            .filter(|item| !item.is_optional)
+            .map(|item| item.amount)
+            .sum()
+    }
+}
+
+/// A row of the student ledger (`finance_records`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FinanceRecord {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub school_id: Option<ObjectId>,
+
+    // Enrollment id, the same `_id` the students API returns
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub student_id: Option<ObjectId>,
+
+    pub record_type: FinanceRecordType,
+    pub description: Option<String>,
+    pub amount: f64,
+    pub currency: String,
+    pub status: FinanceStatus,
+
+    pub due_date: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub invoice_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub related_record_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub fee_structure_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub education_year_id: Option<ObjectId>,
+
+    pub term_id: Option<String>,
+    pub reference_number: Option<String>,
+    pub payment_method: Option<PaymentMethod>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub created_by: Option<ObjectId>,
+
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl FinanceRecord {
+    /// Effect of this record on the student's balance. Legacy `fee` rows that
+    /// were already marked paid are settled and contribute nothing.
+    pub fn signed_amount(&self) -> f64 {
+        if self.record_type == FinanceRecordType::Fee && self.status == FinanceStatus::Paid {
+            return 0.0;
+        }
+        self.amount * self.record_type.balance_sign()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvoiceWithLedger {
+    #[serde(flatten)]
+    pub invoice: FinanceRecord,
+
+    /// Payments, refunds, adjustments and carried balances linked to the invoice
+    pub entries: Vec<FinanceRecord>,
+    pub amount_due: f64,
+    pub amount_paid: f64,
+    pub balance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Receipt {
+    pub receipt_number: String,
+    pub school_name: Option<String>,
+    pub student_name: Option<String>,
+    pub registration_number: Option<String>,
+    pub payment: FinanceRecord,
+    pub invoice_reference: Option<String>,
+    pub balance_after_payment: f64,
+    pub issued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatementLine {
+    #[serde(flatten)]
+    pub record: FinanceRecord,
+    pub running_balance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StudentStatement {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+    pub currency: String,
+    pub total_billed: f64,
+    pub total_paid: f64,
+    pub total_refunded: f64,
+    pub balance: f64,
+    pub lines: Vec<StatementLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateInvoicesRequest {
+    /// Move each student's unpaid balance from earlier invoices onto the new one
+    #[serde(default)]
+    pub carry_forward_balances: bool,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GenerateInvoicesResult {
+    pub created: usize,
+    pub skipped_existing: usize,
+    pub carried_forward: usize,
+    pub invoices: Vec<FinanceRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordPaymentRequest {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub invoice_id: ObjectId,
+    pub amount: f64,
+    pub payment_method: PaymentMethod,
+    /// Bank slip or mobile money transaction id
+    pub reference_number: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefundRequest {
+    pub amount: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdjustmentRequest {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub invoice_id: ObjectId,
+    /// Negative for discounts/bursaries, positive for penalties or extra charges
+    pub amount: f64,
+    pub reason: String,
+}
+
+// ========== QUERY PARAMETERS ==========
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FinanceQuery {
+    pub student_id: Option<String>,
+    pub class_id: Option<String>,
+    pub fee_structure_id: Option<String>,
+    pub education_year_id: Option<String>,
+    pub term_id: Option<String>,
+    pub record_type: Option<FinanceRecordType>,
+    pub status: Option<FinanceStatus>,
+    pub limit: Option<i64>,
+    pub skip: Option<i64>,
+}
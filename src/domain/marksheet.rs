@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// Header row of a marksheet, in order. Only the first four are read back
+/// as required; the name is there for the teacher.
+pub const MARKSHEET_COLUMNS: [&str; 6] = [
+    "student_id",
+    "registration_number",
+    "student_name",
+    "score",
+    "max_score",
+    "remarks",
+];
+
+/// Which marks a sheet is for
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarksheetTarget {
+    pub class_subject_id: String,
+    pub exam_id: String,
+    pub assessment_category_id: String,
+}
+
+/// Query string of the template download. Not flattened from
+/// `MarksheetTarget`, which would stop `max_score` parsing from a query.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarksheetTemplateQuery {
+    pub class_subject_id: String,
+    pub exam_id: String,
+    pub assessment_category_id: String,
+    /// `csv` or `xlsx` (default)
+    pub format: Option<String>,
+    /// Pre-filled for students without a mark yet
+    pub max_score: Option<f64>,
+}
+
+impl MarksheetTemplateQuery {
+    pub fn target(&self) -> MarksheetTarget {
+        MarksheetTarget {
+            class_subject_id: self.class_subject_id.clone(),
+            exam_id: self.exam_id.clone(),
+            assessment_category_id: self.assessment_category_id.clone(),
+        }
+    }
+}
+
+/// The `data` part of a marksheet upload
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarksheetUpload {
+    #[serde(flatten)]
+    pub target: MarksheetTarget,
+    /// Used for rows whose max_score cell is empty
+    pub max_score: Option<f64>,
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    /// Recorded in the score audit log for changed marks
+    pub change_reason: Option<String>,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+impl MarksheetUpload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .max_score
+            .is_some_and(|max| max <= 0.0 || !max.is_finite())
+        {
+            return Err("Max score must be greater than 0".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MarkStatus {
+    New,
+    Changed,
+    Unchanged,
+    /// No score entered; existing marks are left as they are
+    Blank,
+    Invalid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarkRow {
+    /// Spreadsheet row number, the header being row 1
+    pub row_number: u32,
+    pub status: MarkStatus,
+
+    /// Student profile id, once the row is matched to the class roster
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub student_id: Option<ObjectId>,
+    pub student_name: Option<String>,
+    pub registration_number: Option<String>,
+
+    pub score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub old_score: Option<f64>,
+    pub remarks: Option<String>,
+
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// A class member the uploaded sheet has no row for
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MissingStudent {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+    pub name: String,
+    pub registration_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarksheetPreview {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_subject_id: ObjectId,
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub exam_id: ObjectId,
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub assessment_category_id: ObjectId,
+
+    pub dry_run: bool,
+    pub committed: bool,
+    pub total_rows: u32,
+    pub new_count: u32,
+    pub changed_count: u32,
+    pub unchanged_count: u32,
+    pub blank_count: u32,
+    pub error_count: u32,
+    pub rows: Vec<MarkRow>,
+    pub missing_students: Vec<MissingStudent>,
+}
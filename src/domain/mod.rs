@@ -1,11 +1,16 @@
+pub mod account_token;
 pub mod analytics;
 pub mod announcement;
 pub mod assessment_category;
 pub mod assignment;
+pub mod attendance;
 pub mod audit_log;
 pub mod auth;
+pub mod auth_session;
 pub mod auth_user;
 pub mod backup;
+pub mod bulk_import;
+pub mod calendar_feed;
 pub mod class;
 pub mod class_subject;
 pub mod class_timetable;
@@ -15,6 +20,7 @@ pub mod conversation;
 pub mod database_status;
 pub mod education_year;
 pub mod exam;
+pub mod finance;
 pub mod grading_scale;
 pub mod guardian;
 pub mod join_school_request;
@@ -22,19 +28,30 @@ pub mod learning_material;
 pub mod like;
 pub mod location;
 pub mod main_class;
+pub mod marksheet;
 pub mod message;
+pub mod notification;
 pub mod parent;
 pub mod promotion;
+pub mod report_card;
 pub mod role;
 pub mod school;
 pub mod school_staff;
+pub mod school_room;
 pub mod school_timetable;
 pub mod score;
+pub mod score_moderation;
 pub mod sector;
 pub mod student;
+pub mod student_annual_result;
 pub mod student_term_result;
 pub mod teacher;
+pub mod teacher_cover;
 pub mod template_subject;
+pub mod tenant;
+pub mod timetable_solver;
+pub mod timetable_validation;
 pub mod trade;
+pub mod two_factor;
 pub mod user;
 pub mod user_public_key;
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// An inbox entry. `user_id` is set for personal notifications; school-wide
+/// notifications only carry `school_id` and are visible to every active member.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Notification {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub user_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub school_id: Option<ObjectId>,
+
+    /// Entity type of the originating event (`attendance`, `invoice`, ...)
+    pub category: String,
+    pub event_type: String,
+    pub entity_id: Option<String>,
+    pub title: String,
+    pub data: serde_json::Value,
+
+    #[serde(default)]
+    pub read_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl Notification {
+    /// Build a notification from an event; the title is derived from the
+    /// category and event type ("Fee structure created").
+    pub fn from_event(
+        event_type: &str,
+        category: &str,
+        entity_id: Option<&str>,
+        school_id: Option<&str>,
+        user_id: Option<&str>,
+        data: serde_json::Value,
+    ) -> Self {
+        let subject = category.replace(['_', '-'], " ");
+        let mut title = format!("{} {}", subject.trim(), event_type.replace('_', " "));
+        if let Some(first) = title.get(0..1) {
+            title = format!("{}{}", first.to_uppercase(), &title[1..]);
+        }
+
+        Self {
+            id: Some(ObjectId::new()),
+            user_id: user_id.and_then(|id| ObjectId::parse_str(id).ok()),
+            school_id: school_id.and_then(|id| ObjectId::parse_str(id).ok()),
+            category: category.to_string(),
+            event_type: event_type.to_string(),
+            entity_id: entity_id.map(ToOwned::to_owned),
+            title,
+            data,
+            read_at: None,
+            created_at: Some(Utc::now()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationPreference {
+    pub category: String,
+    pub muted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateNotificationPreference {
+    pub muted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UnreadNotificationCount {
+    pub unread: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MarkNotificationsReadResult {
+    pub marked: u64,
+}
+
+// ========== QUERY PARAMETERS ==========
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct NotificationQuery {
+    #[serde(default)]
+    pub unread_only: bool,
+    pub category: Option<String>,
+    pub limit: Option<i64>,
+    pub skip: Option<i64>,
+}
@@ -3,14 +3,27 @@ use serde::{Deserialize, Serialize};
 
 use crate::{helpers::object_id_helpers, make_partial, utils::object_id::ObjectId};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+/// Subject percentage counted as a pass when a rule's `custom_rules` does not
+/// set `subject_pass_percentage`. Matches the lowest non-failing grade.
+pub const DEFAULT_SUBJECT_PASS_PERCENTAGE: f64 = 50.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PromotionStatus {
     Promoted,
     Repeated,
     Graduated,
+    /// No decision could be made (e.g. no results); a reviewer must set one.
     Pending,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PromotionBatchStatus {
+    #[default]
+    Proposed,
+    Approved,
+    Cancelled,
+}
+
 make_partial! {
     #[derive(Debug, Serialize, Deserialize, Clone)]
     pub struct PromotionRule {
@@ -67,6 +80,35 @@ make_partial! {
     } => PromotionRulePartial
 }
 
+impl PromotionRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".into());
+        }
+        if !self.min_gpa_threshold.is_finite() || self.min_gpa_threshold < 0.0 {
+            return Err("min_gpa_threshold must be a non-negative number".into());
+        }
+        if let Some(attendance) = self.min_attendance_percentage {
+            if !(0.0..=100.0).contains(&attendance) {
+                return Err("min_attendance_percentage must be between 0 and 100".into());
+            }
+        }
+        if !(0.0..=100.0).contains(&self.subject_pass_percentage()) {
+            return Err("custom_rules.subject_pass_percentage must be between 0 and 100".into());
+        }
+        Ok(())
+    }
+
+    /// Percentage a required subject needs to count as passed.
+    pub fn subject_pass_percentage(&self) -> f64 {
+        self.custom_rules
+            .as_ref()
+            .and_then(|rules| rules.get("subject_pass_percentage"))
+            .and_then(|value| value.as_f64())
+            .unwrap_or(DEFAULT_SUBJECT_PASS_PERCENTAGE)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PromotionResult {
     #[serde(
@@ -75,9 +117,23 @@ pub struct PromotionResult {
         skip_serializing_if = "Option::is_none",
         default
     )]
-    pub student_id: Option<ObjectId>,
+    pub student_id: Option<ObjectId>, // Enrollment id, as in the students API
     pub student_name: String,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub from_class_id: Option<ObjectId>,
+
     pub current_gpa: f64,
+    pub attendance_percentage: Option<f64>,
+
+    #[serde(default)]
+    pub failed_subjects: Vec<String>,
+
     pub promotion_status: PromotionStatus,
     pub reason: String,
 
@@ -88,6 +144,10 @@ pub struct PromotionResult {
         default
     )]
     pub promoted_to_class_id: Option<ObjectId>,
+
+    /// Set when a reviewer changed the engine's decision
+    #[serde(default)]
+    pub is_overridden: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -117,6 +177,22 @@ pub struct PromotionBatch {
     )]
     pub education_year_id: Option<ObjectId>,
 
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub to_education_year_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub rule_id: Option<ObjectId>,
+
     #[serde(
         serialize_with = "object_id_helpers::serialize",
         deserialize_with = "object_id_helpers::deserialize",
@@ -133,8 +209,19 @@ pub struct PromotionBatch {
     )]
     pub to_class_id: Option<ObjectId>,
 
+    #[serde(default)]
+    pub status: PromotionBatchStatus,
+
     pub promotion_results: Vec<PromotionResult>,
 
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub created_by: Option<ObjectId>,
+
     #[serde(
         serialize_with = "object_id_helpers::serialize",
         deserialize_with = "object_id_helpers::deserialize",
@@ -145,4 +232,115 @@ pub struct PromotionBatch {
 
     #[serde(default)]
     pub executed_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromotionSummary {
+    pub promoted: usize,
+    pub repeated: usize,
+    pub graduated: usize,
+    pub pending: usize,
+}
+
+impl PromotionBatch {
+    pub fn summary(&self) -> PromotionSummary {
+        let count = |status: PromotionStatus| {
+            self.promotion_results
+                .iter()
+                .filter(|result| result.promotion_status == status)
+                .count()
+        };
+        PromotionSummary {
+            promoted: count(PromotionStatus::Promoted),
+            repeated: count(PromotionStatus::Repeated),
+            graduated: count(PromotionStatus::Graduated),
+            pending: count(PromotionStatus::Pending),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromotionBatchWithSummary {
+    #[serde(flatten)]
+    pub batch: PromotionBatch,
+    pub summary: PromotionSummary,
+}
+
+impl From<PromotionBatch> for PromotionBatchWithSummary {
+    fn from(batch: PromotionBatch) -> Self {
+        let summary = batch.summary();
+        Self { batch, summary }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreatePromotionProposal {
+    /// The year being closed; its term results and attendance are evaluated
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub education_year_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub to_education_year_id: ObjectId,
+
+    /// Defaults to the school's rule for the year, then its general rule
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub rule_id: Option<ObjectId>,
+
+    /// Limit the proposal to one class
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub from_class_id: Option<ObjectId>,
+
+    /// Send promoted students of `from_class_id` here instead of the class
+    /// found for the next level
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub to_class_id: Option<ObjectId>,
+}
+
+/// A reviewer's decision for one student of a proposal.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromotionDecision {
+    pub promotion_status: PromotionStatus,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub promoted_to_class_id: Option<ObjectId>,
+
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PromotionBatchQuery {
+    pub education_year_id: Option<String>,
+    pub status: Option<PromotionBatchStatus>,
 }
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{grading_scale::GradingScale, student_term_result::StudentTermResult},
+    helpers::object_id_helpers,
+    utils::object_id::ObjectId,
+};
+
+/// A teacher's remark on a student's report card. Without a subject it is
+/// the class teacher's general remark.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportCardComment {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub exam_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_subject_id: Option<ObjectId>,
+
+    pub body: String,
+    pub author_name: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UpsertReportCardComment {
+    pub student_id: String,
+    pub exam_id: String,
+    pub class_subject_id: Option<String>,
+    pub body: String,
+}
+
+impl UpsertReportCardComment {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.body.trim().is_empty() {
+            return Err("Comment body cannot be empty".into());
+        }
+        if self.body.chars().count() > 600 {
+            return Err("Comment body cannot exceed 600 characters".into());
+        }
+        Ok(())
+    }
+}
+
+/// Everything printed on one student's card
+#[derive(Debug, Clone)]
+pub struct ReportCard {
+    pub school_name: String,
+    pub student_name: String,
+    pub class_name: Option<String>,
+    pub exam_name: Option<String>,
+    pub year_label: Option<String>,
+    pub result: StudentTermResult,
+    pub comments: Vec<ReportCardComment>,
+    pub grading_scale: Option<GradingScale>,
+}
@@ -18,6 +18,66 @@ pub enum PermissionScope {
     School,
 }
 
+impl PermissionScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PermissionScope::Own => "Own",
+            PermissionScope::Class => "Class",
+            PermissionScope::School => "School",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Own" => Some(PermissionScope::Own),
+            "Class" => Some(PermissionScope::Class),
+            "School" => Some(PermissionScope::School),
+            _ => None,
+        }
+    }
+
+    /// Scope of a permission that is not one of the defaults, read off its
+    /// code: `*.own` and `*.class` are narrowed, anything else is school-wide
+    pub fn for_code(code: &str) -> Self {
+        if code.ends_with(".own") || code.starts_with("parent.read.child.") {
+            PermissionScope::Own
+        } else if code.ends_with(".class") {
+            PermissionScope::Class
+        } else {
+            PermissionScope::School
+        }
+    }
+}
+
+/// What a permission is being exercised on. A `School` grant covers the
+/// whole school, a `Class` grant only classes the holder teaches and an
+/// `Own` grant only records owned by the holder.
+#[derive(Debug, Clone, Default)]
+pub struct PermissionTarget {
+    pub school_id: String,
+    pub class_id: Option<String>,
+    pub owner_user_id: Option<String>,
+}
+
+impl PermissionTarget {
+    pub fn school(school_id: impl Into<String>) -> Self {
+        Self {
+            school_id: school_id.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn class(mut self, class_id: impl Into<String>) -> Self {
+        self.class_id = Some(class_id.into());
+        self
+    }
+
+    pub fn owner(mut self, user_id: impl Into<String>) -> Self {
+        self.owner_user_id = Some(user_id.into());
+        self
+    }
+}
+
 make_partial! {
     #[derive(Debug, Serialize, Deserialize, Clone)]
     pub struct Role {
@@ -85,3 +145,35 @@ pub struct UserRoleAssignment {
     #[serde(default = "Utc::now")]
     pub assigned_at: DateTime<Utc>,
 }
+
+/// A role of the user that grants the permission being evaluated
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PermissionGrant {
+    pub role_id: String,
+    pub role_name: String,
+    pub scope: PermissionScope,
+}
+
+/// Outcome of a permission check, with the reasoning shown to admins
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PermissionDecision {
+    pub user_id: String,
+    pub permission: String,
+    pub school_id: Option<String>,
+    pub allowed: bool,
+    pub reason: String,
+    /// Whether the user is an active member of the school
+    pub is_member: bool,
+    /// Whether the user's platform role carries the permission by default
+    pub built_in: bool,
+    pub grants: Vec<PermissionGrant>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PermissionExplainQuery {
+    pub user_id: String,
+    pub permission: String,
+    pub school_id: Option<String>,
+    pub class_id: Option<String>,
+    pub owner_user_id: Option<String>,
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, make_partial, utils::object_id::ObjectId};
+
+make_partial! {
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct SchoolRoom {
+        #[serde(
+            rename = "_id",
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub id: Option<ObjectId>,
+
+        #[serde(
+            serialize_with = "object_id_helpers::serialize",
+            deserialize_with = "object_id_helpers::deserialize",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub school_id: Option<ObjectId>,
+
+        pub name: String,
+        /// Free-form kind of room, e.g. "Classroom", "Science Lab", "Workshop"
+        #[serde(default = "default_room_type")]
+        pub room_type: String,
+        pub capacity: Option<i32>,
+
+        #[serde(default = "crate::models::default_model::default_true")]
+        pub is_active: bool,
+
+        #[serde(default)]
+        pub created_at: Option<DateTime<Utc>>,
+
+        #[serde(default)]
+        pub updated_at: Option<DateTime<Utc>>,
+    } => SchoolRoomPartial
+}
+
+fn default_room_type() -> String {
+    "Classroom".to_string()
+}
+
+impl SchoolRoom {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Room name is required".into());
+        }
+        if self.room_type.trim().is_empty() {
+            return Err("room_type is required".into());
+        }
+        if matches!(self.capacity, Some(capacity) if capacity <= 0) {
+            return Err("capacity must be a positive number".into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// Permission that makes a user a reviewer (head of department) of marks
+pub const REVIEW_PERMISSION: &str = "scores.review";
+/// Permission to approve marks and reopen approved ones
+pub const MODERATE_PERMISSION: &str = "result.moderate";
+/// Permission to release an exam's results to students and parents
+pub const PUBLISH_PERMISSION: &str = "result.publish";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModerationStatus {
+    #[default]
+    Draft,
+    Submitted,
+    Reviewed,
+    /// Locked; only a reopen makes the marks editable again
+    Approved,
+    Reopened,
+}
+
+impl ModerationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationStatus::Draft => "Draft",
+            ModerationStatus::Submitted => "Submitted",
+            ModerationStatus::Reviewed => "Reviewed",
+            ModerationStatus::Approved => "Approved",
+            ModerationStatus::Reopened => "Reopened",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "Draft" => Ok(ModerationStatus::Draft),
+            "Submitted" => Ok(ModerationStatus::Submitted),
+            "Reviewed" => Ok(ModerationStatus::Reviewed),
+            "Approved" => Ok(ModerationStatus::Approved),
+            "Reopened" => Ok(ModerationStatus::Reopened),
+            other => Err(format!("Unknown moderation status: {}", other)),
+        }
+    }
+
+    /// Marks can be entered and changed only before submission or after a
+    /// reopen
+    pub fn is_editable(&self) -> bool {
+        matches!(self, ModerationStatus::Draft | ModerationStatus::Reopened)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    /// Teacher hands the marks in
+    Submit,
+    /// Head of department signs them off
+    Review,
+    /// Reviewer or admin sends them back to the teacher
+    Return,
+    /// Admin approves and locks them
+    Approve,
+    /// Admin unlocks approved marks for correction
+    Reopen,
+}
+
+impl ModerationAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationAction::Submit => "Submit",
+            ModerationAction::Review => "Review",
+            ModerationAction::Return => "Return",
+            ModerationAction::Approve => "Approve",
+            ModerationAction::Reopen => "Reopen",
+        }
+    }
+
+    /// The status the action moves to, if allowed from `from`
+    pub fn transition(&self, from: ModerationStatus) -> Result<ModerationStatus, String> {
+        use ModerationStatus::*;
+        let to = match (self, from) {
+            (ModerationAction::Submit, Draft | Reopened) => Submitted,
+            (ModerationAction::Review, Submitted) => Reviewed,
+            (ModerationAction::Return, Submitted | Reviewed) => Draft,
+            (ModerationAction::Approve, Reviewed) => Approved,
+            (ModerationAction::Reopen, Approved) => Reopened,
+            _ => {
+                return Err(format!(
+                    "Cannot {} marks that are {}",
+                    self.as_str().to_lowercase(),
+                    from.as_str()
+                ))
+            }
+        };
+        Ok(to)
+    }
+
+    /// Returns and reopens have to say why
+    pub fn requires_note(&self) -> bool {
+        matches!(self, ModerationAction::Return | ModerationAction::Reopen)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ModerationRequest {
+    pub exam_id: String,
+    pub class_subject_id: String,
+    pub action: ModerationAction,
+    pub note: Option<String>,
+}
+
+impl ModerationRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let has_note = self
+            .note
+            .as_deref()
+            .is_some_and(|note| !note.trim().is_empty());
+        if self.action.requires_note() && !has_note {
+            return Err(format!(
+                "A note is required to {}",
+                self.action.as_str().to_lowercase()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModerationEvent {
+    pub action: ModerationAction,
+    pub from_status: ModerationStatus,
+    pub to_status: ModerationStatus,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub actor_user_id: Option<ObjectId>,
+    pub actor_name: Option<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoreModeration {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub exam_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_subject_id: ObjectId,
+    pub subject_name: Option<String>,
+    pub class_name: Option<String>,
+
+    pub status: ModerationStatus,
+    /// Scores entered for the subject and exam
+    pub score_count: i64,
+
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+
+    #[serde(default)]
+    pub history: Vec<ModerationEvent>,
+}
+
+/// Where every subject of an exam stands
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExamModeration {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub exam_id: ObjectId,
+    pub results_published_at: Option<DateTime<Utc>>,
+    /// True once every subject with marks is approved
+    pub ready_to_publish: bool,
+    pub subjects: Vec<ScoreModeration>,
+}
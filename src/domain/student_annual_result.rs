@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// Relative weight of one term in the annual result. Weights do not need to
+/// add up to 100; they are normalised over the terms a student has results for.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TermWeight {
+    pub term_id: String,
+    pub weight: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnnualTermResult {
+    pub term_id: String,
+    pub weight: f64,
+    pub average_percentage: f64,
+    pub gpa: f64,
+    pub results_counted: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnnualSubjectResult {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_subject_id: Option<ObjectId>,
+    pub subject_name: String,
+    pub percentage: f64,
+    pub grade: String,
+    pub credits: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StudentAnnualResult {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub school_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub student_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub education_year_id: Option<ObjectId>,
+
+    pub term_results: Vec<AnnualTermResult>,
+    pub subject_results: Vec<AnnualSubjectResult>,
+
+    pub average_percentage: f64,
+    pub gpa: f64,
+    /// Mean of the annual GPAs of this and every earlier education year
+    pub cumulative_gpa: Option<f64>,
+    pub total_credits: Option<i32>,
+    pub grade: String,
+
+    pub rank_in_class: Option<i32>,
+    pub total_students: Option<i32>,
+
+    #[serde(default)]
+    pub calculated_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub is_finalized: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct YearGpa {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub education_year_id: Option<ObjectId>,
+    pub label: String,
+    pub average_percentage: f64,
+    pub gpa: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CumulativeGpa {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub student_id: Option<ObjectId>,
+    pub years: Vec<YearGpa>,
+    pub cumulative_gpa: f64,
+}
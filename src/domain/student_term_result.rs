@@ -117,3 +117,34 @@ pub struct StudentTermResultWithRelations {
     pub education_year: Option<crate::domain::education_year::EducationYear>,
     pub exam: Option<crate::domain::exam::Exam>,
 }
+
+/// A student's average on an exam against their previous exam
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StudentResultDelta {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub student_id: ObjectId,
+    pub student_name: Option<String>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub previous_exam_id: ObjectId,
+    pub previous_percentage: f64,
+    pub current_percentage: f64,
+    /// Percentage points gained, negative for a drop
+    pub delta: f64,
+    /// The drop is larger than the threshold asked for
+    pub flagged: bool,
+}
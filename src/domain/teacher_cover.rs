@@ -0,0 +1,242 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+/// Longest date range a schedule view may span
+pub const MAX_SCHEDULE_DAYS: i64 = 31;
+/// Suggestions returned for each uncovered period
+pub const MAX_COVER_SUGGESTIONS: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AbsenceStatus {
+    #[default]
+    Active,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverStatus {
+    #[default]
+    Confirmed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TeacherAbsence {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub school_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub teacher_id: ObjectId,
+
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub status: AbsenceStatus,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub created_by: Option<ObjectId>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateTeacherAbsence {
+    pub teacher_id: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub reason: Option<String>,
+}
+
+impl CreateTeacherAbsence {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ends_on < self.starts_on {
+            return Err("ends_on cannot be earlier than starts_on".into());
+        }
+        if (self.ends_on - self.starts_on).num_days() > 366 {
+            return Err("An absence cannot span more than a year".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TeacherCover {
+    #[serde(
+        rename = "_id",
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub school_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub absence_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_timetable_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub period_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_subject_id: Option<ObjectId>,
+
+    pub cover_date: NaiveDate,
+    pub starts_at: NaiveTime,
+    pub ends_at: NaiveTime,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub absent_teacher_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub cover_teacher_id: ObjectId,
+
+    #[serde(default)]
+    pub status: CoverStatus,
+    pub note: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConfirmCover {
+    pub period_id: String,
+    pub cover_date: NaiveDate,
+    pub cover_teacher_id: String,
+    pub note: Option<String>,
+}
+
+/// A teacher who could take an affected period
+#[derive(Debug, Serialize, Clone)]
+pub struct CoverSuggestion {
+    #[serde(serialize_with = "object_id_helpers::serialize_oid")]
+    pub teacher_id: ObjectId,
+    pub name: Option<String>,
+    /// Teaches the same subject somewhere in the school
+    pub qualified: bool,
+    /// Already teaches the class
+    pub knows_class: bool,
+    /// Periods the teacher already has that day
+    pub periods_that_day: usize,
+}
+
+/// A lesson on a specific date, with any cover applied
+#[derive(Debug, Serialize, Clone)]
+pub struct DatedPeriod {
+    pub date: NaiveDate,
+    pub day: Weekday,
+    #[serde(serialize_with = "object_id_helpers::serialize_oid")]
+    pub class_id: ObjectId,
+    #[serde(serialize_with = "object_id_helpers::serialize_oid")]
+    pub class_timetable_id: ObjectId,
+    #[serde(serialize_with = "object_id_helpers::serialize_oid")]
+    pub period_id: ObjectId,
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub class_subject_id: Option<ObjectId>,
+    pub subject_name: Option<String>,
+    pub starts_at: NaiveTime,
+    pub ends_at: NaiveTime,
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub room_id: Option<ObjectId>,
+    /// Teacher taking the lesson on this date
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub teacher_id: Option<ObjectId>,
+    /// Timetabled teacher, set only when someone else covers
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub substituted_teacher_id: Option<ObjectId>,
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cover_id: Option<ObjectId>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AffectedPeriod {
+    #[serde(flatten)]
+    pub period: DatedPeriod,
+    pub suggestions: Vec<CoverSuggestion>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AbsenceCoverPlan {
+    pub absence: TeacherAbsence,
+    pub periods: Vec<AffectedPeriod>,
+    pub covered: usize,
+    pub uncovered: usize,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DateRangeQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub teacher_id: Option<String>,
+}
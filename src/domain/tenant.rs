@@ -0,0 +1,23 @@
+/// School-owned records a tenant guard can resolve the owning school of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantEntity {
+    School,
+    Class,
+    ClassSubject,
+    Teacher,
+    Exam,
+    Student,
+}
+
+impl TenantEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TenantEntity::School => "school",
+            TenantEntity::Class => "class",
+            TenantEntity::ClassSubject => "class subject",
+            TenantEntity::Teacher => "teacher",
+            TenantEntity::Exam => "exam",
+            TenantEntity::Student => "student",
+        }
+    }
+}
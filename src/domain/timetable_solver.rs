@@ -0,0 +1,103 @@
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::class_timetable::{ClassTimetable, DayStructureConfig},
+    helpers::object_id_helpers,
+    utils::object_id::ObjectId,
+};
+
+/// Consecutive periods of one subject a class may have when no rule says otherwise
+pub const DEFAULT_MAX_CONSECUTIVE_PERIODS: i32 = 2;
+
+/// Scheduling inputs for one class subject
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubjectSchedulingRule {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub class_subject_id: Option<ObjectId>,
+
+    /// Lessons must take place in a room of this type, e.g. "Science Lab"
+    pub required_room_type: Option<String>,
+    /// Heavy subjects are kept in the morning. Defaults from the subject category.
+    pub is_heavy: Option<bool>,
+    pub max_consecutive_periods: Option<i32>,
+}
+
+impl SubjectSchedulingRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.max_consecutive_periods, Some(max) if max <= 0) {
+            return Err("max_consecutive_periods must be a positive number".into());
+        }
+        if matches!(&self.required_room_type, Some(room_type) if room_type.trim().is_empty()) {
+            return Err("required_room_type cannot be empty".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TimetableSolveRequest {
+    /// Term to plan. Defaults to the current term.
+    pub term_order: Option<i32>,
+    /// Classes to plan. Defaults to every active class of the school.
+    #[serde(default)]
+    pub class_ids: Option<Vec<String>>,
+    pub days: Option<Vec<Weekday>>,
+    /// "HH:MM" start of the first period
+    pub start_time: Option<String>,
+    pub day_template: Option<Vec<DayStructureConfig>>,
+    /// Longest run of periods a teacher may teach back to back
+    pub teacher_max_consecutive_periods: Option<i32>,
+    /// Overwrite timetables the classes already have for the term
+    #[serde(default)]
+    pub replace_existing: bool,
+    /// Solve without saving
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TimetableConstraint {
+    TeacherDoubleBooked,
+    TeacherUnavailable,
+    TeacherMaxConsecutive,
+    RoomUnavailable,
+    MaxConsecutive,
+    ClassFull,
+}
+
+/// A lesson the solver could not place, with the constraint that blocked it most
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnplacedLesson {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_subject_id: ObjectId,
+
+    pub subject_name: String,
+    pub constraint: TimetableConstraint,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimetableSolveResult {
+    pub term_order: i32,
+    pub timetables: Vec<ClassTimetable>,
+    pub unplaced: Vec<UnplacedLesson>,
+    pub lessons_placed: usize,
+    /// Sum of soft preference penalties, lower is better
+    pub penalty: i64,
+    pub saved: bool,
+}
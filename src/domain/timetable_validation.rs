@@ -0,0 +1,133 @@
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::object_id_helpers, utils::object_id::ObjectId};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimetableIssueKind {
+    /// A teacher has overlapping periods in two classes, or twice in one
+    TeacherDoubleBooked,
+    /// A subject period overlaps a break or lunch of the school timetable
+    BreakOverlap,
+    /// A subject period is scheduled on a holiday or non-school day
+    HolidayPeriod,
+    /// A subject has fewer weekly periods than its estimated hours
+    UnderScheduled,
+}
+
+impl TimetableIssueKind {
+    /// Hard clashes make a save fail; the rest are warnings
+    pub fn is_hard(&self) -> bool {
+        !matches!(self, TimetableIssueKind::UnderScheduled)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimetableIssue {
+    pub kind: TimetableIssueKind,
+    pub hard: bool,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub class_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub timetable_id: Option<ObjectId>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<Weekday>,
+
+    /// "HH:MM" start of the offending period
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub period_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub subject_id: Option<ObjectId>,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub teacher_id: Option<ObjectId>,
+
+    /// The class on the other side of a double booking
+    #[serde(
+        serialize_with = "object_id_helpers::serialize",
+        deserialize_with = "object_id_helpers::deserialize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub other_class_id: Option<ObjectId>,
+
+    pub message: String,
+}
+
+impl TimetableIssue {
+    /// Identifies the same clash across two versions of a timetable, where
+    /// period ids may have been regenerated.
+    pub fn key(&self) -> String {
+        format!(
+            "{:?}|{}|{:?}|{}|{}|{}|{}",
+            self.kind,
+            self.class_id.to_hex(),
+            self.day,
+            self.start_time.as_deref().unwrap_or(""),
+            self.subject_id.map(|id| id.to_hex()).unwrap_or_default(),
+            self.teacher_id.map(|id| id.to_hex()).unwrap_or_default(),
+            self.other_class_id
+                .map(|id| id.to_hex())
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimetableValidationReport {
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub school_id: ObjectId,
+
+    #[serde(
+        serialize_with = "object_id_helpers::serialize_oid",
+        deserialize_with = "object_id_helpers::deserialize_oid"
+    )]
+    pub education_year_id: ObjectId,
+
+    pub term_order: i32,
+    pub timetables_checked: usize,
+    pub hard_clashes: usize,
+    pub warnings: usize,
+    pub issues: Vec<TimetableIssue>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TimetableValidationQuery {
+    /// Defaults to the current education year
+    pub education_year_id: Option<String>,
+    /// Defaults to the current term
+    pub term_order: Option<i32>,
+}
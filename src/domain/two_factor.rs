@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::auth::LoginResponse;
+
+/// Returned by login instead of tokens while a second factor is owed
+#[derive(Debug, Serialize)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    /// The user has no authenticator yet and has to enrol before signing in,
+    /// because a school they work at requires it
+    pub setup_required: bool,
+    /// Seconds the challenge stays valid
+    pub expires_in: i64,
+}
+
+/// What login produced: tokens, or a challenge for the second factor
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Authenticated(Box<LoginResponse>),
+    SecondFactorRequired(TwoFactorChallenge),
+}
+
+/// Secret for a pending enrolment, shown once for the authenticator app
+#[derive(Debug, Serialize)]
+pub struct TwoFactorSetup {
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// Required by a school the user works at, so it cannot be turned off
+    pub required: bool,
+    pub recovery_codes_left: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Tokens issued once the second factor checked out, with the recovery
+/// codes when this sign-in also completed enrolment
+#[derive(Serialize)]
+pub struct TwoFactorLoginResponse {
+    #[serde(flatten)]
+    pub login: LoginResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorChallengeRequest {
+    pub challenge_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorVerifyRequest {
+    pub challenge_token: String,
+    /// Authenticator code or an unused recovery code
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchoolTwoFactorPolicy {
+    pub require_two_factor: bool,
+}
@@ -1,10 +1,123 @@
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use serde::Serialize;
 use std::fmt;
 
+tokio::task_local! {
+    /// Id of the request being handled, set by the request logging middleware
+    pub static REQUEST_ID: String;
+}
+
+/// Machine-readable error kind; clients branch on this rather than on the
+/// message text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    BadRequest,
+    Validation,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PayloadTooLarge,
+    TooManyRequests,
+    Database,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::BadRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Validation => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Database | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// One invalid input field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AppError {
+    pub code: ErrorCode,
     pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+    /// Matches the `x-request-id` response header and the request log
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        AppError {
+            code,
+            message: message.into(),
+            details: Vec::new(),
+            request_id: REQUEST_ID.try_with(|id| id.clone()).ok(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::BadRequest, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Validation, message)
+    }
+
+    /// Validation failure of a single named field
+    pub fn field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::validation(message.clone()).with_detail(field, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unauthorized, message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Forbidden, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Conflict, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn with_detail(mut self, field: impl Into<String>, message: impl Into<String>) -> Self {
+        self.details.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.code.status()
+    }
+
+    /// The error as a JSON response with the status its code maps to
+    pub fn to_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status()).json(self)
+    }
 }
 
 impl fmt::Display for AppError {
@@ -13,10 +126,52 @@ impl fmt::Display for AppError {
     }
 }
 
+impl std::error::Error for AppError {}
+
 impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        self.status()
+    }
+
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": self.message
-        }))
+        self.to_response()
+    }
+}
+
+/// Central mapping of Postgres failures. Constraint violations are the
+/// client's doing and say which constraint; anything else is logged and
+/// reported without driver details.
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::RowNotFound => AppError::not_found("Record not found"),
+            sqlx::Error::Database(db) => {
+                let constraint = db.constraint().map(str::to_string);
+                let with_constraint = |err: AppError| match &constraint {
+                    Some(name) => err.with_detail(name.clone(), db.message()),
+                    None => err,
+                };
+                match db.kind() {
+                    sqlx::error::ErrorKind::UniqueViolation => with_constraint(AppError::conflict(
+                        "A record with these values already exists",
+                    )),
+                    sqlx::error::ErrorKind::ForeignKeyViolation => with_constraint(
+                        AppError::conflict("A referenced record does not exist or is still in use"),
+                    ),
+                    sqlx::error::ErrorKind::NotNullViolation
+                    | sqlx::error::ErrorKind::CheckViolation => {
+                        with_constraint(AppError::validation("A value is missing or out of range"))
+                    }
+                    _ => {
+                        log::error!("PostgreSQL Error: {}", error);
+                        AppError::new(ErrorCode::Database, "PostgreSQL Error")
+                    }
+                }
+            }
+            _ => {
+                log::error!("PostgreSQL Error: {}", error);
+                AppError::new(ErrorCode::Database, "PostgreSQL Error")
+            }
+        }
     }
 }
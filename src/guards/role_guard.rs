@@ -1,5 +1,16 @@
-use crate::domain::{auth_user::AuthUserDto, common_details::UserRole};
-use actix_web::{error, Error};
+use crate::{
+    domain::{
+        auth_user::AuthUserDto, common_details::UserRole, role::PermissionTarget,
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    services::{
+        parent_service::ParentService, permission_service::PermissionService,
+        tenant_service::TenantService,
+    },
+    utils::request_context::request_context,
+};
+use actix_web::{error, Error, HttpRequest};
 
 /// Check if user is admin
 pub fn is_admin(user: &AuthUserDto) -> bool {
@@ -38,270 +49,238 @@ pub fn check_admin(user: &AuthUserDto) -> Result<(), Error> {
     }
 }
 
-/// Check if user is admin or school staff
-pub fn check_admin_or_staff(user: &AuthUserDto) -> Result<(), String> {
-    if user.role == Some(UserRole::ADMIN) || user.role == Some(UserRole::SCHOOLSTAFF) {
-        Ok(())
-    } else {
-        Err("Insufficient permissions. Requires Admin or School staff role.".to_string())
+/// Require the caller to be an active member of the school owning the
+/// record, and return that school's id. Admins pass for any school.
+pub async fn require_tenant_access(
+    user: &AuthUserDto,
+    entity: TenantEntity,
+    id: &str,
+    tenant_service: &TenantService,
+) -> Result<String, String> {
+    let school_id = tenant_service
+        .school_of(entity, id)
+        .await
+        .map_err(|err| err.message)?;
+
+    if user.role == Some(UserRole::ADMIN) {
+        return Ok(school_id);
+    }
+
+    match tenant_service.is_member(&user.id, &school_id).await {
+        Ok(true) => Ok(school_id),
+        Ok(false) => Err(format!(
+            "Access denied: this {} belongs to a school you are not a member of",
+            entity.as_str()
+        )),
+        Err(_) => Err("Error checking school membership".to_string()),
     }
 }
 
-/// Check if user has access to school operations
-pub fn check_school_access(user: &AuthUserDto, _school_id: &str) -> Result<(), String> {
-    // Admin has full access
+/// School the request acts on, from the signed school token or the user's
+/// current school, else an `x-school-id` header. Whichever names it, the
+/// caller must be an active member of that school; admins pass for any.
+pub async fn require_request_school(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    tenant_service: &TenantService,
+) -> Result<String, AppError> {
+    let school_id = request_context(req)
+        .school_id
+        .ok_or_else(|| AppError::bad_request("School ID is required"))?;
+
     if user.role == Some(UserRole::ADMIN) {
-        return Ok(());
+        return Ok(school_id);
     }
 
-    // School staff can access schools they're associated with
-    if user.role == Some(UserRole::SCHOOLSTAFF) {
-        // Here you might want to check if the staff member belongs to this school
-        // For now, we'll allow all school staff to update any school
-        // You can implement more granular permissions later
-        return Ok(());
+    if tenant_service.is_member(&user.id, &school_id).await? {
+        Ok(school_id)
+    } else {
+        Err(AppError::forbidden(
+            "Access denied: you are not a member of this school",
+        ))
     }
-
-    Err("Insufficient permissions to access school".to_string())
 }
 
-pub fn check_admin_staff_or_teacher(user: &AuthUserDto) -> Result<(), String> {
-    if user.role == Some(UserRole::SCHOOLSTAFF)
-        || user.role == Some(UserRole::ADMIN)
-        || user.role == Some(UserRole::TEACHER)
-    {
-        Ok(())
-    } else {
-        Err("Access denied: Admin, Staff, or Teacher role required".to_string())
+/// Check if user has access to school operations: admins, or staff who are
+/// active members of the school
+pub async fn check_school_access(
+    user: &AuthUserDto,
+    school_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
+    if user.role == Some(UserRole::ADMIN) {
+        return Ok(());
+    }
+
+    if user.role != Some(UserRole::SCHOOLSTAFF) {
+        return Err("Insufficient permissions to access school".to_string());
     }
+
+    require_tenant_access(user, TenantEntity::School, school_id, tenant_service)
+        .await
+        .map(|_| ())
 }
 
-/// Check if user has access to the class (admin, class teacher, or school staff)
-pub fn check_class_access(user: &AuthUserDto, class_id: &str) -> Result<(), String> {
-    // Admin has access to everything
+/// Check if user has access to the class: admins, staff of the school that
+/// owns it, or a member teacher who teaches it
+pub async fn check_class_access(
+    user: &AuthUserDto,
+    class_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
     if user.role == Some(UserRole::ADMIN) {
         return Ok(());
     }
 
-    // Check if user is the class teacher for this class
-    if user.role == Some(UserRole::TEACHER) {
-        // Use unwrap_or_default() to handle Option<Vec<String>>
-        if user
-            .accessible_classes
-            .as_ref()
-            .unwrap_or(&Vec::new())
-            .contains(&class_id.to_string())
-        {
-            return Ok(());
-        }
+    if user.role != Some(UserRole::SCHOOLSTAFF) && user.role != Some(UserRole::TEACHER) {
+        return Err("Access denied: No permission to access this class".to_string());
     }
 
-    // Check if user is staff member of the school that owns this class
+    require_tenant_access(user, TenantEntity::Class, class_id, tenant_service).await?;
+
     if user.role == Some(UserRole::SCHOOLSTAFF) {
-        // For school staff, we can check if they have access to the school that owns this class
-        // This would typically require a database query to get the school_id from the class_id
-        // For now, we'll implement a basic check using the schools field
-        if let Some(schools) = &user.schools {
-            // In a real implementation, you would query the class to get its school_id
-            // and check if that school_id is in the user's schools list
-            // For now, we'll return true if the user has any schools (placeholder)
-            if !schools.is_empty() {
-                return Ok(());
-            }
-        }
+        return Ok(());
     }
 
-    Err("Access denied: No permission to access this class".to_string())
+    match tenant_service.teaches_class(&user.id, class_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Access denied: No permission to access this class".to_string()),
+        Err(_) => Err("Error checking class access".to_string()),
+    }
 }
 
-/// Check if user is admin or class teacher
-pub fn check_admin_or_class_teacher(user: &AuthUserDto, class_id: &str) -> Result<(), String> {
+/// Check if user is admin or a member teacher who teaches the class
+pub async fn check_admin_or_class_teacher(
+    user: &AuthUserDto,
+    class_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
     if user.role == Some(UserRole::ADMIN) {
         return Ok(());
     }
 
-    if user.role == Some(UserRole::TEACHER)
-        && user
-            .accessible_classes
-            .as_ref()
-            .unwrap_or(&Vec::new())
-            .contains(&class_id.to_string())
-    {
-        return Ok(());
+    if user.role != Some(UserRole::TEACHER) {
+        return Err("Access denied: Admin or Class Teacher role required".to_string());
     }
 
-    Err("Access denied: Admin or Class Teacher role required".to_string())
+    require_tenant_access(user, TenantEntity::Class, class_id, tenant_service).await?;
+
+    match tenant_service.teaches_class(&user.id, class_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Access denied: Admin or Class Teacher role required".to_string()),
+        Err(_) => Err("Error checking class access".to_string()),
+    }
 }
 
 // ========== SUBJECT ACCESS CONTROL ==========
 
-/// Check if user has access to subject (Admin, Staff, or Subject Teacher)
-pub fn check_subject_access(user: &AuthUserDto, subject_id: &str) -> Result<(), String> {
-    // Admin has full access
+/// Check if user has access to the class subject: admins, staff of the
+/// school that owns it, or a member teacher who teaches it
+pub async fn check_subject_access(
+    user: &AuthUserDto,
+    subject_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
     if user.role == Some(UserRole::ADMIN) {
         return Ok(());
     }
 
-    // School staff can access subjects
-    if user.role == Some(UserRole::SCHOOLSTAFF) {
-        return Ok(());
+    if user.role != Some(UserRole::SCHOOLSTAFF) && user.role != Some(UserRole::TEACHER) {
+        return Err("Access denied: insufficient permissions for subject".to_string());
     }
 
-    // For teachers, check if they are the class teacher for this subject
-    if user.role == Some(UserRole::TEACHER) && is_subject_teacher(&user.id, subject_id) {
+    require_tenant_access(user, TenantEntity::ClassSubject, subject_id, tenant_service).await?;
+
+    if user.role == Some(UserRole::SCHOOLSTAFF) {
         return Ok(());
     }
 
-    Err("Access denied: insufficient permissions for subject".to_string())
+    match tenant_service.teaches_subject(&user.id, subject_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Access denied: insufficient permissions for subject".to_string()),
+        Err(_) => Err("Error checking subject access".to_string()),
+    }
 }
 
-/// Check if user is Admin or Subject Teacher
-pub fn check_admin_or_subject_teacher(user: &AuthUserDto, subject_id: &str) -> Result<(), String> {
+/// Check if user is Admin or a member teacher who teaches the class subject
+pub async fn check_admin_or_subject_teacher(
+    user: &AuthUserDto,
+    subject_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
     if user.role == Some(UserRole::ADMIN) {
         return Ok(());
     }
 
-    if user.role == Some(UserRole::TEACHER) && is_subject_teacher(&user.id, subject_id) {
-        return Ok(());
+    if user.role != Some(UserRole::TEACHER) {
+        return Err("Access denied: must be admin or subject teacher".to_string());
     }
 
-    Err("Access denied: must be admin or subject teacher".to_string())
-}
+    require_tenant_access(user, TenantEntity::ClassSubject, subject_id, tenant_service).await?;
 
-/// Helper function to check if user is the subject teacher
-/// You'll need to implement this based on your database structure
-fn is_subject_teacher(user_id: &str, subject_id: &str) -> bool {
-    // This is a placeholder implementation
-    // You would typically:
-    // 1. Parse the subject_id to ObjectId
-    // 2. Query the subjects collection to find the subject
-    // 3. Check if the user_id matches the class_teacher_id of the subject
-    let _ = user_id;
-    let _ = subject_id;
-    // For now, return false - implement based on your actual data model
-    // You might want to use your database connection to query the subject
-    // and check if the class_teacher_id matches the user_id
-
-    // Example of how this might look (pseudo-code):
-    /*
-    use crate::repositories::subject_repo::SubjectRepo;
-    use crate::utils::object_id::parse_object_id;
-    use crate::models::id_model::IdType;
-
-    let subject_repo = SubjectRepo::new(&db_connection);
-    let subject_id_parsed = IdType::from_string(subject_id);
-
-    match subject_repo.find_by_id(&subject_id_parsed).await {
-        Ok(Some(subject)) => {
-            if let Some(class_teacher_id) = subject.class_teacher_id {
-                // Compare the ObjectId with user_id (you might need to convert user_id to ObjectId)
-                // This depends on how your user IDs are stored
-                class_teacher_id.to_hex() == user_id
-            } else {
-                false
-            }
-        }
-        _ => false
+    match tenant_service.teaches_subject(&user.id, subject_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Access denied: must be admin or subject teacher".to_string()),
+        Err(_) => Err("Error checking subject access".to_string()),
     }
-    */
-
-    // For now, we'll return a simple check based on accessible_classes
-    // This assumes that if a teacher has access to a class, they might be teaching subjects in that class
-    // Adjust this based on your actual data model
-    false
 }
 
-/// Check if user has access to student (Admin, Staff, Teacher, or Student themselves)
-pub fn check_student_access(user: &AuthUserDto, student_id: &str) -> Result<(), String> {
-    // Admin has full access
+/// Check if user has access to the student: admins, staff and teachers who
+/// are members of the student's school, or the student themselves
+pub async fn check_student_access(
+    user: &AuthUserDto,
+    student_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
     if user.role == Some(UserRole::ADMIN) {
         return Ok(());
     }
 
-    // School staff can access students
-    if user.role == Some(UserRole::SCHOOLSTAFF) {
-        return Ok(());
-    }
-
-    // Teachers can access students
-    if user.role == Some(UserRole::TEACHER) {
-        return Ok(());
+    if user.role == Some(UserRole::STUDENT) {
+        return match tenant_service.is_student_user(&user.id, student_id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("Access denied: insufficient permissions for student".to_string()),
+            Err(_) => Err("Error checking student access".to_string()),
+        };
     }
 
-    // Students can access their own data
-    if user.role == Some(UserRole::STUDENT) {
-        // Check if this student is accessing their own data
-        // This assumes the student_id in the URL matches the user's student record ID
-        // You might need to adjust this logic based on your data model
-        if user.id == student_id {
-            return Ok(());
-        }
+    if user.role != Some(UserRole::SCHOOLSTAFF) && user.role != Some(UserRole::TEACHER) {
+        return Err("Access denied: insufficient permissions for student".to_string());
     }
 
-    Err("Access denied: insufficient permissions for student".to_string())
+    require_tenant_access(user, TenantEntity::Student, student_id, tenant_service)
+        .await
+        .map(|_| ())
 }
 
-/// Check if user is Admin or Student Creator
-pub fn check_admin_or_student_creator(user: &AuthUserDto, student_id: &str) -> Result<(), String> {
+/// Check if user is Admin, staff of the student's school, or the member
+/// teacher who created the student
+pub async fn check_admin_or_student_creator(
+    user: &AuthUserDto,
+    student_id: &str,
+    tenant_service: &TenantService,
+) -> Result<(), String> {
     if user.role == Some(UserRole::ADMIN) {
         return Ok(());
     }
 
-    let _ = student_id;
+    if user.role != Some(UserRole::SCHOOLSTAFF) && user.role != Some(UserRole::TEACHER) {
+        return Err("Access denied: must be admin or student creator".to_string());
+    }
+
+    require_tenant_access(user, TenantEntity::Student, student_id, tenant_service).await?;
 
-    // For non-admin users, we need to check if they created this student
-    // This would typically require a database query to check the creator_id
-    // For now, we'll implement a basic version that checks if the user is staff/teacher
-    // You can enhance this with actual database checks later
-    if user.role == Some(UserRole::SCHOOLSTAFF) || user.role == Some(UserRole::TEACHER) {
-        // In a real implementation, you would:
-        // 1. Parse the student_id to ObjectId
-        // 2. Query the students collection to find the student
-        // 3. Check if the user_id matches the creator_id of the student
-        // For now, we'll return Ok() as a placeholder
+    if user.role == Some(UserRole::SCHOOLSTAFF) {
         return Ok(());
     }
 
-    Err("Access denied: must be admin or student creator".to_string())
+    match tenant_service.created_student(&user.id, student_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Access denied: must be admin or student creator".to_string()),
+        Err(_) => Err("Error checking student access".to_string()),
+    }
 }
 
-// Helper function to check if user is the student creator
-// You'll need to implement this based on your database structure
-// fn is_student_creator(user_id: &str, student_id: &str) -> bool {
-//     // This is a placeholder implementation
-//     // You would typically:
-//     // 1. Parse the student_id to ObjectId
-//     // 2. Query the students collection to find the student
-//     // 3. Check if the user_id matches the creator_id of the student
-
-//     let _ = user_id;
-//     let _ = student_id;
-
-//     // Example of how this might look (pseudo-code):
-//     /*
-//     use crate::repositories::student_repo::StudentRepo;
-//     use crate::utils::object_id::parse_object_id;
-//     use crate::models::id_model::IdType;
-
-//     let student_repo = StudentRepo::new(&db_connection);
-//     let student_id_parsed = IdType::from_string(student_id);
-
-//     match student_repo.find_by_id(&student_id_parsed).await {
-//         Ok(Some(student)) => {
-//             if let Some(creator_id) = student.creator_id {
-//                 // Compare the ObjectId with user_id (you might need to convert user_id to ObjectId)
-//                 creator_id.to_hex() == user_id
-//             } else {
-//                 false
-//             }
-//         }
-//         _ => false
-//     }
-//     */
-//     // For now, return true as a placeholder - implement based on your actual data model
-//     true
-// }
-
 // Check if user is Admin, Staff, Teacher, or the Student themselves
 // pub fn check_student_access_extended(user: &AuthUserDto, student_id: &str) -> Result<(), String> {
 //     // Admin has full access
@@ -360,31 +339,6 @@ pub fn check_admin_or_student_creator(user: &AuthUserDto, student_id: &str) -> R
 //     }
 // }
 
-/// Check if user has access to teacher (Admin, Staff, Teacher themselves)
-pub fn check_teacher_access(user: &AuthUserDto, teacher_id: &str) -> Result<(), String> {
-    // Admin has full access
-    if user.role == Some(UserRole::ADMIN) {
-        return Ok(());
-    }
-
-    // School staff can access teachers
-    if user.role == Some(UserRole::SCHOOLSTAFF) {
-        return Ok(());
-    }
-
-    // Teachers can access their own data
-    if user.role == Some(UserRole::TEACHER) {
-        // Check if this teacher is accessing their own data
-        // This assumes the teacher_id in the URL matches the user's teacher record ID
-        // You might need to adjust this logic based on your data model
-        if user.id == teacher_id {
-            return Ok(());
-        }
-    }
-
-    Err("Access denied: insufficient permissions for teacher".to_string())
-}
-
 /// Check if user is a parent
 pub fn check_parent(user: &AuthUserDto) -> Result<(), String> {
     if user.role == Some(UserRole::PARENT) {
@@ -394,72 +348,6 @@ pub fn check_parent(user: &AuthUserDto) -> Result<(), String> {
     }
 }
 
-/// Check if parent has access to a specific student (async version)
-pub async fn check_parent_access(
-    user: &AuthUserDto,
-    student_id: &str,
-    parent_service: &crate::services::parent_service::ParentService,
-) -> Result<(), String> {
-    // Admin and staff have full access
-    if user.role == Some(UserRole::ADMIN) || user.role == Some(UserRole::SCHOOLSTAFF) {
-        return Ok(());
-    }
-
-    // Check if user is a parent
-    if user.role != Some(UserRole::PARENT) {
-        return Err("Access denied: Parent role required".to_string());
-    }
-
-    let parent = match parent_service
-        .find_by_user_id(&user.id, user.current_school_id.as_deref())
-        .await
-    {
-        Ok(p) => p,
-        Err(_) => return Err("Parent record not found".to_string()),
-    };
-
-    let parent_id = match parent.id {
-        Some(id) => crate::models::id_model::IdType::ObjectId(id),
-        None => return Err("Parent ID not found".to_string()),
-    };
-
-    let student_id_type = crate::models::id_model::IdType::from_string(student_id.to_string());
-
-    // Validate parent has access to this student
-    match parent_service
-        .validate_parent_student_access(&parent_id, &student_id_type)
-        .await
-    {
-        Ok(true) => Ok(()),
-        Ok(false) => Err("Access denied: You do not have access to this student".to_string()),
-        Err(_) => Err("Error validating parent access".to_string()),
-    }
-}
-
-/// Check if user is Admin or Teacher Creator
-pub fn check_admin_or_teacher_creator(user: &AuthUserDto, teacher_id: &str) -> Result<(), String> {
-    if user.role == Some(UserRole::ADMIN) {
-        return Ok(());
-    }
-
-    let _ = teacher_id;
-
-    // For non-admin users, we need to check if they created this teacher
-    // This would typically require a database query to check the creator_id
-    // For now, we'll implement a basic version that checks if the user is staff/teacher
-    // You can enhance this with actual database checks later
-    if user.role == Some(UserRole::SCHOOLSTAFF) || user.role == Some(UserRole::TEACHER) {
-        // In a real implementation, you would:
-        // 1. Parse the teacher_id to ObjectId
-        // 2. Query the teachers collection to find the teacher
-        // 3. Check if the user_id matches the creator_id of the teacher
-        // For now, we'll return Ok() as a placeholder
-        return Ok(());
-    }
-
-    Err("Access denied: must be admin or teacher creator".to_string())
-}
-
 // Helper function to check if user is the teacher
 // You'll need to implement this based on your database structure
 // fn is_teacher_user(user_id: &str, teacher_id: &str) -> bool {
@@ -506,27 +394,18 @@ pub fn check_admin_or_teacher_creator(user: &AuthUserDto, teacher_id: &str) -> R
 // NEW PERMISSION-BASED GUARDS
 // =========================
 
-/// Check if user has a specific permission (synchronous version for simple checks)
-pub fn check_permission(user: &AuthUserDto, permission: &str) -> Result<(), String> {
-    // Admin always has all permissions
-    if user.role == Some(UserRole::ADMIN) {
-        return Ok(());
-    }
-
-    // School staff has audit.view permission
-    if permission == "audit.view" && user.role == Some(UserRole::SCHOOLSTAFF) {
-        return Ok(());
-    }
-
-    // Analytics permissions
-    if permission == "analytics.read.school" {
-        // Director, Admin, Accountant, Vice President, School Staff can view analytics
-        if user.role == Some(UserRole::SCHOOLSTAFF) {
-            return Ok(());
-        }
-    }
-
-    Err(format!("Access denied: {} permission required", permission))
+/// Check if user holds the permission in the school, built-in or through
+/// one of their school roles. Without a school only admins pass.
+pub async fn check_permission(
+    user: &AuthUserDto,
+    school_id: Option<&str>,
+    permission: &str,
+    permission_service: &PermissionService,
+) -> Result<(), String> {
+    let target = school_id.map(PermissionTarget::school);
+    permission_service
+        .check(user, permission, target.as_ref())
+        .await
 }
 
 /// Require specific role
@@ -538,66 +417,130 @@ pub fn require_role(user: &AuthUserDto, required_role: &UserRole) -> Result<(),
     }
 }
 
-/// Require specific permission (async version)
+/// Require a school-wide grant of the permission (async version)
 pub async fn require_permission(
     user: &AuthUserDto,
     school_id: &str,
     permission: &str,
-    role_service: &crate::services::role_service::RoleService,
+    permission_service: &PermissionService,
 ) -> Result<(), String> {
-    if user.role == Some(UserRole::ADMIN) {
-        return Ok(());
-    }
+    check_permission(user, Some(school_id), permission, permission_service).await
+}
 
-    let user_id = crate::models::id_model::IdType::from_string(user.id.clone());
-    let school_id_type = crate::models::id_model::IdType::from_string(school_id.to_string());
+/// Require the permission on a specific target, honouring the scope it was
+/// granted at: `School` covers the whole school, `Class` the classes the
+/// user teaches and `Own` records the user owns
+pub async fn require_scoped_permission(
+    user: &AuthUserDto,
+    permission: &str,
+    target: &PermissionTarget,
+    permission_service: &PermissionService,
+) -> Result<(), String> {
+    permission_service
+        .check(user, permission, Some(target))
+        .await
+}
 
-    match role_service
-        .user_has_permission(&user_id, &school_id_type, permission)
+/// Require the permission in the school the request acts on, resolved as
+/// in [`require_request_school`], and return that school
+pub async fn require_request_permission(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    permission: &str,
+    permission_service: &PermissionService,
+) -> Result<String, AppError> {
+    let tenant_service = TenantService::new(&permission_service.pool);
+    let school_id = require_request_school(req, user, &tenant_service).await?;
+    require_permission(user, &school_id, permission, permission_service)
         .await
-    {
-        Ok(true) => Ok(()),
-        Ok(false) => Err(format!("Access denied: {} permission required", permission)),
-        Err(_) => Err("Error checking permissions".to_string()),
-    }
+        .map_err(AppError::forbidden)?;
+    Ok(school_id)
+}
+
+/// Require the permission in the request's school when it names one, and
+/// as a platform action otherwise. Returns the school the request acts on.
+pub async fn require_context_permission(
+    req: &HttpRequest,
+    user: &AuthUserDto,
+    permission: &str,
+    permission_service: &PermissionService,
+) -> Result<Option<String>, AppError> {
+    if request_context(req).school_id.is_some() {
+        return require_request_permission(req, user, permission, permission_service)
+            .await
+            .map(Some);
+    }
+    check_permission(user, None, permission, permission_service)
+        .await
+        .map_err(AppError::forbidden)?;
+    Ok(None)
+}
+
+/// Require the permission in the school owning the record and return that
+/// school. A class is passed on as the target, so class-scoped grants
+/// cover only the classes the user teaches.
+pub async fn require_entity_permission(
+    user: &AuthUserDto,
+    entity: TenantEntity,
+    id: &str,
+    permission: &str,
+    permission_service: &PermissionService,
+) -> Result<String, AppError> {
+    let school_id = TenantService::new(&permission_service.pool)
+        .school_of(entity, id)
+        .await?;
+    let mut target = PermissionTarget::school(&school_id);
+    if entity == TenantEntity::Class {
+        target = target.class(id);
+    }
+    require_scoped_permission(user, permission, &target, permission_service)
+        .await
+        .map_err(AppError::forbidden)?;
+    Ok(school_id)
 }
 
-/// Require parent-child access (async version)
+/// Require access to a student's records: parents through their link to
+/// the child, everyone else through the permission in the school and class
+/// the student is enrolled in
 pub async fn require_parent_child_access(
     user: &AuthUserDto,
     student_id: &str,
-    parent_service: &crate::services::parent_service::ParentService,
-) -> Result<(), String> {
-    if user.role == Some(UserRole::ADMIN) || user.role == Some(UserRole::SCHOOLSTAFF) {
-        return Ok(());
-    }
-
+    permission: &str,
+    permission_service: &PermissionService,
+) -> Result<(), AppError> {
     if user.role != Some(UserRole::PARENT) {
-        return Err("Access denied: Parent role required".to_string());
+        let (school_id, class_id) = TenantService::new(&permission_service.pool)
+            .student_placement(student_id)
+            .await?;
+        let mut target = PermissionTarget::school(school_id);
+        if let Some(class_id) = class_id {
+            target = target.class(class_id);
+        }
+        return require_scoped_permission(user, permission, &target, permission_service)
+            .await
+            .map_err(AppError::forbidden);
     }
 
-    let parent = match parent_service
+    let parent_service = ParentService::new(&permission_service.pool);
+    let parent = parent_service
         .find_by_user_id(&user.id, user.current_school_id.as_deref())
         .await
-    {
-        Ok(p) => p,
-        Err(_) => return Err("Parent record not found".to_string()),
-    };
-
-    let parent_id = match parent.id {
-        Some(id) => crate::models::id_model::IdType::ObjectId(id),
-        None => return Err("Parent ID not found".to_string()),
-    };
+        .map_err(|_| AppError::forbidden("Parent record not found"))?;
+    let parent_id = parent
+        .id
+        .map(crate::models::id_model::IdType::ObjectId)
+        .ok_or_else(|| AppError::forbidden("Parent ID not found"))?;
 
     let student_id_type = crate::models::id_model::IdType::from_string(student_id.to_string());
-
-    match parent_service
+    if parent_service
         .validate_parent_student_access(&parent_id, &student_id_type)
-        .await
+        .await?
     {
-        Ok(true) => Ok(()),
-        Ok(false) => Err("Access denied: You do not have access to this student".to_string()),
-        Err(_) => Err("Error validating parent access".to_string()),
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Access denied: You do not have access to this student",
+        ))
     }
 }
 
@@ -1,161 +1,813 @@
-use crate::{domain::class_timetable::{DayStructureConfig, PeriodType}, utils::object_id::ObjectId};
+use std::collections::HashMap;
 
-use chrono::Weekday;
-use rand::{seq::SliceRandom, thread_rng};
+use chrono::{NaiveTime, Timelike, Weekday};
 
-// Import your structs
-use crate::domain::{
-    class_subject::ClassSubject,
-    class_timetable::{ClassTimetable, Period, WeekSchedule},
+use crate::{
+    domain::{
+        class_timetable::{ClassTimetable, DayStructureConfig, Period, PeriodType, WeekSchedule},
+        common_details::DailyAvailability,
+        timetable_solver::{TimetableConstraint, UnplacedLesson},
+    },
+    utils::object_id::ObjectId,
 };
 
-pub fn auto_generate_schedule(
-    class_id: ObjectId,
-    education_year_id: ObjectId,
-    term_order: i32,
-    mut subjects: Vec<ClassSubject>,
-    start_time_str: String,                // e.g. "08:00"
-    days_to_schedule: Vec<Weekday>,        // e.g. [Mon, Tue, Wed, Thu, Fri]
-    day_template: Vec<DayStructureConfig>, // The structure from step 1
-) -> Result<ClassTimetable, String> {
-    // 1. ANALYZE CAPACITY
-    // How many "Subject" slots do we have per day?
-    let subject_slots_per_day = day_template
-        .iter()
-        .filter(|s| matches!(s.period_type, PeriodType::Subject))
-        .count();
+/// A class subject as the solver sees it
+#[derive(Debug, Clone)]
+pub struct SolverSubject {
+    pub id: ObjectId,
+    pub class_id: ObjectId,
+    pub name: String,
+    pub teacher_id: Option<ObjectId>,
+    /// Credits, or estimated hours when there are no credits
+    pub weight: i32,
+    pub required_room_type: Option<String>,
+    pub is_heavy: bool,
+    pub max_consecutive: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverRoom {
+    pub id: ObjectId,
+    pub room_type: String,
+}
+
+/// Time already taken by a timetable the solver must not change
+#[derive(Debug, Clone)]
+pub struct Booking {
+    pub day: Weekday,
+    /// Minutes since midnight
+    pub start: i32,
+    pub end: i32,
+    pub teacher_id: Option<ObjectId>,
+    pub room_id: Option<ObjectId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverInput {
+    pub education_year_id: ObjectId,
+    pub term_order: i32,
+    pub class_ids: Vec<ObjectId>,
+    pub subjects: Vec<SolverSubject>,
+    pub rooms: Vec<SolverRoom>,
+    /// Teachers without an entry are always available
+    pub availability: HashMap<ObjectId, Vec<DailyAvailability>>,
+    pub fixed_bookings: Vec<Booking>,
+    pub days: Vec<Weekday>,
+    pub start_time: NaiveTime,
+    pub day_template: Vec<DayStructureConfig>,
+    pub teacher_max_consecutive: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverOutput {
+    pub timetables: Vec<ClassTimetable>,
+    pub unplaced: Vec<UnplacedLesson>,
+    pub lessons_placed: usize,
+    pub penalty: i64,
+}
+
+const NOON: i32 = 12 * 60;
+const SAME_DAY_PENALTY: i64 = 10;
+const HEAVY_AFTERNOON_PENALTY: i64 = 6;
+const LIGHT_MORNING_PENALTY: i64 = 1;
+const TEACHER_DAY_LOAD_PENALTY: i64 = 1;
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    start: i32,
+    end: i32,
+    /// Subject slots in the same block are not separated by a break
+    block: usize,
+}
 
-    let total_weekly_capacity = subject_slots_per_day * days_to_schedule.len();
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    cell: usize,
+    room: Option<usize>,
+    cost: i64,
+}
 
-    // 2. CALCULATE SUBJECT WEIGHTS
-    // Use 'credits' preferably, fall back to 'estimated_hours', default to 1
-    let total_credits: i32 = subjects
-        .iter()
-        .map(|s| s.credits.or(Some(s.estimated_hours)).unwrap_or(1))
-        .sum();
+struct Solver<'a> {
+    input: &'a SolverInput,
+    slots: Vec<Slot>,
+    /// lesson -> subject index
+    lessons: Vec<usize>,
+    placements: Vec<Option<Placement>>,
+    class_cells: HashMap<ObjectId, Vec<Option<usize>>>,
+    teacher_cells: HashMap<ObjectId, Vec<bool>>,
+    room_cells: Vec<Vec<bool>>,
+    available: HashMap<ObjectId, Vec<bool>>,
+}
 
-    if total_credits == 0 {
-        return Err("Cannot generate schedule: Total subject credits is 0".into());
+impl<'a> Solver<'a> {
+    fn new(input: &'a SolverInput) -> Self {
+        let day_start = (input.start_time.hour() * 60 + input.start_time.minute()) as i32;
+        let mut slots = Vec::new();
+        let mut offset = 0;
+        let mut block = 0;
+        for entry in &input.day_template {
+            if entry.period_type == PeriodType::Subject {
+                slots.push(Slot {
+                    start: day_start + offset,
+                    end: day_start + offset + entry.duration_minutes,
+                    block,
+                });
+            } else {
+                block += 1;
+            }
+            offset += entry.duration_minutes;
+        }
+
+        let cells = input.days.len() * slots.len();
+        let mut solver = Self {
+            input,
+            slots,
+            lessons: Vec::new(),
+            placements: Vec::new(),
+            class_cells: input
+                .class_ids
+                .iter()
+                .map(|class_id| (*class_id, vec![None; cells]))
+                .collect(),
+            teacher_cells: HashMap::new(),
+            room_cells: vec![vec![false; cells]; input.rooms.len()],
+            available: HashMap::new(),
+        };
+        solver.book_fixed();
+        solver.index_availability();
+        solver.build_lessons();
+        solver
     }
 
-    // 3. FILL THE "SUBJECT DECK"
-    // Create a list of all subject instances needed.
-    // If Math has high credits, it appears many times in this list.
-    let mut subject_deck: Vec<ObjectId> = Vec::new();
+    fn cell_count(&self) -> usize {
+        self.input.days.len() * self.slots.len()
+    }
 
-    for sub in &subjects {
-        let weight = sub.credits.or(Some(sub.estimated_hours)).unwrap_or(1);
+    fn cell(&self, day: usize, slot: usize) -> usize {
+        day * self.slots.len() + slot
+    }
 
-        // Formula: (Subject Weight / Total Weight) * Available Slots
-        let raw_count = (weight as f32 / total_credits as f32) * total_weekly_capacity as f32;
-        let count = raw_count.round() as usize;
+    fn split(&self, cell: usize) -> (usize, usize) {
+        (cell / self.slots.len(), cell % self.slots.len())
+    }
 
-        if let Some(sid) = sub.id {
-            for _ in 0..count {
-                subject_deck.push(sid);
+    fn book_fixed(&mut self) {
+        let cells = self.cell_count();
+        for booking in &self.input.fixed_bookings {
+            let Some(day) = self.input.days.iter().position(|day| *day == booking.day) else {
+                continue;
+            };
+            for (index, slot) in self.slots.iter().enumerate() {
+                if slot.start >= booking.end || booking.start >= slot.end {
+                    continue;
+                }
+                let cell = day * self.slots.len() + index;
+                if let Some(teacher_id) = booking.teacher_id {
+                    self.teacher_cells
+                        .entry(teacher_id)
+                        .or_insert_with(|| vec![false; cells])[cell] = true;
+                }
+                if let Some(room) = booking
+                    .room_id
+                    .and_then(|room_id| self.input.rooms.iter().position(|r| r.id == room_id))
+                {
+                    self.room_cells[room][cell] = true;
+                }
             }
         }
     }
 
-    // 4. BALANCING THE DECK
-    // If rounding caused us to have too few or too many subjects, fix it.
-    let mut rng = thread_rng();
+    fn index_availability(&mut self) {
+        for (teacher_id, ranges) in &self.input.availability {
+            if ranges.is_empty() {
+                continue;
+            }
+            let mut cells = vec![false; self.cell_count()];
+            for (day_index, day) in self.input.days.iter().enumerate() {
+                for (slot_index, slot) in self.slots.iter().enumerate() {
+                    cells[day_index * self.slots.len() + slot_index] = ranges.iter().any(|range| {
+                        let start = (range.time_range.start.hour() * 60
+                            + range.time_range.start.minute())
+                            as i32;
+                        let end = (range.time_range.end.hour() * 60 + range.time_range.end.minute())
+                            as i32;
+                        range.day == *day && start <= slot.start && slot.end <= end
+                    });
+                }
+            }
+            self.available.insert(*teacher_id, cells);
+        }
+    }
 
-    // Fill if short
-    while subject_deck.len() < total_weekly_capacity {
-        // Pick a random subject to fill the gap
-        if let Some(random_sub) = subjects.choose_mut(&mut rng) {
-            if let Some(sid) = random_sub.id {
-                subject_deck.push(sid);
+    /// Share each class's subject periods out by weight, largest remainder first.
+    fn build_lessons(&mut self) {
+        let capacity = self.cell_count();
+        for class_id in &self.input.class_ids {
+            let subjects = self
+                .input
+                .subjects
+                .iter()
+                .enumerate()
+                .filter(|(_, subject)| subject.class_id == *class_id)
+                .map(|(index, subject)| (index, subject.weight.max(0)))
+                .collect::<Vec<_>>();
+            if subjects.is_empty() || capacity == 0 {
+                continue;
+            }
+
+            let total: i32 = subjects.iter().map(|(_, weight)| weight).sum();
+            let weights = if total == 0 {
+                subjects.iter().map(|(index, _)| (*index, 1)).collect()
+            } else {
+                subjects
+            };
+            let total: i32 = weights.iter().map(|(_, weight)| weight).sum();
+
+            let mut shares = weights
+                .iter()
+                .map(|(index, weight)| {
+                    let exact = *weight as f64 * capacity as f64 / total as f64;
+                    (*index, exact.floor() as usize, exact - exact.floor())
+                })
+                .collect::<Vec<_>>();
+            let mut remaining = capacity - shares.iter().map(|(_, count, _)| count).sum::<usize>();
+            let mut by_remainder = (0..shares.len()).collect::<Vec<_>>();
+            by_remainder.sort_by(|a, b| shares[*b].2.total_cmp(&shares[*a].2));
+            for position in by_remainder {
+                if remaining == 0 {
+                    break;
+                }
+                shares[position].1 += 1;
+                remaining -= 1;
+            }
+
+            for (index, count, _) in shares {
+                self.lessons.extend(std::iter::repeat_n(index, count));
             }
         }
+
+        // Hardest lessons first: fewest usable cells, then heavy, then biggest subjects
+        let flexibility = self
+            .lessons
+            .iter()
+            .map(|subject| self.flexibility(*subject))
+            .collect::<Vec<_>>();
+        let mut order = (0..self.lessons.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let (left, right) = (
+                &self.input.subjects[self.lessons[*a]],
+                &self.input.subjects[self.lessons[*b]],
+            );
+            flexibility[*a]
+                .cmp(&flexibility[*b])
+                .then(right.is_heavy.cmp(&left.is_heavy))
+                .then(right.weight.cmp(&left.weight))
+                .then(left.id.to_hex().cmp(&right.id.to_hex()))
+        });
+        self.lessons = order
+            .into_iter()
+            .map(|lesson| self.lessons[lesson])
+            .collect();
+        self.placements = vec![None; self.lessons.len()];
+    }
+
+    fn flexibility(&self, subject: usize) -> usize {
+        let subject = &self.input.subjects[subject];
+        let room_ok = subject.required_room_type.as_ref().is_none_or(|room_type| {
+            self.input
+                .rooms
+                .iter()
+                .any(|room| room.room_type.eq_ignore_ascii_case(room_type))
+        });
+        if !room_ok {
+            return 0;
+        }
+        (0..self.cell_count())
+            .filter(|cell| self.teacher_available(subject.teacher_id, *cell))
+            .count()
+    }
+
+    fn teacher_available(&self, teacher_id: Option<ObjectId>, cell: usize) -> bool {
+        teacher_id
+            .and_then(|teacher_id| self.available.get(&teacher_id))
+            .is_none_or(|cells| cells[cell])
+    }
+
+    fn teacher_busy(&self, teacher_id: Option<ObjectId>, cell: usize) -> bool {
+        teacher_id
+            .and_then(|teacher_id| self.teacher_cells.get(&teacher_id))
+            .is_some_and(|cells| cells[cell])
     }
-    // Trim if over
-    while subject_deck.len() > total_weekly_capacity {
-        subject_deck.pop();
+
+    /// Length of the run through `cell` within its block where `taken` holds.
+    fn run_through(&self, cell: usize, taken: impl Fn(usize) -> bool) -> i32 {
+        let (day, slot) = self.split(cell);
+        let block = self.slots[slot].block;
+        let mut run = 1;
+        let mut left = slot;
+        while left > 0 && self.slots[left - 1].block == block && taken(self.cell(day, left - 1)) {
+            run += 1;
+            left -= 1;
+        }
+        let mut right = slot + 1;
+        while right < self.slots.len()
+            && self.slots[right].block == block
+            && taken(self.cell(day, right))
+        {
+            run += 1;
+            right += 1;
+        }
+        run
     }
 
-    // Shuffle strictly to ensure randomness before distribution
-    subject_deck.shuffle(&mut rng);
+    /// Check the hard constraints for a lesson of `subject` in an empty `cell`
+    /// and pick a room when one is required.
+    fn check(&self, subject: usize, cell: usize) -> Result<Option<usize>, TimetableConstraint> {
+        let info = &self.input.subjects[subject];
+        if !self.teacher_available(info.teacher_id, cell) {
+            return Err(TimetableConstraint::TeacherUnavailable);
+        }
+        if self.teacher_busy(info.teacher_id, cell) {
+            return Err(TimetableConstraint::TeacherDoubleBooked);
+        }
 
-    // 5. DISTRIBUTE INTO DAYS (Round Robin)
-    // We do this to ensure Math isn't scheduled 5 times on Monday and 0 on Friday.
-    // We create 'buckets' for each day.
-    let mut day_buckets: Vec<Vec<ObjectId>> = vec![Vec::new(); days_to_schedule.len()];
+        let class_cells = &self.class_cells[&info.class_id];
+        let same_subject =
+            |other: usize| class_cells[other].is_some_and(|lesson| self.lessons[lesson] == subject);
+        if self.run_through(cell, same_subject) > info.max_consecutive {
+            return Err(TimetableConstraint::MaxConsecutive);
+        }
 
-    for (i, subject_id) in subject_deck.iter().enumerate() {
-        let day_index = i % days_to_schedule.len();
-        day_buckets[day_index].push(*subject_id);
+        if let (Some(limit), Some(teacher_id)) =
+            (self.input.teacher_max_consecutive, info.teacher_id)
+        {
+            if let Some(cells) = self.teacher_cells.get(&teacher_id) {
+                if self.run_through(cell, |other| cells[other]) > limit {
+                    return Err(TimetableConstraint::TeacherMaxConsecutive);
+                }
+            }
+        }
+
+        match &info.required_room_type {
+            Some(room_type) => self
+                .input
+                .rooms
+                .iter()
+                .enumerate()
+                .find(|(index, room)| {
+                    room.room_type.eq_ignore_ascii_case(room_type) && !self.room_cells[*index][cell]
+                })
+                .map(|(index, _)| Some(index))
+                .ok_or(TimetableConstraint::RoomUnavailable),
+            None => Ok(None),
+        }
     }
 
-    // 6. BUILD THE ACTUAL SCHEDULE
-    let mut weekly_schedule: Vec<WeekSchedule> = Vec::new();
+    fn cost(&self, subject: usize, cell: usize) -> i64 {
+        let info = &self.input.subjects[subject];
+        let (day, slot) = self.split(cell);
+        let day_cells = (0..self.slots.len()).map(|slot| self.cell(day, slot));
+        let class_cells = &self.class_cells[&info.class_id];
+
+        let same_day = day_cells
+            .clone()
+            .filter(|other| {
+                class_cells[*other].is_some_and(|lesson| self.lessons[lesson] == subject)
+            })
+            .count() as i64;
+        let teacher_load = info
+            .teacher_id
+            .and_then(|teacher_id| self.teacher_cells.get(&teacher_id))
+            .map(|cells| day_cells.filter(|other| cells[*other]).count() as i64)
+            .unwrap_or(0);
+        let morning = self.slots[slot].start < NOON;
+        let time_of_day = match (info.is_heavy, morning) {
+            (true, false) => HEAVY_AFTERNOON_PENALTY,
+            (false, true) => LIGHT_MORNING_PENALTY,
+            _ => 0,
+        };
 
-    for (day_idx, day_name) in days_to_schedule.into_iter().enumerate() {
-        // Get the subjects assigned to this day and shuffle them *again* // so the order within the day is random
-        let mut daily_subjects = day_buckets[day_idx].clone();
-        daily_subjects.shuffle(&mut rng);
-        let mut daily_subj_iter = daily_subjects.into_iter();
+        same_day * SAME_DAY_PENALTY + teacher_load * TEACHER_DAY_LOAD_PENALTY + time_of_day
+    }
+
+    fn place(&mut self, lesson: usize, cell: usize, room: Option<usize>, cost: i64) {
+        let subject = &self.input.subjects[self.lessons[lesson]];
+        if let Some(cells) = self.class_cells.get_mut(&subject.class_id) {
+            cells[cell] = Some(lesson);
+        }
+        if let Some(teacher_id) = subject.teacher_id {
+            let cells = self.cell_count();
+            self.teacher_cells
+                .entry(teacher_id)
+                .or_insert_with(|| vec![false; cells])[cell] = true;
+        }
+        if let Some(room) = room {
+            self.room_cells[room][cell] = true;
+        }
+        self.placements[lesson] = Some(Placement { cell, room, cost });
+    }
 
-        let mut periods: Vec<Period> = Vec::new();
-        let mut current_offset_minutes = 0;
-        let mut order_counter = 1;
+    fn unplace(&mut self, lesson: usize) -> Option<Placement> {
+        let placement = self.placements[lesson].take()?;
+        let subject = &self.input.subjects[self.lessons[lesson]];
+        if let Some(cells) = self.class_cells.get_mut(&subject.class_id) {
+            cells[placement.cell] = None;
+        }
+        if let Some(cells) = subject
+            .teacher_id
+            .and_then(|teacher_id| self.teacher_cells.get_mut(&teacher_id))
+        {
+            cells[placement.cell] = false;
+        }
+        if let Some(room) = placement.room {
+            self.room_cells[room][placement.cell] = false;
+        }
+        Some(placement)
+    }
 
-        for slot in &day_template {
-            let period_oid = ObjectId::new();
-            let mut final_subject_id = None;
-            let mut final_title = slot.title.clone();
-            let mut final_type = slot.period_type.clone();
+    /// Cheapest empty cell of the lesson's class that passes every hard
+    /// constraint, or the constraint that blocked the most cells.
+    fn best_cell(&self, lesson: usize) -> Result<(usize, Option<usize>, i64), TimetableConstraint> {
+        let subject = self.lessons[lesson];
+        let class_cells = &self.class_cells[&self.input.subjects[subject].class_id];
+        let mut best: Option<(usize, Option<usize>, i64)> = None;
+        let mut blocked: Vec<(TimetableConstraint, usize)> = Vec::new();
 
-            // If this slot is meant for a Subject, pull one from our daily bucket
-            if matches!(slot.period_type, PeriodType::Subject) {
-                if let Some(sid) = daily_subj_iter.next() {
-                    final_subject_id = Some(sid);
-                } else {
-                    // If we ran out of subjects for this day (rare), make it Free
-                    final_type = PeriodType::Free;
-                    final_title = Some("Free Period".to_string());
+        for (cell, occupied) in class_cells.iter().enumerate() {
+            if occupied.is_some() {
+                continue;
+            }
+            match self.check(subject, cell) {
+                Ok(room) => {
+                    let cost = self.cost(subject, cell);
+                    if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                        best = Some((cell, room, cost));
+                    }
                 }
+                Err(constraint) => match blocked.iter_mut().find(|(c, _)| *c == constraint) {
+                    Some((_, count)) => *count += 1,
+                    None => blocked.push((constraint, 1)),
+                },
             }
+        }
+
+        best.ok_or_else(|| {
+            blocked
+                .into_iter()
+                .max_by_key(|(_, count)| *count)
+                .map(|(constraint, _)| constraint)
+                .unwrap_or(TimetableConstraint::ClassFull)
+        })
+    }
 
-            // Create the Period Struct
-            let p = Period {
-                period_id: period_oid,
-                r#type: final_type,
-                order: order_counter,
-                title: final_title,
-                description: None,
-                subject_id: final_subject_id,
-                start_offset: current_offset_minutes,
-                duration_minutes: slot.duration_minutes,
-                enabled: Some(true),
+    /// Free a cell for `lesson` by moving one lesson of the same class elsewhere.
+    fn repair(&mut self, lesson: usize) -> bool {
+        let class_id = self.input.subjects[self.lessons[lesson]].class_id;
+        for cell in 0..self.cell_count() {
+            let Some(other) = self.class_cells[&class_id][cell] else {
+                continue;
             };
+            if self.lessons[other] == self.lessons[lesson] {
+                continue;
+            }
 
-            periods.push(p);
+            let Some(previous) = self.unplace(other) else {
+                continue;
+            };
+            if let Ok(room) = self.check(self.lessons[lesson], cell) {
+                let cost = self.cost(self.lessons[lesson], cell);
+                self.place(lesson, cell, room, cost);
+                if let Ok((new_cell, new_room, new_cost)) = self.best_cell(other) {
+                    self.place(other, new_cell, new_room, new_cost);
+                    return true;
+                }
+                self.unplace(lesson);
+            }
+            self.place(other, previous.cell, previous.room, previous.cost);
+        }
+        false
+    }
 
-            // Advance time
-            current_offset_minutes += slot.duration_minutes;
-            order_counter += 1;
+    fn solve(mut self) -> SolverOutput {
+        let mut unplaced = Vec::new();
+        for lesson in 0..self.lessons.len() {
+            match self.best_cell(lesson) {
+                Ok((cell, room, cost)) => self.place(lesson, cell, room, cost),
+                Err(constraint) => {
+                    if self.repair(lesson) {
+                        continue;
+                    }
+                    let subject = &self.input.subjects[self.lessons[lesson]];
+                    unplaced.push(UnplacedLesson {
+                        class_id: subject.class_id,
+                        class_subject_id: subject.id,
+                        subject_name: subject.name.clone(),
+                        constraint,
+                        message: describe(constraint, subject),
+                    });
+                }
+            }
         }
 
-        // Add the day to the week
-        weekly_schedule.push(WeekSchedule {
-            day: day_name,
-            is_holiday: false, // Default
-            start_on: Some(start_time_str.clone()),
-            periods,
-        });
+        let lessons_placed = self.placements.iter().filter(|p| p.is_some()).count();
+        let penalty = self.placements.iter().flatten().map(|p| p.cost).sum();
+        SolverOutput {
+            timetables: self.build_timetables(),
+            unplaced,
+            lessons_placed,
+            penalty,
+        }
     }
 
-    // 7. RETURN FINAL STRUCT
-    Ok(ClassTimetable {
-        id: None, // Will be created by MongoDB on insert
-        class_id,
-        education_year_id,
-        term_order,
-        weekly_schedule,
-        disabled: Some(false),
-        created_at: None,
-        updated_at: None,
-    })
+    fn build_timetables(&self) -> Vec<ClassTimetable> {
+        let start_on = self.input.start_time.format("%H:%M").to_string();
+        self.input
+            .class_ids
+            .iter()
+            .filter(|class_id| self.input.subjects.iter().any(|s| s.class_id == **class_id))
+            .map(|class_id| {
+                let cells = &self.class_cells[class_id];
+                let weekly_schedule = self
+                    .input
+                    .days
+                    .iter()
+                    .enumerate()
+                    .map(|(day_index, day)| {
+                        let mut periods = Vec::new();
+                        let mut offset = 0;
+                        let mut slot = 0;
+                        for (order, entry) in self.input.day_template.iter().enumerate() {
+                            let mut period = Period {
+                                period_id: ObjectId::new(),
+                                r#type: entry.period_type.clone(),
+                                order: order as i32 + 1,
+                                title: entry.title.clone(),
+                                description: None,
+                                subject_id: None,
+                                teacher_id: None,
+                                room_id: None,
+                                start_offset: offset,
+                                duration_minutes: entry.duration_minutes,
+                                enabled: Some(true),
+                            };
+                            if entry.period_type == PeriodType::Subject {
+                                let lesson = cells[self.cell(day_index, slot)];
+                                match lesson.and_then(|lesson| {
+                                    self.placements[lesson].map(|placement| (lesson, placement))
+                                }) {
+                                    Some((lesson, placement)) => {
+                                        let subject = &self.input.subjects[self.lessons[lesson]];
+                                        period.subject_id = Some(subject.id);
+                                        period.teacher_id = subject.teacher_id;
+                                        period.room_id =
+                                            placement.room.map(|room| self.input.rooms[room].id);
+                                    }
+                                    None => {
+                                        period.r#type = PeriodType::Free;
+                                        period.title = Some("Free Period".to_string());
+                                    }
+                                }
+                                slot += 1;
+                            }
+                            offset += entry.duration_minutes;
+                            periods.push(period);
+                        }
+                        WeekSchedule {
+                            day: *day,
+                            is_holiday: false,
+                            start_on: Some(start_on.clone()),
+                            periods,
+                        }
+                    })
+                    .collect();
+
+                ClassTimetable {
+                    id: None,
+                    class_id: *class_id,
+                    education_year_id: self.input.education_year_id,
+                    term_order: self.input.term_order,
+                    weekly_schedule,
+                    disabled: Some(false),
+                    created_at: None,
+                    updated_at: None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn describe(constraint: TimetableConstraint, subject: &SolverSubject) -> String {
+    match constraint {
+        TimetableConstraint::TeacherDoubleBooked => format!(
+            "The teacher of {} is already teaching another class in every remaining slot",
+            subject.name
+        ),
+        TimetableConstraint::TeacherUnavailable => format!(
+            "The teacher of {} is not available in any remaining slot",
+            subject.name
+        ),
+        TimetableConstraint::TeacherMaxConsecutive => format!(
+            "The teacher of {} would exceed the consecutive period limit",
+            subject.name
+        ),
+        TimetableConstraint::RoomUnavailable => format!(
+            "No free {} is left for {}",
+            subject.required_room_type.as_deref().unwrap_or("room"),
+            subject.name
+        ),
+        TimetableConstraint::MaxConsecutive => format!(
+            "{} would run for more than {} consecutive periods",
+            subject.name, subject.max_consecutive
+        ),
+        TimetableConstraint::ClassFull => {
+            format!("The class has no free slot left for {}", subject.name)
+        }
+    }
+}
+
+/// Build the timetables of all given classes together so teachers and rooms
+/// are never booked twice. Lessons that break a hard constraint are left out
+/// and reported instead.
+pub fn solve_timetables(input: &SolverInput) -> SolverOutput {
+    Solver::new(input).solve()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::domain::common_details::TimeRange;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    /// One Monday of `periods` 40 minute lessons from 08:00
+    fn input(periods: usize, subjects: Vec<SolverSubject>) -> SolverInput {
+        let mut class_ids = Vec::new();
+        for subject in &subjects {
+            if !class_ids.contains(&subject.class_id) {
+                class_ids.push(subject.class_id);
+            }
+        }
+        SolverInput {
+            education_year_id: ObjectId::new(),
+            term_order: 1,
+            class_ids,
+            subjects,
+            rooms: Vec::new(),
+            availability: HashMap::new(),
+            fixed_bookings: Vec::new(),
+            days: vec![Weekday::Mon],
+            start_time: time(8, 0),
+            day_template: (0..periods)
+                .map(|_| DayStructureConfig {
+                    period_type: PeriodType::Subject,
+                    duration_minutes: 40,
+                    title: None,
+                })
+                .collect(),
+            teacher_max_consecutive: None,
+        }
+    }
+
+    fn subject(class_id: ObjectId, name: &str, teacher_id: Option<ObjectId>) -> SolverSubject {
+        SolverSubject {
+            id: ObjectId::new(),
+            class_id,
+            name: name.to_string(),
+            teacher_id,
+            weight: 1,
+            required_room_type: None,
+            is_heavy: false,
+            max_consecutive: 4,
+        }
+    }
+
+    fn lessons(output: &SolverOutput) -> Vec<(ObjectId, Weekday, &Period)> {
+        output
+            .timetables
+            .iter()
+            .flat_map(|timetable| {
+                timetable.weekly_schedule.iter().flat_map(move |day| {
+                    day.periods
+                        .iter()
+                        .filter(|period| period.subject_id.is_some())
+                        .map(move |period| (timetable.class_id, day.day, period))
+                })
+            })
+            .collect()
+    }
+
+    /// No teacher or room is in two places at once
+    fn assert_no_double_booking(output: &SolverOutput) {
+        let mut teachers = HashSet::new();
+        let mut rooms = HashSet::new();
+        for (_, day, period) in lessons(output) {
+            if let Some(teacher_id) = period.teacher_id {
+                assert!(teachers.insert((teacher_id, day, period.start_offset)));
+            }
+            if let Some(room_id) = period.room_id {
+                assert!(rooms.insert((room_id, day, period.start_offset)));
+            }
+        }
+    }
+
+    #[test]
+    fn shared_teacher_is_never_double_booked() {
+        let (class_a, class_b, shared) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        // Class A needs the shared teacher twice, class B once more, in two slots
+        let output = solve_timetables(&input(
+            2,
+            vec![
+                subject(class_a, "Maths", Some(shared)),
+                subject(class_b, "Maths", Some(shared)),
+                subject(class_b, "History", Some(ObjectId::new())),
+            ],
+        ));
+
+        assert_no_double_booking(&output);
+        assert_eq!(output.lessons_placed, 3);
+        assert_eq!(output.unplaced.len(), 1);
+        assert_eq!(
+            output.unplaced[0].constraint,
+            TimetableConstraint::TeacherDoubleBooked
+        );
+        assert_eq!(
+            lessons(&output)
+                .iter()
+                .filter(|(_, _, period)| period.teacher_id == Some(shared))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn lessons_only_go_where_the_teacher_is_available() {
+        let (class_id, teacher_id) = (ObjectId::new(), ObjectId::new());
+        let mut solver_input = input(
+            2,
+            vec![
+                subject(class_id, "Physics", Some(teacher_id)),
+                subject(class_id, "Art", None),
+            ],
+        );
+        // Free for the second period only
+        solver_input.availability.insert(
+            teacher_id,
+            vec![DailyAvailability {
+                day: Weekday::Mon,
+                time_range: TimeRange {
+                    start: time(8, 40),
+                    end: time(9, 20),
+                },
+            }],
+        );
+
+        let output = solve_timetables(&solver_input);
+        assert!(output.unplaced.is_empty());
+        let physics = lessons(&output)
+            .into_iter()
+            .find(|(_, _, period)| period.teacher_id == Some(teacher_id))
+            .map(|(_, _, period)| period.start_offset);
+        assert_eq!(physics, Some(40));
+
+        // With only that subject, the first period cannot be filled
+        solver_input.subjects.truncate(1);
+        let output = solve_timetables(&solver_input);
+        assert_eq!(output.lessons_placed, 1);
+        assert_eq!(output.unplaced.len(), 1);
+        assert_eq!(
+            output.unplaced[0].constraint,
+            TimetableConstraint::TeacherUnavailable
+        );
+    }
+
+    #[test]
+    fn a_single_lab_is_shared_between_classes() {
+        let lab = ObjectId::new();
+        let mut chemistry = Vec::new();
+        for _ in 0..2 {
+            let mut lesson = subject(ObjectId::new(), "Chemistry", Some(ObjectId::new()));
+            lesson.required_room_type = Some("lab".to_string());
+            chemistry.push(lesson);
+        }
+        let mut solver_input = input(1, chemistry);
+        solver_input.rooms = vec![SolverRoom {
+            id: lab,
+            room_type: "Lab".to_string(),
+        }];
+
+        let output = solve_timetables(&solver_input);
+        assert_no_double_booking(&output);
+        assert_eq!(output.lessons_placed, 1);
+        assert_eq!(output.unplaced.len(), 1);
+        assert_eq!(
+            output.unplaced[0].constraint,
+            TimetableConstraint::RoomUnavailable
+        );
+        assert!(output.unplaced[0].message.contains("lab"));
+        assert_eq!(lessons(&output)[0].2.room_id, Some(lab));
+    }
 }
@@ -1,2 +1,4 @@
 pub mod class_timetable_handler;
 pub mod delete_target_handler;
+pub mod report_card_handler;
+pub mod timetable_clash_handler;
@@ -0,0 +1,416 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::report_card::ReportCard,
+    utils::pdf::{truncate_to_width, wrap_text, Font, ImageId, PdfDocument, PdfImage, PdfPage},
+};
+
+const MARGIN: f32 = 40.0;
+const BOTTOM: f32 = 60.0;
+const ROW_HEIGHT: f32 = 18.0;
+const LOGO_SIZE: f32 = 56.0;
+
+// Subject table columns
+const SUBJECT_X: f32 = MARGIN + 4.0;
+const SUBJECT_WIDTH: f32 = 140.0;
+const BREAKDOWN_X: f32 = 190.0;
+const BREAKDOWN_WIDTH: f32 = 215.0;
+const PERCENT_RIGHT: f32 = 455.0;
+const GRADE_CENTER: f32 = 495.0;
+const CREDITS_RIGHT: f32 = 551.0;
+
+fn format_score(value: f64) -> String {
+    if value.fract().abs() < 0.05 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+/// Lays out one card, starting continuation pages when the table overflows
+struct Layout<'a> {
+    document: &'a mut PdfDocument,
+    page: PdfPage,
+    y: f32,
+    footer: String,
+}
+
+impl Layout<'_> {
+    fn finish_page(&mut self) {
+        let width = self.page.width;
+        self.page.line(MARGIN, 44.0, width - MARGIN, 44.0, 0.5);
+        self.page
+            .text(MARGIN, 32.0, Font::Regular, 8.0, &self.footer);
+        let page = std::mem::replace(&mut self.page, PdfPage::a4());
+        self.document.push_page(page);
+    }
+
+    /// Moves to a new page unless `height` still fits above the footer
+    fn ensure(&mut self, height: f32, continued: &str) -> bool {
+        if self.y - height >= BOTTOM {
+            return false;
+        }
+        self.finish_page();
+        self.y = self.page.height - MARGIN;
+        self.page
+            .text(MARGIN, self.y - 10.0, Font::Bold, 10.0, continued);
+        self.y -= 24.0;
+        true
+    }
+}
+
+fn subject_table_header(page: &mut PdfPage, y: f32) {
+    let width = page.width;
+    page.fill_rect(
+        MARGIN,
+        y - ROW_HEIGHT + 4.0,
+        width - 2.0 * MARGIN,
+        ROW_HEIGHT,
+        0.88,
+    );
+    let baseline = y - 9.0;
+    page.text(SUBJECT_X, baseline, Font::Bold, 9.0, "Subject");
+    page.text(BREAKDOWN_X, baseline, Font::Bold, 9.0, "Breakdown");
+    page.text_right(PERCENT_RIGHT, baseline, Font::Bold, 9.0, "Score %");
+    page.text_centered(GRADE_CENTER, baseline, Font::Bold, 9.0, "Grade");
+    page.text_right(CREDITS_RIGHT, baseline, Font::Bold, 9.0, "Credits");
+}
+
+fn render_card(
+    document: &mut PdfDocument,
+    card: &ReportCard,
+    logo: Option<ImageId>,
+    generated_at: DateTime<Utc>,
+) {
+    let result = &card.result;
+    let mut layout = Layout {
+        document,
+        page: PdfPage::a4(),
+        y: 0.0,
+        footer: format!(
+            "{} - {} - generated {}",
+            card.school_name,
+            card.student_name,
+            generated_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
+    let width = layout.page.width;
+    let top = layout.page.height - MARGIN;
+
+    // School header
+    let mut title_x = MARGIN;
+    if let Some(logo) = logo {
+        layout
+            .page
+            .image(logo, MARGIN, top - LOGO_SIZE, LOGO_SIZE, LOGO_SIZE);
+        title_x += LOGO_SIZE + 14.0;
+    }
+    let title_width = width - MARGIN - title_x;
+    layout.page.text(
+        title_x,
+        top - 20.0,
+        Font::Bold,
+        18.0,
+        &truncate_to_width(&card.school_name, Font::Bold, 18.0, title_width),
+    );
+    layout.page.text(
+        title_x,
+        top - 38.0,
+        Font::Regular,
+        12.0,
+        "Student Report Card",
+    );
+    let period = [
+        card.exam_name.clone(),
+        result.term_id.clone(),
+        card.year_label.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join("  |  ");
+    layout.page.text(
+        title_x,
+        top - 52.0,
+        Font::Regular,
+        9.0,
+        &truncate_to_width(&period, Font::Regular, 9.0, title_width),
+    );
+    layout.y = top - LOGO_SIZE - 14.0;
+    layout
+        .page
+        .line(MARGIN, layout.y, width - MARGIN, layout.y, 1.0);
+
+    // Student summary
+    layout.y -= 18.0;
+    let rank = match (result.rank_in_class, result.total_students) {
+        (Some(rank), Some(total)) => format!("{} of {}", rank, total),
+        (Some(rank), None) => rank.to_string(),
+        _ => "-".to_string(),
+    };
+    let left = [
+        ("Student", card.student_name.clone()),
+        (
+            "Class",
+            card.class_name.clone().unwrap_or_else(|| "-".into()),
+        ),
+        ("Position", rank),
+    ];
+    let right = [
+        (
+            "Average",
+            format!("{}%", format_score(result.average_percentage)),
+        ),
+        ("GPA", format!("{:.2}", result.gpa)),
+        ("Overall grade", result.grade.clone()),
+    ];
+    for ((left_label, left_value), (right_label, right_value)) in left.iter().zip(right.iter()) {
+        let y = layout.y;
+        layout
+            .page
+            .text(MARGIN, y, Font::Bold, 10.0, &format!("{}:", left_label));
+        layout.page.text(
+            MARGIN + 60.0,
+            y,
+            Font::Regular,
+            10.0,
+            &truncate_to_width(left_value, Font::Regular, 10.0, 230.0),
+        );
+        layout
+            .page
+            .text(340.0, y, Font::Bold, 10.0, &format!("{}:", right_label));
+        layout.page.text(420.0, y, Font::Regular, 10.0, right_value);
+        layout.y -= 15.0;
+    }
+
+    // Subject breakdown
+    layout.y -= 10.0;
+    subject_table_header(&mut layout.page, layout.y);
+    layout.y -= ROW_HEIGHT;
+    let continued = format!("{} (continued)", card.student_name);
+    for (index, subject) in result.subject_results.iter().enumerate() {
+        let comments = card
+            .comments
+            .iter()
+            .filter(|comment| {
+                comment.class_subject_id.is_some()
+                    && comment.class_subject_id == subject.class_subject_id
+            })
+            .flat_map(|comment| {
+                let text = match &comment.author_name {
+                    Some(author) => format!("\"{}\" - {}", comment.body.trim(), author),
+                    None => format!("\"{}\"", comment.body.trim()),
+                };
+                wrap_text(
+                    &text,
+                    Font::Regular,
+                    8.0,
+                    BREAKDOWN_X + BREAKDOWN_WIDTH - SUBJECT_X - 12.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        let row_height = ROW_HEIGHT + comments.len() as f32 * 10.0;
+        if layout.ensure(row_height, &continued) {
+            subject_table_header(&mut layout.page, layout.y);
+            layout.y -= ROW_HEIGHT;
+        }
+
+        if index % 2 == 1 {
+            layout.page.fill_rect(
+                MARGIN,
+                layout.y - row_height + 4.0,
+                width - 2.0 * MARGIN,
+                row_height,
+                0.96,
+            );
+        }
+        let baseline = layout.y - 9.0;
+        let breakdown = subject
+            .category_scores
+            .iter()
+            .map(|score| {
+                format!(
+                    "{} {}/{}",
+                    score.category_name,
+                    format_score(score.score),
+                    format_score(score.max_score)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        layout.page.text(
+            SUBJECT_X,
+            baseline,
+            Font::Regular,
+            9.0,
+            &truncate_to_width(&subject.subject_name, Font::Regular, 9.0, SUBJECT_WIDTH),
+        );
+        layout.page.text(
+            BREAKDOWN_X,
+            baseline,
+            Font::Regular,
+            8.0,
+            &truncate_to_width(&breakdown, Font::Regular, 8.0, BREAKDOWN_WIDTH),
+        );
+        layout.page.text_right(
+            PERCENT_RIGHT,
+            baseline,
+            Font::Regular,
+            9.0,
+            &format_score(subject.percentage),
+        );
+        layout
+            .page
+            .text_centered(GRADE_CENTER, baseline, Font::Bold, 9.0, &subject.grade);
+        layout.page.text_right(
+            CREDITS_RIGHT,
+            baseline,
+            Font::Regular,
+            9.0,
+            &subject
+                .credits
+                .map(|credits| credits.to_string())
+                .unwrap_or_else(|| "-".into()),
+        );
+        for (line_index, line) in comments.iter().enumerate() {
+            layout.page.text(
+                SUBJECT_X + 12.0,
+                baseline - 11.0 - line_index as f32 * 10.0,
+                Font::Regular,
+                8.0,
+                line,
+            );
+        }
+        layout.y -= row_height;
+    }
+    layout
+        .page
+        .line(MARGIN, layout.y + 2.0, width - MARGIN, layout.y + 2.0, 0.5);
+    layout.y -= 8.0;
+    layout.page.text_right(
+        PERCENT_RIGHT,
+        layout.y,
+        Font::Bold,
+        9.0,
+        &format!(
+            "Total {}/{}",
+            format_score(result.total_score),
+            format_score(result.total_max_score)
+        ),
+    );
+    if let Some(total_credits) = result.total_credits {
+        layout.page.text_right(
+            CREDITS_RIGHT,
+            layout.y,
+            Font::Bold,
+            9.0,
+            &total_credits.to_string(),
+        );
+    }
+    layout.y -= 20.0;
+
+    // General remarks
+    for comment in card
+        .comments
+        .iter()
+        .filter(|c| c.class_subject_id.is_none())
+    {
+        let lines = wrap_text(
+            comment.body.trim(),
+            Font::Regular,
+            9.0,
+            width - 2.0 * MARGIN,
+        );
+        layout.ensure(26.0 + lines.len() as f32 * 12.0, &continued);
+        let heading = match &comment.author_name {
+            Some(author) => format!("Class teacher's remarks ({})", author),
+            None => "Class teacher's remarks".to_string(),
+        };
+        layout
+            .page
+            .text(MARGIN, layout.y, Font::Bold, 10.0, &heading);
+        layout.y -= 14.0;
+        for line in lines {
+            layout
+                .page
+                .text(MARGIN, layout.y, Font::Regular, 9.0, &line);
+            layout.y -= 12.0;
+        }
+        layout.y -= 8.0;
+    }
+
+    // Grading scale legend
+    if let Some(scale) = card
+        .grading_scale
+        .as_ref()
+        .filter(|scale| !scale.grade_boundaries.is_empty())
+    {
+        let legend = scale
+            .grade_boundaries
+            .iter()
+            .map(|boundary| {
+                let range = format!(
+                    "{}-{}",
+                    format_score(boundary.min_score),
+                    format_score(boundary.max_score)
+                );
+                match &boundary.description {
+                    Some(description) if !description.trim().is_empty() => {
+                        format!("{}: {} ({})", boundary.grade, range, description.trim())
+                    }
+                    _ => format!("{}: {}", boundary.grade, range),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("    ");
+        let lines = wrap_text(&legend, Font::Regular, 8.0, width - 2.0 * MARGIN);
+        layout.ensure(20.0 + lines.len() as f32 * 10.0, &continued);
+        layout.page.text(
+            MARGIN,
+            layout.y,
+            Font::Bold,
+            9.0,
+            &format!("Grading scale: {}", scale.name),
+        );
+        layout.y -= 12.0;
+        for line in lines {
+            layout
+                .page
+                .text(MARGIN, layout.y, Font::Regular, 8.0, &line);
+            layout.y -= 10.0;
+        }
+    }
+
+    layout.finish_page();
+}
+
+/// Renders the cards into one PDF, each card starting on its own page.
+/// `logo` is the school logo as JPEG or PNG bytes; unreadable logos are
+/// left out.
+pub fn render_report_cards(
+    title: &str,
+    cards: &[ReportCard],
+    logo: Option<&[u8]>,
+    generated_at: DateTime<Utc>,
+) -> Vec<u8> {
+    let mut document = PdfDocument::new(title);
+    let logo = logo
+        .and_then(PdfImage::from_bytes)
+        .map(|image| document.add_image(image));
+    for card in cards {
+        render_card(&mut document, card, logo, generated_at);
+    }
+    if document.page_count() == 0 {
+        let mut page = PdfPage::a4();
+        page.text(
+            MARGIN,
+            page.height - MARGIN - 12.0,
+            Font::Regular,
+            12.0,
+            "No report cards",
+        );
+        document.push_page(page);
+    }
+    document.render()
+}
@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveTime, Timelike, Weekday};
+
+use crate::{
+    domain::{
+        class_timetable::{ClassTimetable, PeriodType},
+        school_timetable::{DailySchoolSchedule, DaySpecialType, TimeBlock},
+        timetable_validation::{TimetableIssue, TimetableIssueKind},
+    },
+    utils::object_id::ObjectId,
+};
+
+/// A class subject as the clash checker sees it
+#[derive(Debug, Clone)]
+pub struct ClashSubject {
+    pub class_id: ObjectId,
+    pub name: String,
+    pub teacher_id: Option<ObjectId>,
+    pub estimated_hours: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClashContext {
+    /// Keyed by class subject id
+    pub subjects: HashMap<ObjectId, ClashSubject>,
+    /// School day layout that applies to each class, overrides included
+    pub school_days: HashMap<ObjectId, Vec<DailySchoolSchedule>>,
+}
+
+struct Booking<'a> {
+    timetable: &'a ClassTimetable,
+    day: Weekday,
+    start: i32,
+    end: i32,
+    period_id: ObjectId,
+    subject_id: Option<ObjectId>,
+}
+
+fn minutes(raw: &str) -> Option<i32> {
+    NaiveTime::parse_from_str(raw, "%H:%M")
+        .ok()
+        .map(|time| (time.hour() * 60 + time.minute()) as i32)
+}
+
+fn hhmm(minutes: i32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+fn issue(kind: TimetableIssueKind, timetable: &ClassTimetable, message: String) -> TimetableIssue {
+    TimetableIssue {
+        kind,
+        hard: kind.is_hard(),
+        class_id: timetable.class_id,
+        timetable_id: timetable.id,
+        day: None,
+        start_time: None,
+        period_id: None,
+        subject_id: None,
+        teacher_id: None,
+        other_class_id: None,
+        message,
+    }
+}
+
+impl ClashContext {
+    fn subject_name(&self, subject_id: Option<ObjectId>) -> String {
+        subject_id
+            .and_then(|id| self.subjects.get(&id))
+            .map(|subject| subject.name.clone())
+            .unwrap_or_else(|| "A subject".to_string())
+    }
+
+    fn school_day(&self, class_id: &ObjectId, day: Weekday) -> Option<&DailySchoolSchedule> {
+        self.school_days
+            .get(class_id)
+            .and_then(|days| days.iter().find(|schedule| schedule.day == day))
+    }
+}
+
+/// Check class timetables of one term against each other and against the
+/// school timetable.
+pub fn find_clashes(timetables: &[ClassTimetable], context: &ClashContext) -> Vec<TimetableIssue> {
+    let mut issues = Vec::new();
+    let mut teacher_bookings: HashMap<(ObjectId, Weekday), Vec<Booking>> = HashMap::new();
+
+    for timetable in timetables {
+        let mut weekly_periods: HashMap<ObjectId, i32> = HashMap::new();
+
+        for day in &timetable.weekly_schedule {
+            let school_day = context.school_day(&timetable.class_id, day.day);
+            let school_closed = school_day.is_some_and(|schedule| {
+                !schedule.is_school_day || matches!(schedule.special_type, DaySpecialType::Holiday)
+            });
+            let day_start = day.start_on.as_deref().and_then(minutes);
+
+            for period in &day.periods {
+                if period.r#type != PeriodType::Subject || period.enabled == Some(false) {
+                    continue;
+                }
+                let start = day_start.map(|day_start| day_start + period.start_offset);
+                let end = start.map(|start| start + period.duration_minutes);
+                let subject_name = context.subject_name(period.subject_id);
+
+                let located = |kind: TimetableIssueKind, message: String| TimetableIssue {
+                    day: Some(day.day),
+                    start_time: start.map(hhmm),
+                    period_id: Some(period.period_id),
+                    subject_id: period.subject_id,
+                    ..issue(kind, timetable, message)
+                };
+
+                if day.is_holiday || school_closed {
+                    issues.push(located(
+                        TimetableIssueKind::HolidayPeriod,
+                        format!(
+                            "{} is scheduled on {}, which is a holiday",
+                            subject_name, day.day
+                        ),
+                    ));
+                    continue;
+                }
+                if let Some(subject_id) = period.subject_id {
+                    *weekly_periods.entry(subject_id).or_default() += 1;
+                }
+
+                let (Some(start), Some(end)) = (start, end) else {
+                    continue;
+                };
+
+                let blocks: Vec<&TimeBlock> = school_day
+                    .map(|schedule| {
+                        schedule
+                            .breaks
+                            .iter()
+                            .chain(schedule.lunch.iter())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                for block in blocks {
+                    let (Some(block_start), Some(block_end)) =
+                        (minutes(&block.start_time), minutes(&block.end_time))
+                    else {
+                        continue;
+                    };
+                    if start < block_end && block_start < end {
+                        issues.push(located(
+                            TimetableIssueKind::BreakOverlap,
+                            format!(
+                                "{} on {} at {} overlaps {} ({}-{})",
+                                subject_name,
+                                day.day,
+                                hhmm(start),
+                                block.title,
+                                block.start_time,
+                                block.end_time
+                            ),
+                        ));
+                    }
+                }
+
+                let teacher_id = period.teacher_id.or_else(|| {
+                    period
+                        .subject_id
+                        .and_then(|id| context.subjects.get(&id))
+                        .and_then(|subject| subject.teacher_id)
+                });
+                if let Some(teacher_id) = teacher_id {
+                    teacher_bookings
+                        .entry((teacher_id, day.day))
+                        .or_default()
+                        .push(Booking {
+                            timetable,
+                            day: day.day,
+                            start,
+                            end,
+                            period_id: period.period_id,
+                            subject_id: period.subject_id,
+                        });
+                }
+            }
+        }
+
+        for (subject_id, subject) in context.subjects.iter().filter(|(_, subject)| {
+            subject.class_id == timetable.class_id && subject.estimated_hours > 0
+        }) {
+            let scheduled = weekly_periods.get(subject_id).copied().unwrap_or(0);
+            if scheduled < subject.estimated_hours {
+                issues.push(TimetableIssue {
+                    subject_id: Some(*subject_id),
+                    ..issue(
+                        TimetableIssueKind::UnderScheduled,
+                        timetable,
+                        format!(
+                            "{} has {} weekly periods but needs {}",
+                            subject.name, scheduled, subject.estimated_hours
+                        ),
+                    )
+                });
+            }
+        }
+    }
+
+    for ((teacher_id, _), mut bookings) in teacher_bookings {
+        bookings.sort_by_key(|booking| (booking.start, booking.end));
+        for (index, first) in bookings.iter().enumerate() {
+            for second in bookings[index + 1..]
+                .iter()
+                .take_while(|b| b.start < first.end)
+            {
+                for (own, other) in [(first, second), (second, first)] {
+                    issues.push(TimetableIssue {
+                        day: Some(own.day),
+                        start_time: Some(hhmm(own.start)),
+                        period_id: Some(own.period_id),
+                        subject_id: own.subject_id,
+                        teacher_id: Some(teacher_id),
+                        other_class_id: Some(other.timetable.class_id),
+                        ..issue(
+                            TimetableIssueKind::TeacherDoubleBooked,
+                            own.timetable,
+                            format!(
+                                "The teacher of {} on {} at {} also teaches {} at {}",
+                                context.subject_name(own.subject_id),
+                                own.day,
+                                hhmm(own.start),
+                                context.subject_name(other.subject_id),
+                                hhmm(other.start)
+                            ),
+                        )
+                    });
+                }
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| {
+        b.hard
+            .cmp(&a.hard)
+            .then(a.class_id.to_hex().cmp(&b.class_id.to_hex()))
+            .then(
+                a.day
+                    .map(|d| d.num_days_from_monday())
+                    .cmp(&b.day.map(|d| d.num_days_from_monday())),
+            )
+            .then(a.start_time.cmp(&b.start_time))
+    });
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::class_timetable::{Period, WeekSchedule};
+
+    /// A period `offset` minutes into the day, lasting 40 minutes
+    fn period(offset: i32, subject_id: ObjectId, teacher_id: Option<ObjectId>) -> Period {
+        Period {
+            period_id: ObjectId::new(),
+            r#type: PeriodType::Subject,
+            order: 1,
+            title: None,
+            description: None,
+            subject_id: Some(subject_id),
+            teacher_id,
+            room_id: None,
+            start_offset: offset,
+            duration_minutes: 40,
+            enabled: Some(true),
+        }
+    }
+
+    /// A Monday timetable starting at `start_on`
+    fn timetable(class_id: ObjectId, start_on: &str, periods: Vec<Period>) -> ClassTimetable {
+        ClassTimetable {
+            id: Some(ObjectId::new()),
+            class_id,
+            education_year_id: ObjectId::new(),
+            term_order: 1,
+            weekly_schedule: vec![WeekSchedule {
+                day: Weekday::Mon,
+                is_holiday: false,
+                start_on: Some(start_on.to_string()),
+                periods,
+            }],
+            disabled: Some(false),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn school_day(breaks: Vec<TimeBlock>, special_type: DaySpecialType) -> DailySchoolSchedule {
+        DailySchoolSchedule {
+            day: Weekday::Mon,
+            is_school_day: true,
+            school_start_time: "07:30".to_string(),
+            school_end_time: "16:00".to_string(),
+            study_start_time: "08:00".to_string(),
+            study_end_time: "15:30".to_string(),
+            breaks,
+            lunch: None,
+            activities: Vec::new(),
+            special_type,
+        }
+    }
+
+    fn kinds(issues: &[TimetableIssue]) -> Vec<TimetableIssueKind> {
+        issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn overlapping_periods_of_one_teacher_clash_in_both_classes() {
+        let (class_a, class_b, teacher) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        let timetables = [
+            timetable(
+                class_a,
+                "08:00",
+                vec![period(0, ObjectId::new(), Some(teacher))],
+            ),
+            timetable(
+                class_b,
+                "08:20",
+                vec![period(0, ObjectId::new(), Some(teacher))],
+            ),
+        ];
+
+        let issues = find_clashes(&timetables, &ClashContext::default());
+        assert_eq!(
+            kinds(&issues),
+            vec![TimetableIssueKind::TeacherDoubleBooked; 2]
+        );
+        assert!(issues.iter().all(|issue| issue.hard));
+        for issue in &issues {
+            let other = if issue.class_id == class_a {
+                class_b
+            } else {
+                class_a
+            };
+            assert_eq!(issue.other_class_id, Some(other));
+            assert_eq!(issue.teacher_id, Some(teacher));
+        }
+    }
+
+    #[test]
+    fn back_to_back_periods_and_other_teachers_do_not_clash() {
+        let teacher = ObjectId::new();
+        let timetables = [
+            timetable(
+                ObjectId::new(),
+                "08:00",
+                vec![period(0, ObjectId::new(), Some(teacher))],
+            ),
+            timetable(
+                ObjectId::new(),
+                "08:00",
+                vec![
+                    period(0, ObjectId::new(), Some(ObjectId::new())),
+                    period(40, ObjectId::new(), Some(teacher)),
+                ],
+            ),
+        ];
+
+        assert!(find_clashes(&timetables, &ClashContext::default()).is_empty());
+    }
+
+    #[test]
+    fn teacher_of_the_class_subject_is_used_when_the_period_has_none() {
+        let (class_a, class_b, teacher) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        let subject_id = ObjectId::new();
+        let mut context = ClashContext::default();
+        context.subjects.insert(
+            subject_id,
+            ClashSubject {
+                class_id: class_a,
+                name: "Biology".to_string(),
+                teacher_id: Some(teacher),
+                estimated_hours: 0,
+            },
+        );
+        let timetables = [
+            timetable(class_a, "08:00", vec![period(0, subject_id, None)]),
+            timetable(
+                class_b,
+                "08:00",
+                vec![period(0, ObjectId::new(), Some(teacher))],
+            ),
+        ];
+
+        let issues = find_clashes(&timetables, &context);
+        assert_eq!(issues.len(), 2);
+        let own = issues
+            .iter()
+            .find(|issue| issue.class_id == class_a)
+            .unwrap();
+        assert!(own
+            .message
+            .starts_with("The teacher of Biology on Mon at 08:00"));
+    }
+
+    #[test]
+    fn periods_over_breaks_and_on_holidays_are_hard_clashes() {
+        let class_id = ObjectId::new();
+        let mut context = ClashContext::default();
+        let morning_break = TimeBlock {
+            title: "Break".to_string(),
+            start_time: "10:00".to_string(),
+            end_time: "10:20".to_string(),
+            description: None,
+        };
+        context.school_days.insert(
+            class_id,
+            vec![school_day(vec![morning_break], DaySpecialType::Normal)],
+        );
+
+        // 09:50-10:30 runs into the break; 09:10-09:50 ends before it
+        let timetables = [timetable(
+            class_id,
+            "09:10",
+            vec![
+                period(0, ObjectId::new(), None),
+                period(40, ObjectId::new(), None),
+            ],
+        )];
+        let issues = find_clashes(&timetables, &context);
+        assert_eq!(kinds(&issues), vec![TimetableIssueKind::BreakOverlap]);
+        assert_eq!(issues[0].start_time.as_deref(), Some("09:50"));
+
+        context.school_days.insert(
+            class_id,
+            vec![school_day(Vec::new(), DaySpecialType::Holiday)],
+        );
+        let issues = find_clashes(&timetables, &context);
+        assert_eq!(kinds(&issues), vec![TimetableIssueKind::HolidayPeriod; 2]);
+    }
+
+    #[test]
+    fn missing_weekly_periods_are_a_soft_warning_listed_last() {
+        let (class_id, teacher) = (ObjectId::new(), ObjectId::new());
+        let (maths, english) = (ObjectId::new(), ObjectId::new());
+        let mut context = ClashContext::default();
+        for (subject_id, name) in [(maths, "Maths"), (english, "English")] {
+            context.subjects.insert(
+                subject_id,
+                ClashSubject {
+                    class_id,
+                    name: name.to_string(),
+                    teacher_id: None,
+                    estimated_hours: 2,
+                },
+            );
+        }
+        let timetables = [timetable(
+            class_id,
+            "08:00",
+            vec![
+                period(0, maths, Some(teacher)),
+                period(20, maths, Some(teacher)),
+                period(80, english, None),
+            ],
+        )];
+
+        let issues = find_clashes(&timetables, &context);
+        assert_eq!(
+            kinds(&issues),
+            vec![
+                TimetableIssueKind::TeacherDoubleBooked,
+                TimetableIssueKind::TeacherDoubleBooked,
+                TimetableIssueKind::UnderScheduled,
+            ]
+        );
+        assert!(!issues[2].hard);
+        assert_eq!(
+            issues[2].message,
+            "English has 1 weekly periods but needs 2"
+        );
+    }
+}
@@ -40,6 +40,8 @@ async fn main() -> std::io::Result<()> {
         .await
         .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))?;
     let state = web::Data::new(config::state::AppState::new(pg_manager.clone()));
+    state.event_bus.start_transport().await;
+    services::backup_scheduler::start(pg_manager.pool.clone());
 
     println!("🚀 Space-Together backend starting on {address}");
 
@@ -11,10 +11,9 @@ pub fn to_school_school_token(
     let school_db = match &school.database_name {
         Some(db_name) => db_name.clone(),
         None => {
-            return Err(AppError {
-                message: "School does not have a database_name. Token cannot be created."
-                    .to_string(),
-            });
+            return Err(AppError::bad_request(
+                "School does not have a database_name. Token cannot be created.",
+            ));
         }
     };
     Ok(SchoolToken {
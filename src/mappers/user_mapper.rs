@@ -25,6 +25,7 @@ pub fn to_auth_dto(user: &User, current_school_user_id: Option<String>) -> AuthU
             .map(|schools| schools.iter().map(|id| id.to_string()).collect()),
         current_school_id: user.current_school_id.as_ref().map(|id| id.to_string()),
         current_school_user_id,
+        session_id: None,
         iat: None,
         exp: None,
     }
@@ -1,5 +1,6 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
     Error,
 };
 use futures_util::future::{ok, LocalBoxFuture, Ready};
@@ -81,8 +82,17 @@ where
         );
 
         Box::pin(async move {
-            match service.call(req).await {
-                Ok(response) => {
+            // Errors built while handling the request carry its id
+            let result = crate::errors::REQUEST_ID
+                .scope(request_id.clone(), service.call(req))
+                .await;
+            match result {
+                Ok(mut response) => {
+                    if let Ok(value) = HeaderValue::from_str(&request_id) {
+                        response
+                            .headers_mut()
+                            .insert(HeaderName::from_static("x-request-id"), value);
+                    }
                     let status = response.status();
                     let duration_ms = started_at.elapsed().as_millis();
 
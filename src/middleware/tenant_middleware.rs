@@ -50,24 +50,12 @@ where
         let srv = Rc::clone(&self.service);
 
         Box::pin(async move {
-            // 1) Try header X-School-ID
-            let maybe_school_id = req
-                .headers()
-                .get("x-school-id")
-                .and_then(|v| v.to_str().ok())
-                .map(|s| s.to_string());
-
-            // 2) Fallback: extract subdomain from host (localhost handling)
-            let school_id = if let Some(id) = maybe_school_id {
-                id
-            } else {
-                // host like st-theresa.space-together.app
-                let host = req.connection_info().host().to_string();
-                let sub = host.split('.').next().unwrap_or("").to_string();
-                // optional: map subdomain to school id by looking up main_db schools collection
-                // if sub is "localhost", skip
-                sub
-            };
+            // Only the subdomain is read here. An `x-school-id` header is
+            // resolved per request by `require_request_school`, which checks
+            // the caller belongs to that school instead of trusting it.
+            // host like st-theresa.space-together.app
+            let host = req.connection_info().host().to_string();
+            let school_id = host.split('.').next().unwrap_or("").to_string();
 
             // if school_id is empty or equals "localhost", skip and continue (global endpoints)
             if !school_id.is_empty() && school_id != "localhost" {
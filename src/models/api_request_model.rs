@@ -13,6 +13,8 @@ pub struct RequestQuery {
     pub term_id: Option<String>,
     pub exam_id: Option<String>,
     pub gpa_threshold: Option<f64>,
+    pub drop_threshold: Option<f64>,
+    pub compare_exam_id: Option<String>,
     pub by_ids: Vec<String>,
 }
 
@@ -37,6 +39,8 @@ impl<'de> Deserialize<'de> for RequestQuery {
             term_id: None,
             exam_id: None,
             gpa_threshold: None,
+            drop_threshold: None,
+            compare_exam_id: None,
             by_ids: Vec::new(),
         };
 
@@ -54,6 +58,8 @@ impl<'de> Deserialize<'de> for RequestQuery {
                 "term_id" => query.term_id = Some(val),
                 "exam_id" => query.exam_id = Some(val),
                 "gpa_threshold" => query.gpa_threshold = val.parse().ok(),
+                "drop_threshold" => query.drop_threshold = val.parse().ok(),
+                "compare_exam_id" => query.compare_exam_id = Some(val),
                 "by_ids" => query.by_ids.push(val),
                 _ => {} // Ignore unknown fields
             }
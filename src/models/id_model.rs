@@ -54,11 +54,11 @@ impl IdType {
     pub fn to_object_id(&self) -> Result<ObjectId, AppError> {
         match self {
             IdType::ObjectId(oid) => Ok(oid.clone()),
-            IdType::String(s) => ObjectId::parse_str(s).map_err(|e| AppError {
-                message: format!(
+            IdType::String(s) => ObjectId::parse_str(s).map_err(|e| {
+                AppError::bad_request(format!(
                     "Failed to parse IdType string to ObjectId-compatible ID: {}",
                     e
-                ),
+                ))
             }),
         }
     }
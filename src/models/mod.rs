@@ -2,5 +2,4 @@ pub mod api_request_model;
 pub mod default_model;
 pub mod id_model;
 pub mod mongo_model;
-pub mod request_error_model;
 pub mod school_token_model;
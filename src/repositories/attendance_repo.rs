@@ -0,0 +1,499 @@
+use crate::domain::{
+    attendance::{
+        Attendance, AttendanceCounts, AttendanceEntry, AttendanceQuery, AttendanceStatus,
+        RegisterStudent,
+    },
+    common_details::Paginated,
+};
+use crate::errors::AppError;
+use crate::models::id_model::IdType;
+use crate::utils::object_id::ObjectId;
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+
+pub struct AttendanceRepo {
+    pub pool: PgPool,
+}
+
+impl AttendanceRepo {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn id_to_string(id: &IdType) -> Result<String, AppError> {
+        Ok(IdType::to_object_id(id)?.to_hex())
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn parse_oid_opt(raw: Option<String>, field: &str) -> Result<Option<ObjectId>, AppError> {
+        raw.map(|value| Self::parse_oid(&value, field)).transpose()
+    }
+
+    fn enum_to_string<T: Serialize>(value: &T) -> Option<String> {
+        match serde_json::to_value(value).ok()? {
+            serde_json::Value::String(value) => Some(value),
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn enum_from_string<T: DeserializeOwned>(value: Option<String>) -> Option<T> {
+        value.and_then(|raw| serde_json::from_value(serde_json::Value::String(raw)).ok())
+    }
+
+    /// Older rows were written in lower case; normalise before parsing.
+    fn status_from_string(value: Option<String>) -> Option<AttendanceStatus> {
+        Self::enum_from_string(value.map(|raw| {
+            let mut chars = raw.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.map(|c| c.to_ascii_lowercase()))
+                    .collect(),
+                None => raw,
+            }
+        }))
+    }
+
+    fn select_sql() -> &'static str {
+        r#"
+        SELECT a.id, a.school_id, a.class_id, a.student_id AS profile_id,
+               COALESCE(a.enrollment_id, (
+                 SELECT sse.id
+                 FROM student_school_enrollments sse
+                 WHERE sse.student_id = a.student_id
+                   AND sse.school_id = a.school_id
+                   AND sse.deleted_at IS NULL
+                 ORDER BY sse.created_at DESC
+                 LIMIT 1
+               )) AS enrollment_id,
+               a.date, a.period_id, a.status, a.note, a.recorded_by,
+               a.created_at, a.updated_at
+        FROM attendance a
+        WHERE a.deleted_at IS NULL
+        "#
+    }
+
+    fn attendance_from_row(row: PgRow) -> Result<Attendance, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let student_id: String = row
+            .try_get::<Option<String>, _>("enrollment_id")
+            .ok()
+            .flatten()
+            .map_or_else(|| row.try_get("profile_id").map_err(Self::db_error), Ok)?;
+
+        Ok(Attendance {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok(), "school_id")?,
+            class_id: Self::parse_oid_opt(row.try_get("class_id").ok().flatten(), "class_id")?,
+            student_id: Self::parse_oid(&student_id, "student_id")?,
+            date: row.try_get("date").map_err(Self::db_error)?,
+            period_id: Self::parse_oid_opt(row.try_get("period_id").ok().flatten(), "period_id")?,
+            status: Self::status_from_string(row.try_get("status").ok())
+                .unwrap_or(AttendanceStatus::Absent),
+            note: row.try_get("note").ok().flatten(),
+            recorded_by: Self::parse_oid_opt(
+                row.try_get("recorded_by").ok().flatten(),
+                "recorded_by",
+            )?,
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    fn push_query_filters<'a>(
+        sql: &mut QueryBuilder<'a, Postgres>,
+        school_id: &'a str,
+        query: &'a AttendanceQuery,
+        status: &'a Option<String>,
+    ) -> Result<(), AppError> {
+        sql.push(" AND a.school_id = ").push_bind(school_id);
+
+        if let Some(class_id) = &query.class_id {
+            Self::parse_oid(class_id, "class_id")?;
+            sql.push(" AND a.class_id = ").push_bind(class_id);
+        }
+        if let Some(student_id) = &query.student_id {
+            Self::parse_oid(student_id, "student_id")?;
+            sql.push(" AND (a.enrollment_id = ")
+                .push_bind(student_id)
+                .push(" OR a.student_id = ")
+                .push_bind(student_id)
+                .push(")");
+        }
+        if let Some(period_id) = &query.period_id {
+            Self::parse_oid(period_id, "period_id")?;
+            sql.push(" AND a.period_id = ").push_bind(period_id);
+        }
+        if let Some(status) = status {
+            sql.push(" AND lower(a.status) = lower(")
+                .push_bind(status)
+                .push(")");
+        }
+        if let Some(from) = query.from {
+            sql.push(" AND a.date >= ").push_bind(from);
+        }
+        if let Some(to) = query.to {
+            sql.push(" AND a.date <= ").push_bind(to);
+        }
+
+        Ok(())
+    }
+
+    /// Resolve an enrollment id (or a raw profile id) to the student profile id
+    /// the `attendance.student_id` column references.
+    async fn profile_id_for(
+        &self,
+        school_id: &str,
+        student_id: &str,
+    ) -> Result<Option<(String, String)>, AppError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, student_id
+            FROM student_school_enrollments
+            WHERE school_id = $1 AND (id = $2 OR student_id = $2) AND deleted_at IS NULL
+            ORDER BY (id = $2) DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(school_id)
+        .bind(student_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        row.map(|row| {
+            Ok((
+                row.try_get("id").map_err(Self::db_error)?,
+                row.try_get("student_id").map_err(Self::db_error)?,
+            ))
+        })
+        .transpose()
+    }
+
+    pub async fn class_school_id(&self, class_id: &str) -> Result<Option<String>, AppError> {
+        sqlx::query_scalar::<_, Option<String>>(
+            "SELECT school_id FROM classes WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(class_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map(Option::flatten)
+        .map_err(Self::db_error)
+    }
+
+    /// Whether `period_id` appears in any of the class's timetables.
+    pub async fn class_has_period(
+        &self,
+        class_id: &str,
+        period_id: &str,
+    ) -> Result<bool, AppError> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+              SELECT 1
+              FROM class_timetables ct,
+                   jsonb_array_elements(ct.weekly_schedule) AS day,
+                   jsonb_array_elements(day->'periods') AS period
+              WHERE ct.class_id = $1 AND period->>'period_id' = $2
+            )
+            "#,
+        )
+        .bind(class_id)
+        .bind(period_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Enrollment ids of the students currently placed in the class or subclass.
+    pub async fn class_enrollment_ids(&self, class_id: &str) -> Result<Vec<String>, AppError> {
+        sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT id
+            FROM student_school_enrollments
+            WHERE (class_id = $1 OR subclass_id = $1)
+              AND deleted_at IS NULL
+              AND is_active = true
+            "#,
+        )
+        .bind(class_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    pub async fn find_by_id(&self, id: &IdType) -> Result<Attendance, AppError> {
+        let id = Self::id_to_string(id)?;
+        let row = sqlx::query(&format!("{} AND a.id = $1 LIMIT 1", Self::select_sql()))
+            .bind(&id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        match row {
+            Some(row) => Self::attendance_from_row(row),
+            None => Err(AppError::not_found("Attendance record not found")),
+        }
+    }
+
+    pub async fn find_many(
+        &self,
+        school_id: &str,
+        query: &AttendanceQuery,
+    ) -> Result<Paginated<Attendance>, AppError> {
+        let limit = query.limit.unwrap_or(50).max(1);
+        let skip = query.skip.unwrap_or(0).max(0);
+        let status = query.status.as_ref().and_then(Self::enum_to_string);
+
+        let mut count_query = QueryBuilder::<Postgres>::new(
+            "SELECT count(*) FROM attendance a WHERE a.deleted_at IS NULL",
+        );
+        Self::push_query_filters(&mut count_query, school_id, query, &status)?;
+        let total: i64 = count_query
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        let mut data_query = QueryBuilder::<Postgres>::new(Self::select_sql());
+        Self::push_query_filters(&mut data_query, school_id, query, &status)?;
+        data_query
+            .push(" ORDER BY a.date DESC, a.updated_at DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(skip);
+
+        let rows = data_query
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        let data = rows
+            .into_iter()
+            .map(Self::attendance_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Paginated {
+            data,
+            total,
+            total_pages: ((total as f64) / (limit as f64)).ceil() as i64,
+            current_page: (skip / limit) + 1,
+        })
+    }
+
+    /// Write a whole class roll in one transaction. Existing marks for the same
+    /// student, date and period are overwritten.
+    pub async fn upsert_many(
+        &self,
+        school_id: &str,
+        class_id: &str,
+        date: NaiveDate,
+        period_id: Option<&str>,
+        entries: &[AttendanceEntry],
+        recorded_by: Option<&str>,
+    ) -> Result<usize, AppError> {
+        let mut resolved = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let student_id = entry.student_id.to_hex();
+            let Some(ids) = self.profile_id_for(school_id, &student_id).await? else {
+                return Err(AppError::bad_request(format!(
+                    "Student {} is not enrolled in this school",
+                    student_id
+                )));
+            };
+            resolved.push((ids, entry));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        for ((enrollment_id, profile_id), entry) in &resolved {
+            sqlx::query(
+                r#"
+                INSERT INTO attendance (
+                  id, school_id, student_id, enrollment_id, class_id, date, period_id,
+                  status, note, recorded_by
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (school_id, student_id, date, (COALESCE(period_id, '')))
+                DO UPDATE SET enrollment_id = EXCLUDED.enrollment_id,
+                              class_id = EXCLUDED.class_id,
+                              status = EXCLUDED.status,
+                              note = EXCLUDED.note,
+                              recorded_by = EXCLUDED.recorded_by,
+                              deleted_at = NULL
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(school_id)
+            .bind(profile_id)
+            .bind(enrollment_id)
+            .bind(class_id)
+            .bind(date)
+            .bind(period_id)
+            .bind(Self::enum_to_string(&entry.status))
+            .bind(&entry.note)
+            .bind(recorded_by)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+        tx.commit().await.map_err(Self::db_error)?;
+
+        Ok(resolved.len())
+    }
+
+    pub async fn update(
+        &self,
+        id: &IdType,
+        status: Option<AttendanceStatus>,
+        note: Option<Option<String>>,
+        recorded_by: Option<&str>,
+    ) -> Result<Attendance, AppError> {
+        let id_string = Self::id_to_string(id)?;
+        let mut sql = QueryBuilder::<Postgres>::new("UPDATE attendance SET updated_at = now()");
+        if let Some(status) = status {
+            sql.push(", status = ")
+                .push_bind(Self::enum_to_string(&status));
+        }
+        if let Some(note) = note {
+            sql.push(", note = ").push_bind(note);
+        }
+        if let Some(recorded_by) = recorded_by {
+            sql.push(", recorded_by = ")
+                .push_bind(recorded_by.to_string());
+        }
+        sql.push(" WHERE id = ")
+            .push_bind(&id_string)
+            .push(" AND deleted_at IS NULL");
+        sql.build()
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        self.find_by_id(id).await
+    }
+
+    pub async fn soft_delete(&self, id: &IdType) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE attendance SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(Self::id_to_string(id)?)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Every student in the class with their mark (if any) for the given day/period.
+    pub async fn class_register(
+        &self,
+        class_id: &str,
+        date: NaiveDate,
+        period_id: Option<&str>,
+    ) -> Result<Vec<RegisterStudent>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sse.id AS enrollment_id, sp.name, sse.registration_number, sp.image,
+                   a.id AS attendance_id, a.status, a.note
+            FROM student_school_enrollments sse
+            JOIN student_profiles sp ON sp.id = sse.student_id
+            LEFT JOIN attendance a
+              ON a.student_id = sse.student_id
+             AND a.school_id = sse.school_id
+             AND a.date = $2
+             AND COALESCE(a.period_id, '') = COALESCE($3, '')
+             AND a.deleted_at IS NULL
+            WHERE (sse.class_id = $1 OR sse.subclass_id = $1)
+              AND sse.deleted_at IS NULL
+              AND sse.is_active = true
+            ORDER BY lower(sp.name) ASC
+            "#,
+        )
+        .bind(class_id)
+        .bind(date)
+        .bind(period_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let enrollment_id: String = row.try_get("enrollment_id").map_err(Self::db_error)?;
+                Ok(RegisterStudent {
+                    student_id: Self::parse_oid(&enrollment_id, "student_id")?,
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    registration_number: row.try_get("registration_number").ok().flatten(),
+                    image: row.try_get("image").ok().flatten(),
+                    attendance_id: Self::parse_oid_opt(
+                        row.try_get("attendance_id").ok().flatten(),
+                        "attendance_id",
+                    )?,
+                    status: Self::status_from_string(row.try_get("status").ok().flatten()),
+                    note: row.try_get("note").ok().flatten(),
+                })
+            })
+            .collect()
+    }
+
+    /// Daily marks for one student. Per-period rows are left out so a day
+    /// with several lessons is still counted once.
+    pub async fn student_counts(
+        &self,
+        school_id: &str,
+        student_id: &str,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<AttendanceCounts, AppError> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT count(*) FILTER (WHERE lower(status) = 'present')::BIGINT AS present,
+                   count(*) FILTER (WHERE lower(status) = 'absent')::BIGINT AS absent,
+                   count(*) FILTER (WHERE lower(status) = 'late')::BIGINT AS late,
+                   count(*) FILTER (WHERE lower(status) = 'excused')::BIGINT AS excused
+            FROM attendance
+            WHERE deleted_at IS NULL AND period_id IS NULL AND school_id =
+            "#,
+        );
+        query
+            .push_bind(school_id)
+            .push(" AND (enrollment_id = ")
+            .push_bind(student_id)
+            .push(" OR student_id = ")
+            .push_bind(student_id)
+            .push(")");
+        if let Some(from) = from {
+            query.push(" AND date >= ").push_bind(from);
+        }
+        if let Some(to) = to {
+            query.push(" AND date <= ").push_bind(to);
+        }
+
+        let row = query
+            .build()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        Ok(AttendanceCounts {
+            present: row.try_get("present").map_err(Self::db_error)?,
+            absent: row.try_get("absent").map_err(Self::db_error)?,
+            late: row.try_get("late").map_err(Self::db_error)?,
+            excused: row.try_get("excused").map_err(Self::db_error)?,
+            unmarked: 0,
+        })
+    }
+}
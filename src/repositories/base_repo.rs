@@ -140,9 +140,7 @@ impl BaseRepository {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn push_value<'a>(query: &mut QueryBuilder<'a, Postgres>, value: &'a SqlValue) {
@@ -248,9 +246,7 @@ impl BaseRepository {
         for<'r> T: FromRow<'r, PgRow> + Send + Unpin,
     {
         if values.is_empty() {
-            return Err(AppError {
-                message: "No valid fields to create".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to create"));
         }
 
         let mut query = QueryBuilder::<Postgres>::new("INSERT INTO ");
@@ -374,9 +370,7 @@ impl BaseRepository {
         for<'r> T: FromRow<'r, PgRow> + Send + Unpin,
     {
         if values.is_empty() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         let mut query = QueryBuilder::<Postgres>::new("UPDATE ");
@@ -394,9 +388,7 @@ impl BaseRepository {
             .fetch_optional(&self.pool)
             .await
             .map_err(Self::db_error)?
-            .ok_or(AppError {
-                message: "Record not found".into(),
-            })
+            .ok_or(AppError::not_found("Record not found"))
     }
 
     pub async fn update_many_and_fetch<T>(
@@ -408,14 +400,10 @@ impl BaseRepository {
         for<'r> T: FromRow<'r, PgRow> + Send + Unpin,
     {
         if filter.is_empty() {
-            return Err(AppError {
-                message: "Update filter cannot be empty".into(),
-            });
+            return Err(AppError::bad_request("Update filter cannot be empty"));
         }
         if values.is_empty() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         let mut query = QueryBuilder::<Postgres>::new("UPDATE ");
@@ -446,9 +434,7 @@ impl BaseRepository {
             .await
             .map_err(Self::db_error)?;
         if result.rows_affected() == 0 {
-            Err(AppError {
-                message: "Record not found".into(),
-            })
+            Err(AppError::not_found("Record not found"))
         } else {
             Ok(())
         }
@@ -456,9 +442,7 @@ impl BaseRepository {
 
     pub async fn delete_many(&self, filter: SqlFilter) -> Result<(), AppError> {
         if filter.is_empty() {
-            return Err(AppError {
-                message: "Delete filter cannot be empty".into(),
-            });
+            return Err(AppError::bad_request("Delete filter cannot be empty"));
         }
 
         let mut query = QueryBuilder::<Postgres>::new(self.base_delete());
@@ -521,8 +505,7 @@ impl BaseRepository {
     }
 
     pub fn serialize_for_response<T: Serialize>(value: &T) -> Result<serde_json::Value, AppError> {
-        serde_json::to_value(value).map_err(|e| AppError {
-            message: format!("Failed to serialize response: {}", e),
-        })
+        serde_json::to_value(value)
+            .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
     }
 }
@@ -0,0 +1,761 @@
+use crate::domain::{
+    common_details::Paginated,
+    finance::{
+        FeeItem, FeeStructure, FeeStructurePartial, FinanceQuery, FinanceRecord, FinanceRecordType,
+        FinanceStatus, DEFAULT_CURRENCY,
+    },
+};
+use crate::errors::AppError;
+use crate::models::id_model::IdType;
+use crate::utils::object_id::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Postgres, QueryBuilder, Row, Transaction};
+
+/// Student placement needed to bill an enrolled student.
+#[derive(Debug, Clone)]
+pub struct BillableStudent {
+    pub enrollment_id: String,
+    pub profile_id: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptContext {
+    pub school_name: Option<String>,
+    pub student_name: Option<String>,
+    pub registration_number: Option<String>,
+}
+
+pub struct FinanceRepo {
+    pub pool: PgPool,
+}
+
+impl FinanceRepo {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    pub fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn id_to_string(id: &IdType) -> Result<String, AppError> {
+        Ok(IdType::to_object_id(id)?.to_hex())
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn parse_oid_opt(raw: Option<String>, field: &str) -> Result<Option<ObjectId>, AppError> {
+        raw.map(|value| Self::parse_oid(&value, field)).transpose()
+    }
+
+    fn enum_to_string<T: Serialize>(value: &T) -> Option<String> {
+        match serde_json::to_value(value).ok()? {
+            serde_json::Value::String(value) => Some(value),
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn enum_from_string<T: DeserializeOwned>(value: Option<String>) -> Option<T> {
+        value.and_then(|raw| {
+            serde_json::from_value(serde_json::Value::String(raw.to_lowercase())).ok()
+        })
+    }
+
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>, AppError> {
+        self.pool.begin().await.map_err(Self::db_error)
+    }
+
+    pub async fn class_school_id(&self, class_id: &str) -> Result<Option<String>, AppError> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT school_id FROM classes WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(class_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    // ========== FEE STRUCTURES ==========
+
+    fn structure_select_sql() -> &'static str {
+        r#"
+        SELECT fs.id, fs.school_id, fs.class_id, fs.education_year_id, fs.term_id, fs.name,
+               fs.description, fs.currency, fs.due_at, fs.is_active, fs.created_by,
+               fs.created_at, fs.updated_at,
+               COALESCE((
+                 SELECT jsonb_agg(jsonb_build_object(
+                          'name', i.name,
+                          'amount', i.amount::DOUBLE PRECISION,
+                          'is_optional', i.is_optional
+                        ) ORDER BY i.position)
+                 FROM fee_structure_items i
+                 WHERE i.fee_structure_id = fs.id
+               ), '[]'::jsonb) AS items
+        FROM fee_structures fs
+        WHERE fs.deleted_at IS NULL
+        "#
+    }
+
+    fn structure_from_row(row: PgRow) -> Result<FeeStructure, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let items_json: serde_json::Value = row
+            .try_get("items")
+            .unwrap_or(serde_json::Value::Array(vec![]));
+        let items: Vec<FeeItem> = serde_json::from_value(items_json).unwrap_or_default();
+        let total_amount = items.iter().map(|item| item.amount).sum();
+
+        Ok(FeeStructure {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok(), "school_id")?,
+            class_id: Self::parse_oid_opt(row.try_get("class_id").ok().flatten(), "class_id")?,
+            education_year_id: Self::parse_oid_opt(
+                row.try_get("education_year_id").ok().flatten(),
+                "education_year_id",
+            )?,
+            term_id: row.try_get("term_id").ok().flatten(),
+            name: row.try_get("name").map_err(Self::db_error)?,
+            description: row.try_get("description").ok().flatten(),
+            items,
+            total_amount,
+            currency: row
+                .try_get("currency")
+                .unwrap_or_else(|_| DEFAULT_CURRENCY.to_string()),
+            due_date: row.try_get("due_at").ok().flatten(),
+            is_active: row.try_get("is_active").ok(),
+            created_by: Self::parse_oid_opt(
+                row.try_get("created_by").ok().flatten(),
+                "created_by",
+            )?,
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    async fn replace_items(
+        conn: &mut PgConnection,
+        structure_id: &str,
+        items: &[FeeItem],
+    ) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM fee_structure_items WHERE fee_structure_id = $1")
+            .bind(structure_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(Self::db_error)?;
+
+        for (position, item) in items.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO fee_structure_items (id, fee_structure_id, name, amount, is_optional, position)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(structure_id)
+            .bind(&item.name)
+            .bind(item.amount)
+            .bind(item.is_optional)
+            .bind(position as i32)
+            .execute(&mut *conn)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn insert_structure(
+        &self,
+        structure: &FeeStructure,
+    ) -> Result<FeeStructure, AppError> {
+        let school_id = structure
+            .school_id
+            .as_ref()
+            .map(|id| id.to_hex())
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
+        let id = structure
+            .id
+            .map(|id| id.to_hex())
+            .unwrap_or_else(Self::new_id);
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        sqlx::query(
+            r#"
+            INSERT INTO fee_structures (
+              id, school_id, class_id, education_year_id, term_id, name, description,
+              currency, due_at, is_active, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(&id)
+        .bind(&school_id)
+        .bind(structure.class_id.as_ref().map(|id| id.to_hex()))
+        .bind(structure.education_year_id.as_ref().map(|id| id.to_hex()))
+        .bind(&structure.term_id)
+        .bind(&structure.name)
+        .bind(&structure.description)
+        .bind(&structure.currency)
+        .bind(structure.due_date)
+        .bind(structure.is_active.unwrap_or(true))
+        .bind(structure.created_by.as_ref().map(|id| id.to_hex()))
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        Self::replace_items(&mut tx, &id, &structure.items).await?;
+        tx.commit().await.map_err(Self::db_error)?;
+
+        self.find_structure(&IdType::from_string(id)).await
+    }
+
+    pub async fn find_structure(&self, id: &IdType) -> Result<FeeStructure, AppError> {
+        let row = sqlx::query(&format!("{} AND fs.id = $1", Self::structure_select_sql()))
+            .bind(Self::id_to_string(id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        match row {
+            Some(row) => Self::structure_from_row(row),
+            None => Err(AppError::not_found("Fee structure not found")),
+        }
+    }
+
+    pub async fn list_structures(
+        &self,
+        school_id: &str,
+        query: &FinanceQuery,
+    ) -> Result<Vec<FeeStructure>, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(Self::structure_select_sql());
+        sql.push(" AND fs.school_id = ").push_bind(school_id);
+        if let Some(class_id) = &query.class_id {
+            Self::parse_oid(class_id, "class_id")?;
+            sql.push(" AND fs.class_id = ").push_bind(class_id);
+        }
+        if let Some(education_year_id) = &query.education_year_id {
+            Self::parse_oid(education_year_id, "education_year_id")?;
+            sql.push(" AND fs.education_year_id = ")
+                .push_bind(education_year_id);
+        }
+        if let Some(term_id) = &query.term_id {
+            sql.push(" AND fs.term_id = ").push_bind(term_id);
+        }
+        sql.push(" ORDER BY fs.updated_at DESC");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::structure_from_row).collect()
+    }
+
+    pub async fn update_structure(
+        &self,
+        id: &IdType,
+        update: &FeeStructurePartial,
+    ) -> Result<FeeStructure, AppError> {
+        let id_string = Self::id_to_string(id)?;
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+
+        let mut sql = QueryBuilder::<Postgres>::new("UPDATE fee_structures SET updated_at = now()");
+        if let Some(name) = update.name.clone() {
+            sql.push(", name = ").push_bind(name);
+        }
+        if let Some(description) = update.description.clone() {
+            sql.push(", description = ").push_bind(description);
+        }
+        if let Some(term_id) = update.term_id.clone() {
+            sql.push(", term_id = ").push_bind(term_id);
+        }
+        if let Some(class_id) = update.class_id {
+            sql.push(", class_id = ")
+                .push_bind(class_id.map(|id| id.to_hex()));
+        }
+        if let Some(education_year_id) = update.education_year_id {
+            sql.push(", education_year_id = ")
+                .push_bind(education_year_id.map(|id| id.to_hex()));
+        }
+        if let Some(currency) = update.currency.clone() {
+            sql.push(", currency = ").push_bind(currency);
+        }
+        if let Some(due_date) = update.due_date {
+            sql.push(", due_at = ").push_bind(due_date);
+        }
+        if let Some(is_active) = update.is_active {
+            sql.push(", is_active = ")
+                .push_bind(is_active.unwrap_or(true));
+        }
+        sql.push(" WHERE id = ")
+            .push_bind(&id_string)
+            .push(" AND deleted_at IS NULL");
+        sql.build()
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+
+        if let Some(items) = &update.items {
+            Self::replace_items(&mut tx, &id_string, items).await?;
+        }
+        tx.commit().await.map_err(Self::db_error)?;
+
+        self.find_structure(id).await
+    }
+
+    pub async fn soft_delete_structure(&self, id: &IdType) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE fee_structures SET deleted_at = now(), is_active = false WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(Self::id_to_string(id)?)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Active students placed in the class or one of its subclasses.
+    pub async fn billable_students(
+        &self,
+        class_id: &str,
+    ) -> Result<Vec<BillableStudent>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, student_id
+            FROM student_school_enrollments
+            WHERE (class_id = $1 OR subclass_id = $1
+                   OR class_id IN (SELECT id FROM classes WHERE parent_class_id = $1))
+              AND deleted_at IS NULL
+              AND is_active = true
+            "#,
+        )
+        .bind(class_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(BillableStudent {
+                    enrollment_id: row.try_get("id").map_err(Self::db_error)?,
+                    profile_id: row.try_get("student_id").map_err(Self::db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn student_enrollment(
+        &self,
+        school_id: &str,
+        student_id: &str,
+    ) -> Result<Option<BillableStudent>, AppError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, student_id
+            FROM student_school_enrollments
+            WHERE school_id = $1 AND (id = $2 OR student_id = $2) AND deleted_at IS NULL
+            ORDER BY (id = $2) DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(school_id)
+        .bind(student_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        row.map(|row| {
+            Ok(BillableStudent {
+                enrollment_id: row.try_get("id").map_err(Self::db_error)?,
+                profile_id: row.try_get("student_id").map_err(Self::db_error)?,
+            })
+        })
+        .transpose()
+    }
+
+    // ========== LEDGER ==========
+
+    fn record_select_sql() -> &'static str {
+        r#"
+        SELECT id, school_id, student_id AS profile_id, enrollment_id, record_type, description,
+               amount::DOUBLE PRECISION AS amount, currency, status, due_at, paid_at,
+               invoice_id, related_record_id, fee_structure_id, class_id, education_year_id,
+               term_id, reference_number, payment_method, created_by, created_at, updated_at
+        FROM finance_records
+        WHERE deleted_at IS NULL
+        "#
+    }
+
+    fn record_from_row(row: PgRow) -> Result<FinanceRecord, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let student_id: Option<String> = row
+            .try_get::<Option<String>, _>("enrollment_id")
+            .ok()
+            .flatten()
+            .or_else(|| row.try_get("profile_id").ok().flatten());
+
+        Ok(FinanceRecord {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok(), "school_id")?,
+            student_id: Self::parse_oid_opt(student_id, "student_id")?,
+            record_type: Self::enum_from_string(row.try_get("record_type").ok())
+                .unwrap_or(FinanceRecordType::Fee),
+            description: row.try_get("description").ok().flatten(),
+            amount: row.try_get("amount").unwrap_or(0.0),
+            currency: row
+                .try_get("currency")
+                .unwrap_or_else(|_| DEFAULT_CURRENCY.to_string()),
+            status: Self::enum_from_string(row.try_get("status").ok())
+                .unwrap_or(FinanceStatus::Pending),
+            due_date: row.try_get("due_at").ok().flatten(),
+            paid_at: row.try_get("paid_at").ok().flatten(),
+            invoice_id: Self::parse_oid_opt(
+                row.try_get("invoice_id").ok().flatten(),
+                "invoice_id",
+            )?,
+            related_record_id: Self::parse_oid_opt(
+                row.try_get("related_record_id").ok().flatten(),
+                "related_record_id",
+            )?,
+            fee_structure_id: Self::parse_oid_opt(
+                row.try_get("fee_structure_id").ok().flatten(),
+                "fee_structure_id",
+            )?,
+            class_id: Self::parse_oid_opt(row.try_get("class_id").ok().flatten(), "class_id")?,
+            education_year_id: Self::parse_oid_opt(
+                row.try_get("education_year_id").ok().flatten(),
+                "education_year_id",
+            )?,
+            term_id: row.try_get("term_id").ok().flatten(),
+            reference_number: row.try_get("reference_number").ok().flatten(),
+            payment_method: Self::enum_from_string(row.try_get("payment_method").ok().flatten()),
+            created_by: Self::parse_oid_opt(
+                row.try_get("created_by").ok().flatten(),
+                "created_by",
+            )?,
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    /// Insert one ledger row. `profile_id` is the student profile the
+    /// `finance_records.student_id` column references.
+    pub async fn insert_record(
+        conn: &mut PgConnection,
+        record: &FinanceRecord,
+        profile_id: Option<&str>,
+    ) -> Result<String, AppError> {
+        let id = record.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
+        sqlx::query(
+            r#"
+            INSERT INTO finance_records (
+              id, school_id, student_id, enrollment_id, record_type, description, amount,
+              currency, status, due_at, paid_at, invoice_id, related_record_id,
+              fee_structure_id, class_id, education_year_id, term_id, reference_number,
+              payment_method, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            "#,
+        )
+        .bind(&id)
+        .bind(record.school_id.as_ref().map(|id| id.to_hex()))
+        .bind(profile_id)
+        .bind(record.student_id.as_ref().map(|id| id.to_hex()))
+        .bind(Self::enum_to_string(&record.record_type))
+        .bind(&record.description)
+        .bind(record.amount)
+        .bind(&record.currency)
+        .bind(Self::enum_to_string(&record.status))
+        .bind(record.due_date)
+        .bind(record.paid_at)
+        .bind(record.invoice_id.as_ref().map(|id| id.to_hex()))
+        .bind(record.related_record_id.as_ref().map(|id| id.to_hex()))
+        .bind(record.fee_structure_id.as_ref().map(|id| id.to_hex()))
+        .bind(record.class_id.as_ref().map(|id| id.to_hex()))
+        .bind(record.education_year_id.as_ref().map(|id| id.to_hex()))
+        .bind(&record.term_id)
+        .bind(&record.reference_number)
+        .bind(record.payment_method.as_ref().and_then(Self::enum_to_string))
+        .bind(record.created_by.as_ref().map(|id| id.to_hex()))
+        .execute(&mut *conn)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(id)
+    }
+
+    pub async fn set_status(
+        conn: &mut PgConnection,
+        id: &str,
+        status: FinanceStatus,
+        paid_at: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE finance_records SET status = $2, paid_at = COALESCE($3, paid_at) WHERE id = $1",
+        )
+        .bind(id)
+        .bind(Self::enum_to_string(&status))
+        .bind(paid_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    pub async fn find_record(&self, id: &IdType) -> Result<FinanceRecord, AppError> {
+        let row = sqlx::query(&format!("{} AND id = $1", Self::record_select_sql()))
+            .bind(Self::id_to_string(id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        match row {
+            Some(row) => Self::record_from_row(row),
+            None => Err(AppError::not_found("Finance record not found")),
+        }
+    }
+
+    /// Ledger rows attached to an invoice (payments, refunds, adjustments, carried balances).
+    pub async fn invoice_entries(
+        conn: &mut PgConnection,
+        invoice_id: &str,
+    ) -> Result<Vec<FinanceRecord>, AppError> {
+        let rows = sqlx::query(&format!(
+            "{} AND invoice_id = $1 ORDER BY created_at ASC",
+            Self::record_select_sql()
+        ))
+        .bind(invoice_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter().map(Self::record_from_row).collect()
+    }
+
+    pub async fn find_invoice_entries(
+        &self,
+        invoice_id: &str,
+    ) -> Result<Vec<FinanceRecord>, AppError> {
+        let mut conn = self.pool.acquire().await.map_err(Self::db_error)?;
+        Self::invoice_entries(&mut conn, invoice_id).await
+    }
+
+    /// Lock the invoice row so concurrent payments cannot both pass the balance check.
+    pub async fn lock_invoice(
+        conn: &mut PgConnection,
+        invoice_id: &str,
+    ) -> Result<FinanceRecord, AppError> {
+        let row = sqlx::query(&format!(
+            "{} AND id = $1 AND record_type = 'invoice' FOR UPDATE",
+            Self::record_select_sql()
+        ))
+        .bind(invoice_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Self::db_error)?;
+
+        match row {
+            Some(row) => Self::record_from_row(row),
+            None => Err(AppError::not_found("Invoice not found")),
+        }
+    }
+
+    /// Earlier invoices of a student that still hold a balance, excluding `structure_id`.
+    pub async fn open_invoices_for_student(
+        conn: &mut PgConnection,
+        profile_id: &str,
+        exclude_structure_id: &str,
+    ) -> Result<Vec<FinanceRecord>, AppError> {
+        let rows = sqlx::query(&format!(
+            r#"{} AND student_id = $1
+               AND record_type = 'invoice'
+               AND status IN ('pending', 'partial')
+               AND fee_structure_id IS DISTINCT FROM $2
+               ORDER BY created_at ASC FOR UPDATE"#,
+            Self::record_select_sql()
+        ))
+        .bind(profile_id)
+        .bind(exclude_structure_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter().map(Self::record_from_row).collect()
+    }
+
+    pub async fn invoiced_profiles(&self, structure_id: &str) -> Result<Vec<String>, AppError> {
+        sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT student_id
+            FROM finance_records
+            WHERE fee_structure_id = $1 AND record_type = 'invoice'
+              AND deleted_at IS NULL AND student_id IS NOT NULL
+            "#,
+        )
+        .bind(structure_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    fn push_record_filters<'a>(
+        sql: &mut QueryBuilder<'a, Postgres>,
+        school_id: &'a str,
+        query: &'a FinanceQuery,
+        record_type: &'a Option<String>,
+        status: &'a Option<String>,
+    ) -> Result<(), AppError> {
+        sql.push(" AND school_id = ").push_bind(school_id);
+        if let Some(student_id) = &query.student_id {
+            Self::parse_oid(student_id, "student_id")?;
+            sql.push(" AND (enrollment_id = ")
+                .push_bind(student_id)
+                .push(" OR student_id = ")
+                .push_bind(student_id)
+                .push(")");
+        }
+        if let Some(class_id) = &query.class_id {
+            Self::parse_oid(class_id, "class_id")?;
+            sql.push(" AND class_id = ").push_bind(class_id);
+        }
+        if let Some(fee_structure_id) = &query.fee_structure_id {
+            Self::parse_oid(fee_structure_id, "fee_structure_id")?;
+            sql.push(" AND fee_structure_id = ")
+                .push_bind(fee_structure_id);
+        }
+        if let Some(education_year_id) = &query.education_year_id {
+            Self::parse_oid(education_year_id, "education_year_id")?;
+            sql.push(" AND education_year_id = ")
+                .push_bind(education_year_id);
+        }
+        if let Some(term_id) = &query.term_id {
+            sql.push(" AND term_id = ").push_bind(term_id);
+        }
+        if let Some(record_type) = record_type {
+            sql.push(" AND record_type = ").push_bind(record_type);
+        }
+        if let Some(status) = status {
+            sql.push(" AND lower(status) = ").push_bind(status);
+        }
+        Ok(())
+    }
+
+    pub async fn list_records(
+        &self,
+        school_id: &str,
+        query: &FinanceQuery,
+    ) -> Result<Paginated<FinanceRecord>, AppError> {
+        let limit = query.limit.unwrap_or(20).max(1);
+        let skip = query.skip.unwrap_or(0).max(0);
+        let record_type = query.record_type.as_ref().and_then(Self::enum_to_string);
+        let status = query.status.as_ref().and_then(Self::enum_to_string);
+
+        let mut count_query = QueryBuilder::<Postgres>::new(
+            "SELECT count(*) FROM finance_records WHERE deleted_at IS NULL",
+        );
+        Self::push_record_filters(&mut count_query, school_id, query, &record_type, &status)?;
+        let total: i64 = count_query
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        let mut data_query = QueryBuilder::<Postgres>::new(Self::record_select_sql());
+        Self::push_record_filters(&mut data_query, school_id, query, &record_type, &status)?;
+        data_query
+            .push(" ORDER BY created_at DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(skip);
+
+        let rows = data_query
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        let data = rows
+            .into_iter()
+            .map(Self::record_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Paginated {
+            data,
+            total,
+            total_pages: ((total as f64) / (limit as f64)).ceil() as i64,
+            current_page: (skip / limit) + 1,
+        })
+    }
+
+    /// Every ledger row of a student in chronological order.
+    pub async fn student_ledger(
+        &self,
+        school_id: &str,
+        profile_id: &str,
+    ) -> Result<Vec<FinanceRecord>, AppError> {
+        let rows = sqlx::query(&format!(
+            "{} AND school_id = $1 AND student_id = $2 ORDER BY created_at ASC",
+            Self::record_select_sql()
+        ))
+        .bind(school_id)
+        .bind(profile_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter().map(Self::record_from_row).collect()
+    }
+
+    /// Total already refunded against a payment.
+    pub async fn refunded_amount(
+        conn: &mut PgConnection,
+        payment_id: &str,
+    ) -> Result<f64, AppError> {
+        sqlx::query_scalar::<_, f64>(
+            r#"
+            SELECT COALESCE(sum(amount), 0)::DOUBLE PRECISION
+            FROM finance_records
+            WHERE related_record_id = $1 AND record_type = 'refund' AND deleted_at IS NULL
+            "#,
+        )
+        .bind(payment_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    pub async fn receipt_context(
+        &self,
+        school_id: Option<&str>,
+        enrollment_id: Option<&str>,
+    ) -> Result<ReceiptContext, AppError> {
+        let row = sqlx::query(
+            r#"
+            SELECT s.name AS school_name, sp.name AS student_name, sse.registration_number
+            FROM (SELECT 1) AS one
+            LEFT JOIN schools s ON s.id = $1
+            LEFT JOIN student_school_enrollments sse ON sse.id = $2
+            LEFT JOIN student_profiles sp ON sp.id = sse.student_id
+            "#,
+        )
+        .bind(school_id)
+        .bind(enrollment_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(ReceiptContext {
+            school_name: row.try_get("school_name").ok().flatten(),
+            student_name: row.try_get("student_name").ok().flatten(),
+            registration_number: row.try_get("registration_number").ok().flatten(),
+        })
+    }
+}
@@ -1,3 +1,7 @@
+pub mod attendance_repo;
 pub mod base_repo;
+pub mod finance_repo;
 pub mod legacy_mongo_base_repo;
+pub mod notification_repo;
+pub mod promotion_repo;
 pub mod user_repo;
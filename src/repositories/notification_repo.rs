@@ -0,0 +1,342 @@
+use crate::domain::{
+    common_details::Paginated,
+    notification::{Notification, NotificationPreference, NotificationQuery},
+};
+use crate::errors::AppError;
+use crate::models::id_model::IdType;
+use crate::utils::object_id::ObjectId;
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+
+pub struct NotificationRepo {
+    pub pool: PgPool,
+}
+
+impl NotificationRepo {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn id_to_string(id: &IdType) -> Result<String, AppError> {
+        Ok(IdType::to_object_id(id)?.to_hex())
+    }
+
+    fn parse_oid_opt(raw: Option<String>, field: &str) -> Result<Option<ObjectId>, AppError> {
+        raw.map(|value| {
+            ObjectId::parse_str(&value).map_err(|e| {
+                AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+            })
+        })
+        .transpose()
+    }
+
+    fn select_sql() -> &'static str {
+        r#"
+        SELECT n.id, n.user_id, n.school_id, n.category, n.event_type, n.entity_id, n.title,
+               n.data, n.created_at, r.read_at
+        FROM notifications n
+        "#
+    }
+
+    /// Restrict to notifications the user can see: personal ones, plus
+    /// school-wide ones of schools where they are an active member (only
+    /// those created after they joined). Muted categories are left out.
+    fn push_visible<'a>(
+        sql: &mut QueryBuilder<'a, Postgres>,
+        user_id: &'a str,
+        school_id: Option<&'a str>,
+    ) {
+        sql.push(" LEFT JOIN notification_reads r ON r.notification_id = n.id AND r.user_id = ")
+            .push_bind(user_id)
+            .push(" WHERE (n.user_id = ")
+            .push_bind(user_id)
+            .push(
+                " OR (n.user_id IS NULL AND EXISTS (
+                    SELECT 1 FROM school_memberships m
+                    WHERE m.school_id = n.school_id
+                      AND m.status = 'active'
+                      AND n.created_at >= m.created_at
+                      AND m.user_id = ",
+            )
+            .push_bind(user_id)
+            .push(")))")
+            .push(
+                " AND NOT EXISTS (
+                    SELECT 1 FROM notification_preferences p
+                    WHERE p.category = n.category AND p.muted AND p.user_id = ",
+            )
+            .push_bind(user_id)
+            .push(")");
+
+        if let Some(school_id) = school_id {
+            sql.push(" AND (n.school_id = ")
+                .push_bind(school_id)
+                .push(" OR n.school_id IS NULL)");
+        }
+    }
+
+    fn from_row(row: PgRow) -> Result<Notification, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+
+        Ok(Notification {
+            id: Self::parse_oid_opt(Some(id), "id")?,
+            user_id: Self::parse_oid_opt(row.try_get("user_id").ok().flatten(), "user_id")?,
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok().flatten(), "school_id")?,
+            category: row.try_get("category").map_err(Self::db_error)?,
+            event_type: row.try_get("event_type").map_err(Self::db_error)?,
+            entity_id: row.try_get("entity_id").ok().flatten(),
+            title: row.try_get("title").map_err(Self::db_error)?,
+            data: row.try_get("data").unwrap_or(serde_json::Value::Null),
+            read_at: row.try_get("read_at").ok().flatten(),
+            created_at: row.try_get("created_at").ok(),
+        })
+    }
+
+    pub async fn insert(&self, notification: &Notification) -> Result<Notification, AppError> {
+        let id = notification
+            .id
+            .map(|id| id.to_hex())
+            .unwrap_or_else(|| ObjectId::new().to_hex());
+
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (
+              id, user_id, school_id, category, event_type, entity_id, title, data, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, COALESCE($9, now()))
+            "#,
+        )
+        .bind(&id)
+        .bind(notification.user_id.map(|id| id.to_hex()))
+        .bind(notification.school_id.map(|id| id.to_hex()))
+        .bind(&notification.category)
+        .bind(&notification.event_type)
+        .bind(&notification.entity_id)
+        .bind(&notification.title)
+        .bind(&notification.data)
+        .bind(notification.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut inserted = notification.clone();
+        inserted.id = Some(ObjectId::parse_str(&id).map_err(|e| {
+            AppError::bad_request(format!("Invalid id ObjectId-compatible ID: {}", e))
+        })?);
+        Ok(inserted)
+    }
+
+    pub async fn find_many(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+        query: &NotificationQuery,
+    ) -> Result<Paginated<Notification>, AppError> {
+        let limit = query.limit.unwrap_or(20).clamp(1, 100);
+        let skip = query.skip.unwrap_or(0).max(0);
+
+        let mut count_query = QueryBuilder::<Postgres>::new("SELECT count(*) FROM notifications n");
+        Self::push_visible(&mut count_query, user_id, school_id);
+        Self::push_filters(&mut count_query, query);
+        let total: i64 = count_query
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        let mut data_query = QueryBuilder::<Postgres>::new(Self::select_sql());
+        Self::push_visible(&mut data_query, user_id, school_id);
+        Self::push_filters(&mut data_query, query);
+        data_query
+            .push(" ORDER BY n.created_at DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(skip);
+
+        let rows = data_query
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        let data = rows
+            .into_iter()
+            .map(Self::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Paginated {
+            data,
+            total,
+            total_pages: ((total as f64) / (limit as f64)).ceil() as i64,
+            current_page: (skip / limit) + 1,
+        })
+    }
+
+    fn push_filters<'a>(sql: &mut QueryBuilder<'a, Postgres>, query: &'a NotificationQuery) {
+        if query.unread_only {
+            sql.push(" AND r.read_at IS NULL");
+        }
+        if let Some(category) = &query.category {
+            sql.push(" AND n.category = ").push_bind(category);
+        }
+    }
+
+    pub async fn find_visible(
+        &self,
+        id: &IdType,
+        user_id: &str,
+    ) -> Result<Option<Notification>, AppError> {
+        let id = Self::id_to_string(id)?;
+        let mut sql = QueryBuilder::<Postgres>::new(Self::select_sql());
+        Self::push_visible(&mut sql, user_id, None);
+        sql.push(" AND n.id = ").push_bind(&id);
+
+        let row = sql
+            .build()
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        row.map(Self::from_row).transpose()
+    }
+
+    /// Oldest-first unread notifications, used to replay the backlog to a
+    /// client that has just connected to the event stream.
+    pub async fn unread_for_replay(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Notification>, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "SELECT * FROM (SELECT n.id, n.user_id, n.school_id, n.category, n.event_type, \
+             n.entity_id, n.title, n.data, n.created_at, r.read_at FROM notifications n",
+        );
+        Self::push_visible(&mut sql, user_id, school_id);
+        sql.push(" AND r.read_at IS NULL ORDER BY n.created_at DESC LIMIT ")
+            .push_bind(limit)
+            .push(") recent ORDER BY created_at ASC");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    pub async fn unread_count(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+    ) -> Result<i64, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new("SELECT count(*) FROM notifications n");
+        Self::push_visible(&mut sql, user_id, school_id);
+        sql.push(" AND r.read_at IS NULL");
+
+        sql.build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)
+    }
+
+    pub async fn mark_read(&self, notification_id: &str, user_id: &str) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_reads (notification_id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT (notification_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(notification_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    pub async fn mark_unread(&self, notification_id: &str, user_id: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM notification_reads WHERE notification_id = $1 AND user_id = $2")
+            .bind(notification_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    pub async fn mark_all_read(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "INSERT INTO notification_reads (notification_id, user_id) SELECT n.id, ",
+        );
+        sql.push_bind(user_id).push(" FROM notifications n");
+        Self::push_visible(&mut sql, user_id, school_id);
+        sql.push(" AND r.read_at IS NULL");
+        if let Some(category) = category {
+            sql.push(" AND n.category = ").push_bind(category);
+        }
+        sql.push(" ON CONFLICT (notification_id, user_id) DO NOTHING");
+
+        let result = sql
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<NotificationPreference>, AppError> {
+        let rows = sqlx::query(
+            "SELECT category, muted FROM notification_preferences WHERE user_id = $1 ORDER BY category",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(NotificationPreference {
+                    category: row.try_get("category").map_err(Self::db_error)?,
+                    muted: row.try_get("muted").map_err(Self::db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn set_preference(
+        &self,
+        user_id: &str,
+        category: &str,
+        muted: bool,
+    ) -> Result<NotificationPreference, AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, category, muted)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, category) DO UPDATE SET muted = EXCLUDED.muted
+            "#,
+        )
+        .bind(user_id)
+        .bind(category)
+        .bind(muted)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(NotificationPreference {
+            category: category.to_string(),
+            muted,
+        })
+    }
+}
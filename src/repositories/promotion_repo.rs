@@ -0,0 +1,811 @@
+use std::collections::HashMap;
+
+use crate::domain::promotion::{
+    PromotionBatch, PromotionBatchQuery, PromotionResult, PromotionRule, PromotionStatus,
+};
+use crate::errors::AppError;
+use crate::models::id_model::IdType;
+use crate::utils::object_id::ObjectId;
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+
+/// An active enrollment considered for promotion.
+#[derive(Debug, Clone)]
+pub struct PromotionCandidate {
+    pub enrollment_id: String,
+    pub profile_id: String,
+    pub name: String,
+    pub class_id: String,
+}
+
+/// A school class with the level it belongs to, used to find where students
+/// go next.
+#[derive(Debug, Clone)]
+pub struct ClassPlacement {
+    pub id: String,
+    pub name: String,
+    pub parent_class_id: Option<String>,
+    pub main_class_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MainClassLevel {
+    pub id: String,
+    pub trade_id: Option<String>,
+    pub level: Option<i32>,
+}
+
+/// A student's average for one subject over the year's term results.
+#[derive(Debug, Clone)]
+pub struct SubjectAverage {
+    pub class_subject_id: Option<String>,
+    pub main_subject_id: Option<String>,
+    pub subject_name: String,
+    pub percentage: f64,
+}
+
+pub struct PromotionRepo {
+    pub pool: PgPool,
+}
+
+impl PromotionRepo {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    pub fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn id_to_string(id: &IdType) -> Result<String, AppError> {
+        Ok(IdType::to_object_id(id)?.to_hex())
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn parse_oid_opt(raw: Option<String>, field: &str) -> Result<Option<ObjectId>, AppError> {
+        raw.map(|value| Self::parse_oid(&value, field)).transpose()
+    }
+
+    fn enum_to_string<T: Serialize>(value: &T) -> Option<String> {
+        match serde_json::to_value(value).ok()? {
+            serde_json::Value::String(value) => Some(value),
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn enum_from_string<T: DeserializeOwned>(value: Option<String>) -> Option<T> {
+        value.and_then(|raw| serde_json::from_value(serde_json::Value::String(raw)).ok())
+    }
+
+    pub async fn year_school_id(
+        &self,
+        education_year_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT school_id FROM education_years WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(education_year_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    pub async fn year_bounds(
+        &self,
+        education_year_id: &str,
+    ) -> Result<(Option<NaiveDate>, Option<NaiveDate>), AppError> {
+        let row = sqlx::query("SELECT starts_on, ends_on FROM education_years WHERE id = $1")
+            .bind(education_year_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        Ok(row
+            .map(|row| {
+                (
+                    row.try_get("starts_on").ok().flatten(),
+                    row.try_get("ends_on").ok().flatten(),
+                )
+            })
+            .unwrap_or((None, None)))
+    }
+
+    pub async fn class_school_id(&self, class_id: &str) -> Result<Option<String>, AppError> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT school_id FROM classes WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(class_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    // ========== RULES ==========
+
+    fn rule_select_sql() -> &'static str {
+        r#"
+        SELECT id, school_id, education_year_id, name, min_gpa_threshold,
+               min_attendance_percentage, required_subjects_passed, custom_rules,
+               created_by, created_at, updated_at
+        FROM promotion_rules
+        WHERE deleted_at IS NULL
+        "#
+    }
+
+    fn rule_from_row(row: PgRow) -> Result<PromotionRule, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        Ok(PromotionRule {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok(), "school_id")?,
+            education_year_id: Self::parse_oid_opt(
+                row.try_get("education_year_id").ok().flatten(),
+                "education_year_id",
+            )?,
+            name: row.try_get("name").map_err(Self::db_error)?,
+            min_gpa_threshold: row.try_get("min_gpa_threshold").map_err(Self::db_error)?,
+            min_attendance_percentage: row.try_get("min_attendance_percentage").ok().flatten(),
+            required_subjects_passed: row.try_get("required_subjects_passed").unwrap_or_default(),
+            custom_rules: row.try_get("custom_rules").ok().flatten(),
+            created_by: Self::parse_oid_opt(
+                row.try_get("created_by").ok().flatten(),
+                "created_by",
+            )?,
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+            is_deleted: false,
+        })
+    }
+
+    pub async fn insert_rule(&self, rule: &PromotionRule) -> Result<PromotionRule, AppError> {
+        let id = rule.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
+        sqlx::query(
+            r#"
+            INSERT INTO promotion_rules (
+              id, school_id, education_year_id, name, min_gpa_threshold,
+              min_attendance_percentage, required_subjects_passed, custom_rules, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&id)
+        .bind(rule.school_id.map(|id| id.to_hex()))
+        .bind(rule.education_year_id.map(|id| id.to_hex()))
+        .bind(&rule.name)
+        .bind(rule.min_gpa_threshold)
+        .bind(rule.min_attendance_percentage)
+        .bind(&rule.required_subjects_passed)
+        .bind(&rule.custom_rules)
+        .bind(rule.created_by.map(|id| id.to_hex()))
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        self.find_rule(&IdType::String(id)).await
+    }
+
+    pub async fn find_rule(&self, id: &IdType) -> Result<PromotionRule, AppError> {
+        let row = sqlx::query(&format!("{} AND id = $1", Self::rule_select_sql()))
+            .bind(Self::id_to_string(id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?
+            .ok_or(AppError::not_found("Promotion rule not found"))?;
+        Self::rule_from_row(row)
+    }
+
+    pub async fn list_rules(
+        &self,
+        school_id: &str,
+        education_year_id: Option<&str>,
+    ) -> Result<Vec<PromotionRule>, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(Self::rule_select_sql());
+        sql.push(" AND school_id = ").push_bind(school_id);
+        if let Some(education_year_id) = education_year_id {
+            sql.push(" AND education_year_id = ")
+                .push_bind(education_year_id);
+        }
+        sql.push(" ORDER BY created_at DESC");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::rule_from_row).collect()
+    }
+
+    /// The rule for the year, falling back to the school's general rule.
+    pub async fn applicable_rule(
+        &self,
+        school_id: &str,
+        education_year_id: &str,
+    ) -> Result<Option<PromotionRule>, AppError> {
+        let row = sqlx::query(&format!(
+            r#"{}
+            AND school_id = $1
+            AND (education_year_id = $2 OR education_year_id IS NULL)
+            ORDER BY (education_year_id IS NULL) ASC, updated_at DESC
+            LIMIT 1
+            "#,
+            Self::rule_select_sql()
+        ))
+        .bind(school_id)
+        .bind(education_year_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        row.map(Self::rule_from_row).transpose()
+    }
+
+    pub async fn update_rule(&self, rule: &PromotionRule) -> Result<PromotionRule, AppError> {
+        let id = rule
+            .id
+            .ok_or(AppError::bad_request("Promotion rule id is required"))?;
+        sqlx::query(
+            r#"
+            UPDATE promotion_rules
+            SET education_year_id = $2, name = $3, min_gpa_threshold = $4,
+                min_attendance_percentage = $5, required_subjects_passed = $6,
+                custom_rules = $7
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id.to_hex())
+        .bind(rule.education_year_id.map(|id| id.to_hex()))
+        .bind(&rule.name)
+        .bind(rule.min_gpa_threshold)
+        .bind(rule.min_attendance_percentage)
+        .bind(&rule.required_subjects_passed)
+        .bind(&rule.custom_rules)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        self.find_rule(&IdType::ObjectId(id)).await
+    }
+
+    pub async fn soft_delete_rule(&self, id: &IdType) -> Result<(), AppError> {
+        sqlx::query("UPDATE promotion_rules SET deleted_at = now() WHERE id = $1")
+            .bind(Self::id_to_string(id)?)
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    // ========== EVALUATION INPUTS ==========
+
+    pub async fn candidates(
+        &self,
+        school_id: &str,
+        class_id: Option<&str>,
+    ) -> Result<Vec<PromotionCandidate>, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT sse.id AS enrollment_id, sse.student_id AS profile_id, sp.name, sse.class_id
+            FROM student_school_enrollments sse
+            JOIN student_profiles sp ON sp.id = sse.student_id
+            WHERE sse.deleted_at IS NULL
+              AND sp.deleted_at IS NULL
+              AND sse.is_active = true
+              AND lower(sse.status) = 'active'
+              AND sse.class_id IS NOT NULL
+              AND sse.school_id = "#,
+        );
+        sql.push_bind(school_id);
+        if let Some(class_id) = class_id {
+            sql.push(" AND sse.class_id = ").push_bind(class_id);
+        }
+        sql.push(" ORDER BY sse.class_id, sp.name");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(PromotionCandidate {
+                    enrollment_id: row.try_get("enrollment_id").map_err(Self::db_error)?,
+                    profile_id: row.try_get("profile_id").map_err(Self::db_error)?,
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    class_id: row.try_get("class_id").map_err(Self::db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn school_classes(&self, school_id: &str) -> Result<Vec<ClassPlacement>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, parent_class_id, main_class_id
+            FROM classes
+            WHERE school_id = $1 AND deleted_at IS NULL AND is_active = true
+            ORDER BY name
+            "#,
+        )
+        .bind(school_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ClassPlacement {
+                    id: row.try_get("id").map_err(Self::db_error)?,
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    parent_class_id: row.try_get("parent_class_id").ok().flatten(),
+                    main_class_id: row.try_get("main_class_id").ok().flatten(),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn main_class_levels(&self) -> Result<Vec<MainClassLevel>, AppError> {
+        let rows = sqlx::query(
+            "SELECT id, trade_id, level FROM main_classes WHERE disable = false ORDER BY level",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(MainClassLevel {
+                    id: row.try_get("id").map_err(Self::db_error)?,
+                    trade_id: row.try_get("trade_id").ok().flatten(),
+                    level: row.try_get("level").ok().flatten(),
+                })
+            })
+            .collect()
+    }
+
+    /// Annual GPA of each student in the year, keyed by profile id. Falls back
+    /// to the mean of the term results when the annual result is not calculated.
+    pub async fn year_gpas(
+        &self,
+        school_id: &str,
+        education_year_id: &str,
+    ) -> Result<HashMap<String, f64>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT str.student_id,
+                   COALESCE(sar.gpa, AVG(str.gpa))::DOUBLE PRECISION AS gpa
+            FROM student_term_results str
+            LEFT JOIN student_annual_results sar
+              ON sar.student_id = str.student_id AND sar.education_year_id = str.education_year_id
+            WHERE str.school_id = $1 AND str.education_year_id = $2 AND str.deleted_at IS NULL
+            GROUP BY str.student_id, sar.gpa
+            "#,
+        )
+        .bind(school_id)
+        .bind(education_year_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<String, _>("student_id")
+                        .map_err(Self::db_error)?,
+                    row.try_get::<Option<f64>, _>("gpa")
+                        .map_err(Self::db_error)?
+                        .unwrap_or(0.0),
+                ))
+            })
+            .collect()
+    }
+
+    /// Per-subject averages over the year's term results, keyed by profile id.
+    pub async fn year_subject_averages(
+        &self,
+        school_id: &str,
+        education_year_id: &str,
+    ) -> Result<HashMap<String, Vec<SubjectAverage>>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT str.student_id, sr.class_subject_id, cs.main_subject_id,
+                   MIN(sr.subject_name) AS subject_name,
+                   AVG(sr.percentage)::DOUBLE PRECISION AS percentage
+            FROM student_term_results str
+            JOIN student_term_subject_results sr ON sr.result_id = str.id
+            LEFT JOIN class_subjects cs ON cs.id = sr.class_subject_id
+            WHERE str.school_id = $1 AND str.education_year_id = $2 AND str.deleted_at IS NULL
+            GROUP BY str.student_id, sr.class_subject_id, cs.main_subject_id
+            "#,
+        )
+        .bind(school_id)
+        .bind(education_year_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut averages: HashMap<String, Vec<SubjectAverage>> = HashMap::new();
+        for row in rows {
+            let student_id: String = row.try_get("student_id").map_err(Self::db_error)?;
+            averages
+                .entry(student_id)
+                .or_default()
+                .push(SubjectAverage {
+                    class_subject_id: row.try_get("class_subject_id").ok().flatten(),
+                    main_subject_id: row.try_get("main_subject_id").ok().flatten(),
+                    subject_name: row.try_get("subject_name").unwrap_or_default(),
+                    percentage: row
+                        .try_get::<Option<f64>, _>("percentage")
+                        .ok()
+                        .flatten()
+                        .unwrap_or(0.0),
+                });
+        }
+        Ok(averages)
+    }
+
+    /// Share of days attended (present or late) between the dates, keyed by
+    /// profile id. Per-period rows are ignored so each day counts once.
+    pub async fn attendance_rates(
+        &self,
+        school_id: &str,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<HashMap<String, f64>, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT student_id,
+                   count(*) FILTER (WHERE lower(status) IN ('present', 'late')) AS present,
+                   count(*) AS total
+            FROM attendance
+            WHERE deleted_at IS NULL AND period_id IS NULL AND school_id = "#,
+        );
+        sql.push_bind(school_id);
+        if let Some(from) = from {
+            sql.push(" AND date >= ").push_bind(from);
+        }
+        if let Some(to) = to {
+            sql.push(" AND date <= ").push_bind(to);
+        }
+        sql.push(" GROUP BY student_id");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        let mut rates = HashMap::new();
+        for row in rows {
+            let student_id: String = row.try_get("student_id").map_err(Self::db_error)?;
+            let present: i64 = row.try_get("present").map_err(Self::db_error)?;
+            let total: i64 = row.try_get("total").map_err(Self::db_error)?;
+            if total > 0 {
+                rates.insert(student_id, present as f64 / total as f64 * 100.0);
+            }
+        }
+        Ok(rates)
+    }
+
+    // ========== BATCHES ==========
+
+    fn batch_select_sql() -> &'static str {
+        r#"
+        SELECT id, school_id, rule_id, education_year_id, to_education_year_id, from_class_id,
+               to_class_id, status, created_by, executed_by, executed_at, created_at, updated_at
+        FROM promotion_batches
+        WHERE true
+        "#
+    }
+
+    fn batch_from_row(row: &PgRow) -> Result<PromotionBatch, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        Ok(PromotionBatch {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok(), "school_id")?,
+            education_year_id: Self::parse_oid_opt(
+                row.try_get("education_year_id").ok(),
+                "education_year_id",
+            )?,
+            to_education_year_id: Self::parse_oid_opt(
+                row.try_get("to_education_year_id").ok().flatten(),
+                "to_education_year_id",
+            )?,
+            rule_id: Self::parse_oid_opt(row.try_get("rule_id").ok().flatten(), "rule_id")?,
+            from_class_id: Self::parse_oid_opt(
+                row.try_get("from_class_id").ok().flatten(),
+                "from_class_id",
+            )?,
+            to_class_id: Self::parse_oid_opt(
+                row.try_get("to_class_id").ok().flatten(),
+                "to_class_id",
+            )?,
+            status: Self::enum_from_string(row.try_get("status").ok()).unwrap_or_default(),
+            promotion_results: Vec::new(),
+            created_by: Self::parse_oid_opt(
+                row.try_get("created_by").ok().flatten(),
+                "created_by",
+            )?,
+            executed_by: Self::parse_oid_opt(
+                row.try_get("executed_by").ok().flatten(),
+                "executed_by",
+            )?,
+            executed_at: row.try_get("executed_at").ok().flatten(),
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    fn result_from_row(row: PgRow) -> Result<PromotionResult, AppError> {
+        let enrollment_id: String = row.try_get("enrollment_id").map_err(Self::db_error)?;
+        Ok(PromotionResult {
+            student_id: Some(Self::parse_oid(&enrollment_id, "student_id")?),
+            student_name: row.try_get("student_name").map_err(Self::db_error)?,
+            from_class_id: Self::parse_oid_opt(
+                row.try_get("from_class_id").ok().flatten(),
+                "from_class_id",
+            )?,
+            current_gpa: row.try_get("current_gpa").map_err(Self::db_error)?,
+            attendance_percentage: row.try_get("attendance_percentage").ok().flatten(),
+            failed_subjects: row.try_get("failed_subjects").unwrap_or_default(),
+            promotion_status: Self::enum_from_string(row.try_get("promotion_status").ok())
+                .unwrap_or(PromotionStatus::Pending),
+            reason: row.try_get("reason").map_err(Self::db_error)?,
+            promoted_to_class_id: Self::parse_oid_opt(
+                row.try_get("promoted_to_class_id").ok().flatten(),
+                "promoted_to_class_id",
+            )?,
+            is_overridden: row.try_get("is_overridden").unwrap_or(false),
+        })
+    }
+
+    async fn batch_results(&self, batch_id: &str) -> Result<Vec<PromotionResult>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT enrollment_id, student_name, from_class_id, current_gpa,
+                   attendance_percentage, failed_subjects, promotion_status, reason,
+                   promoted_to_class_id, is_overridden
+            FROM promotion_batch_results
+            WHERE batch_id = $1
+            ORDER BY from_class_id, student_name
+            "#,
+        )
+        .bind(batch_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::result_from_row).collect()
+    }
+
+    pub async fn insert_batch(&self, batch: &PromotionBatch) -> Result<PromotionBatch, AppError> {
+        let id = batch.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO promotion_batches (
+              id, school_id, rule_id, education_year_id, to_education_year_id,
+              from_class_id, to_class_id, status, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&id)
+        .bind(batch.school_id.map(|id| id.to_hex()))
+        .bind(batch.rule_id.map(|id| id.to_hex()))
+        .bind(batch.education_year_id.map(|id| id.to_hex()))
+        .bind(batch.to_education_year_id.map(|id| id.to_hex()))
+        .bind(batch.from_class_id.map(|id| id.to_hex()))
+        .bind(batch.to_class_id.map(|id| id.to_hex()))
+        .bind(Self::enum_to_string(&batch.status))
+        .bind(batch.created_by.map(|id| id.to_hex()))
+        .execute(&mut *tx)
+        .await
+        .map_err(|error| match &error {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                AppError::conflict("An open promotion proposal already exists for this year")
+            }
+            _ => Self::db_error(error),
+        })?;
+
+        for result in &batch.promotion_results {
+            sqlx::query(
+                r#"
+                INSERT INTO promotion_batch_results (
+                  id, batch_id, enrollment_id, student_name, from_class_id, current_gpa,
+                  attendance_percentage, failed_subjects, promotion_status, reason,
+                  promoted_to_class_id
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(&id)
+            .bind(result.student_id.map(|id| id.to_hex()))
+            .bind(&result.student_name)
+            .bind(result.from_class_id.map(|id| id.to_hex()))
+            .bind(result.current_gpa)
+            .bind(result.attendance_percentage)
+            .bind(&result.failed_subjects)
+            .bind(Self::enum_to_string(&result.promotion_status))
+            .bind(&result.reason)
+            .bind(result.promoted_to_class_id.map(|id| id.to_hex()))
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        tx.commit().await.map_err(Self::db_error)?;
+        self.find_batch(&IdType::String(id)).await
+    }
+
+    pub async fn find_batch(&self, id: &IdType) -> Result<PromotionBatch, AppError> {
+        let id = Self::id_to_string(id)?;
+        let row = sqlx::query(&format!("{} AND id = $1", Self::batch_select_sql()))
+            .bind(&id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?
+            .ok_or(AppError::not_found("Promotion proposal not found"))?;
+
+        let mut batch = Self::batch_from_row(&row)?;
+        batch.promotion_results = self.batch_results(&id).await?;
+        Ok(batch)
+    }
+
+    pub async fn list_batches(
+        &self,
+        school_id: &str,
+        query: &PromotionBatchQuery,
+    ) -> Result<Vec<PromotionBatch>, AppError> {
+        let mut sql = QueryBuilder::<Postgres>::new(Self::batch_select_sql());
+        sql.push(" AND school_id = ").push_bind(school_id);
+        if let Some(education_year_id) = &query.education_year_id {
+            sql.push(" AND education_year_id = ")
+                .push_bind(education_year_id);
+        }
+        if let Some(status) = query.status.as_ref().and_then(Self::enum_to_string) {
+            sql.push(" AND status = ").push_bind(status);
+        }
+        sql.push(" ORDER BY created_at DESC");
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        let mut batches = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut batch = Self::batch_from_row(&row)?;
+            if let Some(id) = batch.id {
+                batch.promotion_results = self.batch_results(&id.to_hex()).await?;
+            }
+            batches.push(batch);
+        }
+        Ok(batches)
+    }
+
+    pub async fn update_result(
+        &self,
+        batch_id: &str,
+        enrollment_id: &str,
+        status: PromotionStatus,
+        promoted_to_class_id: Option<&str>,
+        reason: &str,
+    ) -> Result<bool, AppError> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE promotion_batch_results
+            SET promotion_status = $3, promoted_to_class_id = $4, reason = $5, is_overridden = true
+            WHERE batch_id = $1 AND enrollment_id = $2
+            "#,
+        )
+        .bind(batch_id)
+        .bind(enrollment_id)
+        .bind(Self::enum_to_string(&status))
+        .bind(promoted_to_class_id)
+        .bind(reason)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(updated.rows_affected() > 0)
+    }
+
+    pub async fn cancel_batch(&self, batch_id: &str) -> Result<bool, AppError> {
+        let updated = sqlx::query(
+            "UPDATE promotion_batches SET status = 'Cancelled' WHERE id = $1 AND status = 'Proposed'",
+        )
+        .bind(batch_id)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(updated.rows_affected() > 0)
+    }
+
+    /// Apply an approved proposal in one transaction: promoted students move
+    /// to their new class, graduates leave the school, repeaters stay. A
+    /// student whose class changed since the proposal is left alone. Returns
+    /// the number of enrollments changed.
+    pub async fn apply_batch(
+        &self,
+        batch: &PromotionBatch,
+        executed_by: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let batch_id = batch
+            .id
+            .map(|id| id.to_hex())
+            .ok_or(AppError::bad_request("Promotion proposal id is required"))?;
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+
+        let claimed = sqlx::query(
+            r#"
+            UPDATE promotion_batches
+            SET status = 'Approved', executed_by = $2, executed_at = now()
+            WHERE id = $1 AND status = 'Proposed'
+            "#,
+        )
+        .bind(&batch_id)
+        .bind(executed_by)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        if claimed.rows_affected() == 0 {
+            return Err(AppError::conflict("Promotion proposal is no longer open"));
+        }
+
+        let mut changed = 0;
+        for result in &batch.promotion_results {
+            let (Some(enrollment_id), Some(from_class_id)) =
+                (result.student_id, result.from_class_id)
+            else {
+                continue;
+            };
+
+            let outcome = match result.promotion_status {
+                PromotionStatus::Promoted => {
+                    let Some(to_class_id) = result.promoted_to_class_id else {
+                        continue;
+                    };
+                    sqlx::query(
+                        r#"
+                        UPDATE student_school_enrollments
+                        SET class_id = $3, subclass_id = NULL
+                        WHERE id = $1 AND class_id = $2 AND deleted_at IS NULL
+                        "#,
+                    )
+                    .bind(enrollment_id.to_hex())
+                    .bind(from_class_id.to_hex())
+                    .bind(to_class_id.to_hex())
+                    .execute(&mut *tx)
+                    .await
+                }
+                PromotionStatus::Graduated => {
+                    sqlx::query(
+                        r#"
+                        UPDATE student_school_enrollments
+                        SET status = 'Graduated', is_active = false, ended_at = now()
+                        WHERE id = $1 AND class_id = $2 AND deleted_at IS NULL
+                        "#,
+                    )
+                    .bind(enrollment_id.to_hex())
+                    .bind(from_class_id.to_hex())
+                    .execute(&mut *tx)
+                    .await
+                }
+                PromotionStatus::Repeated | PromotionStatus::Pending => continue,
+            };
+            changed += outcome.map_err(Self::db_error)?.rows_affected();
+        }
+
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(changed)
+    }
+}
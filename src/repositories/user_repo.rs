@@ -1,6 +1,7 @@
 use crate::domain::{
     common_details::{
-        Address, Age, Image, Language, SocialMedia, StudyStyle, SubjectCategory, UserRole,
+        Address, Age, DailyAvailability, Image, Language, SocialMedia, StudyStyle,
+        SubjectCategory, TimeRange, UserRole,
     },
     user::{PaginatedUsers, UpdateUserDto, User, UserStats},
 };
@@ -8,7 +9,7 @@ use crate::errors::AppError;
 use crate::models::id_model::IdType;
 use crate::services::user_service::normalize_user_ids;
 use crate::utils::object_id::ObjectId;
-use chrono::{Duration, Utc, Weekday};
+use chrono::{Duration, NaiveTime, Utc, Weekday};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
@@ -28,9 +29,7 @@ impl UserRepo {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -42,8 +41,8 @@ impl UserRepo {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -189,6 +188,40 @@ impl UserRepo {
         Ok((!items.is_empty()).then_some(items))
     }
 
+    async fn fetch_availability(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<Vec<DailyAvailability>>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT day, starts_at, ends_at
+            FROM user_availability
+            WHERE user_id = $1
+            ORDER BY position ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let items = rows
+            .into_iter()
+            .filter_map(|row| {
+                let day = row.try_get::<String, _>("day").ok()?.parse::<Weekday>().ok()?;
+                Some(DailyAvailability {
+                    day,
+                    time_range: TimeRange {
+                        start: row.try_get::<NaiveTime, _>("starts_at").ok()?,
+                        end: row.try_get::<NaiveTime, _>("ends_at").ok()?,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok((!items.is_empty()).then_some(items))
+    }
+
     async fn fetch_profile_values(
         &self,
         user_id: &str,
@@ -316,7 +349,7 @@ impl UserRepo {
                 row.try_get("preferred_age_group").ok().flatten(),
             ),
             professional_goals: Self::values_as_enums(&values, "professional_goals"),
-            availability_schedule: None,
+            availability_schedule: self.fetch_availability(&id).await?,
             department: Self::enum_from_string(row.try_get("department").ok().flatten()),
             job_title: Self::enum_from_string(row.try_get("job_title").ok().flatten()),
             teaching_style: Self::values_as_enums(&values, "teaching_style"),
@@ -381,12 +414,10 @@ impl UserRepo {
     }
 
     pub async fn find_one(&self, id: Option<&IdType>) -> Result<User, AppError> {
-        let id = id.ok_or_else(|| AppError {
-            message: "User ID is required".into(),
-        })?;
-        self.find_by_id(id).await?.ok_or(AppError {
-            message: "User not found".into(),
-        })
+        let id = id.ok_or_else(|| AppError::bad_request("User ID is required"))?;
+        self.find_by_id(id)
+            .await?
+            .ok_or(AppError::not_found("User not found"))
     }
 
     async fn sync_background_images(
@@ -459,6 +490,48 @@ impl UserRepo {
         Ok(())
     }
 
+    async fn sync_availability(
+        &self,
+        user_id: &str,
+        availability: &[DailyAvailability],
+    ) -> Result<(), AppError> {
+        if let Some(invalid) = availability
+            .iter()
+            .find(|slot| slot.time_range.end <= slot.time_range.start)
+        {
+            return Err(AppError::bad_request(format!(
+                "Availability on {} must end after it starts",
+                invalid.day
+            )));
+        }
+
+        sqlx::query("DELETE FROM user_availability WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+
+        for (position, slot) in availability.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO user_availability (id, user_id, day, starts_at, ends_at, position)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(user_id)
+            .bind(slot.day.to_string())
+            .bind(slot.time_range.start)
+            .bind(slot.time_range.end)
+            .bind(position as i32)
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        Ok(())
+    }
+
     async fn replace_profile_values(
         &self,
         user_id: &str,
@@ -539,6 +612,9 @@ impl UserRepo {
             self.replace_profile_values(user_id, "teaching_level", &ids)
                 .await?;
         }
+        if let Some(availability) = &user.availability_schedule {
+            self.sync_availability(user_id, availability).await?;
+        }
 
         Ok(())
     }
@@ -682,9 +758,7 @@ impl UserRepo {
 
         self.find_by_id(&IdType::from_string(id))
             .await?
-            .ok_or(AppError {
-                message: "User not found after insert".into(),
-            })
+            .ok_or(AppError::not_found("User not found after insert"))
     }
 
     pub async fn get_all_users(
@@ -873,18 +947,15 @@ impl UserRepo {
             || update_dto.special_skills.is_some()
             || update_dto.favorite_subjects_category.is_some()
             || update_dto.preferred_study_styles.is_some()
-            || update_dto.teaching_level.is_some();
+            || update_dto.teaching_level.is_some()
+            || update_dto.availability_schedule.is_some();
 
         if result.rows_affected() == 0 {
-            return Err(AppError {
-                message: "User not found".into(),
-            });
+            return Err(AppError::not_found("User not found"));
         }
 
         if !touched_scalar && !has_child_updates {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         if let Some(images) = &update_dto.background_images {
@@ -926,12 +997,13 @@ impl UserRepo {
             self.replace_profile_values(id, "teaching_level", &ids)
                 .await?;
         }
+        if let Some(availability) = &update_dto.availability_schedule {
+            self.sync_availability(id, availability).await?;
+        }
 
         self.find_by_id(&IdType::from_string(id))
             .await?
-            .ok_or(AppError {
-                message: "User not found after update".into(),
-            })
+            .ok_or(AppError::not_found("User not found after update"))
     }
 
     pub async fn update(&self, id: &IdType, update_dto: &UpdateUserDto) -> Result<User, AppError> {
@@ -961,9 +1033,7 @@ impl UserRepo {
         let _ = deleted_by;
         self.find_by_id(&IdType::from_string(id))
             .await?
-            .ok_or(AppError {
-                message: "User not found after soft delete".into(),
-            })
+            .ok_or(AppError::not_found("User not found after soft delete"))
     }
 
     pub async fn restore(&self, id: &IdType) -> Result<User, AppError> {
@@ -978,9 +1048,7 @@ impl UserRepo {
 
         self.find_by_id(&IdType::from_string(id))
             .await?
-            .ok_or(AppError {
-                message: "User not found after restore".into(),
-            })
+            .ok_or(AppError::not_found("User not found after restore"))
     }
 
     pub async fn get_user_stats(&self) -> Result<UserStats, AppError> {
@@ -1032,9 +1100,7 @@ impl UserRepo {
         let user = self
             .find_by_id(&IdType::from_string(&user_id))
             .await?
-            .ok_or(AppError {
-                message: "User not found".into(),
-            })?;
+            .ok_or(AppError::not_found("User not found"))?;
         let member_type = user
             .role
             .as_ref()
@@ -1066,9 +1132,7 @@ impl UserRepo {
 
         self.find_by_id(&IdType::from_string(user_id))
             .await?
-            .ok_or(AppError {
-                message: "User not found after school update".into(),
-            })
+            .ok_or(AppError::not_found("User not found after school update"))
     }
 
     pub async fn remove_school_from_user(
@@ -1107,8 +1171,6 @@ impl UserRepo {
 
         self.find_by_id(&IdType::from_string(user_id))
             .await?
-            .ok_or(AppError {
-                message: "User not found after school removal".into(),
-            })
+            .ok_or(AppError::not_found("User not found after school removal"))
     }
 }
@@ -0,0 +1,321 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+
+use crate::{
+    domain::account_token::AccountTokenPurpose,
+    errors::{AppError, ErrorCode},
+    repositories::user_repo::UserRepo,
+    services::{
+        auth_session_service::AuthSessionService,
+        mail_transport::{MailMessage, MailTransport},
+    },
+    utils::{email::is_valid_email, hash::hash_password, object_id::ObjectId},
+};
+
+const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+const MIN_PASSWORD_LENGTH: usize = 8;
+const RESET_WINDOW_MINUTES: i32 = 15;
+const MAX_RESETS_PER_EMAIL: i64 = 3;
+const MAX_RESETS_PER_IP: i64 = 10;
+
+/// Password-reset and email-verification links. Tokens are random, stored
+/// only as hashes, expire, and are redeemed at most once.
+pub struct AccountTokenService {
+    pub pool: PgPool,
+    mailer: Arc<dyn MailTransport>,
+}
+
+impl AccountTokenService {
+    pub fn new(pool: &PgPool, mailer: Arc<dyn MailTransport>) -> Self {
+        Self {
+            pool: pool.clone(),
+            mailer,
+        }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn hash_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    fn ttl(purpose: AccountTokenPurpose) -> Duration {
+        match purpose {
+            AccountTokenPurpose::PasswordReset => Duration::minutes(PASSWORD_RESET_TTL_MINUTES),
+            AccountTokenPurpose::EmailVerification => Duration::hours(EMAIL_VERIFICATION_TTL_HOURS),
+        }
+    }
+
+    /// Link into the web app when `PUBLIC_APP_URL` is set, else the bare token
+    fn action_link(path: &str, token: &str) -> String {
+        match std::env::var("PUBLIC_APP_URL") {
+            Ok(url) if !url.trim().is_empty() => {
+                format!("{}/{}?token={}", url.trim_end_matches('/'), path, token)
+            }
+            _ => token.to_string(),
+        }
+    }
+
+    /// Issue a token, retiring any unused one of the same purpose
+    async fn issue(
+        &self,
+        user_id: &str,
+        email: &str,
+        purpose: AccountTokenPurpose,
+    ) -> Result<String, AppError> {
+        let token = hex::encode(rand::random::<[u8; 32]>());
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        sqlx::query(
+            "UPDATE account_tokens SET used_at = now() \
+             WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(purpose.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        sqlx::query(
+            "INSERT INTO account_tokens (id, user_id, purpose, token_hash, email, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(ObjectId::new().to_hex())
+        .bind(user_id)
+        .bind(purpose.as_str())
+        .bind(Self::hash_token(&token))
+        .bind(email)
+        .bind(Utc::now() + Self::ttl(purpose))
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
+
+        Ok(token)
+    }
+
+    /// Redeem a live token, returning the user and address it was sent to
+    async fn consume(
+        tx: &mut Transaction<'static, Postgres>,
+        token: &str,
+        purpose: AccountTokenPurpose,
+    ) -> Result<(String, String), AppError> {
+        let row = sqlx::query(
+            "UPDATE account_tokens SET used_at = now() \
+             WHERE token_hash = $1 AND purpose = $2 \
+               AND used_at IS NULL AND expires_at > now() \
+             RETURNING user_id, email",
+        )
+        .bind(Self::hash_token(token))
+        .bind(purpose.as_str())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| AppError::bad_request("Link is invalid or has expired"))?;
+
+        Ok((
+            row.try_get("user_id").map_err(Self::db_error)?,
+            row.try_get("email").map_err(Self::db_error)?,
+        ))
+    }
+
+    /// Record the request and refuse it once the address or the client has
+    /// asked too often in the window. Done for every address, known or not.
+    async fn throttle_password_reset(
+        &self,
+        email: &str,
+        ip_address: Option<&str>,
+    ) -> Result<(), AppError> {
+        let row = sqlx::query(
+            "WITH recorded AS ( \
+               INSERT INTO password_reset_requests (id, email, ip_address) \
+               VALUES ($1, $2, $3) \
+             ) \
+             SELECT \
+               (SELECT count(*) FROM password_reset_requests \
+                 WHERE email = $2 AND created_at > now() - make_interval(mins => $4)) AS by_email, \
+               (SELECT count(*) FROM password_reset_requests \
+                 WHERE ip_address = $3 AND created_at > now() - make_interval(mins => $4)) AS by_ip",
+        )
+        .bind(ObjectId::new().to_hex())
+        .bind(email)
+        .bind(ip_address)
+        .bind(RESET_WINDOW_MINUTES)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        // The statement does not see its own insert, so these are the
+        // requests made before this one
+        let by_email: i64 = row.try_get("by_email").map_err(Self::db_error)?;
+        let by_ip: i64 = row.try_get("by_ip").map_err(Self::db_error)?;
+        if by_email >= MAX_RESETS_PER_EMAIL || by_ip >= MAX_RESETS_PER_IP {
+            return Err(AppError::new(
+                ErrorCode::TooManyRequests,
+                "Too many password reset requests, try again later",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Mail a reset link if the address belongs to an account. Succeeds
+    /// either way so the endpoint does not reveal which addresses exist:
+    /// the lookup and the mail happen after the response, so it takes the
+    /// same time too.
+    pub async fn request_password_reset(
+        &self,
+        email: &str,
+        ip_address: Option<&str>,
+    ) -> Result<(), AppError> {
+        let email = email.trim().to_lowercase();
+        is_valid_email(&email).map_err(AppError::validation)?;
+        self.throttle_password_reset(&email, ip_address).await?;
+
+        let service = Self::new(&self.pool, self.mailer.clone());
+        actix_rt::spawn(async move {
+            if let Err(err) = service.send_password_reset(&email).await {
+                log::error!("Failed to send password reset mail: {}", err.message);
+            }
+        });
+        Ok(())
+    }
+
+    async fn send_password_reset(&self, email: &str) -> Result<(), AppError> {
+        sqlx::query(
+            "DELETE FROM password_reset_requests WHERE created_at < now() - interval '1 day'",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let repo = UserRepo::new(&self.pool);
+        let Some(user) = repo.find_by_email(email).await? else {
+            return Ok(());
+        };
+        let Some(user_id) = user.id.map(|id| id.to_hex()) else {
+            return Ok(());
+        };
+
+        let token = self
+            .issue(&user_id, &user.email, AccountTokenPurpose::PasswordReset)
+            .await?;
+        let message = MailMessage {
+            to: user.email.clone(),
+            subject: "Reset your Space Together password".to_string(),
+            body: format!(
+                "Hi {},\n\nUse this link to choose a new password. It expires in {} minutes and works once:\n\n{}\n\nIf you did not ask for this, you can ignore this email.",
+                user.name,
+                PASSWORD_RESET_TTL_MINUTES,
+                Self::action_link("auth/reset-password", &token)
+            ),
+        };
+        self.mailer.send(&message).await
+    }
+
+    /// Set a new password with a reset token and sign out every session
+    pub async fn reset_password(&self, token: &str, password: &str) -> Result<(), AppError> {
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AppError::field(
+                "password",
+                format!(
+                    "Password must be at least {} characters",
+                    MIN_PASSWORD_LENGTH
+                ),
+            ));
+        }
+
+        let password = password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to hash password: {}", e)))?;
+
+        // The token is spent only if the password change and the sign-out
+        // land with it
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let (user_id, email) =
+            Self::consume(&mut tx, token, AccountTokenPurpose::PasswordReset).await?;
+
+        // The link reached the inbox, which proves the address as well
+        sqlx::query(
+            "UPDATE users SET password_hash = $1, updated_at = now(), \
+               email_verified_at = CASE WHEN email = $3 \
+                 THEN coalesce(email_verified_at, now()) ELSE email_verified_at END \
+             WHERE id = $2",
+        )
+        .bind(&password_hash)
+        .bind(&user_id)
+        .bind(&email)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        AuthSessionService::revoke_all_in(&mut tx, &user_id, "password_reset").await?;
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Mail a verification link for the account's current address
+    pub async fn send_verification(&self, user_id: &str) -> Result<(), AppError> {
+        let row = sqlx::query(
+            "SELECT name, email, email_verified_at IS NOT NULL AS verified \
+             FROM users WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| AppError::not_found("User not found"))?;
+
+        let verified: bool = row.try_get("verified").map_err(Self::db_error)?;
+        if verified {
+            return Err(AppError::conflict("Email address is already verified"));
+        }
+        let name: String = row.try_get("name").map_err(Self::db_error)?;
+        let email: String = row.try_get("email").map_err(Self::db_error)?;
+
+        let token = self
+            .issue(user_id, &email, AccountTokenPurpose::EmailVerification)
+            .await?;
+        self.mailer
+            .send(&MailMessage {
+                to: email,
+                subject: "Verify your Space Together email".to_string(),
+                body: format!(
+                    "Hi {},\n\nConfirm this is your email address with the link below. It expires in {} hours:\n\n{}",
+                    name,
+                    EMAIL_VERIFICATION_TTL_HOURS,
+                    Self::action_link("auth/verify-email", &token)
+                ),
+            })
+            .await
+    }
+
+    /// Mark the address verified, as long as it is still the account's address
+    pub async fn verify_email(&self, token: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let (user_id, email) =
+            Self::consume(&mut tx, token, AccountTokenPurpose::EmailVerification).await?;
+
+        let updated = sqlx::query(
+            "UPDATE users SET email_verified_at = coalesce(email_verified_at, now()) \
+             WHERE id = $1 AND email = $2 AND deleted_at IS NULL",
+        )
+        .bind(&user_id)
+        .bind(&email)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        if updated.rows_affected() == 0 {
+            return Err(AppError::bad_request(
+                "The account's email address changed after this link was sent",
+            ));
+        }
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(())
+    }
+}
@@ -1,15 +1,39 @@
+use std::collections::{BTreeMap, HashMap};
+
 use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, QueryBuilder, Row};
 
 use crate::{
     domain::analytics::{
-        AttendanceRate, EnrollmentTrend, FeeCollectionSummary, PassFailDistribution,
+        AttendanceRate, EnrollmentTrend, ExamStatistics, ExamStatisticsQuery, FeeCollectionSummary,
+        ParallelSubject, PassFailDistribution, SubjectStatistics, TeacherComparison,
         TeacherWorkload,
     },
     errors::AppError,
     models::id_model::IdType,
+    services::ranking_service::RankingService,
+    utils::{object_id::parse_object_id_value, statistics::distribution},
 };
 
+/// Default histogram buckets and flagging drop for exam statistics
+const DEFAULT_BINS: usize = 10;
+const MAX_BINS: usize = 50;
+const DEFAULT_DROP_THRESHOLD: f64 = 10.0;
+
+/// A class subject with marks on the exam, and what it is compared by
+struct SubjectMarks {
+    class_subject_id: String,
+    subject_name: String,
+    /// main subject id, or the lowercased name, shared by parallel subjects
+    subject_key: String,
+    class_id: Option<String>,
+    class_name: Option<String>,
+    parent_class_id: Option<String>,
+    parent_class_name: Option<String>,
+    teacher_name: Option<String>,
+    percentages: Vec<f64>,
+}
+
 pub struct AnalyticsService {
     pub pool: PgPool,
 }
@@ -20,9 +44,7 @@ impl AnalyticsService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn id_to_string(id: &IdType) -> Result<String, AppError> {
@@ -78,12 +100,16 @@ impl AnalyticsService {
         let mut query = QueryBuilder::<Postgres>::new(
             r#"
             SELECT count(*)::BIGINT AS total_records,
-                   count(*) FILTER (WHERE lower(status) = 'present')::BIGINT AS present_count
+                   count(*) FILTER (WHERE lower(status) IN ('present', 'late'))::BIGINT AS present_count
             FROM attendance
             WHERE school_id =
             "#,
         );
-        query.push_bind(school_id).push(" AND deleted_at IS NULL");
+        // Daily rows only, and late arrivals count as attended, matching
+        // the per-student summaries
+        query
+            .push_bind(school_id)
+            .push(" AND period_id IS NULL AND deleted_at IS NULL");
 
         if let Some(from) = from {
             query.push(" AND date >= ").push_bind(from.date_naive());
@@ -161,8 +187,15 @@ impl AnalyticsService {
         let school_id = Self::id_to_string(school_id)?;
         let row = sqlx::query(
             r#"
-            SELECT COALESCE(SUM(amount), 0)::DOUBLE PRECISION AS total_expected,
-                   COALESCE(SUM(amount) FILTER (WHERE lower(status) = 'paid'), 0)::DOUBLE PRECISION AS total_collected
+            SELECT COALESCE(SUM(amount) FILTER (
+                     WHERE record_type IN ('invoice', 'adjustment', 'fee')
+                   ), 0)::DOUBLE PRECISION AS total_expected,
+                   COALESCE(SUM(CASE
+                     WHEN record_type = 'payment' THEN amount
+                     WHEN record_type = 'refund' THEN -amount
+                     WHEN record_type = 'fee' AND lower(status) = 'paid' THEN amount
+                     ELSE 0
+                   END), 0)::DOUBLE PRECISION AS total_collected
             FROM finance_records
             WHERE school_id = $1 AND deleted_at IS NULL
             "#,
@@ -236,4 +269,199 @@ impl AnalyticsService {
             })
             .collect()
     }
+
+    /// Mark distributions per class subject for one exam, the same subject
+    /// compared across parallel subclasses, and each student's change since
+    /// their previous exam. A student's mark in a subject is their total
+    /// over all assessment categories of the exam, as a percentage.
+    pub async fn get_exam_statistics(
+        &self,
+        school_id: &IdType,
+        exam_id: &IdType,
+        query: &ExamStatisticsQuery,
+    ) -> Result<ExamStatistics, AppError> {
+        let school_id = Self::id_to_string(school_id)?;
+        let exam_oid = IdType::to_object_id(exam_id)?;
+        let exam_id = exam_oid.to_hex();
+        let bins = query.bins.unwrap_or(DEFAULT_BINS).clamp(1, MAX_BINS);
+        let drop_threshold = query.drop_threshold.unwrap_or(DEFAULT_DROP_THRESHOLD);
+        let compare_exam_id = query
+            .compare_exam_id
+            .as_deref()
+            .map(parse_object_id_value)
+            .transpose()?;
+
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM exams WHERE id = $1 AND school_id = $2 AND deleted_at IS NULL)",
+        )
+        .bind(&exam_id)
+        .bind(&school_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if !exists {
+            return Err(AppError::not_found("Exam not found in this school"));
+        }
+
+        // The class and its subclasses, when limited to a class
+        let class_ids: Vec<String> = match query.class_id.as_deref() {
+            Some(class_id) => {
+                let class_id = parse_object_id_value(class_id)?.to_hex();
+                sqlx::query_scalar(
+                    r#"SELECT id FROM classes
+                       WHERE school_id = $2 AND deleted_at IS NULL
+                         AND (id = $1 OR parent_class_id = $1)"#,
+                )
+                .bind(&class_id)
+                .bind(&school_id)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(Self::db_error)?
+            }
+            None => Vec::new(),
+        };
+        if query.class_id.is_some() && class_ids.is_empty() {
+            return Err(AppError::not_found("Class not found in this school"));
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT cs.id AS class_subject_id, cs.name AS subject_name,
+                   COALESCE(cs.main_subject_id, lower(cs.name)) AS subject_key,
+                   c.id AS class_id, c.name AS class_name,
+                   parent.id AS parent_class_id, parent.name AS parent_class_name,
+                   t.name AS teacher_name,
+                   marks.percentage
+            FROM (
+              SELECT s.class_subject_id,
+                     (sum(s.score) / NULLIF(sum(s.max_score), 0) * 100)::DOUBLE PRECISION AS percentage
+              FROM scores s
+              WHERE s.exam_id = $1 AND s.school_id = $2 AND s.deleted_at IS NULL
+              GROUP BY s.class_subject_id, s.student_id
+            ) marks
+            JOIN class_subjects cs ON cs.id = marks.class_subject_id
+            LEFT JOIN classes c ON c.id = cs.class_id
+            LEFT JOIN classes parent ON parent.id = c.parent_class_id
+            LEFT JOIN teachers t
+              ON t.id = cs.teacher_id
+              OR (cs.teacher_id IS NULL AND t.user_id = cs.teacher_user_id AND t.school_id = cs.school_id)
+            WHERE marks.percentage IS NOT NULL
+              AND (cardinality($3::TEXT[]) = 0 OR cs.class_id = ANY($3))
+            ORDER BY c.name, cs.name
+            "#,
+        )
+        .bind(&exam_id)
+        .bind(&school_id)
+        .bind(&class_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut subjects: Vec<SubjectMarks> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in rows {
+            let class_subject_id: String =
+                row.try_get("class_subject_id").map_err(Self::db_error)?;
+            let percentage: f64 = row.try_get("percentage").map_err(Self::db_error)?;
+            if let Some(&i) = index.get(&class_subject_id) {
+                subjects[i].percentages.push(percentage);
+                continue;
+            }
+            index.insert(class_subject_id.clone(), subjects.len());
+            subjects.push(SubjectMarks {
+                class_subject_id,
+                subject_name: row.try_get("subject_name").map_err(Self::db_error)?,
+                subject_key: row.try_get("subject_key").map_err(Self::db_error)?,
+                class_id: row.try_get("class_id").map_err(Self::db_error)?,
+                class_name: row.try_get("class_name").map_err(Self::db_error)?,
+                parent_class_id: row.try_get("parent_class_id").map_err(Self::db_error)?,
+                parent_class_name: row.try_get("parent_class_name").map_err(Self::db_error)?,
+                teacher_name: row.try_get("teacher_name").map_err(Self::db_error)?,
+                percentages: vec![percentage],
+            });
+        }
+
+        let all: Vec<f64> = subjects
+            .iter()
+            .flat_map(|subject| subject.percentages.iter().copied())
+            .collect();
+        let subject_statistics: Vec<SubjectStatistics> = subjects
+            .iter()
+            .map(|subject| SubjectStatistics {
+                class_subject_id: subject.class_subject_id.clone(),
+                subject_name: subject.subject_name.clone(),
+                class_id: subject.class_id.clone(),
+                class_name: subject.class_name.clone(),
+                teacher_name: subject.teacher_name.clone(),
+                distribution: distribution(&subject.percentages, bins),
+            })
+            .collect();
+
+        let deltas = RankingService::new(&self.pool)
+            .get_result_deltas(
+                &exam_oid,
+                &class_ids,
+                compare_exam_id.as_ref(),
+                drop_threshold,
+            )
+            .await?;
+
+        Ok(ExamStatistics {
+            exam_id,
+            overall: distribution(&all, bins),
+            subjects: subject_statistics,
+            teacher_comparisons: Self::teacher_comparisons(&subjects),
+            drop_threshold,
+            flagged_count: deltas.iter().filter(|delta| delta.flagged).count() as i64,
+            student_deltas: deltas,
+        })
+    }
+
+    /// Groups the same subject across subclasses of one parent class, where
+    /// there are at least two to compare
+    fn teacher_comparisons(subjects: &[SubjectMarks]) -> Vec<TeacherComparison> {
+        let mut groups: BTreeMap<(String, String), Vec<&SubjectMarks>> = BTreeMap::new();
+        for subject in subjects {
+            if let Some(parent_class_id) = &subject.parent_class_id {
+                groups
+                    .entry((parent_class_id.clone(), subject.subject_key.clone()))
+                    .or_default()
+                    .push(subject);
+            }
+        }
+
+        groups
+            .into_iter()
+            .filter(|(_, group)| group.len() > 1)
+            .map(|((parent_class_id, _), group)| {
+                let all: Vec<f64> = group
+                    .iter()
+                    .flat_map(|subject| subject.percentages.iter().copied())
+                    .collect();
+                let group_mean = distribution(&all, 1).mean;
+                TeacherComparison {
+                    parent_class_id,
+                    parent_class_name: group[0].parent_class_name.clone(),
+                    subject_name: group[0].subject_name.clone(),
+                    group_mean,
+                    subjects: group
+                        .iter()
+                        .map(|subject| {
+                            let stats = distribution(&subject.percentages, 1);
+                            ParallelSubject {
+                                class_subject_id: subject.class_subject_id.clone(),
+                                class_name: subject.class_name.clone(),
+                                teacher_name: subject.teacher_name.clone(),
+                                count: stats.count,
+                                mean: stats.mean,
+                                median: stats.median,
+                                delta_from_group: ((stats.mean - group_mean) * 100.0).round()
+                                    / 100.0,
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
 }
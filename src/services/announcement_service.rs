@@ -26,9 +26,7 @@ impl AnnouncementService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -40,8 +38,8 @@ impl AnnouncementService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -363,9 +361,7 @@ impl AnnouncementService {
             .map_err(Self::db_error)?
             .map(|row| Self::announcement_from_row(&row))
             .transpose()?
-            .ok_or(AppError {
-                message: "Announcement not found".into(),
-            })
+            .ok_or(AppError::not_found("Announcement not found"))
     }
 
     pub async fn get_all(
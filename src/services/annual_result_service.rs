@@ -0,0 +1,929 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+use crate::{
+    domain::student_annual_result::{
+        AnnualSubjectResult, AnnualTermResult, CumulativeGpa, StudentAnnualResult, TermWeight,
+        YearGpa,
+    },
+    errors::AppError,
+    services::gpa_calculation_service::GpaCalculationService,
+    utils::object_id::ObjectId,
+};
+
+#[derive(Debug, Clone)]
+struct TermRow {
+    term_id: String,
+    class_id: Option<String>,
+    average_percentage: f64,
+    gpa: f64,
+}
+
+#[derive(Debug, Clone)]
+struct SubjectRow {
+    term_id: String,
+    class_subject_id: Option<String>,
+    subject_name: String,
+    percentage: f64,
+    credits: Option<i32>,
+}
+
+/// Combines the term results of an education year into one annual result.
+pub struct AnnualResultService {
+    pub pool: PgPool,
+}
+
+impl AnnualResultService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn parse_oid_opt(raw: Option<String>, field: &str) -> Result<Option<ObjectId>, AppError> {
+        raw.map(|value| Self::parse_oid(&value, field)).transpose()
+    }
+
+    fn weighted_mean(values: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+        let (sum, total_weight) = values
+            .filter(|(_, weight)| *weight > 0.0)
+            .fold((0.0, 0.0), |(sum, total), (value, weight)| {
+                (sum + value * weight, total + weight)
+            });
+        (total_weight > 0.0).then(|| sum / total_weight)
+    }
+
+    fn result_from_row(row: PgRow) -> Result<StudentAnnualResult, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        Ok(StudentAnnualResult {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid_opt(row.try_get("school_id").ok().flatten(), "school_id")?,
+            student_id: Self::parse_oid_opt(
+                row.try_get("student_id").ok().flatten(),
+                "student_id",
+            )?,
+            class_id: Self::parse_oid_opt(row.try_get("class_id").ok().flatten(), "class_id")?,
+            education_year_id: Self::parse_oid_opt(
+                row.try_get("education_year_id").ok().flatten(),
+                "education_year_id",
+            )?,
+            term_results: Vec::new(),
+            subject_results: Vec::new(),
+            average_percentage: row.try_get("average_percentage").map_err(Self::db_error)?,
+            gpa: row.try_get("gpa").map_err(Self::db_error)?,
+            cumulative_gpa: row.try_get("cumulative_gpa").ok().flatten(),
+            total_credits: row.try_get("total_credits").ok().flatten(),
+            grade: row.try_get("grade").map_err(Self::db_error)?,
+            rank_in_class: row.try_get("rank_in_class").ok().flatten(),
+            total_students: row.try_get("total_students").ok().flatten(),
+            calculated_at: row
+                .try_get::<Option<DateTime<Utc>>, _>("calculated_at")
+                .ok()
+                .flatten(),
+            is_finalized: row.try_get("is_finalized").ok().unwrap_or(false),
+        })
+    }
+
+    const RESULT_COLUMNS: &'static str = r#"
+        SELECT id, school_id, student_id, class_id, education_year_id,
+               average_percentage, gpa, cumulative_gpa, total_credits, grade,
+               rank_in_class, total_students, calculated_at, is_finalized
+        FROM student_annual_results
+    "#;
+
+    async fn hydrate(&self, result: &mut StudentAnnualResult) -> Result<(), AppError> {
+        let Some(result_id) = result.id else {
+            return Ok(());
+        };
+
+        let term_rows = sqlx::query(
+            r#"
+            SELECT term_id, weight, average_percentage, gpa, results_counted
+            FROM student_annual_term_results
+            WHERE result_id = $1
+            ORDER BY position ASC
+            "#,
+        )
+        .bind(result_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        result.term_results = term_rows
+            .into_iter()
+            .map(|row| {
+                Ok(AnnualTermResult {
+                    term_id: row.try_get("term_id").map_err(Self::db_error)?,
+                    weight: row.try_get("weight").map_err(Self::db_error)?,
+                    average_percentage: row
+                        .try_get("average_percentage")
+                        .map_err(Self::db_error)?,
+                    gpa: row.try_get("gpa").map_err(Self::db_error)?,
+                    results_counted: row.try_get("results_counted").map_err(Self::db_error)?,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let subject_rows = sqlx::query(
+            r#"
+            SELECT class_subject_id, subject_name, percentage, grade, credits
+            FROM student_annual_subject_results
+            WHERE result_id = $1
+            ORDER BY position ASC, subject_name ASC
+            "#,
+        )
+        .bind(result_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        result.subject_results = subject_rows
+            .into_iter()
+            .map(|row| {
+                Ok(AnnualSubjectResult {
+                    class_subject_id: Self::parse_oid_opt(
+                        row.try_get("class_subject_id").ok().flatten(),
+                        "class_subject_id",
+                    )?,
+                    subject_name: row.try_get("subject_name").map_err(Self::db_error)?,
+                    percentage: row.try_get("percentage").map_err(Self::db_error)?,
+                    grade: row.try_get("grade").map_err(Self::db_error)?,
+                    credits: row.try_get("credits").ok().flatten(),
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok(())
+    }
+
+    async fn ensure_year(
+        &self,
+        education_year_id: &ObjectId,
+        school_id: &ObjectId,
+    ) -> Result<(), AppError> {
+        let found: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM education_years WHERE id = $1 AND school_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(education_year_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        found.map(|_| ()).ok_or(AppError::not_found(
+            "Education year not found in this school",
+        ))
+    }
+
+    // ========== TERM WEIGHTS ==========
+
+    pub async fn get_term_weights(
+        &self,
+        education_year_id: &ObjectId,
+    ) -> Result<Vec<TermWeight>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT term_id, weight
+            FROM education_year_term_weights
+            WHERE education_year_id = $1
+            ORDER BY term_id ASC
+            "#,
+        )
+        .bind(education_year_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(TermWeight {
+                    term_id: row.try_get("term_id").map_err(Self::db_error)?,
+                    weight: row.try_get("weight").map_err(Self::db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the term weights of a year. An empty list weighs every term equally.
+    pub async fn set_term_weights(
+        &self,
+        school_id: &ObjectId,
+        education_year_id: &ObjectId,
+        weights: Vec<TermWeight>,
+    ) -> Result<Vec<TermWeight>, AppError> {
+        let mut seen = HashSet::new();
+        for weight in &weights {
+            let term_id = weight.term_id.trim();
+            if term_id.is_empty() {
+                return Err(AppError::bad_request(
+                    "term_id is required for every term weight",
+                ));
+            }
+            if !weight.weight.is_finite() || weight.weight < 0.0 {
+                return Err(AppError::bad_request(format!(
+                    "Weight for term {} must be zero or positive",
+                    term_id
+                )));
+            }
+            if !seen.insert(term_id.to_string()) {
+                return Err(AppError::bad_request(format!(
+                    "Term {} is weighted more than once",
+                    term_id
+                )));
+            }
+        }
+        if !weights.is_empty() && weights.iter().all(|weight| weight.weight == 0.0) {
+            return Err(AppError::bad_request(
+                "At least one term needs a weight above zero",
+            ));
+        }
+        self.ensure_year(education_year_id, school_id).await?;
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        sqlx::query("DELETE FROM education_year_term_weights WHERE education_year_id = $1")
+            .bind(education_year_id.to_hex())
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        for weight in &weights {
+            sqlx::query(
+                r#"
+                INSERT INTO education_year_term_weights (id, school_id, education_year_id, term_id, weight)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(school_id.to_hex())
+            .bind(education_year_id.to_hex())
+            .bind(weight.term_id.trim())
+            .bind(weight.weight)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+        tx.commit().await.map_err(Self::db_error)?;
+
+        self.get_term_weights(education_year_id).await
+    }
+
+    // ========== CALCULATION ==========
+
+    async fn term_rows(
+        &self,
+        student_id: &ObjectId,
+        education_year_id: &ObjectId,
+        school_id: &ObjectId,
+    ) -> Result<(Vec<TermRow>, Vec<SubjectRow>), AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT COALESCE(term_id, term) AS term_id, class_id,
+                   COALESCE(average_percentage, average_score, 0)::DOUBLE PRECISION AS average_percentage,
+                   COALESCE(gpa, average_score, 0)::DOUBLE PRECISION AS gpa
+            FROM student_term_results
+            WHERE student_id = $1 AND education_year_id = $2 AND school_id = $3
+              AND deleted_at IS NULL
+            ORDER BY COALESCE(calculated_at, updated_at) ASC
+            "#,
+        )
+        .bind(student_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        let terms = rows
+            .into_iter()
+            .map(|row| {
+                Ok(TermRow {
+                    term_id: row.try_get("term_id").map_err(Self::db_error)?,
+                    class_id: row.try_get("class_id").ok().flatten(),
+                    average_percentage: row
+                        .try_get("average_percentage")
+                        .map_err(Self::db_error)?,
+                    gpa: row.try_get("gpa").map_err(Self::db_error)?,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT COALESCE(str.term_id, str.term) AS term_id, sr.class_subject_id,
+                   sr.subject_name, sr.percentage, sr.credits
+            FROM student_term_results str
+            JOIN student_term_subject_results sr ON sr.result_id = str.id
+            WHERE str.student_id = $1 AND str.education_year_id = $2 AND str.school_id = $3
+              AND str.deleted_at IS NULL
+            ORDER BY sr.position ASC
+            "#,
+        )
+        .bind(student_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        let subjects = rows
+            .into_iter()
+            .map(|row| {
+                Ok(SubjectRow {
+                    term_id: row.try_get("term_id").map_err(Self::db_error)?,
+                    class_subject_id: row.try_get("class_subject_id").ok().flatten(),
+                    subject_name: row.try_get("subject_name").map_err(Self::db_error)?,
+                    percentage: row.try_get("percentage").map_err(Self::db_error)?,
+                    credits: row.try_get("credits").ok().flatten(),
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok((terms, subjects))
+    }
+
+    /// Average each term's exam results, then weigh the terms. Terms without a
+    /// configured weight count for nothing once any weight is configured.
+    fn combine_terms(terms: &[TermRow], weights: &HashMap<String, f64>) -> Vec<AnnualTermResult> {
+        let mut combined: Vec<AnnualTermResult> = Vec::new();
+        for term in terms {
+            match combined
+                .iter_mut()
+                .find(|existing| existing.term_id == term.term_id)
+            {
+                Some(existing) => {
+                    let counted = existing.results_counted as f64;
+                    existing.average_percentage = (existing.average_percentage * counted
+                        + term.average_percentage)
+                        / (counted + 1.0);
+                    existing.gpa = (existing.gpa * counted + term.gpa) / (counted + 1.0);
+                    existing.results_counted += 1;
+                }
+                None => combined.push(AnnualTermResult {
+                    term_id: term.term_id.clone(),
+                    weight: if weights.is_empty() {
+                        1.0
+                    } else {
+                        weights.get(&term.term_id).copied().unwrap_or(0.0)
+                    },
+                    average_percentage: term.average_percentage,
+                    gpa: term.gpa,
+                    results_counted: 1,
+                }),
+            }
+        }
+        combined
+    }
+
+    /// The year's average percentage and GPA, each term counting by its weight
+    fn weigh_terms(term_results: &[AnnualTermResult]) -> Result<(f64, f64), AppError> {
+        let average_percentage = Self::weighted_mean(
+            term_results
+                .iter()
+                .map(|term| (term.average_percentage, term.weight)),
+        )
+        .ok_or(AppError::bad_request(
+            "None of this student's terms carry a weight for the year",
+        ))?;
+        let gpa = Self::weighted_mean(term_results.iter().map(|term| (term.gpa, term.weight)))
+            .unwrap_or_default();
+        Ok((average_percentage, gpa))
+    }
+
+    fn combine_subjects(
+        subjects: &[SubjectRow],
+        term_results: &[AnnualTermResult],
+    ) -> Vec<AnnualSubjectResult> {
+        let term_weights: HashMap<&str, f64> = term_results
+            .iter()
+            .map(|term| (term.term_id.as_str(), term.weight))
+            .collect();
+
+        // subject key -> (name, credits, term -> percentages)
+        let mut order: Vec<String> = Vec::new();
+        let mut grouped: HashMap<String, (SubjectRow, HashMap<String, Vec<f64>>)> = HashMap::new();
+        for subject in subjects {
+            let key = subject
+                .class_subject_id
+                .clone()
+                .unwrap_or_else(|| subject.subject_name.to_lowercase());
+            let entry = grouped.entry(key.clone()).or_insert_with(|| {
+                order.push(key);
+                (subject.clone(), HashMap::new())
+            });
+            if subject.credits > entry.0.credits {
+                entry.0.credits = subject.credits;
+            }
+            entry
+                .1
+                .entry(subject.term_id.clone())
+                .or_default()
+                .push(subject.percentage);
+        }
+
+        order
+            .into_iter()
+            .filter_map(|key| {
+                let (subject, by_term) = grouped.remove(&key)?;
+                let percentage = Self::weighted_mean(by_term.iter().map(|(term_id, values)| {
+                    let mean = values.iter().sum::<f64>() / values.len() as f64;
+                    (
+                        mean,
+                        term_weights.get(term_id.as_str()).copied().unwrap_or(0.0),
+                    )
+                }))?;
+                Some(AnnualSubjectResult {
+                    class_subject_id: subject
+                        .class_subject_id
+                        .as_deref()
+                        .and_then(|id| ObjectId::parse_str(id).ok()),
+                    subject_name: subject.subject_name,
+                    percentage,
+                    grade: GpaCalculationService::determine_grade(percentage),
+                    credits: subject.credits,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn calculate_student_annual_result(
+        &self,
+        student_id: &ObjectId,
+        education_year_id: &ObjectId,
+        school_id: &ObjectId,
+    ) -> Result<StudentAnnualResult, AppError> {
+        let (terms, subjects) = self
+            .term_rows(student_id, education_year_id, school_id)
+            .await?;
+        if terms.is_empty() {
+            return Err(AppError::bad_request(
+                "No term results found for this student and year",
+            ));
+        }
+
+        let weights: HashMap<String, f64> = self
+            .get_term_weights(education_year_id)
+            .await?
+            .into_iter()
+            .map(|weight| (weight.term_id, weight.weight))
+            .collect();
+        let term_results = Self::combine_terms(&terms, &weights);
+        let (average_percentage, gpa) = Self::weigh_terms(&term_results)?;
+
+        let subject_results = Self::combine_subjects(&subjects, &term_results);
+        let total_credits: i32 = subject_results
+            .iter()
+            .filter_map(|subject| subject.credits)
+            .sum();
+        let grade = GpaCalculationService::new(&self.pool)
+            .calculate_grade_from_active_scale(school_id, education_year_id, average_percentage)
+            .await?;
+        let class_id = terms
+            .iter()
+            .rev()
+            .find_map(|term| term.class_id.as_deref())
+            .map(|id| Self::parse_oid(id, "class_id"))
+            .transpose()?;
+
+        let result = StudentAnnualResult {
+            id: None,
+            school_id: Some(*school_id),
+            student_id: Some(*student_id),
+            class_id,
+            education_year_id: Some(*education_year_id),
+            term_results,
+            subject_results,
+            average_percentage,
+            gpa,
+            cumulative_gpa: None,
+            total_credits: (total_credits > 0).then_some(total_credits),
+            grade,
+            rank_in_class: None,
+            total_students: None,
+            calculated_at: Some(Utc::now()),
+            is_finalized: false,
+        };
+
+        let result_id = self.save_result(&result).await?;
+        self.refresh_cumulative_gpa(student_id).await?;
+        self.get_result_by_id(&result_id).await
+    }
+
+    async fn save_result(&self, result: &StudentAnnualResult) -> Result<String, AppError> {
+        let student_id = result
+            .student_id
+            .ok_or_else(|| AppError::bad_request("Result has no student_id"))?;
+        let education_year_id = result
+            .education_year_id
+            .ok_or_else(|| AppError::bad_request("Result has no education_year_id"))?;
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let result_id: String = sqlx::query_scalar(
+            r#"
+            INSERT INTO student_annual_results (
+              id, school_id, student_id, class_id, education_year_id,
+              average_percentage, gpa, total_credits, grade, calculated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (student_id, education_year_id) DO UPDATE SET
+              school_id = EXCLUDED.school_id,
+              class_id = EXCLUDED.class_id,
+              average_percentage = EXCLUDED.average_percentage,
+              gpa = EXCLUDED.gpa,
+              total_credits = EXCLUDED.total_credits,
+              grade = EXCLUDED.grade,
+              calculated_at = EXCLUDED.calculated_at
+            RETURNING id
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(result.school_id.map(|id| id.to_hex()))
+        .bind(student_id.to_hex())
+        .bind(result.class_id.map(|id| id.to_hex()))
+        .bind(education_year_id.to_hex())
+        .bind(result.average_percentage)
+        .bind(result.gpa)
+        .bind(result.total_credits)
+        .bind(&result.grade)
+        .bind(result.calculated_at.unwrap_or_else(Utc::now))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query("DELETE FROM student_annual_term_results WHERE result_id = $1")
+            .bind(&result_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        sqlx::query("DELETE FROM student_annual_subject_results WHERE result_id = $1")
+            .bind(&result_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+
+        for (position, term) in result.term_results.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO student_annual_term_results (
+                  id, result_id, term_id, weight, average_percentage, gpa, results_counted, position
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(&result_id)
+            .bind(&term.term_id)
+            .bind(term.weight)
+            .bind(term.average_percentage)
+            .bind(term.gpa)
+            .bind(term.results_counted)
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        for (position, subject) in result.subject_results.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO student_annual_subject_results (
+                  id, result_id, class_subject_id, subject_name, percentage, grade, credits, position
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(&result_id)
+            .bind(subject.class_subject_id.map(|id| id.to_hex()))
+            .bind(&subject.subject_name)
+            .bind(subject.percentage)
+            .bind(&subject.grade)
+            .bind(subject.credits)
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(result_id)
+    }
+
+    /// Recompute the running cumulative GPA over all of a student's years, so a
+    /// recalculated earlier year also corrects the later ones.
+    async fn refresh_cumulative_gpa(&self, student_id: &ObjectId) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE student_annual_results sar
+            SET cumulative_gpa = running.cumulative_gpa
+            FROM (
+              SELECT sar.id,
+                     AVG(sar.gpa) OVER (
+                       ORDER BY ey.starts_on ASC NULLS LAST, ey.created_at ASC
+                     ) AS cumulative_gpa
+              FROM student_annual_results sar
+              JOIN education_years ey ON ey.id = sar.education_year_id
+              WHERE sar.student_id = $1
+            ) running
+            WHERE sar.id = running.id
+            "#,
+        )
+        .bind(student_id.to_hex())
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Rank the annual results of a class by GPA. Equal GPAs share a rank.
+    async fn rank_class(
+        &self,
+        class_id: &ObjectId,
+        education_year_id: &ObjectId,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE student_annual_results sar
+            SET rank_in_class = ranked.rank_in_class, total_students = ranked.total_students
+            FROM (
+              SELECT id,
+                     RANK() OVER (ORDER BY ROUND(gpa::NUMERIC, 3) DESC)::INTEGER AS rank_in_class,
+                     COUNT(*) OVER ()::INTEGER AS total_students
+              FROM student_annual_results
+              WHERE class_id = $1 AND education_year_id = $2
+            ) ranked
+            WHERE sar.id = ranked.id
+            "#,
+        )
+        .bind(class_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Calculate the annual result of everyone with term results in the class
+    /// for the year, then rank the class.
+    pub async fn calculate_class_annual_results(
+        &self,
+        class_id: &ObjectId,
+        education_year_id: &ObjectId,
+        school_id: &ObjectId,
+    ) -> Result<Vec<StudentAnnualResult>, AppError> {
+        self.ensure_year(education_year_id, school_id).await?;
+
+        let student_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT student_id
+            FROM student_term_results
+            WHERE class_id = $1 AND education_year_id = $2 AND school_id = $3
+              AND deleted_at IS NULL
+            "#,
+        )
+        .bind(class_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        for student_id in student_ids {
+            let student_id = Self::parse_oid(&student_id, "student_id")?;
+            if let Err(error) = self
+                .calculate_student_annual_result(&student_id, education_year_id, school_id)
+                .await
+            {
+                log::warn!(
+                    "Failed to calculate annual result for student {}: {}",
+                    student_id,
+                    error.message
+                );
+            }
+        }
+
+        self.rank_class(class_id, education_year_id).await?;
+        self.get_class_annual_results(class_id, education_year_id)
+            .await
+    }
+
+    // ========== QUERIES ==========
+
+    async fn get_result_by_id(&self, result_id: &str) -> Result<StudentAnnualResult, AppError> {
+        let row = sqlx::query(&format!("{} WHERE id = $1", Self::RESULT_COLUMNS))
+            .bind(result_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?
+            .ok_or_else(|| AppError::not_found("Annual result not found"))?;
+        let mut result = Self::result_from_row(row)?;
+        self.hydrate(&mut result).await?;
+        Ok(result)
+    }
+
+    pub async fn get_student_annual_result(
+        &self,
+        student_id: &ObjectId,
+        education_year_id: &ObjectId,
+    ) -> Result<Option<StudentAnnualResult>, AppError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE student_id = $1 AND education_year_id = $2",
+            Self::RESULT_COLUMNS
+        ))
+        .bind(student_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        match row {
+            Some(row) => {
+                let mut result = Self::result_from_row(row)?;
+                self.hydrate(&mut result).await?;
+                Ok(Some(result))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_class_annual_results(
+        &self,
+        class_id: &ObjectId,
+        education_year_id: &ObjectId,
+    ) -> Result<Vec<StudentAnnualResult>, AppError> {
+        let rows = sqlx::query(&format!(
+            r#"{} WHERE class_id = $1 AND education_year_id = $2
+            ORDER BY rank_in_class ASC NULLS LAST, gpa DESC, average_percentage DESC"#,
+            Self::RESULT_COLUMNS
+        ))
+        .bind(class_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut result = Self::result_from_row(row)?;
+            self.hydrate(&mut result).await?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Annual GPA per education year, oldest first, with the mean over all years.
+    pub async fn get_cumulative_gpa(
+        &self,
+        student_id: &ObjectId,
+    ) -> Result<CumulativeGpa, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sar.education_year_id, ey.name AS label, sar.average_percentage, sar.gpa
+            FROM student_annual_results sar
+            JOIN education_years ey ON ey.id = sar.education_year_id
+            WHERE sar.student_id = $1
+            ORDER BY ey.starts_on ASC NULLS LAST, ey.created_at ASC
+            "#,
+        )
+        .bind(student_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let years = rows
+            .into_iter()
+            .map(|row| {
+                Ok(YearGpa {
+                    education_year_id: Self::parse_oid_opt(
+                        row.try_get("education_year_id").ok().flatten(),
+                        "education_year_id",
+                    )?,
+                    label: row.try_get("label").map_err(Self::db_error)?,
+                    average_percentage: row
+                        .try_get("average_percentage")
+                        .map_err(Self::db_error)?,
+                    gpa: row.try_get("gpa").map_err(Self::db_error)?,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        let cumulative_gpa =
+            Self::weighted_mean(years.iter().map(|year| (year.gpa, 1.0))).unwrap_or_default();
+
+        Ok(CumulativeGpa {
+            student_id: Some(*student_id),
+            years,
+            cumulative_gpa,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term_id: &str, average_percentage: f64, gpa: f64) -> TermRow {
+        TermRow {
+            term_id: term_id.to_string(),
+            class_id: None,
+            average_percentage,
+            gpa,
+        }
+    }
+
+    fn subject(term_id: &str, name: &str, percentage: f64) -> SubjectRow {
+        SubjectRow {
+            term_id: term_id.to_string(),
+            class_subject_id: None,
+            subject_name: name.to_string(),
+            percentage,
+            credits: Some(3),
+        }
+    }
+
+    fn weights(pairs: &[(&str, f64)]) -> HashMap<String, f64> {
+        pairs
+            .iter()
+            .map(|(term_id, weight)| (term_id.to_string(), *weight))
+            .collect()
+    }
+
+    #[test]
+    fn terms_count_by_their_configured_weight() {
+        let terms = [term("t1", 60.0, 3.0), term("t2", 80.0, 4.0)];
+        let combined =
+            AnnualResultService::combine_terms(&terms, &weights(&[("t1", 1.0), ("t2", 3.0)]));
+        let (average, gpa) = AnnualResultService::weigh_terms(&combined).unwrap();
+        assert!((average - 75.0).abs() < 1e-9);
+        assert!((gpa - 3.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn terms_count_equally_until_weights_are_configured() {
+        let terms = [term("t1", 60.0, 3.0), term("t2", 80.0, 4.0)];
+        let combined = AnnualResultService::combine_terms(&terms, &HashMap::new());
+        assert!(combined.iter().all(|term| term.weight == 1.0));
+        let (average, gpa) = AnnualResultService::weigh_terms(&combined).unwrap();
+        assert!((average - 70.0).abs() < 1e-9);
+        assert!((gpa - 3.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unweighted_terms_are_left_out_once_weights_exist() {
+        let terms = [term("t1", 40.0, 1.0), term("t2", 90.0, 4.0)];
+        let combined = AnnualResultService::combine_terms(&terms, &weights(&[("t2", 1.0)]));
+        assert_eq!(combined[0].weight, 0.0);
+        let (average, gpa) = AnnualResultService::weigh_terms(&combined).unwrap();
+        assert!((average - 90.0).abs() < 1e-9);
+        assert!((gpa - 4.0).abs() < 1e-9);
+
+        let combined = AnnualResultService::combine_terms(&terms, &weights(&[("t3", 1.0)]));
+        assert!(AnnualResultService::weigh_terms(&combined).is_err());
+    }
+
+    #[test]
+    fn several_results_in_one_term_are_averaged_first() {
+        let terms = [
+            term("t1", 50.0, 2.0),
+            term("t1", 70.0, 3.0),
+            term("t2", 90.0, 4.0),
+        ];
+        let combined = AnnualResultService::combine_terms(&terms, &HashMap::new());
+        assert_eq!(combined.len(), 2);
+        assert_eq!(combined[0].results_counted, 2);
+        assert!((combined[0].average_percentage - 60.0).abs() < 1e-9);
+        assert!((combined[0].gpa - 2.5).abs() < 1e-9);
+        let (_, gpa) = AnnualResultService::weigh_terms(&combined).unwrap();
+        assert!((gpa - 3.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn subjects_are_weighted_like_their_terms() {
+        let terms = AnnualResultService::combine_terms(
+            &[term("t1", 0.0, 0.0), term("t2", 0.0, 0.0)],
+            &weights(&[("t1", 1.0), ("t2", 3.0)]),
+        );
+        let subjects = AnnualResultService::combine_subjects(
+            &[
+                subject("t1", "Maths", 60.0),
+                subject("t2", "Maths", 80.0),
+                subject("t3", "Music", 100.0),
+            ],
+            &terms,
+        );
+
+        // Music was only taken in a term that carries no weight
+        assert_eq!(subjects.len(), 1);
+        assert_eq!(subjects[0].subject_name, "Maths");
+        assert!((subjects[0].percentage - 75.0).abs() < 1e-9);
+        assert_eq!(subjects[0].grade, "C");
+        assert_eq!(subjects[0].credits, Some(3));
+    }
+}
@@ -26,9 +26,9 @@ impl AssessmentCategoryQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -69,9 +69,7 @@ impl AssessmentCategoryService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -83,8 +81,8 @@ impl AssessmentCategoryService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -152,9 +150,10 @@ impl AssessmentCategoryService {
                         .push(")");
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported assessment category filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported assessment category filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -232,9 +231,7 @@ impl AssessmentCategoryService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let id = category
             .id
             .map(|id| id.to_hex())
@@ -284,9 +281,7 @@ impl AssessmentCategoryService {
 
         match row {
             Some(row) => Self::category_from_row(row),
-            None => Err(AppError {
-                message: "Assessment category not found".into(),
-            }),
+            None => Err(AppError::not_found("Assessment category not found")),
         }
     }
 
@@ -425,9 +420,7 @@ impl AssessmentCategoryService {
         }
 
         if !touched {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
         sql.push(" WHERE id = ")
             .push_bind(&id_string)
@@ -490,12 +483,10 @@ impl AssessmentCategoryService {
         let total_weight = current + new_weight;
 
         if total_weight > 100.0 {
-            return Err(AppError {
-                message: format!(
-                    "Total weight percentage exceeds 100%. Current total: {:.2}%",
-                    total_weight
-                ),
-            });
+            return Err(AppError::bad_request(format!(
+                "Total weight percentage exceeds 100%. Current total: {:.2}%",
+                total_weight
+            )));
         }
 
         Ok(())
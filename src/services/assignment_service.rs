@@ -44,9 +44,9 @@ impl AssignmentQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -89,9 +89,7 @@ impl AssignmentService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -103,8 +101,8 @@ impl AssignmentService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -217,9 +215,10 @@ impl AssignmentService {
                         .push(")");
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported assignment filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported assignment filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -272,9 +271,10 @@ impl AssignmentService {
                         .push(")");
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported submission filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported submission filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -515,14 +515,10 @@ impl AssignmentService {
         self.ensure_indexes().await?;
 
         if dto.title.trim().is_empty() {
-            return Err(AppError {
-                message: "Assignment title is required".into(),
-            });
+            return Err(AppError::bad_request("Assignment title is required"));
         }
         if dto.max_score <= 0.0 {
-            return Err(AppError {
-                message: "Max score must be greater than 0".into(),
-            });
+            return Err(AppError::bad_request("Max score must be greater than 0"));
         }
 
         if let (Some(teacher_id), Some(subject_id)) = (dto.teacher_id, dto.subject_id) {
@@ -550,9 +546,9 @@ impl AssignmentService {
             .map_err(Self::db_error)?;
 
             if assigned == Some(false) {
-                return Err(AppError {
-                    message: "Teacher is not assigned to this subject".into(),
-                });
+                return Err(AppError::bad_request(
+                    "Teacher is not assigned to this subject",
+                ));
             }
         }
 
@@ -560,7 +556,7 @@ impl AssignmentService {
         if let Some(attachment_data) = assignment.attachment_url.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&attachment_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             assignment.attachment_id = Some(cloud_res.public_id);
             assignment.attachment_url = Some(cloud_res.secure_url);
         }
@@ -569,9 +565,7 @@ impl AssignmentService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let id = assignment
             .id
             .map(|id| id.to_hex())
@@ -642,9 +636,7 @@ impl AssignmentService {
         row.as_ref()
             .map(Self::assignment_from_row)
             .transpose()?
-            .ok_or(AppError {
-                message: "Assignment not found".into(),
-            })
+            .ok_or(AppError::not_found("Assignment not found"))
     }
 
     pub async fn find_one_assignment_with_relations(
@@ -665,9 +657,7 @@ impl AssignmentService {
             .map_err(Self::db_error)?;
         row.map(Self::assignment_with_relations_from_row)
             .transpose()?
-            .ok_or(AppError {
-                message: "Assignment not found".into(),
-            })
+            .ok_or(AppError::not_found("Assignment not found"))
     }
 
     pub async fn get_all_assignments(
@@ -751,16 +741,12 @@ impl AssignmentService {
         let existing = self.find_one_assignment(Some(id), None).await?;
         if let Some(ref title) = update.title {
             if title.trim().is_empty() {
-                return Err(AppError {
-                    message: "Assignment title cannot be empty".into(),
-                });
+                return Err(AppError::bad_request("Assignment title cannot be empty"));
             }
         }
         if let Some(max_score) = update.max_score {
             if max_score <= 0.0 {
-                return Err(AppError {
-                    message: "Max score must be greater than 0".into(),
-                });
+                return Err(AppError::bad_request("Max score must be greater than 0"));
             }
         }
 
@@ -774,7 +760,7 @@ impl AssignmentService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_attachment)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.attachment_id = Some(Some(cloud_res.public_id));
                 update_data.attachment_url = Some(Some(cloud_res.secure_url));
             }
@@ -868,9 +854,7 @@ impl AssignmentService {
         }
 
         if !touched {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
         sql.push(" WHERE id = ")
             .push_bind(id_string)
@@ -924,10 +908,9 @@ impl AssignmentService {
     ) -> Result<bool, AppError> {
         let is_late = Utc::now() > assignment.due_date;
         if is_late && !assignment.allow_late_submission {
-            return Err(AppError {
-                message: "Submission deadline has passed and late submissions are not allowed"
-                    .into(),
-            });
+            return Err(AppError::bad_request(
+                "Submission deadline has passed and late submissions are not allowed",
+            ));
         }
         Ok(is_late)
     }
@@ -937,14 +920,10 @@ impl AssignmentService {
         let assignment_id = dto
             .assignment_id
             .map(|id| IdType::ObjectId(id))
-            .ok_or(AppError {
-                message: "Assignment ID is required".into(),
-            })?;
+            .ok_or(AppError::bad_request("Assignment ID is required"))?;
         let assignment = self.find_one_assignment(Some(&assignment_id), None).await?;
         if !matches!(assignment.status, AssignmentStatus::Published) {
-            return Err(AppError {
-                message: "Assignment is not published".into(),
-            });
+            return Err(AppError::bad_request("Assignment is not published"));
         }
         let is_late = self.validate_submission_deadline(&assignment).await?;
         let assignment_id_string = Self::id_to_string(&assignment_id)?;
@@ -952,9 +931,7 @@ impl AssignmentService {
             .student_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "Student ID is required".into(),
-            })?;
+            .ok_or_else(|| AppError::bad_request("Student ID is required"))?;
 
         let existing: bool = sqlx::query_scalar(
             "SELECT EXISTS(SELECT 1 FROM submissions WHERE assignment_id = $1 AND student_id = $2 AND deleted_at IS NULL)",
@@ -965,9 +942,9 @@ impl AssignmentService {
         .await
         .map_err(Self::db_error)?;
         if existing {
-            return Err(AppError {
-                message: "You have already submitted this assignment. Use update instead.".into(),
-            });
+            return Err(AppError::conflict(
+                "You have already submitted this assignment. Use update instead.",
+            ));
         }
 
         let mut submission = dto;
@@ -975,7 +952,7 @@ impl AssignmentService {
         if let Some(file_data) = submission.file_url.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&file_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             submission.file_id = Some(cloud_res.public_id);
             submission.file_url = Some(cloud_res.secure_url);
         }
@@ -987,9 +964,7 @@ impl AssignmentService {
         let school_id = assignment
             .school_id
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "Assignment has no school_id".into(),
-            })?;
+            .ok_or_else(|| AppError::bad_request("Assignment has no school_id"))?;
 
         sqlx::query(
             r#"
@@ -1049,9 +1024,7 @@ impl AssignmentService {
         row.as_ref()
             .map(Self::submission_from_row)
             .transpose()?
-            .ok_or(AppError {
-                message: "Submission not found".into(),
-            })
+            .ok_or(AppError::not_found("Submission not found"))
     }
 
     pub async fn get_all_submissions(
@@ -1139,12 +1112,10 @@ impl AssignmentService {
                     .find_one_assignment(Some(&IdType::ObjectId(assignment_id)), None)
                     .await?;
                 if score > assignment.max_score {
-                    return Err(AppError {
-                        message: format!(
-                            "Score cannot exceed max score of {}",
-                            assignment.max_score
-                        ),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Score cannot exceed max score of {}",
+                        assignment.max_score
+                    )));
                 }
             }
         }
@@ -1159,7 +1130,7 @@ impl AssignmentService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_file)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.file_id = Some(Some(cloud_res.public_id));
                 update_data.file_url = Some(Some(cloud_res.secure_url));
             }
@@ -1173,7 +1144,7 @@ impl AssignmentService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&feedback_file)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.feedback_file_id = Some(Some(cloud_res.public_id));
                 update_data.feedback_file_url = Some(Some(cloud_res.secure_url));
             }
@@ -1244,9 +1215,7 @@ impl AssignmentService {
             touched = true;
         }
         if !touched {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
         sql.push(" WHERE id = ")
             .push_bind(Self::id_to_string(id)?)
@@ -1272,9 +1241,10 @@ impl AssignmentService {
                 .find_one_assignment(Some(&IdType::ObjectId(assignment_id)), None)
                 .await?;
             if score > assignment.max_score {
-                return Err(AppError {
-                    message: format!("Score cannot exceed max score of {}", assignment.max_score),
-                });
+                return Err(AppError::bad_request(format!(
+                    "Score cannot exceed max score of {}",
+                    assignment.max_score
+                )));
             }
         }
 
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, Utc};
+
+use crate::{
+    domain::{
+        attendance::{
+            Attendance, AttendanceCounts, AttendanceEntry, AttendancePartial, AttendanceQuery,
+            AttendanceStatus, BulkAttendanceRequest, ClassAttendanceRegister,
+            StudentAttendanceSummary,
+        },
+        common_details::Paginated,
+    },
+    errors::AppError,
+    models::id_model::IdType,
+    repositories::attendance_repo::AttendanceRepo,
+    utils::object_id::ObjectId,
+};
+
+pub struct AttendanceService<'a> {
+    repo: &'a AttendanceRepo,
+}
+
+impl<'a> AttendanceService<'a> {
+    pub fn new(repo: &'a AttendanceRepo) -> Self {
+        Self { repo }
+    }
+
+    /// Make sure the class exists and belongs to the school in the request context.
+    pub async fn resolve_class_school(
+        &self,
+        class_id: &str,
+        context_school_id: Option<&str>,
+    ) -> Result<String, AppError> {
+        ObjectId::parse_str(class_id).map_err(|e| {
+            AppError::bad_request(format!("Invalid class_id ObjectId-compatible ID: {}", e))
+        })?;
+
+        let school_id = self
+            .repo
+            .class_school_id(class_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Class not found"))?;
+
+        if let Some(context_school_id) = context_school_id {
+            if context_school_id != school_id {
+                return Err(AppError::bad_request(
+                    "Class does not belong to this school",
+                ));
+            }
+        }
+
+        Ok(school_id)
+    }
+
+    async fn validate_period(
+        &self,
+        class_id: &str,
+        period_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        if let Some(period_id) = period_id {
+            if !self.repo.class_has_period(class_id, period_id).await? {
+                return Err(AppError::bad_request(
+                    "period_id does not belong to this class timetable",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Submit the roll for a whole class. Students omitted from `records` are
+    /// marked with `default_status` when one is given, otherwise left unmarked.
+    pub async fn submit_register(
+        &self,
+        school_id: &str,
+        class_id: &str,
+        request: BulkAttendanceRequest,
+        recorded_by: Option<&str>,
+    ) -> Result<ClassAttendanceRegister, AppError> {
+        if request.date > Utc::now().date_naive() {
+            return Err(AppError::bad_request(
+                "Attendance cannot be recorded for a future date",
+            ));
+        }
+
+        let period_id = request.period_id.map(|id| id.to_hex());
+        self.validate_period(class_id, period_id.as_deref()).await?;
+
+        let enrolled: HashSet<String> = self
+            .repo
+            .class_enrollment_ids(class_id)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut seen = HashSet::new();
+        for entry in &request.records {
+            let student_id = entry.student_id.to_hex();
+            if !enrolled.contains(&student_id) {
+                return Err(AppError::bad_request(format!(
+                    "Student {} is not in this class",
+                    student_id
+                )));
+            }
+            if !seen.insert(student_id.clone()) {
+                return Err(AppError::bad_request(format!(
+                    "Student {} appears more than once",
+                    student_id
+                )));
+            }
+        }
+
+        let mut entries = request.records;
+        if let Some(default_status) = request.default_status {
+            for student_id in enrolled.difference(&seen) {
+                entries.push(AttendanceEntry {
+                    student_id: ObjectId::parse_str(student_id).map_err(|e| {
+                        AppError::bad_request(format!(
+                            "Invalid student_id ObjectId-compatible ID: {}",
+                            e
+                        ))
+                    })?,
+                    status: default_status,
+                    note: None,
+                });
+            }
+        }
+
+        if entries.is_empty() {
+            return Err(AppError::bad_request("No attendance records to save"));
+        }
+
+        self.repo
+            .upsert_many(
+                school_id,
+                class_id,
+                request.date,
+                period_id.as_deref(),
+                &entries,
+                recorded_by,
+            )
+            .await?;
+
+        self.get_register(class_id, request.date, period_id.as_deref())
+            .await
+    }
+
+    pub async fn get_register(
+        &self,
+        class_id: &str,
+        date: NaiveDate,
+        period_id: Option<&str>,
+    ) -> Result<ClassAttendanceRegister, AppError> {
+        self.validate_period(class_id, period_id).await?;
+        let students = self.repo.class_register(class_id, date, period_id).await?;
+
+        let mut counts = AttendanceCounts::default();
+        for student in &students {
+            Self::tally(&mut counts, student.status);
+        }
+
+        Ok(ClassAttendanceRegister {
+            class_id: ObjectId::parse_str(class_id).map_err(|e| {
+                AppError::bad_request(format!("Invalid class_id ObjectId-compatible ID: {}", e))
+            })?,
+            date,
+            period_id: period_id
+                .map(ObjectId::parse_str)
+                .transpose()
+                .map_err(|e| {
+                    AppError::bad_request(format!(
+                        "Invalid period_id ObjectId-compatible ID: {}",
+                        e
+                    ))
+                })?,
+            students,
+            counts,
+        })
+    }
+
+    fn tally(counts: &mut AttendanceCounts, status: Option<AttendanceStatus>) {
+        match status {
+            Some(AttendanceStatus::Present) => counts.present += 1,
+            Some(AttendanceStatus::Absent) => counts.absent += 1,
+            Some(AttendanceStatus::Late) => counts.late += 1,
+            Some(AttendanceStatus::Excused) => counts.excused += 1,
+            None => counts.unmarked += 1,
+        }
+    }
+
+    pub async fn get_all(
+        &self,
+        school_id: &str,
+        query: &AttendanceQuery,
+    ) -> Result<Paginated<Attendance>, AppError> {
+        self.repo.find_many(school_id, query).await
+    }
+
+    pub async fn find_one(&self, id: &IdType) -> Result<Attendance, AppError> {
+        self.repo.find_by_id(id).await
+    }
+
+    /// Correct a single mark. Only `status` and `note` can change; moving a record
+    /// to another student, class or day means deleting it and taking the roll again.
+    pub async fn update(
+        &self,
+        id: &IdType,
+        update: AttendancePartial,
+        recorded_by: Option<&str>,
+    ) -> Result<Attendance, AppError> {
+        if update.status.is_none() && update.note.is_none() {
+            return Err(AppError::bad_request("No valid fields to update"));
+        }
+
+        self.repo
+            .update(id, update.status, update.note, recorded_by)
+            .await
+    }
+
+    pub async fn delete(&self, id: &IdType) -> Result<Attendance, AppError> {
+        let record = self.repo.find_by_id(id).await?;
+        self.repo.soft_delete(id).await?;
+        Ok(record)
+    }
+
+    pub async fn student_summary(
+        &self,
+        school_id: &str,
+        student_id: &str,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<StudentAttendanceSummary, AppError> {
+        let student_oid = ObjectId::parse_str(student_id).map_err(|e| {
+            AppError::bad_request(format!("Invalid student_id ObjectId-compatible ID: {}", e))
+        })?;
+        let counts = self
+            .repo
+            .student_counts(school_id, student_id, from, to)
+            .await?;
+
+        Ok(StudentAttendanceSummary {
+            student_id: student_oid,
+            total_records: counts.total_marked(),
+            attendance_percentage: counts.attendance_percentage(),
+            counts,
+        })
+    }
+}
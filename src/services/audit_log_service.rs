@@ -26,9 +26,7 @@ impl AuditLogService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -44,8 +42,8 @@ impl AuditLogService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -241,6 +239,40 @@ impl AuditLogService {
         Ok(())
     }
 
+    /// Record an action taken by the server itself (e.g. a scheduled job),
+    /// with no acting user.
+    pub async fn log_system_event(
+        &self,
+        school_id: ObjectId,
+        action: &str,
+        entity_type: &str,
+        entity_id: ObjectId,
+        metadata: Option<serde_json::Value>,
+        severity: Option<AuditSeverity>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_logs (
+              id, school_id, entity_type, entity_id, action, severity, metadata, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(school_id.to_hex())
+        .bind(entity_type)
+        .bind(entity_id.to_hex())
+        .bind(action)
+        .bind(Self::severity_to_string(severity.unwrap_or_default()))
+        .bind(metadata.unwrap_or_else(|| serde_json::json!({})))
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(())
+    }
+
     async fn log_event_internal(
         &self,
         school_id: ObjectId,
@@ -305,9 +337,7 @@ impl AuditLogService {
             .map_err(Self::db_error)?
             .map(|row| Self::audit_log_from_row(&row))
             .transpose()?
-            .ok_or(AppError {
-                message: "Audit log not found".into(),
-            })
+            .ok_or(AppError::not_found("Audit log not found"))
     }
 
     pub async fn get_all(
@@ -2,18 +2,23 @@ use crate::{
     config::state::AppState,
     domain::{
         auth::{LoginResponse, LoginUser, RegisterUser},
+        auth_session::{SessionMetadata, TokenPair},
         common_details::UserRole,
+        two_factor::{LoginOutcome, TwoFactorLoginResponse},
         user::{UpdateUserDto, User},
     },
     errors::AppError,
     mappers::user_mapper::to_auth_dto,
     models::id_model::IdType,
     repositories::user_repo::UserRepo,
-    services::{school_service::SchoolService, user_service::UserService},
+    services::{
+        auth_session_service::AuthSessionService, school_service::SchoolService,
+        two_factor_service::TwoFactorService, user_service::UserService,
+    },
     utils::{
         email::is_valid_email,
         hash::{hash_password, password_needs_rehash, verify_password},
-        jwt::{create_jwt, verify_jwt},
+        jwt::{access_token_ttl, create_jwt, verify_jwt},
         names::{generate_username, is_valid_name},
         user_utils::sanitize_user,
     },
@@ -29,31 +34,33 @@ impl<'a> AuthService<'a> {
         Self { repo }
     }
 
+    /// Resolve the user and issue an access token bound to the session. A
+    /// password sign-in by a user who owes a second factor gets a challenge
+    /// instead, before any access or school token is issued.
     pub async fn get_auth_user(
         &self,
         user_email: &str,
         password: Option<&String>,
         state: &AppState,
-    ) -> Result<LoginResponse, AppError> {
+        session_id: Option<String>,
+    ) -> Result<LoginOutcome, AppError> {
         // Optimized: Use single database query to check both email and username
         let user = self
             .repo
             .find_by_login(user_email)
             .await?
-            .ok_or_else(|| AppError {
-                message: "User not found".to_string(),
-            })?;
+            .ok_or_else(|| AppError::unauthorized("Incorrect email or password"))?;
 
         // Verify password if provided (this is the slowest operation due to Argon2)
         if let Some(user_password) = password {
-            let hash = user.password_hash.as_ref().ok_or_else(|| AppError {
-                message: "This account does not have a password set".to_string(),
+            let hash = user.password_hash.as_ref().ok_or_else(|| {
+                AppError::unauthorized("This account does not have a password set")
             })?;
 
             if !verify_password(hash, user_password) {
-                return Err(AppError {
-                    message: "Incorrect password, please try again".to_string(),
-                });
+                return Err(AppError::unauthorized(
+                    "Incorrect password, please try again",
+                ));
             }
 
             if password_needs_rehash(hash) {
@@ -75,6 +82,37 @@ impl<'a> AuthService<'a> {
             }
         }
 
+        if password.is_some() {
+            let user_id = user
+                .id
+                .as_ref()
+                .ok_or_else(|| AppError::bad_request("User does not have an ID"))?
+                .to_hex();
+            let two_factor = TwoFactorService::new(&self.repo.pool);
+            let enabled = two_factor.is_enabled(&user_id).await?;
+            let setup_required =
+                !enabled && two_factor.is_required(&user_id, user.role.as_ref()).await?;
+            if enabled || setup_required {
+                return Ok(LoginOutcome::SecondFactorRequired(
+                    two_factor
+                        .create_challenge(&user_id, setup_required)
+                        .await?,
+                ));
+            }
+        }
+
+        Ok(LoginOutcome::Authenticated(Box::new(
+            self.issue_tokens(user, state, session_id).await?,
+        )))
+    }
+
+    /// Access token bound to the session, plus the current school's token
+    async fn issue_tokens(
+        &self,
+        user: User,
+        state: &AppState,
+        session_id: Option<String>,
+    ) -> Result<LoginResponse, AppError> {
         let school_service = SchoolService::new(&state.pg.pool);
         let mut current_school_user_id = None;
         let mut school_access_token = None;
@@ -84,9 +122,7 @@ impl<'a> AuthService<'a> {
             let user_id = user
                 .id
                 .as_ref()
-                .ok_or_else(|| AppError {
-                    message: "User does not have an ID".to_string(),
-                })?
+                .ok_or_else(|| AppError::bad_request("User does not have an ID"))?
                 .to_hex();
             let school_id_string = school_id.to_hex();
 
@@ -106,7 +142,8 @@ impl<'a> AuthService<'a> {
         }
 
         // Create main auth DTO and access token
-        let auth_user_dto = to_auth_dto(&user, current_school_user_id.clone());
+        let mut auth_user_dto = to_auth_dto(&user, current_school_user_id.clone());
+        auth_user_dto.session_id = session_id;
         let access_token = create_jwt(&auth_user_dto);
 
         let user = sanitize_user(user);
@@ -116,6 +153,7 @@ impl<'a> AuthService<'a> {
             email: user.email,
             name: user.name,
             access_token,
+            refresh_token: None,
             image: user.image,
             role: user.role,
             username: user.username,
@@ -129,12 +167,13 @@ impl<'a> AuthService<'a> {
         })
     }
 
-    /// ✅ Register a new user
+    /// ✅ Register a new user, returning access token, refresh token and user
     pub async fn register(
         &self,
         user_service: &UserService<'a>,
         data: RegisterUser,
-    ) -> Result<(String, User), String> {
+        meta: &SessionMetadata,
+    ) -> Result<(String, String, User), String> {
         is_valid_email(&data.email)?;
 
         // 🔒 Ensure email not already taken
@@ -198,23 +237,91 @@ impl<'a> AuthService<'a> {
             .await
             .map_err(|e| e.to_string())?;
 
-        // 🪪 Generate token
-        let dto = to_auth_dto(&sanitize_user(res.clone()), None);
+        // 🪪 Start a session and generate tokens
+        let session_id = AuthSessionService::next_session_id();
+        let mut dto = to_auth_dto(&sanitize_user(res.clone()), None);
+        dto.session_id = Some(session_id.clone());
+        let refresh_token = AuthSessionService::new(&self.repo.pool)
+            .start(&session_id, &dto.id, meta)
+            .await
+            .map_err(|e| e.message)?;
         let token = create_jwt(&dto);
 
-        Ok((token, sanitize_user(res)))
+        Ok((token, refresh_token, sanitize_user(res)))
     }
 
-    /// ✅ Log in existing user
+    /// ✅ Log in existing user and start a session for the device, or hand
+    /// back a second-factor challenge
     pub async fn login(
         &self,
         data: LoginUser,
         state: &AppState,
-    ) -> Result<LoginResponse, AppError> {
-        self.get_auth_user(&data.email, Some(&data.password), state)
+        meta: &SessionMetadata,
+    ) -> Result<LoginOutcome, AppError> {
+        let session_id = AuthSessionService::next_session_id();
+        let outcome = self
+            .get_auth_user(
+                &data.email,
+                Some(&data.password),
+                state,
+                Some(session_id.clone()),
+            )
+            .await?;
+
+        match outcome {
+            LoginOutcome::Authenticated(mut response) => {
+                response.refresh_token =
+                    Some(self.start_session(&session_id, &response, meta).await?);
+                Ok(LoginOutcome::Authenticated(response))
+            }
+            challenge => Ok(challenge),
+        }
+    }
+
+    async fn start_session(
+        &self,
+        session_id: &str,
+        response: &LoginResponse,
+        meta: &SessionMetadata,
+    ) -> Result<String, AppError> {
+        let user_id = response
+            .id
+            .as_deref()
+            .ok_or_else(|| AppError::bad_request("User does not have an ID"))?;
+        AuthSessionService::new(&self.repo.pool)
+            .start(session_id, user_id, meta)
             .await
     }
 
+    /// 🔐 Finish a sign-in that was challenged for a second factor
+    pub async fn complete_two_factor_login(
+        &self,
+        challenge_token: &str,
+        code: &str,
+        state: &AppState,
+        meta: &SessionMetadata,
+    ) -> Result<TwoFactorLoginResponse, AppError> {
+        let (user_id, recovery_codes) = TwoFactorService::new(&self.repo.pool)
+            .redeem_challenge(challenge_token, code)
+            .await?;
+        let user = self
+            .repo
+            .find_by_id(&IdType::from_string(&user_id))
+            .await?
+            .ok_or_else(|| AppError::unauthorized("User not found"))?;
+
+        let session_id = AuthSessionService::next_session_id();
+        let mut login = self
+            .issue_tokens(user, state, Some(session_id.clone()))
+            .await?;
+        login.refresh_token = Some(self.start_session(&session_id, &login, meta).await?);
+
+        Ok(TwoFactorLoginResponse {
+            login,
+            recovery_codes,
+        })
+    }
+
     /// ✅ Get user info from JWT
     pub async fn get_user_from_token(&self, token: &str) -> Result<User, String> {
         let token_clean = token.replace("Bearer ", "");
@@ -232,6 +339,7 @@ impl<'a> AuthService<'a> {
     pub async fn onboard_user(
         &self,
         user_id: &str,
+        session_id: Option<String>,
         updated_data: UpdateUserDto,
         user_service: &UserService<'a>,
     ) -> Result<(String, User), String> {
@@ -241,22 +349,38 @@ impl<'a> AuthService<'a> {
         let updated_user = user_service.update_user(&id, updated_data).await?;
 
         // 🪪 Issue fresh token
-        let dto = to_auth_dto(&sanitize_user(updated_user.clone()), None);
+        let mut dto = to_auth_dto(&sanitize_user(updated_user.clone()), None);
+        dto.session_id = session_id;
         let new_token = create_jwt(&dto);
 
         Ok((new_token, sanitize_user(updated_user)))
     }
 
-    /// 🔄 Refresh JWT token if still valid
-    pub async fn refresh_token(&self, token: &str, state: &AppState) -> Result<String, AppError> {
-        // remove "Bearer " if present
-        let token_clean = token.replace("Bearer ", "");
-        let claims = verify_jwt(&token_clean).ok_or_else(|| AppError {
-            message: "Invalid token".to_string(),
-        })?;
+    /// 🔄 Exchange a refresh token for a fresh access token and the next
+    /// refresh token of the same session
+    pub async fn refresh_token(
+        &self,
+        refresh_token: &str,
+        state: &AppState,
+        meta: &SessionMetadata,
+    ) -> Result<TokenPair, AppError> {
+        let rotated = AuthSessionService::new(&self.repo.pool)
+            .rotate(refresh_token, meta)
+            .await?;
 
-        let auth = self.get_auth_user(&claims.user.email, None, state).await?;
+        let user = self
+            .repo
+            .find_by_id(&IdType::from_string(&rotated.user_id))
+            .await?
+            .ok_or_else(|| AppError::unauthorized("User not found"))?;
+        let auth = self
+            .issue_tokens(user, state, Some(rotated.session_id))
+            .await?;
 
-        Ok(auth.access_token)
+        Ok(TokenPair {
+            access_token: auth.access_token,
+            refresh_token: rotated.refresh_token,
+            expires_in: access_token_ttl().num_seconds(),
+        })
     }
 }
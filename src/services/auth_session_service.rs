@@ -0,0 +1,289 @@
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
+
+use crate::{
+    domain::auth_session::{AuthSession, SessionMetadata},
+    errors::AppError,
+    utils::object_id::ObjectId,
+};
+
+/// How long a session survives without being refreshed.
+/// Overridable with `REFRESH_TOKEN_TTL_DAYS`.
+const DEFAULT_REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+fn refresh_token_ttl() -> Duration {
+    let days = std::env::var("REFRESH_TOKEN_TTL_DAYS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_REFRESH_TOKEN_TTL_DAYS);
+    Duration::days(days)
+}
+
+/// Session and user a refresh token was rotated for
+#[derive(Debug, Clone)]
+pub struct RotatedToken {
+    pub session_id: String,
+    pub user_id: String,
+    pub refresh_token: String,
+}
+
+/// Server-side sessions, each a family of single-use refresh tokens.
+/// Presenting a token that was already exchanged means it leaked, so the
+/// whole family is revoked. Revoking a session stops further refreshes;
+/// access tokens already issued run out within their short TTL.
+pub struct AuthSessionService {
+    pub pool: PgPool,
+}
+
+impl AuthSessionService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn new_token() -> String {
+        hex::encode(rand::random::<[u8; 32]>())
+    }
+
+    fn hash_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    fn row_to_session(row: PgRow, current: Option<&str>) -> Result<AuthSession, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        Ok(AuthSession {
+            current: current == Some(id.as_str()),
+            id,
+            user_agent: row.try_get("user_agent").map_err(Self::db_error)?,
+            ip_address: row.try_get("ip_address").map_err(Self::db_error)?,
+            created_at: row.try_get("created_at").map_err(Self::db_error)?,
+            last_used_at: row.try_get("last_used_at").map_err(Self::db_error)?,
+            expires_at: row.try_get("expires_at").map_err(Self::db_error)?,
+            revoked_at: row.try_get("revoked_at").map_err(Self::db_error)?,
+            revoked_reason: row.try_get("revoked_reason").map_err(Self::db_error)?,
+        })
+    }
+
+    /// Id for a session about to be started, so the access token issued
+    /// alongside it can carry it
+    pub fn next_session_id() -> String {
+        Self::new_id()
+    }
+
+    /// Record a sign-in and return the session's first refresh token
+    pub async fn start(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        meta: &SessionMetadata,
+    ) -> Result<String, AppError> {
+        let expires_at = Utc::now() + refresh_token_ttl();
+        let token = Self::new_token();
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        sqlx::query(
+            "INSERT INTO auth_sessions (id, user_id, user_agent, ip_address, expires_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(&meta.user_agent)
+        .bind(&meta.ip_address)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        sqlx::query(
+            "INSERT INTO refresh_tokens (id, session_id, token_hash, expires_at) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(Self::new_id())
+        .bind(session_id)
+        .bind(Self::hash_token(&token))
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
+
+        Ok(token)
+    }
+
+    /// Exchange a refresh token for a new one from the same family
+    pub async fn rotate(
+        &self,
+        refresh_token: &str,
+        meta: &SessionMetadata,
+    ) -> Result<RotatedToken, AppError> {
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let row = sqlx::query(
+            r#"
+            SELECT rt.id, rt.session_id, rt.expires_at, rt.used_at, s.user_id, s.revoked_at
+            FROM refresh_tokens rt
+            JOIN auth_sessions s ON s.id = rt.session_id
+            WHERE rt.token_hash = $1
+            FOR UPDATE OF rt, s
+            "#,
+        )
+        .bind(Self::hash_token(refresh_token))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| AppError::unauthorized("Invalid refresh token"))?;
+
+        let token_id: String = row.try_get("id").map_err(Self::db_error)?;
+        let session_id: String = row.try_get("session_id").map_err(Self::db_error)?;
+        let user_id: String = row.try_get("user_id").map_err(Self::db_error)?;
+        let expires_at: DateTime<Utc> = row.try_get("expires_at").map_err(Self::db_error)?;
+        let used_at: Option<DateTime<Utc>> = row.try_get("used_at").map_err(Self::db_error)?;
+        let revoked_at: Option<DateTime<Utc>> =
+            row.try_get("revoked_at").map_err(Self::db_error)?;
+
+        if revoked_at.is_some() {
+            return Err(AppError::unauthorized("Session has been revoked"));
+        }
+        if used_at.is_some() {
+            sqlx::query(
+                "UPDATE auth_sessions SET revoked_at = now(), revoked_reason = 'token_reuse' \
+                 WHERE id = $1",
+            )
+            .bind(&session_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+            tx.commit().await.map_err(Self::db_error)?;
+            log::warn!(
+                "Refresh token reuse detected, revoked session {} of user {}",
+                session_id,
+                user_id
+            );
+            return Err(AppError::unauthorized(
+                "Refresh token was already used; the session has been revoked",
+            ));
+        }
+        if expires_at <= Utc::now() {
+            return Err(AppError::unauthorized("Refresh token has expired"));
+        }
+
+        let new_expires_at = Utc::now() + refresh_token_ttl();
+        let token = Self::new_token();
+        sqlx::query("UPDATE refresh_tokens SET used_at = now() WHERE id = $1")
+            .bind(&token_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        sqlx::query(
+            "INSERT INTO refresh_tokens (id, session_id, token_hash, parent_id, expires_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(Self::new_id())
+        .bind(&session_id)
+        .bind(Self::hash_token(&token))
+        .bind(&token_id)
+        .bind(new_expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        sqlx::query(
+            "UPDATE auth_sessions SET last_used_at = now(), expires_at = $2, \
+             user_agent = coalesce($3, user_agent), ip_address = coalesce($4, ip_address) \
+             WHERE id = $1",
+        )
+        .bind(&session_id)
+        .bind(new_expires_at)
+        .bind(&meta.user_agent)
+        .bind(&meta.ip_address)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
+
+        Ok(RotatedToken {
+            session_id,
+            user_id,
+            refresh_token: token,
+        })
+    }
+
+    /// Live sessions of the user, most recently used first
+    pub async fn list(
+        &self,
+        user_id: &str,
+        current: Option<&str>,
+    ) -> Result<Vec<AuthSession>, AppError> {
+        let rows = sqlx::query(
+            "SELECT id, user_agent, ip_address, created_at, last_used_at, expires_at, \
+             revoked_at, revoked_reason \
+             FROM auth_sessions \
+             WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > now() \
+             ORDER BY last_used_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter()
+            .map(|row| Self::row_to_session(row, current))
+            .collect()
+    }
+
+    pub async fn revoke(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        current: Option<&str>,
+    ) -> Result<AuthSession, AppError> {
+        let row = sqlx::query(
+            "UPDATE auth_sessions \
+             SET revoked_at = coalesce(revoked_at, now()), \
+                 revoked_reason = coalesce(revoked_reason, 'revoked') \
+             WHERE id = $1 AND user_id = $2 \
+             RETURNING id, user_agent, ip_address, created_at, last_used_at, expires_at, \
+                       revoked_at, revoked_reason",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_session(row, current),
+            None => Err(AppError::not_found("Session not found")),
+        }
+    }
+
+    /// Log out everywhere, returning how many sessions were revoked
+    pub async fn revoke_all(&self, user_id: &str, reason: &str) -> Result<u64, AppError> {
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let revoked = Self::revoke_all_in(&mut tx, user_id, reason).await?;
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(revoked)
+    }
+
+    /// [`Self::revoke_all`] as part of the caller's transaction
+    pub async fn revoke_all_in(
+        tx: &mut Transaction<'static, Postgres>,
+        user_id: &str,
+        reason: &str,
+    ) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "UPDATE auth_sessions SET revoked_at = now(), revoked_reason = $2 \
+             WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(reason)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,722 @@
+//! Native PostgreSQL per-school snapshots.
+//!
+//! Tenant tables are discovered from the catalog: every table with a
+//! `school_id` column, plus tables that reach one of those through a foreign
+//! key (e.g. `fee_structure_items` → `fee_structures`). An archive is a gzip
+//! file of JSON lines; the first line is a [`BackupManifest`], every following
+//! line is one row. Restores replace the tenant's rows in a single transaction.
+//! Archive files are read and written on the blocking pool, streaming rows to
+//! and from the database through a bounded channel.
+
+use chrono::Utc;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+use crate::{
+    domain::backup::{
+        BackupArchiveLine, BackupManifest, BackupTableManifest, BACKUP_FORMAT,
+        BACKUP_FORMAT_VERSION,
+    },
+    errors::AppError,
+};
+
+/// Tables that carry a `school_id` but must not be rolled back with the
+/// tenant: backup bookkeeping and the audit trail, plus shared identity rows.
+const EXCLUDED_TABLES: &[&str] = &[
+    "school_backups",
+    "school_backup_policies",
+    "audit_logs",
+    "users",
+    "student_profiles",
+    "_sqlx_migrations",
+];
+
+/// Rows per INSERT for tables without self-references.
+const INSERT_BATCH: usize = 500;
+
+/// Archive lines in flight between the database and the file.
+const LINE_BUFFER: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct TenantTable {
+    pub name: String,
+    /// WHERE clause selecting the tenant's rows; `$1` is the school id
+    pub condition: String,
+    pub columns: Vec<String>,
+    pub self_referencing: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ForeignKey {
+    child_table: String,
+    child_column: String,
+    parent_table: String,
+    parent_column: String,
+}
+
+pub fn backup_dir() -> PathBuf {
+    PathBuf::from(std::env::var("BACKUP_DIR").unwrap_or_else(|_| "backups".to_string()))
+}
+
+fn db_error(error: sqlx::Error) -> AppError {
+    AppError::from(error)
+}
+
+fn io_error(context: &str, error: std::io::Error) -> AppError {
+    AppError::internal(format!("{}: {}", context, error))
+}
+
+/// Run file work on the blocking pool so it never stalls the runtime.
+async fn blocking<T, F>(task: F) -> Result<T, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| AppError::internal(format!("Backup file task failed: {}", e)))?
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Tenant tables in foreign-key order (parents first), starting with `schools`.
+pub async fn tenant_plan(pool: &PgPool) -> Result<Vec<TenantTable>, AppError> {
+    let column_rows = sqlx::query(
+        r#"
+        SELECT c.table_name::TEXT AS table_name, c.column_name::TEXT AS column_name
+        FROM information_schema.columns c
+        JOIN information_schema.tables t
+          ON t.table_schema = c.table_schema AND t.table_name = c.table_name
+        WHERE c.table_schema = current_schema()
+          AND t.table_type = 'BASE TABLE'
+          AND c.is_generated = 'NEVER'
+        ORDER BY c.table_name, c.ordinal_position
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+
+    let mut columns: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for row in column_rows {
+        let table: String = row.try_get("table_name").map_err(db_error)?;
+        let column: String = row.try_get("column_name").map_err(db_error)?;
+        columns.entry(table).or_default().push(column);
+    }
+
+    let fk_rows = sqlx::query(
+        r#"
+        SELECT child.relname::TEXT AS child_table, ca.attname::TEXT AS child_column,
+               parent.relname::TEXT AS parent_table, pa.attname::TEXT AS parent_column
+        FROM pg_constraint con
+        JOIN pg_class child ON child.oid = con.conrelid
+        JOIN pg_class parent ON parent.oid = con.confrelid
+        JOIN pg_namespace ns ON ns.oid = child.relnamespace
+        JOIN pg_attribute ca ON ca.attrelid = con.conrelid AND ca.attnum = con.conkey[1]
+        JOIN pg_attribute pa ON pa.attrelid = con.confrelid AND pa.attnum = con.confkey[1]
+        WHERE con.contype = 'f'
+          AND cardinality(con.conkey) = 1
+          AND ns.nspname = current_schema()
+        ORDER BY child.relname, ca.attname
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+
+    let foreign_keys = fk_rows
+        .into_iter()
+        .map(|row| {
+            Ok(ForeignKey {
+                child_table: row.try_get("child_table").map_err(db_error)?,
+                child_column: row.try_get("child_column").map_err(db_error)?,
+                parent_table: row.try_get("parent_table").map_err(db_error)?,
+                parent_column: row.try_get("parent_column").map_err(db_error)?,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok(build_plan(&columns, &foreign_keys))
+}
+
+fn build_plan(
+    columns: &BTreeMap<String, Vec<String>>,
+    foreign_keys: &[ForeignKey],
+) -> Vec<TenantTable> {
+    let mut conditions: BTreeMap<String, String> = BTreeMap::new();
+    if columns.contains_key("schools") {
+        conditions.insert("schools".into(), "id = $1".into());
+    }
+    for (table, table_columns) in columns {
+        if !EXCLUDED_TABLES.contains(&table.as_str())
+            && table_columns.iter().any(|column| column == "school_id")
+        {
+            conditions.insert(table.clone(), "school_id = $1".into());
+        }
+    }
+
+    // Pull in tables that only reach a school through a parent row. A few
+    // rounds cover grandchildren such as student_term_category_scores.
+    for _ in 0..4 {
+        let mut discovered: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for fk in foreign_keys {
+            if conditions.contains_key(&fk.child_table)
+                || EXCLUDED_TABLES.contains(&fk.child_table.as_str())
+                || fk.child_table == fk.parent_table
+            {
+                continue;
+            }
+            if let Some(parent_condition) = conditions.get(&fk.parent_table) {
+                discovered
+                    .entry(fk.child_table.clone())
+                    .or_default()
+                    .push(format!(
+                        "{} IN (SELECT {} FROM {} WHERE {})",
+                        quote_ident(&fk.child_column),
+                        quote_ident(&fk.parent_column),
+                        quote_ident(&fk.parent_table),
+                        parent_condition
+                    ));
+            }
+        }
+        if discovered.is_empty() {
+            break;
+        }
+        for (table, clauses) in discovered {
+            conditions.insert(table, format!("({})", clauses.join(" OR ")));
+        }
+    }
+
+    // Kahn's algorithm over foreign keys between tenant tables.
+    let mut parents: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    let mut self_referencing: BTreeSet<&str> = BTreeSet::new();
+    for fk in foreign_keys {
+        if !conditions.contains_key(&fk.child_table) || !conditions.contains_key(&fk.parent_table) {
+            continue;
+        }
+        if fk.child_table == fk.parent_table {
+            self_referencing.insert(fk.child_table.as_str());
+        } else {
+            parents
+                .entry(fk.child_table.as_str())
+                .or_default()
+                .insert(fk.parent_table.as_str());
+        }
+    }
+
+    let mut ordered: Vec<String> = Vec::with_capacity(conditions.len());
+    let mut remaining: BTreeSet<&str> = conditions.keys().map(String::as_str).collect();
+    while !remaining.is_empty() {
+        let ready: Vec<&str> = remaining
+            .iter()
+            .copied()
+            .filter(|table| {
+                parents
+                    .get(table)
+                    .map(|deps| deps.iter().all(|dep| !remaining.contains(dep)))
+                    .unwrap_or(true)
+            })
+            .collect();
+        // A foreign-key cycle; keep name order and let the constraints decide.
+        let batch = if ready.is_empty() {
+            remaining.iter().copied().collect()
+        } else {
+            ready
+        };
+        for table in batch {
+            remaining.remove(table);
+            ordered.push(table.to_string());
+        }
+    }
+
+    ordered
+        .into_iter()
+        .map(|name| TenantTable {
+            condition: conditions[&name].clone(),
+            columns: columns.get(&name).cloned().unwrap_or_default(),
+            self_referencing: self_referencing.contains(name.as_str()),
+            name,
+        })
+        .collect()
+}
+
+fn sha256_of(path: &Path) -> Result<String, AppError> {
+    let mut file = File::open(path).map_err(|e| io_error("Failed to open backup file", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| io_error("Failed to read backup file", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+async fn file_sha256(path: &Path) -> Result<String, AppError> {
+    let path = path.to_path_buf();
+    blocking(move || sha256_of(&path)).await
+}
+
+/// Write a snapshot of every tenant row of `school_id` to `path`.
+/// Returns the archive size in bytes and its SHA-256.
+pub async fn export_school(
+    pool: &PgPool,
+    school_id: &str,
+    path: &Path,
+) -> Result<(i64, String), AppError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| io_error("Failed to create backup directory", e))?;
+    }
+
+    let plan = tenant_plan(pool).await?;
+    let (sender, mut receiver) = mpsc::channel::<String>(LINE_BUFFER);
+    let writer = tokio::task::spawn_blocking(move || -> Result<File, AppError> {
+        let mut payload =
+            tempfile::tempfile().map_err(|e| io_error("Failed to create temp file", e))?;
+        {
+            let mut writer = BufWriter::new(&mut payload);
+            while let Some(line) = receiver.blocking_recv() {
+                writer
+                    .write_all(line.as_bytes())
+                    .map_err(|e| io_error("Failed to write backup payload", e))?;
+            }
+            writer
+                .flush()
+                .map_err(|e| io_error("Failed to write backup payload", e))?;
+        }
+        Ok(payload)
+    });
+
+    let mut hasher = Sha256::new();
+    let streamed = stream_rows(pool, school_id, &plan, sender, &mut hasher).await;
+    // A failed writer explains why the rows stopped, so it is reported first.
+    let payload = writer
+        .await
+        .map_err(|e| AppError::internal(format!("Backup file task failed: {}", e)))??;
+    let (tables, total_rows) = streamed?;
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_FORMAT_VERSION,
+        school_id: school_id.to_string(),
+        created_at: Utc::now(),
+        tables,
+        total_rows,
+        payload_sha256: hex::encode(hasher.finalize()),
+    };
+
+    let archive = path.to_path_buf();
+    blocking(move || write_archive(&archive, &manifest, payload)).await?;
+
+    let size = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| io_error("Failed to get backup file size", e))?
+        .len() as i64;
+    Ok((size, file_sha256(path).await?))
+}
+
+/// Send every tenant row to the payload writer, one consistent view of the
+/// tenant across all tables. Returns the table manifests and the row total.
+async fn stream_rows(
+    pool: &PgPool,
+    school_id: &str,
+    plan: &[TenantTable],
+    sender: mpsc::Sender<String>,
+    hasher: &mut Sha256,
+) -> Result<(Vec<BackupTableManifest>, u64), AppError> {
+    let mut tables = Vec::with_capacity(plan.len());
+    let mut total_rows = 0u64;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    for table in plan {
+        let sql = format!(
+            "SELECT to_jsonb(t) AS row FROM {} t WHERE {}",
+            quote_ident(&table.name),
+            table.condition
+        );
+        let mut rows = sqlx::query(&sql).bind(school_id).fetch(&mut *tx);
+        let mut row_count = 0u64;
+        while let Some(row) = rows.try_next().await.map_err(db_error)? {
+            let line = BackupArchiveLine {
+                table: table.name.clone(),
+                row: row.try_get("row").map_err(db_error)?,
+            };
+            let mut encoded = serde_json::to_string(&line).map_err(|e| {
+                AppError::internal(format!("Failed to encode row of {}: {}", table.name, e))
+            })?;
+            encoded.push('\n');
+            hasher.update(encoded.as_bytes());
+            sender
+                .send(encoded)
+                .await
+                .map_err(|_| AppError::internal("Backup payload writer stopped"))?;
+            row_count += 1;
+        }
+
+        total_rows += row_count;
+        tables.push(BackupTableManifest {
+            name: table.name.clone(),
+            columns: table.columns.clone(),
+            row_count,
+        });
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok((tables, total_rows))
+}
+
+/// Gzip the manifest line followed by the payload into `path`.
+fn write_archive(
+    path: &Path,
+    manifest: &BackupManifest,
+    mut payload: File,
+) -> Result<(), AppError> {
+    let file = File::create(path).map_err(|e| io_error("Failed to create backup file", e))?;
+    let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    let mut manifest_line = serde_json::to_string(manifest)
+        .map_err(|e| AppError::internal(format!("Failed to encode backup manifest: {}", e)))?;
+    manifest_line.push('\n');
+    encoder
+        .write_all(manifest_line.as_bytes())
+        .map_err(|e| io_error("Failed to write backup file", e))?;
+    payload
+        .seek(SeekFrom::Start(0))
+        .map_err(|e| io_error("Failed to read backup payload", e))?;
+    std::io::copy(&mut payload, &mut encoder)
+        .map_err(|e| io_error("Failed to write backup file", e))?;
+    encoder
+        .finish()
+        .and_then(|mut writer| writer.flush())
+        .map_err(|e| io_error("Failed to write backup file", e))
+}
+
+type ArchiveReader = BufReader<GzDecoder<File>>;
+
+/// Open an archive and read its manifest, leaving the reader at the first row.
+fn open_archive(path: &Path) -> Result<(BackupManifest, ArchiveReader), AppError> {
+    let file = File::open(path).map_err(|e| io_error("Failed to open backup file", e))?;
+    let mut reader = BufReader::new(GzDecoder::new(file));
+    let manifest = parse_manifest(&mut reader)?;
+    Ok((manifest, reader))
+}
+
+pub async fn read_manifest(path: &Path) -> Result<BackupManifest, AppError> {
+    let path = path.to_path_buf();
+    blocking(move || open_archive(&path).map(|(manifest, _)| manifest)).await
+}
+
+fn parse_manifest(reader: &mut impl BufRead) -> Result<BackupManifest, AppError> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|e| io_error("Failed to read backup manifest", e))?;
+    let manifest: BackupManifest = serde_json::from_str(line.trim_end())
+        .map_err(|e| AppError::bad_request(format!("Backup manifest is invalid: {}", e)))?;
+
+    if manifest.format != BACKUP_FORMAT {
+        return Err(AppError::bad_request(format!(
+            "Unsupported backup format '{}'",
+            manifest.format
+        )));
+    }
+    if manifest.version > BACKUP_FORMAT_VERSION {
+        return Err(AppError::bad_request(format!(
+            "Backup format version {} is newer than supported version {}",
+            manifest.version, BACKUP_FORMAT_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// What the archive reader hands to a restore
+enum ArchiveItem {
+    Row(BackupArchiveLine),
+    /// Every row was read; carries the SHA-256 of the payload lines
+    End(String),
+}
+
+/// Read the archive rows on the blocking pool. The reader stops after the
+/// first error it sends, or once the restore hangs up.
+fn read_rows(mut reader: ArchiveReader) -> mpsc::Receiver<Result<ArchiveItem, AppError>> {
+    let (sender, receiver) = mpsc::channel(LINE_BUFFER);
+    tokio::task::spawn_blocking(move || {
+        let mut hasher = Sha256::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let row = match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    hasher.update(line.as_bytes());
+                    serde_json::from_str::<BackupArchiveLine>(line.trim_end())
+                        .map_err(|e| AppError::bad_request(format!("Backup row is invalid: {}", e)))
+                }
+                Err(e) => Err(io_error("Failed to read backup file", e)),
+            };
+            let failed = row.is_err();
+            if sender.blocking_send(row.map(ArchiveItem::Row)).is_err() || failed {
+                return;
+            }
+        }
+        let _ = sender.blocking_send(Ok(ArchiveItem::End(hex::encode(hasher.finalize()))));
+    });
+    receiver
+}
+
+/// Rows that name their school must name the one being restored: the
+/// `schools` row by its id, every other tenant table by `school_id`.
+fn check_row_school(
+    table: &TenantTable,
+    row: &serde_json::Value,
+    school_id: &str,
+) -> Result<(), AppError> {
+    let column = if table.name == "schools" {
+        "id"
+    } else if table.columns.iter().any(|column| column == "school_id") {
+        "school_id"
+    } else {
+        return Ok(());
+    };
+    match row.get(column).and_then(serde_json::Value::as_str) {
+        Some(id) if id == school_id => Ok(()),
+        _ => Err(AppError::bad_request(format!(
+            "Backup row for '{}' belongs to a different school",
+            table.name
+        ))),
+    }
+}
+
+async fn insert_rows(
+    conn: &mut sqlx::PgConnection,
+    table: &TenantTable,
+    archived_columns: &[String],
+    rows: Vec<serde_json::Value>,
+) -> Result<(), AppError> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    // Columns missing from an older archive fall back to their defaults.
+    let columns: Vec<String> = table
+        .columns
+        .iter()
+        .filter(|column| archived_columns.contains(column))
+        .map(|column| quote_ident(column))
+        .collect();
+    let column_list = columns.join(", ");
+    let table_name = quote_ident(&table.name);
+
+    let mut sql = format!(
+        "INSERT INTO {table_name} ({column_list}) SELECT {column_list} \
+         FROM jsonb_populate_recordset(NULL::{table_name}, $1::jsonb)"
+    );
+    if table.name == "schools" {
+        let updates: Vec<String> = columns
+            .iter()
+            .filter(|column| column.as_str() != "\"id\"")
+            .map(|column| format!("{column} = EXCLUDED.{column}"))
+            .collect();
+        sql.push_str(&format!(
+            " ON CONFLICT (id) DO UPDATE SET {}",
+            updates.join(", ")
+        ));
+    }
+
+    // Self-referencing rows go in one statement so the foreign key is checked
+    // once all of them exist.
+    let batch = if table.self_referencing {
+        rows.len()
+    } else {
+        INSERT_BATCH
+    };
+    let mut rows = rows.into_iter().peekable();
+    while rows.peek().is_some() {
+        let chunk: Vec<serde_json::Value> = rows.by_ref().take(batch).collect();
+        sqlx::query(&sql)
+            .bind(serde_json::Value::Array(chunk))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to restore {}: {}", table.name, e)))?;
+    }
+    Ok(())
+}
+
+/// Replace every tenant row of `school_id` with the archive content.
+/// Nothing is committed unless the file checksum, the payload checksum and
+/// row counts match, and every restored row belongs to `school_id`. Returns
+/// the number of rows restored.
+pub async fn restore_school(
+    pool: &PgPool,
+    school_id: &str,
+    path: &Path,
+    expected_checksum: &str,
+) -> Result<u64, AppError> {
+    if file_sha256(path).await? != expected_checksum {
+        return Err(AppError::bad_request(
+            "Backup file checksum does not match; the archive is corrupt or was modified",
+        ));
+    }
+
+    let archive = path.to_path_buf();
+    let (manifest, reader) = blocking(move || open_archive(&archive)).await?;
+    if manifest.school_id != school_id {
+        return Err(AppError::bad_request(
+            "Backup belongs to a different school",
+        ));
+    }
+
+    let plan = tenant_plan(pool).await?;
+    let by_name: HashMap<&str, &TenantTable> = plan
+        .iter()
+        .map(|table| (table.name.as_str(), table))
+        .collect();
+    let archived: HashMap<&str, &BackupTableManifest> = manifest
+        .tables
+        .iter()
+        .map(|table| (table.name.as_str(), table))
+        .collect();
+    for table in &manifest.tables {
+        if !by_name.contains_key(table.name.as_str()) {
+            return Err(AppError::bad_request(format!(
+                "Backup contains table '{}' which is not a tenant table in this database",
+                table.name
+            )));
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('school_restore:' || $1))")
+        .bind(school_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    // Children first so parent conditions still resolve while deleting.
+    for table in plan.iter().rev().filter(|table| table.name != "schools") {
+        let sql = format!(
+            "DELETE FROM {} WHERE {}",
+            quote_ident(&table.name),
+            table.condition
+        );
+        sqlx::query(&sql)
+            .bind(school_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to clear {}: {}", table.name, e)))?;
+    }
+
+    let mut rows = read_rows(reader);
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut pending: Vec<serde_json::Value> = Vec::new();
+    let mut payload_sha256 = String::new();
+
+    loop {
+        let parsed = match rows.recv().await {
+            Some(Ok(ArchiveItem::Row(row))) => Some(row),
+            Some(Ok(ArchiveItem::End(sha256))) => {
+                payload_sha256 = sha256;
+                None
+            }
+            Some(Err(err)) => return Err(err),
+            None => return Err(AppError::internal("Backup reader stopped unexpectedly")),
+        };
+
+        let table_changed = match (&current, &parsed) {
+            (Some(current), Some(parsed)) => current != &parsed.table,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        if table_changed {
+            if let Some(name) = current.take() {
+                let table = by_name[name.as_str()];
+                let archived_columns = &archived[name.as_str()].columns;
+                insert_rows(
+                    &mut tx,
+                    table,
+                    archived_columns,
+                    std::mem::take(&mut pending),
+                )
+                .await?;
+            }
+        }
+
+        let Some(parsed) = parsed else {
+            break;
+        };
+        if !archived.contains_key(parsed.table.as_str()) {
+            return Err(AppError::bad_request(format!(
+                "Backup row for '{}' is not listed in the manifest",
+                parsed.table
+            )));
+        }
+        check_row_school(by_name[parsed.table.as_str()], &parsed.row, school_id)?;
+        *counts.entry(parsed.table.clone()).or_default() += 1;
+        current = Some(parsed.table);
+        pending.push(parsed.row);
+    }
+
+    if payload_sha256 != manifest.payload_sha256 {
+        return Err(AppError::bad_request(
+            "Backup payload checksum does not match the manifest",
+        ));
+    }
+    for table in &manifest.tables {
+        let restored = counts.get(&table.name).copied().unwrap_or(0);
+        if restored != table.row_count {
+            return Err(AppError::bad_request(format!(
+                "Backup table '{}' has {} rows but the manifest lists {}",
+                table.name, restored, table.row_count
+            )));
+        }
+    }
+
+    // Rows of tables reached through a parent carry no school_id; once the
+    // tenant's old rows are gone, each must still resolve to this school.
+    // The schools row is kept across the restore and was checked by id.
+    for table in manifest
+        .tables
+        .iter()
+        .filter(|table| table.name != "schools")
+    {
+        let tenant = by_name[table.name.as_str()];
+        let sql = format!(
+            "SELECT count(*) FROM {} WHERE {}",
+            quote_ident(&tenant.name),
+            tenant.condition
+        );
+        let owned: i64 = sqlx::query_scalar(&sql)
+            .bind(school_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        if owned as u64 != table.row_count {
+            return Err(AppError::bad_request(format!(
+                "Backup table '{}' has rows that belong to a different school",
+                table.name
+            )));
+        }
+    }
+
+    tx.commit().await.map_err(db_error)?;
+    Ok(manifest.total_rows)
+}
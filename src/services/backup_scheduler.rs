@@ -0,0 +1,101 @@
+//! In-process scheduler for automated school backups.
+//!
+//! Every minute the enabled `school_backup_policies` are checked against the
+//! current UTC minute. A due school is claimed through its `last_run_at` slot
+//! before running, so several API instances can share the schedule without
+//! running the same backup twice. Set `BACKUP_SCHEDULER_ENABLED=false` to keep
+//! an instance out of the rotation.
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use sqlx::PgPool;
+
+use crate::{services::backup_service::BackupService, utils::cron_schedule::CronSchedule};
+
+pub fn scheduler_enabled() -> bool {
+    std::env::var("BACKUP_SCHEDULER_ENABLED")
+        .map(|value| {
+            !matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "false" | "0" | "off"
+            )
+        })
+        .unwrap_or(true)
+}
+
+pub fn start(pool: PgPool) {
+    if !scheduler_enabled() {
+        log::info!("Backup scheduler disabled by BACKUP_SCHEDULER_ENABLED");
+        return;
+    }
+
+    actix_rt::spawn(async move {
+        loop {
+            let now = Utc::now();
+            let slot =
+                now.duration_trunc(Duration::minutes(1)).unwrap_or(now) + Duration::minutes(1);
+            let wait = (slot - now).to_std().unwrap_or_default();
+            actix_rt::time::sleep(wait).await;
+
+            // Jobs of one slot run in the background so a slow backup does
+            // not make the ticker skip the next minutes.
+            let pool = pool.clone();
+            actix_rt::spawn(async move { run_due_backups(&pool, slot).await });
+        }
+    });
+}
+
+async fn run_due_backups(pool: &PgPool, slot: DateTime<Utc>) {
+    let service = BackupService::new(pool);
+    let policies = match service.enabled_policies().await {
+        Ok(policies) => policies,
+        Err(err) => {
+            log::warn!("Backup scheduler could not load policies: {}", err.message);
+            return;
+        }
+    };
+
+    for policy in policies {
+        let Some(school_id) = policy.school_id else {
+            continue;
+        };
+        match CronSchedule::parse(&policy.schedule) {
+            Ok(schedule) if schedule.matches(&slot) => {}
+            Ok(_) => continue,
+            Err(err) => {
+                log::warn!(
+                    "Skipping backup schedule of school {}: {}",
+                    school_id.to_hex(),
+                    err
+                );
+                continue;
+            }
+        }
+
+        match service.claim_schedule_slot(&school_id, slot).await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                log::warn!(
+                    "Backup scheduler could not claim slot for school {}: {}",
+                    school_id.to_hex(),
+                    err.message
+                );
+                continue;
+            }
+        }
+
+        match service.run_automated_backup(school_id, &policy).await {
+            Ok(backup) => log::info!(
+                "Automated backup {} for school {} finished: {:?}",
+                backup.backup_name,
+                school_id.to_hex(),
+                backup.status
+            ),
+            Err(err) => log::warn!(
+                "Automated backup for school {} failed: {}",
+                school_id.to_hex(),
+                err.message
+            ),
+        }
+    }
+}
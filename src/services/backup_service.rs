@@ -1,19 +1,24 @@
-use chrono::Utc;
+use chrono::{DateTime, Datelike, Utc};
+use serde_json::json;
 use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
-use std::path::PathBuf;
-use std::process::Command;
+use std::collections::HashSet;
+use std::path::Path;
 
 use crate::{
     config::state::AppState,
     domain::{
+        audit_log::AuditSeverity,
         auth_user::AuthUserDto,
-        backup::{BackupStatus, BackupType, SchoolBackup, SchoolBackupWithRelations},
+        backup::{
+            BackupPolicy, BackupStatus, BackupType, SchoolBackup, SchoolBackupWithRelations,
+            UpdateBackupPolicy, DEFAULT_BACKUP_SCHEDULE,
+        },
         common_details::Paginated,
     },
     errors::AppError,
     models::{id_model::IdType, mongo_model::CountDoc},
-    services::audit_log_service::AuditLogService,
-    utils::object_id::ObjectId,
+    services::{audit_log_service::AuditLogService, backup_archive},
+    utils::{cron_schedule::CronSchedule, object_id::ObjectId},
 };
 
 pub struct BackupService {
@@ -26,7 +31,7 @@ impl BackupService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -38,8 +43,8 @@ impl BackupService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -92,12 +97,13 @@ impl BackupService {
             created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
             completed_at: row.try_get("completed_at").ok().flatten(),
             error_message: row.try_get("error_message").ok().flatten(),
+            checksum: row.try_get("checksum").ok().flatten(),
         })
     }
 
     fn select_sql() -> &'static str {
         "SELECT id, school_id, backup_name, backup_type, file_path, size_bytes, status, \
-         created_by, created_at, completed_at, error_message FROM school_backups WHERE 1=1"
+         created_by, created_at, completed_at, error_message, checksum FROM school_backups WHERE 1=1"
     }
 
     fn push_search<'a>(sql: &mut QueryBuilder<'a, Postgres>, like: &'a str) {
@@ -108,17 +114,23 @@ impl BackupService {
             .push(")");
     }
 
-    pub async fn create_manual_backup(
+    /// Insert an `InProgress` backup row and return its id and archive path.
+    async fn insert_backup_row(
         &self,
-        school_id: ObjectId,
-        user: &AuthUserDto,
-        state: &AppState,
-    ) -> Result<SchoolBackup, AppError> {
-        let backup_name = format!("manual_backup_{}_{}", school_id.to_hex(), Utc::now().format("%Y%m%d_%H%M%S"));
-        let file_path = format!("backups/{}.archive", backup_name);
+        school_id: &ObjectId,
+        backup_type: BackupType,
+        created_by: Option<&ObjectId>,
+    ) -> Result<(String, String), AppError> {
+        let prefix = match backup_type {
+            BackupType::Manual => "manual_backup",
+            BackupType::Automated => "automated_backup",
+        };
+        let backup_name = format!("{}_{}_{}", prefix, school_id.to_hex(), Utc::now().format("%Y%m%d_%H%M%S"));
+        let file_path = backup_archive::backup_dir()
+            .join(format!("{}.jsonl.gz", backup_name))
+            .to_string_lossy()
+            .into_owned();
         let id = Self::new_id();
-        let now = Utc::now();
-        let created_by = ObjectId::parse_str(&user.id).map_err(|_| AppError { message: "Invalid user ID".to_string() })?;
 
         sqlx::query(
             r#"INSERT INTO school_backups (id, school_id, backup_name, backup_type, file_path, size_bytes, status, created_by, created_at)
@@ -127,61 +139,290 @@ impl BackupService {
         .bind(&id)
         .bind(school_id.to_hex())
         .bind(&backup_name)
-        .bind(Self::backup_type_to_string(&BackupType::Manual))
+        .bind(Self::backup_type_to_string(&backup_type))
         .bind(&file_path)
         .bind(Self::backup_status_to_string(&BackupStatus::InProgress))
-        .bind(created_by.to_hex())
-        .bind(now)
+        .bind(created_by.map(|id| id.to_hex()))
+        .bind(Utc::now())
         .execute(&self.pool)
         .await
         .map_err(Self::db_error)?;
 
+        Ok((id, file_path))
+    }
+
+    /// Export the school to `file_path` and record the outcome on the backup row.
+    async fn perform_backup(pool: &PgPool, backup_id: &str, school_id: &str, file_path: &str) -> Result<(), AppError> {
+        match backup_archive::export_school(pool, school_id, Path::new(file_path)).await {
+            Ok((size, checksum)) => {
+                sqlx::query("UPDATE school_backups SET status = 'Completed', size_bytes = $1, checksum = $2, completed_at = now() WHERE id = $3")
+                    .bind(size).bind(checksum).bind(backup_id).execute(pool).await.map_err(Self::db_error)?;
+                Ok(())
+            }
+            Err(e) => {
+                tokio::fs::remove_file(file_path).await.ok();
+                sqlx::query("UPDATE school_backups SET status = 'Failed', error_message = $1, completed_at = now() WHERE id = $2")
+                    .bind(&e.message).bind(backup_id).execute(pool).await.map_err(Self::db_error)?;
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn create_manual_backup(
+        &self,
+        school_id: ObjectId,
+        user: &AuthUserDto,
+        state: &AppState,
+    ) -> Result<SchoolBackup, AppError> {
+        let created_by =
+            ObjectId::parse_str(&user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
+        let (id, file_path) = self
+            .insert_backup_row(&school_id, BackupType::Manual, Some(&created_by))
+            .await?;
+
         let created_backup = self.find_one(Some(&IdType::from_string(id.clone())), None).await?;
 
         let audit_service = AuditLogService::new(&state.pg.pool);
         audit_service.log_event(school_id, user, "backup.manual.create", "backup", created_by, None, None, None).await.ok();
 
         let pool_clone = self.pool.clone();
-        let file_path_clone = file_path.clone();
-        let db_name = format!("school_{}", school_id.to_hex());
-        let backup_id = id.clone();
+        let school_hex = school_id.to_hex();
 
         actix_rt::spawn(async move {
-            let result = Self::perform_backup(&db_name, &file_path_clone).await;
-            match result {
-                Ok(size) => {
-                    let _ = sqlx::query("UPDATE school_backups SET status = 'Completed', size_bytes = $1, completed_at = now() WHERE id = $2")
-                        .bind(size).bind(&backup_id).execute(&pool_clone).await;
-                }
-                Err(e) => {
-                    let _ = sqlx::query("UPDATE school_backups SET status = 'Failed', error_message = $1, completed_at = now() WHERE id = $2")
-                        .bind(e.message).bind(&backup_id).execute(&pool_clone).await;
-                }
+            if let Err(e) = Self::perform_backup(&pool_clone, &id, &school_hex, &file_path).await {
+                log::warn!("Manual backup {} failed: {}", id, e.message);
             }
         });
 
         Ok(created_backup)
     }
 
-    async fn perform_backup(db_name: &str, file_path: &str) -> Result<i64, AppError> {
-        let backup_dir = PathBuf::from("backups");
-        std::fs::create_dir_all(&backup_dir).map_err(|e| AppError { message: format!("Failed to create backup directory: {}", e) })?;
-
-        let mongo_uri = std::env::var("MONGODB_URI").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
-        let output = Command::new("mongodump")
-            .arg("--uri").arg(&mongo_uri)
-            .arg("--db").arg(db_name)
-            .arg("--archive").arg(file_path)
-            .arg("--gzip")
-            .output()
-            .map_err(|e| AppError { message: format!("Failed to execute mongodump: {}", e) })?;
-
-        if !output.status.success() {
-            return Err(AppError { message: format!("Backup failed: {}", String::from_utf8_lossy(&output.stderr)) });
+    /// Run one scheduled backup to completion, then prune old automated
+    /// backups according to `policy`. Both are recorded in the audit log.
+    pub async fn run_automated_backup(
+        &self,
+        school_id: ObjectId,
+        policy: &BackupPolicy,
+    ) -> Result<SchoolBackup, AppError> {
+        let (id, file_path) = self.insert_backup_row(&school_id, BackupType::Automated, None).await?;
+        let result = Self::perform_backup(&self.pool, &id, &school_id.to_hex(), &file_path).await;
+        let backup = self.find_one(Some(&IdType::from_string(id.clone())), None).await?;
+
+        let audit_service = AuditLogService::new(&self.pool);
+        let (action, severity) = match result {
+            Ok(_) => ("backup.automated.create", AuditSeverity::INFO),
+            Err(_) => ("backup.automated.failed", AuditSeverity::WARNING),
+        };
+        audit_service
+            .log_system_event(school_id, action, "backup", Self::parse_oid(&id, "id")?, Some(json!({ "error": backup.error_message })), Some(severity))
+            .await
+            .ok();
+
+        if matches!(backup.status, BackupStatus::Completed) {
+            self.apply_retention(&school_id, policy).await?;
+        }
+        Ok(backup)
+    }
+
+    /// Ids of the backups to keep: the newest backup of each of the last
+    /// `keep_daily` days, `keep_weekly` ISO weeks and `keep_monthly` months
+    /// that have one. `backups` must be sorted newest first.
+    pub fn retained_backup_ids(
+        backups: &[(String, DateTime<Utc>)],
+        keep_daily: usize,
+        keep_weekly: usize,
+        keep_monthly: usize,
+    ) -> HashSet<String> {
+        fn keep_newest_per<K: Eq + std::hash::Hash>(
+            backups: &[(String, DateTime<Utc>)],
+            limit: usize,
+            key: impl Fn(&DateTime<Utc>) -> K,
+            keep: &mut HashSet<String>,
+        ) {
+            let mut seen = HashSet::new();
+            for (id, created_at) in backups {
+                if seen.len() >= limit {
+                    break;
+                }
+                if seen.insert(key(created_at)) {
+                    keep.insert(id.clone());
+                }
+            }
+        }
+
+        let mut keep = HashSet::new();
+        keep_newest_per(backups, keep_daily, |at| at.date_naive(), &mut keep);
+        keep_newest_per(backups, keep_weekly, |at| { let week = at.iso_week(); (week.year(), week.week()) }, &mut keep);
+        keep_newest_per(backups, keep_monthly, |at| (at.year(), at.month()), &mut keep);
+        keep
+    }
+
+    /// Delete completed automated backups outside the retention policy, and
+    /// failed automated runs older than the oldest backup kept. Manual backups
+    /// are never touched.
+    pub async fn apply_retention(
+        &self,
+        school_id: &ObjectId,
+        policy: &BackupPolicy,
+    ) -> Result<Vec<SchoolBackup>, AppError> {
+        let rows = sqlx::query(
+            "SELECT id, created_at FROM school_backups \
+             WHERE school_id = $1 AND backup_type = 'Automated' AND status = 'Completed' ORDER BY created_at DESC",
+        )
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        let completed = rows
+            .into_iter()
+            .map(|row| Ok((row.try_get::<String, _>("id")?, row.try_get::<DateTime<Utc>, _>("created_at")?)))
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(Self::db_error)?;
+
+        let keep = Self::retained_backup_ids(
+            &completed,
+            policy.keep_daily.max(0) as usize,
+            policy.keep_weekly.max(0) as usize,
+            policy.keep_monthly.max(0) as usize,
+        );
+        let mut expired: Vec<String> = completed.iter().filter(|(id, _)| !keep.contains(id)).map(|(id, _)| id.clone()).collect();
+
+        if let Some(oldest_kept) = completed.iter().filter(|(id, _)| keep.contains(id)).map(|(_, at)| *at).min() {
+            let failed: Vec<String> = sqlx::query_scalar(
+                "SELECT id FROM school_backups \
+                 WHERE school_id = $1 AND backup_type = 'Automated' AND status = 'Failed' AND created_at < $2",
+            )
+            .bind(school_id.to_hex())
+            .bind(oldest_kept)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+            expired.extend(failed);
+        }
+
+        let audit_service = AuditLogService::new(&self.pool);
+        let mut pruned = Vec::with_capacity(expired.len());
+        for id in expired {
+            let backup = self.delete(&IdType::from_string(id.clone())).await?;
+            audit_service
+                .log_system_event(*school_id, "backup.retention.prune", "backup", Self::parse_oid(&id, "id")?, Some(json!({ "backup_name": backup.backup_name })), None)
+                .await
+                .ok();
+            pruned.push(backup);
+        }
+        Ok(pruned)
+    }
+
+    fn row_to_policy(row: PgRow) -> Result<BackupPolicy, AppError> {
+        let school_id: String = row.try_get("school_id").map_err(Self::db_error)?;
+        Ok(BackupPolicy {
+            school_id: Some(Self::parse_oid(&school_id, "school_id")?),
+            enabled: row.try_get("enabled").map_err(Self::db_error)?,
+            schedule: row.try_get("schedule").map_err(Self::db_error)?,
+            keep_daily: row.try_get("keep_daily").map_err(Self::db_error)?,
+            keep_weekly: row.try_get("keep_weekly").map_err(Self::db_error)?,
+            keep_monthly: row.try_get("keep_monthly").map_err(Self::db_error)?,
+            last_run_at: row.try_get("last_run_at").ok().flatten(),
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    fn policy_select_sql() -> &'static str {
+        "SELECT school_id, enabled, schedule, keep_daily, keep_weekly, keep_monthly, last_run_at, \
+         created_at, updated_at FROM school_backup_policies"
+    }
+
+    /// The school's policy; schools that never saved one are not scheduled.
+    pub async fn get_policy(&self, school_id: &ObjectId) -> Result<BackupPolicy, AppError> {
+        let row = sqlx::query(&format!("{} WHERE school_id = $1", Self::policy_select_sql()))
+            .bind(school_id.to_hex())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_policy(row),
+            None => Ok(BackupPolicy {
+                school_id: Some(*school_id),
+                enabled: false,
+                schedule: DEFAULT_BACKUP_SCHEDULE.to_string(),
+                keep_daily: 7,
+                keep_weekly: 4,
+                keep_monthly: 12,
+                last_run_at: None,
+                created_at: None,
+                updated_at: None,
+            }),
+        }
+    }
+
+    pub async fn update_policy(
+        &self,
+        school_id: &ObjectId,
+        update: UpdateBackupPolicy,
+        user: &AuthUserDto,
+    ) -> Result<BackupPolicy, AppError> {
+        let current = self.get_policy(school_id).await?;
+        let schedule = update.schedule.map(|s| s.trim().to_string()).unwrap_or(current.schedule);
+        CronSchedule::parse(&schedule).map_err(AppError::bad_request)?;
+        let keep_daily = update.keep_daily.unwrap_or(current.keep_daily);
+        let keep_weekly = update.keep_weekly.unwrap_or(current.keep_weekly);
+        let keep_monthly = update.keep_monthly.unwrap_or(current.keep_monthly);
+        if keep_daily < 0 || keep_weekly < 0 || keep_monthly < 0 {
+            return Err(AppError::bad_request("Retention counts cannot be negative"));
+        }
+        if keep_daily + keep_weekly + keep_monthly == 0 {
+            return Err(AppError::bad_request(
+                "Retention must keep at least one backup",
+            ));
         }
+        // Saving a policy turns scheduling on unless it is explicitly disabled
+        let enabled = update.enabled.unwrap_or(current.created_at.is_none() || current.enabled);
+
+        let row = sqlx::query(
+            r#"INSERT INTO school_backup_policies (school_id, enabled, schedule, keep_daily, keep_weekly, keep_monthly)
+               VALUES ($1,$2,$3,$4,$5,$6)
+               ON CONFLICT (school_id) DO UPDATE SET enabled = EXCLUDED.enabled, schedule = EXCLUDED.schedule,
+                 keep_daily = EXCLUDED.keep_daily, keep_weekly = EXCLUDED.keep_weekly, keep_monthly = EXCLUDED.keep_monthly
+               RETURNING school_id, enabled, schedule, keep_daily, keep_weekly, keep_monthly, last_run_at, created_at, updated_at"#,
+        )
+        .bind(school_id.to_hex())
+        .bind(enabled)
+        .bind(&schedule)
+        .bind(keep_daily)
+        .bind(keep_weekly)
+        .bind(keep_monthly)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let audit_service = AuditLogService::new(&self.pool);
+        audit_service.log_event(*school_id, user, "backup.policy.update", "backup_policy", *school_id, None, None, None).await.ok();
+
+        Self::row_to_policy(row)
+    }
 
-        let metadata = std::fs::metadata(file_path).map_err(|e| AppError { message: format!("Failed to get backup file size: {}", e) })?;
-        Ok(metadata.len() as i64)
+    pub async fn enabled_policies(&self) -> Result<Vec<BackupPolicy>, AppError> {
+        let rows = sqlx::query(&format!("{} WHERE enabled ORDER BY school_id", Self::policy_select_sql()))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_policy).collect()
+    }
+
+    /// Claim a schedule slot for a school. Returns false when the slot was
+    /// already taken, e.g. by another API instance.
+    pub async fn claim_schedule_slot(&self, school_id: &ObjectId, slot: DateTime<Utc>) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE school_backup_policies SET last_run_at = $2 \
+             WHERE school_id = $1 AND enabled AND (last_run_at IS NULL OR last_run_at < $2)",
+        )
+        .bind(school_id.to_hex())
+        .bind(slot)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(result.rows_affected() == 1)
     }
 
     pub async fn restore_backup(
@@ -193,14 +434,33 @@ impl BackupService {
         let backup = self.find_one(Some(backup_id), None).await?;
 
         if !matches!(backup.status, BackupStatus::Completed) {
-            return Err(AppError { message: "Cannot restore: backup is not completed".to_string() });
+            return Err(AppError::bad_request(
+                "Cannot restore: backup is not completed",
+            ));
         }
 
-        if !std::path::Path::new(&backup.file_path).exists() {
-            return Err(AppError { message: "Backup file not found".to_string() });
+        if !tokio::fs::try_exists(&backup.file_path).await.unwrap_or(false) {
+            return Err(AppError::not_found("Backup file not found"));
         }
 
-        let school_id = backup.school_id.ok_or(AppError { message: "Backup has no school_id".to_string() })?;
+        // Without a checksum the archive cannot be told apart from a modified one
+        let checksum = backup
+            .checksum
+            .clone()
+            .ok_or(AppError::bad_request("Backup has no checksum and cannot be verified"))?;
+
+        let school_id = backup
+            .school_id
+            .ok_or(AppError::bad_request("Backup has no school_id"))?;
+
+        // Fail fast on legacy mongodump archives or foreign files before the
+        // restore is queued; the checksum is verified again by the restore.
+        let manifest = backup_archive::read_manifest(Path::new(&backup.file_path)).await?;
+        if manifest.school_id != school_id.to_hex() {
+            return Err(AppError::bad_request(
+                "Backup belongs to a different school",
+            ));
+        }
 
         let ongoing: i64 = sqlx::query_scalar("SELECT count(*) FROM school_backups WHERE school_id = $1 AND status = 'InProgress'")
             .bind(school_id.to_hex())
@@ -209,11 +469,14 @@ impl BackupService {
             .map_err(Self::db_error)?;
 
         if ongoing > 0 {
-            return Err(AppError { message: "Another restore is already in progress for this school".to_string() });
+            return Err(AppError::conflict(
+                "Another restore is already in progress for this school",
+            ));
         }
 
         let restore_id = Self::new_id();
-        let created_by = ObjectId::parse_str(&user.id).map_err(|_| AppError { message: "Invalid user ID".to_string() })?;
+        let created_by =
+            ObjectId::parse_str(&user.id).map_err(|_| AppError::bad_request("Invalid user ID"))?;
 
         sqlx::query(
             r#"INSERT INTO school_backups (id, school_id, backup_name, backup_type, file_path, size_bytes, status, created_by, created_at)
@@ -238,10 +501,10 @@ impl BackupService {
 
         let pool_clone = self.pool.clone();
         let file_path = backup.file_path.clone();
-        let db_name = format!("school_{}", school_id.to_hex());
+        let school_hex = school_id.to_hex();
 
         actix_rt::spawn(async move {
-            let result = Self::perform_restore(&db_name, &file_path).await;
+            let result = backup_archive::restore_school(&pool_clone, &school_hex, Path::new(&file_path), &checksum).await;
             match result {
                 Ok(_) => {
                     let _ = sqlx::query("UPDATE school_backups SET status = 'Completed', completed_at = now() WHERE id = $1")
@@ -257,23 +520,6 @@ impl BackupService {
         Ok(restore_doc)
     }
 
-    async fn perform_restore(db_name: &str, file_path: &str) -> Result<(), AppError> {
-        let mongo_uri = std::env::var("MONGODB_URI").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
-        let output = Command::new("mongorestore")
-            .arg("--uri").arg(&mongo_uri)
-            .arg("--db").arg(db_name)
-            .arg("--archive").arg(file_path)
-            .arg("--gzip")
-            .arg("--drop")
-            .output()
-            .map_err(|e| AppError { message: format!("Failed to execute mongorestore: {}", e) })?;
-
-        if !output.status.success() {
-            return Err(AppError { message: format!("Restore failed: {}", String::from_utf8_lossy(&output.stderr)) });
-        }
-        Ok(())
-    }
-
     pub async fn find_one(
         &self,
         id: Option<&IdType>,
@@ -287,7 +533,7 @@ impl BackupService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_backup(r),
-            None => Err(AppError { message: "Backup not found".into() }),
+            None => Err(AppError::not_found("Backup not found")),
         }
     }
 
@@ -342,8 +588,8 @@ impl BackupService {
 
     pub async fn delete(&self, id: &IdType) -> Result<SchoolBackup, AppError> {
         let backup = self.find_one(Some(id), None).await?;
-        if std::path::Path::new(&backup.file_path).exists() {
-            std::fs::remove_file(&backup.file_path).ok();
+        if tokio::fs::try_exists(&backup.file_path).await.unwrap_or(false) {
+            tokio::fs::remove_file(&backup.file_path).await.ok();
         }
         sqlx::query("DELETE FROM school_backups WHERE id = $1")
             .bind(Self::id_to_string(id)?)
@@ -0,0 +1,1180 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
+
+use crate::{
+    domain::{
+        auth_user::AuthUserDto,
+        bulk_import::{
+            ImportKind, ImportOptions, ImportReport, ImportRowResult, ImportRowStatus,
+            FIELD_ALIASES, MAX_IMPORT_ROWS,
+        },
+        teacher::parse_teacher_type,
+    },
+    errors::{AppError, ErrorCode},
+    services::student_service::{StudentQuery, StudentService},
+    utils::{
+        email::is_valid_email,
+        names::is_valid_name,
+        object_id::ObjectId,
+        spreadsheet::{read_table, Table},
+    },
+};
+
+/// Matched in this order, the same fields `/students/match` filters on
+const STUDENT_MATCH_FIELDS: [&str; 3] = ["email", "registration_number", "phone"];
+
+struct ParentRow {
+    name: String,
+    email: Option<String>,
+    phone: Option<String>,
+    relationship: Option<String>,
+}
+
+struct StudentRow {
+    name: String,
+    email: String,
+    phone: Option<String>,
+    gender: Option<String>,
+    date_of_birth: Option<NaiveDate>,
+    registration_number: Option<String>,
+    admission_year: Option<i32>,
+    class_id: Option<String>,
+    parent: Option<ParentRow>,
+}
+
+struct TeacherRow {
+    name: String,
+    email: String,
+    phone: Option<String>,
+    gender: Option<String>,
+    teacher_type: String,
+    class_id: Option<String>,
+}
+
+enum ValidRow {
+    Student(StudentRow),
+    Teacher(TeacherRow),
+}
+
+struct ColumnMapping {
+    /// Column index each mapped field is read from
+    columns: HashMap<&'static str, usize>,
+    /// Field to the header text, as reported back
+    used: BTreeMap<String, String>,
+    unmapped: Vec<String>,
+}
+
+/// Header text reduced to lowercase words joined by underscores
+fn normalize_header(header: &str) -> String {
+    header
+        .trim()
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn parse_gender(raw: &str) -> Result<String, String> {
+    match raw.trim().to_lowercase().as_str() {
+        "m" | "male" | "boy" => Ok("MALE".into()),
+        "f" | "female" | "girl" => Ok("FEMALE".into()),
+        "o" | "other" => Ok("OTHER".into()),
+        _ => Err(format!("Unknown gender: {}", raw)),
+    }
+}
+
+/// ISO dates, day-first slashed dates, or an Excel serial day number
+fn parse_date(raw: &str) -> Result<NaiveDate, String> {
+    let raw = raw.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%d/%m/%Y") {
+        return Ok(date);
+    }
+    if let Ok(serial) = raw.parse::<f64>() {
+        if (1.0..=100_000.0).contains(&serial) {
+            let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).unwrap_or_default();
+            return Ok(epoch + Duration::days(serial.trunc() as i64));
+        }
+    }
+    Err(format!("Invalid date: {} (use YYYY-MM-DD)", raw))
+}
+
+fn optional(value: Option<&String>) -> Option<String> {
+    value
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Phone numbers read from spreadsheets often come back as "788123456.0"
+fn clean_phone(raw: String) -> String {
+    raw.strip_suffix(".0").map(str::to_string).unwrap_or(raw)
+}
+
+pub struct BulkImportService {
+    pub pool: PgPool,
+}
+
+impl BulkImportService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn resolve_columns(
+        kind: ImportKind,
+        mapping: &BTreeMap<String, String>,
+        headers: &[String],
+    ) -> Result<ColumnMapping, AppError> {
+        let mut columns = HashMap::new();
+        for field in kind.fields() {
+            let index = match mapping.get(*field) {
+                Some(header) => Some(
+                    headers
+                        .iter()
+                        .position(|h| h.eq_ignore_ascii_case(header.trim()))
+                        .ok_or_else(|| {
+                            AppError::not_found(format!(
+                                "Mapped column not found in file: {}",
+                                header
+                            ))
+                        })?,
+                ),
+                None => headers.iter().position(|header| {
+                    let normalized = normalize_header(header);
+                    normalized == *field
+                        || FIELD_ALIASES
+                            .iter()
+                            .any(|(alias, target)| target == field && normalized == *alias)
+                }),
+            };
+            if let Some(index) = index {
+                columns.insert(*field, index);
+            }
+        }
+
+        if let Some(missing) = kind
+            .required_fields()
+            .iter()
+            .find(|field| !columns.contains_key(**field))
+        {
+            return Err(AppError::bad_request(format!(
+                "No column found for required field: {}",
+                missing
+            )));
+        }
+
+        let used: BTreeMap<String, String> = columns
+            .iter()
+            .map(|(field, index)| (field.to_string(), headers[*index].clone()))
+            .collect();
+        let unmapped = headers
+            .iter()
+            .enumerate()
+            .filter(|(index, header)| {
+                !header.is_empty() && !columns.values().any(|used| used == index)
+            })
+            .map(|(_, header)| header.clone())
+            .collect();
+
+        Ok(ColumnMapping {
+            columns,
+            used,
+            unmapped,
+        })
+    }
+
+    /// Class ids keyed by lowercased id, name, username and code
+    async fn class_lookup(&self, school_id: &str) -> Result<HashMap<String, String>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, username, code
+            FROM classes
+            WHERE school_id = $1 AND deleted_at IS NULL AND is_active = true
+            "#,
+        )
+        .bind(school_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut lookup = HashMap::new();
+        for row in rows {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            for column in ["name", "username", "code"] {
+                let value: Option<String> = row.try_get(column).map_err(Self::db_error)?;
+                if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+                    lookup
+                        .entry(value.trim().to_lowercase())
+                        .or_insert_with(|| id.clone());
+                }
+            }
+            lookup.insert(id.to_lowercase(), id);
+        }
+        Ok(lookup)
+    }
+
+    fn validate_row(
+        kind: ImportKind,
+        values: &HashMap<&'static str, String>,
+        classes: &HashMap<String, String>,
+        default_class: Option<&String>,
+    ) -> Result<ValidRow, Vec<String>> {
+        let mut errors = Vec::new();
+        let get = |field: &str| optional(values.get(field));
+
+        let name = match get("name") {
+            Some(name) => is_valid_name(&name).unwrap_or_else(|e| {
+                errors.push(e);
+                name
+            }),
+            None => {
+                errors.push("name is required".into());
+                String::new()
+            }
+        };
+        let email = match get("email") {
+            Some(email) => {
+                if let Err(e) = is_valid_email(&email) {
+                    errors.push(e);
+                }
+                email.to_lowercase()
+            }
+            None => {
+                errors.push("email is required".into());
+                String::new()
+            }
+        };
+        let phone = get("phone").map(clean_phone);
+        let gender =
+            get("gender").and_then(|raw| parse_gender(&raw).map_err(|e| errors.push(e)).ok());
+        let class_id = match get("class") {
+            Some(class) => match classes.get(&class.to_lowercase()) {
+                Some(id) => Some(id.clone()),
+                None => {
+                    errors.push(format!("Class not found: {}", class));
+                    None
+                }
+            },
+            None => default_class.cloned(),
+        };
+
+        let row = match kind {
+            ImportKind::Teacher => ValidRow::Teacher(TeacherRow {
+                name,
+                email,
+                phone,
+                gender,
+                teacher_type: parse_teacher_type(&get("teacher_type").unwrap_or_default())
+                    .to_string(),
+                class_id,
+            }),
+            ImportKind::Student => {
+                let date_of_birth = get("date_of_birth")
+                    .and_then(|raw| parse_date(&raw).map_err(|e| errors.push(e)).ok());
+                if date_of_birth.is_some_and(|dob| dob > Utc::now().date_naive()) {
+                    errors.push("date_of_birth is in the future".into());
+                }
+                let admission_year = get("admission_year").and_then(|raw| {
+                    raw.trim_end_matches(".0")
+                        .parse::<i32>()
+                        .ok()
+                        .filter(|year| (1900..=2100).contains(year))
+                        .or_else(|| {
+                            errors.push(format!("Invalid admission_year: {}", raw));
+                            None
+                        })
+                });
+
+                let parent_email = get("parent_email").map(|email| email.to_lowercase());
+                let parent_phone = get("parent_phone").map(clean_phone);
+                let parent = match get("parent_name") {
+                    Some(parent_name) => {
+                        let parent_name = is_valid_name(&parent_name).unwrap_or_else(|e| {
+                            errors.push(format!("parent_name: {}", e));
+                            parent_name
+                        });
+                        if let Some(parent_email) = &parent_email {
+                            if let Err(e) = is_valid_email(parent_email) {
+                                errors.push(format!("parent_email: {}", e));
+                            }
+                        }
+                        if parent_email.is_none() && parent_phone.is_none() {
+                            errors.push("parent_email or parent_phone is required".into());
+                        }
+                        Some(ParentRow {
+                            name: parent_name,
+                            email: parent_email,
+                            phone: parent_phone,
+                            relationship: get("parent_relationship"),
+                        })
+                    }
+                    None if parent_email.is_some() || parent_phone.is_some() => {
+                        errors.push("parent_name is required with parent contacts".into());
+                        None
+                    }
+                    None => None,
+                };
+
+                ValidRow::Student(StudentRow {
+                    name,
+                    email,
+                    phone,
+                    gender,
+                    date_of_birth,
+                    registration_number: get("registration_number"),
+                    admission_year,
+                    class_id,
+                    parent,
+                })
+            }
+        };
+
+        if errors.is_empty() {
+            Ok(row)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Looks the student up field by field the way `/students/match` does
+    async fn existing_student(
+        &self,
+        school_id: &str,
+        row: &StudentRow,
+    ) -> Result<Option<(ObjectId, &'static str)>, AppError> {
+        let students = StudentService::new(&self.pool);
+        for field in STUDENT_MATCH_FIELDS {
+            let value = match field {
+                "email" => Some(row.email.clone()),
+                "registration_number" => row.registration_number.clone(),
+                _ => row.phone.clone(),
+            };
+            let Some(value) = value else {
+                continue;
+            };
+
+            let query = StudentQuery {
+                school_id: Some(school_id.to_string()),
+                field_values: vec![(field.to_string(), value)],
+                ..StudentQuery::default()
+            };
+            match students.find_one(None, Some(query)).await {
+                Ok(student) => return Ok(student.id.map(|id| (id, field))),
+                Err(err) if err.code == ErrorCode::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(None)
+    }
+
+    async fn existing_teacher(
+        &self,
+        school_id: &str,
+        email: &str,
+    ) -> Result<Option<ObjectId>, AppError> {
+        let id = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT id FROM teachers
+            WHERE school_id = $1 AND lower(coalesce(email, '')) = lower($2) AND deleted_at IS NULL
+            LIMIT 1
+            "#,
+        )
+        .bind(school_id)
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        id.map(|id| Self::parse_oid(&id, "teacher_id")).transpose()
+    }
+
+    async fn account_exists(&self, email: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE lower(email) = lower($1))")
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)
+    }
+
+    async fn school_parent_exists(
+        &self,
+        school_id: &str,
+        parent: &ParentRow,
+    ) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM parents
+              WHERE school_id = $1 AND deleted_at IS NULL
+                AND ((email IS NOT NULL AND lower(email) = lower($2)) OR (phone IS NOT NULL AND phone = $3))
+            )
+            "#,
+        )
+        .bind(school_id)
+        .bind(&parent.email)
+        .bind(&parent.phone)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Why a new row would land on an account that already exists. Imports
+    /// only create accounts; existing people join through their own account.
+    async fn account_conflict(
+        &self,
+        school_id: &str,
+        row: &ValidRow,
+    ) -> Result<Option<String>, AppError> {
+        let (email, parent) = match row {
+            ValidRow::Student(student) => (&student.email, student.parent.as_ref()),
+            ValidRow::Teacher(teacher) => (&teacher.email, None),
+        };
+        if self.account_exists(email).await? {
+            return Ok(Some(
+                "An account already uses this email; invite the person to the school instead"
+                    .to_string(),
+            ));
+        }
+
+        // A parent the school already has is linked, not created
+        if let Some(parent) = parent {
+            if let Some(parent_email) = &parent.email {
+                if self.account_exists(parent_email).await?
+                    && !self.school_parent_exists(school_id, parent).await?
+                {
+                    return Ok(Some(
+                        "An account already uses the parent email; link that parent from their account instead"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Validates every row and, unless it is a dry run or any row is
+    /// invalid, creates all new rows in one transaction. Duplicates of
+    /// people already in the school, and rows whose email already has an
+    /// account, are reported and skipped.
+    pub async fn run(
+        &self,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        options: ImportOptions,
+        file_name: Option<String>,
+        bytes: &[u8],
+    ) -> Result<ImportReport, AppError> {
+        options.validate().map_err(AppError::validation)?;
+        let Table { headers, rows } = read_table(bytes).map_err(AppError::bad_request)?;
+        if rows.is_empty() {
+            return Err(AppError::bad_request("The file has no data rows"));
+        }
+        if rows.len() > MAX_IMPORT_ROWS {
+            return Err(AppError::bad_request(format!(
+                "Too many rows ({}); split the file into batches of {}",
+                rows.len(),
+                MAX_IMPORT_ROWS
+            )));
+        }
+
+        let school = school_id.to_hex();
+        let ColumnMapping {
+            columns,
+            used: mapping,
+            unmapped: unmapped_columns,
+        } = Self::resolve_columns(options.kind, &options.mapping, &headers)?;
+        let classes = self.class_lookup(&school).await?;
+        let default_class = match &options.class_id {
+            Some(class_id) => Some(
+                classes
+                    .get(&class_id.to_lowercase())
+                    .cloned()
+                    .ok_or_else(|| AppError::not_found("Class not found in this school"))?,
+            ),
+            None => None,
+        };
+
+        let mut results = Vec::with_capacity(rows.len());
+        let mut valid = Vec::new();
+        let mut seen: HashMap<String, u32> = HashMap::new();
+
+        for (index, cells) in rows.iter().enumerate() {
+            let row_number = index as u32 + 2;
+            let values: HashMap<&'static str, String> = columns
+                .iter()
+                .map(|(field, column)| (*field, cells[*column].clone()))
+                .collect();
+            let mut result = ImportRowResult {
+                row_number,
+                status: ImportRowStatus::Ready,
+                name: optional(values.get("name")),
+                email: optional(values.get("email")),
+                entity_id: None,
+                parent_id: None,
+                errors: Vec::new(),
+            };
+
+            let row =
+                match Self::validate_row(options.kind, &values, &classes, default_class.as_ref()) {
+                    Ok(row) => row,
+                    Err(errors) => {
+                        result.status = ImportRowStatus::Invalid;
+                        result.errors = errors;
+                        results.push(result);
+                        continue;
+                    }
+                };
+
+            // Repeats inside the file itself
+            let keys = match &row {
+                ValidRow::Student(student) => vec![
+                    Some(format!("email:{}", student.email)),
+                    student
+                        .registration_number
+                        .as_ref()
+                        .map(|reg| format!("registration_number:{}", reg.to_lowercase())),
+                ],
+                ValidRow::Teacher(teacher) => vec![Some(format!("email:{}", teacher.email))],
+            };
+            if let Some((key, first)) = keys
+                .iter()
+                .flatten()
+                .find_map(|key| seen.get(key).map(|first| (key, *first)))
+            {
+                result.status = ImportRowStatus::Invalid;
+                result.errors.push(format!(
+                    "{} repeats row {}",
+                    key.split(':').next().unwrap_or_default(),
+                    first
+                ));
+                results.push(result);
+                continue;
+            }
+            for key in keys.into_iter().flatten() {
+                seen.insert(key, row_number);
+            }
+
+            let existing = match &row {
+                ValidRow::Student(student) => self
+                    .existing_student(&school, student)
+                    .await?
+                    .map(|(id, field)| (id, format!("Matches an existing student by {}", field))),
+                ValidRow::Teacher(teacher) => self
+                    .existing_teacher(&school, &teacher.email)
+                    .await?
+                    .map(|id| (id, "Matches an existing teacher by email".to_string())),
+            };
+            if let Some((id, reason)) = existing {
+                result.status = ImportRowStatus::Duplicate;
+                result.entity_id = Some(id);
+                result.errors.push(reason);
+                results.push(result);
+                continue;
+            }
+            if let Some(reason) = self.account_conflict(&school, &row).await? {
+                result.status = ImportRowStatus::Conflict;
+                result.errors.push(reason);
+                results.push(result);
+                continue;
+            }
+
+            valid.push((results.len(), row));
+            results.push(result);
+        }
+
+        let count = |status: ImportRowStatus| {
+            results.iter().filter(|row| row.status == status).count() as u32
+        };
+        let mut report = ImportReport {
+            id: None,
+            school_id: *school_id,
+            kind: options.kind,
+            file_name,
+            dry_run: options.dry_run,
+            committed: false,
+            mapping,
+            unmapped_columns,
+            total_rows: results.len() as u32,
+            ready_count: count(ImportRowStatus::Ready),
+            created_count: 0,
+            duplicate_count: count(ImportRowStatus::Duplicate),
+            conflict_count: count(ImportRowStatus::Conflict),
+            error_count: count(ImportRowStatus::Invalid),
+            rows: results,
+            created_by: ObjectId::parse_str(&user.id).ok(),
+            created_at: Some(Utc::now()),
+        };
+
+        if options.dry_run || report.error_count > 0 {
+            return Ok(report);
+        }
+
+        self.commit(&mut report, valid).await?;
+        Ok(report)
+    }
+
+    async fn commit(
+        &self,
+        report: &mut ImportReport,
+        rows: Vec<(usize, ValidRow)>,
+    ) -> Result<(), AppError> {
+        let school_id = report.school_id.to_hex();
+        let creator_id = report.created_by.map(|id| id.to_hex());
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+
+        for (index, row) in rows {
+            let (entity_id, parent_id) = match row {
+                ValidRow::Student(student) => {
+                    Self::create_student(&mut tx, &school_id, creator_id.as_deref(), &student)
+                        .await?
+                }
+                ValidRow::Teacher(teacher) => (
+                    Self::create_teacher(&mut tx, &school_id, creator_id.as_deref(), &teacher)
+                        .await?,
+                    None,
+                ),
+            };
+            let result = &mut report.rows[index];
+            result.status = ImportRowStatus::Created;
+            result.entity_id = Some(Self::parse_oid(&entity_id, "entity_id")?);
+            result.parent_id = parent_id
+                .map(|id| Self::parse_oid(&id, "parent_id"))
+                .transpose()?;
+        }
+
+        report.created_count = report.ready_count;
+        report.ready_count = 0;
+        let import_id = Self::new_id();
+        Self::save_report(&mut tx, &import_id, report).await?;
+        tx.commit().await.map_err(Self::db_error)?;
+
+        report.id = Some(Self::parse_oid(&import_id, "id")?);
+        report.committed = true;
+        Ok(())
+    }
+
+    /// Account for a new row. It has no password; the person chooses one
+    /// with `POST /auth/password/forgot`. Rows whose email already has an
+    /// account were reported as conflicts, so this never reuses one.
+    async fn create_user(
+        tx: &mut Transaction<'static, Postgres>,
+        school_id: &str,
+        name: &str,
+        email: &str,
+        phone: Option<&str>,
+        gender: Option<&str>,
+        role: &str,
+    ) -> Result<String, AppError> {
+        let id = Self::new_id();
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, name, email, role, phone, gender, current_school_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&id)
+        .bind(name)
+        .bind(email)
+        .bind(role)
+        .bind(phone)
+        .bind(gender)
+        .bind(school_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(id)
+    }
+
+    async fn add_membership(
+        tx: &mut Transaction<'static, Postgres>,
+        school_id: &str,
+        user_id: &str,
+        school_user_id: &str,
+        member_type: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO school_memberships (id, school_id, user_id, school_user_id, member_type, status, joined_at)
+            VALUES ($1, $2, $3, $4, $5, 'active', now())
+            ON CONFLICT (school_id, user_id, member_type)
+            DO UPDATE SET school_user_id = EXCLUDED.school_user_id, status = 'active', ended_at = NULL, updated_at = now()
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(school_id)
+        .bind(user_id)
+        .bind(school_user_id)
+        .bind(member_type)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_role_assignments (id, user_id, school_id, role, scope, starts_at)
+            VALUES ($1, $2, $3, $4, 'school', now())
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(user_id)
+        .bind(school_id)
+        .bind(member_type)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(())
+    }
+
+    /// Returns the enrollment id and the linked parent's id
+    async fn create_student(
+        tx: &mut Transaction<'static, Postgres>,
+        school_id: &str,
+        creator_id: Option<&str>,
+        row: &StudentRow,
+    ) -> Result<(String, Option<String>), AppError> {
+        let user_id = Self::create_user(
+            tx,
+            school_id,
+            &row.name,
+            &row.email,
+            row.phone.as_deref(),
+            row.gender.as_deref(),
+            "STUDENT",
+        )
+        .await?;
+
+        let existing_profile = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT id FROM student_profiles
+            WHERE (user_id = $1 OR lower(email) = lower($2)) AND deleted_at IS NULL
+            ORDER BY (user_id = $1) DESC, created_at ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(&user_id)
+        .bind(&row.email)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        let profile_id = match existing_profile {
+            Some(id) => id,
+            None => {
+                let id = Self::new_id();
+                sqlx::query(
+                    r#"
+                    INSERT INTO student_profiles (
+                      id, user_id, name, email, phone, gender,
+                      date_of_birth_year, date_of_birth_month, date_of_birth_day
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    "#,
+                )
+                .bind(&id)
+                .bind(&user_id)
+                .bind(&row.name)
+                .bind(&row.email)
+                .bind(&row.phone)
+                .bind(&row.gender)
+                .bind(row.date_of_birth.map(|dob| dob.year()))
+                .bind(row.date_of_birth.map(|dob| dob.month() as i32))
+                .bind(row.date_of_birth.map(|dob| dob.day() as i32))
+                .execute(&mut **tx)
+                .await
+                .map_err(Self::db_error)?;
+                id
+            }
+        };
+
+        let enrollment_id = Self::new_id();
+        sqlx::query(
+            r#"
+            INSERT INTO student_school_enrollments (
+              id, student_id, school_id, class_id, source_student_id,
+              registration_number, admission_year, status, is_active, creator_id
+            )
+            VALUES ($1, $2, $3, $4, $1, $5, $6, 'active', true, $7)
+            "#,
+        )
+        .bind(&enrollment_id)
+        .bind(&profile_id)
+        .bind(school_id)
+        .bind(&row.class_id)
+        .bind(&row.registration_number)
+        .bind(row.admission_year)
+        .bind(creator_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        Self::add_membership(tx, school_id, &user_id, &enrollment_id, "STUDENT").await?;
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET current_school_id = coalesce(current_school_id, $1), current_school_user_id = $2, updated_at = now()
+            WHERE id = $3
+            "#,
+        )
+        .bind(school_id)
+        .bind(&enrollment_id)
+        .bind(&user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        let parent_id = match &row.parent {
+            Some(parent) => Some(Self::link_parent(tx, school_id, &profile_id, parent).await?),
+            None => None,
+        };
+
+        Ok((enrollment_id, parent_id))
+    }
+
+    /// Reuses the school's parent with the same email or phone, so siblings
+    /// on separate rows share one parent record
+    async fn link_parent(
+        tx: &mut Transaction<'static, Postgres>,
+        school_id: &str,
+        profile_id: &str,
+        parent: &ParentRow,
+    ) -> Result<String, AppError> {
+        let existing = sqlx::query(
+            r#"
+            SELECT id, user_id FROM parents
+            WHERE school_id = $1 AND deleted_at IS NULL
+              AND ((email IS NOT NULL AND lower(email) = lower($2)) OR (phone IS NOT NULL AND phone = $3))
+            ORDER BY created_at ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(school_id)
+        .bind(&parent.email)
+        .bind(&parent.phone)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        let (parent_id, user_id) = match existing {
+            Some(row) => (
+                row.try_get::<String, _>("id").map_err(Self::db_error)?,
+                row.try_get::<Option<String>, _>("user_id")
+                    .map_err(Self::db_error)?,
+            ),
+            None => {
+                let user_id = match &parent.email {
+                    Some(email) => Some(
+                        Self::create_user(
+                            tx,
+                            school_id,
+                            &parent.name,
+                            email,
+                            parent.phone.as_deref(),
+                            None,
+                            "PARENT",
+                        )
+                        .await?,
+                    ),
+                    None => None,
+                };
+                let id = Self::new_id();
+                sqlx::query(
+                    r#"
+                    INSERT INTO parents (id, school_id, user_id, name, email, phone, relationship, status, is_active)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, 'active', true)
+                    "#,
+                )
+                .bind(&id)
+                .bind(school_id)
+                .bind(&user_id)
+                .bind(&parent.name)
+                .bind(&parent.email)
+                .bind(&parent.phone)
+                .bind(&parent.relationship)
+                .execute(&mut **tx)
+                .await
+                .map_err(Self::db_error)?;
+
+                if let Some(user_id) = &user_id {
+                    Self::add_membership(tx, school_id, user_id, &id, "PARENT").await?;
+                }
+                (id, user_id)
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO parent_student_links (
+              id, parent_id, parent_user_id, student_id, school_id, relationship, status, starts_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, 'active', now())
+            ON CONFLICT (parent_id, student_id, school_id)
+            DO UPDATE SET status = 'active', relationship = EXCLUDED.relationship, updated_at = now()
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(&parent_id)
+        .bind(&user_id)
+        .bind(profile_id)
+        .bind(school_id)
+        .bind(&parent.relationship)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(parent_id)
+    }
+
+    async fn create_teacher(
+        tx: &mut Transaction<'static, Postgres>,
+        school_id: &str,
+        creator_id: Option<&str>,
+        row: &TeacherRow,
+    ) -> Result<String, AppError> {
+        let user_id = Self::create_user(
+            tx,
+            school_id,
+            &row.name,
+            &row.email,
+            row.phone.as_deref(),
+            row.gender.as_deref(),
+            "TEACHER",
+        )
+        .await?;
+
+        let id = Self::new_id();
+        sqlx::query(
+            r#"
+            INSERT INTO teachers (
+              id, school_id, user_id, creator_id, name, email, phone, gender, teacher_type, is_active
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, true)
+            "#,
+        )
+        .bind(&id)
+        .bind(school_id)
+        .bind(&user_id)
+        .bind(creator_id)
+        .bind(&row.name)
+        .bind(&row.email)
+        .bind(&row.phone)
+        .bind(&row.gender)
+        .bind(&row.teacher_type)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        if let Some(class_id) = &row.class_id {
+            sqlx::query(
+                r#"
+                INSERT INTO teacher_classes (id, teacher_id, class_id, school_id, position)
+                VALUES ($1, $2, $3, $4, 0)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(&id)
+            .bind(class_id)
+            .bind(school_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        Self::add_membership(tx, school_id, &user_id, &id, "TEACHER").await?;
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET current_school_id = coalesce(current_school_id, $1), updated_at = now()
+            WHERE id = $2
+            "#,
+        )
+        .bind(school_id)
+        .bind(&user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(id)
+    }
+
+    async fn save_report(
+        tx: &mut Transaction<'static, Postgres>,
+        import_id: &str,
+        report: &ImportReport,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO bulk_imports (
+              id, school_id, kind, file_name, total_rows, created_count,
+              duplicate_count, conflict_count, error_count, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(import_id)
+        .bind(report.school_id.to_hex())
+        .bind(report.kind.as_str())
+        .bind(&report.file_name)
+        .bind(report.total_rows as i32)
+        .bind(report.created_count as i32)
+        .bind(report.duplicate_count as i32)
+        .bind(report.conflict_count as i32)
+        .bind(report.error_count as i32)
+        .bind(report.created_by.map(|id| id.to_hex()))
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        for row in &report.rows {
+            sqlx::query(
+                r#"
+                INSERT INTO bulk_import_rows (
+                  id, import_id, row_number, status, name, email, entity_id, parent_id, errors
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+            )
+            .bind(Self::new_id())
+            .bind(import_id)
+            .bind(row.row_number as i32)
+            .bind(row.status.as_str())
+            .bind(&row.name)
+            .bind(&row.email)
+            .bind(row.entity_id.map(|id| id.to_hex()))
+            .bind(row.parent_id.map(|id| id.to_hex()))
+            .bind(&row.errors)
+            .execute(&mut **tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn row_to_report(row: &PgRow) -> Result<ImportReport, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let school_id: String = row.try_get("school_id").map_err(Self::db_error)?;
+        let kind: String = row.try_get("kind").map_err(Self::db_error)?;
+        let created_by: Option<String> = row.try_get("created_by").map_err(Self::db_error)?;
+        let count = |column: &str| -> Result<u32, AppError> {
+            row.try_get::<i32, _>(column)
+                .map(|value| value as u32)
+                .map_err(Self::db_error)
+        };
+
+        Ok(ImportReport {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid(&school_id, "school_id")?,
+            kind: if kind == "Teacher" {
+                ImportKind::Teacher
+            } else {
+                ImportKind::Student
+            },
+            file_name: row.try_get("file_name").map_err(Self::db_error)?,
+            dry_run: false,
+            committed: true,
+            mapping: BTreeMap::new(),
+            unmapped_columns: Vec::new(),
+            total_rows: count("total_rows")?,
+            ready_count: 0,
+            created_count: count("created_count")?,
+            duplicate_count: count("duplicate_count")?,
+            conflict_count: count("conflict_count")?,
+            error_count: count("error_count")?,
+            rows: Vec::new(),
+            created_by: created_by
+                .map(|id| Self::parse_oid(&id, "created_by"))
+                .transpose()?,
+            created_at: row.try_get("created_at").ok(),
+        })
+    }
+
+    /// Committed imports of the school, newest first, without their rows
+    pub async fn list(&self, school_id: &ObjectId) -> Result<Vec<ImportReport>, AppError> {
+        let rows = sqlx::query(
+            "SELECT * FROM bulk_imports WHERE school_id = $1 ORDER BY created_at DESC LIMIT 100",
+        )
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.iter().map(Self::row_to_report).collect()
+    }
+
+    pub async fn find(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<ImportReport, AppError> {
+        let row = sqlx::query("SELECT * FROM bulk_imports WHERE id = $1 AND school_id = $2")
+            .bind(id.to_hex())
+            .bind(school_id.to_hex())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?
+            .ok_or_else(|| AppError::not_found("Import not found"))?;
+        let mut report = Self::row_to_report(&row)?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT row_number, status, name, email, entity_id, parent_id, errors
+            FROM bulk_import_rows
+            WHERE import_id = $1
+            ORDER BY row_number
+            "#,
+        )
+        .bind(id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        for row in rows {
+            let status: String = row.try_get("status").map_err(Self::db_error)?;
+            let entity_id: Option<String> = row.try_get("entity_id").map_err(Self::db_error)?;
+            let parent_id: Option<String> = row.try_get("parent_id").map_err(Self::db_error)?;
+            report.rows.push(ImportRowResult {
+                row_number: row
+                    .try_get::<i32, _>("row_number")
+                    .map_err(Self::db_error)? as u32,
+                status: match status.as_str() {
+                    "Created" => ImportRowStatus::Created,
+                    "Duplicate" => ImportRowStatus::Duplicate,
+                    "Conflict" => ImportRowStatus::Conflict,
+                    _ => ImportRowStatus::Invalid,
+                },
+                name: row.try_get("name").map_err(Self::db_error)?,
+                email: row.try_get("email").map_err(Self::db_error)?,
+                entity_id: entity_id
+                    .map(|id| Self::parse_oid(&id, "entity_id"))
+                    .transpose()?,
+                parent_id: parent_id
+                    .map(|id| Self::parse_oid(&id, "parent_id"))
+                    .transpose()?,
+                errors: row.try_get("errors").map_err(Self::db_error)?,
+            });
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+use crate::{
+    domain::{
+        calendar_feed::{CalendarFeed, CreateCalendarFeed},
+        class_timetable::PeriodType,
+        school_timetable::DaySpecialType,
+    },
+    errors::AppError,
+    services::{
+        class_timetable_service::ClassTimetableService,
+        education_year_service::{EducationYearQuery, EducationYearService},
+        timetable_validation_service::TimetableValidationService,
+    },
+    utils::{
+        ical::{IcsCalendar, IcsEvent, IcsTime},
+        object_id::ObjectId,
+    },
+};
+
+const UID_DOMAIN: &str = "space-together";
+/// How far back exams and assignments reach when the school has no current year
+const FALLBACK_HISTORY_DAYS: i64 = 365;
+
+struct ClassSubjectInfo {
+    name: String,
+    teacher_id: Option<String>,
+}
+
+pub struct CalendarFeedService {
+    pub pool: PgPool,
+}
+
+impl CalendarFeedService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn hash_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    fn row_to_feed(row: PgRow) -> Result<CalendarFeed, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let user_id: String = row.try_get("user_id").map_err(Self::db_error)?;
+        let school_id: String = row.try_get("school_id").map_err(Self::db_error)?;
+        Ok(CalendarFeed {
+            id: Some(Self::parse_oid(&id, "id")?),
+            user_id: Self::parse_oid(&user_id, "user_id")?,
+            school_id: Self::parse_oid(&school_id, "school_id")?,
+            label: row.try_get("label").map_err(Self::db_error)?,
+            last_used_at: row.try_get("last_used_at").map_err(Self::db_error)?,
+            revoked_at: row.try_get("revoked_at").map_err(Self::db_error)?,
+            created_at: row.try_get("created_at").ok(),
+        })
+    }
+
+    const SELECT_SQL: &'static str =
+        "SELECT id, user_id, school_id, label, last_used_at, revoked_at, created_at \
+         FROM calendar_feed_tokens";
+
+    /// Create a feed and return it with its token. Only the token's hash is kept.
+    pub async fn create(
+        &self,
+        user_id: &ObjectId,
+        school_id: &ObjectId,
+        dto: CreateCalendarFeed,
+    ) -> Result<(CalendarFeed, String), AppError> {
+        let token = hex::encode(rand::random::<[u8; 32]>());
+        let label = dto
+            .label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty());
+
+        let row = sqlx::query(
+            "INSERT INTO calendar_feed_tokens (id, user_id, school_id, token_hash, label) \
+             VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id, user_id, school_id, label, last_used_at, revoked_at, created_at",
+        )
+        .bind(Self::new_id())
+        .bind(user_id.to_hex())
+        .bind(school_id.to_hex())
+        .bind(Self::hash_token(&token))
+        .bind(&label)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok((Self::row_to_feed(row)?, token))
+    }
+
+    pub async fn list(&self, user_id: &ObjectId) -> Result<Vec<CalendarFeed>, AppError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE user_id = $1 ORDER BY created_at DESC",
+            Self::SELECT_SQL
+        ))
+        .bind(user_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_feed).collect()
+    }
+
+    pub async fn revoke(
+        &self,
+        user_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<CalendarFeed, AppError> {
+        let row = sqlx::query(
+            "UPDATE calendar_feed_tokens SET revoked_at = coalesce(revoked_at, now()) \
+             WHERE id = $1 AND user_id = $2 \
+             RETURNING id, user_id, school_id, label, last_used_at, revoked_at, created_at",
+        )
+        .bind(id.to_hex())
+        .bind(user_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_feed(row),
+            None => Err(AppError::not_found("Calendar feed not found")),
+        }
+    }
+
+    /// Look up a live feed by its token and record the access.
+    pub async fn resolve(&self, token: &str) -> Result<CalendarFeed, AppError> {
+        let row = sqlx::query(
+            "UPDATE calendar_feed_tokens SET last_used_at = now() \
+             WHERE token_hash = $1 AND revoked_at IS NULL \
+             RETURNING id, user_id, school_id, label, last_used_at, revoked_at, created_at",
+        )
+        .bind(Self::hash_token(token))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_feed(row),
+            None => Err(AppError::not_found("Calendar feed not found")),
+        }
+    }
+
+    async fn learner_class_ids(
+        &self,
+        user_id: &str,
+        school_id: &str,
+    ) -> Result<HashSet<String>, AppError> {
+        let rows: Vec<Option<String>> = sqlx::query_scalar(
+            r#"SELECT DISTINCT unnest(ARRAY[sse.class_id, sse.subclass_id])
+               FROM student_school_enrollments sse
+               JOIN student_profiles sp ON sp.id = sse.student_id
+               WHERE sse.school_id = $2 AND sse.is_active AND sse.deleted_at IS NULL
+                 AND (
+                   sp.user_id = $1
+                   OR sse.student_id IN (
+                     SELECT student_id FROM parent_student_links
+                     WHERE parent_user_id = $1 AND status = 'active'
+                   )
+                 )"#,
+        )
+        .bind(user_id)
+        .bind(school_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(rows.into_iter().flatten().collect())
+    }
+
+    /// Build the feed's calendar: weekly lessons bounded by each term, term
+    /// boundaries, school events, exams and assignment due dates.
+    pub async fn render(&self, feed: &CalendarFeed) -> Result<String, AppError> {
+        let school_hex = feed.school_id.to_hex();
+        let user_hex = feed.user_id.to_hex();
+
+        let school_name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM schools WHERE id = $1")
+                .bind(&school_hex)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::db_error)?;
+        let mut calendar =
+            IcsCalendar::new(school_name.unwrap_or_else(|| "School calendar".to_string()));
+
+        let learner_classes = self.learner_class_ids(&user_hex, &school_hex).await?;
+        let teacher_ids: HashSet<String> = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM teachers WHERE user_id = $1 AND school_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(&user_hex)
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .into_iter()
+        .collect();
+
+        let mut subjects = HashMap::new();
+        let mut taught_classes = HashSet::new();
+        for row in sqlx::query(
+            "SELECT id, class_id, name, teacher_id FROM class_subjects WHERE school_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            let class_id: String = row.try_get("class_id").map_err(Self::db_error)?;
+            let teacher_id: Option<String> = row.try_get("teacher_id").map_err(Self::db_error)?;
+            if teacher_id.as_ref().is_some_and(|id| teacher_ids.contains(id)) {
+                taught_classes.insert(class_id);
+            }
+            subjects.insert(
+                id,
+                ClassSubjectInfo {
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    teacher_id,
+                },
+            );
+        }
+
+        let class_names: HashMap<String, String> =
+            sqlx::query("SELECT id, name FROM classes WHERE school_id = $1")
+                .bind(&school_hex)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(Self::db_error)?
+                .into_iter()
+                .filter_map(|row| Some((row.try_get("id").ok()?, row.try_get("name").ok()?)))
+                .collect();
+        let room_names: HashMap<String, String> =
+            sqlx::query("SELECT id, name FROM school_rooms WHERE school_id = $1")
+                .bind(&school_hex)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(Self::db_error)?
+                .into_iter()
+                .filter_map(|row| Some((row.try_get("id").ok()?, row.try_get("name").ok()?)))
+                .collect();
+
+        let education_year = EducationYearService::new(&self.pool)
+            .get_current_year_and_term(
+                None,
+                Some(EducationYearQuery::from_school_context(Some(
+                    school_hex.clone(),
+                ))),
+            )
+            .await
+            .ok()
+            .map(|(year, _)| year);
+
+        let validation = TimetableValidationService::new(&self.pool);
+        let timetables = ClassTimetableService::new(&self.pool);
+        if let Some(year) = &education_year {
+            let year_id = year
+                .id
+                .ok_or_else(|| AppError::bad_request("Current education year has no id"))?;
+            let school_timetable = validation.school_timetable(&feed.school_id, &year_id).await;
+            let school_days = match &school_timetable {
+                Some(school_timetable) => {
+                    validation
+                        .class_school_days(&feed.school_id, school_timetable)
+                        .await?
+                }
+                None => HashMap::new(),
+            };
+
+            for term in &year.terms {
+                let term_start = term.start_date.date_naive();
+                let term_end = term.end_date.date_naive();
+                let mut boundary = IcsEvent::new(
+                    format!("term-{}-{}@{}", year_id.to_hex(), term.order, UID_DOMAIN),
+                    term.name.clone(),
+                    IcsTime::Date(term_start),
+                );
+                boundary.end = Some(IcsTime::Date(term_end + Duration::days(1)));
+                boundary.categories = vec!["Term".to_string()];
+                calendar.push(boundary);
+
+                for timetable in timetables
+                    .find_by_school_and_term(&feed.school_id, &year_id, term.order)
+                    .await?
+                    .into_iter()
+                    .filter(|timetable| timetable.disabled != Some(true))
+                {
+                    let class_hex = timetable.class_id.to_hex();
+                    let class_name = class_names.get(&class_hex).cloned().unwrap_or_default();
+                    for day in &timetable.weekly_schedule {
+                        let closed = school_days
+                            .get(&timetable.class_id)
+                            .and_then(|days| days.iter().find(|schedule| schedule.day == day.day))
+                            .is_some_and(|schedule| {
+                                !schedule.is_school_day
+                                    || matches!(schedule.special_type, DaySpecialType::Holiday)
+                            });
+                        if day.is_holiday || closed {
+                            continue;
+                        }
+                        let Some(day_start) = day
+                            .start_on
+                            .as_deref()
+                            .and_then(|start| NaiveTime::parse_from_str(start, "%H:%M").ok())
+                        else {
+                            continue;
+                        };
+                        let offset = (7 + day.day.num_days_from_monday() as i64
+                            - term_start.weekday().num_days_from_monday() as i64)
+                            % 7;
+                        let first_date = term_start + Duration::days(offset);
+                        if first_date > term_end {
+                            continue;
+                        }
+
+                        for period in &day.periods {
+                            if period.r#type != PeriodType::Subject || period.enabled == Some(false)
+                            {
+                                continue;
+                            }
+                            let subject =
+                                period.subject_id.and_then(|id| subjects.get(&id.to_hex()));
+                            let teacher_id = period
+                                .teacher_id
+                                .map(|id| id.to_hex())
+                                .or_else(|| subject.and_then(|subject| subject.teacher_id.clone()));
+                            let teaches = teacher_id
+                                .as_ref()
+                                .is_some_and(|id| teacher_ids.contains(id));
+                            if !teaches && !learner_classes.contains(&class_hex) {
+                                continue;
+                            }
+
+                            let start = first_date.and_time(day_start)
+                                + Duration::minutes(period.start_offset as i64);
+                            let subject_name = subject
+                                .map(|subject| subject.name.clone())
+                                .or_else(|| period.title.clone())
+                                .unwrap_or_else(|| "Lesson".to_string());
+                            let summary = if teaches && !class_name.is_empty() {
+                                format!("{} ({})", subject_name, class_name)
+                            } else {
+                                subject_name
+                            };
+
+                            let mut event = IcsEvent::new(
+                                format!(
+                                    "period-{}-{}@{}",
+                                    period.period_id.to_hex(),
+                                    term.order,
+                                    UID_DOMAIN
+                                ),
+                                summary,
+                                IcsTime::Floating(start),
+                            );
+                            event.end = Some(IcsTime::Floating(
+                                start + Duration::minutes(period.duration_minutes as i64),
+                            ));
+                            event.weekly_until = Some(IcsTime::Floating(term_end.and_time(
+                                NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default(),
+                            )));
+                            event.location = period
+                                .room_id
+                                .and_then(|id| room_names.get(&id.to_hex()).cloned());
+                            event.description = period.description.clone();
+                            event.categories = vec!["Lesson".to_string()];
+                            calendar.push(event);
+                        }
+                    }
+                }
+            }
+
+            for school_event in school_timetable
+                .iter()
+                .flat_map(|t| t.events.iter().flatten())
+            {
+                let mut event = IcsEvent::new(
+                    format!("school-event-{}@{}", school_event.event_id, UID_DOMAIN),
+                    school_event.title.clone(),
+                    IcsTime::Utc(school_event.start_date),
+                );
+                event.end = school_event.end_date.map(IcsTime::Utc);
+                event.description = school_event.description.clone();
+                event.categories = vec!["School event".to_string()];
+                calendar.push(event);
+            }
+        }
+
+        let since: DateTime<Utc> = education_year
+            .as_ref()
+            .map(|year| year.start_date)
+            .unwrap_or_else(|| Utc::now() - Duration::days(FALLBACK_HISTORY_DAYS));
+        let relevant_classes = learner_classes
+            .union(&taught_classes)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for row in sqlx::query(
+            r#"SELECT id, class_id, name, description, starts_at, ends_at
+               FROM exams
+               WHERE school_id = $1 AND deleted_at IS NULL AND status <> 'Draft'
+                 AND starts_at IS NOT NULL AND starts_at >= $2
+                 AND (class_id IS NULL OR class_id = ANY($3))
+               ORDER BY starts_at"#,
+        )
+        .bind(&school_hex)
+        .bind(since)
+        .bind(&relevant_classes)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            let name: String = row.try_get("name").map_err(Self::db_error)?;
+            let class_id: Option<String> = row.try_get("class_id").map_err(Self::db_error)?;
+            let starts_at: DateTime<Utc> = row.try_get("starts_at").map_err(Self::db_error)?;
+            let ends_at: Option<DateTime<Utc>> = row.try_get("ends_at").map_err(Self::db_error)?;
+
+            let summary = match class_id.and_then(|id| class_names.get(&id).cloned()) {
+                Some(class_name) => format!("Exam: {} ({})", name, class_name),
+                None => format!("Exam: {}", name),
+            };
+            let mut event = IcsEvent::new(
+                format!("exam-{}@{}", id, UID_DOMAIN),
+                summary,
+                IcsTime::Utc(starts_at),
+            );
+            event.end = ends_at.filter(|end| *end > starts_at).map(IcsTime::Utc);
+            event.description = row.try_get("description").map_err(Self::db_error)?;
+            event.categories = vec!["Exam".to_string()];
+            calendar.push(event);
+        }
+
+        for row in sqlx::query(
+            r#"SELECT id, class_id, title, description, coalesce(due_date, due_at) AS due
+               FROM assignments
+               WHERE school_id = $1 AND deleted_at IS NULL
+                 AND coalesce(due_date, due_at) >= $2
+                 AND class_id = ANY($3)
+               ORDER BY due"#,
+        )
+        .bind(&school_hex)
+        .bind(since)
+        .bind(&relevant_classes)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            let title: String = row.try_get("title").map_err(Self::db_error)?;
+            let due: DateTime<Utc> = row.try_get("due").map_err(Self::db_error)?;
+
+            let mut event = IcsEvent::new(
+                format!("assignment-{}@{}", id, UID_DOMAIN),
+                format!("Due: {}", title),
+                IcsTime::Utc(due),
+            );
+            event.description = row.try_get("description").map_err(Self::db_error)?;
+            event.categories = vec!["Assignment".to_string()];
+            calendar.push(event);
+        }
+
+        Ok(calendar.render(Utc::now()))
+    }
+}
@@ -35,9 +35,9 @@ impl ClassQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -76,9 +76,7 @@ impl ClassService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -90,8 +88,8 @@ impl ClassService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -199,15 +197,16 @@ impl ClassService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported class filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported class filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -422,30 +421,29 @@ impl ClassService {
 
     pub async fn create(&self, mut dto: Class) -> Result<Class, AppError> {
         self.ensure_indexes().await?;
-        is_valid_username(&dto.username).map_err(|e| AppError { message: e })?;
+        is_valid_username(&dto.username).map_err(AppError::bad_request)?;
 
         let school_id = dto
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
 
         if self
             .username_exists(&dto.username, Some(&school_id), None)
             .await?
             .is_some()
         {
-            return Err(AppError {
-                message: format!("Class username already exists: {}", dto.username),
-            });
+            return Err(AppError::conflict(format!(
+                "Class username already exists: {}",
+                dto.username
+            )));
         }
 
         if let Some(image_data) = dto.image.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&image_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             dto.image_id = Some(cloud_res.public_id);
             dto.image = Some(cloud_res.secure_url);
         }
@@ -455,7 +453,7 @@ impl ClassService {
             for bg in background_images_data {
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&bg.url)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 uploaded_images.push(Image {
                     id: cloud_res.public_id,
                     url: cloud_res.secure_url,
@@ -536,9 +534,7 @@ impl ClassService {
 
         match row {
             Some(row) => self.class_from_row(row).await,
-            None => Err(AppError {
-                message: "Class not found".into(),
-            }),
+            None => Err(AppError::not_found("Class not found")),
         }
     }
 
@@ -600,7 +596,7 @@ impl ClassService {
 
     pub async fn update(&self, id: &IdType, update: &UpdateClass) -> Result<Class, AppError> {
         if let Some(ref username) = update.username {
-            is_valid_username(username).map_err(|e| AppError { message: e })?;
+            is_valid_username(username).map_err(AppError::bad_request)?;
         }
 
         let existing = self.find_one(Some(id), None).await?;
@@ -609,9 +605,7 @@ impl ClassService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
 
         if let Some(ref username) = update.username {
             if existing.username != *username
@@ -620,9 +614,10 @@ impl ClassService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("Username already exists: {}", username),
-                });
+                return Err(AppError::conflict(format!(
+                    "Username already exists: {}",
+                    username
+                )));
             }
         }
 
@@ -636,7 +631,7 @@ impl ClassService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_image)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.image_id = Some(Some(cloud_res.public_id));
                 update_data.image = Some(Some(cloud_res.secure_url));
             }
@@ -652,8 +647,8 @@ impl ClassService {
             for bg in bg_images {
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&bg.url)
                     .await
-                    .map_err(|e| AppError {
-                        message: format!("Failed to upload background image: {}", e),
+                    .map_err(|e| {
+                        AppError::internal(format!("Failed to upload background image: {}", e))
                     })?;
                 uploaded_bgs.push(Image {
                     id: cloud_res.public_id,
@@ -760,9 +755,7 @@ impl ClassService {
         }
 
         if !touched && update_data.tags.is_none() && update_data.background_images.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         self.find_one(Some(id), None).await
@@ -872,9 +865,9 @@ impl ClassService {
         creator_id: ObjectId,
     ) -> Result<Vec<Class>, AppError> {
         if !(1..=12).contains(&num_sub_classes) {
-            return Err(AppError {
-                message: "Number of sub-classes must be between 1 and 12".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of sub-classes must be between 1 and 12",
+            ));
         }
 
         let main_class = self.find_one(Some(main_class_id), None).await?;
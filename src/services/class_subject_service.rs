@@ -30,9 +30,9 @@ impl ClassSubjectQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -72,9 +72,7 @@ impl ClassSubjectService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -86,8 +84,8 @@ impl ClassSubjectService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -178,15 +176,16 @@ impl ClassSubjectService {
                         .push(")");
                 }
                 "disable" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid disable filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid disable filter value"))?;
                     sql.push(" AND disable = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported class subject filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported class subject filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -353,21 +352,18 @@ impl ClassSubjectService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let class_id = dto
             .class_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "class_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("class_id", "class_id is required"))?;
 
         if let Some(existing) = self.code_exists(&dto.code, Some(&school_id), None).await? {
-            return Err(AppError {
-                message: format!("Class subject code already exists: {}", existing.code),
-            });
+            return Err(AppError::conflict(format!(
+                "Class subject code already exists: {}",
+                existing.code
+            )));
         }
 
         let id = dto.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
@@ -427,9 +423,7 @@ impl ClassSubjectService {
 
         match row {
             Some(row) => self.subject_from_row(row).await,
-            None => Err(AppError {
-                message: "Class subject not found".into(),
-            }),
+            None => Err(AppError::not_found("Class subject not found")),
         }
     }
 
@@ -506,9 +500,10 @@ impl ClassSubjectService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("Code '{}' already exists", code),
-                });
+                return Err(AppError::conflict(format!(
+                    "Code '{}' already exists",
+                    code
+                )));
             }
         }
 
@@ -586,9 +581,7 @@ impl ClassSubjectService {
             self.sync_topics(&id_string, topics).await?;
         }
         if !touched && update.topics.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         self.find_one(Some(id), None).await
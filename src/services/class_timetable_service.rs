@@ -1,23 +1,30 @@
-use actix_web::web;
-use chrono::Weekday;
+use std::collections::HashMap;
+
+use chrono::{NaiveTime, Timelike, Weekday};
 use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
 
 use crate::{
-    config::state::AppState,
     domain::{
-        class_timetable::{ClassTimetable, ClassTimetablePartial, DayStructureConfig},
-        common_details::Paginated,
+        class_timetable::{ClassTimetable, ClassTimetablePartial, DayStructureConfig, PeriodType, WeekSchedule},
+        common_details::{DailyAvailability, Paginated, TimeRange},
+        timetable_solver::{
+            SubjectSchedulingRule, TimetableSolveRequest, TimetableSolveResult,
+            DEFAULT_MAX_CONSECUTIVE_PERIODS,
+        },
     },
     errors::AppError,
-    handler::class_timetable_handler::auto_generate_schedule,
+    handler::class_timetable_handler::{solve_timetables, Booking, SolverInput, SolverRoom, SolverSubject},
     models::{id_model::IdType, school_token_model::SchoolToken},
     services::{
-        class_subject_service::{ClassSubjectQuery, ClassSubjectService},
         education_year_service::{EducationYearQuery, EducationYearService},
+        timetable_validation_service::TimetableValidationService,
     },
-    utils::object_id::ObjectId,
+    utils::{object_id::ObjectId, time_utils::is_valid_hhmm},
 };
 
+/// Subject categories treated as heavy when a subject has no scheduling rule
+const HEAVY_CATEGORIES: [&str; 3] = ["Mathematics", "Science", "Engineering"];
+
 pub struct ClassTimetableService {
     pub pool: PgPool,
 }
@@ -28,7 +35,7 @@ impl ClassTimetableService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -40,8 +47,8 @@ impl ClassTimetableService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -69,20 +76,28 @@ impl ClassTimetableService {
     }
 
     pub async fn create(&self, dto: ClassTimetable) -> Result<ClassTimetable, AppError> {
-        if self.find_by_class_and_year(&dto.class_id, &dto.education_year_id).await.is_ok() {
-            return Err(AppError {
-                message: format!(
-                    "Timetable for class {} in year {} already exists",
-                    dto.class_id, dto.education_year_id
-                ),
-            });
+        if self
+            .find_by_class_year_and_term(&dto.class_id, &dto.education_year_id, dto.term_order)
+            .await
+            .is_ok()
+        {
+            return Err(AppError::conflict(format!(
+                "Timetable for class {} in year {} term {} already exists",
+                dto.class_id, dto.education_year_id, dto.term_order
+            )));
         }
 
         for week_schedule in &dto.weekly_schedule {
             if let Err(e) = week_schedule.validate() {
-                return Err(AppError { message: format!("Validation error on {}: {}", week_schedule.day, e) });
+                return Err(AppError::bad_request(format!(
+                    "Validation error on {}: {}",
+                    week_schedule.day, e
+                )));
             }
         }
+        if dto.disabled != Some(true) {
+            TimetableValidationService::new(&self.pool).check_save(&dto, None).await?;
+        }
 
         let id = Self::new_id();
         let class_id_str = dto.class_id.to_hex();
@@ -115,7 +130,7 @@ impl ClassTimetableService {
             .map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_timetable(r),
-            None => Err(AppError { message: "Class timetable not found".to_string() }),
+            None => Err(AppError::not_found("Class timetable not found")),
         }
     }
 
@@ -132,10 +147,58 @@ impl ClassTimetableService {
             .map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_timetable(r),
-            None => Err(AppError { message: "Class timetable not found for this class/year".to_string() }),
+            None => Err(AppError::not_found(
+                "Class timetable not found for this class/year",
+            )),
+        }
+    }
+
+    pub async fn find_by_class_year_and_term(
+        &self,
+        class_id: &ObjectId,
+        education_year_id: &ObjectId,
+        term_order: i32,
+    ) -> Result<ClassTimetable, AppError> {
+        let row = sqlx::query(&format!(
+            "{} AND class_id = $1 AND education_year_id = $2 AND term_order = $3 LIMIT 1",
+            Self::select_sql()
+        ))
+        .bind(class_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .bind(term_order)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(r) => Self::row_to_timetable(r),
+            None => Err(AppError::not_found(
+                "Class timetable not found for this class/term",
+            )),
         }
     }
 
+    /// Every class timetable of a school for one term
+    pub async fn find_by_school_and_term(
+        &self,
+        school_id: &ObjectId,
+        education_year_id: &ObjectId,
+        term_order: i32,
+    ) -> Result<Vec<ClassTimetable>, AppError> {
+        let rows = sqlx::query(
+            "SELECT ct.id, ct.class_id, ct.education_year_id, ct.term_order, ct.weekly_schedule, ct.disabled, \
+             ct.created_at, ct.updated_at FROM class_timetables ct JOIN classes c ON c.id = ct.class_id \
+             WHERE c.school_id = $1 AND ct.education_year_id = $2 AND ct.term_order = $3 \
+             ORDER BY ct.class_id",
+        )
+        .bind(school_id.to_hex())
+        .bind(education_year_id.to_hex())
+        .bind(term_order)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_timetable).collect()
+    }
+
     pub async fn get_all(
         &self,
         filter: Option<String>,
@@ -175,6 +238,24 @@ impl ClassTimetableService {
         dto: &ClassTimetablePartial,
     ) -> Result<ClassTimetable, AppError> {
         let id_str = Self::id_to_string(id)?;
+        let existing = self.find_one_by_id(id).await?;
+        let mut candidate = existing.clone();
+        candidate.merge(dto.clone());
+        candidate.id = existing.id;
+        for week_schedule in &candidate.weekly_schedule {
+            if let Err(e) = week_schedule.validate() {
+                return Err(AppError::bad_request(format!(
+                    "Validation error on {}: {}",
+                    week_schedule.day, e
+                )));
+            }
+        }
+        if candidate.disabled != Some(true) {
+            TimetableValidationService::new(&self.pool)
+                .check_save(&candidate, Some(&existing))
+                .await?;
+        }
+
         let mut sql = QueryBuilder::<Postgres>::new("UPDATE class_timetables SET updated_at = now()");
         let mut touched = false;
 
@@ -187,7 +268,9 @@ impl ClassTimetableService {
             sql.push(", weekly_schedule = ").push_bind(j); touched = true;
         }
 
-        if !touched { return Err(AppError { message: "No valid fields to update".into() }); }
+        if !touched {
+            return Err(AppError::bad_request("No valid fields to update"));
+        }
 
         sql.push(" WHERE id = ").push_bind(&id_str);
         sql.build().execute(&self.pool).await.map_err(Self::db_error)?;
@@ -205,45 +288,406 @@ impl ClassTimetableService {
         Ok(existing)
     }
 
+
+    /// Solve a single class against the timetables its school already has
+    /// for the current term and save the result.
     pub async fn generate_timetable(
         &self,
         class_id: &IdType,
-        state: &web::Data<AppState>,
         school_claims: &Option<SchoolToken>,
     ) -> Result<ClassTimetable, AppError> {
-        let education_service = EducationYearService::new(&state.pg.pool);
-        let subject_service = ClassSubjectService::new(&state.pg.pool);
-
-        let (education_year, term_info) = education_service
-            .get_current_year_and_term(None, Some(EducationYearQuery::from_school_context(school_claims.as_ref().map(|c| c.id.clone()))))
+        let school_id = school_claims
+            .as_ref()
+            .map(|claims| Self::parse_oid(&claims.id, "school_id"))
+            .transpose()?
+            .ok_or_else(|| AppError::bad_request("School token required"))?;
+
+        let result = self
+            .solve_term(
+                &school_id,
+                TimetableSolveRequest {
+                    class_ids: Some(vec![IdType::to_object_id(class_id)?.to_hex()]),
+                    ..TimetableSolveRequest::default()
+                },
+            )
             .await?;
 
-        let term_order = term_info.map(|t| t.order).unwrap_or(1);
+        result
+            .timetables
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::bad_request("Class has no subjects to schedule"))
+    }
+
+    pub async fn get_subject_rules(&self, school_id: &ObjectId) -> Result<Vec<SubjectSchedulingRule>, AppError> {
+        let rows = sqlx::query(
+            "SELECT class_subject_id, required_room_type, is_heavy, max_consecutive_periods \
+             FROM class_subject_scheduling_rules WHERE school_id = $1 ORDER BY class_subject_id",
+        )
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let class_subject_id: String = row.try_get("class_subject_id").map_err(Self::db_error)?;
+                Ok(SubjectSchedulingRule {
+                    class_subject_id: Some(Self::parse_oid(&class_subject_id, "class_subject_id")?),
+                    required_room_type: row.try_get("required_room_type").map_err(Self::db_error)?,
+                    is_heavy: row.try_get("is_heavy").map_err(Self::db_error)?,
+                    max_consecutive_periods: row.try_get("max_consecutive_periods").map_err(Self::db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn upsert_subject_rule(
+        &self,
+        school_id: &ObjectId,
+        class_subject_id: &ObjectId,
+        rule: SubjectSchedulingRule,
+    ) -> Result<SubjectSchedulingRule, AppError> {
+        rule.validate().map_err(AppError::validation)?;
+
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM class_subjects WHERE id = $1 AND school_id = $2 AND deleted_at IS NULL)",
+        )
+        .bind(class_subject_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if !exists {
+            return Err(AppError::not_found("Class subject not found"));
+        }
+
+        let required_room_type = rule.required_room_type.map(|room_type| room_type.trim().to_string());
+        sqlx::query(
+            r#"INSERT INTO class_subject_scheduling_rules
+                 (class_subject_id, school_id, required_room_type, is_heavy, max_consecutive_periods)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (class_subject_id) DO UPDATE SET
+                 required_room_type = EXCLUDED.required_room_type,
+                 is_heavy = EXCLUDED.is_heavy,
+                 max_consecutive_periods = EXCLUDED.max_consecutive_periods"#,
+        )
+        .bind(class_subject_id.to_hex())
+        .bind(school_id.to_hex())
+        .bind(&required_room_type)
+        .bind(rule.is_heavy)
+        .bind(rule.max_consecutive_periods)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(SubjectSchedulingRule {
+            class_subject_id: Some(*class_subject_id),
+            required_room_type,
+            is_heavy: rule.is_heavy,
+            max_consecutive_periods: rule.max_consecutive_periods,
+        })
+    }
+
+    fn minutes(time: NaiveTime) -> i32 {
+        (time.hour() * 60 + time.minute()) as i32
+    }
 
-        let class_subjects = subject_service
-            .get_all(None, None, None, Some(ClassSubjectQuery {
-                school_id: school_claims.as_ref().map(|c| c.id.clone()),
-                class_id: Some(IdType::to_object_id(class_id)?.to_hex()),
-                ..ClassSubjectQuery::default()
-            }))
+    /// Plan every requested class of a school for one term in a single pass.
+    /// Timetables of classes outside the request stay as they are and count
+    /// as bookings for their teachers and rooms.
+    pub async fn solve_term(
+        &self,
+        school_id: &ObjectId,
+        request: TimetableSolveRequest,
+    ) -> Result<TimetableSolveResult, AppError> {
+        let school_hex = school_id.to_hex();
+        let (education_year, term) = EducationYearService::new(&self.pool)
+            .get_current_year_and_term(None, Some(EducationYearQuery::from_school_context(Some(school_hex.clone()))))
             .await?;
+        let education_year_id = education_year
+            .id
+            .ok_or_else(|| AppError::bad_request("Current education year has no id"))?;
+        let term_order = request.term_order.or(term.map(|t| t.order)).unwrap_or(1);
+
+        let start_time = request.start_time.clone().unwrap_or_else(|| "08:00".to_string());
+        if !is_valid_hhmm(&start_time) {
+            return Err(AppError::bad_request(
+                "start_time must be HH:MM 24-hour format",
+            ));
+        }
+        let start_time = NaiveTime::parse_from_str(&start_time, "%H:%M")
+            .map_err(|e| AppError::bad_request(format!("Invalid start_time: {}", e)))?;
+        let days = request
+            .days
+            .clone()
+            .filter(|days| !days.is_empty())
+            .unwrap_or_else(|| vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]);
+        let day_template = request.day_template.clone().unwrap_or_else(DayStructureConfig::standard_day);
+        if day_template.iter().any(|entry| entry.duration_minutes <= 0) {
+            return Err(AppError::bad_request(
+                "day_template durations must be positive",
+            ));
+        }
+        if !day_template
+            .iter()
+            .any(|entry| entry.period_type == PeriodType::Subject)
+        {
+            return Err(AppError::bad_request(
+                "day_template needs at least one subject period",
+            ));
+        }
+        if matches!(request.teacher_max_consecutive_periods, Some(max) if max <= 0) {
+            return Err(AppError::bad_request(
+                "teacher_max_consecutive_periods must be a positive number",
+            ));
+        }
+
+        let class_ids: Vec<String> = match &request.class_ids {
+            Some(ids) => {
+                let ids = ids
+                    .iter()
+                    .map(|id| Self::parse_oid(id, "class_id").map(|oid| oid.to_hex()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let found: Vec<String> = sqlx::query_scalar(
+                    "SELECT id FROM classes WHERE school_id = $1 AND id = ANY($2) AND deleted_at IS NULL",
+                )
+                .bind(&school_hex)
+                .bind(&ids)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(Self::db_error)?;
+                if let Some(missing) = ids.iter().find(|id| !found.contains(id)) {
+                    return Err(AppError::not_found(format!("Class {} not found in this school", missing)));
+                }
+                ids
+            }
+            None => sqlx::query_scalar(
+                "SELECT id FROM classes WHERE school_id = $1 AND is_active AND deleted_at IS NULL ORDER BY name",
+            )
+            .bind(&school_hex)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?,
+        };
+        if class_ids.is_empty() {
+            return Err(AppError::bad_request("No classes to schedule"));
+        }
+
+        let subject_teachers: HashMap<String, String> = sqlx::query(
+            "SELECT id, teacher_id FROM class_subjects WHERE school_id = $1 AND teacher_id IS NOT NULL",
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .into_iter()
+        .filter_map(|row| Some((row.try_get("id").ok()?, row.try_get("teacher_id").ok()?)))
+        .collect();
+
+        let existing = sqlx::query(
+            r#"SELECT ct.class_id, ct.weekly_schedule
+               FROM class_timetables ct
+               JOIN classes c ON c.id = ct.class_id
+               WHERE c.school_id = $1 AND ct.education_year_id = $2 AND ct.term_order = $3
+                 AND NOT coalesce(ct.disabled, false)"#,
+        )
+        .bind(&school_hex)
+        .bind(education_year_id.to_hex())
+        .bind(term_order)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut fixed_bookings = Vec::new();
+        for row in existing {
+            let class_id: String = row.try_get("class_id").map_err(Self::db_error)?;
+            if class_ids.contains(&class_id) {
+                if !request.replace_existing {
+                    return Err(AppError::conflict(format!( "Class {} already has a timetable for term {}; set replace_existing to overwrite it", class_id, term_order )));
+                }
+                continue;
+            }
+            let weekly: serde_json::Value = row.try_get("weekly_schedule").map_err(Self::db_error)?;
+            let weekly: Vec<WeekSchedule> = serde_json::from_value(weekly).unwrap_or_default();
+            for day in weekly.into_iter().filter(|day| !day.is_holiday) {
+                let Some(day_start) = day
+                    .start_on
+                    .as_deref()
+                    .and_then(|start| NaiveTime::parse_from_str(start, "%H:%M").ok())
+                    .map(Self::minutes)
+                else {
+                    continue;
+                };
+                for period in day.periods {
+                    if period.r#type != PeriodType::Subject || period.enabled == Some(false) {
+                        continue;
+                    }
+                    let teacher_id = period.teacher_id.or_else(|| {
+                        period
+                            .subject_id
+                            .and_then(|subject_id| subject_teachers.get(&subject_id.to_hex()))
+                            .and_then(|teacher_id| ObjectId::parse_str(teacher_id).ok())
+                    });
+                    fixed_bookings.push(Booking {
+                        day: day.day,
+                        start: day_start + period.start_offset,
+                        end: day_start + period.start_offset + period.duration_minutes,
+                        teacher_id,
+                        room_id: period.room_id,
+                    });
+                }
+            }
+        }
+
+        let subject_rows = sqlx::query(
+            r#"SELECT cs.id, cs.class_id, cs.name, cs.teacher_id, cs.category, cs.estimated_hours, cs.credits,
+                      r.required_room_type, r.is_heavy, r.max_consecutive_periods
+               FROM class_subjects cs
+               LEFT JOIN class_subject_scheduling_rules r ON r.class_subject_id = cs.id
+               WHERE cs.school_id = $1 AND cs.class_id = ANY($2)
+                 AND cs.deleted_at IS NULL AND NOT coalesce(cs.disable, false)
+               ORDER BY cs.class_id, cs.name"#,
+        )
+        .bind(&school_hex)
+        .bind(&class_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut subjects = Vec::with_capacity(subject_rows.len());
+        for row in subject_rows {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            let class_id: String = row.try_get("class_id").map_err(Self::db_error)?;
+            let teacher_id: Option<String> = row.try_get("teacher_id").map_err(Self::db_error)?;
+            let category: Option<String> = row.try_get("category").map_err(Self::db_error)?;
+            let credits: Option<i32> = row.try_get("credits").map_err(Self::db_error)?;
+            let estimated_hours: i32 = row.try_get("estimated_hours").map_err(Self::db_error)?;
+            let is_heavy: Option<bool> = row.try_get("is_heavy").map_err(Self::db_error)?;
+            let max_consecutive: Option<i32> = row.try_get("max_consecutive_periods").map_err(Self::db_error)?;
+
+            subjects.push(SolverSubject {
+                id: Self::parse_oid(&id, "class_subject_id")?,
+                class_id: Self::parse_oid(&class_id, "class_id")?,
+                name: row.try_get("name").map_err(Self::db_error)?,
+                teacher_id: teacher_id.as_deref().map(|t| Self::parse_oid(t, "teacher_id")).transpose()?,
+                weight: credits.unwrap_or(estimated_hours),
+                required_room_type: row.try_get("required_room_type").map_err(Self::db_error)?,
+                is_heavy: is_heavy.unwrap_or_else(|| {
+                    category.as_deref().is_some_and(|category| HEAVY_CATEGORIES.contains(&category))
+                }),
+                max_consecutive: max_consecutive.unwrap_or(DEFAULT_MAX_CONSECUTIVE_PERIODS),
+            });
+        }
+
+        let teacher_ids = subjects
+            .iter()
+            .filter_map(|subject| subject.teacher_id.map(|id| id.to_hex()))
+            .collect::<Vec<_>>();
+        let availability_rows = sqlx::query(
+            r#"SELECT t.id AS teacher_id, ua.day, ua.starts_at, ua.ends_at
+               FROM teachers t
+               JOIN user_availability ua ON ua.user_id = t.user_id
+               WHERE t.id = ANY($1)
+               ORDER BY t.id, ua.position"#,
+        )
+        .bind(&teacher_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut availability: HashMap<ObjectId, Vec<DailyAvailability>> = HashMap::new();
+        for row in availability_rows {
+            let teacher_id: String = row.try_get("teacher_id").map_err(Self::db_error)?;
+            let day: String = row.try_get("day").map_err(Self::db_error)?;
+            let Ok(day) = day.parse::<Weekday>() else {
+                continue;
+            };
+            availability
+                .entry(Self::parse_oid(&teacher_id, "teacher_id")?)
+                .or_default()
+                .push(DailyAvailability {
+                    day,
+                    time_range: TimeRange {
+                        start: row.try_get("starts_at").map_err(Self::db_error)?,
+                        end: row.try_get("ends_at").map_err(Self::db_error)?,
+                    },
+                });
+        }
 
-        let class_id_obj = IdType::to_object_id(class_id)?;
-        let education_year_id = education_year.id.unwrap();
-        let days = vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
-        let day_template = DayStructureConfig::standard_day();
+        let rooms = sqlx::query(
+            "SELECT id, room_type FROM school_rooms \
+             WHERE school_id = $1 AND is_active AND deleted_at IS NULL ORDER BY lower(name)",
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .into_iter()
+        .map(|row| {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            Ok(SolverRoom {
+                id: Self::parse_oid(&id, "room_id")?,
+                room_type: row.try_get("room_type").map_err(Self::db_error)?,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
 
-        let timetable = auto_generate_schedule(
-            class_id_obj,
+        let input = SolverInput {
             education_year_id,
             term_order,
-            class_subjects.data,
-            "08:00".to_string(),
+            class_ids: class_ids
+                .iter()
+                .map(|id| Self::parse_oid(id, "class_id"))
+                .collect::<Result<Vec<_>, _>>()?,
+            subjects,
+            rooms,
+            availability,
+            fixed_bookings,
             days,
+            start_time,
             day_template,
-        )
-        .map_err(|err| AppError { message: err })?;
+            teacher_max_consecutive: request.teacher_max_consecutive_periods,
+        };
+        let output = solve_timetables(&input);
+
+        let mut timetables = output.timetables;
+        if !request.dry_run {
+            let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+            for timetable in &mut timetables {
+                let weekly_json =
+                    serde_json::to_value(&timetable.weekly_schedule).map_err(|e| {
+                        AppError::internal(format!("Failed to encode timetable: {}", e))
+                    })?;
+                let id: String = sqlx::query_scalar(
+                    r#"INSERT INTO class_timetables (id, class_id, education_year_id, term_order, weekly_schedule, disabled)
+                       VALUES ($1, $2, $3, $4, $5, false)
+                       ON CONFLICT (class_id, education_year_id, term_order) DO UPDATE SET
+                         weekly_schedule = EXCLUDED.weekly_schedule,
+                         disabled = false,
+                         updated_at = now()
+                       RETURNING id"#,
+                )
+                .bind(Self::new_id())
+                .bind(timetable.class_id.to_hex())
+                .bind(timetable.education_year_id.to_hex())
+                .bind(timetable.term_order)
+                .bind(&weekly_json)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(Self::db_error)?;
+                timetable.id = Some(Self::parse_oid(&id, "id")?);
+            }
+            tx.commit().await.map_err(Self::db_error)?;
+        }
 
-        self.create(timetable).await
+        Ok(TimetableSolveResult {
+            term_order,
+            timetables,
+            unplaced: output.unplaced,
+            lessons_placed: output.lessons_placed,
+            penalty: output.penalty,
+            saved: !request.dry_run,
+        })
     }
 }
@@ -40,6 +40,23 @@ impl CloudinaryService {
         env::var(key).unwrap_or_else(|_| panic!("Missing env key {}", key))
     }
 
+    /// Whether `url` is an asset delivered for this account: https on the
+    /// delivery host, under our cloud name. Lets the server fetch stored
+    /// files back without following links to arbitrary hosts.
+    pub fn is_own_asset_url(url: &str) -> bool {
+        dotenv().ok();
+        let (Ok(url), Ok(cloud_name)) =
+            (reqwest::Url::parse(url), env::var("CLOUDINARY_CLOUD_NAME"))
+        else {
+            return false;
+        };
+        url.scheme() == "https"
+            && url.host_str() == Some("res.cloudinary.com")
+            && url.port().is_none()
+            && url.username().is_empty()
+            && url.path().starts_with(&format!("/{}/", cloud_name))
+    }
+
     fn generate_signature(params: HashMap<&str, ParamValue>, api_secret: &str) -> String {
         let mut sorted_keys: Vec<&&str> = params.keys().collect();
         sorted_keys.sort();
@@ -26,9 +26,7 @@ impl CommentService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -40,8 +38,8 @@ impl CommentService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -236,9 +234,7 @@ impl CommentService {
             .map_err(Self::db_error)?
             .map(|row| Self::comment_from_row(&row))
             .transpose()?
-            .ok_or(AppError {
-                message: "Comment not found".into(),
-            })
+            .ok_or(AppError::not_found("Comment not found"))
     }
 
     pub async fn get_all(
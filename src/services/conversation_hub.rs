@@ -0,0 +1,313 @@
+use futures::channel::mpsc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::domain::message::Message;
+use crate::services::event_bus::{Event, EventTransport, LocalTransport};
+
+/// Entity type of the bus events that carry hub traffic between nodes
+pub const CONVERSATION_HUB_ENTITY: &str = "conversation_hub";
+
+/// Frames exchanged on `/m/ws/{conversation_id}`. `Typing`, `ReadAck` and
+/// `Ping` are accepted from clients; the rest are pushed by the server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsMessage {
+    MessageCreated {
+        conversation_id: String,
+        message_id: String,
+        data: Box<Message>,
+    },
+    MessageRead {
+        message_id: String,
+        user_id: String,
+        read_at: chrono::DateTime<chrono::Utc>,
+    },
+    MessageDeleted {
+        message_id: String,
+    },
+    ConversationCreated {
+        conversation_id: String,
+    },
+    ParticipantAdded {
+        conversation_id: String,
+        user_id: String,
+    },
+    ParticipantRemoved {
+        conversation_id: String,
+        user_id: String,
+    },
+    /// From a client: `{"type":"typing","is_typing":true}`. Relayed to the
+    /// other participants with the sender's `user_id`.
+    Typing {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        user_id: Option<String>,
+        is_typing: bool,
+    },
+    /// From a client: marks a message of the conversation as read.
+    ReadAck {
+        message_id: String,
+    },
+    Error {
+        message: String,
+    },
+    Ping,
+    Pong,
+}
+
+impl WsMessage {
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// What one node's hub hands the others through the event transport
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum HubRelay {
+    Frame {
+        conversation_id: String,
+        message: WsMessage,
+    },
+    Unsubscribe {
+        conversation_id: String,
+        user_id: String,
+    },
+}
+
+struct HubSession {
+    user_id: String,
+    sender: mpsc::UnboundedSender<String>,
+}
+
+type ConversationSessions = HashMap<String, HashMap<Uuid, HubSession>>;
+
+/// Live WebSocket sessions grouped by conversation. Sessions are local to the
+/// API node they are connected to; frames and unsubscribes also go out over
+/// the event transport, and the [`EventBus`](crate::services::event_bus::EventBus)
+/// of every other node hands them back through [`ConversationHub::deliver_remote`].
+#[derive(Clone)]
+pub struct ConversationHub {
+    conversations: Arc<RwLock<ConversationSessions>>,
+    transport: Arc<dyn EventTransport>,
+}
+
+impl Default for ConversationHub {
+    fn default() -> Self {
+        Self::with_transport(Arc::new(LocalTransport))
+    }
+}
+
+impl ConversationHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_transport(transport: Arc<dyn EventTransport>) -> Self {
+        Self {
+            conversations: Arc::new(RwLock::new(HashMap::new())),
+            transport,
+        }
+    }
+
+    pub async fn join(
+        &self,
+        conversation_id: &str,
+        user_id: &str,
+    ) -> (Uuid, mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded();
+        let session_id = Uuid::new_v4();
+        self.conversations
+            .write()
+            .await
+            .entry(conversation_id.to_string())
+            .or_default()
+            .insert(
+                session_id,
+                HubSession {
+                    user_id: user_id.to_string(),
+                    sender,
+                },
+            );
+        (session_id, receiver)
+    }
+
+    pub async fn leave(&self, conversation_id: &str, session_id: &Uuid) {
+        let mut conversations = self.conversations.write().await;
+        if let Some(sessions) = conversations.get_mut(conversation_id) {
+            sessions.remove(session_id);
+            if sessions.is_empty() {
+                conversations.remove(conversation_id);
+            }
+        }
+    }
+
+    /// Push a frame to every session of the conversation, on every node,
+    /// except `skip` (typically the session that caused it).
+    pub async fn publish(&self, conversation_id: &str, message: &WsMessage, skip: Option<&Uuid>) {
+        self.deliver(conversation_id, message, skip).await;
+        self.relay(HubRelay::Frame {
+            conversation_id: conversation_id.to_string(),
+            message: message.clone(),
+        })
+        .await;
+    }
+
+    /// Close every session the user has open in the conversation, on every
+    /// node. Their sockets see the end of the frame stream and disconnect.
+    pub async fn unsubscribe(&self, conversation_id: &str, user_id: &str) {
+        self.drop_user(conversation_id, user_id).await;
+        self.relay(HubRelay::Unsubscribe {
+            conversation_id: conversation_id.to_string(),
+            user_id: user_id.to_string(),
+        })
+        .await;
+    }
+
+    /// Apply hub traffic published by another node
+    pub async fn deliver_remote(&self, event: &Event) {
+        match serde_json::from_value::<HubRelay>(event.data.clone()) {
+            Ok(HubRelay::Frame {
+                conversation_id,
+                message,
+            }) => self.deliver(&conversation_id, &message, None).await,
+            Ok(HubRelay::Unsubscribe {
+                conversation_id,
+                user_id,
+            }) => self.drop_user(&conversation_id, &user_id).await,
+            Err(err) => log::warn!("Dropping conversation hub relay: {}", err),
+        }
+    }
+
+    async fn relay(&self, relay: HubRelay) {
+        let data = serde_json::to_value(&relay).unwrap_or(serde_json::Value::Null);
+        let event = Event::new("relay", CONVERSATION_HUB_ENTITY, data);
+        if let Err(err) = self.transport.publish(&event).await {
+            log::warn!(
+                "Failed to relay conversation frame to other nodes: {}",
+                err.message
+            );
+        }
+    }
+
+    async fn drop_user(&self, conversation_id: &str, user_id: &str) {
+        let mut conversations = self.conversations.write().await;
+        if let Some(sessions) = conversations.get_mut(conversation_id) {
+            // Dropping the senders ends each session's frame stream
+            sessions.retain(|_, session| session.user_id != user_id);
+            if sessions.is_empty() {
+                conversations.remove(conversation_id);
+            }
+        }
+    }
+
+    async fn deliver(&self, conversation_id: &str, message: &WsMessage, skip: Option<&Uuid>) {
+        let text = message.to_text();
+        let mut closed = Vec::new();
+        {
+            let conversations = self.conversations.read().await;
+            let Some(sessions) = conversations.get(conversation_id) else {
+                return;
+            };
+            for (session_id, session) in sessions {
+                if Some(session_id) == skip {
+                    continue;
+                }
+                if session.sender.unbounded_send(text.clone()).is_err() {
+                    closed.push(*session_id);
+                }
+            }
+        }
+
+        for session_id in closed {
+            self.leave(conversation_id, &session_id).await;
+        }
+    }
+
+    /// Users with at least one open session in the conversation.
+    pub async fn online_users(&self, conversation_id: &str) -> Vec<String> {
+        let conversations = self.conversations.read().await;
+        let mut users: Vec<String> = conversations
+            .get(conversation_id)
+            .map(|sessions| sessions.values().map(|s| s.user_id.clone()).collect())
+            .unwrap_or_default();
+        users.sort();
+        users.dedup();
+        users
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::AppError;
+    use futures::{executor::block_on, future::BoxFuture, StreamExt};
+    use std::sync::Mutex;
+
+    /// Keeps what a node published so the test can hand it to another node
+    #[derive(Default)]
+    struct Wire(Mutex<Vec<Event>>);
+
+    impl EventTransport for Wire {
+        fn publish<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<(), AppError>> {
+            self.0.lock().unwrap().push(event.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    fn nodes() -> (ConversationHub, ConversationHub, Arc<Wire>) {
+        let wire = Arc::new(Wire::default());
+        let sender = ConversationHub::with_transport(wire.clone());
+        let receiver = ConversationHub::new();
+        (sender, receiver, wire)
+    }
+
+    async fn carry(wire: &Wire, to: &ConversationHub) {
+        let events: Vec<Event> = wire.0.lock().unwrap().drain(..).collect();
+        for event in events {
+            assert_eq!(event.entity_type, CONVERSATION_HUB_ENTITY);
+            to.deliver_remote(&event).await;
+        }
+    }
+
+    #[test]
+    fn frames_reach_sessions_on_other_nodes() {
+        block_on(async {
+            let (node_a, node_b, wire) = nodes();
+            let (writer, _writer_rx) = node_a.join("c1", "u1").await;
+            let (_, mut reader_rx) = node_b.join("c1", "u2").await;
+            let (_, mut elsewhere_rx) = node_b.join("c2", "u2").await;
+
+            let typing = WsMessage::Typing {
+                user_id: Some("u1".to_string()),
+                is_typing: true,
+            };
+            node_a.publish("c1", &typing, Some(&writer)).await;
+            carry(&wire, &node_b).await;
+
+            assert_eq!(reader_rx.next().await, Some(typing.to_text()));
+            assert!(elsewhere_rx.try_next().is_err());
+        });
+    }
+
+    #[test]
+    fn unsubscribe_ends_the_users_sessions_on_every_node() {
+        block_on(async {
+            let (node_a, node_b, wire) = nodes();
+            let (_, mut local_rx) = node_a.join("c1", "u2").await;
+            let (_, mut remote_rx) = node_b.join("c1", "u2").await;
+            let (_, mut other_rx) = node_b.join("c1", "u3").await;
+
+            node_a.unsubscribe("c1", "u2").await;
+            carry(&wire, &node_b).await;
+
+            assert_eq!(local_rx.next().await, None);
+            assert_eq!(remote_rx.next().await, None);
+            assert_eq!(node_b.online_users("c1").await, vec!["u3".to_string()]);
+            assert!(other_rx.try_next().is_err());
+        });
+    }
+}
@@ -26,9 +26,7 @@ impl ConversationService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -40,8 +38,8 @@ impl ConversationService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -223,9 +221,7 @@ impl ConversationService {
             .fetch_optional(&self.pool)
             .await
             .map_err(Self::db_error)?
-            .ok_or(AppError {
-                message: "Conversation not found".into(),
-            })?;
+            .ok_or(AppError::not_found("Conversation not found"))?;
 
         let mut conversation = Self::conversation_from_row(&row)?;
         self.hydrate_participants(&mut conversation).await?;
@@ -450,9 +446,7 @@ impl ConversationService {
         .fetch_optional(&self.pool)
         .await
         .map_err(Self::db_error)?
-        .ok_or(AppError {
-            message: "Conversation key not found".into(),
-        })?;
+        .ok_or(AppError::not_found("Conversation key not found"))?;
 
         let id: String = row.try_get("id").map_err(Self::db_error)?;
         let conversation_id: String = row.try_get("conversation_id").map_err(Self::db_error)?;
@@ -471,6 +465,136 @@ impl ConversationService {
         })
     }
 
+    /// Add `participant` to a group conversation with their copy of the
+    /// conversation key. Returns false when they were already a participant.
+    pub async fn add_participant(
+        &self,
+        conversation_id: ObjectId,
+        participant: &ActorRef,
+        encrypted_key: &str,
+    ) -> Result<bool, AppError> {
+        if self.is_participant(conversation_id, participant.id).await? {
+            return Ok(false);
+        }
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let school_id: Option<Option<String>> =
+            sqlx::query_scalar("SELECT school_id FROM conversations WHERE id = $1 AND is_group")
+                .bind(conversation_id.to_hex())
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(Self::db_error)?;
+        let Some(school_id) = school_id else {
+            return Err(AppError::bad_request(
+                "Participants can only be added to group conversations",
+            ));
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO conversation_participants (
+              id, conversation_id, school_id, actor_id, actor_role, role, joined_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $5, now())
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(conversation_id.to_hex())
+        .bind(&school_id)
+        .bind(participant.id.to_hex())
+        .bind(Self::role_to_string(&participant.role))
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO conversation_keys (
+              id, conversation_id, actor_id, actor_role, user_role,
+              encrypted_key, encrypted_key_for_user, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $4, $5, $5, now(), now())
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(conversation_id.to_hex())
+        .bind(participant.id.to_hex())
+        .bind(Self::role_to_string(&participant.role))
+        .bind(encrypted_key)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query("UPDATE conversations SET updated_at = now() WHERE id = $1")
+            .bind(conversation_id.to_hex())
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(true)
+    }
+
+    /// Mark `participant_id` as having left a group conversation and drop
+    /// their copy of the key. Returns false when they were not a participant.
+    pub async fn remove_participant(
+        &self,
+        conversation_id: ObjectId,
+        participant_id: ObjectId,
+    ) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let is_group: Option<bool> =
+            sqlx::query_scalar("SELECT is_group FROM conversations WHERE id = $1 FOR UPDATE")
+                .bind(conversation_id.to_hex())
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(Self::db_error)?;
+        if is_group != Some(true) {
+            return Err(AppError::bad_request(
+                "Participants can only be removed from group conversations",
+            ));
+        }
+
+        let removed = sqlx::query(
+            r#"
+            UPDATE conversation_participants SET left_at = now()
+            WHERE conversation_id = $1
+              AND left_at IS NULL
+              AND COALESCE(actor_id, user_id, student_id) = $2
+            "#,
+        )
+        .bind(conversation_id.to_hex())
+        .bind(participant_id.to_hex())
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?
+        .rows_affected();
+        if removed == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "DELETE FROM conversation_keys \
+             WHERE conversation_id = $1 AND COALESCE(actor_id, user_id) = $2",
+        )
+        .bind(conversation_id.to_hex())
+        .bind(participant_id.to_hex())
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query("UPDATE conversations SET updated_at = now() WHERE id = $1")
+            .bind(conversation_id.to_hex())
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(true)
+    }
+
     pub async fn is_participant(
         &self,
         conversation_id: ObjectId,
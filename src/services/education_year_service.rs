@@ -24,9 +24,9 @@ impl EducationYearQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -65,9 +65,7 @@ impl EducationYearService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -79,8 +77,8 @@ impl EducationYearService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -144,9 +142,10 @@ impl EducationYearService {
                         .push(")");
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported education year filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported education year filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -259,9 +258,7 @@ impl EducationYearService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let curriculum_id = dto.curriculum_id.to_hex();
 
         if self
@@ -269,12 +266,10 @@ impl EducationYearService {
             .await
             .is_ok()
         {
-            return Err(AppError {
-                message: format!(
-                    "Academic year {} already exists under this curriculum",
-                    dto.label
-                ),
-            });
+            return Err(AppError::conflict(format!(
+                "Academic year {} already exists under this curriculum",
+                dto.label
+            )));
         }
 
         let id = dto.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
@@ -323,9 +318,7 @@ impl EducationYearService {
 
         match row {
             Some(row) => self.year_from_row(row).await,
-            None => Err(AppError {
-                message: "Academic year not found".to_string(),
-            }),
+            None => Err(AppError::not_found("Academic year not found")),
         }
     }
 
@@ -418,9 +411,10 @@ impl EducationYearService {
                     .await
                     .is_ok()
             {
-                return Err(AppError {
-                    message: format!("Academic year {} already exists for this curriculum", label),
-                });
+                return Err(AppError::conflict(format!(
+                    "Academic year {} already exists for this curriculum",
+                    label
+                )));
             }
         }
 
@@ -469,15 +463,11 @@ impl EducationYearService {
                 .school_id
                 .as_ref()
                 .map(|id| id.to_hex())
-                .ok_or_else(|| AppError {
-                    message: "school_id is required".into(),
-                })?;
+                .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
             self.sync_terms(&id_string, &school_id, terms).await?;
         }
         if !touched && update.terms.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         self.find_one(Some(id), None).await
@@ -578,9 +568,9 @@ impl EducationYearService {
         let year = match row {
             Some(row) => self.year_from_row(row).await?,
             None => {
-                return Err(AppError {
-                    message: "No active education year found for this date".to_string(),
-                })
+                return Err(AppError::bad_request(
+                    "No active education year found for this date",
+                ))
             }
         };
 
@@ -1,11 +1,16 @@
 use chrono::Utc;
 use futures::channel::mpsc;
-use serde::Serialize;
-use std::collections::HashMap;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::errors::AppError;
+use crate::services::conversation_hub::{ConversationHub, CONVERSATION_HUB_ENTITY};
+
 pub type EventChannel = mpsc::UnboundedSender<String>;
 
 #[derive(Clone, Debug)]
@@ -17,13 +22,68 @@ pub struct ClientSession {
 
 type EventSessions = Arc<RwLock<HashMap<Uuid, ClientSession>>>;
 
+/// Recent events kept per school (and one bucket for events without a school)
+/// so reconnecting clients can catch up from their `Last-Event-ID`.
+pub const REPLAY_BUFFER_SIZE: usize = 500;
+
+#[derive(Default)]
+struct ReplayBucket {
+    events: VecDeque<Event>,
+    /// Highest id that has been evicted from this bucket
+    evicted_through: u64,
+}
+
+type ReplayHistory = Arc<RwLock<HashMap<Option<String>, ReplayBucket>>>;
+
+/// Events a reconnecting client missed. `complete` is false when some of them
+/// are no longer buffered (evicted, or sent before this node started), in
+/// which case the client has to refetch.
+pub struct EventReplay {
+    pub events: Vec<Event>,
+    pub complete: bool,
+}
+
+/// Carries events between API nodes. Every node delivers an event to its own
+/// sessions directly; the transport only has to reach the other nodes, which
+/// hand what they receive to [`EventBus::deliver_local`].
+pub trait EventTransport: Send + Sync {
+    fn publish<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<(), AppError>>;
+
+    /// Cluster-wide event id. `None` lets the bus number events itself, which
+    /// is only monotonic within one process.
+    fn next_event_id(&self) -> BoxFuture<'_, Result<Option<u64>, AppError>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Start receiving events from other nodes. Called once at startup.
+    fn subscribe(self: Arc<Self>, _bus: Arc<EventBus>) {}
+}
+
+/// Single-node transport: nothing leaves the process.
+pub struct LocalTransport;
+
+impl EventTransport for LocalTransport {
+    fn publish<'a>(&'a self, _event: &'a Event) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
 #[derive(Clone)]
 pub struct EventBus {
     sessions: EventSessions,
+    transport: Arc<dyn EventTransport>,
+    history: ReplayHistory,
+    local_ids: Arc<AtomicU64>,
+    /// First id this node can replay from; older ids may have been missed
+    replay_floor: Arc<AtomicU64>,
+    /// Receives the conversation frames other nodes relay over the transport
+    conversation_hub: Option<Arc<ConversationHub>>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Event {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<u64>, // Stream position, sent as the SSE `id:` field
     pub event_type: String,
     pub entity_type: String,
     pub entity_id: Option<String>,
@@ -31,11 +91,14 @@ pub struct Event {
     pub timestamp: chrono::DateTime<Utc>,
     pub school_id: Option<String>, // Which school this event belongs to
     pub target_user_id: Option<String>, // For user-specific events
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub notification_id: Option<String>, // Inbox entry persisted for this event
 }
 
 impl Event {
     pub fn new(event_type: &str, entity_type: &str, data: serde_json::Value) -> Self {
         Self {
+            id: None,
             event_type: event_type.to_string(),
             entity_type: entity_type.to_string(),
             entity_id: None,
@@ -43,6 +106,7 @@ impl Event {
             timestamp: Utc::now(),
             school_id: None,
             target_user_id: None,
+            notification_id: None,
         }
     }
 
@@ -61,20 +125,32 @@ impl Event {
         self
     }
 
+    pub fn with_notification_id(mut self, notification_id: Option<String>) -> Self {
+        self.notification_id = notification_id;
+        self
+    }
+
     pub fn to_sse_format(&self) -> String {
         let json_data = serde_json::to_string(&self).unwrap_or_else(|_| "{}".to_string());
-        format!("data: {}\n\n", json_data)
+        match self.id {
+            Some(id) => format!("id: {}\ndata: {}\n\n", id, json_data),
+            None => format!("data: {}\n\n", json_data),
+        }
     }
 
     /// Check if event should be sent to a specific client
     fn should_send_to_client(&self, client: &ClientSession) -> bool {
+        self.is_visible_to(&client.user_id, client.school_id.as_deref())
+    }
+
+    fn is_visible_to(&self, user_id: &str, school_id: Option<&str>) -> bool {
         // User-specific event - only send to that user
         if let Some(target_user) = &self.target_user_id {
-            return target_user == &client.user_id;
+            return target_user == user_id;
         }
 
         // School event logic
-        match (&self.school_id, &client.school_id) {
+        match (self.school_id.as_deref(), school_id) {
             // Event has school_id and client has school_id - must match
             (Some(event_school), Some(client_school)) => event_school == client_school,
 
@@ -92,14 +168,112 @@ pub const EVENT_CREATED: &str = "created";
 pub const EVENT_UPDATED: &str = "updated";
 pub const EVENT_DELETED: &str = "deleted";
 pub const EVENT_CONNECTED: &str = "connected";
+pub const EVENT_RESYNC: &str = "resync";
 
 impl EventBus {
     pub fn new() -> Self {
+        Self::with_transport(Arc::new(LocalTransport))
+    }
+
+    pub fn with_transport(transport: Arc<dyn EventTransport>) -> Self {
+        // Local ids start at the current time so they keep increasing across
+        // restarts of a single node.
+        let local_start = Utc::now().timestamp_micros().max(0) as u64;
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            transport,
+            history: Arc::new(RwLock::new(HashMap::new())),
+            local_ids: Arc::new(AtomicU64::new(local_start)),
+            replay_floor: Arc::new(AtomicU64::new(local_start)),
+            conversation_hub: None,
+        }
+    }
+
+    pub fn with_conversation_hub(mut self, hub: Arc<ConversationHub>) -> Self {
+        self.conversation_hub = Some(hub);
+        self
+    }
+
+    /// Start receiving events published by other nodes.
+    pub async fn start_transport(self: &Arc<Self>) {
+        // Anything numbered before this point was never seen by this node
+        if let Ok(Some(id)) = self.transport.next_event_id().await {
+            self.replay_floor.store(id, Ordering::SeqCst);
+        }
+        self.transport.clone().subscribe(self.clone());
+    }
+
+    async fn assign_id(&self) -> Option<u64> {
+        match self.transport.next_event_id().await {
+            Ok(Some(id)) => Some(id),
+            Ok(None) => Some(self.local_ids.fetch_add(1, Ordering::SeqCst) + 1),
+            Err(err) => {
+                // Local numbers would not line up with the cluster sequence;
+                // send the event without an id instead
+                log::warn!("Failed to number event: {}", err.message);
+                None
+            }
         }
     }
 
+    async fn remember(&self, event: &Event) {
+        let Some(id) = event.id else {
+            return;
+        };
+        let mut history = self.history.write().await;
+        let bucket = history.entry(event.school_id.clone()).or_default();
+        // Events from other nodes can arrive slightly out of order
+        let position = bucket
+            .events
+            .iter()
+            .rposition(|buffered| buffered.id.unwrap_or(0) < id)
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        bucket.events.insert(position, event.clone());
+        while bucket.events.len() > REPLAY_BUFFER_SIZE {
+            if let Some(evicted) = bucket.events.pop_front() {
+                bucket.evicted_through = bucket.evicted_through.max(evicted.id.unwrap_or(0));
+            }
+        }
+    }
+
+    /// Buffered events after `last_event_id` that a client of `school_id`
+    /// (or without a school) would have received, oldest first.
+    pub async fn replay_since(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+        last_event_id: u64,
+    ) -> EventReplay {
+        let history = self.history.read().await;
+        let mut complete = last_event_id + 1 >= self.replay_floor.load(Ordering::SeqCst);
+        let mut events = Vec::new();
+
+        let mut keys = vec![None];
+        if let Some(school_id) = school_id {
+            keys.push(Some(school_id.to_string()));
+        }
+        for key in keys {
+            let Some(bucket) = history.get(&key) else {
+                continue;
+            };
+            if bucket.evicted_through > last_event_id {
+                complete = false;
+            }
+            events.extend(
+                bucket
+                    .events
+                    .iter()
+                    .filter(|event| event.id.unwrap_or(0) > last_event_id)
+                    .filter(|event| event.is_visible_to(user_id, school_id))
+                    .cloned(),
+            );
+        }
+
+        events.sort_by_key(|event| event.id);
+        EventReplay { events, complete }
+    }
+
     /// Register a new event client (school_id is optional)
     pub async fn register_client(
         &self,
@@ -134,8 +308,35 @@ impl EventBus {
         println!("🔔 Event client disconnected: {}", client_id);
     }
 
-    /// Broadcast event with automatic filtering
+    /// Broadcast event to the sessions of every node
     pub async fn broadcast_event(&self, event: &Event) {
+        let mut event = event.clone();
+        event.id = self.assign_id().await;
+        let event = &event;
+        self.deliver_local(event).await;
+
+        if let Err(err) = self.transport.publish(event).await {
+            log::warn!(
+                "Failed to publish {}::{} to other nodes: {}",
+                event.entity_type,
+                event.event_type,
+                err.message
+            );
+        }
+    }
+
+    /// Send an event to the sessions connected to this node, with automatic
+    /// filtering
+    pub async fn deliver_local(&self, event: &Event) {
+        // Conversation frames belong to WebSocket sessions, not the stream
+        if event.entity_type == CONVERSATION_HUB_ENTITY {
+            if let Some(hub) = &self.conversation_hub {
+                hub.deliver_remote(event).await;
+            }
+            return;
+        }
+        self.remember(event).await;
+
         let sessions = self.sessions.read().await;
         let mut disconnected_clients = Vec::new();
         let mut sent_count = 0;
@@ -156,9 +357,13 @@ impl EventBus {
             }
         }
 
+        // Release the read lock before cleaning up, or the write lock below
+        // would wait on ourselves
+        let total_clients = sessions.len();
+        drop(sessions);
+
         // Clean up disconnected clients
         if !disconnected_clients.is_empty() {
-            drop(sessions.clone());
             let mut sessions_write = self.sessions.write().await;
             for client_id in disconnected_clients {
                 sessions_write.remove(&client_id);
@@ -167,11 +372,7 @@ impl EventBus {
 
         println!(
             "🔔 Broadcasted event: {}::{} to {}/{} clients (school: {:?})",
-            event.entity_type,
-            event.event_type,
-            sent_count,
-            sessions.len(),
-            event.school_id
+            event.entity_type, event.event_type, sent_count, total_clients, event.school_id
         );
     }
 
@@ -1,25 +1,91 @@
 use crate::config::state::AppState;
+use crate::domain::notification::Notification;
+use crate::repositories::notification_repo::NotificationRepo;
+use crate::services::event_bus::{Event, EVENT_CREATED, EVENT_DELETED, EVENT_UPDATED};
+use crate::services::notification_service::NotificationService;
+use crate::utils::request_context::postgres_pool;
 use actix_web::web;
 use serde::Serialize;
 
 pub struct EventService;
 
 impl EventService {
-    /// Broadcast entity creation event
-    /// school_id: Some(id) for school events, None for global events
-    pub async fn broadcast_created<T: Serialize>(
+    /// Persist the event in the notification inbox so users who are not
+    /// connected still see it. Global events (no school, no user) are only
+    /// streamed. Returns the notification id when one was stored.
+    async fn persist(
+        state: &web::Data<AppState>,
+        event: &Event,
+        school_id: Option<&str>,
+        user_id: Option<&str>,
+    ) -> Option<String> {
+        if school_id.is_none() && user_id.is_none() {
+            return None;
+        }
+
+        let notification = Notification::from_event(
+            &event.event_type,
+            &event.entity_type,
+            event.entity_id.as_deref(),
+            school_id,
+            user_id,
+            event.data.clone(),
+        );
+        let repo = NotificationRepo::new(postgres_pool(state));
+        match NotificationService::new(&repo).record(&notification).await {
+            Ok(saved) => saved.id.map(|id| id.to_hex()),
+            Err(err) => {
+                log::warn!(
+                    "Failed to store notification for {}::{}: {}",
+                    event.entity_type,
+                    event.event_type,
+                    err.message
+                );
+                None
+            }
+        }
+    }
+
+    async fn publish_school_event<T: Serialize>(
         state: &web::Data<AppState>,
+        event_type: &str,
         entity_type: &str,
         entity_id: &str,
         school_id: Option<String>,
         data: &T,
     ) {
+        let json_data = serde_json::to_value(data).unwrap_or_else(|_| serde_json::Value::Null);
+        let event = Event::new(event_type, entity_type, json_data)
+            .with_entity_id(entity_id)
+            .for_school(school_id.clone());
+        let notification_id = Self::persist(state, &event, school_id.as_deref(), None).await;
+
         state
             .event_bus
-            .broadcast_created(entity_type, entity_id, school_id, data)
+            .broadcast_event(&event.with_notification_id(notification_id))
             .await;
     }
 
+    /// Broadcast entity creation event
+    /// school_id: Some(id) for school events, None for global events
+    pub async fn broadcast_created<T: Serialize>(
+        state: &web::Data<AppState>,
+        entity_type: &str,
+        entity_id: &str,
+        school_id: Option<String>,
+        data: &T,
+    ) {
+        Self::publish_school_event(
+            state,
+            EVENT_CREATED,
+            entity_type,
+            entity_id,
+            school_id,
+            data,
+        )
+        .await;
+    }
+
     /// Broadcast entity update event
     pub async fn broadcast_updated<T: Serialize>(
         state: &web::Data<AppState>,
@@ -28,10 +94,15 @@ impl EventService {
         school_id: Option<String>,
         data: &T,
     ) {
-        state
-            .event_bus
-            .broadcast_updated(entity_type, entity_id, school_id, data)
-            .await;
+        Self::publish_school_event(
+            state,
+            EVENT_UPDATED,
+            entity_type,
+            entity_id,
+            school_id,
+            data,
+        )
+        .await;
     }
 
     /// Broadcast entity deletion event
@@ -42,10 +113,15 @@ impl EventService {
         school_id: Option<String>,
         data: &T,
     ) {
-        state
-            .event_bus
-            .broadcast_deleted(entity_type, entity_id, school_id, data)
-            .await;
+        Self::publish_school_event(
+            state,
+            EVENT_DELETED,
+            entity_type,
+            entity_id,
+            school_id,
+            data,
+        )
+        .await;
     }
 
     /// Broadcast to specific user (e.g., notifications)
@@ -57,9 +133,15 @@ impl EventService {
         user_id: &str,
         data: &T,
     ) {
+        let json_data = serde_json::to_value(data).unwrap_or_else(|_| serde_json::Value::Null);
+        let event = Event::new(event_type, entity_type, json_data)
+            .with_entity_id(entity_id)
+            .for_user(user_id);
+        let notification_id = Self::persist(state, &event, None, Some(user_id)).await;
+
         state
             .event_bus
-            .broadcast_to_user(event_type, entity_type, entity_id, user_id, data)
+            .broadcast_event(&event.with_notification_id(notification_id))
             .await;
     }
 }
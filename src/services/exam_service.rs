@@ -29,9 +29,9 @@ impl ExamQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -73,9 +73,7 @@ impl ExamService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -87,8 +85,8 @@ impl ExamService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -179,9 +177,10 @@ impl ExamService {
                         .push(")");
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported exam filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported exam filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -242,9 +241,7 @@ impl ExamService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let id = exam.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
 
         sqlx::query(
@@ -291,9 +288,7 @@ impl ExamService {
 
         match row {
             Some(row) => Self::exam_from_row(row),
-            None => Err(AppError {
-                message: "Exam not found".into(),
-            }),
+            None => Err(AppError::not_found("Exam not found")),
         }
     }
 
@@ -427,9 +422,7 @@ impl ExamService {
         }
 
         if !touched {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         sql.push(" WHERE id = ")
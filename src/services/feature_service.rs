@@ -12,7 +12,7 @@ impl FeatureService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn id_to_string(id: &IdType) -> Result<String, AppError> {
@@ -0,0 +1,696 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+
+use crate::{
+    domain::{
+        common_details::Paginated,
+        finance::{
+            AdjustmentRequest, FeeStructure, FeeStructurePartial, FinanceQuery, FinanceRecord,
+            FinanceRecordType, FinanceStatus, GenerateInvoicesRequest, GenerateInvoicesResult,
+            InvoiceWithLedger, Receipt, RecordPaymentRequest, RefundRequest, StatementLine,
+            StudentStatement, DEFAULT_CURRENCY,
+        },
+    },
+    errors::AppError,
+    models::id_model::IdType,
+    repositories::finance_repo::FinanceRepo,
+    utils::object_id::ObjectId,
+};
+
+/// Amounts are stored as NUMERIC but handled as f64; anything below this is rounding noise.
+const BALANCE_EPSILON: f64 = 0.005;
+
+pub struct FinanceService<'a> {
+    repo: &'a FinanceRepo,
+}
+
+impl<'a> FinanceService<'a> {
+    pub fn new(repo: &'a FinanceRepo) -> Self {
+        Self { repo }
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn ensure_school(record_school: Option<ObjectId>, school_id: &str) -> Result<(), AppError> {
+        match record_school {
+            Some(id) if id.to_hex() == school_id => Ok(()),
+            _ => Err(AppError::not_found("Finance record not found")),
+        }
+    }
+
+    fn ensure_positive(amount: f64) -> Result<(), AppError> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(AppError::bad_request("amount must be greater than zero"));
+        }
+        Ok(())
+    }
+
+    fn reference(prefix: &str, id: &ObjectId) -> String {
+        format!("{}-{}", prefix, id.to_hex().to_uppercase())
+    }
+
+    /// Totals for an invoice from its linked ledger rows: (amount_due, amount_paid).
+    fn invoice_totals(invoice: &FinanceRecord, entries: &[FinanceRecord]) -> (f64, f64) {
+        let mut amount_due = invoice.amount;
+        let mut amount_paid = 0.0;
+        for entry in entries {
+            match entry.record_type {
+                FinanceRecordType::Adjustment | FinanceRecordType::BalanceForward => {
+                    amount_due += entry.amount
+                }
+                FinanceRecordType::Payment => amount_paid += entry.amount,
+                FinanceRecordType::Refund => amount_paid -= entry.amount,
+                FinanceRecordType::Invoice | FinanceRecordType::Fee => {}
+            }
+        }
+        (amount_due, amount_paid)
+    }
+
+    fn status_for(amount_due: f64, amount_paid: f64) -> FinanceStatus {
+        if amount_due - amount_paid <= BALANCE_EPSILON {
+            FinanceStatus::Paid
+        } else if amount_paid > BALANCE_EPSILON {
+            FinanceStatus::Partial
+        } else {
+            FinanceStatus::Pending
+        }
+    }
+
+    fn with_ledger(invoice: FinanceRecord, entries: Vec<FinanceRecord>) -> InvoiceWithLedger {
+        let (amount_due, amount_paid) = Self::invoice_totals(&invoice, &entries);
+        InvoiceWithLedger {
+            invoice,
+            entries,
+            amount_due,
+            amount_paid,
+            balance: amount_due - amount_paid,
+        }
+    }
+
+    fn ensure_open(invoice: &FinanceRecord) -> Result<(), AppError> {
+        match invoice.status {
+            FinanceStatus::Cancelled | FinanceStatus::CarriedForward => Err(AppError::bad_request(
+                "Invoice is closed; its balance was carried forward or it was cancelled",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // ========== FEE STRUCTURES ==========
+
+    pub async fn create_structure(
+        &self,
+        school_id: &str,
+        mut structure: FeeStructure,
+        created_by: Option<&str>,
+    ) -> Result<FeeStructure, AppError> {
+        structure.validate().map_err(AppError::validation)?;
+
+        if let Some(class_id) = structure.class_id {
+            let class_school = self.repo.class_school_id(&class_id.to_hex()).await?;
+            if class_school.as_deref() != Some(school_id) {
+                return Err(AppError::bad_request(
+                    "Class does not belong to this school",
+                ));
+            }
+        }
+
+        structure.id = None;
+        structure.school_id = Some(Self::parse_oid(school_id, "school_id")?);
+        structure.created_by = created_by
+            .map(|id| Self::parse_oid(id, "created_by"))
+            .transpose()?;
+
+        self.repo.insert_structure(&structure).await
+    }
+
+    pub async fn get_structures(
+        &self,
+        school_id: &str,
+        query: &FinanceQuery,
+    ) -> Result<Vec<FeeStructure>, AppError> {
+        self.repo.list_structures(school_id, query).await
+    }
+
+    pub async fn find_structure(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<FeeStructure, AppError> {
+        let structure = self.repo.find_structure(id).await?;
+        Self::ensure_school(structure.school_id, school_id)?;
+        Ok(structure)
+    }
+
+    /// Item changes only affect invoices generated afterwards; issued invoices
+    /// are corrected with adjustments.
+    pub async fn update_structure(
+        &self,
+        id: &IdType,
+        school_id: &str,
+        update: FeeStructurePartial,
+    ) -> Result<FeeStructure, AppError> {
+        let mut merged = self.find_structure(id, school_id).await?;
+        if let Some(name) = update.name.clone() {
+            merged.name = name;
+        }
+        if let Some(items) = update.items.clone() {
+            merged.items = items;
+        }
+        merged.validate().map_err(AppError::validation)?;
+
+        if let Some(Some(class_id)) = update.class_id {
+            let class_school = self.repo.class_school_id(&class_id.to_hex()).await?;
+            if class_school.as_deref() != Some(school_id) {
+                return Err(AppError::bad_request(
+                    "Class does not belong to this school",
+                ));
+            }
+        }
+
+        self.repo.update_structure(id, &update).await
+    }
+
+    pub async fn delete_structure(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<FeeStructure, AppError> {
+        let structure = self.find_structure(id, school_id).await?;
+        self.repo.soft_delete_structure(id).await?;
+        Ok(structure)
+    }
+
+    // ========== INVOICES ==========
+
+    /// Bill every active student of the structure's class. Students that
+    /// already have an invoice for this structure are skipped, so the call is
+    /// safe to repeat after new enrollments.
+    pub async fn generate_invoices(
+        &self,
+        structure_id: &IdType,
+        school_id: &str,
+        request: GenerateInvoicesRequest,
+        created_by: Option<&str>,
+    ) -> Result<GenerateInvoicesResult, AppError> {
+        let structure = self.find_structure(structure_id, school_id).await?;
+        if structure.is_active == Some(false) {
+            return Err(AppError::bad_request("Fee structure is not active"));
+        }
+        let class_id = structure
+            .class_id
+            .ok_or_else(|| AppError::bad_request("Fee structure has no class to bill"))?;
+        let structure_hex = structure.id.map(|id| id.to_hex()).unwrap_or_default();
+        let created_by = created_by
+            .map(|id| Self::parse_oid(id, "created_by"))
+            .transpose()?;
+
+        let students = self.repo.billable_students(&class_id.to_hex()).await?;
+        let already_billed: HashSet<String> = self
+            .repo
+            .invoiced_profiles(&structure_hex)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut result = GenerateInvoicesResult::default();
+        let now = Utc::now();
+        let mut tx = self.repo.begin().await?;
+
+        for student in students {
+            if already_billed.contains(&student.profile_id) {
+                result.skipped_existing += 1;
+                continue;
+            }
+
+            let invoice_id = ObjectId::new();
+            let invoice = FinanceRecord {
+                id: Some(invoice_id),
+                school_id: structure.school_id,
+                student_id: Some(Self::parse_oid(&student.enrollment_id, "student_id")?),
+                record_type: FinanceRecordType::Invoice,
+                description: Some(structure.name.clone()),
+                amount: structure.mandatory_total(),
+                currency: structure.currency.clone(),
+                status: FinanceStatus::Pending,
+                due_date: request.due_date.or(structure.due_date),
+                paid_at: None,
+                invoice_id: None,
+                related_record_id: None,
+                fee_structure_id: structure.id,
+                class_id: Some(class_id),
+                education_year_id: structure.education_year_id,
+                term_id: structure.term_id.clone(),
+                reference_number: Some(Self::reference("INV", &invoice_id)),
+                payment_method: None,
+                created_by,
+                created_at: Some(now),
+                updated_at: Some(now),
+            };
+            FinanceRepo::insert_record(&mut tx, &invoice, Some(&student.profile_id)).await?;
+
+            if request.carry_forward_balances {
+                let open = FinanceRepo::open_invoices_for_student(
+                    &mut tx,
+                    &student.profile_id,
+                    &structure_hex,
+                )
+                .await?;
+
+                let mut carried = 0.0;
+                for previous in open {
+                    let previous_id = previous.id.map(|id| id.to_hex()).unwrap_or_default();
+                    let entries = FinanceRepo::invoice_entries(&mut tx, &previous_id).await?;
+                    let (due, paid) = Self::invoice_totals(&previous, &entries);
+                    let balance = due - paid;
+                    if balance <= BALANCE_EPSILON {
+                        continue;
+                    }
+
+                    let description = format!(
+                        "Balance brought forward from {}",
+                        previous
+                            .reference_number
+                            .clone()
+                            .unwrap_or_else(|| previous_id.clone())
+                    );
+                    let forward = FinanceRecord {
+                        id: None,
+                        record_type: FinanceRecordType::BalanceForward,
+                        description: Some(description),
+                        amount: balance,
+                        status: FinanceStatus::Completed,
+                        due_date: None,
+                        invoice_id: Some(invoice_id),
+                        related_record_id: previous.id,
+                        reference_number: None,
+                        ..invoice.clone()
+                    };
+                    FinanceRepo::insert_record(&mut tx, &forward, Some(&student.profile_id))
+                        .await?;
+
+                    let closing = FinanceRecord {
+                        description: Some(format!(
+                            "Balance carried forward to {}",
+                            invoice.reference_number.clone().unwrap_or_default()
+                        )),
+                        amount: -balance,
+                        invoice_id: previous.id,
+                        related_record_id: Some(invoice_id),
+                        ..forward
+                    };
+                    FinanceRepo::insert_record(&mut tx, &closing, Some(&student.profile_id))
+                        .await?;
+                    FinanceRepo::set_status(
+                        &mut tx,
+                        &previous_id,
+                        FinanceStatus::CarriedForward,
+                        None,
+                    )
+                    .await?;
+                    carried += balance;
+                }
+
+                if carried > 0.0 {
+                    result.carried_forward += 1;
+                }
+            }
+
+            result.created += 1;
+            result.invoices.push(invoice);
+        }
+
+        tx.commit().await.map_err(AppError::from)?;
+
+        Ok(result)
+    }
+
+    pub async fn get_records(
+        &self,
+        school_id: &str,
+        query: &FinanceQuery,
+    ) -> Result<Paginated<FinanceRecord>, AppError> {
+        self.repo.list_records(school_id, query).await
+    }
+
+    pub async fn find_record(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<FinanceRecord, AppError> {
+        let record = self.repo.find_record(id).await?;
+        Self::ensure_school(record.school_id, school_id)?;
+        Ok(record)
+    }
+
+    pub async fn get_invoice(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<InvoiceWithLedger, AppError> {
+        let invoice = self.find_record(id, school_id).await?;
+        if invoice.record_type != FinanceRecordType::Invoice {
+            return Err(AppError::bad_request("Record is not an invoice"));
+        }
+
+        let entries = self
+            .repo
+            .find_invoice_entries(&invoice.id.map(|id| id.to_hex()).unwrap_or_default())
+            .await?;
+        Ok(Self::with_ledger(invoice, entries))
+    }
+
+    // ========== PAYMENTS ==========
+
+    /// Post a payment against an invoice. Overpayments are rejected; the
+    /// invoice moves to `partial` or `paid` in the same transaction.
+    pub async fn record_payment(
+        &self,
+        school_id: &str,
+        request: RecordPaymentRequest,
+        recorded_by: Option<&str>,
+    ) -> Result<Receipt, AppError> {
+        Self::ensure_positive(request.amount)?;
+        let recorded_by = recorded_by
+            .map(|id| Self::parse_oid(id, "recorded_by"))
+            .transpose()?;
+        let invoice_hex = request.invoice_id.to_hex();
+
+        let mut tx = self.repo.begin().await?;
+        let invoice = FinanceRepo::lock_invoice(&mut tx, &invoice_hex).await?;
+        Self::ensure_school(invoice.school_id, school_id)?;
+        Self::ensure_open(&invoice)?;
+
+        let entries = FinanceRepo::invoice_entries(&mut tx, &invoice_hex).await?;
+        let (amount_due, amount_paid) = Self::invoice_totals(&invoice, &entries);
+        let balance = amount_due - amount_paid;
+        if request.amount > balance + BALANCE_EPSILON {
+            return Err(AppError::bad_request(format!(
+                "Payment of {:.2} exceeds the outstanding balance of {:.2}",
+                request.amount,
+                balance.max(0.0)
+            )));
+        }
+
+        let paid_at = request.paid_at.unwrap_or_else(Utc::now);
+        let payment_id = ObjectId::new();
+        let payment = FinanceRecord {
+            id: Some(payment_id),
+            record_type: FinanceRecordType::Payment,
+            description: request.description.clone(),
+            amount: request.amount,
+            status: FinanceStatus::Completed,
+            due_date: None,
+            paid_at: Some(paid_at),
+            invoice_id: invoice.id,
+            related_record_id: None,
+            reference_number: request.reference_number.clone(),
+            payment_method: Some(request.payment_method),
+            created_by: recorded_by,
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            ..invoice.clone()
+        };
+        let profile_id = self.profile_for(&invoice).await?;
+        FinanceRepo::insert_record(&mut tx, &payment, profile_id.as_deref()).await?;
+
+        let status = Self::status_for(amount_due, amount_paid + request.amount);
+        let settled_at = (status == FinanceStatus::Paid).then_some(paid_at);
+        FinanceRepo::set_status(&mut tx, &invoice_hex, status, settled_at).await?;
+
+        tx.commit().await.map_err(AppError::from)?;
+
+        self.build_receipt(payment, &invoice, balance - request.amount)
+            .await
+    }
+
+    async fn profile_for(&self, invoice: &FinanceRecord) -> Result<Option<String>, AppError> {
+        let (Some(school_id), Some(student_id)) = (invoice.school_id, invoice.student_id) else {
+            return Ok(None);
+        };
+        Ok(self
+            .repo
+            .student_enrollment(&school_id.to_hex(), &student_id.to_hex())
+            .await?
+            .map(|student| student.profile_id))
+    }
+
+    async fn build_receipt(
+        &self,
+        payment: FinanceRecord,
+        invoice: &FinanceRecord,
+        balance_after_payment: f64,
+    ) -> Result<Receipt, AppError> {
+        let context = self
+            .repo
+            .receipt_context(
+                payment.school_id.map(|id| id.to_hex()).as_deref(),
+                payment.student_id.map(|id| id.to_hex()).as_deref(),
+            )
+            .await?;
+
+        Ok(Receipt {
+            receipt_number: Self::reference("RCT", &payment.id.unwrap_or_default()),
+            school_name: context.school_name,
+            student_name: context.student_name,
+            registration_number: context.registration_number,
+            invoice_reference: invoice.reference_number.clone(),
+            balance_after_payment: balance_after_payment.max(0.0),
+            issued_at: payment.paid_at.unwrap_or_else(Utc::now),
+            payment,
+        })
+    }
+
+    /// Receipt for an existing payment; the balance shown is the invoice
+    /// balance right after this payment was posted.
+    pub async fn get_receipt(
+        &self,
+        payment_id: &IdType,
+        school_id: &str,
+    ) -> Result<Receipt, AppError> {
+        let payment = self.find_record(payment_id, school_id).await?;
+        if payment.record_type != FinanceRecordType::Payment {
+            return Err(AppError::bad_request("Record is not a payment"));
+        }
+        let invoice_id = payment
+            .invoice_id
+            .ok_or_else(|| AppError::bad_request("Payment is not linked to an invoice"))?;
+        let invoice = self
+            .repo
+            .find_record(&IdType::from_string(invoice_id.to_hex()))
+            .await?;
+
+        let entries = self.repo.find_invoice_entries(&invoice_id.to_hex()).await?;
+        let mut upto = Vec::new();
+        for entry in entries {
+            let is_payment = entry.id == payment.id;
+            upto.push(entry);
+            if is_payment {
+                break;
+            }
+        }
+        let (due, paid) = Self::invoice_totals(&invoice, &upto);
+
+        self.build_receipt(payment, &invoice, due - paid).await
+    }
+
+    /// Refund part or all of a payment. The invoice balance goes back up and
+    /// its status is recomputed.
+    pub async fn refund_payment(
+        &self,
+        payment_id: &IdType,
+        school_id: &str,
+        request: RefundRequest,
+        recorded_by: Option<&str>,
+    ) -> Result<FinanceRecord, AppError> {
+        Self::ensure_positive(request.amount)?;
+        if request.reason.trim().is_empty() {
+            return Err(AppError::field("reason", "reason is required"));
+        }
+        let payment = self.find_record(payment_id, school_id).await?;
+        if payment.record_type != FinanceRecordType::Payment {
+            return Err(AppError::bad_request("Only payments can be refunded"));
+        }
+        let payment_hex = payment.id.map(|id| id.to_hex()).unwrap_or_default();
+        let invoice_hex = payment
+            .invoice_id
+            .map(|id| id.to_hex())
+            .ok_or_else(|| AppError::bad_request("Payment is not linked to an invoice"))?;
+        let recorded_by = recorded_by
+            .map(|id| Self::parse_oid(id, "recorded_by"))
+            .transpose()?;
+
+        let mut tx = self.repo.begin().await?;
+        let invoice = FinanceRepo::lock_invoice(&mut tx, &invoice_hex).await?;
+        Self::ensure_open(&invoice)?;
+        let refunded = FinanceRepo::refunded_amount(&mut tx, &payment_hex).await?;
+        let refundable = payment.amount - refunded;
+        if request.amount > refundable + BALANCE_EPSILON {
+            return Err(AppError::bad_request(format!(
+                "Refund of {:.2} exceeds the refundable amount of {:.2}",
+                request.amount,
+                refundable.max(0.0)
+            )));
+        }
+
+        let now = Utc::now();
+        let refund = FinanceRecord {
+            id: Some(ObjectId::new()),
+            record_type: FinanceRecordType::Refund,
+            description: Some(request.reason),
+            amount: request.amount,
+            status: FinanceStatus::Completed,
+            paid_at: Some(now),
+            related_record_id: payment.id,
+            reference_number: None,
+            created_by: recorded_by,
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..payment.clone()
+        };
+        let profile_id = self.profile_for(&invoice).await?;
+        FinanceRepo::insert_record(&mut tx, &refund, profile_id.as_deref()).await?;
+
+        let entries = FinanceRepo::invoice_entries(&mut tx, &invoice_hex).await?;
+        let (due, paid) = Self::invoice_totals(&invoice, &entries);
+        FinanceRepo::set_status(&mut tx, &invoice_hex, Self::status_for(due, paid), None).await?;
+
+        tx.commit().await.map_err(AppError::from)?;
+
+        Ok(refund)
+    }
+
+    /// Discounts, bursaries (negative) or penalties (positive) on an invoice.
+    pub async fn adjust_invoice(
+        &self,
+        school_id: &str,
+        request: AdjustmentRequest,
+        recorded_by: Option<&str>,
+    ) -> Result<InvoiceWithLedger, AppError> {
+        if !request.amount.is_finite() || request.amount.abs() <= BALANCE_EPSILON {
+            return Err(AppError::bad_request("amount must be a non-zero number"));
+        }
+        if request.reason.trim().is_empty() {
+            return Err(AppError::field("reason", "reason is required"));
+        }
+        let recorded_by = recorded_by
+            .map(|id| Self::parse_oid(id, "recorded_by"))
+            .transpose()?;
+        let invoice_hex = request.invoice_id.to_hex();
+
+        let mut tx = self.repo.begin().await?;
+        let invoice = FinanceRepo::lock_invoice(&mut tx, &invoice_hex).await?;
+        Self::ensure_school(invoice.school_id, school_id)?;
+        Self::ensure_open(&invoice)?;
+
+        let entries = FinanceRepo::invoice_entries(&mut tx, &invoice_hex).await?;
+        let (amount_due, amount_paid) = Self::invoice_totals(&invoice, &entries);
+        let new_due = amount_due + request.amount;
+        if new_due < -BALANCE_EPSILON {
+            return Err(AppError::bad_request(
+                "Adjustment would make the invoice total negative",
+            ));
+        }
+        if new_due + BALANCE_EPSILON < amount_paid {
+            return Err(AppError::bad_request(
+                "Adjustment would leave the invoice overpaid; refund the payment first",
+            ));
+        }
+
+        let now = Utc::now();
+        let adjustment = FinanceRecord {
+            id: Some(ObjectId::new()),
+            record_type: FinanceRecordType::Adjustment,
+            description: Some(request.reason),
+            amount: request.amount,
+            status: FinanceStatus::Completed,
+            due_date: None,
+            paid_at: None,
+            invoice_id: invoice.id,
+            related_record_id: None,
+            reference_number: None,
+            payment_method: None,
+            created_by: recorded_by,
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..invoice.clone()
+        };
+        let profile_id = self.profile_for(&invoice).await?;
+        FinanceRepo::insert_record(&mut tx, &adjustment, profile_id.as_deref()).await?;
+
+        let status = Self::status_for(new_due, amount_paid);
+        let settled_at = (status == FinanceStatus::Paid).then_some(now);
+        FinanceRepo::set_status(&mut tx, &invoice_hex, status, settled_at).await?;
+
+        let entries = FinanceRepo::invoice_entries(&mut tx, &invoice_hex).await?;
+        tx.commit().await.map_err(AppError::from)?;
+
+        Ok(Self::with_ledger(
+            FinanceRecord { status, ..invoice },
+            entries,
+        ))
+    }
+
+    // ========== STATEMENTS ==========
+
+    /// Chronological ledger of a student with a running balance.
+    pub async fn student_statement(
+        &self,
+        school_id: &str,
+        student_id: &str,
+    ) -> Result<StudentStatement, AppError> {
+        Self::parse_oid(student_id, "student_id")?;
+        let student = self
+            .repo
+            .student_enrollment(school_id, student_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Student not found in this school"))?;
+
+        let records = self
+            .repo
+            .student_ledger(school_id, &student.profile_id)
+            .await?;
+
+        let mut statement = StudentStatement {
+            student_id: Self::parse_oid(&student.enrollment_id, "student_id")?,
+            currency: records
+                .first()
+                .map(|record| record.currency.clone())
+                .unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+            total_billed: 0.0,
+            total_paid: 0.0,
+            total_refunded: 0.0,
+            balance: 0.0,
+            lines: Vec::with_capacity(records.len()),
+        };
+
+        for record in records {
+            match record.record_type {
+                FinanceRecordType::Invoice | FinanceRecordType::Adjustment => {
+                    statement.total_billed += record.amount
+                }
+                FinanceRecordType::Fee => {
+                    statement.total_billed += record.amount;
+                    if record.status == FinanceStatus::Paid {
+                        statement.total_paid += record.amount;
+                    }
+                }
+                FinanceRecordType::Payment => statement.total_paid += record.amount,
+                FinanceRecordType::Refund => statement.total_refunded += record.amount,
+                FinanceRecordType::BalanceForward => {}
+            }
+            statement.balance += record.signed_amount();
+            statement.lines.push(StatementLine {
+                running_balance: statement.balance,
+                record,
+            });
+        }
+
+        Ok(statement)
+    }
+}
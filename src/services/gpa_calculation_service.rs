@@ -9,6 +9,7 @@ use crate::{
         student_term_result::{CategoryScore, StudentTermResult, SubjectResult},
     },
     errors::AppError,
+    services::score_moderation_service::ScoreModerationService,
     utils::object_id::ObjectId,
 };
 
@@ -38,9 +39,7 @@ impl GpaCalculationService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -48,8 +47,8 @@ impl GpaCalculationService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -244,9 +243,9 @@ impl GpaCalculationService {
             .collect::<Result<Vec<_>, _>>()?;
 
         if scores.is_empty() {
-            return Err(AppError {
-                message: "No scores found for this student and exam".to_string(),
-            });
+            return Err(AppError::bad_request(
+                "No scores found for this student and exam",
+            ));
         }
 
         let mut subject_scores: HashMap<ObjectId, Vec<Score>> = HashMap::new();
@@ -346,7 +345,7 @@ impl GpaCalculationService {
             category_scores,
             weighted_score: weighted_total,
             percentage: weighted_total,
-            grade: self.determine_grade(weighted_total),
+            grade: Self::determine_grade(weighted_total),
             credits: subject.credits,
         })
     }
@@ -359,9 +358,7 @@ impl GpaCalculationService {
         .fetch_optional(&self.pool)
         .await
         .map_err(Self::db_error)?
-        .ok_or_else(|| AppError {
-            message: "Subject not found".to_string(),
-        })?;
+        .ok_or_else(|| AppError::not_found("Subject not found"))?;
 
         Ok(SubjectMeta {
             name: row.try_get("name").map_err(Self::db_error)?,
@@ -401,7 +398,7 @@ impl GpaCalculationService {
         Ok(categories)
     }
 
-    fn determine_grade(&self, percentage: f64) -> String {
+    pub(crate) fn determine_grade(percentage: f64) -> String {
         if percentage >= 90.0 {
             "A".to_string()
         } else if percentage >= 80.0 {
@@ -417,7 +414,7 @@ impl GpaCalculationService {
         }
     }
 
-    async fn calculate_grade_from_active_scale(
+    pub(crate) async fn calculate_grade_from_active_scale(
         &self,
         school_id: &ObjectId,
         education_year_id: &ObjectId,
@@ -450,21 +447,21 @@ impl GpaCalculationService {
     }
 
     async fn save_result(&self, result: &StudentTermResult) -> Result<StudentTermResult, AppError> {
-        let school_id = result.school_id.ok_or_else(|| AppError {
-            message: "Result has no school_id".into(),
-        })?;
-        let student_id = result.student_id.ok_or_else(|| AppError {
-            message: "Result has no student_id".into(),
-        })?;
-        let class_id = result.class_id.ok_or_else(|| AppError {
-            message: "Result has no class_id".into(),
-        })?;
-        let education_year_id = result.education_year_id.ok_or_else(|| AppError {
-            message: "Result has no education_year_id".into(),
-        })?;
-        let exam_id = result.exam_id.ok_or_else(|| AppError {
-            message: "Result has no exam_id".into(),
-        })?;
+        let school_id = result
+            .school_id
+            .ok_or_else(|| AppError::bad_request("Result has no school_id"))?;
+        let student_id = result
+            .student_id
+            .ok_or_else(|| AppError::bad_request("Result has no student_id"))?;
+        let class_id = result
+            .class_id
+            .ok_or_else(|| AppError::bad_request("Result has no class_id"))?;
+        let education_year_id = result
+            .education_year_id
+            .ok_or_else(|| AppError::bad_request("Result has no education_year_id"))?;
+        let exam_id = result
+            .exam_id
+            .ok_or_else(|| AppError::bad_request("Result has no exam_id"))?;
 
         let existing_id: Option<String> = sqlx::query_scalar(
             "SELECT id FROM student_term_results WHERE student_id = $1 AND exam_id = $2 AND deleted_at IS NULL LIMIT 1",
@@ -609,9 +606,7 @@ impl GpaCalculationService {
             .fetch_optional(&self.pool)
             .await
             .map_err(Self::db_error)?
-            .ok_or_else(|| AppError {
-                message: "Result not found".into(),
-            })?;
+            .ok_or_else(|| AppError::not_found("Result not found"))?;
         let mut result = Self::result_from_row(row)?;
         self.hydrate_result_subjects(&mut result).await?;
         Ok(result)
@@ -659,10 +654,21 @@ impl GpaCalculationService {
         .await
         .map_err(Self::db_error)?;
 
+        // Published results are final: hand back what was published
+        let published = ScoreModerationService::new(&self.pool)
+            .results_published(exam_id)
+            .await?;
+
         let mut results = Vec::new();
         for row in rows {
             let student_id: String = row.try_get("student_id").map_err(Self::db_error)?;
             let student_id = Self::parse_oid(&student_id, "student_id")?;
+            if published {
+                if let Some(result) = self.get_student_result(&student_id, exam_id).await? {
+                    results.push(result);
+                }
+                continue;
+            }
             match self
                 .calculate_student_result(
                     &student_id,
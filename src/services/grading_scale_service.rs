@@ -27,9 +27,9 @@ impl GradingScaleQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -69,9 +69,7 @@ impl GradingScaleService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -83,8 +81,8 @@ impl GradingScaleService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -158,15 +156,16 @@ impl GradingScaleService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported grading scale filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported grading scale filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -282,9 +281,7 @@ impl GradingScaleService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let id = scale.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
 
         sqlx::query(
@@ -332,9 +329,7 @@ impl GradingScaleService {
             .map_err(Self::db_error)?;
         match row {
             Some(row) => self.scale_from_row(row).await,
-            None => Err(AppError {
-                message: "Grading scale not found".into(),
-            }),
+            None => Err(AppError::not_found("Grading scale not found")),
         }
     }
 
@@ -457,9 +452,7 @@ impl GradingScaleService {
             self.deactivate_other_scales(&id_string).await?;
         }
         if !touched && update.grade_boundaries.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         self.find_one(id, None).await
@@ -467,12 +460,12 @@ impl GradingScaleService {
 
     async fn deactivate_other_scales(&self, id: &str) -> Result<(), AppError> {
         let scale = self.find_one(&IdType::from_string(id), None).await?;
-        let school_id = scale.school_id.ok_or(AppError {
-            message: "Grading scale has no school_id".into(),
-        })?;
-        let education_year_id = scale.education_year_id.ok_or(AppError {
-            message: "Grading scale has no education_year_id".into(),
-        })?;
+        let school_id = scale
+            .school_id
+            .ok_or(AppError::bad_request("Grading scale has no school_id"))?;
+        let education_year_id = scale.education_year_id.ok_or(AppError::bad_request(
+            "Grading scale has no education_year_id",
+        ))?;
 
         sqlx::query(
             r#"
@@ -43,7 +43,7 @@ impl JoinSchoolRequestService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -55,8 +55,8 @@ impl JoinSchoolRequestService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -145,17 +145,19 @@ impl JoinSchoolRequestService {
         state: &AppState,
     ) -> Result<JoinSchoolRequest, AppError> {
         if let Err(e) = is_valid_email(&request.email) {
-            return Err(AppError { message: e });
+            return Err(AppError::bad_request(e));
         }
 
         if let JoinRole::Student = request.role {
             if request.class_id.is_none() {
-                return Err(AppError { message: "Class is required for student join requests".into() });
+                return Err(AppError::bad_request(
+                    "Class is required for student join requests",
+                ));
             }
         }
 
-        let school_id = ObjectId::from_str(&request.school_id).map_err(|e| AppError {
-            message: format!("Failed to change school id into objectId:{}", e),
+        let school_id = ObjectId::from_str(&request.school_id).map_err(|e| {
+            AppError::bad_request(format!("Failed to change school id into objectId:{}", e))
         })?;
 
         let pending_exists = sqlx::query_scalar::<_, i64>(
@@ -168,25 +170,33 @@ impl JoinSchoolRequestService {
         .map_err(Self::db_error)?;
 
         if pending_exists > 0 {
-            return Err(AppError { message: "A join request with this email already exists".into() });
+            return Err(AppError::conflict(
+                "A join request with this email already exists",
+            ));
         }
 
         let user_repo = UserRepo::new(&state.pg.pool);
         let user_service = UserService::new(&user_repo);
 
         let mut invited_user_id = None;
-        if let Ok(user) = user_service.get_user_by_email(&request.email).await.map_err(|e| AppError { message: e }) {
+        if let Ok(user) = user_service
+            .get_user_by_email(&request.email)
+            .await
+            .map_err(AppError::bad_request)
+        {
             if let Some(schools) = &user.schools {
                 if schools.contains(&school_id) {
-                    return Err(AppError { message: "User is already a member of this school".into() });
+                    return Err(AppError::conflict(
+                        "User is already a member of this school",
+                    ));
                 }
             }
             invited_user_id = user.id;
         }
 
         let class_id = match request.class_id {
-            Some(cid_str) => Some(ObjectId::from_str(&cid_str).map_err(|e| AppError {
-                message: format!("Failed to change class id into objectId:{}", e),
+            Some(cid_str) => Some(ObjectId::from_str(&cid_str).map_err(|e| {
+                AppError::bad_request(format!("Failed to change class id into objectId:{}", e))
             })?),
             None => None,
         };
@@ -238,7 +248,7 @@ impl JoinSchoolRequestService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_request(r),
-            None => Err(AppError { message: "Join school request not found".into() }),
+            None => Err(AppError::not_found("Join school request not found")),
         }
     }
 
@@ -279,12 +289,15 @@ impl JoinSchoolRequestService {
     ) -> Result<JoinSchoolRequestResponseToken, AppError> {
         let request = self.find_one(Some(id), None).await?;
         if !matches!(request.status, JoinStatus::Pending) {
-            return Err(AppError { message: "Join request is not pending".into() });
+            return Err(AppError::conflict("Join request is not pending"));
         }
 
         let user_repo = UserRepo::new(&state.pg.pool);
         let user_service = UserService::new(&user_repo);
-        let user = user_service.get_user_by_email(&request.email).await.map_err(|e| AppError { message: e })?;
+        let user = user_service
+            .get_user_by_email(&request.email)
+            .await
+            .map_err(AppError::bad_request)?;
 
         let _ = self.create_role_entity_school_db(&request, &user, &state).await;
 
@@ -424,7 +437,7 @@ impl JoinSchoolRequestService {
 
                 if let Some(class_id) = request.class_id {
                     if class_service.find_one(Some(&IdType::ObjectId(class_id)), Some(crate::services::class_service::ClassQuery { school_id: Some(school_id.to_hex()), ..Default::default() })).await.is_err() {
-                        return Err(AppError { message: "Class not found in school".into() });
+                        return Err(AppError::not_found("Class not found in school"));
                     }
                 }
 
@@ -514,12 +527,42 @@ impl JoinSchoolRequestService {
 
                 match staff_type {
                     SchoolStaffType::Director => {
-                        let count = staff_service.count_staff(None, Some(SchoolStaffQuery { school_id: Some(school_id.to_hex()), field_values: vec![("type".to_string(), "Director".to_string())], ..Default::default() })).await?;
-                        if count.count >= 1 { return Err(AppError { message: "This school already has a Director".into() }); }
+                        let count = staff_service
+                            .count_staff(
+                                None,
+                                Some(SchoolStaffQuery {
+                                    school_id: Some(school_id.to_hex()),
+                                    field_values: vec![(
+                                        "type".to_string(),
+                                        "Director".to_string(),
+                                    )],
+                                    ..Default::default()
+                                }),
+                            )
+                            .await?;
+                        if count.count >= 1 {
+                            return Err(AppError::conflict("This school already has a Director"));
+                        }
                     }
                     SchoolStaffType::HeadOfStudies => {
-                        let count = staff_service.count_staff(None, Some(SchoolStaffQuery { school_id: Some(school_id.to_hex()), field_values: vec![("type".to_string(), "HeadOfStudies".to_string())], ..Default::default() })).await?;
-                        if count.count >= 5 { return Err(AppError { message: "This school already has 5 HeadOfStudies".into() }); }
+                        let count = staff_service
+                            .count_staff(
+                                None,
+                                Some(SchoolStaffQuery {
+                                    school_id: Some(school_id.to_hex()),
+                                    field_values: vec![(
+                                        "type".to_string(),
+                                        "HeadOfStudies".to_string(),
+                                    )],
+                                    ..Default::default()
+                                }),
+                            )
+                            .await?;
+                        if count.count >= 5 {
+                            return Err(AppError::conflict(
+                                "This school already has 5 HeadOfStudies",
+                            ));
+                        }
                     }
                 }
 
@@ -542,7 +585,7 @@ impl JoinSchoolRequestService {
             }
 
             JoinRole::Parent => {
-                return Err(AppError { message: "Parent role is not yet supported".into() });
+                return Err(AppError::bad_request("Parent role is not yet supported"));
             }
         }
 
@@ -557,18 +600,32 @@ impl JoinSchoolRequestService {
     ) -> Result<JoinSchoolRequestResponseToken, AppError> {
         let user_repo = UserRepo::new(&state.pg.pool);
         let user_service = UserService::new(&user_repo);
-        let user = user_service.get_user_by_email(&auth_user.email).await.map_err(|e| AppError { message: format!("Failed to find user: {}", e) })?;
+        let user = user_service
+            .get_user_by_email(&auth_user.email)
+            .await
+            .map_err(|e| AppError::bad_request(format!("Failed to find user: {}", e)))?;
 
         let school_service = SchoolService::new(&state.pg.pool);
         let school = school_service.find_by_code(&request.code).await?;
 
-        let school_id = school.id.clone().ok_or_else(|| AppError { message: "School ID not found".into() })?;
-        let user_id = user.id.clone().ok_or_else(|| AppError { message: "User ID not found".into() })?;
+        let school_id = school
+            .id
+            .clone()
+            .ok_or_else(|| AppError::not_found("School ID not found"))?;
+        let user_id = user
+            .id
+            .clone()
+            .ok_or_else(|| AppError::not_found("User ID not found"))?;
 
         let join_request = JoinSchoolRequest::new(&user, &school_id, &user_id);
         self.create_role_entity_school_db(&join_request, &user, &state).await?;
 
-        user_service.add_school_to_user(&IdType::ObjectId(user_id), &IdType::from_object_id(school_id.clone())).await.map_err(|e| AppError { message: e })?;
+        user_service
+            .add_school_to_user(
+                &IdType::ObjectId(user_id),
+                &IdType::from_object_id(school_id.clone()),
+            )
+            .await?;
 
         let school_service = SchoolService::new(&state.pg.pool);
         let school_token = school_service.create_school_token(&IdType::ObjectId(school_id), auth_user, &state).await?;
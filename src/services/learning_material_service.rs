@@ -30,9 +30,7 @@ impl LearningMaterialService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -44,8 +42,8 @@ impl LearningMaterialService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -223,20 +221,18 @@ impl LearningMaterialService {
         self.ensure_indexes().await?;
 
         if dto.title.trim().is_empty() {
-            return Err(AppError {
-                message: "Title is required".into(),
-            });
+            return Err(AppError::bad_request("Title is required"));
         }
 
         let mut material = dto;
 
         if let (Some(bytes), Some(name)) = (file_bytes, file_name) {
-            let school_id = material.school_id.ok_or(AppError {
-                message: "School ID is required".into(),
-            })?;
-            let subject_id = material.subject_id.ok_or(AppError {
-                message: "Subject ID is required".into(),
-            })?;
+            let school_id = material
+                .school_id
+                .ok_or(AppError::bad_request("School ID is required"))?;
+            let subject_id = material
+                .subject_id
+                .ok_or(AppError::bad_request("Subject ID is required"))?;
             let folder = format!(
                 "space-together/{}/subjects/{}",
                 school_id.to_hex(),
@@ -245,24 +241,24 @@ impl LearningMaterialService {
 
             let upload_result = CloudinaryService::upload_file(bytes, &name, &folder)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             material.file_url = Some(upload_result.url);
             material.file_public_id = Some(upload_result.public_id);
         }
 
         if matches!(material.material_type, MaterialType::Video) && material.video_url.is_none() {
-            return Err(AppError {
-                message: "Video URL is required for VIDEO type".into(),
-            });
+            return Err(AppError::bad_request(
+                "Video URL is required for VIDEO type",
+            ));
         }
 
         let id = material
             .id
             .map(|id| id.to_hex())
             .unwrap_or_else(Self::new_id);
-        let school_id = material.school_id.ok_or(AppError {
-            message: "School ID is required".into(),
-        })?;
+        let school_id = material
+            .school_id
+            .ok_or(AppError::bad_request("School ID is required"))?;
         let material_type = Self::material_type_to_string(&material.material_type);
 
         sqlx::query(
@@ -318,9 +314,7 @@ impl LearningMaterialService {
             .map_err(Self::db_error)?
             .map(|row| Self::material_from_row(&row))
             .transpose()?
-            .ok_or(AppError {
-                message: "Learning material not found".into(),
-            })
+            .ok_or(AppError::not_found("Learning material not found"))
     }
 
     pub async fn get_all(
@@ -398,12 +392,12 @@ impl LearningMaterialService {
                 CloudinaryService::delete_file(&old_public_id).await.ok();
             }
 
-            let school_id = existing.school_id.ok_or(AppError {
-                message: "School ID is required".into(),
-            })?;
-            let subject_id = existing.subject_id.ok_or(AppError {
-                message: "Subject ID is required".into(),
-            })?;
+            let school_id = existing
+                .school_id
+                .ok_or(AppError::bad_request("School ID is required"))?;
+            let subject_id = existing
+                .subject_id
+                .ok_or(AppError::bad_request("Subject ID is required"))?;
             let folder = format!(
                 "space-together/{}/subjects/{}",
                 school_id.to_hex(),
@@ -412,7 +406,7 @@ impl LearningMaterialService {
 
             let upload_result = CloudinaryService::upload_file(bytes, &name, &folder)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             update_data.file_url = Some(Some(upload_result.url));
             update_data.file_public_id = Some(Some(upload_result.public_id));
         }
@@ -26,9 +26,7 @@ impl LikeService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -40,8 +38,8 @@ impl LikeService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -183,9 +181,7 @@ impl LikeService {
             .map_err(Self::db_error)?
             .map(|row| Self::like_from_row(&row))
             .transpose()?
-            .ok_or(AppError {
-                message: "Like not found".into(),
-            })
+            .ok_or(AppError::not_found("Like not found"))
     }
 
     pub async fn create(&self, dto: Like, school_id: Option<String>) -> Result<Like, AppError> {
@@ -247,9 +243,7 @@ impl LikeService {
             .map_err(Self::db_error)?
             .map(|row| Self::like_from_row(&row))
             .transpose()?
-            .ok_or(AppError {
-                message: "Like not found".into(),
-            })
+            .ok_or(AppError::not_found("Like not found"))
     }
 
     pub async fn get_all(
@@ -61,9 +61,7 @@ impl LocationService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", err),
-        }
+        AppError::from(err)
     }
 
     async fn ensure_seeded(&self) -> Result<(), AppError> {
@@ -77,8 +75,8 @@ impl LocationService {
         }
 
         let raw: RawLocations = serde_json::from_str(include_str!("../../data/locations.json"))
-            .map_err(|err| AppError {
-                message: format!("Failed to parse locations data: {}", err),
+            .map_err(|err| {
+                AppError::internal(format!("Failed to parse locations data: {}", err))
             })?;
 
         let mut rows = Vec::new();
@@ -0,0 +1,164 @@
+//! Outgoing mail behind a [`MailTransport`], selected by `MAIL_TRANSPORT`:
+//!
+//! - `smtp` sends through `SMTP_HOST` (with `SMTP_PORT`, `SMTP_USERNAME`,
+//!   `SMTP_PASSWORD`) from `MAIL_FROM`, for production.
+//! - `file` writes each message as JSON under `MAIL_DIR` (default `mail`), so
+//!   tests and local setups can read the links that were sent.
+//! - `console` only logs the message, for development.
+//!
+//! There is no default: a server started without `MAIL_TRANSPORT` would
+//! quietly drop password reset and verification mail into its log.
+
+use std::{path::PathBuf, sync::Arc};
+
+use chrono::Utc;
+use futures::future::BoxFuture;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub trait MailTransport: Send + Sync {
+    fn send<'a>(&'a self, message: &'a MailMessage) -> BoxFuture<'a, Result<(), AppError>>;
+}
+
+/// Development transport: the message only reaches the log.
+pub struct ConsoleTransport;
+
+impl MailTransport for ConsoleTransport {
+    fn send<'a>(&'a self, message: &'a MailMessage) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            log::info!(
+                "Mail to {} — {}\n{}",
+                message.to,
+                message.subject,
+                message.body
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Writes every message to its own file instead of sending it.
+pub struct FileTransport {
+    dir: PathBuf,
+}
+
+impl FileTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl MailTransport for FileTransport {
+    fn send<'a>(&'a self, message: &'a MailMessage) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(&self.dir)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to create mail dir: {}", e)))?;
+            let path = self.dir.join(format!(
+                "{}-{}.json",
+                Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+                Uuid::new_v4()
+            ));
+            let contents = serde_json::to_vec_pretty(message)
+                .map_err(|e| AppError::internal(format!("Failed to encode mail: {}", e)))?;
+            tokio::fs::write(&path, contents)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to write mail: {}", e)))
+        })
+    }
+}
+
+pub struct SmtpTransport {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpTransport {
+    fn env(key: &str) -> Option<String> {
+        std::env::var(key)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    }
+
+    /// Build from the `SMTP_*` and `MAIL_FROM` variables. Missing settings
+    /// stop the server at startup rather than losing mail later.
+    pub fn from_env() -> Self {
+        let host = Self::env("SMTP_HOST").expect("❌ SMTP_HOST not set for MAIL_TRANSPORT=smtp");
+        let from = Self::env("MAIL_FROM")
+            .expect("❌ MAIL_FROM not set for MAIL_TRANSPORT=smtp")
+            .parse::<Mailbox>()
+            .expect("❌ MAIL_FROM is not a valid mailbox");
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::relay(&host)
+            .expect("❌ SMTP_HOST is not a valid relay");
+        if let Some(port) = Self::env("SMTP_PORT").and_then(|port| port.parse().ok()) {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) =
+            (Self::env("SMTP_USERNAME"), Self::env("SMTP_PASSWORD"))
+        {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Self {
+            transport: builder.build(),
+            from,
+        }
+    }
+}
+
+impl MailTransport for SmtpTransport {
+    fn send<'a>(&'a self, message: &'a MailMessage) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            let to = message
+                .to
+                .parse::<Mailbox>()
+                .map_err(|e| AppError::bad_request(format!("Invalid recipient: {}", e)))?;
+            let email = Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(&message.subject)
+                .body(message.body.clone())
+                .map_err(|e| AppError::internal(format!("Failed to build mail: {}", e)))?;
+            self.transport
+                .send(email)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to send mail: {}", e)))?;
+            Ok(())
+        })
+    }
+}
+
+pub fn transport_from_env() -> Arc<dyn MailTransport> {
+    let transport = std::env::var("MAIL_TRANSPORT")
+        .expect("❌ MAIL_TRANSPORT not set, choose smtp, file or console")
+        .trim()
+        .to_ascii_lowercase();
+    match transport.as_str() {
+        "smtp" => Arc::new(SmtpTransport::from_env()),
+        "file" => Arc::new(FileTransport::new(
+            std::env::var("MAIL_DIR").unwrap_or_else(|_| "mail".to_string()),
+        )),
+        "console" => {
+            log::warn!("MAIL_TRANSPORT=console: outgoing mail is only logged, never delivered");
+            Arc::new(ConsoleTransport)
+        }
+        other => panic!(
+            "❌ MAIL_TRANSPORT={} is not one of smtp, file or console",
+            other
+        ),
+    }
+}
@@ -22,7 +22,7 @@ impl MainClassService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -34,8 +34,8 @@ impl MainClassService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -170,7 +170,9 @@ impl MainClassService {
         }
 
         if q.field.len() != q.value.len() {
-            return Err(AppError { message: "Number of fields must match number of values".into() });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for (field, value) in q.field.iter().zip(q.value.iter()) {
             match field.as_str() {
@@ -179,14 +181,23 @@ impl MainClassService {
                 "name" => { sql.push(format!(" AND lower({}name) = lower(", prefix)).push_bind(value).push(")"); }
                 "username" => { sql.push(format!(" AND lower({}username) = lower(", prefix)).push_bind(value).push(")"); }
                 "level" => {
-                    let n = value.parse::<i32>().map_err(|_| AppError { message: "Invalid level".into() })?;
+                    let n = value
+                        .parse::<i32>()
+                        .map_err(|_| AppError::bad_request("Invalid level"))?;
                     sql.push(format!(" AND {}level = ", prefix)).push_bind(n);
                 }
                 "disable" => {
-                    let b = value.parse::<bool>().map_err(|_| AppError { message: "Invalid disable".into() })?;
+                    let b = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid disable"))?;
                     sql.push(format!(" AND {}disable = ", prefix)).push_bind(b);
                 }
-                _ => return Err(AppError { message: format!("Unsupported filter field: {}", field) }),
+                _ => {
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported filter field: {}",
+                        field
+                    )))
+                }
             }
         }
         Ok(())
@@ -194,10 +205,16 @@ impl MainClassService {
 
     pub async fn create(&self, dto: MainClass) -> Result<MainClass, AppError> {
         if self.find_one_by_name(&dto.name).await?.is_some() {
-            return Err(AppError { message: format!("MainClass name already exists: {}", dto.name) });
+            return Err(AppError::conflict(format!(
+                "MainClass name already exists: {}",
+                dto.name
+            )));
         }
         if self.find_one_by_username(&dto.username).await?.is_some() {
-            return Err(AppError { message: format!("MainClass username already exists: {}", dto.username) });
+            return Err(AppError::conflict(format!(
+                "MainClass username already exists: {}",
+                dto.username
+            )));
         }
 
         let id = Self::new_id();
@@ -263,7 +280,7 @@ impl MainClassService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_main_class(r),
-            None => Err(AppError { message: "MainClass not found".into() }),
+            None => Err(AppError::not_found("MainClass not found")),
         }
     }
 
@@ -346,7 +363,7 @@ impl MainClassService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_main_class_with_others(r),
-            None => Err(AppError { message: "MainClass not found".into() }),
+            None => Err(AppError::not_found("MainClass not found")),
         }
     }
 
@@ -359,12 +376,20 @@ impl MainClassService {
 
         if let Some(ref name) = update.name {
             if existing.name != *name && self.find_one_by_name(name).await?.is_some() {
-                return Err(AppError { message: format!("MainClass name already exists: {}", name) });
+                return Err(AppError::conflict(format!(
+                    "MainClass name already exists: {}",
+                    name
+                )));
             }
         }
         if let Some(ref username) = update.username {
-            if existing.username != *username && self.find_one_by_username(username).await?.is_some() {
-                return Err(AppError { message: format!("MainClass username already exists: {}", username) });
+            if existing.username != *username
+                && self.find_one_by_username(username).await?.is_some()
+            {
+                return Err(AppError::conflict(format!(
+                    "MainClass username already exists: {}",
+                    username
+                )));
             }
         }
 
@@ -383,7 +408,7 @@ impl MainClassService {
         }
 
         if !touched {
-            return Err(AppError { message: "No valid fields to update".into() });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         sql.push(" WHERE id = ").push_bind(&id_str);
@@ -0,0 +1,859 @@
+use std::collections::HashMap;
+
+use sqlx::{PgPool, Postgres, Row, Transaction};
+
+use crate::{
+    domain::{
+        auth_user::AuthUserDto,
+        marksheet::{
+            MarkRow, MarkStatus, MarksheetPreview, MarksheetTarget, MarksheetUpload,
+            MissingStudent, MARKSHEET_COLUMNS,
+        },
+        role::PermissionTarget,
+    },
+    errors::AppError,
+    services::{
+        permission_service::PermissionService, score_moderation_service::ScoreModerationService,
+    },
+    utils::{
+        object_id::ObjectId,
+        spreadsheet::{read_table, Table},
+    },
+};
+
+/// Ids and labels the sheet is checked against, all within one school
+struct SheetContext {
+    class_subject_id: String,
+    exam_id: String,
+    assessment_category_id: String,
+    class_id: String,
+    education_year_id: Option<String>,
+    label: String,
+}
+
+struct RosterEntry {
+    profile_id: String,
+    enrollment_id: String,
+    name: String,
+    registration_number: Option<String>,
+}
+
+struct ExistingMark {
+    score_id: String,
+    score: f64,
+    max_score: f64,
+    remarks: Option<String>,
+}
+
+/// Numbers as a spreadsheet would show them: no trailing ".0"
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+fn percentage(score: f64, max_score: f64) -> f64 {
+    if max_score > 0.0 {
+        (score / max_score) * 100.0
+    } else {
+        0.0
+    }
+}
+
+pub struct MarksheetService {
+    pub pool: PgPool,
+}
+
+impl MarksheetService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    async fn context(
+        &self,
+        school_id: &ObjectId,
+        target: &MarksheetTarget,
+    ) -> Result<SheetContext, AppError> {
+        for (raw, field) in [
+            (&target.class_subject_id, "class_subject_id"),
+            (&target.exam_id, "exam_id"),
+            (&target.assessment_category_id, "assessment_category_id"),
+        ] {
+            Self::parse_oid(raw, field)?;
+        }
+
+        let row = sqlx::query(
+            r#"
+            SELECT cs.class_id, cs.name AS subject_name, c.name AS class_name,
+                   e.name AS exam_name, e.education_year_id,
+                   ac.name AS category_name, ac.class_subject_id AS category_subject_id
+            FROM class_subjects cs
+            JOIN classes c ON c.id = cs.class_id
+            JOIN exams e ON e.id = $2 AND e.school_id = cs.school_id AND e.deleted_at IS NULL
+            JOIN assessment_categories ac
+              ON ac.id = $3 AND ac.school_id = cs.school_id AND ac.deleted_at IS NULL
+            WHERE cs.id = $1 AND cs.school_id = $4 AND cs.deleted_at IS NULL
+            "#,
+        )
+        .bind(&target.class_subject_id)
+        .bind(&target.exam_id)
+        .bind(&target.assessment_category_id)
+        .bind(school_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| {
+            AppError::not_found(
+                "Class subject, exam or assessment category not found in this school",
+            )
+        })?;
+
+        let category_subject_id: Option<String> =
+            row.try_get("category_subject_id").map_err(Self::db_error)?;
+        if category_subject_id.is_some_and(|id| id != target.class_subject_id) {
+            return Err(AppError::bad_request(
+                "Assessment category belongs to another subject",
+            ));
+        }
+
+        let class_name: String = row.try_get("class_name").map_err(Self::db_error)?;
+        let subject_name: String = row.try_get("subject_name").map_err(Self::db_error)?;
+        let exam_name: String = row.try_get("exam_name").map_err(Self::db_error)?;
+        let category_name: String = row.try_get("category_name").map_err(Self::db_error)?;
+
+        Ok(SheetContext {
+            class_subject_id: target.class_subject_id.clone(),
+            exam_id: target.exam_id.clone(),
+            assessment_category_id: target.assessment_category_id.clone(),
+            class_id: row.try_get("class_id").map_err(Self::db_error)?,
+            education_year_id: row.try_get("education_year_id").map_err(Self::db_error)?,
+            label: format!(
+                "{} {} {} {}",
+                class_name, subject_name, exam_name, category_name
+            ),
+        })
+    }
+
+    /// Members holding `result.manage`, or a teacher assigned to the subject
+    pub async fn ensure_can_enter(
+        &self,
+        user: &AuthUserDto,
+        school_id: &ObjectId,
+        class_subject_id: &str,
+    ) -> Result<(), AppError> {
+        let target = PermissionTarget::school(school_id.to_hex());
+        if PermissionService::new(&self.pool)
+            .check(user, "result.manage", Some(&target))
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+
+        let allowed: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (
+                 SELECT 1 FROM class_subjects cs
+                 LEFT JOIN teachers t ON t.id = cs.teacher_id
+                 WHERE cs.id = $1 AND cs.school_id = $2
+                   AND (t.user_id = $3 OR cs.teacher_user_id = $3)
+               ) OR EXISTS (
+                 SELECT 1 FROM teacher_subjects ts
+                 JOIN teachers t ON t.id = ts.teacher_id
+                 WHERE ts.class_subject_id = $1 AND ts.school_id = $2 AND t.user_id = $3
+               )"#,
+        )
+        .bind(class_subject_id)
+        .bind(school_id.to_hex())
+        .bind(&user.id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(AppError::forbidden(
+                "Only teachers of this subject can enter its marks",
+            ))
+        }
+    }
+
+    /// Active members of the class, subclass members included, by name
+    async fn roster(&self, class_id: &str) -> Result<Vec<RosterEntry>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sp.id AS profile_id, sse.id AS enrollment_id, sp.name, sse.registration_number
+            FROM student_school_enrollments sse
+            JOIN student_profiles sp ON sp.id = sse.student_id
+            WHERE (sse.class_id = $1 OR sse.subclass_id = $1)
+              AND sse.is_active = true AND sse.deleted_at IS NULL AND sp.deleted_at IS NULL
+            ORDER BY lower(sp.name), sse.registration_number
+            "#,
+        )
+        .bind(class_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(RosterEntry {
+                    profile_id: row.try_get("profile_id").map_err(Self::db_error)?,
+                    enrollment_id: row.try_get("enrollment_id").map_err(Self::db_error)?,
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    registration_number: row
+                        .try_get("registration_number")
+                        .map_err(Self::db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn existing_marks(
+        &self,
+        context: &SheetContext,
+    ) -> Result<HashMap<String, ExistingMark>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, student_id, score::DOUBLE PRECISION AS score,
+                   max_score::DOUBLE PRECISION AS max_score, remarks
+            FROM scores
+            WHERE class_subject_id = $1 AND exam_id = $2 AND assessment_category_id = $3
+              AND deleted_at IS NULL
+            "#,
+        )
+        .bind(&context.class_subject_id)
+        .bind(&context.exam_id)
+        .bind(&context.assessment_category_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut marks = HashMap::new();
+        for row in rows {
+            marks.insert(
+                row.try_get("student_id").map_err(Self::db_error)?,
+                ExistingMark {
+                    score_id: row.try_get("id").map_err(Self::db_error)?,
+                    score: row
+                        .try_get::<Option<f64>, _>("score")
+                        .map_err(Self::db_error)?
+                        .unwrap_or_default(),
+                    max_score: row
+                        .try_get::<Option<f64>, _>("max_score")
+                        .map_err(Self::db_error)?
+                        .unwrap_or_default(),
+                    remarks: row.try_get("remarks").map_err(Self::db_error)?,
+                },
+            );
+        }
+        Ok(marks)
+    }
+
+    /// The class roster with any marks already entered, as a file name
+    /// stem and the sheet to fill in
+    pub async fn template(
+        &self,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        target: &MarksheetTarget,
+        default_max_score: Option<f64>,
+    ) -> Result<(String, Table), AppError> {
+        let context = self.context(school_id, target).await?;
+        self.ensure_can_enter(user, school_id, &context.class_subject_id)
+            .await?;
+        let roster = self.roster(&context.class_id).await?;
+        let marks = self.existing_marks(&context).await?;
+
+        let rows = roster
+            .into_iter()
+            .map(|student| {
+                let mark = marks.get(&student.profile_id);
+                vec![
+                    student.profile_id,
+                    student.registration_number.unwrap_or_default(),
+                    student.name,
+                    mark.map(|mark| format_number(mark.score))
+                        .unwrap_or_default(),
+                    mark.map(|mark| mark.max_score)
+                        .or(default_max_score)
+                        .map(format_number)
+                        .unwrap_or_default(),
+                    mark.and_then(|mark| mark.remarks.clone())
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        Ok((
+            context.label,
+            Table {
+                headers: MARKSHEET_COLUMNS.iter().map(|c| c.to_string()).collect(),
+                rows,
+            },
+        ))
+    }
+
+    /// Match each sheet row to the class roster and check its score against
+    /// the marks already entered. Also returns the students without a row.
+    fn check_rows(
+        table: Table,
+        roster: &[RosterEntry],
+        marks: &HashMap<String, ExistingMark>,
+        default_max_score: Option<f64>,
+    ) -> Result<(Vec<MarkRow>, Vec<MissingStudent>), AppError> {
+        let Table { headers, rows } = table;
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name))
+        };
+        let (student_col, reg_col, score_col) = (
+            column("student_id"),
+            column("registration_number"),
+            column("score")
+                .ok_or_else(|| AppError::bad_request("The sheet has no score column"))?,
+        );
+        if student_col.is_none() && reg_col.is_none() {
+            return Err(AppError::bad_request(
+                "The sheet needs a student_id or registration_number column",
+            ));
+        }
+        let (name_col, max_col, remarks_col) = (
+            column("student_name"),
+            column("max_score"),
+            column("remarks"),
+        );
+
+        let by_id: HashMap<&str, &RosterEntry> = roster
+            .iter()
+            .flat_map(|student| {
+                [
+                    (student.profile_id.as_str(), student),
+                    (student.enrollment_id.as_str(), student),
+                ]
+            })
+            .collect();
+        let by_registration: HashMap<String, &RosterEntry> = roster
+            .iter()
+            .filter_map(|student| {
+                student
+                    .registration_number
+                    .as_ref()
+                    .map(|reg| (reg.trim().to_lowercase(), student))
+            })
+            .collect();
+
+        let cell = |cells: &[String], index: Option<usize>| {
+            index
+                .and_then(|index| cells.get(index))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let mut results = Vec::with_capacity(rows.len());
+        let mut seen: HashMap<String, u32> = HashMap::new();
+        for (index, cells) in rows.iter().enumerate() {
+            let row_number = index as u32 + 2;
+            let raw_id = cell(cells, student_col);
+            let registration_number = cell(cells, reg_col);
+            let mut row = MarkRow {
+                row_number,
+                status: MarkStatus::Invalid,
+                student_id: None,
+                student_name: cell(cells, name_col),
+                registration_number: registration_number.clone(),
+                score: None,
+                max_score: None,
+                old_score: None,
+                remarks: cell(cells, remarks_col),
+                errors: Vec::new(),
+            };
+
+            let student = raw_id
+                .as_deref()
+                .and_then(|id| by_id.get(id).copied())
+                .or_else(|| {
+                    registration_number
+                        .as_ref()
+                        .and_then(|reg| by_registration.get(&reg.to_lowercase()).copied())
+                });
+            let Some(student) = student else {
+                row.errors.push(format!(
+                    "Unknown student {}",
+                    raw_id.or(registration_number).unwrap_or_default()
+                ));
+                results.push(row);
+                continue;
+            };
+            row.student_id = Some(Self::parse_oid(&student.profile_id, "student_id")?);
+            row.student_name = Some(student.name.clone());
+            if let Some(first) = seen.insert(student.profile_id.clone(), row_number) {
+                row.errors
+                    .push(format!("Student already appears on row {}", first));
+                results.push(row);
+                continue;
+            }
+
+            let existing = marks.get(&student.profile_id);
+            row.old_score = existing.map(|mark| mark.score);
+
+            let Some(raw_score) = cell(cells, Some(score_col)) else {
+                row.status = MarkStatus::Blank;
+                results.push(row);
+                continue;
+            };
+            let score = match raw_score.parse::<f64>() {
+                Ok(score) if score.is_finite() && score >= 0.0 => score,
+                _ => {
+                    row.errors.push(format!("Invalid score: {}", raw_score));
+                    results.push(row);
+                    continue;
+                }
+            };
+            row.score = Some(score);
+
+            let max_score = match cell(cells, max_col) {
+                Some(raw) => match raw.parse::<f64>() {
+                    Ok(max) if max.is_finite() && max > 0.0 => Some(max),
+                    _ => {
+                        row.errors.push(format!("Invalid max_score: {}", raw));
+                        None
+                    }
+                },
+                None => default_max_score
+                    .or(existing.map(|mark| mark.max_score))
+                    .or_else(|| {
+                        row.errors.push("max_score is required".into());
+                        None
+                    }),
+            };
+            row.max_score = max_score;
+            if let Some(max_score) = max_score {
+                if score > max_score {
+                    row.errors.push(format!(
+                        "Score {} is above max_score {}",
+                        format_number(score),
+                        format_number(max_score)
+                    ));
+                }
+            }
+
+            row.status = if !row.errors.is_empty() {
+                MarkStatus::Invalid
+            } else {
+                match existing {
+                    None => MarkStatus::New,
+                    Some(mark)
+                        if mark.score == score
+                            && Some(mark.max_score) == max_score
+                            && (row.remarks.is_none() || mark.remarks == row.remarks) =>
+                    {
+                        MarkStatus::Unchanged
+                    }
+                    Some(_) => MarkStatus::Changed,
+                }
+            };
+            results.push(row);
+        }
+
+        let missing_students = roster
+            .iter()
+            .filter(|student| !seen.contains_key(&student.profile_id))
+            .map(|student| {
+                Ok(MissingStudent {
+                    student_id: Self::parse_oid(&student.profile_id, "student_id")?,
+                    name: student.name.clone(),
+                    registration_number: student.registration_number.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok((results, missing_students))
+    }
+
+    /// Checks an uploaded sheet against the roster and existing marks and,
+    /// unless it is a dry run or any row is invalid, writes the new and
+    /// changed marks in one transaction
+    pub async fn upload(
+        &self,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        options: MarksheetUpload,
+        bytes: &[u8],
+    ) -> Result<MarksheetPreview, AppError> {
+        options.validate().map_err(AppError::validation)?;
+        let context = self.context(school_id, &options.target).await?;
+        self.ensure_can_enter(user, school_id, &context.class_subject_id)
+            .await?;
+        // Held until the marks are written, so the subject can't be submitted
+        // and no one else can change its marks in between
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        ScoreModerationService::ensure_editable(
+            &mut tx,
+            &context.exam_id,
+            &context.class_subject_id,
+        )
+        .await?;
+
+        let table = read_table(bytes).map_err(AppError::bad_request)?;
+        let roster = self.roster(&context.class_id).await?;
+        let marks = self.existing_marks(&context).await?;
+        let (results, missing_students) =
+            Self::check_rows(table, &roster, &marks, options.max_score)?;
+
+        let count =
+            |status: MarkStatus| results.iter().filter(|row| row.status == status).count() as u32;
+        let mut preview = MarksheetPreview {
+            class_subject_id: Self::parse_oid(&context.class_subject_id, "class_subject_id")?,
+            exam_id: Self::parse_oid(&context.exam_id, "exam_id")?,
+            assessment_category_id: Self::parse_oid(
+                &context.assessment_category_id,
+                "assessment_category_id",
+            )?,
+            dry_run: options.dry_run,
+            committed: false,
+            total_rows: results.len() as u32,
+            new_count: count(MarkStatus::New),
+            changed_count: count(MarkStatus::Changed),
+            unchanged_count: count(MarkStatus::Unchanged),
+            blank_count: count(MarkStatus::Blank),
+            error_count: count(MarkStatus::Invalid),
+            rows: results,
+            missing_students,
+        };
+
+        if options.dry_run || preview.error_count > 0 {
+            return Ok(preview);
+        }
+
+        Self::commit(
+            tx,
+            school_id,
+            user,
+            &context,
+            &marks,
+            &preview.rows,
+            options.change_reason,
+        )
+        .await?;
+        preview.committed = true;
+        Ok(preview)
+    }
+
+    async fn commit(
+        mut tx: Transaction<'static, Postgres>,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        context: &SheetContext,
+        marks: &HashMap<String, ExistingMark>,
+        rows: &[MarkRow],
+        change_reason: Option<String>,
+    ) -> Result<(), AppError> {
+        let school_id = school_id.to_hex();
+        let change_reason = change_reason.unwrap_or_else(|| "Marksheet upload".to_string());
+
+        for row in rows {
+            let (Some(student_id), Some(score), Some(max_score)) =
+                (row.student_id, row.score, row.max_score)
+            else {
+                continue;
+            };
+            let student_id = student_id.to_hex();
+
+            match (row.status, marks.get(&student_id)) {
+                (MarkStatus::New, _) => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO scores (
+                          id, school_id, student_id, class_id, class_subject_id, exam_id,
+                          assessment_category_id, education_year_id, score, max_score, percentage,
+                          remarks, entered_by, recorded_by
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $13)
+                        "#,
+                    )
+                    .bind(Self::new_id())
+                    .bind(&school_id)
+                    .bind(&student_id)
+                    .bind(&context.class_id)
+                    .bind(&context.class_subject_id)
+                    .bind(&context.exam_id)
+                    .bind(&context.assessment_category_id)
+                    .bind(&context.education_year_id)
+                    .bind(score)
+                    .bind(max_score)
+                    .bind(percentage(score, max_score))
+                    .bind(&row.remarks)
+                    .bind(&user.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(Self::db_error)?;
+                }
+                (MarkStatus::Changed, Some(existing)) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE scores
+                        SET score = $2, max_score = $3, percentage = $4,
+                            remarks = coalesce($5, remarks), entered_by = $6, recorded_by = $6,
+                            updated_at = now()
+                        WHERE id = $1 AND deleted_at IS NULL
+                        "#,
+                    )
+                    .bind(&existing.score_id)
+                    .bind(score)
+                    .bind(max_score)
+                    .bind(percentage(score, max_score))
+                    .bind(&row.remarks)
+                    .bind(&user.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(Self::db_error)?;
+
+                    if existing.score != score {
+                        sqlx::query(
+                            r#"
+                            INSERT INTO score_audit_logs (
+                              id, school_id, score_id, changed_by, old_score, new_score, reason, change_reason, created_at, changed_at
+                            )
+                            VALUES ($1, $2, $3, $4, $5, $6, $7, $7, now(), now())
+                            "#,
+                        )
+                        .bind(Self::new_id())
+                        .bind(&school_id)
+                        .bind(&existing.score_id)
+                        .bind(&user.id)
+                        .bind(existing.score)
+                        .bind(score)
+                        .bind(&change_reason)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(Self::db_error)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        tx.commit().await.map_err(Self::db_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn student(name: &str, registration_number: Option<&str>) -> RosterEntry {
+        RosterEntry {
+            profile_id: ObjectId::new().to_hex(),
+            enrollment_id: ObjectId::new().to_hex(),
+            name: name.to_string(),
+            registration_number: registration_number.map(str::to_string),
+        }
+    }
+
+    fn mark(score: f64, max_score: f64, remarks: Option<&str>) -> ExistingMark {
+        ExistingMark {
+            score_id: ObjectId::new().to_hex(),
+            score,
+            max_score,
+            remarks: remarks.map(str::to_string),
+        }
+    }
+
+    fn table(headers: &[&str], rows: &[&[&str]]) -> Table {
+        Table {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|c| c.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    fn check(
+        table: Table,
+        roster: &[RosterEntry],
+        marks: &HashMap<String, ExistingMark>,
+        default_max_score: Option<f64>,
+    ) -> (Vec<MarkRow>, Vec<MissingStudent>) {
+        MarksheetService::check_rows(table, roster, marks, default_max_score).unwrap()
+    }
+
+    #[test]
+    fn matches_students_by_id_enrollment_or_registration_number() {
+        let roster = vec![
+            student("Alice", None),
+            student("Bob", None),
+            student("Carol", Some("REG-7")),
+        ];
+        let sheet = table(
+            &["Student_ID", "Registration_Number", "Score", "Max_Score"],
+            &[
+                &[&roster[0].profile_id, "", "12", "20"],
+                &[&roster[1].enrollment_id, "", "15", "20"],
+                &["", " reg-7 ", "18", "20"],
+            ],
+        );
+
+        let (rows, missing) = check(sheet, &roster, &HashMap::new(), None);
+
+        assert!(missing.is_empty());
+        for (row, expected) in rows.iter().zip(&roster) {
+            assert_eq!(row.status, MarkStatus::New);
+            assert_eq!(row.student_name.as_deref(), Some(expected.name.as_str()));
+            assert_eq!(
+                row.student_id.map(|id| id.to_hex()),
+                Some(expected.profile_id.clone())
+            );
+        }
+        assert_eq!(
+            rows.iter().map(|row| row.row_number).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn flags_unknown_and_duplicate_students() {
+        let roster = vec![student("Alice", Some("REG-1"))];
+        let sheet = table(
+            &["student_id", "registration_number", "score", "max_score"],
+            &[
+                &["", "REG-1", "10", "20"],
+                &[&roster[0].profile_id, "", "11", "20"],
+                &["", "REG-404", "12", "20"],
+            ],
+        );
+
+        let (rows, _) = check(sheet, &roster, &HashMap::new(), None);
+
+        assert_eq!(rows[0].status, MarkStatus::New);
+        assert_eq!(rows[1].status, MarkStatus::Invalid);
+        assert_eq!(rows[1].errors, vec!["Student already appears on row 2"]);
+        assert_eq!(rows[2].status, MarkStatus::Invalid);
+        assert_eq!(rows[2].errors, vec!["Unknown student REG-404"]);
+        assert!(rows[2].student_id.is_none());
+    }
+
+    #[test]
+    fn rejects_bad_scores_and_max_scores() {
+        let roster = vec![
+            student("Alice", Some("A")),
+            student("Bob", Some("B")),
+            student("Carol", Some("C")),
+            student("Dan", Some("D")),
+        ];
+        let sheet = table(
+            &["registration_number", "score", "max_score"],
+            &[
+                &["A", "abc", "20"],
+                &["B", "-1", "20"],
+                &["C", "10", "zero"],
+                &["D", "25", "20"],
+            ],
+        );
+
+        let (rows, _) = check(sheet, &roster, &HashMap::new(), None);
+
+        assert!(rows.iter().all(|row| row.status == MarkStatus::Invalid));
+        assert_eq!(rows[0].errors, vec!["Invalid score: abc"]);
+        assert_eq!(rows[1].errors, vec!["Invalid score: -1"]);
+        assert_eq!(rows[2].errors, vec!["Invalid max_score: zero"]);
+        assert_eq!(rows[3].errors, vec!["Score 25 is above max_score 20"]);
+    }
+
+    #[test]
+    fn max_score_falls_back_to_the_option_then_the_existing_mark() {
+        let roster = vec![student("Alice", Some("A")), student("Bob", Some("B"))];
+        let mut marks = HashMap::new();
+        marks.insert(roster[1].profile_id.clone(), mark(5.0, 50.0, None));
+        let sheet = table(
+            &["registration_number", "score"],
+            &[&["A", "8"], &["B", "40"]],
+        );
+
+        let (rows, _) = check(sheet, &roster, &marks, None);
+        assert_eq!(rows[0].errors, vec!["max_score is required"]);
+        assert_eq!(rows[1].max_score, Some(50.0));
+        assert_eq!(rows[1].status, MarkStatus::Changed);
+
+        let sheet = table(&["registration_number", "score"], &[&["A", "8"]]);
+        let (rows, _) = check(sheet, &roster, &marks, Some(10.0));
+        assert_eq!(rows[0].max_score, Some(10.0));
+        assert_eq!(rows[0].status, MarkStatus::New);
+    }
+
+    #[test]
+    fn compares_rows_with_existing_marks() {
+        let roster = vec![
+            student("Alice", Some("A")),
+            student("Bob", Some("B")),
+            student("Carol", Some("C")),
+            student("Dan", Some("D")),
+        ];
+        let mut marks = HashMap::new();
+        marks.insert(roster[0].profile_id.clone(), mark(15.0, 20.0, Some("Good")));
+        marks.insert(roster[1].profile_id.clone(), mark(15.0, 20.0, Some("Good")));
+        marks.insert(roster[2].profile_id.clone(), mark(15.0, 20.0, None));
+        let sheet = table(
+            &["registration_number", "score", "max_score", "remarks"],
+            &[
+                &["A", "15", "20", ""],
+                &["B", "15", "20", "Better"],
+                &["C", "16", "20", ""],
+                &["D", "", "20", ""],
+            ],
+        );
+
+        let (rows, _) = check(sheet, &roster, &marks, None);
+
+        assert_eq!(rows[0].status, MarkStatus::Unchanged);
+        assert_eq!(rows[1].status, MarkStatus::Changed);
+        assert_eq!(rows[2].status, MarkStatus::Changed);
+        assert_eq!(rows[2].old_score, Some(15.0));
+        assert_eq!(rows[3].status, MarkStatus::Blank);
+        assert!(rows[3].errors.is_empty());
+    }
+
+    #[test]
+    fn lists_roster_students_missing_from_the_sheet() {
+        let roster = vec![student("Alice", Some("A")), student("Bob", Some("B"))];
+        let sheet = table(
+            &["registration_number", "score", "max_score"],
+            &[&["A", "10", "20"]],
+        );
+
+        let (_, missing) = check(sheet, &roster, &HashMap::new(), None);
+
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].name, "Bob");
+        assert_eq!(missing[0].registration_number.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn requires_a_score_column_and_a_student_column() {
+        let roster = vec![student("Alice", Some("A"))];
+
+        let no_score = table(&["registration_number", "max_score"], &[&["A", "20"]]);
+        assert!(MarksheetService::check_rows(no_score, &roster, &HashMap::new(), None).is_err());
+
+        let no_student = table(&["student_name", "score"], &[&["Alice", "10"]]);
+        assert!(MarksheetService::check_rows(no_student, &roster, &HashMap::new(), None).is_err());
+    }
+}
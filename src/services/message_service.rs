@@ -34,9 +34,7 @@ impl MessageService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -48,8 +46,8 @@ impl MessageService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -174,9 +172,7 @@ impl MessageService {
         {
             let mut ids = self.recent_message_ids.write().await;
             if ids.contains(&dto.client_message_id) {
-                return Err(AppError {
-                    message: "Duplicate message detected".to_string(),
-                });
+                return Err(AppError::conflict("Duplicate message detected"));
             }
             ids.insert(dto.client_message_id.clone());
 
@@ -186,9 +182,7 @@ impl MessageService {
         }
 
         if dto.encrypted_payload.len() > 100 * 1024 {
-            return Err(AppError {
-                message: "Encrypted payload too large".to_string(),
-            });
+            return Err(AppError::bad_request("Encrypted payload too large"));
         }
 
         let id = dto.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
@@ -246,9 +240,7 @@ impl MessageService {
             .fetch_optional(&self.pool)
             .await
             .map_err(Self::db_error)?
-            .ok_or(AppError {
-                message: "Message not found".into(),
-            })?;
+            .ok_or(AppError::not_found("Message not found"))?;
 
         let mut message = Self::message_from_row(&row)?;
         self.hydrate_read_receipts(&mut message).await?;
@@ -373,6 +365,43 @@ impl MessageService {
         Ok(message)
     }
 
+    /// Record a read receipt for `reader`. Returns when the message was first
+    /// read by them; repeated acks keep the original time.
+    pub async fn mark_read(
+        &self,
+        id: &IdType,
+        reader: &ActorRef,
+    ) -> Result<chrono::DateTime<chrono::Utc>, AppError> {
+        let message_id = Self::id_to_string(id)?;
+        sqlx::query(
+            r#"
+            INSERT INTO message_read_receipts (id, message_id, actor_id, actor_role)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (message_id, actor_id, actor_role) DO NOTHING
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(&message_id)
+        .bind(reader.id.to_hex())
+        .bind(Self::role_to_string(&reader.role))
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query_scalar(
+            r#"
+            SELECT read_at FROM message_read_receipts
+            WHERE message_id = $1 AND actor_id = $2 AND actor_role = $3
+            "#,
+        )
+        .bind(&message_id)
+        .bind(reader.id.to_hex())
+        .bind(Self::role_to_string(&reader.role))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
     pub async fn get_conversation_messages_with_relations(
         &self,
         conversation_id: ObjectId,
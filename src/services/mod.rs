@@ -1,15 +1,24 @@
+pub mod account_token_service;
 pub mod analytics_service;
+pub mod annual_result_service;
 pub mod announcement_service;
 pub mod assessment_category_service;
 pub mod assignment_service;
+pub mod attendance_service;
 pub mod audit_log_service;
 pub mod auth_service;
+pub mod auth_session_service;
+pub mod backup_archive;
+pub mod backup_scheduler;
 pub mod backup_service;
+pub mod bulk_import_service;
+pub mod calendar_feed_service;
 pub mod class_service;
 pub mod class_subject_service;
 pub mod class_timetable_service;
 pub mod cloudinary_service;
 pub mod comment_service;
+pub mod conversation_hub;
 pub mod conversation_service;
 pub mod database_status_service;
 pub mod education_year_service;
@@ -17,26 +26,40 @@ pub mod event_bus;
 pub mod event_service;
 pub mod exam_service;
 pub mod feature_service;
+pub mod finance_service;
 pub mod gpa_calculation_service;
 pub mod grading_scale_service;
 pub mod join_school_request_service;
 pub mod learning_material_service;
 pub mod like_service;
 pub mod location_service;
+pub mod mail_transport;
 pub mod main_class_service;
+pub mod marksheet_service;
 pub mod message_service;
+pub mod notification_service;
 pub mod parent_service;
+pub mod permission_service;
+pub mod pg_event_transport;
+pub mod promotion_service;
 pub mod ranking_service;
 pub mod recycle_bin_service;
+pub mod report_card_service;
 pub mod role_service;
+pub mod school_room_service;
 pub mod school_service;
 pub mod school_staff_service;
 pub mod school_timetable_service;
+pub mod score_moderation_service;
 pub mod score_service;
 pub mod sector_service;
 pub mod student_service;
+pub mod teacher_cover_service;
 pub mod teacher_service;
 pub mod template_subject_service;
+pub mod tenant_service;
+pub mod timetable_validation_service;
 pub mod trade_service;
+pub mod two_factor_service;
 pub mod user_public_key_service;
 pub mod user_service;
@@ -0,0 +1,121 @@
+use crate::{
+    domain::{
+        common_details::Paginated,
+        notification::{
+            MarkNotificationsReadResult, Notification, NotificationPreference, NotificationQuery,
+            UnreadNotificationCount,
+        },
+    },
+    errors::AppError,
+    models::id_model::IdType,
+    repositories::notification_repo::NotificationRepo,
+};
+
+/// Unread items pushed to a client when it connects to the event stream.
+pub const REPLAY_LIMIT: i64 = 50;
+
+pub struct NotificationService<'a> {
+    repo: &'a NotificationRepo,
+}
+
+impl<'a> NotificationService<'a> {
+    pub fn new(repo: &'a NotificationRepo) -> Self {
+        Self { repo }
+    }
+
+    pub async fn record(&self, notification: &Notification) -> Result<Notification, AppError> {
+        if notification.user_id.is_none() && notification.school_id.is_none() {
+            return Err(AppError::bad_request(
+                "Notification needs a user or a school audience",
+            ));
+        }
+        self.repo.insert(notification).await
+    }
+
+    pub async fn get_all(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+        query: &NotificationQuery,
+    ) -> Result<Paginated<Notification>, AppError> {
+        self.repo.find_many(user_id, school_id, query).await
+    }
+
+    pub async fn unread_count(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+    ) -> Result<UnreadNotificationCount, AppError> {
+        Ok(UnreadNotificationCount {
+            unread: self.repo.unread_count(user_id, school_id).await?,
+        })
+    }
+
+    pub async fn replay(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+    ) -> Result<Vec<Notification>, AppError> {
+        self.repo
+            .unread_for_replay(user_id, school_id, REPLAY_LIMIT)
+            .await
+    }
+
+    async fn find_visible(&self, id: &IdType, user_id: &str) -> Result<Notification, AppError> {
+        self.repo
+            .find_visible(id, user_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Notification not found"))
+    }
+
+    pub async fn mark_read(&self, id: &IdType, user_id: &str) -> Result<Notification, AppError> {
+        let notification = self.find_visible(id, user_id).await?;
+        let notification_id = notification.id.map(|id| id.to_hex()).unwrap_or_default();
+        self.repo.mark_read(&notification_id, user_id).await?;
+        self.find_visible(id, user_id).await
+    }
+
+    pub async fn mark_unread(&self, id: &IdType, user_id: &str) -> Result<Notification, AppError> {
+        let notification = self.find_visible(id, user_id).await?;
+        let notification_id = notification.id.map(|id| id.to_hex()).unwrap_or_default();
+        self.repo.mark_unread(&notification_id, user_id).await?;
+        Ok(Notification {
+            read_at: None,
+            ..notification
+        })
+    }
+
+    pub async fn mark_all_read(
+        &self,
+        user_id: &str,
+        school_id: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<MarkNotificationsReadResult, AppError> {
+        Ok(MarkNotificationsReadResult {
+            marked: self
+                .repo
+                .mark_all_read(user_id, school_id, category)
+                .await?,
+        })
+    }
+
+    pub async fn preferences(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<NotificationPreference>, AppError> {
+        self.repo.preferences(user_id).await
+    }
+
+    pub async fn set_preference(
+        &self,
+        user_id: &str,
+        category: &str,
+        muted: bool,
+    ) -> Result<NotificationPreference, AppError> {
+        let category = category.trim();
+        if category.is_empty() {
+            return Err(AppError::field("category", "category is required"));
+        }
+        self.repo.set_preference(user_id, category, muted).await
+    }
+}
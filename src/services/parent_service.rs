@@ -7,11 +7,12 @@ use crate::{
     config::state::AppState,
     domain::{
         announcement::AnnouncementWithRelations,
+        attendance::AttendanceCounts,
         common_details::{Gender, Paginated},
         parent::{
-            AttendanceRecord, AttendanceSummary, ChildSummary, FinanceSummary, Parent,
-            ParentDashboard, ParentPartial, ParentStatus, ParentWithRelations, PaymentRecord,
-            StudentResults,
+            AttendanceRecord, AttendanceSummary, ChildSummary, FinanceSummary, InstallmentInfo,
+            Parent, ParentDashboard, ParentPartial, ParentStatus, ParentWithRelations,
+            PaymentRecord, StudentResults,
         },
     },
     errors::AppError,
@@ -38,9 +39,9 @@ impl ParentQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -80,9 +81,7 @@ impl ParentService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -94,8 +93,8 @@ impl ParentService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -228,15 +227,16 @@ impl ParentService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported parent filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported parent filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -434,24 +434,25 @@ impl ParentService {
 
     pub async fn create(&self, dto: Parent) -> Result<Parent, AppError> {
         self.ensure_indexes().await?;
-        is_valid_name(&dto.name).map_err(|e| AppError { message: e })?;
-        is_valid_email(&dto.email).map_err(|e| AppError { message: e })?;
+        is_valid_name(&dto.name).map_err(AppError::bad_request)?;
+        is_valid_email(&dto.email).map_err(AppError::bad_request)?;
 
         let school_id = dto.school_id.as_ref().map(|id| id.to_hex());
         if let Some(parent) = self
             .email_exists_in_school(&dto.email, school_id.as_deref(), None)
             .await?
         {
-            return Err(AppError {
-                message: format!("Email already exists: {}", parent.email),
-            });
+            return Err(AppError::conflict(format!(
+                "Email already exists: {}",
+                parent.email
+            )));
         }
 
         let mut parent = dto;
         if let Some(image_data) = parent.image.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&image_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             parent.image_id = Some(cloud_res.public_id);
             parent.image = Some(cloud_res.secure_url);
         }
@@ -562,9 +563,7 @@ impl ParentService {
 
         match row {
             Some(row) => Ok(self.parent_from_row(row).await?),
-            None => Err(AppError {
-                message: "Parent not found".into(),
-            }),
+            None => Err(AppError::not_found("Parent not found")),
         }
     }
 
@@ -642,10 +641,10 @@ impl ParentService {
 
     pub async fn update(&self, id: &IdType, update: &ParentPartial) -> Result<Parent, AppError> {
         if let Some(ref name) = update.name {
-            is_valid_name(name).map_err(|e| AppError { message: e })?;
+            is_valid_name(name).map_err(AppError::bad_request)?;
         }
         if let Some(ref email) = update.email {
-            is_valid_email(email).map_err(|e| AppError { message: e })?;
+            is_valid_email(email).map_err(AppError::bad_request)?;
         }
 
         let existing_parent = self.find_one(Some(id), None).await?;
@@ -666,9 +665,10 @@ impl ParentService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("Email already exists: {}", email),
-                });
+                return Err(AppError::conflict(format!(
+                    "Email already exists: {}",
+                    email
+                )));
             }
         }
 
@@ -682,7 +682,7 @@ impl ParentService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_image_data)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.image_id = Some(Some(cloud_res.public_id));
                 update_data.image = Some(Some(cloud_res.secure_url));
             }
@@ -759,9 +759,7 @@ impl ParentService {
         }
 
         if !touched && update_data.student_ids.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         let parent = self.find_one(Some(id), None).await?;
@@ -925,19 +923,22 @@ impl ParentService {
     async fn get_child_summary(
         &self,
         student_id: &str,
-        _school_id: &str,
-        _state: &AppState,
+        school_id: &str,
+        state: &AppState,
     ) -> Result<ChildSummary, AppError> {
         let student_service = crate::services::student_service::StudentService::new(&self.pool);
         let student = student_service
             .find_one(Some(&IdType::from_string(student_id)), None)
             .await?;
+        let attendance = self
+            .get_attendance_summary(student_id, school_id, state)
+            .await?;
 
         Ok(ChildSummary {
             student_id: student.id,
             student_name: student.name,
             class_name: None,
-            attendance_percentage: 85.0,
+            attendance_percentage: attendance.attendance_percentage,
             current_term_gpa: 3.5,
             outstanding_fees: 0.0,
         })
@@ -954,7 +955,8 @@ impl ParentService {
             r#"
             SELECT status, count(*)::bigint AS count
             FROM attendance
-            WHERE school_id = $1 AND student_id = $2 AND deleted_at IS NULL
+            WHERE school_id = $1 AND student_id = $2
+              AND period_id IS NULL AND deleted_at IS NULL
             GROUP BY status
             "#,
         )
@@ -985,7 +987,8 @@ impl ParentService {
             r#"
             SELECT date::text AS date, status, note
             FROM attendance
-            WHERE school_id = $1 AND student_id = $2 AND deleted_at IS NULL
+            WHERE school_id = $1 AND student_id = $2
+              AND period_id IS NULL AND deleted_at IS NULL
             ORDER BY date DESC
             LIMIT 10
             "#,
@@ -1011,12 +1014,15 @@ impl ParentService {
             });
         }
 
-        let total_days = present_count + absent_count + late_count + excused_count;
-        let attendance_percentage = if total_days > 0 {
-            (present_count as f64 / total_days as f64) * 100.0
-        } else {
-            0.0
+        let counts = AttendanceCounts {
+            present: present_count,
+            absent: absent_count,
+            late: late_count,
+            excused: excused_count,
+            unmarked: 0,
         };
+        let total_days = counts.total_marked();
+        let attendance_percentage = counts.attendance_percentage();
 
         Ok(AttendanceSummary {
             present_count,
@@ -1049,7 +1055,8 @@ impl ParentService {
             .push_bind(school_id)
             .push(" AND student_id = ")
             .push_bind(profile_id)
-            .push(" AND deleted_at IS NULL");
+            // Parents only see results once the exam's results are published
+            .push(" AND is_finalized = true AND deleted_at IS NULL");
         if let Some(year_id) = education_year_id {
             query.push(" AND academic_year = ").push_bind(year_id);
         }
@@ -1063,9 +1070,7 @@ impl ParentService {
             .fetch_optional(&self.pool)
             .await
             .map_err(Self::db_error)?
-            .ok_or_else(|| AppError {
-                message: "No results found for this student".into(),
-            })?;
+            .ok_or_else(|| AppError::bad_request("No results found for this student"))?;
 
         let average_score: Option<f64> = row.try_get("average_score").ok().flatten();
         Ok(StudentResults {
@@ -1092,25 +1097,87 @@ impl ParentService {
         let row = sqlx::query(
             r#"
             SELECT
-              COALESCE(sum(amount) FILTER (WHERE status <> 'paid'), 0)::float8 AS outstanding,
-              COALESCE(sum(amount) FILTER (WHERE status = 'paid'), 0)::float8 AS paid,
-              COALESCE(sum(amount), 0)::float8 AS total
+              COALESCE(sum(amount) FILTER (
+                WHERE record_type IN ('invoice', 'adjustment', 'fee')
+              ), 0)::float8 AS total,
+              COALESCE(sum(CASE
+                WHEN record_type = 'payment' THEN amount
+                WHEN record_type = 'refund' THEN -amount
+                WHEN record_type = 'fee' AND lower(status) = 'paid' THEN amount
+                ELSE 0
+              END), 0)::float8 AS paid
             FROM finance_records
             WHERE school_id = $1 AND student_id = $2 AND deleted_at IS NULL
             "#,
         )
         .bind(school_id)
-        .bind(profile_id)
+        .bind(&profile_id)
         .fetch_one(&self.pool)
         .await
         .map_err(Self::db_error)?;
 
+        let history = sqlx::query(
+            r#"
+            SELECT COALESCE(paid_at, created_at) AS paid_on, amount::float8 AS amount,
+                   payment_method, reference_number
+            FROM finance_records
+            WHERE school_id = $1 AND student_id = $2 AND record_type = 'payment'
+              AND deleted_at IS NULL
+            ORDER BY paid_on DESC
+            "#,
+        )
+        .bind(school_id)
+        .bind(&profile_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let installments = sqlx::query(
+            r#"
+            SELECT due_at, amount::float8 AS amount, status
+            FROM finance_records
+            WHERE school_id = $1 AND student_id = $2 AND record_type = 'invoice'
+              AND due_at IS NOT NULL AND deleted_at IS NULL
+            ORDER BY due_at ASC
+            "#,
+        )
+        .bind(school_id)
+        .bind(&profile_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let total: f64 = row.try_get("total").ok().unwrap_or(0.0);
+        let paid: f64 = row.try_get("paid").ok().unwrap_or(0.0);
+
         Ok(FinanceSummary {
-            total_fee_required: row.try_get("total").ok().unwrap_or(0.0),
-            amount_paid: row.try_get("paid").ok().unwrap_or(0.0),
-            outstanding_balance: row.try_get("outstanding").ok().unwrap_or(0.0),
-            payment_history: Vec::<PaymentRecord>::new(),
-            installments: vec![],
+            total_fee_required: total,
+            amount_paid: paid,
+            outstanding_balance: (total - paid).max(0.0),
+            payment_history: history
+                .into_iter()
+                .filter_map(|row| {
+                    Some(PaymentRecord {
+                        date: row.try_get("paid_on").ok()?,
+                        amount: row.try_get("amount").ok().unwrap_or(0.0),
+                        payment_method: row.try_get("payment_method").ok().flatten(),
+                        reference: row.try_get("reference_number").ok().flatten(),
+                    })
+                })
+                .collect(),
+            installments: installments
+                .into_iter()
+                .filter_map(|row| {
+                    Some(InstallmentInfo {
+                        due_date: row.try_get("due_at").ok()?,
+                        amount: row.try_get("amount").ok().unwrap_or(0.0),
+                        status: row
+                            .try_get("status")
+                            .ok()
+                            .unwrap_or_else(|| "pending".to_string()),
+                    })
+                })
+                .collect(),
         })
     }
 
@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{HttpMessage, HttpRequest};
+use sqlx::{PgPool, Row};
+
+use crate::{
+    domain::{
+        auth_user::AuthUserDto,
+        common_details::UserRole,
+        role::{
+            PermissionDecision, PermissionExplainQuery, PermissionGrant, PermissionScope,
+            PermissionTarget,
+        },
+        tenant::TenantEntity,
+    },
+    errors::AppError,
+    services::tenant_service::TenantService,
+};
+
+/// Permissions every member with the platform role holds before any custom
+/// role is assigned, and how far each one reaches
+fn built_in_permissions(role: &UserRole) -> &'static [(&'static str, PermissionScope)] {
+    use PermissionScope::{Class, School};
+    match role {
+        UserRole::SCHOOLSTAFF => &[
+            ("audit.view", School),
+            ("analytics.read.school", School),
+            ("announcement.manage", School),
+            ("attendance.read.class", School),
+            ("attendance.read.school", School),
+            ("attendance.record.class", School),
+            ("class.manage", School),
+            ("exam.manage", School),
+            ("finance.manage", School),
+            ("finance.read", School),
+            ("import.manage", School),
+            ("material.manage", School),
+            ("moderation.read", School),
+            ("parent.manage", School),
+            ("parent.read", School),
+            ("report_card.read", School),
+            ("result.manage", School),
+            ("result.moderate", School),
+            ("result.publish", School),
+            ("result.read", School),
+            ("schedule.read", School),
+            ("student.manage", School),
+            ("teacher.manage", School),
+        ],
+        UserRole::TEACHER => &[
+            ("attendance.read.class", Class),
+            ("attendance.record.class", Class),
+            ("exam.manage", School),
+            ("material.manage", School),
+            ("moderation.read", School),
+            ("report_card.read", Class),
+            ("result.read", Class),
+            ("schedule.read", Class),
+            ("student.manage", School),
+        ],
+        _ => &[],
+    }
+}
+
+/// Actions that belong to no school, and the platform roles allowed them
+fn platform_permissions(role: &UserRole) -> &'static [&'static str] {
+    match role {
+        UserRole::SCHOOLSTAFF => &["school.create", "join_request.cleanup"],
+        UserRole::TEACHER => &["join_request.cleanup"],
+        _ => &[],
+    }
+}
+
+/// What the database says about one user, school and permission
+#[derive(Debug, Clone)]
+struct Grants {
+    is_member: bool,
+    grants: Vec<PermissionGrant>,
+}
+
+/// User, school and permission a lookup was made for
+type GrantKey = (String, String, String);
+
+/// Grants already looked up while handling the current request
+#[derive(Debug, Clone, Default)]
+struct PermissionCache(Arc<Mutex<HashMap<GrantKey, Grants>>>);
+
+/// The one place permissions are evaluated: platform admins, then school
+/// membership, then the platform role's built-in permissions, then the
+/// school roles assigned through `roles_api`, honouring the scope each
+/// permission is granted at.
+pub struct PermissionService {
+    pub pool: PgPool,
+    cache: PermissionCache,
+}
+
+impl PermissionService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self {
+            pool: pool.clone(),
+            cache: PermissionCache::default(),
+        }
+    }
+
+    /// Service sharing its cache with every other check of the same request
+    pub fn for_request(req: &HttpRequest, pool: &PgPool) -> Self {
+        let cache = req
+            .extensions_mut()
+            .get_or_insert_with(PermissionCache::default)
+            .clone();
+        Self {
+            pool: pool.clone(),
+            cache,
+        }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    async fn grants(
+        &self,
+        user_id: &str,
+        school_id: &str,
+        permission: &str,
+    ) -> Result<Grants, AppError> {
+        let key = (
+            user_id.to_string(),
+            school_id.to_string(),
+            permission.to_string(),
+        );
+        if let Some(cached) = self.cache.0.lock().ok().and_then(|c| c.get(&key).cloned()) {
+            return Ok(cached);
+        }
+
+        let is_member = TenantService::new(&self.pool)
+            .is_member(user_id, school_id)
+            .await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT r.id, r.name, p.scope
+            FROM user_role_assignments ura
+            JOIN roles r ON r.id = ura.role_id
+            JOIN role_permissions rp ON rp.role_id = r.id
+            JOIN permissions p ON p.id = rp.permission_id
+            WHERE ura.user_id = $1
+              AND ura.school_id = $2
+              AND ura.revoked_at IS NULL
+              AND (ura.starts_at IS NULL OR ura.starts_at <= now())
+              AND (ura.expires_at IS NULL OR ura.expires_at > now())
+              AND r.is_active = true
+              AND p.code = $3
+            ORDER BY r.name
+            "#,
+        )
+        .bind(user_id)
+        .bind(school_id)
+        .bind(permission)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut grants = Vec::with_capacity(rows.len());
+        for row in rows {
+            let scope: String = row.try_get("scope").map_err(Self::db_error)?;
+            grants.push(PermissionGrant {
+                role_id: row.try_get("id").map_err(Self::db_error)?,
+                role_name: row.try_get("name").map_err(Self::db_error)?,
+                scope: PermissionScope::parse(&scope).unwrap_or(PermissionScope::School),
+            });
+        }
+
+        let grants = Grants { is_member, grants };
+        if let Ok(mut cache) = self.cache.0.lock() {
+            cache.insert(key, grants.clone());
+        }
+        Ok(grants)
+    }
+
+    /// Decide whether the user may exercise the permission on the target
+    pub async fn evaluate(
+        &self,
+        user_id: &str,
+        role: Option<&UserRole>,
+        permission: &str,
+        target: Option<&PermissionTarget>,
+    ) -> Result<PermissionDecision, AppError> {
+        let mut decision = PermissionDecision {
+            user_id: user_id.to_string(),
+            permission: permission.to_string(),
+            school_id: target.map(|target| target.school_id.clone()),
+            allowed: false,
+            reason: String::new(),
+            is_member: false,
+            built_in: false,
+            grants: Vec::new(),
+        };
+
+        if role == Some(&UserRole::ADMIN) {
+            decision.allowed = true;
+            decision.reason = "Platform admins hold every permission".to_string();
+            return Ok(decision);
+        }
+
+        let Some(target) = target else {
+            if role.is_some_and(|role| platform_permissions(role).contains(&permission)) {
+                decision.allowed = true;
+                decision.built_in = true;
+                decision.reason = format!(
+                    "Every {} holds {}",
+                    role.map(UserRole::to_string).unwrap_or_default(),
+                    permission
+                );
+            } else {
+                decision.reason = format!("{} can only be granted within a school", permission);
+            }
+            return Ok(decision);
+        };
+
+        let grants = self.grants(user_id, &target.school_id, permission).await?;
+        decision.is_member = grants.is_member;
+        decision.grants = grants.grants;
+        let built_in = role.and_then(|role| {
+            built_in_permissions(role)
+                .iter()
+                .find(|(code, _)| *code == permission)
+                .map(|(_, scope)| (role, *scope))
+        });
+        decision.built_in = built_in.is_some();
+
+        if !decision.is_member {
+            decision.reason = "User is not an active member of the school".to_string();
+            return Ok(decision);
+        }
+
+        // Narrower built-ins are weighed like a grant from a role
+        if let Some((role, scope)) = built_in.filter(|(_, scope)| *scope != PermissionScope::School)
+        {
+            decision.grants.push(PermissionGrant {
+                role_id: String::new(),
+                role_name: format!("built-in {}", role.to_string()),
+                scope,
+            });
+        }
+
+        if built_in.is_some_and(|(_, scope)| scope == PermissionScope::School) {
+            decision.allowed = true;
+            decision.reason = format!(
+                "Every {} member holds {}",
+                role.map(UserRole::to_string).unwrap_or_default(),
+                permission
+            );
+            return Ok(decision);
+        }
+
+        if let Some(grant) = decision
+            .grants
+            .iter()
+            .find(|grant| grant.scope == PermissionScope::School)
+        {
+            decision.allowed = true;
+            decision.reason = format!("Granted school-wide by role {}", grant.role_name);
+            return Ok(decision);
+        }
+
+        let mut denials = Vec::new();
+        if let Some(grant) = decision
+            .grants
+            .iter()
+            .find(|grant| grant.scope == PermissionScope::Class)
+        {
+            match &target.class_id {
+                Some(class_id) => {
+                    let tenants = TenantService::new(&self.pool);
+                    let in_school = tenants
+                        .school_of(TenantEntity::Class, class_id)
+                        .await
+                        .is_ok_and(|school_id| school_id == target.school_id);
+                    if in_school && tenants.teaches_class(user_id, class_id).await? {
+                        decision.allowed = true;
+                        decision.reason =
+                            format!("Granted for classes they teach by role {}", grant.role_name);
+                        return Ok(decision);
+                    }
+                    denials.push(format!(
+                        "role {} grants it only for classes they teach, which this is not",
+                        grant.role_name
+                    ));
+                }
+                None => denials.push(format!(
+                    "role {} grants it only for classes they teach",
+                    grant.role_name
+                )),
+            }
+        }
+
+        if let Some(grant) = decision
+            .grants
+            .iter()
+            .find(|grant| grant.scope == PermissionScope::Own)
+        {
+            if target.owner_user_id.as_deref() == Some(user_id) {
+                decision.allowed = true;
+                decision.reason =
+                    format!("Granted on their own records by role {}", grant.role_name);
+                return Ok(decision);
+            }
+            denials.push(format!(
+                "role {} grants it only on their own records",
+                grant.role_name
+            ));
+        }
+
+        decision.reason = if denials.is_empty() {
+            format!("No role of the user in this school grants {}", permission)
+        } else {
+            format!("Denied: {}", denials.join("; "))
+        };
+        Ok(decision)
+    }
+
+    /// `Ok` when the signed-in user holds the permission on the target
+    pub async fn check(
+        &self,
+        user: &AuthUserDto,
+        permission: &str,
+        target: Option<&PermissionTarget>,
+    ) -> Result<(), String> {
+        match self
+            .evaluate(&user.id, user.role.as_ref(), permission, target)
+            .await
+        {
+            Ok(decision) if decision.allowed => Ok(()),
+            Ok(_) => Err(format!("Access denied: {} permission required", permission)),
+            Err(_) => Err("Error checking permissions".to_string()),
+        }
+    }
+
+    /// Why a user would or would not be allowed, for admins
+    pub async fn explain(
+        &self,
+        query: &PermissionExplainQuery,
+    ) -> Result<PermissionDecision, AppError> {
+        let role: Option<String> = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+            .bind(&query.user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?
+            .ok_or_else(|| AppError::not_found("User not found"))?;
+        let role: Option<UserRole> =
+            role.and_then(|role| serde_json::from_value(serde_json::Value::String(role)).ok());
+
+        let target = query.school_id.as_ref().map(|school_id| PermissionTarget {
+            school_id: school_id.clone(),
+            class_id: query.class_id.clone(),
+            owner_user_id: query.owner_user_id.clone(),
+        });
+
+        self.evaluate(
+            &query.user_id,
+            role.as_ref(),
+            &query.permission,
+            target.as_ref(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,193 @@
+//! Cross-node [`EventTransport`] over PostgreSQL `LISTEN/NOTIFY`.
+//!
+//! Each node publishes events on one channel and listens on it. Messages carry
+//! the publishing node id so a node skips its own events, which it already
+//! delivered locally. `NOTIFY` payloads are capped at 8000 bytes; larger events
+//! are parked in `event_bus_payloads` and only their id is sent.
+//!
+//! Notifications sent while a listener is reconnecting are lost, like any
+//! event sent while a client is disconnected.
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgListener, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    services::event_bus::{Event, EventBus, EventTransport, LocalTransport},
+};
+
+pub const EVENT_CHANNEL: &str = "space_together_events";
+
+/// Stay under the 8000-byte NOTIFY limit.
+const MAX_NOTIFY_PAYLOAD: usize = 7900;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    node_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    event: Option<Event>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    payload_id: Option<Uuid>,
+}
+
+pub struct PgNotifyTransport {
+    pool: PgPool,
+    node_id: Uuid,
+}
+
+fn db_error(error: sqlx::Error) -> AppError {
+    AppError::from(error)
+}
+
+fn encode(envelope: &Envelope) -> Result<String, AppError> {
+    serde_json::to_string(envelope)
+        .map_err(|e| AppError::internal(format!("Failed to encode event: {}", e)))
+}
+
+/// Transport selected by `EVENT_BUS_TRANSPORT`: `postgres` (default) or
+/// `local` for a single node.
+pub fn transport_from_env(pool: &PgPool) -> Arc<dyn EventTransport> {
+    match std::env::var("EVENT_BUS_TRANSPORT")
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "local" | "memory" => Arc::new(LocalTransport),
+        _ => Arc::new(PgNotifyTransport::new(pool)),
+    }
+}
+
+impl PgNotifyTransport {
+    pub fn new(pool: &PgPool) -> Self {
+        Self {
+            pool: pool.clone(),
+            node_id: Uuid::new_v4(),
+        }
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), AppError> {
+        let mut payload = encode(&Envelope {
+            node_id: self.node_id,
+            event: Some(event.clone()),
+            payload_id: None,
+        })?;
+
+        if payload.len() > MAX_NOTIFY_PAYLOAD {
+            let payload_id = Uuid::new_v4();
+            sqlx::query("INSERT INTO event_bus_payloads (id, event) VALUES ($1, $2)")
+                .bind(payload_id.to_string())
+                .bind(serde_json::to_value(event).unwrap_or(serde_json::Value::Null))
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+            // Every node has had plenty of time to read older payloads
+            sqlx::query(
+                "DELETE FROM event_bus_payloads WHERE created_at < now() - interval '10 minutes'",
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+            payload = encode(&Envelope {
+                node_id: self.node_id,
+                event: None,
+                payload_id: Some(payload_id),
+            })?;
+        }
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(EVENT_CHANNEL)
+            .bind(payload)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn receive(&self, payload: &str) -> Result<Option<Event>, AppError> {
+        let envelope: Envelope = serde_json::from_str(payload)
+            .map_err(|e| AppError::bad_request(format!("Invalid event notification: {}", e)))?;
+        if envelope.node_id == self.node_id {
+            return Ok(None);
+        }
+        if let Some(event) = envelope.event {
+            return Ok(Some(event));
+        }
+        let Some(payload_id) = envelope.payload_id else {
+            return Ok(None);
+        };
+
+        let stored: Option<serde_json::Value> =
+            sqlx::query_scalar("SELECT event FROM event_bus_payloads WHERE id = $1")
+                .bind(payload_id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(db_error)?;
+        stored
+            .map(|event| {
+                serde_json::from_value(event).map_err(|e| {
+                    AppError::bad_request(format!("Invalid stored event payload: {}", e))
+                })
+            })
+            .transpose()
+    }
+
+    async fn listen(&self, bus: &EventBus) -> Result<(), AppError> {
+        let mut listener = PgListener::connect_with(&self.pool)
+            .await
+            .map_err(db_error)?;
+        listener.listen(EVENT_CHANNEL).await.map_err(db_error)?;
+        log::info!(
+            "Event bus node {} listening on {}",
+            self.node_id,
+            EVENT_CHANNEL
+        );
+
+        loop {
+            let notification = listener.recv().await.map_err(db_error)?;
+            match self.receive(notification.payload()).await {
+                Ok(Some(event)) => bus.deliver_local(&event).await,
+                Ok(None) => {}
+                Err(err) => log::warn!("Dropping event notification: {}", err.message),
+            }
+        }
+    }
+}
+
+impl EventTransport for PgNotifyTransport {
+    fn publish<'a>(&'a self, event: &'a Event) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(self.send(event))
+    }
+
+    fn next_event_id(&self) -> BoxFuture<'_, Result<Option<u64>, AppError>> {
+        Box::pin(async move {
+            let id: i64 = sqlx::query_scalar("SELECT nextval('event_bus_event_id_seq')")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(db_error)?;
+            Ok(Some(id as u64))
+        })
+    }
+
+    fn subscribe(self: Arc<Self>, bus: Arc<EventBus>) {
+        actix_rt::spawn(async move {
+            loop {
+                if let Err(err) = self.listen(&bus).await {
+                    log::warn!(
+                        "Event bus listener stopped, reconnecting in {:?}: {}",
+                        RECONNECT_DELAY,
+                        err.message
+                    );
+                }
+                actix_rt::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+    }
+}
@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use crate::{
+    domain::promotion::{
+        CreatePromotionProposal, PromotionBatch, PromotionBatchQuery, PromotionBatchStatus,
+        PromotionDecision, PromotionResult, PromotionRule, PromotionRulePartial, PromotionStatus,
+    },
+    errors::AppError,
+    models::id_model::IdType,
+    repositories::promotion_repo::{
+        ClassPlacement, MainClassLevel, PromotionCandidate, PromotionRepo, SubjectAverage,
+    },
+    utils::object_id::ObjectId,
+};
+
+/// Where a student who passes goes next.
+#[derive(Debug, Clone, PartialEq)]
+enum NextPlacement {
+    Class(String),
+    Graduate,
+    Unknown(String),
+}
+
+/// Outcome of checking one student against a rule, before placement.
+#[derive(Debug, Clone)]
+struct Evaluation {
+    status: PromotionStatus,
+    reason: String,
+    failed_subjects: Vec<String>,
+}
+
+/// Classes and levels of a school, for finding each student's next class.
+struct LevelMap {
+    classes: HashMap<String, ClassPlacement>,
+    levels: Vec<MainClassLevel>,
+}
+
+impl LevelMap {
+    fn main_class_of(&self, class_id: &str) -> Option<&str> {
+        let class = self.classes.get(class_id)?;
+        class.main_class_id.as_deref().or_else(|| {
+            class
+                .parent_class_id
+                .as_deref()
+                .and_then(|parent| self.classes.get(parent))
+                .and_then(|parent| parent.main_class_id.as_deref())
+        })
+    }
+
+    /// The class one level up in the same trade. Between several classes at
+    /// that level, prefer the same kind (main class or subclass) and the same
+    /// stream suffix, so "S1 A" goes to "S2 A".
+    fn next_placement(&self, class_id: &str) -> NextPlacement {
+        let Some(current) = self.classes.get(class_id) else {
+            return NextPlacement::Unknown("Current class not found".into());
+        };
+        let Some(level) = self
+            .main_class_of(class_id)
+            .and_then(|id| self.levels.iter().find(|level| level.id == id))
+        else {
+            return NextPlacement::Unknown("Class is not linked to a level".into());
+        };
+        let Some(current_level) = level.level else {
+            return NextPlacement::Unknown("Class level is not set".into());
+        };
+
+        let Some(next_level) = self
+            .levels
+            .iter()
+            .filter(|candidate| {
+                candidate.trade_id == level.trade_id
+                    && candidate.level.is_some_and(|value| value > current_level)
+            })
+            .min_by_key(|candidate| candidate.level)
+        else {
+            return NextPlacement::Graduate;
+        };
+
+        let mut candidates: Vec<&ClassPlacement> = self
+            .classes
+            .values()
+            .filter(|class| self.main_class_of(&class.id) == Some(next_level.id.as_str()))
+            .collect();
+        if candidates.is_empty() {
+            return NextPlacement::Unknown("No class exists for the next level".into());
+        }
+        candidates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let is_subclass = current.parent_class_id.is_some();
+        let same_kind: Vec<&ClassPlacement> = candidates
+            .iter()
+            .copied()
+            .filter(|class| class.parent_class_id.is_some() == is_subclass)
+            .collect();
+        let pool = if same_kind.is_empty() {
+            &candidates
+        } else {
+            &same_kind
+        };
+
+        let suffix = current.name.split_whitespace().last();
+        pool.iter()
+            .find(|class| pool.len() > 1 && class.name.split_whitespace().last() == suffix)
+            .or_else(|| pool.first())
+            .map(|class| NextPlacement::Class(class.id.clone()))
+            .unwrap_or_else(|| NextPlacement::Unknown("No class exists for the next level".into()))
+    }
+}
+
+pub struct PromotionService<'a> {
+    repo: &'a PromotionRepo,
+}
+
+impl<'a> PromotionService<'a> {
+    pub fn new(repo: &'a PromotionRepo) -> Self {
+        Self { repo }
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn ensure_school(record_school: Option<ObjectId>, school_id: &str) -> Result<(), AppError> {
+        match record_school {
+            Some(id) if id.to_hex() == school_id => Ok(()),
+            _ => Err(AppError::bad_request(
+                "Record does not belong to this school",
+            )),
+        }
+    }
+
+    async fn ensure_year(
+        &self,
+        education_year_id: &ObjectId,
+        school_id: &str,
+    ) -> Result<(), AppError> {
+        let year_school = self
+            .repo
+            .year_school_id(&education_year_id.to_hex())
+            .await?;
+        if year_school.as_deref() != Some(school_id) {
+            return Err(AppError::bad_request(
+                "Education year does not belong to this school",
+            ));
+        }
+        Ok(())
+    }
+
+    async fn ensure_class(&self, class_id: &ObjectId, school_id: &str) -> Result<(), AppError> {
+        let class_school = self.repo.class_school_id(&class_id.to_hex()).await?;
+        if class_school.as_deref() != Some(school_id) {
+            return Err(AppError::bad_request(
+                "Class does not belong to this school",
+            ));
+        }
+        Ok(())
+    }
+
+    // ========== RULES ==========
+
+    pub async fn create_rule(
+        &self,
+        school_id: &str,
+        mut rule: PromotionRule,
+        created_by: Option<&str>,
+    ) -> Result<PromotionRule, AppError> {
+        rule.validate().map_err(AppError::validation)?;
+        if let Some(education_year_id) = rule.education_year_id {
+            self.ensure_year(&education_year_id, school_id).await?;
+        }
+
+        rule.id = None;
+        rule.school_id = Some(Self::parse_oid(school_id, "school_id")?);
+        rule.created_by = created_by
+            .map(|id| Self::parse_oid(id, "created_by"))
+            .transpose()?;
+
+        self.repo.insert_rule(&rule).await
+    }
+
+    pub async fn get_rules(
+        &self,
+        school_id: &str,
+        education_year_id: Option<&str>,
+    ) -> Result<Vec<PromotionRule>, AppError> {
+        self.repo.list_rules(school_id, education_year_id).await
+    }
+
+    pub async fn find_rule(&self, id: &IdType, school_id: &str) -> Result<PromotionRule, AppError> {
+        let rule = self.repo.find_rule(id).await?;
+        Self::ensure_school(rule.school_id, school_id)?;
+        Ok(rule)
+    }
+
+    pub async fn update_rule(
+        &self,
+        id: &IdType,
+        school_id: &str,
+        update: PromotionRulePartial,
+    ) -> Result<PromotionRule, AppError> {
+        let existing = self.find_rule(id, school_id).await?;
+        let mut rule = existing.clone();
+        rule.merge(update);
+        rule.id = existing.id;
+        rule.school_id = existing.school_id;
+        rule.created_by = existing.created_by;
+
+        rule.validate().map_err(AppError::validation)?;
+        if let Some(education_year_id) = rule.education_year_id {
+            self.ensure_year(&education_year_id, school_id).await?;
+        }
+
+        self.repo.update_rule(&rule).await
+    }
+
+    pub async fn delete_rule(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<PromotionRule, AppError> {
+        let rule = self.find_rule(id, school_id).await?;
+        self.repo.soft_delete_rule(id).await?;
+        Ok(rule)
+    }
+
+    // ========== PROPOSALS ==========
+
+    fn evaluate(
+        rule: &PromotionRule,
+        gpa: Option<f64>,
+        attendance: Option<f64>,
+        subjects: &[SubjectAverage],
+    ) -> Evaluation {
+        let Some(gpa) = gpa else {
+            return Evaluation {
+                status: PromotionStatus::Pending,
+                reason: "No term results for the education year".into(),
+                failed_subjects: Vec::new(),
+            };
+        };
+
+        let mut reasons = Vec::new();
+        if gpa < rule.min_gpa_threshold {
+            reasons.push(format!(
+                "GPA {:.2} is below {:.2}",
+                gpa, rule.min_gpa_threshold
+            ));
+        }
+
+        if let (Some(required), Some(attendance)) = (rule.min_attendance_percentage, attendance) {
+            if attendance < required {
+                reasons.push(format!(
+                    "Attendance {:.1}% is below {:.1}%",
+                    attendance, required
+                ));
+            }
+        }
+
+        let pass_mark = rule.subject_pass_percentage();
+        let mut failed_subjects = Vec::new();
+        for subject_id in &rule.required_subjects_passed {
+            let taken = subjects.iter().find(|subject| {
+                subject.class_subject_id.as_deref() == Some(subject_id.as_str())
+                    || subject.main_subject_id.as_deref() == Some(subject_id.as_str())
+            });
+            match taken {
+                Some(subject) if subject.percentage >= pass_mark => {}
+                Some(subject) => failed_subjects.push(subject.subject_name.clone()),
+                None => failed_subjects.push(subject_id.clone()),
+            }
+        }
+        if !failed_subjects.is_empty() {
+            reasons.push(format!(
+                "Required subjects not passed: {}",
+                failed_subjects.join(", ")
+            ));
+        }
+
+        if reasons.is_empty() {
+            Evaluation {
+                status: PromotionStatus::Promoted,
+                reason: "Meets the promotion rule".into(),
+                failed_subjects,
+            }
+        } else {
+            Evaluation {
+                status: PromotionStatus::Repeated,
+                reason: reasons.join("; "),
+                failed_subjects,
+            }
+        }
+    }
+
+    /// Evaluate every active student (or one class) against the school's rule
+    /// and store the outcome as a proposal to review. Nothing moves until the
+    /// proposal is approved.
+    pub async fn propose(
+        &self,
+        school_id: &str,
+        request: CreatePromotionProposal,
+        created_by: Option<&str>,
+    ) -> Result<PromotionBatch, AppError> {
+        if request.education_year_id == request.to_education_year_id {
+            return Err(AppError::bad_request(
+                "to_education_year_id must differ from education_year_id",
+            ));
+        }
+        self.ensure_year(&request.education_year_id, school_id)
+            .await?;
+        self.ensure_year(&request.to_education_year_id, school_id)
+            .await?;
+        if let Some(class_id) = request.from_class_id {
+            self.ensure_class(&class_id, school_id).await?;
+        }
+        if let Some(class_id) = request.to_class_id {
+            if request.from_class_id.is_none() {
+                return Err(AppError::bad_request("to_class_id requires from_class_id"));
+            }
+            self.ensure_class(&class_id, school_id).await?;
+        }
+
+        let year_id = request.education_year_id.to_hex();
+        let rule = match request.rule_id {
+            Some(rule_id) => {
+                self.find_rule(&IdType::ObjectId(rule_id), school_id)
+                    .await?
+            }
+            None => self
+                .repo
+                .applicable_rule(school_id, &year_id)
+                .await?
+                .ok_or(AppError::bad_request(
+                    "No promotion rule is configured for this school",
+                ))?,
+        };
+
+        let from_class = request.from_class_id.map(|id| id.to_hex());
+        let candidates = self
+            .repo
+            .candidates(school_id, from_class.as_deref())
+            .await?;
+        if candidates.is_empty() {
+            return Err(AppError::bad_request("No active students to evaluate"));
+        }
+
+        let gpas = self.repo.year_gpas(school_id, &year_id).await?;
+        let subjects = self.repo.year_subject_averages(school_id, &year_id).await?;
+        let (starts_on, ends_on) = self.repo.year_bounds(&year_id).await?;
+        let attendance = self
+            .repo
+            .attendance_rates(school_id, starts_on, ends_on)
+            .await?;
+        let levels = LevelMap {
+            classes: self
+                .repo
+                .school_classes(school_id)
+                .await?
+                .into_iter()
+                .map(|class| (class.id.clone(), class))
+                .collect(),
+            levels: self.repo.main_class_levels().await?,
+        };
+
+        let mut placements: HashMap<String, NextPlacement> = HashMap::new();
+        let mut results = Vec::with_capacity(candidates.len());
+        for PromotionCandidate {
+            enrollment_id,
+            profile_id,
+            name,
+            class_id,
+        } in candidates
+        {
+            let evaluation = Self::evaluate(
+                &rule,
+                gpas.get(&profile_id).copied(),
+                attendance.get(&profile_id).copied(),
+                subjects
+                    .get(&profile_id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+            );
+
+            let mut status = evaluation.status;
+            let mut reason = evaluation.reason;
+            let mut promoted_to = None;
+            match status {
+                PromotionStatus::Promoted => {
+                    let placement = match request.to_class_id {
+                        Some(to_class_id) => NextPlacement::Class(to_class_id.to_hex()),
+                        None => placements
+                            .entry(class_id.clone())
+                            .or_insert_with(|| levels.next_placement(&class_id))
+                            .clone(),
+                    };
+                    match placement {
+                        NextPlacement::Class(next_class_id) => {
+                            promoted_to = Some(Self::parse_oid(&next_class_id, "class_id")?);
+                        }
+                        NextPlacement::Graduate => {
+                            status = PromotionStatus::Graduated;
+                            reason = "Meets the promotion rule in the final level".into();
+                        }
+                        NextPlacement::Unknown(problem) => {
+                            status = PromotionStatus::Pending;
+                            reason = format!("Meets the promotion rule; {}", problem);
+                        }
+                    }
+                }
+                PromotionStatus::Repeated => {
+                    promoted_to = Some(Self::parse_oid(&class_id, "class_id")?);
+                }
+                _ => {}
+            }
+
+            results.push(PromotionResult {
+                student_id: Some(Self::parse_oid(&enrollment_id, "student_id")?),
+                student_name: name,
+                from_class_id: Some(Self::parse_oid(&class_id, "class_id")?),
+                current_gpa: gpas.get(&profile_id).copied().unwrap_or(0.0),
+                attendance_percentage: attendance.get(&profile_id).copied(),
+                failed_subjects: evaluation.failed_subjects,
+                promotion_status: status,
+                reason,
+                promoted_to_class_id: promoted_to,
+                is_overridden: false,
+            });
+        }
+
+        let batch = PromotionBatch {
+            id: None,
+            school_id: Some(Self::parse_oid(school_id, "school_id")?),
+            education_year_id: Some(request.education_year_id),
+            to_education_year_id: Some(request.to_education_year_id),
+            rule_id: rule.id,
+            from_class_id: request.from_class_id,
+            to_class_id: request.to_class_id,
+            status: PromotionBatchStatus::Proposed,
+            promotion_results: results,
+            created_by: created_by
+                .map(|id| Self::parse_oid(id, "created_by"))
+                .transpose()?,
+            executed_by: None,
+            executed_at: None,
+            created_at: None,
+            updated_at: None,
+        };
+        self.repo.insert_batch(&batch).await
+    }
+
+    pub async fn get_batches(
+        &self,
+        school_id: &str,
+        query: &PromotionBatchQuery,
+    ) -> Result<Vec<PromotionBatch>, AppError> {
+        self.repo.list_batches(school_id, query).await
+    }
+
+    pub async fn find_batch(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<PromotionBatch, AppError> {
+        let batch = self.repo.find_batch(id).await?;
+        Self::ensure_school(batch.school_id, school_id)?;
+        Ok(batch)
+    }
+
+    async fn find_open_batch(
+        &self,
+        id: &IdType,
+        school_id: &str,
+    ) -> Result<PromotionBatch, AppError> {
+        let batch = self.find_batch(id, school_id).await?;
+        if batch.status != PromotionBatchStatus::Proposed {
+            return Err(AppError::conflict("Promotion proposal is no longer open"));
+        }
+        Ok(batch)
+    }
+
+    /// Override the engine's decision for one student.
+    pub async fn decide(
+        &self,
+        id: &IdType,
+        school_id: &str,
+        student_id: &str,
+        decision: PromotionDecision,
+    ) -> Result<PromotionBatch, AppError> {
+        let batch = self.find_open_batch(id, school_id).await?;
+        let student_oid = Self::parse_oid(student_id, "student_id")?;
+        let Some(current) = batch
+            .promotion_results
+            .iter()
+            .find(|result| result.student_id == Some(student_oid))
+        else {
+            return Err(AppError::bad_request(
+                "Student is not part of this proposal",
+            ));
+        };
+
+        let promoted_to = match decision.promotion_status {
+            PromotionStatus::Promoted => {
+                let class_id = decision
+                    .promoted_to_class_id
+                    .or(current.promoted_to_class_id)
+                    .filter(|class_id| Some(*class_id) != current.from_class_id)
+                    .ok_or(AppError::bad_request(
+                        "promoted_to_class_id is required to promote this student",
+                    ))?;
+                self.ensure_class(&class_id, school_id).await?;
+                Some(class_id)
+            }
+            PromotionStatus::Repeated => current.from_class_id,
+            PromotionStatus::Graduated | PromotionStatus::Pending => None,
+        };
+
+        let reason = decision
+            .reason
+            .filter(|reason| !reason.trim().is_empty())
+            .unwrap_or_else(|| "Set by reviewer".into());
+        let batch_id = batch.id.map(|id| id.to_hex()).unwrap_or_default();
+        self.repo
+            .update_result(
+                &batch_id,
+                student_id,
+                decision.promotion_status,
+                promoted_to.map(|id| id.to_hex()).as_deref(),
+                &reason,
+            )
+            .await?;
+
+        self.repo.find_batch(id).await
+    }
+
+    /// Approve the proposal and move the students. Every student needs a
+    /// decision first.
+    pub async fn approve(
+        &self,
+        id: &IdType,
+        school_id: &str,
+        approved_by: Option<&str>,
+    ) -> Result<(PromotionBatch, u64), AppError> {
+        let batch = self.find_open_batch(id, school_id).await?;
+        let pending = batch.summary().pending;
+        if pending > 0 {
+            return Err(AppError::bad_request(format!(
+                "{} students still need a decision",
+                pending
+            )));
+        }
+
+        let moved = self.repo.apply_batch(&batch, approved_by).await?;
+        Ok((self.repo.find_batch(id).await?, moved))
+    }
+
+    pub async fn cancel(&self, id: &IdType, school_id: &str) -> Result<PromotionBatch, AppError> {
+        let batch = self.find_open_batch(id, school_id).await?;
+        let batch_id = batch.id.map(|id| id.to_hex()).unwrap_or_default();
+        if !self.repo.cancel_batch(&batch_id).await? {
+            return Err(AppError::conflict("Promotion proposal is no longer open"));
+        }
+        self.repo.find_batch(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule() -> PromotionRule {
+        PromotionRule {
+            id: None,
+            school_id: None,
+            education_year_id: None,
+            name: "Default".to_string(),
+            min_gpa_threshold: 2.0,
+            min_attendance_percentage: Some(75.0),
+            required_subjects_passed: vec!["maths".to_string()],
+            custom_rules: None,
+            created_by: None,
+            created_at: None,
+            updated_at: None,
+            is_deleted: false,
+        }
+    }
+
+    fn maths(percentage: f64) -> SubjectAverage {
+        SubjectAverage {
+            class_subject_id: Some("cs-maths".to_string()),
+            main_subject_id: Some("maths".to_string()),
+            subject_name: "Mathematics".to_string(),
+            percentage,
+        }
+    }
+
+    fn class(id: &str, name: &str, parent: Option<&str>, main: Option<&str>) -> ClassPlacement {
+        ClassPlacement {
+            id: id.to_string(),
+            name: name.to_string(),
+            parent_class_id: parent.map(str::to_string),
+            main_class_id: main.map(str::to_string),
+        }
+    }
+
+    fn level(id: &str, trade: &str, level: i32) -> MainClassLevel {
+        MainClassLevel {
+            id: id.to_string(),
+            trade_id: Some(trade.to_string()),
+            level: Some(level),
+        }
+    }
+
+    /// Two levels of one trade, each with streams A and B as subclasses
+    fn levels() -> LevelMap {
+        let classes = [
+            class("s1", "S1", None, Some("level-1")),
+            class("s1a", "S1 A", Some("s1"), None),
+            class("s1b", "S1 B", Some("s1"), None),
+            class("s2", "S2", None, Some("level-2")),
+            class("s2a", "S2 A", Some("s2"), None),
+            class("s2b", "S2 B", Some("s2"), None),
+        ];
+        LevelMap {
+            classes: classes
+                .into_iter()
+                .map(|class| (class.id.clone(), class))
+                .collect(),
+            levels: vec![
+                level("level-1", "general", 1),
+                level("level-2", "general", 2),
+            ],
+        }
+    }
+
+    #[test]
+    fn student_meeting_every_threshold_is_promoted() {
+        let evaluation = PromotionService::evaluate(&rule(), Some(2.0), Some(75.0), &[maths(50.0)]);
+        assert_eq!(evaluation.status, PromotionStatus::Promoted);
+        assert!(evaluation.failed_subjects.is_empty());
+    }
+
+    #[test]
+    fn every_failed_threshold_is_given_as_a_reason() {
+        let evaluation = PromotionService::evaluate(&rule(), Some(1.5), Some(60.0), &[maths(49.0)]);
+        assert_eq!(evaluation.status, PromotionStatus::Repeated);
+        assert_eq!(evaluation.failed_subjects, vec!["Mathematics".to_string()]);
+        assert_eq!(
+            evaluation.reason,
+            "GPA 1.50 is below 2.00; Attendance 60.0% is below 75.0%; \
+             Required subjects not passed: Mathematics"
+        );
+    }
+
+    #[test]
+    fn missing_results_leave_the_decision_to_a_reviewer() {
+        let evaluation = PromotionService::evaluate(&rule(), None, Some(100.0), &[]);
+        assert_eq!(evaluation.status, PromotionStatus::Pending);
+
+        // A required subject that was never taken counts as not passed
+        let evaluation = PromotionService::evaluate(&rule(), Some(3.0), None, &[]);
+        assert_eq!(evaluation.status, PromotionStatus::Repeated);
+        assert_eq!(evaluation.failed_subjects, vec!["maths".to_string()]);
+    }
+
+    #[test]
+    fn custom_subject_pass_mark_is_used() {
+        let mut strict = rule();
+        strict.custom_rules = Some(serde_json::json!({ "subject_pass_percentage": 60.0 }));
+        let evaluation = PromotionService::evaluate(&strict, Some(3.0), None, &[maths(55.0)]);
+        assert_eq!(evaluation.status, PromotionStatus::Repeated);
+    }
+
+    #[test]
+    fn next_class_keeps_the_stream_and_top_level_graduates() {
+        let map = levels();
+        assert_eq!(
+            map.next_placement("s1b"),
+            NextPlacement::Class("s2b".to_string())
+        );
+        assert_eq!(
+            map.next_placement("s1"),
+            NextPlacement::Class("s2".to_string())
+        );
+        assert_eq!(map.next_placement("s2a"), NextPlacement::Graduate);
+        assert!(matches!(
+            map.next_placement("missing"),
+            NextPlacement::Unknown(_)
+        ));
+    }
+}
@@ -2,7 +2,9 @@ use chrono::{DateTime, Utc};
 use sqlx::{postgres::PgRow, PgPool, Row};
 
 use crate::{
-    domain::student_term_result::StudentTermResult, errors::AppError, utils::object_id::ObjectId,
+    domain::student_term_result::{StudentResultDelta, StudentTermResult},
+    errors::AppError,
+    utils::object_id::ObjectId,
 };
 
 pub struct RankingService {
@@ -15,14 +17,12 @@ impl RankingService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -204,6 +204,106 @@ impl RankingService {
         rows.into_iter().map(Self::result_from_row).collect()
     }
 
+    /// Each student's average on the exam against the exam given as
+    /// `compare_exam_id`, or else their latest earlier exam, biggest drop
+    /// first. Drops larger than `drop_threshold` percentage points are
+    /// flagged. With no class ids the whole exam is covered.
+    pub async fn get_result_deltas(
+        &self,
+        exam_id: &ObjectId,
+        class_ids: &[String],
+        compare_exam_id: Option<&ObjectId>,
+        drop_threshold: f64,
+    ) -> Result<Vec<StudentResultDelta>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT cur.student_id, sp.name AS student_name, cur.class_id,
+                   prev.exam_id AS previous_exam_id,
+                   prev.percentage AS previous_percentage,
+                   COALESCE(cur.average_percentage, cur.average_score, 0)::DOUBLE PRECISION
+                     AS current_percentage
+            FROM student_term_results cur
+            JOIN exams e ON e.id = cur.exam_id
+            LEFT JOIN student_profiles sp ON sp.id = cur.student_id
+            JOIN LATERAL (
+              SELECT p.exam_id,
+                     COALESCE(p.average_percentage, p.average_score, 0)::DOUBLE PRECISION AS percentage
+              FROM student_term_results p
+              JOIN exams pe ON pe.id = p.exam_id AND pe.deleted_at IS NULL
+              WHERE p.student_id = cur.student_id
+                AND p.exam_id <> cur.exam_id
+                AND p.deleted_at IS NULL
+                AND (
+                  ($3::TEXT IS NULL
+                    AND COALESCE(pe.starts_at, pe.created_at) < COALESCE(e.starts_at, e.created_at))
+                  OR p.exam_id = $3
+                )
+              ORDER BY COALESCE(pe.starts_at, pe.created_at) DESC
+              LIMIT 1
+            ) prev ON true
+            WHERE cur.exam_id = $1
+              AND (cardinality($2::TEXT[]) = 0 OR cur.class_id = ANY($2))
+              AND cur.deleted_at IS NULL
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .bind(class_ids)
+        .bind(compare_exam_id.map(|id| id.to_hex()))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut deltas = rows
+            .into_iter()
+            .map(|row| {
+                let student_id: String = row.try_get("student_id").map_err(Self::db_error)?;
+                let previous_exam_id: String =
+                    row.try_get("previous_exam_id").map_err(Self::db_error)?;
+                let previous: f64 = row.try_get("previous_percentage").map_err(Self::db_error)?;
+                let current: f64 = row.try_get("current_percentage").map_err(Self::db_error)?;
+                let delta = ((current - previous) * 100.0).round() / 100.0;
+                Ok(StudentResultDelta {
+                    student_id: Self::parse_oid(&student_id, "student_id")?,
+                    student_name: row.try_get("student_name").ok().flatten(),
+                    class_id: Self::parse_oid_opt(
+                        row.try_get("class_id").ok().flatten(),
+                        "class_id",
+                    )?,
+                    previous_exam_id: Self::parse_oid(&previous_exam_id, "previous_exam_id")?,
+                    previous_percentage: previous,
+                    current_percentage: current,
+                    delta,
+                    flagged: -delta > drop_threshold,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        deltas.sort_by(|a, b| a.delta.total_cmp(&b.delta));
+        Ok(deltas)
+    }
+
+    /// Students of the class whose average dropped by more than
+    /// `drop_threshold` points since their previous exam; see
+    /// [`Self::get_result_deltas`]
+    pub async fn get_declining_students(
+        &self,
+        class_id: &ObjectId,
+        exam_id: &ObjectId,
+        compare_exam_id: Option<&ObjectId>,
+        drop_threshold: f64,
+    ) -> Result<Vec<StudentResultDelta>, AppError> {
+        Ok(self
+            .get_result_deltas(
+                exam_id,
+                &[class_id.to_hex()],
+                compare_exam_id,
+                drop_threshold,
+            )
+            .await?
+            .into_iter()
+            .filter(|delta| delta.flagged)
+            .collect())
+    }
+
     async fn fetch_rankings(
         &self,
         class_id: &ObjectId,
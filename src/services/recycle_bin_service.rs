@@ -42,7 +42,7 @@ impl RecycleBinService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     pub async fn get_deleted_entities(
@@ -127,7 +127,9 @@ impl RecycleBinService {
         user: &AuthUserDto,
         state: &crate::config::state::AppState,
     ) -> Result<(), AppError> {
-        let table = Self::resolve_table(entity_type).ok_or_else(|| AppError { message: format!("Unknown entity type: {}", entity_type) })?;
+        let table = Self::resolve_table(entity_type).ok_or_else(|| {
+            AppError::bad_request(format!("Unknown entity type: {}", entity_type))
+        })?;
 
         let deleted_at: Option<DateTime<Utc>> = sqlx::query_scalar(
             &format!("SELECT deleted_at FROM {} WHERE id = $1", table)
@@ -139,7 +141,7 @@ impl RecycleBinService {
         .flatten();
 
         if deleted_at.is_none() {
-            return Err(AppError { message: "Entity is not deleted or not found".to_string() });
+            return Err(AppError::not_found("Entity is not deleted or not found"));
         }
 
         sqlx::query(&format!("UPDATE {} SET deleted_at = NULL, updated_at = now() WHERE id = $1", table))
@@ -163,7 +165,9 @@ impl RecycleBinService {
         user: &AuthUserDto,
         state: &crate::config::state::AppState,
     ) -> Result<(), AppError> {
-        let table = Self::resolve_table(entity_type).ok_or_else(|| AppError { message: format!("Unknown entity type: {}", entity_type) })?;
+        let table = Self::resolve_table(entity_type).ok_or_else(|| {
+            AppError::bad_request(format!("Unknown entity type: {}", entity_type))
+        })?;
 
         let deleted_at: Option<DateTime<Utc>> = sqlx::query_scalar(
             &format!("SELECT deleted_at FROM {} WHERE id = $1", table)
@@ -175,7 +179,7 @@ impl RecycleBinService {
         .flatten();
 
         if deleted_at.is_none() {
-            return Err(AppError { message: "Entity is not deleted or not found".to_string() });
+            return Err(AppError::not_found("Entity is not deleted or not found"));
         }
 
         sqlx::query(&format!("DELETE FROM {} WHERE id = $1", table))
@@ -0,0 +1,468 @@
+use std::time::Duration;
+
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+use crate::{
+    domain::{
+        auth_user::AuthUserDto,
+        grading_scale::GradingScale,
+        report_card::{ReportCard, ReportCardComment, UpsertReportCardComment},
+        role::PermissionTarget,
+        student_term_result::StudentTermResult,
+    },
+    errors::AppError,
+    models::id_model::IdType,
+    services::{
+        cloudinary_service::CloudinaryService, gpa_calculation_service::GpaCalculationService,
+        grading_scale_service::GradingScaleService, permission_service::PermissionService,
+        school_service::SchoolService,
+    },
+    utils::object_id::ObjectId,
+};
+
+/// Logos larger than this are left off the card
+const MAX_LOGO_BYTES: usize = 2 * 1024 * 1024;
+const LOGO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What every card of one exam shares
+struct ExamHeader {
+    school_name: String,
+    exam_name: Option<String>,
+    year_label: Option<String>,
+    grading_scale: Option<GradingScale>,
+}
+
+pub struct ReportCardService {
+    pub pool: PgPool,
+}
+
+impl ReportCardService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn row_to_comment(row: PgRow) -> Result<ReportCardComment, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let student_id: String = row.try_get("student_id").map_err(Self::db_error)?;
+        let exam_id: String = row.try_get("exam_id").map_err(Self::db_error)?;
+        let class_subject_id: Option<String> =
+            row.try_get("class_subject_id").map_err(Self::db_error)?;
+        Ok(ReportCardComment {
+            id: Some(Self::parse_oid(&id, "id")?),
+            student_id: Self::parse_oid(&student_id, "student_id")?,
+            exam_id: Self::parse_oid(&exam_id, "exam_id")?,
+            class_subject_id: class_subject_id
+                .map(|id| Self::parse_oid(&id, "class_subject_id"))
+                .transpose()?,
+            body: row.try_get("body").map_err(Self::db_error)?,
+            author_name: row.try_get("author_name").map_err(Self::db_error)?,
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    /// Whether the caller holds `permission` for the student in a school
+    /// they are enrolled in. A class-scoped grant covers the student's class.
+    pub async fn has_student_permission(
+        &self,
+        user: &AuthUserDto,
+        student_id: &ObjectId,
+        permission: &str,
+    ) -> Result<bool, AppError> {
+        let enrollments: Vec<(String, Option<String>)> = sqlx::query_as(
+            r#"SELECT school_id, class_id FROM student_school_enrollments
+               WHERE (id = $1 OR student_id = $1) AND deleted_at IS NULL"#,
+        )
+        .bind(student_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let permission_service = PermissionService::new(&self.pool);
+        for (school_id, class_id) in enrollments {
+            let mut target = PermissionTarget::school(school_id);
+            if let Some(class_id) = class_id {
+                target = target.class(class_id);
+            }
+            if permission_service
+                .check(user, permission, Some(&target))
+                .await
+                .is_ok()
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The student themself, or a linked parent
+    pub async fn is_self_or_parent(
+        &self,
+        user: &AuthUserDto,
+        student_id: &ObjectId,
+    ) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"SELECT EXISTS (
+                 SELECT 1 FROM student_profiles WHERE id = $1 AND user_id = $2
+               ) OR EXISTS (
+                 SELECT 1 FROM parent_student_links
+                 WHERE student_id = $1 AND parent_user_id = $2 AND status = 'active'
+               )"#,
+        )
+        .bind(student_id.to_hex())
+        .bind(&user.id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// The exam has to be the school's and the student enrolled there, in
+    /// the class of the subject when there is one. Members holding
+    /// `result.manage` may then comment on anything. Teachers comment on the
+    /// subjects they teach, and give the general remark for classes they are
+    /// assigned to.
+    async fn ensure_can_comment(
+        &self,
+        user: &AuthUserDto,
+        school_id: &ObjectId,
+        student_id: &ObjectId,
+        exam_id: &ObjectId,
+        class_subject_id: Option<&ObjectId>,
+    ) -> Result<(), AppError> {
+        let in_school: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (
+                 SELECT 1 FROM exams
+                 WHERE id = $2 AND school_id = $1 AND deleted_at IS NULL
+               ) AND EXISTS (
+                 SELECT 1 FROM student_school_enrollments sse
+                 LEFT JOIN class_subjects cs ON cs.id = $4 AND cs.school_id = $1
+                 WHERE sse.student_id = $3 AND sse.school_id = $1
+                   AND sse.is_active = true AND sse.deleted_at IS NULL
+                   AND ($4::TEXT IS NULL OR cs.class_id IN (sse.class_id, sse.subclass_id))
+               )"#,
+        )
+        .bind(school_id.to_hex())
+        .bind(exam_id.to_hex())
+        .bind(student_id.to_hex())
+        .bind(class_subject_id.map(|id| id.to_hex()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if !in_school {
+            return Err(AppError::not_found(
+                "No such exam, or the student is not enrolled in this class, in this school",
+            ));
+        }
+
+        let target = PermissionTarget::school(school_id.to_hex());
+        if PermissionService::new(&self.pool)
+            .check(user, "result.manage", Some(&target))
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+
+        let allowed: bool = match class_subject_id {
+            Some(class_subject_id) => sqlx::query_scalar(
+                r#"SELECT EXISTS (
+                     SELECT 1 FROM class_subjects cs
+                     JOIN teachers t ON t.id = cs.teacher_id
+                     WHERE cs.id = $1 AND cs.school_id = $2 AND t.user_id = $3
+                   ) OR EXISTS (
+                     SELECT 1 FROM teacher_subjects ts
+                     JOIN teachers t ON t.id = ts.teacher_id
+                     WHERE ts.class_subject_id = $1 AND ts.school_id = $2 AND t.user_id = $3
+                   )"#,
+            )
+            .bind(class_subject_id.to_hex())
+            .bind(school_id.to_hex())
+            .bind(&user.id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)?,
+            None => sqlx::query_scalar(
+                r#"SELECT EXISTS (
+                     SELECT 1 FROM teacher_classes tc
+                     JOIN teachers t ON t.id = tc.teacher_id
+                     JOIN student_school_enrollments sse
+                       ON sse.class_id = tc.class_id AND sse.is_active = true AND sse.deleted_at IS NULL
+                     WHERE sse.student_id = $1 AND tc.school_id = $2 AND t.user_id = $3
+                   )"#,
+            )
+            .bind(student_id.to_hex())
+            .bind(school_id.to_hex())
+            .bind(&user.id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Self::db_error)?,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(AppError::forbidden(
+                "Only the subject or class teacher can comment here",
+            ))
+        }
+    }
+
+    pub async fn upsert_comment(
+        &self,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        dto: UpsertReportCardComment,
+    ) -> Result<ReportCardComment, AppError> {
+        dto.validate().map_err(AppError::validation)?;
+        let student_id = Self::parse_oid(&dto.student_id, "student_id")?;
+        let exam_id = Self::parse_oid(&dto.exam_id, "exam_id")?;
+        let class_subject_id = dto
+            .class_subject_id
+            .as_deref()
+            .map(|id| Self::parse_oid(id, "class_subject_id"))
+            .transpose()?;
+        self.ensure_can_comment(
+            user,
+            school_id,
+            &student_id,
+            &exam_id,
+            class_subject_id.as_ref(),
+        )
+        .await?;
+
+        let id: String = sqlx::query_scalar(
+            r#"INSERT INTO report_card_comments (
+                 id, school_id, student_id, exam_id, class_subject_id, body, author_user_id
+               ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+               ON CONFLICT (school_id, student_id, exam_id, coalesce(class_subject_id, ''))
+               DO UPDATE SET body = EXCLUDED.body, author_user_id = EXCLUDED.author_user_id
+               RETURNING id"#,
+        )
+        .bind(Self::new_id())
+        .bind(school_id.to_hex())
+        .bind(student_id.to_hex())
+        .bind(exam_id.to_hex())
+        .bind(class_subject_id.map(|id| id.to_hex()))
+        .bind(dto.body.trim())
+        .bind(&user.id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        self.comments(&student_id, &exam_id)
+            .await?
+            .into_iter()
+            .find(|comment| comment.id.map(|oid| oid.to_hex()) == Some(id.clone()))
+            .ok_or_else(|| AppError::not_found("Report card comment not found"))
+    }
+
+    pub async fn comments(
+        &self,
+        student_id: &ObjectId,
+        exam_id: &ObjectId,
+    ) -> Result<Vec<ReportCardComment>, AppError> {
+        let rows = sqlx::query(
+            r#"SELECT c.id, c.student_id, c.exam_id, c.class_subject_id, c.body, c.updated_at,
+                      u.name AS author_name
+               FROM report_card_comments c
+               LEFT JOIN users u ON u.id = c.author_user_id
+               WHERE c.student_id = $1 AND c.exam_id = $2
+               ORDER BY c.class_subject_id NULLS LAST, c.updated_at"#,
+        )
+        .bind(student_id.to_hex())
+        .bind(exam_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_comment).collect()
+    }
+
+    /// The school's logo URL, for [`Self::fetch_logo`]
+    pub async fn school_logo(&self, school_id: &ObjectId) -> Option<String> {
+        SchoolService::new(&self.pool)
+            .find_one(Some(&IdType::from_string(school_id.to_hex())), None)
+            .await
+            .ok()
+            .and_then(|school| school.logo)
+    }
+
+    /// Downloads the logo once per request; a missing or slow logo only
+    /// leaves the card without one. Only logos in our upload storage are
+    /// fetched, without redirects, and reading stops past the size cap.
+    pub async fn fetch_logo(url: &str) -> Option<Vec<u8>> {
+        if !CloudinaryService::is_own_asset_url(url) {
+            return None;
+        }
+        let client = reqwest::Client::builder()
+            .timeout(LOGO_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .ok()?;
+        let mut response = client.get(url).send().await.ok()?.error_for_status().ok()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > MAX_LOGO_BYTES as u64)
+        {
+            return None;
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.ok()? {
+            if bytes.len() + chunk.len() > MAX_LOGO_BYTES {
+                return None;
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Some(bytes)
+    }
+
+    async fn exam_header(
+        &self,
+        school_id: &ObjectId,
+        exam_id: &ObjectId,
+        result: Option<&StudentTermResult>,
+    ) -> Result<ExamHeader, AppError> {
+        let school = SchoolService::new(&self.pool)
+            .find_one(Some(&IdType::from_string(school_id.to_hex())), None)
+            .await?;
+        let exam_name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM exams WHERE id = $1 AND school_id = $2")
+                .bind(exam_id.to_hex())
+                .bind(school_id.to_hex())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::db_error)?;
+
+        let year_id = result.and_then(|result| result.education_year_id);
+        let (year_label, grading_scale) = match year_id {
+            Some(year_id) => {
+                let label: Option<String> =
+                    sqlx::query_scalar("SELECT label FROM education_years WHERE id = $1")
+                        .bind(year_id.to_hex())
+                        .fetch_optional(&self.pool)
+                        .await
+                        .map_err(Self::db_error)?;
+                let scale = GradingScaleService::new(&self.pool)
+                    .get_active_scale(school_id, &year_id)
+                    .await
+                    .ok()
+                    .flatten();
+                (label, scale)
+            }
+            None => (None, None),
+        };
+
+        Ok(ExamHeader {
+            school_name: school.name,
+            exam_name,
+            year_label,
+            grading_scale,
+        })
+    }
+
+    async fn build_card(
+        &self,
+        header: &ExamHeader,
+        result: StudentTermResult,
+        exam_id: &ObjectId,
+    ) -> Result<ReportCard, AppError> {
+        let student_id = result
+            .student_id
+            .ok_or_else(|| AppError::bad_request("Result has no student"))?;
+        let student_name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM student_profiles WHERE id = $1")
+                .bind(student_id.to_hex())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::db_error)?;
+        let class_name: Option<String> = match result.class_id {
+            Some(class_id) => sqlx::query_scalar("SELECT name FROM classes WHERE id = $1")
+                .bind(class_id.to_hex())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::db_error)?,
+            None => None,
+        };
+
+        Ok(ReportCard {
+            school_name: header.school_name.clone(),
+            student_name: student_name.unwrap_or_else(|| "Student".to_string()),
+            class_name,
+            exam_name: header.exam_name.clone(),
+            year_label: header.year_label.clone(),
+            comments: self.comments(&student_id, exam_id).await?,
+            grading_scale: header.grading_scale.clone(),
+            result,
+        })
+    }
+
+    pub async fn student_card(
+        &self,
+        school_id: &ObjectId,
+        student_id: &ObjectId,
+        exam_id: &ObjectId,
+    ) -> Result<ReportCard, AppError> {
+        let result = GpaCalculationService::new(&self.pool)
+            .get_student_result(student_id, exam_id)
+            .await?
+            .filter(|result| result.school_id == Some(*school_id))
+            .ok_or_else(|| AppError::not_found("Result not found"))?;
+        let header = self.exam_header(school_id, exam_id, Some(&result)).await?;
+        self.build_card(&header, result, exam_id).await
+    }
+
+    /// Cards for every student of the class with a result for the exam,
+    /// best ranked first
+    pub async fn class_cards(
+        &self,
+        school_id: &ObjectId,
+        class_id: &ObjectId,
+        exam_id: &ObjectId,
+    ) -> Result<Vec<ReportCard>, AppError> {
+        let student_ids: Vec<String> = sqlx::query_scalar(
+            r#"SELECT DISTINCT ON (r.student_id) r.student_id
+               FROM student_term_results r
+               WHERE r.class_id = $1 AND r.exam_id = $2 AND r.school_id = $3 AND r.deleted_at IS NULL
+               ORDER BY r.student_id, r.updated_at DESC"#,
+        )
+        .bind(class_id.to_hex())
+        .bind(exam_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let results_service = GpaCalculationService::new(&self.pool);
+        let mut results = Vec::with_capacity(student_ids.len());
+        for student_id in student_ids {
+            let student_id = Self::parse_oid(&student_id, "student_id")?;
+            if let Some(result) = results_service
+                .get_student_result(&student_id, exam_id)
+                .await?
+            {
+                results.push(result);
+            }
+        }
+        results.sort_by_key(|result| result.rank_in_class.unwrap_or(i32::MAX));
+
+        let header = self
+            .exam_header(school_id, exam_id, results.first())
+            .await?;
+        let mut cards = Vec::with_capacity(results.len());
+        for result in results {
+            cards.push(self.build_card(&header, result, exam_id).await?);
+        }
+        Ok(cards)
+    }
+}
@@ -29,9 +29,9 @@ impl RoleQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -70,9 +70,7 @@ impl RoleService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -84,8 +82,8 @@ impl RoleService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -172,15 +170,16 @@ impl RoleService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported role filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported role filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -234,7 +233,9 @@ impl RoleService {
             .map_err(Self::db_error)?;
 
         for permission in permissions {
-            let permission_id = self.ensure_permission(permission, None).await?;
+            let permission_id = self
+                .ensure_permission(permission, None, PermissionScope::for_code(permission))
+                .await?;
             sqlx::query(
                 "INSERT INTO role_permissions (role_id, permission_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
             )
@@ -252,6 +253,7 @@ impl RoleService {
         &self,
         code: &str,
         description: Option<&str>,
+        scope: PermissionScope,
     ) -> Result<String, AppError> {
         let existing =
             sqlx::query_scalar::<_, String>("SELECT id FROM permissions WHERE code = $1")
@@ -265,13 +267,16 @@ impl RoleService {
         }
 
         let id = Self::new_id();
-        sqlx::query("INSERT INTO permissions (id, code, description) VALUES ($1, $2, $3)")
-            .bind(&id)
-            .bind(code)
-            .bind(description)
-            .execute(&self.pool)
-            .await
-            .map_err(Self::db_error)?;
+        sqlx::query(
+            "INSERT INTO permissions (id, code, description, scope) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&id)
+        .bind(code)
+        .bind(description)
+        .bind(scope.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
 
         Ok(id)
     }
@@ -330,8 +335,12 @@ impl RoleService {
 
     pub async fn seed_default_permissions(&self) -> Result<(), AppError> {
         for permission in Self::get_default_permissions() {
-            self.ensure_permission(&permission.name, permission.description.as_deref())
-                .await?;
+            self.ensure_permission(
+                &permission.name,
+                permission.description.as_deref(),
+                permission.scope,
+            )
+            .await?;
         }
         Ok(())
     }
@@ -341,9 +350,7 @@ impl RoleService {
         self.seed_default_permissions().await?;
 
         if dto.name.trim().is_empty() {
-            return Err(AppError {
-                message: "Role name cannot be empty".into(),
-            });
+            return Err(AppError::bad_request("Role name cannot be empty"));
         }
 
         let school_id = dto.school_id.as_ref().map(|id| id.to_hex());
@@ -352,9 +359,10 @@ impl RoleService {
             .await?
             .is_some()
         {
-            return Err(AppError {
-                message: format!("Role with name '{}' already exists", dto.name),
-            });
+            return Err(AppError::conflict(format!(
+                "Role with name '{}' already exists",
+                dto.name
+            )));
         }
 
         let id = dto
@@ -409,9 +417,7 @@ impl RoleService {
 
         match row {
             Some(row) => Ok(self.role_from_row(row).await?),
-            None => Err(AppError {
-                message: "Role not found".into(),
-            }),
+            None => Err(AppError::not_found("Role not found")),
         }
     }
 
@@ -474,16 +480,12 @@ impl RoleService {
     pub async fn update_role(&self, id: &IdType, update: &RolePartial) -> Result<Role, AppError> {
         let existing_role = self.find_one(Some(id), None).await?;
         if existing_role.role_type == RoleType::System {
-            return Err(AppError {
-                message: "Cannot modify system roles".into(),
-            });
+            return Err(AppError::bad_request("Cannot modify system roles"));
         }
 
         if let Some(ref name) = update.name {
             if name.trim().is_empty() {
-                return Err(AppError {
-                    message: "Role name cannot be empty".into(),
-                });
+                return Err(AppError::bad_request("Role name cannot be empty"));
             }
             if existing_role.name != *name
                 && self
@@ -499,9 +501,10 @@ impl RoleService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("Role with name '{}' already exists", name),
-                });
+                return Err(AppError::conflict(format!(
+                    "Role with name '{}' already exists",
+                    name
+                )));
             }
         }
 
@@ -548,9 +551,7 @@ impl RoleService {
         }
 
         if !touched && update.permissions.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         self.find_one(Some(id), None).await
@@ -559,9 +560,7 @@ impl RoleService {
     pub async fn delete_role(&self, id: &IdType) -> Result<Role, AppError> {
         let role = self.find_one(Some(id), None).await?;
         if role.role_type == RoleType::System {
-            return Err(AppError {
-                message: "Cannot delete system roles".into(),
-            });
+            return Err(AppError::bad_request("Cannot delete system roles"));
         }
 
         let id_string = Self::id_to_string(id)?;
@@ -573,12 +572,10 @@ impl RoleService {
                 .map_err(Self::db_error)?;
 
         if assignment_count > 0 {
-            return Err(AppError {
-                message: format!(
-                    "Cannot delete role. It is assigned to {} user(s)",
-                    assignment_count
-                ),
-            });
+            return Err(AppError::bad_request(format!(
+                "Cannot delete role. It is assigned to {} user(s)",
+                assignment_count
+            )));
         }
 
         sqlx::query("DELETE FROM roles WHERE id = $1")
@@ -598,9 +595,7 @@ impl RoleService {
     ) -> Result<UserRoleAssignment, AppError> {
         let role = self.find_one(Some(role_id), None).await?;
         if !role.is_active {
-            return Err(AppError {
-                message: "Cannot assign inactive role".into(),
-            });
+            return Err(AppError::bad_request("Cannot assign inactive role"));
         }
 
         let user_id_string = Self::id_to_string(user_id)?;
@@ -609,9 +604,9 @@ impl RoleService {
 
         if let Some(role_school_id) = role.school_id {
             if role_school_id.to_hex() != school_id_string {
-                return Err(AppError {
-                    message: "Cannot assign role from different school".into(),
-                });
+                return Err(AppError::bad_request(
+                    "Cannot assign role from different school",
+                ));
             }
         }
 
@@ -630,9 +625,7 @@ impl RoleService {
         .map_err(Self::db_error)?;
 
         if exists > 0 {
-            return Err(AppError {
-                message: "User already has this role assigned".into(),
-            });
+            return Err(AppError::conflict("User already has this role assigned"));
         }
 
         let id = Self::new_id();
@@ -662,39 +655,6 @@ impl RoleService {
         })
     }
 
-    pub async fn user_has_permission(
-        &self,
-        user_id: &IdType,
-        school_id: &IdType,
-        permission: &str,
-    ) -> Result<bool, AppError> {
-        let user_id = Self::id_to_string(user_id)?;
-        let school_id = Self::id_to_string(school_id)?;
-
-        let count: i64 = sqlx::query_scalar(
-            r#"
-            SELECT count(*)
-            FROM user_role_assignments ura
-            JOIN role_permissions rp ON rp.role_id = ura.role_id
-            JOIN permissions p ON p.id = rp.permission_id
-            JOIN roles r ON r.id = ura.role_id
-            WHERE ura.user_id = $1
-              AND ura.school_id = $2
-              AND ura.revoked_at IS NULL
-              AND r.is_active = true
-              AND p.code = $3
-            "#,
-        )
-        .bind(user_id)
-        .bind(school_id)
-        .bind(permission)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(Self::db_error)?;
-
-        Ok(count > 0)
-    }
-
     pub async fn get_all_with_relations(
         &self,
         filter: Option<String>,
@@ -834,6 +794,175 @@ impl RoleService {
                 description: Some("Toggle features".to_string()),
                 scope: PermissionScope::School,
             },
+            Permission {
+                name: "audit.view".to_string(),
+                description: Some("View the school's audit log".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "analytics.read.school".to_string(),
+                description: Some("Read school analytics".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "attendance.read.class".to_string(),
+                description: Some("Read attendance of classes they teach".to_string()),
+                scope: PermissionScope::Class,
+            },
+            Permission {
+                name: "attendance.record.class".to_string(),
+                description: Some("Take attendance for classes they teach".to_string()),
+                scope: PermissionScope::Class,
+            },
+            Permission {
+                name: "announcement.manage".to_string(),
+                description: Some("Publish and edit school announcements".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "attendance.read.school".to_string(),
+                description: Some("Read attendance across the school".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "backup.read".to_string(),
+                description: Some("List the school's backups".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "backup.manage".to_string(),
+                description: Some("Run, restore and configure backups".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "class.manage".to_string(),
+                description: Some("Create, update and delete classes".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "cover.manage".to_string(),
+                description: Some("Record teacher absences and assign covers".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "education_year.manage".to_string(),
+                description: Some("Manage education years and terms".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "exam.manage".to_string(),
+                description: Some("Create, update, delete and publish exams".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "finance.manage".to_string(),
+                description: Some(
+                    "Manage fees, invoices, payments, refunds and adjustments".to_string(),
+                ),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "finance.read".to_string(),
+                description: Some("Read fee structures, invoices and the ledger".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "import.manage".to_string(),
+                description: Some("Import students and teachers from spreadsheets".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "material.manage".to_string(),
+                description: Some("Create, update and delete learning materials".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "moderation.read".to_string(),
+                description: Some("Read the moderation status of exam marks".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "parent.manage".to_string(),
+                description: Some("Create, update and delete parents".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "parent.read".to_string(),
+                description: Some("Read the school's parents".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "promotion.manage".to_string(),
+                description: Some("Configure promotion rules and run promotions".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "recycle_bin.manage".to_string(),
+                description: Some("View the school's recycle bin".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "report_card.read".to_string(),
+                description: Some("Read and download report cards".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "result.configure".to_string(),
+                description: Some("Configure how term results are weighted".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "result.manage".to_string(),
+                description: Some(
+                    "Enter marks and report card comments for any subject".to_string(),
+                ),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "result.moderate".to_string(),
+                description: Some("Approve marks and reopen approved ones".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "result.publish".to_string(),
+                description: Some("Publish exam results to students and parents".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "result.read".to_string(),
+                description: Some("Read exam and annual results".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "school.security.manage".to_string(),
+                description: Some("Set the school's two-factor sign-in policy".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "schedule.read".to_string(),
+                description: Some("View dated teacher and class schedules".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "student.manage".to_string(),
+                description: Some("Create, update, delete and restore students".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "teacher.manage".to_string(),
+                description: Some("Create, update and delete teachers".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "timetable.manage".to_string(),
+                description: Some("Create, update and delete class timetables".to_string()),
+                scope: PermissionScope::School,
+            },
+            Permission {
+                name: "scores.review".to_string(),
+                description: Some("Review submitted marks".to_string()),
+                scope: PermissionScope::School,
+            },
         ]
     }
 }
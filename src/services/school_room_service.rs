@@ -0,0 +1,178 @@
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+
+use crate::{
+    domain::school_room::{SchoolRoom, SchoolRoomPartial},
+    errors::AppError,
+    utils::object_id::ObjectId,
+};
+
+pub struct SchoolRoomService {
+    pub pool: PgPool,
+}
+
+impl SchoolRoomService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn select_sql() -> &'static str {
+        "SELECT id, school_id, name, room_type, capacity, is_active, created_at, updated_at \
+         FROM school_rooms WHERE deleted_at IS NULL"
+    }
+
+    fn row_to_room(row: PgRow) -> Result<SchoolRoom, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let school_id: String = row.try_get("school_id").map_err(Self::db_error)?;
+        Ok(SchoolRoom {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Some(Self::parse_oid(&school_id, "school_id")?),
+            name: row.try_get("name").map_err(Self::db_error)?,
+            room_type: row.try_get("room_type").map_err(Self::db_error)?,
+            capacity: row.try_get("capacity").map_err(Self::db_error)?,
+            is_active: row.try_get("is_active").map_err(Self::db_error)?,
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    fn map_write_error(error: sqlx::Error) -> AppError {
+        match &error {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                AppError::conflict("A room with this name already exists")
+            }
+            _ => Self::db_error(error),
+        }
+    }
+
+    pub async fn get_all(
+        &self,
+        school_id: &ObjectId,
+        room_type: Option<String>,
+    ) -> Result<Vec<SchoolRoom>, AppError> {
+        let mut query = QueryBuilder::<Postgres>::new(Self::select_sql());
+        query
+            .push(" AND school_id = ")
+            .push_bind(school_id.to_hex());
+        if let Some(room_type) = room_type.filter(|value| !value.trim().is_empty()) {
+            query
+                .push(" AND lower(room_type) = ")
+                .push_bind(room_type.trim().to_lowercase());
+        }
+        query.push(" ORDER BY lower(name)");
+
+        let rows = query
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_room).collect()
+    }
+
+    pub async fn find_one(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<SchoolRoom, AppError> {
+        let row = sqlx::query(&format!(
+            "{} AND school_id = $1 AND id = $2",
+            Self::select_sql()
+        ))
+        .bind(school_id.to_hex())
+        .bind(id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_room(row),
+            None => Err(AppError::not_found("Room not found")),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        school_id: &ObjectId,
+        room: SchoolRoom,
+    ) -> Result<SchoolRoom, AppError> {
+        room.validate().map_err(AppError::validation)?;
+
+        let id = Self::new_id();
+        sqlx::query(
+            r#"INSERT INTO school_rooms (id, school_id, name, room_type, capacity, is_active)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
+        )
+        .bind(&id)
+        .bind(school_id.to_hex())
+        .bind(room.name.trim())
+        .bind(room.room_type.trim())
+        .bind(room.capacity)
+        .bind(room.is_active)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::map_write_error)?;
+
+        self.find_one(school_id, &Self::parse_oid(&id, "id")?).await
+    }
+
+    pub async fn update(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+        update: SchoolRoomPartial,
+    ) -> Result<SchoolRoom, AppError> {
+        let existing = self.find_one(school_id, id).await?;
+        let room = SchoolRoom {
+            name: update.name.unwrap_or(existing.name),
+            room_type: update.room_type.unwrap_or(existing.room_type),
+            capacity: update.capacity.unwrap_or(existing.capacity),
+            is_active: update.is_active.unwrap_or(existing.is_active),
+            ..existing
+        };
+        room.validate().map_err(AppError::validation)?;
+
+        sqlx::query(
+            r#"UPDATE school_rooms
+               SET name = $3, room_type = $4, capacity = $5, is_active = $6
+               WHERE school_id = $1 AND id = $2 AND deleted_at IS NULL"#,
+        )
+        .bind(school_id.to_hex())
+        .bind(id.to_hex())
+        .bind(room.name.trim())
+        .bind(room.room_type.trim())
+        .bind(room.capacity)
+        .bind(room.is_active)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::map_write_error)?;
+
+        self.find_one(school_id, id).await
+    }
+
+    pub async fn delete(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<SchoolRoom, AppError> {
+        let existing = self.find_one(school_id, id).await?;
+        sqlx::query("UPDATE school_rooms SET deleted_at = now() WHERE school_id = $1 AND id = $2")
+            .bind(school_id.to_hex())
+            .bind(id.to_hex())
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        Ok(existing)
+    }
+}
@@ -49,9 +49,7 @@ impl SchoolService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -63,8 +61,8 @@ impl SchoolService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -97,9 +95,9 @@ impl SchoolService {
             return Ok(None);
         }
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
 
         let field = query.field[0].as_str();
@@ -116,9 +114,10 @@ impl SchoolService {
                 parse_object_id_value(&value)?;
                 Ok(Some(SchoolLookup::CreatorId(value)))
             }
-            _ => Err(AppError {
-                message: format!("Unsupported school filter field: {}", field),
-            }),
+            _ => Err(AppError::bad_request(format!(
+                "Unsupported school filter field: {}",
+                field
+            ))),
         }
     }
 
@@ -368,16 +367,17 @@ impl SchoolService {
 
     pub async fn create(&self, dto: School) -> Result<School, AppError> {
         self.ensure_indexes().await?;
-        is_valid_username(&dto.username).map_err(|e| AppError { message: e })?;
-        is_valid_name(&dto.name).map_err(|e| AppError { message: e })?;
+        is_valid_username(&dto.username).map_err(AppError::bad_request)?;
+        is_valid_name(&dto.name).map_err(AppError::bad_request)?;
 
         if let Some(existing) = self
             .find_one_by_lookup(SchoolLookup::Username(dto.username.clone()))
             .await?
         {
-            return Err(AppError {
-                message: format!("School username already exists: {}", existing.username),
-            });
+            return Err(AppError::conflict(format!(
+                "School username already exists: {}",
+                existing.username
+            )));
         }
 
         let mut new_school = dto.clone();
@@ -396,7 +396,7 @@ impl SchoolService {
         if let Some(logo_file) = new_school.logo.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&logo_file)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             new_school.logo_id = Some(cloud_res.public_id);
             new_school.logo = Some(cloud_res.secure_url);
         }
@@ -499,14 +499,12 @@ impl SchoolService {
     ) -> Result<School, AppError> {
         let lookup = match id {
             Some(id) => SchoolLookup::Id(Self::id_to_string(id)?),
-            None => lookup.ok_or_else(|| AppError {
-                message: "School lookup is required".into(),
-            })?,
+            None => lookup.ok_or_else(|| AppError::bad_request("School lookup is required"))?,
         };
 
-        self.find_one_by_lookup(lookup).await?.ok_or(AppError {
-            message: "School not found".into(),
-        })
+        self.find_one_by_lookup(lookup)
+            .await?
+            .ok_or(AppError::not_found("School not found"))
     }
 
     pub async fn find_by_code(&self, code: &str) -> Result<School, AppError> {
@@ -587,10 +585,10 @@ impl SchoolService {
 
     pub async fn update(&self, id: &IdType, update: &SchoolPartial) -> Result<School, AppError> {
         if let Some(ref name) = update.name {
-            is_valid_name(name).map_err(|e| AppError { message: e })?;
+            is_valid_name(name).map_err(AppError::bad_request)?;
         }
         if let Some(ref username) = update.username {
-            is_valid_username(username).map_err(|e| AppError { message: e })?;
+            is_valid_username(username).map_err(AppError::bad_request)?;
         }
 
         let existing = self.find_one(Some(id), None).await?;
@@ -602,9 +600,10 @@ impl SchoolService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("School username already exists: {}", username),
-                });
+                return Err(AppError::conflict(format!(
+                    "School username already exists: {}",
+                    username
+                )));
             }
         }
 
@@ -619,7 +618,7 @@ impl SchoolService {
 
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_logo_data)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
 
                 update_data.logo_id = Some(Some(cloud_res.public_id));
                 update_data.logo = Some(Some(cloud_res.secure_url));
@@ -742,14 +741,10 @@ impl SchoolService {
             || update_data.sports_extracurricular.is_some();
 
         if result.rows_affected() == 0 {
-            return Err(AppError {
-                message: "School not found".into(),
-            });
+            return Err(AppError::not_found("School not found"));
         }
         if !touched_scalar && !touched_relations {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         self.sync_school_partial_relations(&id, &update_data)
@@ -777,9 +772,8 @@ impl SchoolService {
 
     pub async fn refresh_school_token(&self, token: &str) -> Result<String, AppError> {
         let token_clean = token.replace("Bearer ", "");
-        let claims = verify_school_token(&token_clean).ok_or_else(|| AppError {
-            message: "Invalid token".to_string(),
-        })?;
+        let claims = verify_school_token(&token_clean)
+            .ok_or_else(|| AppError::unauthorized("Invalid token"))?;
 
         let school_id = IdType::from_string(&claims.id);
         let school = self.find_one(Some(&school_id), None).await?;
@@ -830,11 +824,9 @@ impl SchoolService {
         _logged_user: AuthUserDto,
     ) -> Result<SchoolAcademicResponse, AppError> {
         self.find_one(Some(school_id), None).await?;
-        Err(AppError {
-            message:
-                "School academic setup is pending the classes and subjects PostgreSQL migration"
-                    .to_string(),
-        })
+        Err(AppError::bad_request(
+            "School academic setup is pending the classes and subjects PostgreSQL migration",
+        ))
     }
 
     pub async fn search_members(
@@ -32,9 +32,9 @@ impl SchoolStaffQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -73,9 +73,7 @@ impl SchoolStaffService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -87,8 +85,8 @@ impl SchoolStaffService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -193,15 +191,16 @@ impl SchoolStaffService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported school staff filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported school staff filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -334,23 +333,24 @@ impl SchoolStaffService {
         _state: Option<&AppState>,
     ) -> Result<SchoolStaff, AppError> {
         self.ensure_indexes().await?;
-        is_valid_name(&dto.name).map_err(|e| AppError { message: e })?;
-        is_valid_email(&dto.email).map_err(|e| AppError { message: e })?;
+        is_valid_name(&dto.name).map_err(AppError::bad_request)?;
+        is_valid_email(&dto.email).map_err(AppError::bad_request)?;
 
         let school_id = dto.school_id.as_ref().map(|id| id.to_hex());
         if let Some(existing) = self
             .email_exists(&dto.email, school_id.as_deref(), None)
             .await?
         {
-            return Err(AppError {
-                message: format!("Email already exists: {}", existing.email),
-            });
+            return Err(AppError::conflict(format!(
+                "Email already exists: {}",
+                existing.email
+            )));
         }
 
         if let Some(image_data) = dto.image.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&image_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             dto.image_id = Some(cloud_res.public_id);
             dto.image = Some(cloud_res.secure_url);
         }
@@ -452,9 +452,7 @@ impl SchoolStaffService {
 
         match row {
             Some(row) => Ok(self.staff_from_row(row).await?),
-            None => Err(AppError {
-                message: "School staff not found".into(),
-            }),
+            None => Err(AppError::not_found("School staff not found")),
         }
     }
 
@@ -521,10 +519,10 @@ impl SchoolStaffService {
         update: &SchoolStaffPartial,
     ) -> Result<SchoolStaff, AppError> {
         if let Some(ref name) = update.name {
-            is_valid_name(name).map_err(|e| AppError { message: e })?;
+            is_valid_name(name).map_err(AppError::bad_request)?;
         }
         if let Some(ref email) = update.email {
-            is_valid_email(email).map_err(|e| AppError { message: e })?;
+            is_valid_email(email).map_err(AppError::bad_request)?;
         }
 
         let existing = self.find_one(Some(id), None).await?;
@@ -541,9 +539,10 @@ impl SchoolStaffService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("Email already exists: {}", email),
-                });
+                return Err(AppError::conflict(format!(
+                    "Email already exists: {}",
+                    email
+                )));
             }
         }
 
@@ -557,7 +556,7 @@ impl SchoolStaffService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_image_data)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.image_id = Some(Some(cloud_res.public_id));
                 update_data.image = Some(Some(cloud_res.secure_url));
             }
@@ -628,9 +627,7 @@ impl SchoolStaffService {
         }
 
         if !touched && update_data.tags.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         let staff = self.find_one(Some(id), None).await?;
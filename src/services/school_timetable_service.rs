@@ -23,7 +23,7 @@ impl SchoolTimetableService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -35,8 +35,8 @@ impl SchoolTimetableService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -67,12 +67,18 @@ impl SchoolTimetableService {
     }
 
     pub async fn create(&self, dto: SchoolTimetable) -> Result<SchoolTimetable, AppError> {
-        if self.find_by_school_and_academic_year(&dto.school_id, &dto.academic_year_id).await.is_ok() {
-            return Err(AppError { message: "School timetable for academic year already exists".to_string() });
+        if self
+            .find_by_school_and_academic_year(&dto.school_id, &dto.academic_year_id)
+            .await
+            .is_ok()
+        {
+            return Err(AppError::conflict(
+                "School timetable for academic year already exists",
+            ));
         }
 
         if let Err(e) = dto.validate() {
-            return Err(AppError { message: format!("Validation error: {}", e) });
+            return Err(AppError::bad_request(format!("Validation error: {}", e)));
         }
 
         let id = Self::new_id();
@@ -116,7 +122,7 @@ impl SchoolTimetableService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_timetable(r),
-            None => Err(AppError { message: "School timetable not found".to_string() }),
+            None => Err(AppError::not_found("School timetable not found")),
         }
     }
 
@@ -136,7 +142,9 @@ impl SchoolTimetableService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_timetable(r),
-            None => Err(AppError { message: "School timetable not found for this academic year".into() }),
+            None => Err(AppError::not_found(
+                "School timetable not found for this academic year",
+            )),
         }
     }
 
@@ -193,7 +201,9 @@ impl SchoolTimetableService {
                 .await
                 .map_err(Self::db_error)?;
                 if row.is_some() {
-                    return Err(AppError { message: "Timetable for this academic year already exists".into() });
+                    return Err(AppError::conflict(
+                        "Timetable for this academic year already exists",
+                    ));
                 }
             }
         }
@@ -220,7 +230,9 @@ impl SchoolTimetableService {
             sql.push(", events = ").push_bind(j); touched = true;
         }
 
-        if !touched { return Err(AppError { message: "No valid fields to update".into() }); }
+        if !touched {
+            return Err(AppError::bad_request("No valid fields to update"));
+        }
 
         sql.push(" WHERE id = ").push_bind(&obj_id_str);
         sql.build().execute(&self.pool).await.map_err(Self::db_error)?;
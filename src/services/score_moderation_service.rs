@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
+
+use crate::{
+    domain::{
+        auth_user::AuthUserDto,
+        score_moderation::{
+            ExamModeration, ModerationAction, ModerationEvent, ModerationRequest, ModerationStatus,
+            ScoreModeration, MODERATE_PERMISSION, PUBLISH_PERMISSION, REVIEW_PERMISSION,
+        },
+    },
+    errors::AppError,
+    guards::role_guard::require_permission,
+    services::{marksheet_service::MarksheetService, permission_service::PermissionService},
+    utils::object_id::ObjectId,
+};
+
+pub struct ScoreModerationService {
+    pub pool: PgPool,
+}
+
+impl ScoreModerationService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn parse_status(raw: Option<String>) -> Result<ModerationStatus, AppError> {
+        raw.map(|status| ModerationStatus::parse(&status))
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(AppError::bad_request)
+    }
+
+    /// Locks the subject's moderation row for the rest of the transaction, so
+    /// it cannot move on while marks are written, then checks the marks may
+    /// change; see [`Self::check_editable`]
+    pub async fn ensure_editable(
+        tx: &mut Transaction<'static, Postgres>,
+        exam_id: &str,
+        class_subject_id: &str,
+    ) -> Result<(), AppError> {
+        // A draft row for the lock to hold until the subject is first
+        // submitted. Unknown exams and subjects are for the caller's own
+        // validation to report.
+        sqlx::query(
+            r#"
+            INSERT INTO score_moderations (id, school_id, exam_id, class_subject_id)
+            SELECT $1, e.school_id, e.id, cs.id
+            FROM exams e
+            JOIN class_subjects cs ON cs.id = $3
+            WHERE e.id = $2
+            ON CONFLICT (exam_id, class_subject_id) DO NOTHING
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(exam_id)
+        .bind(class_subject_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT e.results_published_at IS NOT NULL AS published, m.status
+            FROM score_moderations m
+            JOIN exams e ON e.id = m.exam_id
+            WHERE m.exam_id = $1 AND m.class_subject_id = $2
+            FOR UPDATE OF m
+            "#,
+        )
+        .bind(exam_id)
+        .bind(class_subject_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+        let Some(row) = row else {
+            return Ok(());
+        };
+
+        let published: bool = row.try_get("published").map_err(Self::db_error)?;
+        let status = Self::parse_status(row.try_get("status").map_err(Self::db_error)?)?;
+        Self::check_editable(published, status)
+    }
+
+    /// Rejects mark changes for a subject that is submitted, under review or
+    /// approved, and for any subject of an exam whose results are published,
+    /// unless an admin reopened it
+    fn check_editable(published: bool, status: ModerationStatus) -> Result<(), AppError> {
+        if status == ModerationStatus::Reopened {
+            return Ok(());
+        }
+        if published {
+            return Err(AppError::conflict(
+                "Results for this exam are published; marks can only change after the \
+                 subject is reopened",
+            ));
+        }
+        if !status.is_editable() {
+            return Err(AppError::conflict(format!(
+                "Marks for this subject are {} and cannot be changed",
+                status.as_str().to_lowercase()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether parents and students may see the exam's results
+    pub async fn results_published(&self, exam_id: &ObjectId) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM exams WHERE id = $1 AND results_published_at IS NOT NULL
+            )
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Subjects of the exam that have marks or have been through moderation.
+    /// Draft rows left by mark writes alone don't count.
+    async fn subjects(
+        &self,
+        school_id: &ObjectId,
+        exam_id: &ObjectId,
+        class_subject_id: Option<&ObjectId>,
+    ) -> Result<Vec<ScoreModeration>, AppError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT cs.id AS class_subject_id, cs.name AS subject_name, c.name AS class_name,
+                   m.id, m.status, m.submitted_at, m.reviewed_at, m.approved_at, m.note,
+                   (SELECT count(*) FROM scores s
+                    WHERE s.exam_id = $1 AND s.class_subject_id = cs.id AND s.deleted_at IS NULL
+                   ) AS score_count
+            FROM class_subjects cs
+            LEFT JOIN classes c ON c.id = cs.class_id
+            LEFT JOIN score_moderations m ON m.class_subject_id = cs.id AND m.exam_id = $1
+            WHERE cs.school_id = $2
+              AND ($3::TEXT IS NULL OR cs.id = $3)
+              AND (EXISTS (
+                     SELECT 1 FROM score_moderation_events ev WHERE ev.moderation_id = m.id
+                   ) OR EXISTS (
+                     SELECT 1 FROM scores s
+                     WHERE s.exam_id = $1 AND s.class_subject_id = cs.id AND s.deleted_at IS NULL
+                   ))
+            ORDER BY c.name, cs.name
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .bind(school_id.to_hex())
+        .bind(class_subject_id.map(|id| id.to_hex()))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut subjects = rows
+            .iter()
+            .map(|row| Self::row_to_moderation(row, exam_id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ids: Vec<String> = subjects
+            .iter()
+            .filter_map(|subject| subject.id.map(|id| id.to_hex()))
+            .collect();
+        let mut history = self.history(&ids).await?;
+        for subject in &mut subjects {
+            if let Some(id) = subject.id {
+                subject.history = history.remove(&id.to_hex()).unwrap_or_default();
+            }
+        }
+        Ok(subjects)
+    }
+
+    fn row_to_moderation(row: &PgRow, exam_id: &ObjectId) -> Result<ScoreModeration, AppError> {
+        let id: Option<String> = row.try_get("id").map_err(Self::db_error)?;
+        let class_subject_id: String = row.try_get("class_subject_id").map_err(Self::db_error)?;
+        Ok(ScoreModeration {
+            id: id.map(|id| Self::parse_oid(&id, "id")).transpose()?,
+            exam_id: *exam_id,
+            class_subject_id: Self::parse_oid(&class_subject_id, "class_subject_id")?,
+            subject_name: row.try_get("subject_name").map_err(Self::db_error)?,
+            class_name: row.try_get("class_name").map_err(Self::db_error)?,
+            status: Self::parse_status(row.try_get("status").map_err(Self::db_error)?)?,
+            score_count: row.try_get("score_count").map_err(Self::db_error)?,
+            submitted_at: row.try_get("submitted_at").map_err(Self::db_error)?,
+            reviewed_at: row.try_get("reviewed_at").map_err(Self::db_error)?,
+            approved_at: row.try_get("approved_at").map_err(Self::db_error)?,
+            note: row.try_get("note").map_err(Self::db_error)?,
+            history: Vec::new(),
+        })
+    }
+
+    async fn history(
+        &self,
+        moderation_ids: &[String],
+    ) -> Result<HashMap<String, Vec<ModerationEvent>>, AppError> {
+        if moderation_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT ev.moderation_id, ev.action, ev.from_status, ev.to_status,
+                   ev.actor_user_id, u.name AS actor_name, ev.note, ev.created_at
+            FROM score_moderation_events ev
+            LEFT JOIN users u ON u.id = ev.actor_user_id
+            WHERE ev.moderation_id = ANY($1)
+            ORDER BY ev.created_at
+            "#,
+        )
+        .bind(moderation_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut history: HashMap<String, Vec<ModerationEvent>> = HashMap::new();
+        for row in rows {
+            let moderation_id: String = row.try_get("moderation_id").map_err(Self::db_error)?;
+            let action: String = row.try_get("action").map_err(Self::db_error)?;
+            let action = match action.as_str() {
+                "Submit" => ModerationAction::Submit,
+                "Review" => ModerationAction::Review,
+                "Return" => ModerationAction::Return,
+                "Approve" => ModerationAction::Approve,
+                _ => ModerationAction::Reopen,
+            };
+            let actor: Option<String> = row.try_get("actor_user_id").map_err(Self::db_error)?;
+            history
+                .entry(moderation_id)
+                .or_default()
+                .push(ModerationEvent {
+                    action,
+                    from_status: Self::parse_status(
+                        row.try_get("from_status").map_err(Self::db_error)?,
+                    )?,
+                    to_status: Self::parse_status(
+                        row.try_get("to_status").map_err(Self::db_error)?,
+                    )?,
+                    actor_user_id: actor
+                        .map(|id| Self::parse_oid(&id, "actor_user_id"))
+                        .transpose()?,
+                    actor_name: row.try_get("actor_name").map_err(Self::db_error)?,
+                    note: row.try_get("note").map_err(Self::db_error)?,
+                    created_at: row.try_get("created_at").map_err(Self::db_error)?,
+                });
+        }
+        Ok(history)
+    }
+
+    pub async fn exam(
+        &self,
+        school_id: &ObjectId,
+        exam_id: &ObjectId,
+    ) -> Result<ExamModeration, AppError> {
+        let published_at = sqlx::query_scalar(
+            r#"
+            SELECT results_published_at FROM exams
+            WHERE id = $1 AND school_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| AppError::not_found("Exam not found in this school"))?;
+
+        let subjects = self.subjects(school_id, exam_id, None).await?;
+        let ready_to_publish = !subjects.is_empty()
+            && subjects
+                .iter()
+                .all(|subject| subject.status == ModerationStatus::Approved);
+        Ok(ExamModeration {
+            exam_id: *exam_id,
+            results_published_at: published_at,
+            ready_to_publish,
+            subjects,
+        })
+    }
+
+    async fn require(
+        &self,
+        user: &AuthUserDto,
+        school_id: &ObjectId,
+        permission: &str,
+    ) -> Result<(), AppError> {
+        require_permission(
+            user,
+            &school_id.to_hex(),
+            permission,
+            &PermissionService::new(&self.pool),
+        )
+        .await
+        .map_err(AppError::forbidden)
+    }
+
+    /// Who may take each step: teachers of the subject submit, reviewers
+    /// (holders of `scores.review`) review or return, holders of
+    /// `result.moderate` approve and reopen
+    async fn ensure_can_act(
+        &self,
+        user: &AuthUserDto,
+        school_id: &ObjectId,
+        class_subject_id: &ObjectId,
+        action: ModerationAction,
+    ) -> Result<(), AppError> {
+        match action {
+            ModerationAction::Submit => {
+                MarksheetService::new(&self.pool)
+                    .ensure_can_enter(user, school_id, &class_subject_id.to_hex())
+                    .await
+            }
+            ModerationAction::Review | ModerationAction::Return => {
+                self.require(user, school_id, REVIEW_PERMISSION).await
+            }
+            ModerationAction::Approve | ModerationAction::Reopen => {
+                self.require(user, school_id, MODERATE_PERMISSION).await
+            }
+        }
+    }
+
+    /// Moves a subject's marks one step through moderation, recording the
+    /// transition. A reopen of an exam with published results withdraws
+    /// them until they are published again.
+    pub async fn act(
+        &self,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        request: ModerationRequest,
+    ) -> Result<ScoreModeration, AppError> {
+        request.validate().map_err(AppError::validation)?;
+        let exam_id = Self::parse_oid(&request.exam_id, "exam_id")?;
+        let class_subject_id = Self::parse_oid(&request.class_subject_id, "class_subject_id")?;
+
+        let found: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (
+                 SELECT 1 FROM class_subjects cs
+                 JOIN exams e ON e.id = $2 AND e.school_id = cs.school_id AND e.deleted_at IS NULL
+                 WHERE cs.id = $1 AND cs.school_id = $3 AND cs.deleted_at IS NULL
+               )"#,
+        )
+        .bind(class_subject_id.to_hex())
+        .bind(exam_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if !found {
+            return Err(AppError::not_found(
+                "Class subject or exam not found in this school",
+            ));
+        }
+        self.ensure_can_act(user, school_id, &class_subject_id, request.action)
+            .await?;
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let (moderation_id, from) =
+            Self::lock_moderation(&mut tx, school_id, &exam_id, &class_subject_id).await?;
+        let to = request
+            .action
+            .transition(from)
+            .map_err(AppError::bad_request)?;
+
+        if request.action == ModerationAction::Submit {
+            let score_count: i64 = sqlx::query_scalar(
+                r#"
+                SELECT count(*) FROM scores
+                WHERE exam_id = $1 AND class_subject_id = $2 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(exam_id.to_hex())
+            .bind(class_subject_id.to_hex())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+            if score_count == 0 {
+                return Err(AppError::bad_request(
+                    "There are no marks to submit for this subject",
+                ));
+            }
+        }
+
+        let stamp = Self::stamp(request.action);
+        let note = request
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty());
+        let sql = format!(
+            "UPDATE score_moderations SET status = $2, note = $3, {} WHERE id = $1",
+            stamp
+        );
+        let mut update = sqlx::query(&sql)
+            .bind(&moderation_id)
+            .bind(to.as_str())
+            .bind(note);
+        if stamp.contains("$4") {
+            update = update.bind(&user.id);
+        }
+        update.execute(&mut *tx).await.map_err(Self::db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO score_moderation_events (
+              id, moderation_id, action, from_status, to_status, actor_user_id, note
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(&moderation_id)
+        .bind(request.action.as_str())
+        .bind(from.as_str())
+        .bind(to.as_str())
+        .bind(&user.id)
+        .bind(note)
+        .execute(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        if request.action == ModerationAction::Reopen {
+            Self::set_published(&mut tx, &exam_id, None).await?;
+        }
+        tx.commit().await.map_err(Self::db_error)?;
+
+        self.subjects(school_id, &exam_id, Some(&class_subject_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::not_found("Moderation record not found"))
+    }
+
+    /// Columns an action sets besides the status and note; `$4` is the user
+    /// taking it
+    fn stamp(action: ModerationAction) -> &'static str {
+        match action {
+            ModerationAction::Submit => {
+                "submitted_by = $4, submitted_at = now(), reviewed_by = NULL, reviewed_at = NULL"
+            }
+            ModerationAction::Review => "reviewed_by = $4, reviewed_at = now()",
+            ModerationAction::Approve => "approved_by = $4, approved_at = now()",
+            ModerationAction::Return | ModerationAction::Reopen => {
+                "approved_by = NULL, approved_at = NULL, reviewed_by = NULL, reviewed_at = NULL"
+            }
+        }
+    }
+
+    /// The moderation row of the subject, created as a draft on first use,
+    /// locked for the rest of the transaction
+    async fn lock_moderation(
+        tx: &mut Transaction<'static, Postgres>,
+        school_id: &ObjectId,
+        exam_id: &ObjectId,
+        class_subject_id: &ObjectId,
+    ) -> Result<(String, ModerationStatus), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO score_moderations (id, school_id, exam_id, class_subject_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (exam_id, class_subject_id) DO NOTHING
+            "#,
+        )
+        .bind(Self::new_id())
+        .bind(school_id.to_hex())
+        .bind(exam_id.to_hex())
+        .bind(class_subject_id.to_hex())
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, status FROM score_moderations
+            WHERE exam_id = $1 AND class_subject_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .bind(class_subject_id.to_hex())
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let status = Self::parse_status(row.try_get("status").map_err(Self::db_error)?)?;
+        Ok((id, status))
+    }
+
+    /// Publishes (or, with no user, withdraws) the exam's results, marking
+    /// its calculated term results finalized to match
+    async fn set_published(
+        tx: &mut Transaction<'static, Postgres>,
+        exam_id: &ObjectId,
+        published_by: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE exams
+            SET results_published_at = CASE WHEN $2::TEXT IS NULL THEN NULL ELSE now() END,
+                results_published_by = $2,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .bind(published_by)
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE student_term_results SET is_finalized = $2, updated_at = now()
+            WHERE exam_id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .bind(published_by.is_some())
+        .execute(&mut **tx)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Finalizes and publishes the exam's results once every subject with
+    /// marks is approved and the results have been calculated
+    pub async fn publish_results(
+        &self,
+        school_id: &ObjectId,
+        user: &AuthUserDto,
+        exam_id: &ObjectId,
+    ) -> Result<ExamModeration, AppError> {
+        self.require(user, school_id, PUBLISH_PERMISSION).await?;
+        let moderation = self.exam(school_id, exam_id).await?;
+        if moderation.results_published_at.is_some() {
+            return Err(AppError::conflict(
+                "Results for this exam are already published",
+            ));
+        }
+        if moderation.subjects.is_empty() {
+            return Err(AppError::bad_request("This exam has no marks to publish"));
+        }
+        let pending: Vec<String> = moderation
+            .subjects
+            .iter()
+            .filter(|subject| subject.status != ModerationStatus::Approved)
+            .map(|subject| {
+                format!(
+                    "{} ({})",
+                    subject
+                        .subject_name
+                        .clone()
+                        .unwrap_or_else(|| subject.class_subject_id.to_hex()),
+                    subject.status.as_str()
+                )
+            })
+            .collect();
+        if !pending.is_empty() {
+            return Err(AppError::bad_request(format!(
+                "Subjects not yet approved: {}",
+                pending.join(", ")
+            )));
+        }
+
+        let calculated: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM student_term_results WHERE exam_id = $1 AND deleted_at IS NULL
+            )
+            "#,
+        )
+        .bind(exam_id.to_hex())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if !calculated {
+            return Err(AppError::bad_request(
+                "Calculate the exam results before publishing them",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        Self::set_published(&mut tx, exam_id, Some(&user.id)).await?;
+        tx.commit().await.map_err(Self::db_error)?;
+        self.exam(school_id, exam_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    #[test]
+    fn drafts_and_reopened_subjects_are_editable() {
+        assert!(ScoreModerationService::check_editable(false, ModerationStatus::Draft).is_ok());
+        assert!(ScoreModerationService::check_editable(false, ModerationStatus::Reopened).is_ok());
+    }
+
+    #[test]
+    fn subjects_in_moderation_are_locked() {
+        for status in [
+            ModerationStatus::Submitted,
+            ModerationStatus::Reviewed,
+            ModerationStatus::Approved,
+        ] {
+            let error = ScoreModerationService::check_editable(false, status).unwrap_err();
+            assert_eq!(error.code, ErrorCode::Conflict);
+            assert_eq!(
+                error.message,
+                format!(
+                    "Marks for this subject are {} and cannot be changed",
+                    status.as_str().to_lowercase()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn published_results_lock_every_subject_but_reopened_ones() {
+        for status in [ModerationStatus::Draft, ModerationStatus::Approved] {
+            let error = ScoreModerationService::check_editable(true, status).unwrap_err();
+            assert_eq!(error.code, ErrorCode::Conflict);
+            assert!(error
+                .message
+                .starts_with("Results for this exam are published"));
+        }
+        assert!(ScoreModerationService::check_editable(true, ModerationStatus::Reopened).is_ok());
+    }
+
+    #[test]
+    fn only_forward_steps_record_who_took_them() {
+        for action in [
+            ModerationAction::Submit,
+            ModerationAction::Review,
+            ModerationAction::Approve,
+        ] {
+            assert!(ScoreModerationService::stamp(action).contains("$4"));
+        }
+        for action in [ModerationAction::Return, ModerationAction::Reopen] {
+            let stamp = ScoreModerationService::stamp(action);
+            assert!(!stamp.contains("$4"));
+            assert!(stamp.contains("approved_at = NULL"));
+            assert!(stamp.contains("reviewed_at = NULL"));
+        }
+        // A resubmission starts review over
+        assert!(
+            ScoreModerationService::stamp(ModerationAction::Submit).contains("reviewed_at = NULL")
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row, Transaction};
 
 use crate::{
     domain::{
@@ -8,6 +8,7 @@ use crate::{
     },
     errors::AppError,
     models::{api_request_model::RequestQuery, id_model::IdType},
+    services::score_moderation_service::ScoreModerationService,
     utils::object_id::{parse_object_id_value, ObjectId},
 };
 
@@ -28,9 +29,9 @@ impl ScoreQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -74,9 +75,7 @@ impl ScoreService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -88,8 +87,8 @@ impl ScoreService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -164,9 +163,10 @@ impl ScoreService {
                     }
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported score filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported score filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -241,6 +241,27 @@ impl ScoreService {
         })
     }
 
+    /// Marks under moderation, approved or published are locked; see
+    /// [`ScoreModerationService::ensure_editable`]. The write must go through
+    /// the same transaction.
+    async fn ensure_editable(
+        tx: &mut Transaction<'static, Postgres>,
+        exam_id: Option<&ObjectId>,
+        class_subject_id: Option<&ObjectId>,
+    ) -> Result<(), AppError> {
+        match (exam_id, class_subject_id) {
+            (Some(exam_id), Some(class_subject_id)) => {
+                ScoreModerationService::ensure_editable(
+                    tx,
+                    &exam_id.to_hex(),
+                    &class_subject_id.to_hex(),
+                )
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub async fn create(&self, mut score: Score) -> Result<Score, AppError> {
         self.ensure_indexes().await?;
         score.percentage = if score.max_score > 0.0 {
@@ -249,20 +270,23 @@ impl ScoreService {
             0.0
         };
         if score.score > score.max_score {
-            return Err(AppError {
-                message: "Score cannot exceed maximum score".to_string(),
-            });
+            return Err(AppError::bad_request("Score cannot exceed maximum score"));
         }
 
         let school_id = score
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let id = score.id.map(|id| id.to_hex()).unwrap_or_else(Self::new_id);
 
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        Self::ensure_editable(
+            &mut tx,
+            score.exam_id.as_ref(),
+            score.class_subject_id.as_ref(),
+        )
+        .await?;
         let result = sqlx::query(
             r#"
             INSERT INTO scores (
@@ -288,22 +312,23 @@ impl ScoreService {
         .bind(score.created_at)
         .bind(score.updated_at)
         .bind(if score.is_deleted { Some(Utc::now()) } else { None })
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await;
 
         match result {
-            Ok(_) => self.find_one(&IdType::from_string(id)).await,
+            Ok(_) => {
+                tx.commit().await.map_err(Self::db_error)?;
+                self.find_one(&IdType::from_string(id)).await
+            }
             Err(error)
                 if error
                     .as_database_error()
                     .map(|error| error.is_unique_violation())
                     .unwrap_or(false) =>
             {
-                Err(AppError {
-                    message:
-                        "Score already exists for this student, subject, exam, and assessment category"
-                            .to_string(),
-                })
+                Err(AppError::bad_request(
+                    "Score already exists for this student, subject, exam, and assessment category",
+                ))
             }
             Err(error) => Err(Self::db_error(error)),
         }
@@ -329,9 +354,9 @@ impl ScoreService {
             .fetch_optional(&self.pool)
             .await
             .map_err(Self::db_error)?;
-        row.map(Self::score_from_row).transpose()?.ok_or(AppError {
-            message: "Score not found".into(),
-        })
+        row.map(Self::score_from_row)
+            .transpose()?
+            .ok_or(AppError::not_found("Score not found"))
     }
 
     pub async fn get_all(
@@ -396,13 +421,24 @@ impl ScoreService {
         change_reason: Option<String>,
     ) -> Result<Score, AppError> {
         let existing = self.find_one(id).await?;
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        Self::ensure_editable(
+            &mut tx,
+            existing.exam_id.as_ref(),
+            existing.class_subject_id.as_ref(),
+        )
+        .await?;
+        // Moving a mark into another subject or exam needs that one open too
+        if update.exam_id.is_some() || update.class_subject_id.is_some() {
+            let exam_id = update.exam_id.unwrap_or(existing.exam_id);
+            let class_subject_id = update.class_subject_id.unwrap_or(existing.class_subject_id);
+            Self::ensure_editable(&mut tx, exam_id.as_ref(), class_subject_id.as_ref()).await?;
+        }
         let mut new_score_value = existing.score;
         let next_score = update.score.unwrap_or(existing.score);
         let next_max_score = update.max_score.unwrap_or(existing.max_score);
         if next_score > next_max_score {
-            return Err(AppError {
-                message: "Score cannot exceed maximum score".to_string(),
-            });
+            return Err(AppError::bad_request("Score cannot exceed maximum score"));
         }
         let next_percentage = if next_max_score > 0.0 {
             (next_score / next_max_score) * 100.0
@@ -463,13 +499,12 @@ impl ScoreService {
             touched = true;
         }
         if !touched {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         if new_score_value != existing.score {
-            self.insert_audit_log(
+            Self::insert_audit_log(
+                &mut tx,
                 existing.id,
                 existing.school_id,
                 existing.score,
@@ -484,14 +519,15 @@ impl ScoreService {
             .push_bind(Self::id_to_string(id)?)
             .push(" AND deleted_at IS NULL");
         sql.build()
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
             .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
         self.find_one(id).await
     }
 
     async fn insert_audit_log(
-        &self,
+        tx: &mut Transaction<'static, Postgres>,
         score_id: Option<ObjectId>,
         school_id: Option<ObjectId>,
         old_score: f64,
@@ -499,12 +535,8 @@ impl ScoreService {
         changed_by: ObjectId,
         change_reason: Option<String>,
     ) -> Result<(), AppError> {
-        let score_id = score_id.ok_or_else(|| AppError {
-            message: "Score has no ID".into(),
-        })?;
-        let school_id = school_id.ok_or_else(|| AppError {
-            message: "Score has no school_id".into(),
-        })?;
+        let score_id = score_id.ok_or_else(|| AppError::bad_request("Score has no ID"))?;
+        let school_id = school_id.ok_or_else(|| AppError::bad_request("Score has no school_id"))?;
         sqlx::query(
             r#"
             INSERT INTO score_audit_logs (
@@ -520,7 +552,7 @@ impl ScoreService {
         .bind(old_score)
         .bind(new_score)
         .bind(change_reason)
-        .execute(&self.pool)
+        .execute(&mut **tx)
         .await
         .map_err(Self::db_error)?;
         Ok(())
@@ -528,11 +560,19 @@ impl ScoreService {
 
     pub async fn delete(&self, id: &IdType) -> Result<Score, AppError> {
         let score = self.find_one(id).await?;
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        Self::ensure_editable(
+            &mut tx,
+            score.exam_id.as_ref(),
+            score.class_subject_id.as_ref(),
+        )
+        .await?;
         sqlx::query("UPDATE scores SET deleted_at = now(), updated_at = now() WHERE id = $1")
             .bind(Self::id_to_string(id)?)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
             .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
         Ok(score)
     }
 
@@ -22,7 +22,7 @@ impl SectorService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -34,8 +34,8 @@ impl SectorService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -91,7 +91,9 @@ impl SectorService {
         }
 
         if q.field.len() != q.value.len() {
-            return Err(AppError { message: "Number of fields must match number of values".into() });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for (field, value) in q.field.iter().zip(q.value.iter()) {
             match field.as_str() {
@@ -101,12 +103,17 @@ impl SectorService {
                 "country" => { sql.push(" AND lower(country) = lower(").push_bind(value).push(")"); }
                 "type" => { sql.push(" AND lower(type) = lower(").push_bind(value).push(")"); }
                 "disable" => {
-                    let b = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid disable filter value".into(),
-                    })?;
+                    let b = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid disable filter value"))?;
                     sql.push(" AND disable = ").push_bind(b);
                 }
-                _ => return Err(AppError { message: format!("Unsupported filter field: {}", field) }),
+                _ => {
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported filter field: {}",
+                        field
+                    )))
+                }
             }
         }
         Ok(())
@@ -114,10 +121,16 @@ impl SectorService {
 
     pub async fn create(&self, dto: Sector) -> Result<Sector, AppError> {
         if self.find_one_by_name(&dto.name).await?.is_some() {
-            return Err(AppError { message: format!("Sector name already exists: {}", dto.name) });
+            return Err(AppError::conflict(format!(
+                "Sector name already exists: {}",
+                dto.name
+            )));
         }
         if self.find_one_by_username(&dto.username).await?.is_some() {
-            return Err(AppError { message: format!("Sector username already exists: {}", dto.username) });
+            return Err(AppError::conflict(format!(
+                "Sector username already exists: {}",
+                dto.username
+            )));
         }
 
         let mut sector = dto;
@@ -126,7 +139,7 @@ impl SectorService {
         if let Some(logo) = sector.logo.clone() {
             let res = CloudinaryService::upload_to_cloudinary(&logo)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             sector.logo_id = Some(res.public_id);
             sector.logo = Some(res.secure_url);
         }
@@ -200,7 +213,7 @@ impl SectorService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_sector(r),
-            None => Err(AppError { message: "Sector not found".into() }),
+            None => Err(AppError::not_found("Sector not found")),
         }
     }
 
@@ -245,12 +258,20 @@ impl SectorService {
 
         if let Some(ref name) = update.name {
             if existing.name != *name && self.find_one_by_name(name).await?.is_some() {
-                return Err(AppError { message: format!("Sector name already exists: {}", name) });
+                return Err(AppError::conflict(format!(
+                    "Sector name already exists: {}",
+                    name
+                )));
             }
         }
         if let Some(ref username) = update.username {
-            if existing.username != *username && self.find_one_by_username(username).await?.is_some() {
-                return Err(AppError { message: format!("Sector username already exists: {}", username) });
+            if existing.username != *username
+                && self.find_one_by_username(username).await?.is_some()
+            {
+                return Err(AppError::conflict(format!(
+                    "Sector username already exists: {}",
+                    username
+                )));
             }
         }
 
@@ -287,7 +308,7 @@ impl SectorService {
                     }
                     let res = CloudinaryService::upload_to_cloudinary(new_logo)
                         .await
-                        .map_err(|e| AppError { message: e })?;
+                        .map_err(AppError::bad_request)?;
                     sql.push(", logo = ").push_bind(res.secure_url);
                     sql.push(", logo_id = ").push_bind(res.public_id);
                     touched = true;
@@ -305,7 +326,7 @@ impl SectorService {
         }
 
         if !touched {
-            return Err(AppError { message: "No valid fields to update".into() });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         sql.push(" WHERE id = ").push_bind(&id_str);
@@ -34,9 +34,9 @@ impl StudentQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
 
         for id in &query.by_ids {
@@ -78,9 +78,7 @@ impl StudentService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -92,8 +90,8 @@ impl StudentService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -261,15 +259,16 @@ impl StudentService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND sse.is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported student filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported student filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -602,19 +601,20 @@ impl StudentService {
         _state: Option<&AppState>,
     ) -> Result<Student, AppError> {
         self.ensure_indexes().await?;
-        is_valid_name(&dto.name).map_err(|e| AppError { message: e })?;
-        is_valid_email(&dto.email).map_err(|e| AppError { message: e })?;
+        is_valid_name(&dto.name).map_err(AppError::bad_request)?;
+        is_valid_email(&dto.email).map_err(AppError::bad_request)?;
 
-        let school_id = dto.school_id.ok_or_else(|| AppError {
-            message: "school_id is required".into(),
-        })?;
+        let school_id = dto
+            .school_id
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let school_id = school_id.to_hex();
 
         if let Some(reg_num) = &dto.registration_number {
             if self.registration_exists(&school_id, reg_num, None).await? {
-                return Err(AppError {
-                    message: format!("Registration number already exists: {:?}", reg_num),
-                });
+                return Err(AppError::conflict(format!(
+                    "Registration number already exists: {:?}",
+                    reg_num
+                )));
             }
         }
 
@@ -622,7 +622,7 @@ impl StudentService {
         if let Some(image_data) = student.image.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&image_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             student.image_id = Some(cloud_res.public_id);
             student.image = Some(cloud_res.secure_url);
         }
@@ -636,9 +636,10 @@ impl StudentService {
             .profile_school_enrollment(&profile_id, &school_id)
             .await?
         {
-            return Err(AppError {
-                message: format!("Email already exists: {}", existing.email),
-            });
+            return Err(AppError::conflict(format!(
+                "Email already exists: {}",
+                existing.email
+            )));
         }
 
         let enrollment_id = student
@@ -704,9 +705,7 @@ impl StudentService {
 
         match row {
             Some(row) => Ok(self.student_from_row(row).await?),
-            None => Err(AppError {
-                message: "Student not found".into(),
-            }),
+            None => Err(AppError::not_found("Student not found")),
         }
     }
 
@@ -791,10 +790,10 @@ impl StudentService {
 
     pub async fn update(&self, id: &IdType, update: &StudentPartial) -> Result<Student, AppError> {
         if let Some(ref name) = update.name {
-            is_valid_name(name).map_err(|e| AppError { message: e })?;
+            is_valid_name(name).map_err(AppError::bad_request)?;
         }
         if let Some(ref email) = update.email {
-            is_valid_email(email).map_err(|e| AppError { message: e })?;
+            is_valid_email(email).map_err(AppError::bad_request)?;
         }
 
         let existing_student = self.find_one(Some(id), None).await?;
@@ -818,9 +817,10 @@ impl StudentService {
                 .await
                 .map_err(Self::db_error)?;
                 if existing_profile.is_some() {
-                    return Err(AppError {
-                        message: format!("Email already exists: {}", email),
-                    });
+                    return Err(AppError::conflict(format!(
+                        "Email already exists: {}",
+                        email
+                    )));
                 }
             }
         }
@@ -831,16 +831,15 @@ impl StudentService {
                     .school_id
                     .as_ref()
                     .map(|id| id.to_hex())
-                    .ok_or_else(|| AppError {
-                        message: "school_id is required".into(),
-                    })?;
+                    .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
                 if self
                     .registration_exists(&school_id, reg_number, Some(&enrollment_id))
                     .await?
                 {
-                    return Err(AppError {
-                        message: format!("Registration number already exists: {:?}", reg_number),
-                    });
+                    return Err(AppError::conflict(format!(
+                        "Registration number already exists: {:?}",
+                        reg_number
+                    )));
                 }
             }
         }
@@ -856,7 +855,7 @@ impl StudentService {
 
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_image_data)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
 
                 update_data.image_id = Some(Some(cloud_res.public_id));
                 update_data.image = Some(Some(cloud_res.secure_url));
@@ -1013,9 +1012,7 @@ impl StudentService {
         }
 
         if !profile_touched && !enrollment_touched && update_data.tags.is_none() {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         let student = self.find_one(Some(id), None).await?;
@@ -0,0 +1,919 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+use crate::{
+    domain::{
+        class_timetable::{ClassTimetable, PeriodType},
+        school_timetable::{DailySchoolSchedule, DaySpecialType},
+        teacher_cover::{
+            AbsenceCoverPlan, AbsenceStatus, AffectedPeriod, ConfirmCover, CoverStatus,
+            CoverSuggestion, CreateTeacherAbsence, DateRangeQuery, DatedPeriod, TeacherAbsence,
+            TeacherCover, MAX_COVER_SUGGESTIONS, MAX_SCHEDULE_DAYS,
+        },
+    },
+    errors::AppError,
+    services::{
+        class_timetable_service::ClassTimetableService,
+        education_year_service::{EducationYearQuery, EducationYearService},
+        timetable_validation_service::TimetableValidationService,
+    },
+    utils::object_id::ObjectId,
+};
+
+struct SubjectInfo {
+    class_id: String,
+    name: String,
+    main_subject_id: Option<String>,
+    teacher_id: Option<String>,
+}
+
+impl SubjectInfo {
+    /// Keys under which a teacher of this subject counts as qualified
+    fn keys(&self) -> Vec<String> {
+        let mut keys = vec![format!("name:{}", self.name.trim().to_lowercase())];
+        if let Some(main_subject_id) = &self.main_subject_id {
+            keys.push(format!("main:{}", main_subject_id));
+        }
+        keys
+    }
+}
+
+/// Everything needed to lay the school's timetables onto real dates
+struct SchoolCalendar {
+    /// (term order, first day, last day)
+    terms: Vec<(i32, NaiveDate, NaiveDate)>,
+    timetables: HashMap<i32, Vec<ClassTimetable>>,
+    school_days: HashMap<ObjectId, Vec<DailySchoolSchedule>>,
+    subjects: HashMap<String, SubjectInfo>,
+}
+
+struct Candidate {
+    id: ObjectId,
+    user_id: Option<String>,
+    name: Option<String>,
+    subject_keys: HashSet<String>,
+    classes: HashSet<String>,
+    availability: Vec<(Weekday, NaiveTime, NaiveTime)>,
+    absences: Vec<(NaiveDate, NaiveDate)>,
+}
+
+impl Candidate {
+    fn absent_on(&self, date: NaiveDate) -> bool {
+        self.absences
+            .iter()
+            .any(|(starts_on, ends_on)| *starts_on <= date && date <= *ends_on)
+    }
+
+    /// Teachers without availability rows are always available
+    fn available(&self, day: Weekday, start: NaiveTime, end: NaiveTime) -> bool {
+        self.availability.is_empty()
+            || self
+                .availability
+                .iter()
+                .any(|(d, from, to)| *d == day && *from <= start && end <= *to)
+    }
+}
+
+fn overlaps(a: &DatedPeriod, date: NaiveDate, start: NaiveTime, end: NaiveTime) -> bool {
+    a.date == date && a.starts_at < end && start < a.ends_at
+}
+
+/// The teacher the timetable assigns, before any cover
+fn timetabled_teacher(period: &DatedPeriod) -> Option<ObjectId> {
+    period.substituted_teacher_id.or(period.teacher_id)
+}
+
+pub struct TeacherCoverService {
+    pub pool: PgPool,
+}
+
+impl TeacherCoverService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn new_id() -> String {
+        ObjectId::new().to_hex()
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    fn row_to_absence(row: PgRow) -> Result<TeacherAbsence, AppError> {
+        let id: String = row.try_get("id").map_err(Self::db_error)?;
+        let school_id: String = row.try_get("school_id").map_err(Self::db_error)?;
+        let teacher_id: String = row.try_get("teacher_id").map_err(Self::db_error)?;
+        let status: String = row.try_get("status").map_err(Self::db_error)?;
+        let created_by: Option<String> = row.try_get("created_by").map_err(Self::db_error)?;
+        Ok(TeacherAbsence {
+            id: Some(Self::parse_oid(&id, "id")?),
+            school_id: Self::parse_oid(&school_id, "school_id")?,
+            teacher_id: Self::parse_oid(&teacher_id, "teacher_id")?,
+            starts_on: row.try_get("starts_on").map_err(Self::db_error)?,
+            ends_on: row.try_get("ends_on").map_err(Self::db_error)?,
+            reason: row.try_get("reason").map_err(Self::db_error)?,
+            status: if status == "Cancelled" {
+                AbsenceStatus::Cancelled
+            } else {
+                AbsenceStatus::Active
+            },
+            created_by: created_by.and_then(|id| ObjectId::parse_str(id).ok()),
+            created_at: row.try_get("created_at").ok(),
+            updated_at: row.try_get("updated_at").ok(),
+        })
+    }
+
+    fn row_to_cover(row: PgRow) -> Result<TeacherCover, AppError> {
+        let oid = |field: &str| -> Result<ObjectId, AppError> {
+            let raw: String = row.try_get(field).map_err(Self::db_error)?;
+            Self::parse_oid(&raw, field)
+        };
+        let class_subject_id: Option<String> =
+            row.try_get("class_subject_id").map_err(Self::db_error)?;
+        let status: String = row.try_get("status").map_err(Self::db_error)?;
+        Ok(TeacherCover {
+            id: Some(oid("id")?),
+            school_id: oid("school_id")?,
+            absence_id: oid("absence_id")?,
+            class_timetable_id: oid("class_timetable_id")?,
+            class_id: oid("class_id")?,
+            period_id: oid("period_id")?,
+            class_subject_id: class_subject_id.and_then(|id| ObjectId::parse_str(id).ok()),
+            cover_date: row.try_get("cover_date").map_err(Self::db_error)?,
+            starts_at: row.try_get("starts_at").map_err(Self::db_error)?,
+            ends_at: row.try_get("ends_at").map_err(Self::db_error)?,
+            absent_teacher_id: oid("absent_teacher_id")?,
+            cover_teacher_id: oid("cover_teacher_id")?,
+            status: if status == "Cancelled" {
+                CoverStatus::Cancelled
+            } else {
+                CoverStatus::Confirmed
+            },
+            note: row.try_get("note").map_err(Self::db_error)?,
+            created_at: row.try_get("created_at").ok(),
+        })
+    }
+
+    const ABSENCE_SQL: &'static str =
+        "SELECT id, school_id, teacher_id, starts_on, ends_on, reason, status, created_by, \
+         created_at, updated_at FROM teacher_absences";
+
+    const COVER_SQL: &'static str =
+        "SELECT id, school_id, absence_id, class_timetable_id, class_id, period_id, \
+         class_subject_id, cover_date, starts_at, ends_at, absent_teacher_id, cover_teacher_id, \
+         status, note, created_at FROM teacher_covers";
+
+    /// Defaults to the coming week and rejects ranges longer than a month
+    pub fn resolve_range(
+        query: &DateRangeQuery,
+        today: NaiveDate,
+    ) -> Result<(NaiveDate, NaiveDate), AppError> {
+        let from = query.from.unwrap_or(today);
+        let to = query.to.unwrap_or(from + Duration::days(6));
+        if to < from {
+            return Err(AppError::bad_request("to cannot be earlier than from"));
+        }
+        if (to - from).num_days() >= MAX_SCHEDULE_DAYS {
+            return Err(AppError::bad_request(format!(
+                "A schedule can span at most {} days",
+                MAX_SCHEDULE_DAYS
+            )));
+        }
+        Ok((from, to))
+    }
+
+    async fn ensure_teacher(
+        &self,
+        school_id: &ObjectId,
+        teacher_id: &ObjectId,
+    ) -> Result<(), AppError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM teachers WHERE id = $1 AND school_id = $2 AND deleted_at IS NULL)",
+        )
+        .bind(teacher_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if exists {
+            Ok(())
+        } else {
+            Err(AppError::not_found("Teacher not found in this school"))
+        }
+    }
+
+    /// The account behind a teacher of the school, if they have one
+    pub async fn teacher_user_id(
+        &self,
+        school_id: &ObjectId,
+        teacher_id: &ObjectId,
+    ) -> Result<Option<String>, AppError> {
+        let user_id: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT user_id FROM teachers WHERE id = $1 AND school_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(teacher_id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(user_id.flatten())
+    }
+
+    pub async fn create_absence(
+        &self,
+        school_id: &ObjectId,
+        dto: CreateTeacherAbsence,
+        created_by: Option<&ObjectId>,
+    ) -> Result<TeacherAbsence, AppError> {
+        dto.validate().map_err(AppError::validation)?;
+        let teacher_id = Self::parse_oid(&dto.teacher_id, "teacher_id")?;
+        self.ensure_teacher(school_id, &teacher_id).await?;
+
+        let overlapping: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (
+                 SELECT 1 FROM teacher_absences
+                 WHERE teacher_id = $1 AND status = 'Active' AND starts_on <= $3 AND ends_on >= $2
+               )"#,
+        )
+        .bind(teacher_id.to_hex())
+        .bind(dto.starts_on)
+        .bind(dto.ends_on)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if overlapping {
+            return Err(AppError::conflict(
+                "The teacher already has an absence in this date range",
+            ));
+        }
+
+        let row = sqlx::query(
+            "INSERT INTO teacher_absences (id, school_id, teacher_id, starts_on, ends_on, reason, created_by) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id, school_id, teacher_id, starts_on, ends_on, \
+             reason, status, created_by, created_at, updated_at",
+        )
+        .bind(Self::new_id())
+        .bind(school_id.to_hex())
+        .bind(teacher_id.to_hex())
+        .bind(dto.starts_on)
+        .bind(dto.ends_on)
+        .bind(dto.reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty()))
+        .bind(created_by.map(|id| id.to_hex()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Self::row_to_absence(row)
+    }
+
+    pub async fn list_absences(
+        &self,
+        school_id: &ObjectId,
+        query: &DateRangeQuery,
+    ) -> Result<Vec<TeacherAbsence>, AppError> {
+        let teacher_id = query
+            .teacher_id
+            .as_deref()
+            .map(|id| Self::parse_oid(id, "teacher_id"))
+            .transpose()?;
+        let rows = sqlx::query(&format!(
+            "{} WHERE school_id = $1 AND status = 'Active' \
+             AND ($2::date IS NULL OR ends_on >= $2) AND ($3::date IS NULL OR starts_on <= $3) \
+             AND ($4::text IS NULL OR teacher_id = $4) ORDER BY starts_on DESC, created_at DESC",
+            Self::ABSENCE_SQL
+        ))
+        .bind(school_id.to_hex())
+        .bind(query.from)
+        .bind(query.to)
+        .bind(teacher_id.map(|id| id.to_hex()))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_absence).collect()
+    }
+
+    pub async fn find_absence(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<TeacherAbsence, AppError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE id = $1 AND school_id = $2",
+            Self::ABSENCE_SQL
+        ))
+        .bind(id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_absence(row),
+            None => Err(AppError::not_found("Teacher absence not found")),
+        }
+    }
+
+    /// Cancels the absence together with the covers arranged for it
+    pub async fn cancel_absence(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<TeacherAbsence, AppError> {
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let row = sqlx::query(
+            "UPDATE teacher_absences SET status = 'Cancelled' \
+             WHERE id = $1 AND school_id = $2 AND status = 'Active' RETURNING id",
+        )
+        .bind(id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(Self::db_error)?;
+        if row.is_none() {
+            return Err(AppError::not_found("Active teacher absence not found"));
+        }
+        sqlx::query("UPDATE teacher_covers SET status = 'Cancelled' WHERE absence_id = $1 AND status = 'Confirmed'")
+            .bind(id.to_hex())
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
+        self.find_absence(school_id, id).await
+    }
+
+    async fn load_calendar(&self, school_id: &ObjectId) -> Result<SchoolCalendar, AppError> {
+        let school_hex = school_id.to_hex();
+        let mut subjects = HashMap::new();
+        for row in sqlx::query(
+            "SELECT id, class_id, name, main_subject_id, teacher_id FROM class_subjects \
+             WHERE school_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            subjects.insert(
+                id,
+                SubjectInfo {
+                    class_id: row.try_get("class_id").map_err(Self::db_error)?,
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    main_subject_id: row.try_get("main_subject_id").map_err(Self::db_error)?,
+                    teacher_id: row.try_get("teacher_id").map_err(Self::db_error)?,
+                },
+            );
+        }
+
+        let mut calendar = SchoolCalendar {
+            terms: Vec::new(),
+            timetables: HashMap::new(),
+            school_days: HashMap::new(),
+            subjects,
+        };
+
+        let Ok((year, _)) = EducationYearService::new(&self.pool)
+            .get_current_year_and_term(
+                None,
+                Some(EducationYearQuery::from_school_context(Some(school_hex))),
+            )
+            .await
+        else {
+            return Ok(calendar);
+        };
+        let Some(year_id) = year.id else {
+            return Ok(calendar);
+        };
+
+        let validation = TimetableValidationService::new(&self.pool);
+        if let Some(school_timetable) = validation.school_timetable(school_id, &year_id).await {
+            calendar.school_days = validation
+                .class_school_days(school_id, &school_timetable)
+                .await?;
+        }
+
+        let timetables = ClassTimetableService::new(&self.pool);
+        for term in &year.terms {
+            calendar.terms.push((
+                term.order,
+                term.start_date.date_naive(),
+                term.end_date.date_naive(),
+            ));
+            let term_timetables = timetables
+                .find_by_school_and_term(school_id, &year_id, term.order)
+                .await?
+                .into_iter()
+                .filter(|timetable| timetable.disabled != Some(true))
+                .collect();
+            calendar.timetables.insert(term.order, term_timetables);
+        }
+        Ok(calendar)
+    }
+
+    /// Timetabled lessons falling on each date of the range, before covers
+    fn lessons(calendar: &SchoolCalendar, from: NaiveDate, to: NaiveDate) -> Vec<DatedPeriod> {
+        let mut lessons = Vec::new();
+        for date in from.iter_days().take_while(|date| *date <= to) {
+            let Some((term_order, _, _)) = calendar
+                .terms
+                .iter()
+                .find(|(_, starts, ends)| *starts <= date && date <= *ends)
+            else {
+                continue;
+            };
+            let weekday = date.weekday();
+            for timetable in calendar.timetables.get(term_order).into_iter().flatten() {
+                let closed = calendar
+                    .school_days
+                    .get(&timetable.class_id)
+                    .and_then(|days| days.iter().find(|schedule| schedule.day == weekday))
+                    .is_some_and(|schedule| {
+                        !schedule.is_school_day
+                            || matches!(schedule.special_type, DaySpecialType::Holiday)
+                    });
+                let Some(day) = timetable
+                    .weekly_schedule
+                    .iter()
+                    .find(|day| day.day == weekday && !day.is_holiday)
+                else {
+                    continue;
+                };
+                let Some(day_start) = day
+                    .start_on
+                    .as_deref()
+                    .and_then(|start| NaiveTime::parse_from_str(start, "%H:%M").ok())
+                else {
+                    continue;
+                };
+                if closed {
+                    continue;
+                }
+
+                for period in &day.periods {
+                    if period.r#type != PeriodType::Subject || period.enabled == Some(false) {
+                        continue;
+                    }
+                    let subject = period
+                        .subject_id
+                        .and_then(|id| calendar.subjects.get(&id.to_hex()));
+                    let starts_at = day_start + Duration::minutes(period.start_offset as i64);
+                    lessons.push(DatedPeriod {
+                        date,
+                        day: weekday,
+                        class_id: timetable.class_id,
+                        class_timetable_id: timetable.id.unwrap_or(timetable.class_id),
+                        period_id: period.period_id,
+                        class_subject_id: period.subject_id,
+                        subject_name: subject
+                            .map(|subject| subject.name.clone())
+                            .or_else(|| period.title.clone()),
+                        starts_at,
+                        ends_at: starts_at + Duration::minutes(period.duration_minutes as i64),
+                        room_id: period.room_id,
+                        teacher_id: period.teacher_id.or_else(|| {
+                            subject
+                                .and_then(|subject| subject.teacher_id.as_deref())
+                                .and_then(|id| ObjectId::parse_str(id).ok())
+                        }),
+                        substituted_teacher_id: None,
+                        cover_id: None,
+                    });
+                }
+            }
+        }
+        lessons
+    }
+
+    async fn confirmed_covers(
+        &self,
+        school_id: &ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<TeacherCover>, AppError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE school_id = $1 AND status = 'Confirmed' AND cover_date BETWEEN $2 AND $3",
+            Self::COVER_SQL
+        ))
+        .bind(school_id.to_hex())
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        rows.into_iter().map(Self::row_to_cover).collect()
+    }
+
+    /// Every lesson of the school in the range with confirmed covers applied
+    async fn school_schedule(
+        &self,
+        school_id: &ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<(SchoolCalendar, Vec<DatedPeriod>), AppError> {
+        let calendar = self.load_calendar(school_id).await?;
+        let mut lessons = Self::lessons(&calendar, from, to);
+        let covers: HashMap<(ObjectId, NaiveDate), TeacherCover> = self
+            .confirmed_covers(school_id, from, to)
+            .await?
+            .into_iter()
+            .map(|cover| ((cover.period_id, cover.cover_date), cover))
+            .collect();
+        for lesson in &mut lessons {
+            if let Some(cover) = covers.get(&(lesson.period_id, lesson.date)) {
+                lesson.substituted_teacher_id = lesson.teacher_id.or(Some(cover.absent_teacher_id));
+                lesson.teacher_id = Some(cover.cover_teacher_id);
+                lesson.cover_id = cover.id;
+            }
+        }
+        lessons.sort_by_key(|lesson| (lesson.date, lesson.starts_at));
+        Ok((calendar, lessons))
+    }
+
+    /// A teacher's lessons in the range: their own, minus those someone
+    /// covers, plus the ones they cover.
+    pub async fn teacher_schedule(
+        &self,
+        school_id: &ObjectId,
+        teacher_id: &ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DatedPeriod>, AppError> {
+        self.ensure_teacher(school_id, teacher_id).await?;
+        let (_, lessons) = self.school_schedule(school_id, from, to).await?;
+        Ok(lessons
+            .into_iter()
+            .filter(|lesson| lesson.teacher_id == Some(*teacher_id))
+            .collect())
+    }
+
+    pub async fn class_schedule(
+        &self,
+        school_id: &ObjectId,
+        class_id: &ObjectId,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DatedPeriod>, AppError> {
+        let (_, lessons) = self.school_schedule(school_id, from, to).await?;
+        Ok(lessons
+            .into_iter()
+            .filter(|lesson| lesson.class_id == *class_id)
+            .collect())
+    }
+
+    async fn candidates(
+        &self,
+        school_id: &ObjectId,
+        calendar: &SchoolCalendar,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Candidate>, AppError> {
+        let school_hex = school_id.to_hex();
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for row in sqlx::query(
+            r#"SELECT t.id, t.user_id, u.name
+               FROM teachers t
+               LEFT JOIN users u ON u.id = t.user_id
+               WHERE t.school_id = $1 AND t.deleted_at IS NULL AND lower(t.status) = 'active'
+               ORDER BY t.id"#,
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            candidates.push(Candidate {
+                id: Self::parse_oid(&id, "teacher_id")?,
+                user_id: row.try_get("user_id").map_err(Self::db_error)?,
+                name: row.try_get("name").map_err(Self::db_error)?,
+                subject_keys: HashSet::new(),
+                classes: HashSet::new(),
+                availability: Vec::new(),
+                absences: Vec::new(),
+            });
+        }
+        let index: HashMap<String, usize> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| (candidate.id.to_hex(), i))
+            .collect();
+
+        let mut teaches: Vec<(String, String)> = calendar
+            .subjects
+            .iter()
+            .filter_map(|(id, subject)| Some((id.clone(), subject.teacher_id.clone()?)))
+            .collect();
+        for row in sqlx::query(
+            "SELECT teacher_id, class_subject_id FROM teacher_subjects WHERE school_id = $1",
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let teacher_id: String = row.try_get("teacher_id").map_err(Self::db_error)?;
+            let subject_id: String = row.try_get("class_subject_id").map_err(Self::db_error)?;
+            teaches.push((subject_id, teacher_id));
+        }
+        for (subject_id, teacher_id) in teaches {
+            let (Some(&i), Some(subject)) =
+                (index.get(&teacher_id), calendar.subjects.get(&subject_id))
+            else {
+                continue;
+            };
+            candidates[i].subject_keys.extend(subject.keys());
+            candidates[i].classes.insert(subject.class_id.clone());
+        }
+
+        for row in
+            sqlx::query("SELECT teacher_id, class_id FROM teacher_classes WHERE school_id = $1")
+                .bind(&school_hex)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(Self::db_error)?
+        {
+            let teacher_id: String = row.try_get("teacher_id").map_err(Self::db_error)?;
+            if let Some(&i) = index.get(&teacher_id) {
+                candidates[i]
+                    .classes
+                    .insert(row.try_get("class_id").map_err(Self::db_error)?);
+            }
+        }
+
+        for row in sqlx::query(
+            r#"SELECT t.id AS teacher_id, ua.day, ua.starts_at, ua.ends_at
+               FROM teachers t
+               JOIN user_availability ua ON ua.user_id = t.user_id
+               WHERE t.school_id = $1 AND t.deleted_at IS NULL"#,
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let teacher_id: String = row.try_get("teacher_id").map_err(Self::db_error)?;
+            let day: String = row.try_get("day").map_err(Self::db_error)?;
+            if let (Some(&i), Ok(day)) = (index.get(&teacher_id), day.parse::<Weekday>()) {
+                candidates[i].availability.push((
+                    day,
+                    row.try_get("starts_at").map_err(Self::db_error)?,
+                    row.try_get("ends_at").map_err(Self::db_error)?,
+                ));
+            }
+        }
+
+        for row in sqlx::query(
+            "SELECT teacher_id, starts_on, ends_on FROM teacher_absences \
+             WHERE school_id = $1 AND status = 'Active' AND starts_on <= $3 AND ends_on >= $2",
+        )
+        .bind(&school_hex)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        {
+            let teacher_id: String = row.try_get("teacher_id").map_err(Self::db_error)?;
+            if let Some(&i) = index.get(&teacher_id) {
+                candidates[i].absences.push((
+                    row.try_get("starts_on").map_err(Self::db_error)?,
+                    row.try_get("ends_on").map_err(Self::db_error)?,
+                ));
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// Free, present teachers for a lesson, qualified ones first, then those
+    /// who know the class, then the least loaded that day.
+    fn suggest(
+        lesson: &DatedPeriod,
+        calendar: &SchoolCalendar,
+        candidates: &[Candidate],
+        lessons: &[DatedPeriod],
+    ) -> Vec<CoverSuggestion> {
+        let absent = timetabled_teacher(lesson);
+        let subject_keys = lesson
+            .class_subject_id
+            .and_then(|id| calendar.subjects.get(&id.to_hex()))
+            .map(SubjectInfo::keys)
+            .unwrap_or_default();
+        let class_hex = lesson.class_id.to_hex();
+
+        let mut suggestions: Vec<CoverSuggestion> = candidates
+            .iter()
+            .filter(|candidate| Some(candidate.id) != absent)
+            .filter(|candidate| !candidate.absent_on(lesson.date))
+            .filter(|candidate| candidate.available(lesson.day, lesson.starts_at, lesson.ends_at))
+            .filter(|candidate| {
+                !lessons.iter().any(|other| {
+                    other.teacher_id == Some(candidate.id)
+                        && overlaps(other, lesson.date, lesson.starts_at, lesson.ends_at)
+                })
+            })
+            .map(|candidate| CoverSuggestion {
+                teacher_id: candidate.id,
+                name: candidate.name.clone(),
+                qualified: subject_keys
+                    .iter()
+                    .any(|key| candidate.subject_keys.contains(key)),
+                knows_class: candidate.classes.contains(&class_hex),
+                periods_that_day: lessons
+                    .iter()
+                    .filter(|other| {
+                        other.date == lesson.date && other.teacher_id == Some(candidate.id)
+                    })
+                    .count(),
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.qualified
+                .cmp(&a.qualified)
+                .then(b.knows_class.cmp(&a.knows_class))
+                .then(a.periods_that_day.cmp(&b.periods_that_day))
+                .then(a.teacher_id.to_hex().cmp(&b.teacher_id.to_hex()))
+        });
+        suggestions.truncate(MAX_COVER_SUGGESTIONS);
+        suggestions
+    }
+
+    /// The absent teacher's lessons with their covers, and suggestions for
+    /// the ones nobody covers yet.
+    pub async fn cover_plan(
+        &self,
+        school_id: &ObjectId,
+        absence_id: &ObjectId,
+    ) -> Result<AbsenceCoverPlan, AppError> {
+        let absence = self.find_absence(school_id, absence_id).await?;
+        if absence.status == AbsenceStatus::Cancelled {
+            return Ok(AbsenceCoverPlan {
+                absence,
+                periods: Vec::new(),
+                covered: 0,
+                uncovered: 0,
+            });
+        }
+
+        let (calendar, lessons) = self
+            .school_schedule(school_id, absence.starts_on, absence.ends_on)
+            .await?;
+        let candidates = self
+            .candidates(school_id, &calendar, absence.starts_on, absence.ends_on)
+            .await?;
+
+        let periods: Vec<AffectedPeriod> = lessons
+            .iter()
+            .filter(|lesson| timetabled_teacher(lesson) == Some(absence.teacher_id))
+            .map(|lesson| AffectedPeriod {
+                period: lesson.clone(),
+                suggestions: if lesson.cover_id.is_some() {
+                    Vec::new()
+                } else {
+                    Self::suggest(lesson, &calendar, &candidates, &lessons)
+                },
+            })
+            .collect();
+        let covered = periods
+            .iter()
+            .filter(|period| period.period.cover_id.is_some())
+            .count();
+        Ok(AbsenceCoverPlan {
+            absence,
+            uncovered: periods.len() - covered,
+            covered,
+            periods,
+        })
+    }
+
+    /// Confirms a cover and returns it with the cover teacher's user id,
+    /// which is who gets notified.
+    pub async fn confirm_cover(
+        &self,
+        school_id: &ObjectId,
+        absence_id: &ObjectId,
+        dto: ConfirmCover,
+        confirmed_by: Option<&ObjectId>,
+    ) -> Result<(TeacherCover, Option<String>), AppError> {
+        let absence = self.find_absence(school_id, absence_id).await?;
+        if absence.status == AbsenceStatus::Cancelled {
+            return Err(AppError::bad_request("This absence has been cancelled"));
+        }
+        if dto.cover_date < absence.starts_on || dto.cover_date > absence.ends_on {
+            return Err(AppError::bad_request("cover_date is outside the absence"));
+        }
+        let period_id = Self::parse_oid(&dto.period_id, "period_id")?;
+        let cover_teacher_id = Self::parse_oid(&dto.cover_teacher_id, "cover_teacher_id")?;
+        if cover_teacher_id == absence.teacher_id {
+            return Err(AppError::bad_request(
+                "The absent teacher cannot cover their own lesson",
+            ));
+        }
+
+        let (calendar, lessons) = self
+            .school_schedule(school_id, dto.cover_date, dto.cover_date)
+            .await?;
+        let Some(lesson) = lessons.iter().find(|lesson| {
+            lesson.period_id == period_id && timetabled_teacher(lesson) == Some(absence.teacher_id)
+        }) else {
+            return Err(AppError::bad_request(
+                "The absent teacher has no such lesson on that date",
+            ));
+        };
+        if lesson.cover_id.is_some() {
+            return Err(AppError::conflict("This lesson is already covered"));
+        }
+
+        let candidates = self
+            .candidates(school_id, &calendar, dto.cover_date, dto.cover_date)
+            .await?;
+        let Some(candidate) = candidates.iter().find(|c| c.id == cover_teacher_id) else {
+            return Err(AppError::not_found(
+                "Cover teacher not found in this school",
+            ));
+        };
+        if candidate.absent_on(dto.cover_date) {
+            return Err(AppError::bad_request(
+                "The cover teacher is absent on that date",
+            ));
+        }
+        if let Some(clash) = lessons.iter().find(|other| {
+            other.teacher_id == Some(cover_teacher_id)
+                && overlaps(other, lesson.date, lesson.starts_at, lesson.ends_at)
+        }) {
+            return Err(AppError::conflict(format!(
+                "The cover teacher already teaches at {} on {}",
+                clash.starts_at.format("%H:%M"),
+                clash.date
+            )));
+        }
+
+        let row = sqlx::query(
+            r#"INSERT INTO teacher_covers (
+                 id, school_id, absence_id, class_timetable_id, class_id, period_id,
+                 class_subject_id, cover_date, starts_at, ends_at, absent_teacher_id,
+                 cover_teacher_id, note, confirmed_by
+               ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+               RETURNING id, school_id, absence_id, class_timetable_id, class_id, period_id,
+                 class_subject_id, cover_date, starts_at, ends_at, absent_teacher_id,
+                 cover_teacher_id, status, note, created_at"#,
+        )
+        .bind(Self::new_id())
+        .bind(school_id.to_hex())
+        .bind(absence_id.to_hex())
+        .bind(lesson.class_timetable_id.to_hex())
+        .bind(lesson.class_id.to_hex())
+        .bind(period_id.to_hex())
+        .bind(lesson.class_subject_id.map(|id| id.to_hex()))
+        .bind(lesson.date)
+        .bind(lesson.starts_at)
+        .bind(lesson.ends_at)
+        .bind(absence.teacher_id.to_hex())
+        .bind(cover_teacher_id.to_hex())
+        .bind(
+            dto.note
+                .map(|note| note.trim().to_string())
+                .filter(|note| !note.is_empty()),
+        )
+        .bind(confirmed_by.map(|id| id.to_hex()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|error| match error {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                AppError::conflict("This lesson is already covered")
+            }
+            other => Self::db_error(other),
+        })?;
+        Ok((Self::row_to_cover(row)?, candidate.user_id.clone()))
+    }
+
+    pub async fn cancel_cover(
+        &self,
+        school_id: &ObjectId,
+        id: &ObjectId,
+    ) -> Result<TeacherCover, AppError> {
+        let row = sqlx::query(
+            r#"UPDATE teacher_covers SET status = 'Cancelled'
+               WHERE id = $1 AND school_id = $2 AND status = 'Confirmed'
+               RETURNING id, school_id, absence_id, class_timetable_id, class_id, period_id,
+                 class_subject_id, cover_date, starts_at, ends_at, absent_teacher_id,
+                 cover_teacher_id, status, note, created_at"#,
+        )
+        .bind(id.to_hex())
+        .bind(school_id.to_hex())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        match row {
+            Some(row) => Self::row_to_cover(row),
+            None => Err(AppError::not_found("Confirmed cover not found")),
+        }
+    }
+}
@@ -33,9 +33,9 @@ impl TeacherQuery {
         context_school_id: Option<String>,
     ) -> Result<Self, AppError> {
         if query.field.len() != query.value.len() {
-            return Err(AppError {
-                message: "Number of fields must match number of values".into(),
-            });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for id in &query.by_ids {
             parse_object_id_value(id)?;
@@ -74,9 +74,7 @@ impl TeacherService {
     }
 
     fn db_error(error: sqlx::Error) -> AppError {
-        AppError {
-            message: format!("PostgreSQL Error: {}", error),
-        }
+        AppError::from(error)
     }
 
     fn new_id() -> String {
@@ -88,8 +86,8 @@ impl TeacherService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -214,15 +212,16 @@ impl TeacherService {
                         .push(")");
                 }
                 "is_active" => {
-                    let parsed = value.parse::<bool>().map_err(|_| AppError {
-                        message: "Invalid is_active filter value".into(),
-                    })?;
+                    let parsed = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid is_active filter value"))?;
                     sql.push(" AND is_active = ").push_bind(parsed);
                 }
                 _ => {
-                    return Err(AppError {
-                        message: format!("Unsupported teacher filter field: {}", field),
-                    });
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported teacher filter field: {}",
+                        field
+                    )));
                 }
             }
         }
@@ -425,29 +424,28 @@ impl TeacherService {
         _state: Option<&AppState>,
     ) -> Result<Teacher, AppError> {
         self.ensure_indexes().await?;
-        is_valid_name(&dto.name).map_err(|e| AppError { message: e })?;
-        is_valid_email(&dto.email).map_err(|e| AppError { message: e })?;
+        is_valid_name(&dto.name).map_err(AppError::bad_request)?;
+        is_valid_email(&dto.email).map_err(AppError::bad_request)?;
 
         let school_id = dto
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         if let Some(existing) = self
             .email_exists(&dto.email, Some(&school_id), None)
             .await?
         {
-            return Err(AppError {
-                message: format!("Email already exists: {}", existing.email),
-            });
+            return Err(AppError::conflict(format!(
+                "Email already exists: {}",
+                existing.email
+            )));
         }
 
         if let Some(image_data) = dto.image.clone() {
             let cloud_res = CloudinaryService::upload_to_cloudinary(&image_data)
                 .await
-                .map_err(|e| AppError { message: e })?;
+                .map_err(AppError::bad_request)?;
             dto.image_id = Some(cloud_res.public_id);
             dto.image = Some(cloud_res.secure_url);
         }
@@ -566,9 +564,7 @@ impl TeacherService {
 
         match row {
             Some(row) => Ok(self.teacher_from_row(row).await?),
-            None => Err(AppError {
-                message: "Teacher not found".into(),
-            }),
+            None => Err(AppError::not_found("Teacher not found")),
         }
     }
 
@@ -630,10 +626,10 @@ impl TeacherService {
 
     pub async fn update(&self, id: &IdType, update: &UpdateTeacher) -> Result<Teacher, AppError> {
         if let Some(ref name) = update.name {
-            is_valid_name(name).map_err(|e| AppError { message: e })?;
+            is_valid_name(name).map_err(AppError::bad_request)?;
         }
         if let Some(ref email) = update.email {
-            is_valid_email(email).map_err(|e| AppError { message: e })?;
+            is_valid_email(email).map_err(AppError::bad_request)?;
         }
 
         let existing = self.find_one(Some(id), None).await?;
@@ -642,9 +638,7 @@ impl TeacherService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
 
         if let Some(ref email) = update.email {
             if existing.email != *email
@@ -653,9 +647,10 @@ impl TeacherService {
                     .await?
                     .is_some()
             {
-                return Err(AppError {
-                    message: format!("Email already exists: {}", email),
-                });
+                return Err(AppError::conflict(format!(
+                    "Email already exists: {}",
+                    email
+                )));
             }
         }
 
@@ -669,7 +664,7 @@ impl TeacherService {
                 }
                 let cloud_res = CloudinaryService::upload_to_cloudinary(&new_image)
                     .await
-                    .map_err(|e| AppError { message: e })?;
+                    .map_err(AppError::bad_request)?;
                 update_data.image_id = Some(Some(cloud_res.public_id));
                 update_data.image = Some(Some(cloud_res.secure_url));
             }
@@ -770,9 +765,7 @@ impl TeacherService {
             && update_data.subject_ids.is_none()
             && update_data.tags.is_none()
         {
-            return Err(AppError {
-                message: "No valid fields to update".into(),
-            });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         let teacher = self.find_one(Some(id), None).await?;
@@ -886,9 +879,7 @@ impl TeacherService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let mut ids = teacher.subject_ids.unwrap_or_default();
         for subject_id in subject_ids {
             if !ids.contains(&subject_id) {
@@ -918,9 +909,7 @@ impl TeacherService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let mut ids = teacher.class_ids.unwrap_or_default();
         for class_id in class_ids {
             if !ids.contains(&class_id) {
@@ -944,9 +933,7 @@ impl TeacherService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let ids = teacher
             .class_ids
             .unwrap_or_default()
@@ -970,9 +957,7 @@ impl TeacherService {
             .school_id
             .as_ref()
             .map(|id| id.to_hex())
-            .ok_or_else(|| AppError {
-                message: "school_id is required".into(),
-            })?;
+            .ok_or_else(|| AppError::field("school_id", "school_id is required"))?;
         let ids = teacher
             .subject_ids
             .unwrap_or_default()
@@ -21,7 +21,7 @@ impl TemplateSubjectService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -33,8 +33,8 @@ impl TemplateSubjectService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -102,7 +102,7 @@ impl TemplateSubjectService {
         }
 
         if q.field.len() != q.value.len() {
-            return Err(AppError { message: "Number of fields must match values".into() });
+            return Err(AppError::bad_request("Number of fields must match values"));
         }
         for (field, value) in q.field.iter().zip(q.value.iter()) {
             match field.as_str() {
@@ -111,10 +111,17 @@ impl TemplateSubjectService {
                 "name" => { sql.push(" AND lower(name) = lower(").push_bind(value).push(")"); }
                 "category" => { sql.push(" AND lower(category) = lower(").push_bind(value).push(")"); }
                 "disable" => {
-                    let b = value.parse::<bool>().map_err(|_| AppError { message: "Invalid disable".into() })?;
+                    let b = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid disable"))?;
                     sql.push(" AND disable = ").push_bind(b);
                 }
-                _ => return Err(AppError { message: format!("Unsupported filter: {}", field) }),
+                _ => {
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported filter: {}",
+                        field
+                    )))
+                }
             }
         }
         Ok(())
@@ -122,9 +129,10 @@ impl TemplateSubjectService {
 
     pub async fn create(&self, dto: TemplateSubject) -> Result<TemplateSubject, AppError> {
         if self.find_one_by_code(&dto.code).await.is_ok() {
-            return Err(AppError {
-                message: format!("Subject code is ready exit try other not {}", dto.code),
-            });
+            return Err(AppError::bad_request(format!(
+                "Subject code is ready exit try other not {}",
+                dto.code
+            )));
         }
 
         let id = Self::new_id();
@@ -166,7 +174,7 @@ impl TemplateSubjectService {
             .map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_subject(r),
-            None => Err(AppError { message: "Template subject not found".to_string() }),
+            None => Err(AppError::not_found("Template subject not found")),
         }
     }
 
@@ -178,7 +186,7 @@ impl TemplateSubjectService {
             .map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_subject(r),
-            None => Err(AppError { message: "Template subject not found by code".to_string() }),
+            None => Err(AppError::not_found("Template subject not found by code")),
         }
     }
 
@@ -219,7 +227,10 @@ impl TemplateSubjectService {
                 let existing_id = sub.id.as_ref().map(|o| o.to_hex());
                 let target_id = Some(Self::id_to_string(id)?);
                 if existing_id != target_id {
-                    return Err(AppError { message: format!("Subject code is ready exit try other not {}", sub.code) });
+                    return Err(AppError::bad_request(format!(
+                        "Subject code is ready exit try other not {}",
+                        sub.code
+                    )));
                 }
             }
         }
@@ -249,7 +260,9 @@ impl TemplateSubjectService {
             touched = true;
         }
 
-        if !touched { return Err(AppError { message: "No valid fields to update".into() }); }
+        if !touched {
+            return Err(AppError::bad_request("No valid fields to update"));
+        }
 
         sql.push(" WHERE id = ").push_bind(&id_str);
         sql.build().execute(&self.pool).await.map_err(Self::db_error)?;
@@ -0,0 +1,192 @@
+use sqlx::PgPool;
+
+use crate::{domain::tenant::TenantEntity, errors::AppError};
+
+/// Resolves which school a record belongs to and whether a user is an
+/// active member of that school. Tenant guards build on this instead of
+/// trusting the school id the client sends.
+pub struct TenantService {
+    pub pool: PgPool,
+}
+
+impl TenantService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    /// School owning the record, or not found when the record does not exist
+    pub async fn school_of(&self, entity: TenantEntity, id: &str) -> Result<String, AppError> {
+        let sql = match entity {
+            TenantEntity::School => "SELECT id FROM schools WHERE id = $1",
+            TenantEntity::Class => "SELECT school_id FROM classes WHERE id = $1",
+            TenantEntity::ClassSubject => "SELECT school_id FROM class_subjects WHERE id = $1",
+            TenantEntity::Teacher => "SELECT school_id FROM teachers WHERE id = $1",
+            TenantEntity::Exam => "SELECT school_id FROM exams WHERE id = $1",
+            // Either id a student goes by: the enrollment or the profile,
+            // preferring the profile's live, active enrollment. Deleted
+            // enrollments still count so they can be restored.
+            TenantEntity::Student => {
+                "SELECT school_id FROM student_school_enrollments \
+                 WHERE id = $1 OR student_id = $1 \
+                 ORDER BY deleted_at IS NULL DESC, is_active DESC, created_at DESC LIMIT 1"
+            }
+        };
+
+        sqlx::query_scalar::<_, String>(sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(Self::db_error)?
+            .ok_or_else(|| AppError::not_found(format!("{} not found", entity.as_str())))
+    }
+
+    /// Whether the user holds an active membership of the school
+    pub async fn is_member(&self, user_id: &str, school_id: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM school_memberships
+              WHERE user_id = $1 AND school_id = $2
+                AND status = 'active' AND ended_at IS NULL
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(school_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Whether the user teaches the class: as its class teacher, through one
+    /// of its subjects, or by an explicit class grant. Teaching a main class
+    /// covers its subclasses.
+    pub async fn teaches_class(&self, user_id: &str, class_id: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            WITH target AS (
+              SELECT id, parent_class_id FROM classes WHERE id = $1
+            ), covered AS (
+              SELECT id FROM target
+              UNION
+              SELECT parent_class_id FROM target WHERE parent_class_id IS NOT NULL
+            )
+            SELECT EXISTS (
+              SELECT 1 FROM classes c
+              JOIN teachers t ON t.id = c.class_teacher_id
+              WHERE c.id IN (SELECT id FROM covered) AND t.user_id = $2
+            ) OR EXISTS (
+              SELECT 1 FROM class_subjects cs
+              LEFT JOIN teachers t ON t.id = cs.teacher_id
+              WHERE cs.class_id IN (SELECT id FROM covered)
+                AND cs.deleted_at IS NULL
+                AND (cs.teacher_user_id = $2 OR t.user_id = $2)
+            ) OR EXISTS (
+              SELECT 1 FROM teacher_subjects ts
+              JOIN teachers t ON t.id = ts.teacher_id
+              JOIN class_subjects cs ON cs.id = ts.class_subject_id
+              WHERE cs.class_id IN (SELECT id FROM covered) AND t.user_id = $2
+            ) OR EXISTS (
+              SELECT 1 FROM school_user_accessible_classes ac
+              WHERE ac.class_id IN (SELECT id FROM covered) AND ac.user_id = $2
+            )
+            "#,
+        )
+        .bind(class_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Whether the user teaches the class subject, directly or through a
+    /// teacher subject assignment
+    pub async fn teaches_subject(
+        &self,
+        user_id: &str,
+        class_subject_id: &str,
+    ) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM class_subjects cs
+              LEFT JOIN teachers t ON t.id = cs.teacher_id
+              WHERE cs.id = $1 AND cs.deleted_at IS NULL
+                AND (cs.teacher_user_id = $2 OR t.user_id = $2)
+            ) OR EXISTS (
+              SELECT 1 FROM teacher_subjects ts
+              JOIN teachers t ON t.id = ts.teacher_id
+              WHERE ts.class_subject_id = $1 AND t.user_id = $2
+            )
+            "#,
+        )
+        .bind(class_subject_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// School and class (the subclass when there is one) of the student's
+    /// live enrollment, by enrollment or profile id, preferring the active one
+    pub async fn student_placement(
+        &self,
+        student_id: &str,
+    ) -> Result<(String, Option<String>), AppError> {
+        sqlx::query_as(
+            r#"
+            SELECT school_id, COALESCE(subclass_id, class_id)
+            FROM student_school_enrollments
+            WHERE (id = $1 OR student_id = $1) AND deleted_at IS NULL
+            ORDER BY is_active DESC, created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(student_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| AppError::not_found("student not found"))
+    }
+
+    /// Whether the student record, by enrollment or profile id, is the
+    /// user's own
+    pub async fn is_student_user(&self, user_id: &str, student_id: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM student_profiles sp
+              LEFT JOIN student_school_enrollments sse ON sse.student_id = sp.id
+              WHERE (sp.id = $1 OR sse.id = $1) AND sp.user_id = $2
+            )
+            "#,
+        )
+        .bind(student_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Whether the user created the student's enrollment
+    pub async fn created_student(&self, user_id: &str, student_id: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM student_school_enrollments
+              WHERE (id = $1 OR student_id = $1) AND creator_id = $2
+                AND deleted_at IS NULL
+            )
+            "#,
+        )
+        .bind(student_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use sqlx::{PgPool, Row};
+
+use crate::{
+    domain::{
+        class_timetable::ClassTimetable,
+        school_timetable::{DailySchoolSchedule, SchoolTimetable, TimetableOverrideType},
+        timetable_validation::{TimetableValidationQuery, TimetableValidationReport},
+    },
+    errors::AppError,
+    handler::timetable_clash_handler::{find_clashes, ClashContext, ClashSubject},
+    services::{
+        class_timetable_service::ClassTimetableService,
+        education_year_service::{EducationYearQuery, EducationYearService},
+        school_timetable_service::SchoolTimetableService,
+    },
+    utils::object_id::ObjectId,
+};
+
+/// Hard clashes listed in the error when a save is rejected
+const MAX_REPORTED_CLASHES: usize = 5;
+
+pub struct TimetableValidationService {
+    pub pool: PgPool,
+}
+
+impl TimetableValidationService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
+        })
+    }
+
+    /// The school timetable for the year, falling back to the school's
+    /// year-less one and then to any it has.
+    pub(crate) async fn school_timetable(
+        &self,
+        school_id: &ObjectId,
+        education_year_id: &ObjectId,
+    ) -> Option<SchoolTimetable> {
+        let service = SchoolTimetableService::new(&self.pool);
+        if let Ok(timetable) = service
+            .find_by_school_and_academic_year(school_id, &Some(*education_year_id))
+            .await
+        {
+            return Some(timetable);
+        }
+        if let Ok(timetable) = service
+            .find_by_school_and_academic_year(school_id, &None)
+            .await
+        {
+            return Some(timetable);
+        }
+        service.find_one(None, Some(&school_id.to_hex())).await.ok()
+    }
+
+    /// The school day layout each class of the school follows: the default
+    /// week with any trade or sector override applied.
+    pub(crate) async fn class_school_days(
+        &self,
+        school_id: &ObjectId,
+        school_timetable: &SchoolTimetable,
+    ) -> Result<HashMap<ObjectId, Vec<DailySchoolSchedule>>, AppError> {
+        let mut school_days = HashMap::new();
+        let class_rows = sqlx::query(
+            r#"SELECT c.id, c.trade_id, t.sector_id
+               FROM classes c
+               LEFT JOIN trades t ON t.id = c.trade_id
+               WHERE c.school_id = $1 AND c.deleted_at IS NULL"#,
+        )
+        .bind(school_id.to_hex())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        for row in class_rows {
+            let class_id: String = row.try_get("id").map_err(Self::db_error)?;
+            let trade_id: Option<String> = row.try_get("trade_id").map_err(Self::db_error)?;
+            let sector_id: Option<String> = row.try_get("sector_id").map_err(Self::db_error)?;
+
+            let mut days = school_timetable.default_weekly_schedule.clone();
+            for timetable_override in school_timetable.overrides.iter().flatten() {
+                let target = match timetable_override.r#type {
+                    TimetableOverrideType::Trade => trade_id.as_deref(),
+                    TimetableOverrideType::Sector => sector_id.as_deref(),
+                };
+                let applies = target.is_some_and(|target| {
+                    timetable_override
+                        .applies_to
+                        .iter()
+                        .any(|id| id.to_hex() == target)
+                });
+                if !applies {
+                    continue;
+                }
+                for day in &timetable_override.weekly_schedule {
+                    days.retain(|existing| existing.day != day.day);
+                    days.push(day.clone());
+                }
+            }
+            school_days.insert(Self::parse_oid(&class_id, "class_id")?, days);
+        }
+        Ok(school_days)
+    }
+
+    async fn load_context(
+        &self,
+        school_id: &ObjectId,
+        education_year_id: &ObjectId,
+    ) -> Result<ClashContext, AppError> {
+        let school_hex = school_id.to_hex();
+        let subject_rows = sqlx::query(
+            r#"SELECT id, class_id, name, teacher_id, estimated_hours
+               FROM class_subjects
+               WHERE school_id = $1 AND deleted_at IS NULL AND NOT coalesce(disable, false)"#,
+        )
+        .bind(&school_hex)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        let mut subjects = HashMap::with_capacity(subject_rows.len());
+        for row in subject_rows {
+            let id: String = row.try_get("id").map_err(Self::db_error)?;
+            let class_id: String = row.try_get("class_id").map_err(Self::db_error)?;
+            let teacher_id: Option<String> = row.try_get("teacher_id").map_err(Self::db_error)?;
+            subjects.insert(
+                Self::parse_oid(&id, "class_subject_id")?,
+                ClashSubject {
+                    class_id: Self::parse_oid(&class_id, "class_id")?,
+                    name: row.try_get("name").map_err(Self::db_error)?,
+                    teacher_id: teacher_id
+                        .as_deref()
+                        .map(|id| Self::parse_oid(id, "teacher_id"))
+                        .transpose()?,
+                    estimated_hours: row.try_get("estimated_hours").map_err(Self::db_error)?,
+                },
+            );
+        }
+
+        let school_days = match self.school_timetable(school_id, education_year_id).await {
+            Some(school_timetable) => self.class_school_days(school_id, &school_timetable).await?,
+            None => HashMap::new(),
+        };
+
+        Ok(ClashContext {
+            subjects,
+            school_days,
+        })
+    }
+
+    /// Report every clash and warning across a school's class timetables for one term.
+    pub async fn validate_term(
+        &self,
+        school_id: &ObjectId,
+        query: TimetableValidationQuery,
+    ) -> Result<TimetableValidationReport, AppError> {
+        let (education_year_id, current_term) = match &query.education_year_id {
+            Some(id) => (Self::parse_oid(id, "education_year_id")?, None),
+            None => {
+                let (year, term) = EducationYearService::new(&self.pool)
+                    .get_current_year_and_term(
+                        None,
+                        Some(EducationYearQuery::from_school_context(Some(
+                            school_id.to_hex(),
+                        ))),
+                    )
+                    .await?;
+                let year_id = year
+                    .id
+                    .ok_or_else(|| AppError::bad_request("Current education year has no id"))?;
+                (year_id, term.map(|t| t.order))
+            }
+        };
+        let term_order = query.term_order.or(current_term).unwrap_or(1);
+
+        let timetables = ClassTimetableService::new(&self.pool)
+            .find_by_school_and_term(school_id, &education_year_id, term_order)
+            .await?
+            .into_iter()
+            .filter(|timetable| timetable.disabled != Some(true))
+            .collect::<Vec<_>>();
+        let context = self.load_context(school_id, &education_year_id).await?;
+        let issues = find_clashes(&timetables, &context);
+        let hard_clashes = issues.iter().filter(|issue| issue.hard).count();
+
+        Ok(TimetableValidationReport {
+            school_id: *school_id,
+            education_year_id,
+            term_order,
+            timetables_checked: timetables.len(),
+            hard_clashes,
+            warnings: issues.len() - hard_clashes,
+            issues,
+        })
+    }
+
+    /// Reject a save when it introduces hard clashes for its class. Clashes
+    /// the stored version already had do not block the save.
+    pub async fn check_save(
+        &self,
+        candidate: &ClassTimetable,
+        previous: Option<&ClassTimetable>,
+    ) -> Result<(), AppError> {
+        let school_id: Option<String> =
+            sqlx::query_scalar("SELECT school_id FROM classes WHERE id = $1")
+                .bind(candidate.class_id.to_hex())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::db_error)?;
+        let Some(school_id) = school_id else {
+            return Err(AppError::not_found("Class not found"));
+        };
+        let school_id = Self::parse_oid(&school_id, "school_id")?;
+
+        let others = ClassTimetableService::new(&self.pool)
+            .find_by_school_and_term(
+                &school_id,
+                &candidate.education_year_id,
+                candidate.term_order,
+            )
+            .await?
+            .into_iter()
+            .filter(|timetable| {
+                timetable.class_id != candidate.class_id && timetable.id != candidate.id
+            })
+            .filter(|timetable| timetable.disabled != Some(true))
+            .collect::<Vec<_>>();
+        let context = self
+            .load_context(&school_id, &candidate.education_year_id)
+            .await?;
+
+        let hard_for_class = |timetable: &ClassTimetable| {
+            let mut timetables = others.clone();
+            timetables.push(timetable.clone());
+            find_clashes(&timetables, &context)
+                .into_iter()
+                .filter(|issue| issue.hard && issue.class_id == timetable.class_id)
+                .collect::<Vec<_>>()
+        };
+
+        let known = previous
+            .filter(|previous| {
+                previous.class_id == candidate.class_id
+                    && previous.education_year_id == candidate.education_year_id
+                    && previous.term_order == candidate.term_order
+            })
+            .map(|previous| {
+                hard_for_class(previous)
+                    .into_iter()
+                    .map(|issue| issue.key())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let introduced = hard_for_class(candidate)
+            .into_iter()
+            .filter(|issue| !known.contains(&issue.key()))
+            .collect::<Vec<_>>();
+        if introduced.is_empty() {
+            return Ok(());
+        }
+
+        let mut messages = introduced
+            .iter()
+            .take(MAX_REPORTED_CLASHES)
+            .map(|issue| issue.message.clone())
+            .collect::<Vec<_>>();
+        if introduced.len() > MAX_REPORTED_CLASHES {
+            messages.push(format!(
+                "and {} more",
+                introduced.len() - MAX_REPORTED_CLASHES
+            ));
+        }
+        Err(AppError::bad_request(format!(
+            "Timetable clashes: {}",
+            messages.join("; ")
+        )))
+    }
+}
@@ -24,7 +24,7 @@ impl TradeService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -36,8 +36,8 @@ impl TradeService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -203,7 +203,9 @@ impl TradeService {
         }
 
         if q.field.len() != q.value.len() {
-            return Err(AppError { message: "Number of fields must match number of values".into() });
+            return Err(AppError::bad_request(
+                "Number of fields must match number of values",
+            ));
         }
         for (field, value) in q.field.iter().zip(q.value.iter()) {
             match field.as_str() {
@@ -214,10 +216,17 @@ impl TradeService {
                 "username" => { sql.push(format!(" AND lower({t}username) = lower(")).push_bind(value).push(")"); }
                 "type" => { sql.push(format!(" AND lower({t}type) = lower(")).push_bind(value).push(")"); }
                 "disable" => {
-                    let b = value.parse::<bool>().map_err(|_| AppError { message: "Invalid disable".into() })?;
+                    let b = value
+                        .parse::<bool>()
+                        .map_err(|_| AppError::bad_request("Invalid disable"))?;
                     sql.push(format!(" AND {t}disable = ")).push_bind(b);
                 }
-                _ => return Err(AppError { message: format!("Unsupported filter field: {}", field) }),
+                _ => {
+                    return Err(AppError::bad_request(format!(
+                        "Unsupported filter field: {}",
+                        field
+                    )))
+                }
             }
         }
         Ok(())
@@ -225,10 +234,16 @@ impl TradeService {
 
     pub async fn create(&self, dto: Trade) -> Result<Trade, AppError> {
         if self.find_one_by_name(&dto.name).await?.is_some() {
-            return Err(AppError { message: format!("Trade name already exists: {}", dto.name) });
+            return Err(AppError::conflict(format!(
+                "Trade name already exists: {}",
+                dto.name
+            )));
         }
         if self.find_one_by_username(&dto.username).await?.is_some() {
-            return Err(AppError { message: format!("Trade username already exists: {}", dto.username) });
+            return Err(AppError::conflict(format!(
+                "Trade username already exists: {}",
+                dto.username
+            )));
         }
 
         let id = Self::new_id();
@@ -339,7 +354,7 @@ impl TradeService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_trade(&r),
-            None => Err(AppError { message: "Trade not found".into() }),
+            None => Err(AppError::not_found("Trade not found")),
         }
     }
 
@@ -358,7 +373,7 @@ impl TradeService {
         let row = sql.build().fetch_optional(&self.pool).await.map_err(Self::db_error)?;
         match row {
             Some(r) => Self::row_to_trade_with_others(r),
-            None => Err(AppError { message: "Trade not found".into() }),
+            None => Err(AppError::not_found("Trade not found")),
         }
     }
 
@@ -435,12 +450,20 @@ impl TradeService {
 
         if let Some(ref name) = update.name {
             if existing.name != *name && self.find_one_by_name(name).await?.is_some() {
-                return Err(AppError { message: format!("Trade name already exists: {}", name) });
+                return Err(AppError::conflict(format!(
+                    "Trade name already exists: {}",
+                    name
+                )));
             }
         }
         if let Some(ref username) = update.username {
-            if existing.username != *username && self.find_one_by_username(username).await?.is_some() {
-                return Err(AppError { message: format!("Trade username already exists: {}", username) });
+            if existing.username != *username
+                && self.find_one_by_username(username).await?.is_some()
+            {
+                return Err(AppError::conflict(format!(
+                    "Trade username already exists: {}",
+                    username
+                )));
             }
         }
 
@@ -468,7 +491,7 @@ impl TradeService {
         }
 
         if !touched {
-            return Err(AppError { message: "No valid fields to update".into() });
+            return Err(AppError::bad_request("No valid fields to update"));
         }
 
         sql.push(" WHERE id = ").push_bind(&id_str);
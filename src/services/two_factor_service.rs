@@ -0,0 +1,441 @@
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+
+use crate::{
+    domain::{
+        common_details::UserRole,
+        two_factor::{TwoFactorChallenge, TwoFactorSetup, TwoFactorStatus},
+    },
+    errors::{AppError, ErrorCode},
+    utils::{object_id::ObjectId, totp},
+};
+
+const ISSUER: &str = "Space Together";
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+/// How long a user's wrong codes, across challenges, count towards the limit
+const ATTEMPT_WINDOW_MINUTES: i32 = 15;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// TOTP second factor with one-time recovery codes. Login hands out a
+/// short-lived challenge instead of tokens until the second factor is given.
+pub struct TwoFactorService {
+    pub pool: PgPool,
+}
+
+impl TwoFactorService {
+    pub fn new(pool: &PgPool) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    fn db_error(error: sqlx::Error) -> AppError {
+        AppError::from(error)
+    }
+
+    fn hash(value: &str) -> String {
+        hex::encode(Sha256::digest(value.as_bytes()))
+    }
+
+    /// Recovery codes are compared without case, spaces or dashes
+    fn normalize_recovery_code(code: &str) -> String {
+        code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+
+    fn new_recovery_code() -> String {
+        let raw = hex::encode(rand::random::<[u8; 5]>());
+        format!("{}-{}", &raw[..5], &raw[5..])
+    }
+
+    pub async fn is_enabled(&self, user_id: &str) -> Result<bool, AppError> {
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM user_two_factor \
+             WHERE user_id = $1 AND enabled_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    /// Admins and staff must use a second factor once a school they are an
+    /// active member of requires it
+    pub async fn is_required(
+        &self,
+        user_id: &str,
+        role: Option<&UserRole>,
+    ) -> Result<bool, AppError> {
+        if !matches!(role, Some(UserRole::ADMIN) | Some(UserRole::SCHOOLSTAFF)) {
+            return Ok(false);
+        }
+        sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+              SELECT 1 FROM school_memberships m
+              JOIN schools s ON s.id = m.school_id
+              WHERE m.user_id = $1
+                AND m.status = 'active' AND m.ended_at IS NULL
+                AND s.require_two_factor
+            )
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)
+    }
+
+    pub async fn status(
+        &self,
+        user_id: &str,
+        role: Option<&UserRole>,
+    ) -> Result<TwoFactorStatus, AppError> {
+        let recovery_codes_left: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM user_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(TwoFactorStatus {
+            enabled: self.is_enabled(user_id).await?,
+            required: self.is_required(user_id, role).await?,
+            recovery_codes_left,
+        })
+    }
+
+    /// Start (or restart) enrolment with a fresh secret. It only takes
+    /// effect once a code from it is confirmed.
+    pub async fn begin_setup(
+        &self,
+        user_id: &str,
+        account: &str,
+    ) -> Result<TwoFactorSetup, AppError> {
+        let secret = totp::generate_secret();
+        let stored = sqlx::query(
+            "INSERT INTO user_two_factor (user_id, secret) VALUES ($1, $2) \
+             ON CONFLICT (user_id) DO UPDATE \
+             SET secret = excluded.secret, last_used_step = NULL, updated_at = now() \
+             WHERE user_two_factor.enabled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(&secret)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if stored.rows_affected() == 0 {
+            return Err(AppError::conflict(
+                "Two-factor authentication is already enabled",
+            ));
+        }
+
+        Ok(TwoFactorSetup {
+            otpauth_uri: totp::provisioning_uri(ISSUER, account, &secret),
+            secret,
+        })
+    }
+
+    /// Check an authenticator code, recording its step so it cannot be
+    /// replayed. `enabling` confirms a pending secret instead of an active one.
+    async fn accept_totp(
+        &self,
+        user_id: &str,
+        code: &str,
+        enabling: bool,
+    ) -> Result<bool, AppError> {
+        let Some(row) =
+            sqlx::query("SELECT secret, enabled_at FROM user_two_factor WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::db_error)?
+        else {
+            return Ok(false);
+        };
+
+        let secret: String = row.try_get("secret").map_err(Self::db_error)?;
+        let enabled_at: Option<DateTime<Utc>> =
+            row.try_get("enabled_at").map_err(Self::db_error)?;
+        if enabled_at.is_some() == enabling {
+            return Ok(false);
+        }
+        let Some(step) = totp::verify(&secret, code, Utc::now().timestamp()) else {
+            return Ok(false);
+        };
+
+        let accepted = sqlx::query(
+            "UPDATE user_two_factor \
+             SET last_used_step = $2, updated_at = now(), \
+                 enabled_at = CASE WHEN $3 THEN now() ELSE enabled_at END \
+             WHERE user_id = $1 AND (enabled_at IS NULL) = $3 \
+               AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_id)
+        .bind(step)
+        .bind(enabling)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        Ok(accepted.rows_affected() == 1)
+    }
+
+    async fn replace_recovery_codes(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: &str,
+    ) -> Result<Vec<String>, AppError> {
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(Self::db_error)?;
+
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| Self::new_recovery_code())
+            .collect();
+        for code in &codes {
+            sqlx::query(
+                "INSERT INTO user_recovery_codes (id, user_id, code_hash) VALUES ($1, $2, $3)",
+            )
+            .bind(ObjectId::new().to_hex())
+            .bind(user_id)
+            .bind(Self::hash(&Self::normalize_recovery_code(code)))
+            .execute(&mut **tx)
+            .await
+            .map_err(Self::db_error)?;
+        }
+        Ok(codes)
+    }
+
+    /// Finish enrolment with a first code and hand out recovery codes
+    pub async fn confirm_setup(&self, user_id: &str, code: &str) -> Result<Vec<String>, AppError> {
+        if self.is_enabled(user_id).await? {
+            return Err(AppError::conflict(
+                "Two-factor authentication is already enabled",
+            ));
+        }
+        if !self.accept_totp(user_id, code, true).await? {
+            return Err(AppError::field("code", "Invalid authenticator code"));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let codes = Self::replace_recovery_codes(&mut tx, user_id).await?;
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(codes)
+    }
+
+    /// Accept an authenticator code or spend a recovery code
+    pub async fn verify(&self, user_id: &str, code: &str) -> Result<(), AppError> {
+        if self.accept_totp(user_id, code, false).await? {
+            return Ok(());
+        }
+
+        let spent = sqlx::query(
+            "UPDATE user_recovery_codes SET used_at = now() \
+             WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(Self::hash(&Self::normalize_recovery_code(code)))
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if spent.rows_affected() == 0 {
+            return Err(AppError::unauthorized("Invalid two-factor code"));
+        }
+        Ok(())
+    }
+
+    /// `verify` with a per-user limit on wrong codes per window, shared by
+    /// every login challenge and signed-in check, so signing in again for a
+    /// fresh challenge does not allow more guesses
+    async fn verify_limited(&self, user_id: &str, code: &str) -> Result<(), AppError> {
+        // Take the attempt before checking, so parallel guesses share the limit
+        let reserved = sqlx::query(
+            "UPDATE user_two_factor SET \
+               failed_attempts = CASE WHEN attempts_reset_at <= now() \
+                 THEN 1 ELSE failed_attempts + 1 END, \
+               attempts_reset_at = CASE WHEN attempts_reset_at <= now() \
+                 THEN now() + make_interval(mins => $3) ELSE attempts_reset_at END \
+             WHERE user_id = $1 AND (attempts_reset_at <= now() OR failed_attempts < $2)",
+        )
+        .bind(user_id)
+        .bind(MAX_CHALLENGE_ATTEMPTS)
+        .bind(ATTEMPT_WINDOW_MINUTES)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if reserved.rows_affected() == 0 {
+            return Err(AppError::new(
+                ErrorCode::TooManyRequests,
+                "Too many wrong codes, try again later",
+            ));
+        }
+
+        self.verify(user_id, code).await?;
+        sqlx::query("UPDATE user_two_factor SET failed_attempts = 0 WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Turn the second factor off, unless a school requires it
+    pub async fn disable(
+        &self,
+        user_id: &str,
+        role: Option<&UserRole>,
+        code: &str,
+    ) -> Result<(), AppError> {
+        if self.is_required(user_id, role).await? {
+            return Err(AppError::forbidden(
+                "A school you work at requires two-factor authentication",
+            ));
+        }
+        self.verify_limited(user_id, code).await?;
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        sqlx::query("DELETE FROM user_two_factor WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Self::db_error)?;
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(())
+    }
+
+    /// Replace every recovery code, after proving the second factor
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: &str,
+        code: &str,
+    ) -> Result<Vec<String>, AppError> {
+        self.verify_limited(user_id, code).await?;
+
+        let mut tx = self.pool.begin().await.map_err(Self::db_error)?;
+        let codes = Self::replace_recovery_codes(&mut tx, user_id).await?;
+        tx.commit().await.map_err(Self::db_error)?;
+        Ok(codes)
+    }
+
+    /// Challenge handed out by login in place of tokens
+    pub async fn create_challenge(
+        &self,
+        user_id: &str,
+        setup_required: bool,
+    ) -> Result<TwoFactorChallenge, AppError> {
+        let token = hex::encode(rand::random::<[u8; 32]>());
+        let ttl = Duration::minutes(CHALLENGE_TTL_MINUTES);
+        sqlx::query(
+            "INSERT INTO two_factor_challenges (id, user_id, token_hash, expires_at) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(ObjectId::new().to_hex())
+        .bind(user_id)
+        .bind(Self::hash(&token))
+        .bind(Utc::now() + ttl)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+
+        Ok(TwoFactorChallenge {
+            two_factor_required: true,
+            challenge_token: token,
+            setup_required,
+            expires_in: ttl.num_seconds(),
+        })
+    }
+
+    /// Enrol from a login challenge, for users a school forces to set up
+    /// two-factor before they can sign in
+    pub async fn challenge_setup(&self, challenge_token: &str) -> Result<TwoFactorSetup, AppError> {
+        let row = sqlx::query(
+            "SELECT u.id, u.email FROM two_factor_challenges c \
+             JOIN users u ON u.id = c.user_id \
+             WHERE c.token_hash = $1 AND c.used_at IS NULL \
+               AND c.expires_at > now() AND c.attempts < $2",
+        )
+        .bind(Self::hash(challenge_token))
+        .bind(MAX_CHALLENGE_ATTEMPTS)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| AppError::unauthorized("Challenge is invalid or has expired"))?;
+
+        let user_id: String = row.try_get("id").map_err(Self::db_error)?;
+        let email: String = row.try_get("email").map_err(Self::db_error)?;
+        self.begin_setup(&user_id, &email).await
+    }
+
+    /// Answer a login challenge. Returns the user, plus recovery codes when
+    /// the answer also confirmed a pending enrolment.
+    pub async fn redeem_challenge(
+        &self,
+        challenge_token: &str,
+        code: &str,
+    ) -> Result<(String, Option<Vec<String>>), AppError> {
+        let row = sqlx::query(
+            "UPDATE two_factor_challenges SET attempts = attempts + 1 \
+             WHERE token_hash = $1 AND used_at IS NULL \
+               AND expires_at > now() AND attempts < $2 \
+             RETURNING id, user_id",
+        )
+        .bind(Self::hash(challenge_token))
+        .bind(MAX_CHALLENGE_ATTEMPTS)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::db_error)?
+        .ok_or_else(|| {
+            AppError::unauthorized("Challenge is invalid or has expired, please sign in again")
+        })?;
+        let challenge_id: String = row.try_get("id").map_err(Self::db_error)?;
+        let user_id: String = row.try_get("user_id").map_err(Self::db_error)?;
+
+        let recovery_codes = if self.is_enabled(&user_id).await? {
+            self.verify_limited(&user_id, code).await?;
+            None
+        } else {
+            Some(self.confirm_setup(&user_id, code).await?)
+        };
+
+        let redeemed = sqlx::query(
+            "UPDATE two_factor_challenges SET used_at = now() \
+             WHERE id = $1 AND used_at IS NULL",
+        )
+        .bind(&challenge_id)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if redeemed.rows_affected() == 0 {
+            return Err(AppError::unauthorized("Challenge was already used"));
+        }
+        Ok((user_id, recovery_codes))
+    }
+
+    pub async fn set_school_policy(
+        &self,
+        school_id: &str,
+        require_two_factor: bool,
+    ) -> Result<(), AppError> {
+        let updated = sqlx::query(
+            "UPDATE schools SET require_two_factor = $2, updated_at = now() WHERE id = $1",
+        )
+        .bind(school_id)
+        .bind(require_two_factor)
+        .execute(&self.pool)
+        .await
+        .map_err(Self::db_error)?;
+        if updated.rows_affected() == 0 {
+            return Err(AppError::not_found("School not found"));
+        }
+        Ok(())
+    }
+}
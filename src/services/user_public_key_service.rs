@@ -17,7 +17,7 @@ impl UserPublicKeyService {
     }
 
     fn db_error(err: sqlx::Error) -> AppError {
-        AppError { message: format!("PostgreSQL Error: {}", err) }
+        AppError::from(err)
     }
 
     fn new_id() -> String {
@@ -25,8 +25,8 @@ impl UserPublicKeyService {
     }
 
     fn parse_oid(raw: &str, field: &str) -> Result<ObjectId, AppError> {
-        ObjectId::parse_str(raw).map_err(|e| AppError {
-            message: format!("Invalid {} ObjectId-compatible ID: {}", field, e),
+        ObjectId::parse_str(raw).map_err(|e| {
+            AppError::bad_request(format!("Invalid {} ObjectId-compatible ID: {}", field, e))
         })
     }
 
@@ -50,9 +50,9 @@ impl UserPublicKeyService {
         key_algorithm: String,
     ) -> Result<UserPublicKey, AppError> {
         if !public_key.contains("BEGIN PUBLIC KEY") || !public_key.contains("END PUBLIC KEY") {
-            return Err(AppError {
-                message: "Invalid public key format. Must be PEM format.".to_string(),
-            });
+            return Err(AppError::bad_request(
+                "Invalid public key format. Must be PEM format.",
+            ));
         }
 
         let id = Self::new_id();
@@ -92,7 +92,10 @@ impl UserPublicKeyService {
 
         match row {
             Some(r) => Self::row_to_key(r),
-            None => Err(AppError { message: format!("Public key not found for user: {}", user_id) }),
+            None => Err(AppError::not_found(format!(
+                "Public key not found for user: {}",
+                user_id
+            ))),
         }
     }
 
@@ -102,9 +105,10 @@ impl UserPublicKeyService {
     ) -> Result<Vec<PublicKeyInfo>, AppError> {
         let (keys, missing) = self.get_public_keys_partial(user_ids).await?;
         if !missing.is_empty() {
-            return Err(AppError {
-                message: format!("Public key not found for users: {}", missing.join(", ")),
-            });
+            return Err(AppError::not_found(format!(
+                "Public key not found for users: {}",
+                missing.join(", ")
+            )));
         }
         Ok(keys)
     }
@@ -114,10 +118,12 @@ impl UserPublicKeyService {
         user_ids: Vec<ObjectId>,
     ) -> Result<(Vec<PublicKeyInfo>, Vec<String>), AppError> {
         if user_ids.is_empty() {
-            return Err(AppError { message: "user_ids parameter is required".to_string() });
+            return Err(AppError::bad_request("user_ids parameter is required"));
         }
         if user_ids.len() > 50 {
-            return Err(AppError { message: "Maximum 50 user IDs allowed per request".to_string() });
+            return Err(AppError::bad_request(
+                "Maximum 50 user IDs allowed per request",
+            ));
         }
 
         let strings: Vec<String> = user_ids.iter().map(|o| o.to_hex()).collect();
@@ -151,10 +157,12 @@ impl UserPublicKeyService {
         user_ids: Vec<ObjectId>,
     ) -> Result<Vec<PublicKeyInfo>, AppError> {
         if user_ids.is_empty() {
-            return Err(AppError { message: "user_ids parameter is required".to_string() });
+            return Err(AppError::bad_request("user_ids parameter is required"));
         }
         if user_ids.len() > 50 {
-            return Err(AppError { message: "Maximum 50 user IDs allowed per request".to_string() });
+            return Err(AppError::bad_request(
+                "Maximum 50 user IDs allowed per request",
+            ));
         }
 
         let (mut public_keys, missing_ids) = self.get_public_keys_partial(user_ids.clone()).await?;
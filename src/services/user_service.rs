@@ -246,12 +246,8 @@ impl<'a> UserService<'a> {
         &self,
         user_id: &IdType,
         school_id: &IdType,
-    ) -> Result<User, String> {
-        let updated_user = self
-            .repo
-            .add_school_to_user(user_id, school_id)
-            .await
-            .map_err(|e| e.message)?;
+    ) -> Result<User, AppError> {
+        let updated_user = self.repo.add_school_to_user(user_id, school_id).await?;
         Ok(sanitize_user(updated_user))
     }
 
@@ -260,12 +256,11 @@ impl<'a> UserService<'a> {
         &self,
         user_id: &IdType,
         school_id: &IdType,
-    ) -> Result<User, String> {
+    ) -> Result<User, AppError> {
         let updated_user = self
             .repo
             .remove_school_from_user(user_id, school_id)
-            .await
-            .map_err(|e| e.message)?;
+            .await?;
         Ok(sanitize_user(updated_user))
     }
 }
@@ -282,9 +277,10 @@ pub fn normalize_user_ids(mut user: User) -> Result<User, AppError> {
         match ObjectId::parse_str(&raw) {
             Ok(oid) => user.current_school_id = Some(oid),
             Err(_) => {
-                return Err(AppError {
-                    message: format!("Invalid ObjectId string for current_school_id: {}", raw),
-                })
+                return Err(AppError::bad_request(format!(
+                    "Invalid ObjectId string for current_school_id: {}",
+                    raw
+                )))
             }
         }
     }
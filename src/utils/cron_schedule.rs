@@ -0,0 +1,137 @@
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+/// Five-field cron expression (`minute hour day-of-month month day-of-week`)
+/// evaluated in UTC. Fields accept `*`, numbers, ranges (`1-5`), lists
+/// (`1,15`) and steps (`*/15`, `8-18/2`). Day-of-week is 0-6 with Sunday as 0
+/// (7 is also Sunday). As in cron, when both day fields are restricted a time
+/// matches if either of them does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_any: bool,
+    day_of_week_any: bool,
+}
+
+fn parse_field(field: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Invalid step '{}' in {} field", step, name))?;
+                if step == 0 {
+                    return Err(format!("Step in {} field must be greater than 0", name));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, name)?, parse_value(end, name)?)
+        } else {
+            let value = parse_value(range, name)?;
+            // `5/10` means "from 5 to the end, every 10"
+            (value, if part.contains('/') { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "Value '{}' is out of range {}-{} in {} field",
+                range, min, max, name
+            ));
+        }
+
+        let mut value = start;
+        while value <= end {
+            bits |= 1 << value;
+            value += step;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_value(raw: &str, name: &str) -> Result<u32, String> {
+    raw.parse()
+        .map_err(|_| format!("Invalid value '{}' in {} field", raw, name))
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(format!(
+                "Schedule '{}' must have 5 fields: minute hour day-of-month month day-of-week",
+                expression
+            ));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, "day-of-week", 0, 7)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59)?,
+            hours: parse_field(hour, "hour", 0, 23)?,
+            days_of_month: parse_field(day_of_month, "day-of-month", 1, 31)?,
+            months: parse_field(month, "month", 1, 12)?,
+            days_of_week,
+            day_of_month_any: day_of_month == "*",
+            day_of_week_any: day_of_week == "*",
+        })
+    }
+
+    pub fn matches(&self, at: &DateTime<Utc>) -> bool {
+        let has = |bits: u64, value: u32| bits & (1 << value) != 0;
+
+        let day_of_month = has(self.days_of_month, at.day());
+        let day_of_week = has(self.days_of_week, at.weekday().num_days_from_sunday());
+        let day = match (self.day_of_month_any, self.day_of_week_any) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        };
+
+        day && has(self.minutes, at.minute())
+            && has(self.hours, at.hour())
+            && has(self.months, at.month())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn daily_schedule_matches_only_its_minute() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+
+        assert!(schedule.matches(&Utc.with_ymd_and_hms(2026, 10, 18, 2, 30, 0).unwrap()));
+        assert!(!schedule.matches(&Utc.with_ymd_and_hms(2026, 10, 18, 2, 31, 0).unwrap()));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either_day() {
+        // The 1st of the month or any Sunday, at midnight
+        let schedule = CronSchedule::parse("0 0 1 * 0").unwrap();
+
+        assert!(schedule.matches(&Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()));
+        assert!(schedule.matches(&Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap()));
+        assert!(!schedule.matches(&Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        assert!(CronSchedule::parse("0 2 * *").is_err());
+        assert!(CronSchedule::parse("60 2 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+}
@@ -13,9 +13,8 @@ pub fn generate_rsa_public_key() -> Result<String, AppError> {
     let mut rng = OsRng;
 
     // Generate a 2048-bit RSA key pair
-    let private_key = RsaPrivateKey::new(&mut rng, 2048).map_err(|e| AppError {
-        message: format!("Failed to generate RSA key: {}", e),
-    })?;
+    let private_key = RsaPrivateKey::new(&mut rng, 2048)
+        .map_err(|e| AppError::internal(format!("Failed to generate RSA key: {}", e)))?;
 
     // Extract public key
     let public_key = RsaPublicKey::from(&private_key);
@@ -23,9 +22,7 @@ pub fn generate_rsa_public_key() -> Result<String, AppError> {
     // Convert to PEM format
     let pem = public_key
         .to_public_key_pem(LineEnding::LF)
-        .map_err(|e| AppError {
-            message: format!("Failed to encode public key to PEM: {}", e),
-        })?;
+        .map_err(|e| AppError::internal(format!("Failed to encode public key to PEM: {}", e)))?;
 
     Ok(pem)
 }
@@ -0,0 +1,173 @@
+//! Minimal RFC 5545 writer for the calendar feeds.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+const PRODUCT_ID: &str = "-//Space Together//School Calendar//EN";
+/// Content lines longer than this many octets are folded (RFC 5545 §3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Debug, Clone, Copy)]
+pub enum IcsTime {
+    /// Wall-clock time of the school, no time zone
+    Floating(NaiveDateTime),
+    Utc(DateTime<Utc>),
+    /// All-day value
+    Date(NaiveDate),
+}
+
+impl IcsTime {
+    fn property(&self, name: &str) -> String {
+        match self {
+            IcsTime::Floating(value) => format!("{}:{}", name, value.format("%Y%m%dT%H%M%S")),
+            IcsTime::Utc(value) => format!("{}:{}", name, value.format("%Y%m%dT%H%M%SZ")),
+            IcsTime::Date(value) => format!("{};VALUE=DATE:{}", name, value.format("%Y%m%d")),
+        }
+    }
+
+    /// Value form for RRULE UNTIL, which must match DTSTART's type
+    fn until(&self) -> String {
+        match self {
+            IcsTime::Floating(value) => value.format("%Y%m%dT%H%M%S").to_string(),
+            IcsTime::Utc(value) => value.format("%Y%m%dT%H%M%SZ").to_string(),
+            IcsTime::Date(value) => value.format("%Y%m%d").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub categories: Vec<String>,
+    pub start: IcsTime,
+    pub end: Option<IcsTime>,
+    /// Repeat weekly until this time, inclusive
+    pub weekly_until: Option<IcsTime>,
+    pub exdates: Vec<IcsTime>,
+}
+
+impl IcsEvent {
+    pub fn new(uid: impl Into<String>, summary: impl Into<String>, start: IcsTime) -> Self {
+        Self {
+            uid: uid.into(),
+            summary: summary.into(),
+            description: None,
+            location: None,
+            categories: Vec::new(),
+            start,
+            end: None,
+            weekly_until: None,
+            exdates: Vec::new(),
+        }
+    }
+}
+
+/// Escape TEXT values (RFC 5545 §3.3.11)
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line at 75 octets without splitting a UTF-8 character
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+pub struct IcsCalendar {
+    name: String,
+    events: Vec<IcsEvent>,
+}
+
+impl IcsCalendar {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, event: IcsEvent) {
+        self.events.push(event);
+    }
+
+    pub fn render(&self, stamp: DateTime<Utc>) -> String {
+        let mut out = String::new();
+        push_line(&mut out, "BEGIN:VCALENDAR");
+        push_line(&mut out, "VERSION:2.0");
+        push_line(&mut out, &format!("PRODID:{}", PRODUCT_ID));
+        push_line(&mut out, "CALSCALE:GREGORIAN");
+        push_line(&mut out, "METHOD:PUBLISH");
+        push_line(
+            &mut out,
+            &format!("X-WR-CALNAME:{}", escape_text(&self.name)),
+        );
+
+        let stamp = IcsTime::Utc(stamp).property("DTSTAMP");
+        for event in &self.events {
+            push_line(&mut out, "BEGIN:VEVENT");
+            push_line(&mut out, &format!("UID:{}", event.uid));
+            push_line(&mut out, &stamp);
+            push_line(&mut out, &event.start.property("DTSTART"));
+            if let Some(end) = &event.end {
+                push_line(&mut out, &end.property("DTEND"));
+            }
+            if let Some(until) = &event.weekly_until {
+                push_line(
+                    &mut out,
+                    &format!("RRULE:FREQ=WEEKLY;UNTIL={}", until.until()),
+                );
+            }
+            for exdate in &event.exdates {
+                push_line(&mut out, &exdate.property("EXDATE"));
+            }
+            push_line(
+                &mut out,
+                &format!("SUMMARY:{}", escape_text(&event.summary)),
+            );
+            if let Some(description) = &event.description {
+                push_line(
+                    &mut out,
+                    &format!("DESCRIPTION:{}", escape_text(description)),
+                );
+            }
+            if let Some(location) = &event.location {
+                push_line(&mut out, &format!("LOCATION:{}", escape_text(location)));
+            }
+            if !event.categories.is_empty() {
+                let categories = event
+                    .categories
+                    .iter()
+                    .map(|category| escape_text(category))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                push_line(&mut out, &format!("CATEGORIES:{}", categories));
+            }
+            push_line(&mut out, "END:VEVENT");
+        }
+
+        push_line(&mut out, "END:VCALENDAR");
+        out
+    }
+}
@@ -12,9 +12,22 @@ pub struct Claims {
     pub iat: usize,
 }
 
+/// Access tokens are short-lived; clients renew them with a refresh token.
+/// Overridable with `ACCESS_TOKEN_TTL_MINUTES`.
+const DEFAULT_ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+
+pub fn access_token_ttl() -> Duration {
+    let minutes = env::var("ACCESS_TOKEN_TTL_MINUTES")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|minutes| *minutes > 0)
+        .unwrap_or(DEFAULT_ACCESS_TOKEN_TTL_MINUTES);
+    Duration::minutes(minutes)
+}
+
 pub fn create_jwt(user: &AuthUserDto) -> String {
     let expiration = Utc::now()
-        .checked_add_signed(Duration::days(7))
+        .checked_add_signed(access_token_ttl())
         .expect("valid timestamp")
         .timestamp();
 
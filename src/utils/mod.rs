@@ -2,18 +2,24 @@ pub mod api_utils;
 pub mod bytes;
 pub mod class_utils;
 pub mod code;
+pub mod cron_schedule;
 pub mod crypto_utils;
 pub mod db_utils;
 pub mod email;
 pub mod hash;
+pub mod ical;
 pub mod jwt;
 pub mod mongo_utils;
 pub mod names;
 pub mod object_id;
 pub mod partial_macro;
+pub mod pdf;
 pub mod request_context;
 pub mod route_utils;
 pub mod school_token;
 pub mod school_utils;
+pub mod spreadsheet;
+pub mod statistics;
 pub mod time_utils;
+pub mod totp;
 pub mod user_utils;
@@ -0,0 +1,729 @@
+//! Minimal PDF 1.4 writer for printable documents: text in the standard
+//! Helvetica fonts, lines, filled boxes and JPEG/PNG images.
+
+use std::io::{Read, Write};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+/// A4 portrait in points
+pub const A4_WIDTH: f32 = 595.0;
+pub const A4_HEIGHT: f32 = 842.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Approximate width of `text` in points. Helvetica has no fixed pitch, so
+/// this uses an average glyph width, which is enough for layout decisions.
+pub fn text_width(text: &str, font: Font, size: f32) -> f32 {
+    let average = match font {
+        Font::Regular => 0.5,
+        Font::Bold => 0.55,
+    };
+    text.chars().count() as f32 * size * average
+}
+
+/// Cut `text` to fit `width`, ending with "..." when shortened
+pub fn truncate_to_width(text: &str, font: Font, size: f32, width: f32) -> String {
+    if text_width(text, font, size) <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    for ch in text.chars() {
+        if text_width(&format!("{}{}...", out, ch), font, size) > width {
+            break;
+        }
+        out.push(ch);
+    }
+    format!("{}...", out.trim_end())
+}
+
+/// Split `text` into lines no wider than `width`, breaking on spaces
+pub fn wrap_text(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && text_width(&candidate, font, size) > width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Encode a string for a WinAnsi literal, replacing what it cannot show
+fn encode_text(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        let byte = match ch {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                ch as u8
+            }
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201C}' => 0x93,
+            '\u{201D}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{20AC}' => 0x80,
+            ' '..='~' => ch as u8,
+            '\u{A0}'..='\u{FF}' => ch as u32 as u8,
+            _ => b'?',
+        };
+        out.push(byte);
+    }
+    out
+}
+
+enum ImageData {
+    /// Baseline or progressive JPEG, embedded as is
+    Jpeg { components: u8, bytes: Vec<u8> },
+    /// Decoded 8-bit samples, with the alpha channel kept apart
+    Raw {
+        colors: u8,
+        pixels: Vec<u8>,
+        alpha: Option<Vec<u8>>,
+    },
+}
+
+pub struct PdfImage {
+    pub width: u32,
+    pub height: u32,
+    data: ImageData,
+}
+
+impl PdfImage {
+    /// JPEG, or 8-bit non-interlaced PNG; anything else is unsupported
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0xFF, 0xD8]) {
+            Self::from_jpeg(bytes)
+        } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Self::from_png(bytes)
+        } else {
+            None
+        }
+    }
+
+    fn from_jpeg(bytes: &[u8]) -> Option<Self> {
+        let mut i = 2;
+        while i + 9 < bytes.len() {
+            if bytes[i] != 0xFF {
+                return None;
+            }
+            let marker = bytes[i + 1];
+            let length = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+            // Start-of-frame markers, skipping DHT, JPG and DAC
+            if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                let height = u16::from_be_bytes([bytes[i + 5], bytes[i + 6]]) as u32;
+                let width = u16::from_be_bytes([bytes[i + 7], bytes[i + 8]]) as u32;
+                let components = bytes[i + 9];
+                if width == 0 || height == 0 || ![1, 3].contains(&components) {
+                    return None;
+                }
+                return Some(Self {
+                    width,
+                    height,
+                    data: ImageData::Jpeg {
+                        components,
+                        bytes: bytes.to_vec(),
+                    },
+                });
+            }
+            i += 2 + length;
+        }
+        None
+    }
+
+    fn from_png(bytes: &[u8]) -> Option<Self> {
+        let mut i = 8;
+        let (mut width, mut height, mut color_type) = (0u32, 0u32, 0u8);
+        let mut palette: Vec<u8> = Vec::new();
+        let mut transparency: Vec<u8> = Vec::new();
+        let mut compressed = Vec::new();
+        while i + 8 <= bytes.len() {
+            let length = u32::from_be_bytes(bytes[i..i + 4].try_into().ok()?) as usize;
+            let kind = &bytes[i + 4..i + 8];
+            let chunk = bytes.get(i + 8..i + 8 + length)?;
+            match kind {
+                b"IHDR" => {
+                    width = u32::from_be_bytes(chunk.get(0..4)?.try_into().ok()?);
+                    height = u32::from_be_bytes(chunk.get(4..8)?.try_into().ok()?);
+                    let (depth, interlace) = (*chunk.get(8)?, *chunk.get(12)?);
+                    color_type = *chunk.get(9)?;
+                    if depth != 8 || interlace != 0 {
+                        return None;
+                    }
+                }
+                b"PLTE" => palette = chunk.to_vec(),
+                b"tRNS" => transparency = chunk.to_vec(),
+                b"IDAT" => compressed.extend_from_slice(chunk),
+                b"IEND" => break,
+                _ => {}
+            }
+            i += 12 + length;
+        }
+
+        let channels = match color_type {
+            0 | 3 => 1,
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => return None,
+        };
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let mut inflated = Vec::new();
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_end(&mut inflated)
+            .ok()?;
+        let samples = unfilter(&inflated, width as usize, height as usize, channels)?;
+
+        let pixel_count = (width * height) as usize;
+        let (colors, mut pixels, mut alpha) = (
+            if matches!(color_type, 0 | 4) { 1 } else { 3 },
+            Vec::with_capacity(pixel_count * 3),
+            Vec::with_capacity(pixel_count),
+        );
+        for pixel in samples.chunks(channels) {
+            match color_type {
+                0 | 2 => pixels.extend_from_slice(pixel),
+                4 | 6 => {
+                    pixels.extend_from_slice(&pixel[..channels - 1]);
+                    alpha.push(pixel[channels - 1]);
+                }
+                _ => {
+                    let index = pixel[0] as usize;
+                    pixels.extend_from_slice(palette.get(index * 3..index * 3 + 3)?);
+                    alpha.push(transparency.get(index).copied().unwrap_or(0xFF));
+                }
+            }
+        }
+        let alpha = (!alpha.is_empty() && alpha.iter().any(|a| *a != 0xFF)).then_some(alpha);
+
+        Some(Self {
+            width,
+            height,
+            data: ImageData::Raw {
+                colors,
+                pixels,
+                alpha,
+            },
+        })
+    }
+}
+
+/// Undo PNG row filters (RFC 2083 §6)
+fn unfilter(data: &[u8], width: usize, height: usize, channels: usize) -> Option<Vec<u8>> {
+    let stride = width * channels;
+    let mut out = vec![0u8; stride * height];
+    for row in 0..height {
+        let line = data.get(row * (stride + 1)..(row + 1) * (stride + 1))?;
+        let (filter, line) = (line[0], &line[1..]);
+        for x in 0..stride {
+            let left = if x >= channels {
+                out[row * stride + x - channels]
+            } else {
+                0
+            };
+            let up = if row > 0 {
+                out[(row - 1) * stride + x]
+            } else {
+                0
+            };
+            let up_left = if row > 0 && x >= channels {
+                out[(row - 1) * stride + x - channels]
+            } else {
+                0
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => {
+                    let p = left as i16 + up as i16 - up_left as i16;
+                    let (pa, pb, pc) = (
+                        (p - left as i16).abs(),
+                        (p - up as i16).abs(),
+                        (p - up_left as i16).abs(),
+                    );
+                    if pa <= pb && pa <= pc {
+                        left
+                    } else if pb <= pc {
+                        up
+                    } else {
+                        up_left
+                    }
+                }
+                _ => return None,
+            };
+            out[row * stride + x] = line[x].wrapping_add(predicted);
+        }
+    }
+    Some(out)
+}
+
+/// Append the next numbered object, recording its byte offset
+fn object(out: &mut Vec<u8>, offsets: &mut Vec<usize>, body: &[u8]) {
+    offsets.push(out.len());
+    out.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+    out.extend_from_slice(body);
+    out.extend_from_slice(b"\nendobj\n");
+}
+
+fn stream(dictionary: String, data: &[u8]) -> Vec<u8> {
+    let mut body = format!("<< {} /Length {} >>\nstream\n", dictionary, data.len()).into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(b"\nendstream");
+    body
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec cannot fail
+    let _ = encoder.write_all(data);
+    encoder.finish().unwrap_or_default()
+}
+
+/// Handle to an image added with [`PdfDocument::add_image`]
+#[derive(Debug, Clone, Copy)]
+pub struct ImageId(usize);
+
+pub struct PdfPage {
+    pub width: f32,
+    pub height: f32,
+    content: Vec<u8>,
+    images: Vec<usize>,
+}
+
+impl PdfPage {
+    pub fn a4() -> Self {
+        Self {
+            width: A4_WIDTH,
+            height: A4_HEIGHT,
+            content: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    fn op(&mut self, operation: &str) {
+        self.content.extend_from_slice(operation.as_bytes());
+        self.content.push(b'\n');
+    }
+
+    /// Draw text with its baseline at `y`, measured from the bottom
+    pub fn text(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.op(&format!(
+            "BT /{} {:.1} Tf {:.2} {:.2} Td",
+            font.resource(),
+            size,
+            x,
+            y
+        ));
+        self.content.push(b'(');
+        self.content.extend(encode_text(text));
+        self.content.extend_from_slice(b") Tj ET\n");
+    }
+
+    pub fn text_right(&mut self, right: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.text(right - text_width(text, font, size), y, font, size, text);
+    }
+
+    pub fn text_centered(&mut self, center: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.text(
+            center - text_width(text, font, size) / 2.0,
+            y,
+            font,
+            size,
+            text,
+        );
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32) {
+        self.op(&format!(
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S",
+            width, x1, y1, x2, y2
+        ));
+    }
+
+    /// Filled box in a shade of grey, 0.0 black to 1.0 white
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, gray: f32) {
+        self.op(&format!(
+            "q {:.2} g {:.2} {:.2} {:.2} {:.2} re f Q",
+            gray, x, y, width, height
+        ));
+    }
+
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32, line_width: f32) {
+        self.op(&format!(
+            "{:.2} w {:.2} {:.2} {:.2} {:.2} re S",
+            line_width, x, y, width, height
+        ));
+    }
+
+    pub fn image(&mut self, image: ImageId, x: f32, y: f32, width: f32, height: f32) {
+        if !self.images.contains(&image.0) {
+            self.images.push(image.0);
+        }
+        self.op(&format!(
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q",
+            width, height, x, y, image.0
+        ));
+    }
+}
+
+#[derive(Default)]
+pub struct PdfDocument {
+    title: Option<String>,
+    pages: Vec<PdfPage>,
+    images: Vec<PdfImage>,
+}
+
+impl PdfDocument {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn add_image(&mut self, image: PdfImage) -> ImageId {
+        self.images.push(image);
+        ImageId(self.images.len() - 1)
+    }
+
+    pub fn push_page(&mut self, page: PdfPage) {
+        self.pages.push(page);
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn render(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets: Vec<usize> = Vec::new();
+
+        // 1 catalog, 2 page tree, 3-4 fonts, 5 info, then images and pages
+        let mut image_objects = Vec::with_capacity(self.images.len());
+        let mut next = 6;
+        for image in &self.images {
+            let has_mask = matches!(&image.data, ImageData::Raw { alpha: Some(_), .. });
+            image_objects.push(next);
+            next += if has_mask { 2 } else { 1 };
+        }
+        let page_objects: Vec<usize> = (0..self.pages.len()).map(|i| next + i * 2).collect();
+
+        object(&mut out, &mut offsets, b"<< /Type /Catalog /Pages 2 0 R >>");
+        let kids = page_objects
+            .iter()
+            .map(|id| format!("{} 0 R", id))
+            .collect::<Vec<_>>()
+            .join(" ");
+        object(
+            &mut out,
+            &mut offsets,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids,
+                self.pages.len()
+            )
+            .as_bytes(),
+        );
+        for base_font in ["Helvetica", "Helvetica-Bold"] {
+            object(
+                &mut out,
+                &mut offsets,
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    base_font
+                )
+                .as_bytes(),
+            );
+        }
+        let mut info = b"<< /Producer (Space Together)".to_vec();
+        if let Some(title) = &self.title {
+            info.extend_from_slice(b" /Title (");
+            info.extend(encode_text(title));
+            info.push(b')');
+        }
+        info.extend_from_slice(b" >>");
+        object(&mut out, &mut offsets, &info);
+
+        for (image, id) in self.images.iter().zip(&image_objects) {
+            let header = format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /BitsPerComponent 8",
+                image.width, image.height
+            );
+            match &image.data {
+                ImageData::Jpeg { components, bytes } => {
+                    let space = if *components == 1 {
+                        "DeviceGray"
+                    } else {
+                        "DeviceRGB"
+                    };
+                    object(
+                        &mut out,
+                        &mut offsets,
+                        &stream(
+                            format!("{} /ColorSpace /{} /Filter /DCTDecode", header, space),
+                            bytes,
+                        ),
+                    );
+                }
+                ImageData::Raw {
+                    colors,
+                    pixels,
+                    alpha,
+                } => {
+                    let space = if *colors == 1 {
+                        "DeviceGray"
+                    } else {
+                        "DeviceRGB"
+                    };
+                    let mask = alpha
+                        .as_ref()
+                        .map(|_| format!(" /SMask {} 0 R", id + 1))
+                        .unwrap_or_default();
+                    object(
+                        &mut out,
+                        &mut offsets,
+                        &stream(
+                            format!(
+                                "{} /ColorSpace /{} /Filter /FlateDecode{}",
+                                header, space, mask
+                            ),
+                            &deflate(pixels),
+                        ),
+                    );
+                    if let Some(alpha) = alpha {
+                        object(
+                            &mut out,
+                            &mut offsets,
+                            &stream(
+                                format!("{} /ColorSpace /DeviceGray /Filter /FlateDecode", header),
+                                &deflate(alpha),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        for (page, id) in self.pages.iter().zip(&page_objects) {
+            let images = page
+                .images
+                .iter()
+                .map(|index| format!("/Im{} {} 0 R", index, image_objects[*index]))
+                .collect::<Vec<_>>()
+                .join(" ");
+            object(
+                &mut out,
+                &mut offsets,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.0} {:.0}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {} >> >> \
+                     /Contents {} 0 R >>",
+                    page.width,
+                    page.height,
+                    images,
+                    id + 1
+                )
+                .as_bytes(),
+            );
+            object(
+                &mut out,
+                &mut offsets,
+                &stream("/Filter /FlateDecode".to_string(), &deflate(&page.content)),
+            );
+        }
+
+        let xref = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).as_bytes(),
+        );
+        for offset in &offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+                offsets.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    /// Check the trailer and cross-reference table the way a reader would:
+    /// `startxref` points at the table, and every entry at its object
+    fn assert_xref_consistent(pdf: &[u8]) -> usize {
+        let tail = std::str::from_utf8(&pdf[find(pdf, b"startxref\n").unwrap()..]).unwrap();
+        let xref: usize = tail.lines().nth(1).unwrap().parse().unwrap();
+        let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+        let mut lines = table.lines();
+        assert_eq!(lines.next(), Some("xref"));
+        let count: usize = lines
+            .next()
+            .unwrap()
+            .strip_prefix("0 ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for number in 1..count {
+            let entry = lines.next().unwrap();
+            assert_eq!(entry.len(), 19);
+            assert!(entry.ends_with(" 00000 n "));
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", number).as_bytes()));
+        }
+        assert_eq!(lines.next(), Some("trailer"));
+        assert!(table.contains(&format!("/Size {} ", count)));
+        count - 1
+    }
+
+    /// Smallest PNG the reader accepts; it does not check chunk CRCs
+    fn png(width: u32, height: u32, color_type: u8, rows: &[&[u8]]) -> Vec<u8> {
+        fn chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+            out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            out.extend_from_slice(kind);
+            out.extend_from_slice(data);
+            out.extend_from_slice(&[0; 4]);
+        }
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+        let mut raw = Vec::new();
+        for row in rows {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut out, b"IHDR", &header);
+        chunk(&mut out, b"IDAT", &deflate(&raw));
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    #[test]
+    fn rendered_document_has_header_trailer_and_matching_xref() {
+        let mut document = PdfDocument::new("Report cards");
+        for name in ["Alice", "Bob"] {
+            let mut page = PdfPage::a4();
+            page.text(40.0, 800.0, Font::Bold, 14.0, name);
+            page.line(40.0, 790.0, 555.0, 790.0, 0.5);
+            page.fill_rect(40.0, 700.0, 100.0, 20.0, 0.9);
+            document.push_page(page);
+        }
+        let pdf = document.render();
+
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        // Catalog, page tree, two fonts and info, then a page and its
+        // contents for each of the two pages
+        assert_eq!(assert_xref_consistent(&pdf), 9);
+        assert!(find(&pdf, b"/Type /Pages /Kids [6 0 R 8 0 R] /Count 2").is_some());
+        assert!(find(&pdf, b"/Title (Report cards)").is_some());
+    }
+
+    #[test]
+    fn images_get_their_own_objects_and_a_soft_mask_for_alpha() {
+        let rgba = png(2, 1, 6, &[&[255, 0, 0, 255, 0, 0, 255, 0]]);
+        let image = PdfImage::from_bytes(&rgba).expect("8-bit RGBA png");
+        assert_eq!((image.width, image.height), (2, 1));
+        assert!(matches!(
+            &image.data,
+            ImageData::Raw { colors: 3, pixels, alpha: Some(alpha) }
+                if pixels == &[255, 0, 0, 0, 0, 255] && alpha == &[255, 0]
+        ));
+
+        let mut document = PdfDocument::new("Logo");
+        let logo = document.add_image(image);
+        let mut page = PdfPage::a4();
+        page.image(logo, 40.0, 760.0, 60.0, 30.0);
+        document.push_page(page);
+        let pdf = document.render();
+
+        // Image 6, its mask 7, then the page 8 and its contents 9
+        assert_eq!(assert_xref_consistent(&pdf), 9);
+        assert!(find(&pdf, b"/SMask 7 0 R").is_some());
+        assert!(find(&pdf, b"/XObject << /Im0 6 0 R >>").is_some());
+        assert!(PdfImage::from_bytes(b"GIF89a").is_none());
+    }
+
+    #[test]
+    fn text_escapes_delimiters_and_maps_to_win_ansi() {
+        assert_eq!(
+            encode_text(r"Math (Core) \ 2"),
+            br"Math \(Core\) \\ 2".to_vec()
+        );
+        assert_eq!(
+            encode_text("Café – “A” €5"),
+            b"Caf\xE9 \x96 \x93A\x94 \x805".to_vec()
+        );
+        // Outside WinAnsi there is no glyph, so each character becomes "?"
+        assert_eq!(
+            encode_text("Ωμέγα 学校"),
+            b"?????".iter().chain(b" ??").copied().collect::<Vec<_>>()
+        );
+
+        let mut page = PdfPage::a4();
+        page.text(10.0, 20.0, Font::Regular, 9.0, "Grade (A)");
+        let content = String::from_utf8(page.content).unwrap();
+        assert_eq!(
+            content,
+            "BT /F1 9.0 Tf 10.00 20.00 Td\n(Grade \\(A\\)) Tj ET\n"
+        );
+    }
+
+    #[test]
+    fn long_text_is_wrapped_and_truncated_to_width() {
+        let lines = wrap_text("one two three four five", Font::Regular, 10.0, 50.0);
+        assert!(lines.len() > 1);
+        assert!(lines
+            .iter()
+            .all(|line| text_width(line, Font::Regular, 10.0) <= 50.0));
+        assert_eq!(lines.join(" "), "one two three four five");
+
+        let cut = truncate_to_width("Mathematics", Font::Regular, 10.0, 40.0);
+        assert!(cut.ends_with("..."));
+        assert!(text_width(&cut, Font::Regular, 10.0) <= 40.0);
+    }
+}
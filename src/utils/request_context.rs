@@ -32,13 +32,18 @@ pub fn request_context(req: &HttpRequest) -> RequestContext {
         role: auth_user
             .and_then(|user| user.role.as_ref())
             .map(|role| format!("{role:?}")),
-        school_id: school_token.map(|token| token.id.clone()).or_else(|| {
-            req.headers()
-                .get("x-school-id")
-                .and_then(|value| value.to_str().ok())
-                .filter(|value| !value.trim().is_empty())
-                .map(ToOwned::to_owned)
-        }),
+        // Signed claims win; the header only names a school when the tokens
+        // do not, and tenant guards still check the caller belongs to it
+        school_id: school_token
+            .map(|token| token.id.clone())
+            .or_else(|| auth_user.and_then(|user| user.current_school_id.clone()))
+            .or_else(|| {
+                req.headers()
+                    .get("x-school-id")
+                    .and_then(|value| value.to_str().ok())
+                    .filter(|value| !value.trim().is_empty())
+                    .map(ToOwned::to_owned)
+            }),
         school_database_name: school_token.map(|token| token.database_name.clone()),
     }
 }
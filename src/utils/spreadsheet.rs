@@ -0,0 +1,793 @@
+//! Minimal CSV and XLSX readers and writers for bulk imports and
+//! templates. Only the first worksheet of a workbook is read and every cell
+//! comes back as text.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression, Crc};
+
+/// Largest uncompressed workbook part we are willing to inflate
+const MAX_PART_BYTES: u64 = 64 * 1024 * 1024;
+const ZIP_LOCAL_HEADER: u32 = 0x0403_4b50;
+const ZIP_CENTRAL_HEADER: u32 = 0x0201_4b50;
+const ZIP_END_OF_DIRECTORY: u32 = 0x0605_4b50;
+
+/// Header row plus data rows, each padded to the header width
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn from_records(mut records: Vec<Vec<String>>) -> Result<Self, String> {
+        records.retain(|record| record.iter().any(|cell| !cell.trim().is_empty()));
+        if records.is_empty() {
+            return Err("The file has no header row".into());
+        }
+
+        let headers: Vec<String> = records
+            .remove(0)
+            .into_iter()
+            .map(|header| header.trim().to_string())
+            .collect();
+        let width = headers.len();
+        let rows = records
+            .into_iter()
+            .map(|mut row| {
+                row.resize(width, String::new());
+                row
+            })
+            .collect();
+
+        Ok(Self { headers, rows })
+    }
+}
+
+/// Reads an upload as XLSX when it is a zip archive, otherwise as CSV
+pub fn read_table(bytes: &[u8]) -> Result<Table, String> {
+    if bytes.starts_with(&ZIP_LOCAL_HEADER.to_le_bytes()) {
+        read_xlsx(bytes)
+    } else {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| "CSV files must be UTF-8 encoded".to_string())?;
+        read_csv(text)
+    }
+}
+
+/// RFC 4180 style CSV. The delimiter is a comma unless the header line has
+/// more semicolons, as spreadsheet exports in some locales do.
+pub fn read_csv(text: &str) -> Result<Table, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let header_line = text.lines().next().unwrap_or_default();
+    let delimiter = if header_line.matches(';').count() > header_line.matches(',').count() {
+        ';'
+    } else {
+        ','
+    };
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+
+        match ch {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ if ch == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted field in CSV".into());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Table::from_records(records)
+}
+
+pub fn read_xlsx(bytes: &[u8]) -> Result<Table, String> {
+    let archive = ZipArchive::parse(bytes)?;
+
+    let shared_strings = match archive.read("xl/sharedStrings.xml")? {
+        Some(xml) => shared_strings(&xml),
+        None => Vec::new(),
+    };
+    let sheet_path = first_sheet_path(&archive)?;
+    let sheet = archive
+        .read(&sheet_path)?
+        .ok_or_else(|| format!("Workbook is missing {}", sheet_path))?;
+
+    Table::from_records(sheet_records(&sheet, &shared_strings))
+}
+
+struct ZipEntry {
+    method: u16,
+    compressed_size: usize,
+    local_offset: usize,
+}
+
+struct ZipArchive<'a> {
+    bytes: &'a [u8],
+    entries: HashMap<String, ZipEntry>,
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|raw| u16::from_le_bytes([raw[0], raw[1]]))
+        .ok_or_else(|| "Truncated XLSX archive".to_string())
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|raw| u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]))
+        .ok_or_else(|| "Truncated XLSX archive".to_string())
+}
+
+impl<'a> ZipArchive<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        // The end-of-directory record sits in the last 22 bytes plus an
+        // optional comment of up to 64 KiB
+        let search_from = bytes.len().saturating_sub(22 + u16::MAX as usize);
+        let end = (search_from..bytes.len().saturating_sub(21))
+            .rev()
+            .find(|&offset| u32_at(bytes, offset) == Ok(ZIP_END_OF_DIRECTORY))
+            .ok_or_else(|| "Not a valid XLSX file".to_string())?;
+
+        let count = u16_at(bytes, end + 10)? as usize;
+        let mut offset = u32_at(bytes, end + 16)? as usize;
+        let mut entries = HashMap::with_capacity(count);
+
+        for _ in 0..count {
+            if u32_at(bytes, offset)? != ZIP_CENTRAL_HEADER {
+                return Err("Corrupt XLSX central directory".into());
+            }
+            let method = u16_at(bytes, offset + 10)?;
+            let compressed_size = u32_at(bytes, offset + 20)? as usize;
+            let name_len = u16_at(bytes, offset + 28)? as usize;
+            let extra_len = u16_at(bytes, offset + 30)? as usize;
+            let comment_len = u16_at(bytes, offset + 32)? as usize;
+            let local_offset = u32_at(bytes, offset + 42)? as usize;
+            let name = bytes
+                .get(offset + 46..offset + 46 + name_len)
+                .ok_or_else(|| "Truncated XLSX archive".to_string())?;
+
+            entries.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                ZipEntry {
+                    method,
+                    compressed_size,
+                    local_offset,
+                },
+            );
+            offset += 46 + name_len + extra_len + comment_len;
+        }
+
+        Ok(Self { bytes, entries })
+    }
+
+    fn read(&self, name: &str) -> Result<Option<String>, String> {
+        let Some(entry) = self.entries.get(name) else {
+            return Ok(None);
+        };
+
+        let header = entry.local_offset;
+        if u32_at(self.bytes, header)? != ZIP_LOCAL_HEADER {
+            return Err(format!("Corrupt XLSX entry {}", name));
+        }
+        let start = header
+            + 30
+            + u16_at(self.bytes, header + 26)? as usize
+            + u16_at(self.bytes, header + 28)? as usize;
+        let data = self
+            .bytes
+            .get(start..start + entry.compressed_size)
+            .ok_or_else(|| format!("Truncated XLSX entry {}", name))?;
+
+        let mut raw = Vec::new();
+        match entry.method {
+            0 => raw.extend_from_slice(data),
+            8 => {
+                DeflateDecoder::new(data)
+                    .take(MAX_PART_BYTES)
+                    .read_to_end(&mut raw)
+                    .map_err(|e| format!("Cannot inflate {}: {}", name, e))?;
+            }
+            other => return Err(format!("Unsupported XLSX compression method {}", other)),
+        }
+
+        String::from_utf8(raw)
+            .map(Some)
+            .map_err(|_| format!("{} is not valid UTF-8", name))
+    }
+}
+
+/// The worksheet listed first in the workbook, resolved through its
+/// relationship id
+fn first_sheet_path(archive: &ZipArchive) -> Result<String, String> {
+    let fallback = "xl/worksheets/sheet1.xml".to_string();
+    let (Some(workbook), Some(rels)) = (
+        archive.read("xl/workbook.xml")?,
+        archive.read("xl/_rels/workbook.xml.rels")?,
+    ) else {
+        return Ok(fallback);
+    };
+
+    let Some(rel_id) = XmlReader::new(&workbook).find_map(|event| match event {
+        XmlEvent::Start { name, attrs, .. } if name == "sheet" => attr(&attrs, "id"),
+        _ => None,
+    }) else {
+        return Ok(fallback);
+    };
+
+    let target = XmlReader::new(&rels).find_map(|event| match event {
+        XmlEvent::Start { name, attrs, .. }
+            if name == "Relationship" && attr(&attrs, "Id").as_deref() == Some(&rel_id) =>
+        {
+            attr(&attrs, "Target")
+        }
+        _ => None,
+    });
+
+    Ok(match target {
+        Some(target) if target.starts_with('/') => target.trim_start_matches('/').to_string(),
+        Some(target) => format!("xl/{}", target),
+        None => fallback,
+    })
+}
+
+fn shared_strings(xml: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut current: Option<String> = None;
+    let mut in_text = false;
+
+    for event in XmlReader::new(xml) {
+        match event {
+            XmlEvent::Start { name, empty, .. } if name == "si" => {
+                if empty {
+                    strings.push(String::new());
+                } else {
+                    current = Some(String::new());
+                }
+            }
+            XmlEvent::Start { name, empty, .. } if name == "t" => in_text = !empty,
+            XmlEvent::Text(text) if in_text => {
+                if let Some(current) = current.as_mut() {
+                    current.push_str(&text);
+                }
+            }
+            XmlEvent::End(name) if name == "t" => in_text = false,
+            XmlEvent::End(name) if name == "si" => strings.extend(current.take()),
+            _ => {}
+        }
+    }
+
+    strings
+}
+
+/// Zero-based column index from a cell reference such as "AB12"
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(|byte| byte.is_ascii_alphabetic())
+        .map(|byte| byte.to_ascii_uppercase())
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    Some(
+        letters
+            .iter()
+            .fold(0usize, |acc, byte| acc * 26 + (byte - b'A' + 1) as usize)
+            - 1,
+    )
+}
+
+fn sheet_records(xml: &str, shared: &[String]) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut row: Option<Vec<String>> = None;
+    let mut cell: Option<(usize, Option<String>)> = None;
+    let mut value = String::new();
+    let mut in_value = false;
+
+    for event in XmlReader::new(xml) {
+        match event {
+            XmlEvent::Start { name, empty, .. } if name == "row" => {
+                if empty {
+                    records.push(Vec::new());
+                } else {
+                    row = Some(Vec::new());
+                }
+            }
+            XmlEvent::Start { name, attrs, empty } if name == "c" => {
+                let Some(current) = row.as_ref() else {
+                    continue;
+                };
+                let index = attr(&attrs, "r")
+                    .and_then(|reference| column_index(&reference))
+                    .unwrap_or(current.len());
+                if empty {
+                    continue;
+                }
+                cell = Some((index, attr(&attrs, "t")));
+                value.clear();
+            }
+            XmlEvent::Start { name, empty, .. } if name == "v" || name == "t" => {
+                in_value = cell.is_some() && !empty;
+            }
+            XmlEvent::Text(text) if in_value => value.push_str(&text),
+            XmlEvent::End(name) if name == "v" || name == "t" => in_value = false,
+            XmlEvent::End(name) if name == "c" => {
+                let (Some((index, kind)), Some(current)) = (cell.take(), row.as_mut()) else {
+                    continue;
+                };
+                let text = match kind.as_deref() {
+                    Some("s") => value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| shared.get(i).cloned())
+                        .unwrap_or_default(),
+                    Some("b") => if value.trim() == "1" { "TRUE" } else { "FALSE" }.to_string(),
+                    _ => std::mem::take(&mut value),
+                };
+                if current.len() <= index {
+                    current.resize(index + 1, String::new());
+                }
+                current[index] = text;
+            }
+            XmlEvent::End(name) if name == "row" => records.extend(row.take()),
+            _ => {}
+        }
+    }
+
+    records
+}
+
+enum XmlEvent {
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+        empty: bool,
+    },
+    End(String),
+    Text(String),
+}
+
+fn attr(attrs: &[(String, String)], name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.clone())
+}
+
+/// Drops a namespace prefix, so "x:row" and "r:id" match "row" and "id"
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_string()
+}
+
+fn unescape(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => out.push(ch),
+            None => out.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Pull parser over the subset of XML that Office writes: elements,
+/// attributes, text and entities. Declarations, comments and CDATA-free
+/// processing instructions are skipped.
+struct XmlReader<'a> {
+    rest: &'a str,
+}
+
+impl<'a> XmlReader<'a> {
+    fn new(xml: &'a str) -> Self {
+        Self { rest: xml }
+    }
+
+    fn parse_attrs(raw: &str) -> Vec<(String, String)> {
+        let mut attrs = Vec::new();
+        let mut rest = raw.trim();
+        while let Some(eq) = rest.find('=') {
+            let key = local_name(rest[..eq].trim());
+            let after = rest[eq + 1..].trim_start();
+            let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                break;
+            };
+            let Some(close) = after[1..].find(quote) else {
+                break;
+            };
+            attrs.push((key, unescape(&after[1..close + 1])));
+            rest = after[close + 2..].trim_start();
+        }
+        attrs
+    }
+}
+
+impl Iterator for XmlReader<'_> {
+    type Item = XmlEvent;
+
+    fn next(&mut self) -> Option<XmlEvent> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            if !self.rest.starts_with('<') {
+                let end = self.rest.find('<').unwrap_or(self.rest.len());
+                let text = &self.rest[..end];
+                self.rest = &self.rest[end..];
+                return Some(XmlEvent::Text(unescape(text)));
+            }
+
+            if self.rest.starts_with("<?") || self.rest.starts_with("<!") {
+                let terminator = if self.rest.starts_with("<!--") {
+                    "-->"
+                } else {
+                    ">"
+                };
+                let end = self.rest.find(terminator)? + terminator.len();
+                self.rest = &self.rest[end..];
+                continue;
+            }
+
+            let end = self.rest.find('>')?;
+            let tag = &self.rest[1..end];
+            self.rest = &self.rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                return Some(XmlEvent::End(local_name(name.trim())));
+            }
+
+            let empty = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let (name, attrs) = match tag.find(char::is_whitespace) {
+                Some(split) => (&tag[..split], Self::parse_attrs(&tag[split..])),
+                None => (tag, Vec::new()),
+            };
+            return Some(XmlEvent::Start {
+                name: local_name(name),
+                attrs,
+                empty,
+            });
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn write_csv(table: &Table) -> String {
+    let mut out = String::new();
+    for record in std::iter::once(&table.headers).chain(&table.rows) {
+        let line: Vec<String> = record.iter().map(|cell| csv_field(cell)).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn escape(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// "A", "B", ... "Z", "AA" for a zero-based column index
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn sheet_xml(table: &Table, numeric_columns: &[usize]) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    );
+
+    xml.push_str("<cols>");
+    for (index, header) in table.headers.iter().enumerate() {
+        let widest = table
+            .rows
+            .iter()
+            .filter_map(|row| row.get(index))
+            .map(|cell| cell.chars().count())
+            .chain(std::iter::once(header.chars().count()))
+            .max()
+            .unwrap_or(8);
+        xml.push_str(&format!(
+            r#"<col min="{0}" max="{0}" width="{1}" customWidth="1"/>"#,
+            index + 1,
+            (widest + 2).clamp(8, 60)
+        ));
+    }
+    xml.push_str("</cols><sheetData>");
+
+    for (row_index, record) in std::iter::once(&table.headers)
+        .chain(&table.rows)
+        .enumerate()
+    {
+        xml.push_str(&format!(r#"<row r="{}">"#, row_index + 1));
+        for (column, value) in record.iter().enumerate() {
+            if value.is_empty() {
+                continue;
+            }
+            let reference = format!("{}{}", column_name(column), row_index + 1);
+            let numeric = row_index > 0
+                && numeric_columns.contains(&column)
+                && value.parse::<f64>().is_ok_and(f64::is_finite);
+            if numeric {
+                xml.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, value));
+            } else {
+                xml.push_str(&format!(
+                    r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                    reference,
+                    escape(value)
+                ));
+            }
+        }
+        xml.push_str("</row>");
+    }
+
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// Writes a deflated zip. Timestamps are fixed at 1980-01-01 so the same
+/// content gives the same bytes.
+fn write_zip(parts: &[(&str, String)]) -> Vec<u8> {
+    const VERSION: u16 = 20;
+    const DEFLATE: u16 = 8;
+    const DOS_DATE_1980: u16 = (1 << 5) | 1;
+
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+
+    for (name, body) in parts {
+        let mut crc = Crc::new();
+        crc.update(body.as_bytes());
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        let compressed = encoder
+            .write_all(body.as_bytes())
+            .and_then(|_| encoder.finish())
+            .unwrap_or_default();
+        let offset = out.len() as u32;
+
+        let mut fields = Vec::new();
+        fields.extend_from_slice(&0u16.to_le_bytes()); // flags
+        fields.extend_from_slice(&DEFLATE.to_le_bytes());
+        fields.extend_from_slice(&0u16.to_le_bytes()); // time
+        fields.extend_from_slice(&DOS_DATE_1980.to_le_bytes());
+        fields.extend_from_slice(&crc.sum().to_le_bytes());
+        fields.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        fields.extend_from_slice(&0u16.to_le_bytes()); // extra length
+
+        out.extend_from_slice(&ZIP_LOCAL_HEADER.to_le_bytes());
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&fields);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&compressed);
+
+        central.extend_from_slice(&ZIP_CENTRAL_HEADER.to_le_bytes());
+        central.extend_from_slice(&VERSION.to_le_bytes()); // made by
+        central.extend_from_slice(&VERSION.to_le_bytes()); // needed
+        central.extend_from_slice(&fields);
+        central.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&ZIP_END_OF_DIRECTORY.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // disk numbers
+    out.extend_from_slice(&(parts.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(parts.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    out
+}
+
+/// One-sheet workbook. Cells in `numeric_columns` that parse as numbers are
+/// written as numbers, everything else as text so ids keep their form.
+pub fn write_xlsx(sheet_name: &str, table: &Table, numeric_columns: &[usize]) -> Vec<u8> {
+    // Excel caps sheet names at 31 characters and forbids a few symbols
+    let sheet_name: String = sheet_name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    let sheet_name = if sheet_name.trim().is_empty() {
+        "Sheet1".to_string()
+    } else {
+        sheet_name
+    };
+
+    let content_types = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+    let root_rels = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+    let workbook = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+        escape(&sheet_name)
+    );
+    let workbook_rels = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+    write_zip(&[
+        ("[Content_Types].xml", content_types.to_string()),
+        ("_rels/.rels", root_rels.to_string()),
+        ("xl/workbook.xml", workbook),
+        ("xl/_rels/workbook.xml.rels", workbook_rels.to_string()),
+        (
+            "xl/worksheets/sheet1.xml",
+            sheet_xml(table, numeric_columns),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stored (uncompressed) zip holding the given parts
+    fn stored_zip(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for (name, body) in parts {
+            let offset = out.len() as u32;
+            let mut header = Vec::new();
+            header.extend_from_slice(&ZIP_LOCAL_HEADER.to_le_bytes());
+            header.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            header.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&[0, 0]);
+            out.extend_from_slice(&header);
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(body.as_bytes());
+
+            central.extend_from_slice(&ZIP_CENTRAL_HEADER.to_le_bytes());
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let directory_offset = out.len() as u32;
+        out.extend_from_slice(&central);
+        out.extend_from_slice(&ZIP_END_OF_DIRECTORY.to_le_bytes());
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&(parts.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(parts.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&directory_offset.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+
+    #[test]
+    fn csv_handles_quotes_and_blank_lines() {
+        let table =
+            read_csv("\u{feff}Name,Email\r\n\"Keza, A.\",\"k\"\"z@x.rw\"\r\n\r\nJo,\n").unwrap();
+
+        assert_eq!(table.headers, vec!["Name", "Email"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0], vec!["Keza, A.", "k\"z@x.rw"]);
+        assert_eq!(table.rows[1], vec!["Jo", ""]);
+    }
+
+    #[test]
+    fn semicolon_exports_are_detected() {
+        let table = read_csv("name;class\nAline;S1 A\n").unwrap();
+
+        assert_eq!(table.rows[0], vec!["Aline", "S1 A"]);
+    }
+
+    #[test]
+    fn xlsx_reads_shared_and_inline_strings_with_gaps() {
+        let sheet = r#"<?xml version="1.0"?><worksheet><sheetData>
+            <row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row>
+            <row r="2"><c r="A2" t="inlineStr"><is><t>Mugisha &amp; Co</t></is></c><c r="C2"><v>788123456</v></c></row>
+            </sheetData></worksheet>"#;
+        let strings =
+            r#"<sst><si><t>Name</t></si><si><r><t>Pho</t></r><r><t>ne</t></r></si></sst>"#;
+        let bytes = stored_zip(&[
+            ("xl/sharedStrings.xml", strings),
+            ("xl/worksheets/sheet1.xml", sheet),
+        ]);
+
+        let table = read_table(&bytes).unwrap();
+
+        assert_eq!(table.headers, vec!["Name", "", "Phone"]);
+        assert_eq!(table.rows, vec![vec!["Mugisha & Co", "", "788123456"]]);
+    }
+
+    #[test]
+    fn written_files_read_back() {
+        let table = Table {
+            headers: vec!["student_id".into(), "name".into(), "score".into()],
+            rows: vec![
+                vec!["65e1".into(), "Uwase, \"Ange\"".into(), "17.5".into()],
+                vec!["65e2".into(), "Ishimwe <B>".into(), String::new()],
+            ],
+        };
+
+        let csv = read_csv(&write_csv(&table)).unwrap();
+        assert_eq!(csv.rows, table.rows);
+
+        let xlsx = read_table(&write_xlsx("S1 A / Maths", &table, &[2])).unwrap();
+        assert_eq!(xlsx.headers, table.headers);
+        assert_eq!(xlsx.rows, table.rows);
+        assert_eq!(column_name(27), "AB");
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+/// Histogram bucket over percentages. Buckets are half-open, `[from, to)`,
+/// except the last, which includes 100.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HistogramBin {
+    pub from: f64,
+    pub to: f64,
+    pub count: i64,
+}
+
+/// Descriptive statistics of a set of percentages. Quartiles interpolate
+/// between neighbouring values, as spreadsheets do; the standard deviation
+/// is the population one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Distribution {
+    pub count: i64,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub q1: f64,
+    pub q3: f64,
+    pub histogram: Vec<HistogramBin>,
+}
+
+/// Value at fraction `p` (0 to 1) of sorted `values`
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let position = p * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Statistics of `values`, which are expected to lie within 0..=100, with
+/// `bins` equal histogram buckets (at least one)
+pub fn distribution(values: &[f64], bins: usize) -> Distribution {
+    let bins = bins.max(1);
+    let width = 100.0 / bins as f64;
+    let mut histogram: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            from: round2(i as f64 * width),
+            to: round2((i + 1) as f64 * width),
+            count: 0,
+        })
+        .collect();
+
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    for value in &sorted {
+        let bin = ((value.clamp(0.0, 100.0) / width) as usize).min(bins - 1);
+        histogram[bin].count += 1;
+    }
+
+    if sorted.is_empty() {
+        return Distribution {
+            count: 0,
+            mean: 0.0,
+            median: 0.0,
+            std_dev: 0.0,
+            min: 0.0,
+            max: 0.0,
+            q1: 0.0,
+            q3: 0.0,
+            histogram,
+        };
+    }
+
+    let count = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / count;
+    let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+    Distribution {
+        count: sorted.len() as i64,
+        mean: round2(mean),
+        median: round2(quantile(&sorted, 0.5)),
+        std_dev: round2(variance.sqrt()),
+        min: round2(sorted[0]),
+        max: round2(sorted[sorted.len() - 1]),
+        q1: round2(quantile(&sorted, 0.25)),
+        q3: round2(quantile(&sorted, 0.75)),
+        histogram,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_a_set_of_marks() {
+        let stats = distribution(&[40.0, 50.0, 60.0, 70.0, 100.0], 10);
+
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.mean, 64.0);
+        assert_eq!(stats.median, 60.0);
+        assert_eq!(stats.q1, 50.0);
+        assert_eq!(stats.q3, 70.0);
+        assert_eq!(stats.std_dev, 20.59);
+        assert_eq!(stats.histogram.len(), 10);
+        assert_eq!(stats.histogram[4].count, 1);
+        // 100 lands in the last bucket
+        assert_eq!(stats.histogram[9].count, 1);
+    }
+
+    #[test]
+    fn empty_sets_have_empty_buckets() {
+        let stats = distribution(&[], 4);
+
+        assert_eq!(stats.count, 0);
+        assert_eq!(stats.histogram.len(), 4);
+        assert!(stats.histogram.iter().all(|bin| bin.count == 0));
+    }
+}
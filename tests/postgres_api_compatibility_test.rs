@@ -840,3 +840,820 @@ fn database_status_uses_postgres_without_mongo_storage_apis() {
     assert!(service.contains("pg_total_relation_size"));
     assert!(service.contains("school_id"));
 }
+
+#[test]
+fn attendance_register_uses_postgres_without_mongo_storage_apis() {
+    let migrated_files = [
+        include_str!("../src/repositories/attendance_repo.rs"),
+        include_str!("../src/services/attendance_service.rs"),
+        include_str!("../src/api/attendance.rs"),
+        include_str!("../src/domain/attendance.rs"),
+    ];
+
+    for file in migrated_files {
+        assert!(!file.contains("mongodb::"));
+        assert!(!file.contains("bson::"));
+        assert!(!file.contains("Collection<"));
+        assert!(!file.contains("Database"));
+        assert!(!file.contains("doc!"));
+        assert!(!file.contains("legacy_mongo_base_repo"));
+    }
+
+    let api = include_str!("../src/api/attendance.rs");
+    assert!(api.contains("check_admin_or_class_teacher"));
+}
+
+#[test]
+fn attendance_schema_allows_one_mark_per_period() {
+    let migration = include_str!("../migrations/20261018000100_attendance_register.sql");
+
+    assert!(migration.contains("ADD COLUMN IF NOT EXISTS period_id TEXT"));
+    assert!(migration.contains("DROP CONSTRAINT IF EXISTS attendance_school_id_student_id_date_key"));
+    assert!(migration.contains("attendance_student_date_period_unique"));
+    assert!(migration.contains("COALESCE(period_id, '')"));
+}
+
+#[test]
+fn attendance_rates_count_daily_rows_once_and_late_as_attended() {
+    let repo = include_str!("../src/repositories/attendance_repo.rs");
+    assert!(repo.contains("WHERE deleted_at IS NULL AND period_id IS NULL AND school_id ="));
+
+    let parent = include_str!("../src/services/parent_service.rs");
+    assert_eq!(parent.matches("AND period_id IS NULL AND deleted_at IS NULL").count(), 2);
+    assert!(parent.contains("counts.attendance_percentage()"));
+
+    let analytics = include_str!("../src/services/analytics_service.rs");
+    assert!(analytics.contains(" AND period_id IS NULL AND deleted_at IS NULL"));
+    assert!(analytics.contains("lower(status) IN ('present', 'late')"));
+
+    let promotion = include_str!("../src/repositories/promotion_repo.rs");
+    assert!(promotion.contains("lower(status) IN ('present', 'late')"));
+
+    let domain = include_str!("../src/domain/attendance.rs");
+    assert!(domain.contains("(self.present + self.late) as f64 / total as f64"));
+}
+
+#[test]
+fn finance_ledger_uses_postgres_without_mongo_storage_apis() {
+    let migrated_files = [
+        include_str!("../src/repositories/finance_repo.rs"),
+        include_str!("../src/services/finance_service.rs"),
+        include_str!("../src/api/finance.rs"),
+        include_str!("../src/domain/finance.rs"),
+    ];
+
+    for file in migrated_files {
+        assert!(!file.contains("mongodb::"));
+        assert!(!file.contains("bson::"));
+        assert!(!file.contains("Collection<"));
+        assert!(!file.contains("Database"));
+        assert!(!file.contains("doc!"));
+        assert!(!file.contains("legacy_mongo_base_repo"));
+    }
+
+    let repo = include_str!("../src/repositories/finance_repo.rs");
+    assert!(repo.contains("FOR UPDATE"));
+
+    // Staff only touch the ledger of a school they are a member of
+    let api = include_str!("../src/api/finance.rs");
+    assert!(api.contains("require_request_permission(req, user, permission, &permission_service)"));
+    assert!(!api.contains("request_context(req)"));
+}
+
+#[test]
+fn finance_schema_keeps_one_invoice_per_student_and_structure() {
+    let migration = include_str!("../migrations/20261018000200_finance_fee_billing.sql");
+
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS fee_structures"));
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS fee_structure_items"));
+    assert!(migration.contains("ADD COLUMN IF NOT EXISTS invoice_id TEXT"));
+    assert!(migration.contains("finance_records_invoice_structure_unique"));
+    assert!(migration.contains("WHERE record_type = 'invoice' AND deleted_at IS NULL"));
+}
+
+#[test]
+fn notification_inbox_persists_events_and_replays_unread_on_connect() {
+    let migrated_files = [
+        include_str!("../src/repositories/notification_repo.rs"),
+        include_str!("../src/services/notification_service.rs"),
+        include_str!("../src/api/notifications.rs"),
+        include_str!("../src/domain/notification.rs"),
+    ];
+
+    for file in migrated_files {
+        assert!(!file.contains("mongodb::"));
+        assert!(!file.contains("bson::"));
+        assert!(!file.contains("Collection<"));
+        assert!(!file.contains("doc!"));
+        assert!(!file.contains("legacy_mongo_base_repo"));
+    }
+
+    let event_service = include_str!("../src/services/event_service.rs");
+    assert!(event_service.contains("NotificationService"));
+
+    let events_api = include_str!("../src/api/events.rs");
+    assert!(events_api.contains("unread_replay"));
+
+    let migration = include_str!("../migrations/20261018000300_notification_inbox.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS notifications"));
+    assert!(migration.contains("PRIMARY KEY (notification_id, user_id)"));
+    assert!(migration.contains("PRIMARY KEY (user_id, category)"));
+}
+
+#[test]
+fn school_backups_are_native_postgres_snapshots() {
+    let backup_service = include_str!("../src/services/backup_service.rs");
+    let backup_archive = include_str!("../src/services/backup_archive.rs");
+
+    for file in [backup_service, backup_archive] {
+        assert!(!file.contains("Command::new"));
+        assert!(!file.contains("MONGODB_URI"));
+        assert!(!file.contains("mongodb::"));
+    }
+
+    assert!(backup_service.contains("backup_archive::export_school"));
+    assert!(backup_service.contains("backup_archive::restore_school"));
+    assert!(backup_archive.contains("BackupManifest"));
+    assert!(backup_archive.contains("payload_sha256"));
+    assert!(backup_archive.contains("pg_advisory_xact_lock"));
+    // Archive files stay off the async runtime; restores are always verified
+    assert!(!backup_service.contains("std::fs::"));
+    assert!(backup_archive.contains("tokio::task::spawn_blocking"));
+    assert!(backup_archive.contains("expected_checksum: &str"));
+    assert!(backup_archive.contains("check_row_school("));
+
+    let migration = include_str!("../migrations/20261018000400_native_school_backups.sql");
+    assert!(migration.contains("ADD COLUMN IF NOT EXISTS checksum"));
+}
+
+#[test]
+fn automated_backups_follow_school_schedule_and_retention() {
+    let scheduler = include_str!("../src/services/backup_scheduler.rs");
+    assert!(scheduler.contains("CronSchedule::parse"));
+    assert!(scheduler.contains("claim_schedule_slot"));
+    assert!(scheduler.contains("run_automated_backup"));
+
+    let main = include_str!("../src/main.rs");
+    assert!(main.contains("backup_scheduler::start"));
+
+    let backup_service = include_str!("../src/services/backup_service.rs");
+    assert!(backup_service.contains("BackupType::Automated"));
+    assert!(backup_service.contains("backup.retention.prune"));
+    assert!(backup_service.contains("backup_type = 'Automated'"));
+
+    let migration = include_str!("../migrations/20261018000500_backup_schedules.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS school_backup_policies"));
+    assert!(migration.contains("keep_monthly INTEGER NOT NULL DEFAULT 12"));
+}
+
+#[test]
+fn event_bus_fans_out_across_nodes_with_listen_notify() {
+    let event_bus = include_str!("../src/services/event_bus.rs");
+    assert!(event_bus.contains("pub trait EventTransport"));
+    assert!(event_bus.contains("pub async fn deliver_local"));
+    assert!(event_bus.contains("self.transport.publish(event)"));
+
+    let transport = include_str!("../src/services/pg_event_transport.rs");
+    assert!(transport.contains("PgListener::connect_with"));
+    assert!(transport.contains("pg_notify"));
+    assert!(transport.contains("envelope.node_id == self.node_id"));
+    assert!(transport.contains("bus.deliver_local(&event)"));
+
+    let state = include_str!("../src/config/state.rs");
+    assert!(state.contains("EventBus::with_transport"));
+    // Conversation sockets share the transport, so frames and unsubscribes
+    // reach sessions held by other nodes
+    assert!(state.contains("ConversationHub::with_transport(transport.clone())"));
+    assert!(state.contains(".with_conversation_hub(conversation_hub.clone())"));
+    assert!(event_bus.contains("hub.deliver_remote(event).await"));
+
+    let conversations = include_str!("../src/api/conversations_api.rs");
+    assert!(conversations.contains("#[delete(\"/{id}/participants/{participant_id}\")]"));
+    assert!(conversations.contains(".unsubscribe(&conversation_id, &user_id)"));
+
+    let main = include_str!("../src/main.rs");
+    assert!(main.contains("event_bus.start_transport()"));
+}
+
+#[test]
+fn event_streams_resume_from_last_event_id_with_heartbeats() {
+    let events_api = include_str!("../src/api/events.rs");
+    assert!(events_api.contains("Last-Event-ID"));
+    assert!(events_api.contains("replay_since"));
+    assert!(events_api.contains(": heartbeat"));
+    assert!(events_api.contains("retry: {}"));
+
+    let event_bus = include_str!("../src/services/event_bus.rs");
+    assert!(event_bus.contains("REPLAY_BUFFER_SIZE"));
+    assert!(event_bus.contains("\"id: {}\\ndata: {}\\n\\n\""));
+
+    let transport = include_str!("../src/services/pg_event_transport.rs");
+    assert!(transport.contains("nextval('event_bus_event_id_seq')"));
+}
+
+#[test]
+fn conversation_socket_pushes_hub_events() {
+    let socket = include_str!("../src/api/messaging_socket.rs");
+    assert!(socket.contains("hub.join(&conversation_id"));
+    assert!(socket.contains("WsMessage::Typing"));
+    assert!(socket.contains("WsMessage::ReadAck"));
+    assert!(!socket.contains("get::<ObjectId>()"));
+
+    let messages_api = include_str!("../src/api/messages_api.rs");
+    assert!(messages_api.contains("WsMessage::MessageCreated"));
+    assert!(messages_api.contains("WsMessage::MessageDeleted"));
+    assert!(messages_api.contains("WsMessage::MessageRead"));
+
+    let conversations_api = include_str!("../src/api/conversations_api.rs");
+    assert!(conversations_api.contains("WsMessage::ConversationCreated"));
+    assert!(conversations_api.contains("WsMessage::ParticipantAdded"));
+
+    let routes = include_str!("../src/api/mod.rs");
+    let socket_at = routes.find("messaging_socket::init(cfg)").unwrap();
+    let messages_at = routes.find("messages_api::init(cfg)").unwrap();
+    assert!(socket_at < messages_at);
+}
+
+#[test]
+fn promotion_engine_proposes_and_applies_batches() {
+    let migration = include_str!("../migrations/20261018000800_promotion_engine.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS promotion_batch_results"));
+    assert!(migration.contains("WHERE status = 'Proposed'"));
+
+    let service = include_str!("../src/services/promotion_service.rs");
+    assert!(service.contains("still need a decision"));
+    assert!(service.contains("PromotionStatus::Graduated"));
+
+    let repo = include_str!("../src/repositories/promotion_repo.rs");
+    assert!(repo.contains("WHERE id = $1 AND status = 'Proposed'"));
+    assert!(repo.contains("SET class_id = $3, subclass_id = NULL"));
+    assert!(repo.contains("pool.begin()"));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("promotion_api::init(cfg)"));
+}
+
+#[test]
+fn annual_results_weigh_terms_and_rank_classes() {
+    let migration = include_str!("../migrations/20261018000900_annual_results.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS education_year_term_weights"));
+    assert!(migration.contains("UNIQUE (student_id, education_year_id)"));
+
+    let service = include_str!("../src/services/annual_result_service.rs");
+    assert!(service.contains("FROM education_year_term_weights"));
+    assert!(service.contains("RANK() OVER (ORDER BY ROUND(gpa::NUMERIC, 3) DESC)"));
+    assert!(service.contains("AVG(sar.gpa) OVER"));
+
+    let results_api = include_str!("../src/api/results_api.rs");
+    assert!(results_api.contains("/student/{student_id}/year/{education_year_id}"));
+    assert!(results_api.contains("/class/{class_id}/year/{education_year_id}"));
+    assert!(results_api.contains("/student/{student_id}/cumulative"));
+
+    let promotion_repo = include_str!("../src/repositories/promotion_repo.rs");
+    assert!(promotion_repo.contains("LEFT JOIN student_annual_results sar"));
+}
+
+#[test]
+fn timetable_solver_respects_teacher_and_room_constraints() {
+    let migration = include_str!("../migrations/20261018001000_timetable_solver.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS user_availability"));
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS school_rooms"));
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS class_subject_scheduling_rules"));
+    assert!(migration.contains("ON class_timetables (class_id, education_year_id, term_order)"));
+
+    let solver = include_str!("../src/handler/class_timetable_handler.rs");
+    assert!(!solver.contains("rand::"));
+    assert!(solver.contains("TimetableConstraint::TeacherDoubleBooked"));
+    assert!(solver.contains("TimetableConstraint::RoomUnavailable"));
+    assert!(solver.contains("fn repair("));
+
+    let service = include_str!("../src/services/class_timetable_service.rs");
+    assert!(service.contains("JOIN user_availability ua ON ua.user_id = t.user_id"));
+    assert!(service.contains("ON CONFLICT (class_id, education_year_id, term_order) DO UPDATE"));
+
+    let user_repo = include_str!("../src/repositories/user_repo.rs");
+    assert!(user_repo.contains("INSERT INTO user_availability"));
+
+    let routes = include_str!("../src/api/school_collections/school_class_timetable.rs");
+    assert!(routes.contains("#[post(\"/solve\")]"));
+    assert!(routes.contains("/subject-rules/{class_subject_id}"));
+}
+
+#[test]
+fn timetable_validation_reports_and_blocks_hard_clashes() {
+    let checker = include_str!("../src/handler/timetable_clash_handler.rs");
+    assert!(checker.contains("TimetableIssueKind::TeacherDoubleBooked"));
+    assert!(checker.contains("TimetableIssueKind::BreakOverlap"));
+    assert!(checker.contains("TimetableIssueKind::HolidayPeriod"));
+    assert!(checker.contains("TimetableIssueKind::UnderScheduled"));
+
+    let service = include_str!("../src/services/class_timetable_service.rs");
+    assert!(service.contains(".check_save(&candidate, Some(&existing))"));
+    assert!(service.contains("check_save(&dto, None)"));
+
+    let validation = include_str!("../src/services/timetable_validation_service.rs");
+    assert!(validation.contains("TimetableOverrideType::Trade"));
+    assert!(validation.contains("!known.contains(&issue.key())"));
+
+    let routes = include_str!("../src/api/school_collections/school_class_timetable.rs");
+    assert!(routes.contains("#[get(\"/validation\")]"));
+}
+
+#[test]
+fn calendar_feeds_publish_revocable_ical_subscriptions() {
+    let migration = include_str!("../migrations/20261018001100_calendar_feeds.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS calendar_feed_tokens"));
+    assert!(migration.contains("token_hash TEXT NOT NULL UNIQUE"));
+
+    let ical = include_str!("../src/utils/ical.rs");
+    assert!(ical.contains("RRULE:FREQ=WEEKLY;UNTIL="));
+    assert!(ical.contains("MAX_LINE_OCTETS: usize = 75"));
+
+    let service = include_str!("../src/services/calendar_feed_service.rs");
+    assert!(service.contains("WHERE token_hash = $1 AND revoked_at IS NULL"));
+    assert!(service.contains("class_school_days"));
+    assert!(service.contains("FROM exams"));
+    assert!(service.contains("coalesce(due_date, due_at)"));
+
+    let api = include_str!("../src/api/calendar_feed_api.rs");
+    assert!(api.contains("/calendar-feeds/{token}/calendar.ics"));
+    assert!(api.contains("text/calendar; charset=utf-8"));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("calendar_feed_api::init(cfg)"));
+}
+
+#[test]
+fn teacher_covers_suggest_and_notify_substitutes() {
+    let migration = include_str!("../migrations/20261018001200_teacher_covers.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS teacher_absences"));
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS teacher_covers"));
+    assert!(migration.contains("ON teacher_covers (period_id, cover_date)"));
+
+    let service = include_str!("../src/services/teacher_cover_service.rs");
+    assert!(service.contains("FROM teacher_subjects"));
+    assert!(service.contains("JOIN user_availability ua"));
+    assert!(service.contains("find_by_school_and_term"));
+    assert!(service.contains("This lesson is already covered"));
+
+    let api = include_str!("../src/api/teacher_cover_api.rs");
+    assert!(api.contains("#[post(\"/absences/{id}/covers\")]"));
+    assert!(api.contains("#[get(\"/teacher/{teacher_id}/schedule\")]"));
+    assert!(api.contains("\"cover_assigned\""));
+    // Schedules are readable by school staff, the class's teachers and the
+    // teacher whose timetable it is
+    assert!(api.contains("require_scoped_permission(user, \"schedule.read\", &target, &permission_service)"));
+    assert!(api.contains("check_schedule_access(&req, &user, &school_id, Some(&class_id), &state)"));
+    assert!(api.contains("service.teacher_user_id(&school_id, &teacher_id)"));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("teacher_cover_api::init(cfg)"));
+}
+
+#[test]
+fn report_cards_render_printable_pdfs_per_student_and_class() {
+    let migration = include_str!("../migrations/20261018001300_report_card_comments.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS report_card_comments"));
+
+    let pdf = include_str!("../src/utils/pdf.rs");
+    assert!(pdf.contains("/Encoding /WinAnsiEncoding"));
+    assert!(pdf.contains("/Filter /DCTDecode"));
+    assert!(pdf.contains("/SMask"));
+
+    let renderer = include_str!("../src/handler/report_card_handler.rs");
+    assert!(renderer.contains("pub fn render_report_cards("));
+    assert!(renderer.contains("Grading scale: {}"));
+    assert!(renderer.contains("rank_in_class"));
+
+    let service = include_str!("../src/services/report_card_service.rs");
+    assert!(service.contains("get_active_scale"));
+    assert!(service.contains(
+        "ON CONFLICT (school_id, student_id, exam_id, coalesce(class_subject_id, ''))"
+    ));
+    assert!(service.contains("LEFT JOIN class_subjects cs ON cs.id = $4 AND cs.school_id = $1"));
+    assert!(service.contains("CloudinaryService::is_own_asset_url(url)"));
+    assert!(service.contains("reqwest::redirect::Policy::none()"));
+    assert!(service.contains("bytes.len() + chunk.len() > MAX_LOGO_BYTES"));
+
+    let key = include_str!("../migrations/20261018002300_report_card_comment_school_key.sql");
+    assert!(key.contains("(school_id, student_id, exam_id, coalesce(class_subject_id, ''))"));
+
+    let api = include_str!("../src/api/report_card_api.rs");
+    assert!(api.contains("#[get(\"/class/{class_id}/exam/{exam_id}/pdf\")]"));
+    assert_eq!(api.matches("ensure_can_read(&service, &user").count(), 2);
+    assert!(api.contains("\"application/pdf\""));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("report_card_api::init(cfg)"));
+}
+
+#[test]
+fn bulk_imports_validate_before_committing_in_one_transaction() {
+    let migration = include_str!("../migrations/20261018001400_bulk_imports.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS bulk_imports"));
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS bulk_import_rows"));
+
+    let spreadsheet = include_str!("../src/utils/spreadsheet.rs");
+    assert!(spreadsheet.contains("pub fn read_csv("));
+    assert!(spreadsheet.contains("pub fn read_xlsx("));
+    assert!(spreadsheet.contains("xl/sharedStrings.xml"));
+
+    let service = include_str!("../src/services/bulk_import_service.rs");
+    assert!(service.contains("students.find_one(None, Some(query))"));
+    assert!(service.contains("self.pool.begin()"));
+    assert!(service.contains("INSERT INTO school_memberships"));
+    assert!(service.contains("INSERT INTO parent_student_links"));
+    assert!(service.contains("if options.dry_run || report.error_count > 0"));
+    // Existing accounts are reported, never silently attached
+    assert!(service.contains("ImportRowStatus::Conflict"));
+    assert!(!service.contains("find_or_create_user"));
+    let conflicts = include_str!("../migrations/20261018002000_bulk_import_conflicts.sql");
+    assert!(conflicts.contains("'Conflict'"));
+
+    let api = include_str!("../src/api/bulk_import_api.rs");
+    assert!(api.contains("mut payload: Multipart"));
+    assert!(api.contains("UnprocessableEntity()"));
+    assert!(api.contains("require_request_permission(req, user, \"import.manage\""));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("bulk_import_api::init(cfg)"));
+}
+
+#[test]
+fn marksheets_round_trip_through_templates_with_audited_changes() {
+    let spreadsheet = include_str!("../src/utils/spreadsheet.rs");
+    assert!(spreadsheet.contains("pub fn write_csv("));
+    assert!(spreadsheet.contains("pub fn write_xlsx("));
+
+    let domain = include_str!("../src/domain/marksheet.rs");
+    assert!(domain.contains("pub const MARKSHEET_COLUMNS"));
+
+    let service = include_str!("../src/services/marksheet_service.rs");
+    assert!(service.contains("is above max_score"));
+    assert!(service.contains("Unknown student"));
+    assert!(service.contains("Student already appears on row"));
+    assert!(service.contains("INSERT INTO score_audit_logs"));
+    assert!(service.contains("self.pool.begin()"));
+
+    let api = include_str!("../src/api/marksheet_api.rs");
+    assert!(api.contains("#[get(\"/template\")]"));
+    assert!(api.contains("mut payload: Multipart"));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("marksheet_api::init(cfg)"));
+}
+
+#[test]
+fn finalized_marks_are_locked_until_an_audited_reopen() {
+    let migration = include_str!("../migrations/20261018001500_score_moderation.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS score_moderations"));
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS score_moderation_events"));
+    assert!(migration.contains("results_published_at"));
+
+    let domain = include_str!("../src/domain/score_moderation.rs");
+    assert!(domain.contains("pub const REVIEW_PERMISSION: &str = \"scores.review\""));
+    assert!(domain.contains("(ModerationAction::Reopen, Approved) => Reopened"));
+
+    let service = include_str!("../src/services/score_moderation_service.rs");
+    assert!(service.contains("pub async fn ensure_editable("));
+    assert!(service.contains("FOR UPDATE OF m"));
+    // Schools approve and publish through their own roles
+    assert!(!service.contains("is_admin("));
+    assert!(domain.contains("pub const MODERATE_PERMISSION: &str = \"result.moderate\""));
+    assert!(domain.contains("pub const PUBLISH_PERMISSION: &str = \"result.publish\""));
+    assert!(service.contains("INSERT INTO score_moderation_events"));
+    assert!(service.contains("UPDATE student_term_results SET is_finalized"));
+
+    // Every mark write checks and writes in one transaction
+    let scores = include_str!("../src/services/score_service.rs");
+    assert!(scores.contains("ScoreModerationService::ensure_editable("));
+    assert!(scores.contains("Self::ensure_editable(\n            &mut tx,"));
+    let marksheets = include_str!("../src/services/marksheet_service.rs");
+    assert!(marksheets.contains("ScoreModerationService::ensure_editable(\n            &mut tx,"));
+
+    let parents = include_str!("../src/services/parent_service.rs");
+    assert!(parents.contains("AND is_finalized = true"));
+    let results = include_str!("../src/api/results_api.rs");
+    assert!(results.contains("Results for this exam are not published yet"));
+
+    let routes = include_str!("../src/api/mod.rs");
+    assert!(routes.contains("score_moderation_api::init(cfg)"));
+}
+
+#[test]
+fn exam_statistics_compare_subclasses_and_flag_drops() {
+    let statistics = include_str!("../src/utils/statistics.rs");
+    assert!(statistics.contains("pub fn distribution(values: &[f64], bins: usize)"));
+
+    let service = include_str!("../src/services/analytics_service.rs");
+    assert!(service.contains("pub async fn get_exam_statistics("));
+    assert!(service.contains("LEFT JOIN classes parent ON parent.id = c.parent_class_id"));
+    assert!(service.contains("fn teacher_comparisons("));
+
+    let ranking = include_str!("../src/services/ranking_service.rs");
+    assert!(ranking.contains("pub async fn get_result_deltas("));
+    assert!(ranking.contains("pub async fn get_declining_students("));
+    assert!(ranking.contains("flagged: -delta > drop_threshold"));
+
+    let api = include_str!("../src/api/analytics_api.rs");
+    assert!(api.contains("#[get(\"/exams/{exam_id}/statistics\")]"));
+    let ranking_api = include_str!("../src/api/ranking_api.rs");
+    assert!(ranking_api.contains("/declining"));
+}
+
+#[test]
+fn api_errors_carry_codes_statuses_and_request_ids() {
+    let errors = include_str!("../src/errors.rs");
+    assert!(errors.contains("pub enum ErrorCode"));
+    assert!(errors.contains("ErrorCode::Validation => StatusCode::UNPROCESSABLE_ENTITY"));
+    assert!(errors.contains("impl From<sqlx::Error> for AppError"));
+    assert!(errors.contains("sqlx::error::ErrorKind::UniqueViolation"));
+    assert!(errors.contains("request_id: REQUEST_ID.try_with(|id| id.clone()).ok()"));
+
+    let logging = include_str!("../src/middleware/logging.rs");
+    assert!(logging.contains("REQUEST_ID"));
+    assert!(logging.contains("x-request-id"));
+
+    let exams = include_str!("../src/services/exam_service.rs");
+    assert!(exams.contains("AppError::from(error)"));
+    let school_api = include_str!("../src/api/school_api.rs");
+    assert!(school_api
+        .contains("AppError::unauthorized(\"Missing School-Token header\").to_response()"));
+    assert!(!school_api.contains("HttpResponse::BadRequest().json(err)"));
+
+    for handlers in [
+        include_str!("../src/api/template_subject_api.rs"),
+        include_str!("../src/api/class_timetable.rs"),
+        include_str!("../src/api/comment_api.rs"),
+        include_str!("../src/api/announcement_api.rs"),
+    ] {
+        assert!(!handlers.contains("Err(message) => HttpResponse::BadRequest().json(message)"));
+        assert!(!handlers.contains("Err(message) => HttpResponse::NotFound().json(message)"));
+    }
+
+    for handlers in [
+        include_str!("../src/api/auth_api.rs"),
+        include_str!("../src/api/users.rs"),
+        include_str!("../src/api/database_status.rs"),
+        include_str!("../src/api/parent_api.rs"),
+        include_str!("../src/api/results_api.rs"),
+    ] {
+        assert!(!handlers.contains("ReqErrModel"));
+        assert!(!handlers.contains("\"error\":"));
+        assert!(!handlers.contains("HttpResponse::BadRequest()"));
+    }
+    // Branch on the code, not on the wording of the message
+    let conversations = include_str!("../src/api/conversations_api.rs");
+    assert!(conversations.contains("err.code == ErrorCode::NotFound"));
+    assert!(!conversations.contains("err.message.contains("));
+    let auth = include_str!("../src/api/auth_api.rs");
+    assert!(!auth.contains("HttpResponse::Unauthorized().json(message)"));
+}
+
+#[test]
+fn tenant_guards_check_school_membership_and_permission_scope() {
+    let guards = include_str!("../src/guards/role_guard.rs");
+    assert!(guards.contains("pub async fn require_tenant_access("));
+    assert!(guards.contains("pub async fn check_school_access("));
+    assert!(guards.contains("pub async fn check_class_access("));
+    assert!(guards.contains("pub async fn require_scoped_permission("));
+    assert!(!guards.contains("_school_id: &str"));
+
+    let tenants = include_str!("../src/services/tenant_service.rs");
+    assert!(tenants.contains("FROM school_memberships"));
+    assert!(tenants.contains("status = 'active' AND ended_at IS NULL"));
+    assert!(tenants.contains("pub async fn teaches_class("));
+
+    let engine = include_str!("../src/services/permission_service.rs");
+    assert!(engine.contains("grant.scope == PermissionScope::School"));
+    assert!(engine.contains("tenants.teaches_class(user_id, class_id)"));
+
+    let migration = include_str!("../migrations/20261018001600_permission_scopes.sql");
+    assert!(migration.contains("ADD COLUMN IF NOT EXISTS scope TEXT"));
+
+    let schools = include_str!("../src/api/school_api.rs");
+    assert!(schools.contains("check_school_access(&user, &school_id, &tenant_service)"));
+    let classes = include_str!("../src/api/class_api.rs");
+    assert!(classes.contains("TenantEntity::Class"));
+
+    // The client's school header never outranks the signed claims, and every
+    // school-scoped API resolves its school through one membership check
+    assert!(guards.contains("pub async fn require_request_school("));
+    assert!(guards.contains("pub async fn check_admin_or_class_teacher("));
+    assert!(guards.contains("pub async fn check_student_access("));
+    assert!(!guards.contains("fn is_subject_teacher("));
+    let context = include_str!("../src/utils/request_context.rs");
+    let token = context.find("school_token").unwrap();
+    let header = context.find("\"x-school-id\"").unwrap();
+    assert!(token < header);
+    let tenant_middleware = include_str!("../src/middleware/tenant_middleware.rs");
+    assert!(!tenant_middleware.contains(".get(\"x-school-id\")"));
+    for source in [
+        include_str!("../src/api/teacher_cover_api.rs"),
+        include_str!("../src/api/promotion_api.rs"),
+        include_str!("../src/api/marksheet_api.rs"),
+        include_str!("../src/api/score_moderation_api.rs"),
+        include_str!("../src/api/report_card_api.rs"),
+        include_str!("../src/api/attendance.rs"),
+    ] {
+        assert!(
+            source.contains("require_request_school(")
+                || source.contains("require_request_permission(")
+        );
+    }
+    let attendance = include_str!("../src/api/attendance.rs");
+    assert!(attendance.contains("PermissionTarget::school(school_id).class(class_id)"));
+}
+
+#[test]
+fn permissions_are_evaluated_from_school_roles_with_a_request_cache() {
+    let engine = include_str!("../src/services/permission_service.rs");
+    assert!(engine.contains("pub fn for_request(req: &HttpRequest, pool: &PgPool) -> Self"));
+    assert!(engine.contains("get_or_insert_with(PermissionCache::default)"));
+    assert!(engine.contains("pub async fn evaluate("));
+    assert!(engine.contains("pub async fn explain("));
+    assert!(engine.contains("AND (ura.expires_at IS NULL OR ura.expires_at > now())"));
+
+    let guards = include_str!("../src/guards/role_guard.rs");
+    assert!(!guards.contains("permission == \"audit.view\""));
+    assert!(guards.contains("permission_service: &PermissionService"));
+
+    let audit = include_str!("../src/api/audit_logs_api.rs");
+    assert!(audit.contains("PermissionService::for_request(&req, postgres_pool(&state))"));
+    let roles = include_str!("../src/api/roles_api.rs");
+    assert!(roles.contains("#[get(\"/permissions/explain\")]"));
+    assert!(roles.contains("\"role.assign\""));
+    let defaults = include_str!("../src/services/role_service.rs");
+    assert!(defaults.contains("name: \"audit.view\".to_string()"));
+    assert!(defaults.contains("name: \"finance.manage\".to_string()"));
+    assert!(!defaults.contains("fn user_has_permission("));
+    assert!(!defaults.contains("fn granted_scopes("));
+    assert!(engine.contains("fn built_in_permissions(role: &UserRole)"));
+    assert!(engine.contains("(\"report_card.read\", Class)"));
+    assert!(engine.contains("(\"attendance.read.school\", School)"));
+    assert!(engine.contains("(\"attendance.record.class\", Class)"));
+    assert!(guards.contains("pub async fn require_request_permission("));
+    assert!(guards.contains("pub async fn require_entity_permission("));
+    assert!(!guards.contains("pub fn check_admin_or_staff("));
+    assert!(!guards.contains("pub fn check_admin_staff_or_teacher("));
+    // Role-only guards that skipped the tenant checks
+    assert!(!guards.contains("pub fn check_teacher_access("));
+    assert!(!guards.contains("pub async fn check_parent_access("));
+    assert!(!guards.contains("pub fn check_admin_or_teacher_creator("));
+    assert!(guards.contains(".student_placement(student_id)"));
+}
+
+#[test]
+fn school_handlers_check_permission_keys_so_custom_roles_apply() {
+    let handlers = [
+        include_str!("../src/api/announcement_api.rs"),
+        include_str!("../src/api/attendance.rs"),
+        include_str!("../src/api/backups_api.rs"),
+        include_str!("../src/api/bulk_import_api.rs"),
+        include_str!("../src/api/class_api.rs"),
+        include_str!("../src/api/class_timetable.rs"),
+        include_str!("../src/api/education_year_api.rs"),
+        include_str!("../src/api/exam_api.rs"),
+        include_str!("../src/api/finance.rs"),
+        include_str!("../src/api/join_school_request_api.rs"),
+        include_str!("../src/api/learning_materials_api.rs"),
+        include_str!("../src/api/parent_api.rs"),
+        include_str!("../src/api/promotion_api.rs"),
+        include_str!("../src/api/recycle_bin_api.rs"),
+        include_str!("../src/api/report_card_api.rs"),
+        include_str!("../src/api/results_api.rs"),
+        include_str!("../src/api/roles_api.rs"),
+        include_str!("../src/api/school_api.rs"),
+        include_str!("../src/api/score_moderation_api.rs"),
+        include_str!("../src/api/students_api.rs"),
+        include_str!("../src/api/teacher_cover_api.rs"),
+        include_str!("../src/api/teachers_api.rs"),
+        include_str!("../src/api/template_subject_api.rs"),
+        include_str!("../src/api/users.rs"),
+    ];
+    for handler in handlers {
+        assert!(!handler.contains("check_admin_or_staff"));
+        assert!(!handler.contains("check_admin_staff_or_teacher"));
+        assert!(!handler.contains("check_admin("));
+        // Denials are typed errors carrying `code` and `request_id`
+        assert!(!handler.contains("HttpResponse::Forbidden()"));
+    }
+
+    let finance = include_str!("../src/api/finance.rs");
+    assert!(finance.contains("\"finance.manage\""));
+    assert!(finance.contains("\"finance.read\""));
+    let classes = include_str!("../src/api/class_api.rs");
+    assert!(classes.contains("require_entity_permission("));
+    assert!(classes.contains("\"class.manage\""));
+    let students = include_str!("../src/api/students_api.rs");
+    assert!(students.contains("TenantEntity::Student"));
+    let marksheets = include_str!("../src/services/marksheet_service.rs");
+    assert!(marksheets.contains("\"result.manage\""));
+    let schools = include_str!("../src/api/school_api.rs");
+    assert!(schools.contains("check_permission(&logged_user, None, \"school.create\""));
+}
+
+#[test]
+fn refresh_tokens_rotate_per_session_and_reuse_revokes_the_family() {
+    let jwt = include_str!("../src/utils/jwt.rs");
+    assert!(jwt.contains("ACCESS_TOKEN_TTL_MINUTES"));
+    assert!(!jwt.contains("Duration::days(7)"));
+
+    let sessions = include_str!("../src/services/auth_session_service.rs");
+    assert!(sessions.contains("FOR UPDATE OF rt, s"));
+    assert!(sessions.contains("revoked_reason = 'token_reuse'"));
+    assert!(sessions.contains("pub async fn revoke_all("));
+    assert!(sessions.contains("hex::encode(Sha256::digest(token.as_bytes()))"));
+
+    let migration = include_str!("../migrations/20261018001700_auth_sessions.sql");
+    assert!(migration.contains("CREATE TABLE IF NOT EXISTS auth_sessions"));
+    assert!(migration.contains("token_hash TEXT NOT NULL UNIQUE"));
+
+    let api = include_str!("../src/api/auth_api.rs");
+    assert!(api.contains("#[post(\"/auth/refresh\")]"));
+    assert!(api.contains("#[get(\"/sessions\")]"));
+    assert!(api.contains("#[delete(\"/sessions/{id}\")]"));
+    assert!(api.contains("#[post(\"/logout-all\")]"));
+    assert!(api.find("cfg.service(refresh_token)") < api.find("web::scope(\"/auth\")"));
+}
+
+#[test]
+fn password_reset_and_email_verification_use_single_use_mailed_tokens() {
+    let tokens = include_str!("../src/services/account_token_service.rs");
+    assert!(tokens.contains("hex::encode(Sha256::digest(token.as_bytes()))"));
+    assert!(tokens.contains("AND used_at IS NULL AND expires_at > now()"));
+    // The token, the new password and the sign-out commit together
+    assert!(tokens.contains(
+        "AuthSessionService::revoke_all_in(&mut tx, &user_id, \"password_reset\")"
+    ));
+    assert!(tokens.contains("Self::consume(&mut tx, token, AccountTokenPurpose::PasswordReset)"));
+    assert!(tokens.contains("is_valid_email(&email)"));
+    // Known and unknown addresses take the same path before the response,
+    // and both count towards the per-address and per-client limits
+    assert!(tokens.contains("self.throttle_password_reset(&email, ip_address).await?"));
+    assert!(tokens.contains("INSERT INTO password_reset_requests (id, email, ip_address)"));
+    assert!(tokens.contains("service.send_password_reset(&email).await"));
+    assert!(tokens.contains("actix_rt::spawn(async move {"));
+
+    let mail = include_str!("../src/services/mail_transport.rs");
+    assert!(mail.contains("pub trait MailTransport: Send + Sync"));
+    assert!(mail.contains("\"smtp\" => Arc::new(SmtpTransport::from_env())"));
+    assert!(mail.contains("\"file\" => Arc::new(FileTransport::new("));
+    assert!(mail.contains(".expect(\"❌ MAIL_TRANSPORT not set"));
+    assert!(!mail.contains("_ => Arc::new(ConsoleTransport)"));
+
+    let throttle = include_str!("../migrations/20261018002200_password_reset_requests.sql");
+    assert!(throttle.contains("CREATE TABLE IF NOT EXISTS password_reset_requests"));
+
+    let migration = include_str!("../migrations/20261018001800_account_tokens.sql");
+    assert!(migration.contains("ADD COLUMN IF NOT EXISTS email_verified_at"));
+
+    let api = include_str!("../src/api/auth_api.rs");
+    assert!(api.contains("#[post(\"/auth/password/forgot\")]"));
+    assert!(api.contains("#[post(\"/auth/password/reset\")]"));
+    assert!(api.contains("#[post(\"/auth/email/verify\")]"));
+    assert!(api.contains("tokens.send_verification(&user_id)"));
+}
+
+#[test]
+fn admins_and_staff_can_be_challenged_for_a_totp_second_factor() {
+    let auth = include_str!("../src/services/auth_service.rs");
+    assert!(auth.contains("Result<LoginOutcome, AppError>"));
+    let challenge = auth
+        .find("LoginOutcome::SecondFactorRequired(")
+        .expect("login challenges for a second factor");
+    let token = auth
+        .find("let access_token = create_jwt(&auth_user_dto);")
+        .expect("access token issued");
+    assert!(challenge < token);
+    assert!(auth.contains("pub async fn complete_two_factor_login("));
+
+    let service = include_str!("../src/services/two_factor_service.rs");
+    assert!(service.contains("Some(UserRole::ADMIN) | Some(UserRole::SCHOOLSTAFF)"));
+    assert!(service.contains("AND s.require_two_factor"));
+    assert!(service.contains("last_used_step < $2"));
+    assert!(service.contains("attempts < $2"));
+
+    let totp = include_str!("../src/utils/totp.rs");
+    assert!(totp.contains("otpauth://totp/"));
+
+    let migration = include_str!("../migrations/20261018001900_two_factor.sql");
+    assert!(migration.contains("ADD COLUMN IF NOT EXISTS require_two_factor"));
+
+    let api = include_str!("../src/api/auth_api.rs");
+    assert!(api.contains("#[post(\"/auth/2fa/verify\")]"));
+    assert!(api.contains("#[post(\"/2fa/recovery-codes\")]"));
+    let schools = include_str!("../src/api/school_api.rs");
+    assert!(schools.contains("#[put(\"/{id}/two-factor\")]"));
+    assert!(schools.contains("\"school.security.manage\""));
+    assert!(schools.contains("\"school.two_factor.require\""));
+
+    // Signed-in code checks share the challenge's attempt limit
+    assert!(service.contains("self.verify_limited(user_id, code).await?;"));
+    assert!(!service.contains("        self.verify(user_id, code).await?;\n\n        let mut tx"));
+    // and so do login challenges, so a fresh challenge gives no new guesses
+    assert!(service.contains("self.verify_limited(&user_id, code).await?;"));
+    assert!(!service.contains("self.verify(&user_id, code).await?;"));
+    let attempts = include_str!("../migrations/20261018002100_two_factor_attempts.sql");
+    assert!(attempts.contains("failed_attempts INTEGER NOT NULL DEFAULT 0"));
+}